@@ -115,6 +115,16 @@ impl<'a> CachedPSO<'a> {
         )
     }
 
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        CachedPSO(
+            d3d12::D3D12_CACHED_PIPELINE_STATE {
+                CachedBlobSizeInBytes: bytes.len(),
+                pCachedBlob: bytes.as_ptr().cast(),
+            },
+            PhantomData,
+        )
+    }
+
     // `blob` may not be null.
     pub fn from_blob(blob: &'a Blob) -> Self {
         CachedPSO(
@@ -341,6 +341,9 @@ fn deserialize_features(features: &wgpu_types::Features) -> Vec<&'static str> {
     if features.contains(wgpu_types::Features::SPIRV_SHADER_PASSTHROUGH) {
         return_features.push("spirv-shader-passthrough");
     }
+    if features.contains(wgpu_types::Features::DXIL_SHADER_PASSTHROUGH) {
+        return_features.push("dxil-shader-passthrough");
+    }
     if features.contains(wgpu_types::Features::MULTIVIEW) {
         return_features.push("multiview");
     }
@@ -623,6 +626,10 @@ impl From<GpuRequiredFeatures> for wgpu_types::Features {
             wgpu_types::Features::SPIRV_SHADER_PASSTHROUGH,
             required_features.0.contains("spirv-shader-passthrough"),
         );
+        features.set(
+            wgpu_types::Features::DXIL_SHADER_PASSTHROUGH,
+            required_features.0.contains("dxil-shader-passthrough"),
+        );
         features.set(
             wgpu_types::Features::MULTIVIEW,
             required_features.0.contains("multiview"),
@@ -675,6 +682,7 @@ pub fn op_webgpu_request_device(
         label: Some(Cow::Owned(label)),
         required_features: required_features.into(),
         required_limits: required_limits.unwrap_or_default(),
+        memory_hints: wgpu_types::MemoryHints::default(),
     };
 
     let (device, queue, maybe_err) = gfx_select!(adapter => instance.adapter_request_device(
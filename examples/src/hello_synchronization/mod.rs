@@ -19,6 +19,7 @@ async fn run() {
                 label: None,
                 required_features: wgpu::Features::empty(),
                 required_limits: wgpu::Limits::downlevel_defaults(),
+                memory_hints: wgpu::MemoryHints::default(),
             },
             None,
         )
@@ -104,6 +105,7 @@ async fn execute(
         module: &shaders_module,
         entry_point: "patient_main",
         compilation_options: Default::default(),
+        cache: None,
     });
     let hasty_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
         label: None,
@@ -111,6 +113,7 @@ async fn execute(
         module: &shaders_module,
         entry_point: "hasty_main",
         compilation_options: Default::default(),
+        cache: None,
     });
 
     //----------------------------------------------------------
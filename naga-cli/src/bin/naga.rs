@@ -203,6 +203,7 @@ impl FromStr for ShaderStage {
 #[derive(Debug, Clone, Copy)]
 enum InputKind {
     Bincode,
+    Precompiled,
     Glsl,
     SpirV,
     Wgsl,
@@ -213,6 +214,7 @@ impl FromStr for InputKind {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s.to_lowercase().as_str() {
             "bin" => InputKind::Bincode,
+            "naga" => InputKind::Precompiled,
             "glsl" => InputKind::Glsl,
             "spv" => InputKind::SpirV,
             "wgsl" => InputKind::Wgsl,
@@ -288,6 +290,7 @@ impl FromStr for Overrides {
 #[derive(Default)]
 struct Parameters<'a> {
     validation_flags: naga::valid::ValidationFlags,
+    validation_caps: naga::valid::Capabilities,
     bounds_check_policies: naga::proc::BoundsCheckPolicies,
     entry_point: Option<String>,
     keep_coordinate_space: bool,
@@ -477,6 +480,7 @@ fn run() -> anyhow::Result<()> {
                 };
                 caps & !missing
             });
+    params.validation_caps = validation_caps;
 
     // Validate the IR before compaction.
     let info = match naga::valid::Validator::new(params.validation_flags, validation_caps)
@@ -570,6 +574,7 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
 
     let (module, input_text) = match input_kind {
         InputKind::Bincode => (bincode::deserialize(&input)?, None),
+        InputKind::Precompiled => (naga::precompiled::read(&input)?.module, None),
         InputKind::SpirV => {
             naga::front::spv::parse_u8_slice(&input, &params.spv_in).map(|m| (m, None))?
         }
@@ -612,6 +617,7 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
                         &naga::front::glsl::Options {
                             stage: shader_stage.0,
                             defines: Default::default(),
+                            combined_samplers: Default::default(),
                         },
                         &input,
                     )
@@ -658,6 +664,13 @@ fn write_output(
             let file = fs::File::create(output_path)?;
             bincode::serialize_into(file, module)?;
         }
+        "naga" => {
+            if info.is_none() {
+                return Err(CliError("Only validated modules can be precompiled").into());
+            }
+            let bytes = naga::precompiled::write(module, params.validation_caps)?;
+            fs::write(output_path, bytes)?;
+        }
         "metal" => {
             use naga::back::msl;
 
@@ -33,6 +33,7 @@ fn parse_glsl(stage: naga::ShaderStage, inputs: &[Box<[u8]>]) {
     let options = naga::front::glsl::Options {
         stage,
         defines: Default::default(),
+        combined_samplers: Default::default(),
     };
     for input in inputs.iter() {
         let string = std::str::from_utf8(input).unwrap();
@@ -224,6 +224,24 @@ impl StatementGraph {
                     self.dependencies.push((id, value, "value"));
                     "ImageStore"
                 }
+                S::ImageAtomic {
+                    image,
+                    coordinate,
+                    array_index,
+                    ref fun,
+                    value,
+                } => {
+                    self.dependencies.push((id, image, "image"));
+                    self.dependencies.push((id, coordinate, "coordinate"));
+                    if let Some(expr) = array_index {
+                        self.dependencies.push((id, expr, "array_index"));
+                    }
+                    self.dependencies.push((id, value, "value"));
+                    if let crate::AtomicFunction::Exchange { compare: Some(cmp) } = *fun {
+                        self.dependencies.push((id, cmp, "cmp"));
+                    }
+                    "ImageAtomic"
+                }
                 S::Call {
                     function,
                     ref arguments,
@@ -279,6 +297,64 @@ impl StatementGraph {
                         crate::RayQueryFunction::Terminate => "RayQueryTerminate",
                     }
                 }
+                S::RayPipelineFunction(ref fun) => match *fun {
+                    crate::RayPipelineFunction::TraceRay {
+                        acceleration_structure,
+                        descriptor,
+                        sbt_record_offset,
+                        sbt_record_stride,
+                        miss_index,
+                        payload,
+                    } => {
+                        self.dependencies.push((
+                            id,
+                            acceleration_structure,
+                            "acceleration_structure",
+                        ));
+                        self.dependencies.push((id, descriptor, "descriptor"));
+                        self.dependencies
+                            .push((id, sbt_record_offset, "sbt_record_offset"));
+                        self.dependencies
+                            .push((id, sbt_record_stride, "sbt_record_stride"));
+                        self.dependencies.push((id, miss_index, "miss_index"));
+                        self.dependencies.push((id, payload, "payload"));
+                        "TraceRay"
+                    }
+                    crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                        self.dependencies.push((id, hit_t, "hit_t"));
+                        self.dependencies.push((id, hit_kind, "hit_kind"));
+                        "ReportIntersection"
+                    }
+                    crate::RayPipelineFunction::IgnoreIntersection => "IgnoreIntersection",
+                    crate::RayPipelineFunction::TerminateRay => "TerminateRay",
+                },
+                S::MeshFunction(ref fun) => match *fun {
+                    crate::MeshFunction::EmitMeshTasks { x, y, z } => {
+                        self.dependencies.push((id, x, "x"));
+                        self.dependencies.push((id, y, "y"));
+                        self.dependencies.push((id, z, "z"));
+                        "EmitMeshTasks"
+                    }
+                    crate::MeshFunction::SetMeshOutputs {
+                        vertex_count,
+                        primitive_count,
+                    } => {
+                        self.dependencies.push((id, vertex_count, "vertex_count"));
+                        self.dependencies
+                            .push((id, primitive_count, "primitive_count"));
+                        "SetMeshOutputs"
+                    }
+                    crate::MeshFunction::SetVertex { index, value } => {
+                        self.dependencies.push((id, index, "index"));
+                        self.dependencies.push((id, value, "value"));
+                        "SetVertex"
+                    }
+                    crate::MeshFunction::SetPrimitive { index, value } => {
+                        self.dependencies.push((id, index, "index"));
+                        self.dependencies.push((id, value, "value"));
+                        "SetPrimitive"
+                    }
+                },
                 S::SubgroupBallot { result, predicate } => {
                     if let Some(predicate) = predicate {
                         self.dependencies.push((id, predicate, "predicate"));
@@ -338,6 +414,34 @@ impl StatementGraph {
                             crate::CollectiveOperation::InclusiveScan,
                             crate::SubgroupOperation::Mul,
                         ) => "SubgroupInclusiveMul",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Add,
+                        ) => "SubgroupClusteredAdd",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Mul,
+                        ) => "SubgroupClusteredMul",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Max,
+                        ) => "SubgroupClusteredMax",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Min,
+                        ) => "SubgroupClusteredMin",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::And,
+                        ) => "SubgroupClusteredAnd",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Or,
+                        ) => "SubgroupClusteredOr",
+                        (
+                            crate::CollectiveOperation::ClusteredReduce(_),
+                            crate::SubgroupOperation::Xor,
+                        ) => "SubgroupClusteredXor",
                         _ => unimplemented!(),
                     }
                 }
@@ -347,12 +451,13 @@ impl StatementGraph {
                     result,
                 } => {
                     match mode {
-                        crate::GatherMode::BroadcastFirst => {}
+                        crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                         crate::GatherMode::Broadcast(index)
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             self.dependencies.push((id, index, "index"))
                         }
                     }
@@ -365,6 +470,12 @@ impl StatementGraph {
                         crate::GatherMode::ShuffleDown(_) => "SubgroupShuffleDown",
                         crate::GatherMode::ShuffleUp(_) => "SubgroupShuffleUp",
                         crate::GatherMode::ShuffleXor(_) => "SubgroupShuffleXor",
+                        crate::GatherMode::QuadBroadcast(_) => "SubgroupQuadBroadcast",
+                        crate::GatherMode::QuadSwap(crate::Direction::X) => "SubgroupQuadSwapX",
+                        crate::GatherMode::QuadSwap(crate::Direction::Y) => "SubgroupQuadSwapY",
+                        crate::GatherMode::QuadSwap(crate::Direction::Diagonal) => {
+                            "SubgroupQuadSwapDiagonal"
+                        }
                     }
                 }
             };
@@ -541,6 +652,7 @@ fn write_function_expressions(
                 offset: _,
                 level,
                 depth_ref,
+                residency,
             } => {
                 edges.insert("image", image);
                 edges.insert("sampler", sampler);
@@ -567,7 +679,8 @@ fn write_function_expressions(
                 }
                 let string = match gather {
                     Some(component) => Cow::Owned(format!("ImageGather{component:?}")),
-                    _ => Cow::Borrowed("ImageSample"),
+                    None if residency => Cow::Borrowed("ImageSampleResidency"),
+                    None => Cow::Borrowed("ImageSample"),
                 };
                 (string, 5)
             }
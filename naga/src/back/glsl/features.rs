@@ -9,7 +9,7 @@ use std::fmt::Write;
 bitflags::bitflags! {
     /// Structure used to encode additions to GLSL that aren't supported by all versions.
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-    pub struct Features: u32 {
+    pub struct Features: u64 {
         /// Buffer address space support.
         const BUFFER_STORAGE = 1;
         const ARRAY_OF_ARRAYS = 1 << 1;
@@ -52,6 +52,22 @@ bitflags::bitflags! {
         const TEXTURE_SHADOW_LOD = 1 << 23;
         /// Subgroup operations
         const SUBGROUP_OPERATIONS = 1 << 24;
+        /// Clustered subgroup reductions
+        const SUBGROUP_CLUSTERED = 1 << 25;
+        /// Subgroup quad operations
+        const SUBGROUP_QUAD = 1 << 26;
+        /// 64-bit integer types
+        const INT64_TYPE = 1 << 27;
+        /// Per-primitive fragment inputs written by mesh shaders
+        const MESH_SHADER = 1 << 28;
+        /// Barycentric coordinates fragment input
+        const BARYCENTRIC = 1 << 29;
+        /// Primitive index fragment input
+        const PRIMITIVE_INDEX = 1 << 30;
+        /// Derivatives in compute shaders
+        const COMPUTE_DERIVATIVES = 1 << 31;
+        /// Atomic operations on storage images
+        const IMAGE_ATOMICS = 1 << 32;
     }
 }
 
@@ -106,20 +122,27 @@ impl FeaturesManager {
         check_feature!(COMPUTE_SHADER, 420, 310);
         check_feature!(BUFFER_STORAGE, 400, 310);
         check_feature!(DOUBLE_TYPE, 150);
+        check_feature!(INT64_TYPE, 400);
+        check_feature!(MESH_SHADER, 450);
         check_feature!(CUBE_TEXTURES_ARRAY, 130, 310);
         check_feature!(MULTISAMPLED_TEXTURES, 150, 300);
         check_feature!(MULTISAMPLED_TEXTURE_ARRAYS, 150, 310);
         check_feature!(ARRAY_OF_ARRAYS, 120, 310);
         check_feature!(IMAGE_LOAD_STORE, 130, 310);
+        check_feature!(IMAGE_ATOMICS, 420, 310 /* with extension */);
         check_feature!(CONSERVATIVE_DEPTH, 130, 300);
         check_feature!(NOPERSPECTIVE_QUALIFIER, 130);
         check_feature!(SAMPLE_QUALIFIER, 400, 320);
         check_feature!(CLIP_DISTANCE, 130, 300 /* with extension */);
-        check_feature!(CULL_DISTANCE, 450, 300 /* with extension */);
+        check_feature!(CULL_DISTANCE, 130, 300 /* with extension */);
+        check_feature!(PRIMITIVE_INDEX, 150, 310 /* with extension */);
+        check_feature!(COMPUTE_DERIVATIVES, 450, 320 /* with extension */);
         check_feature!(SAMPLE_VARIABLES, 400, 300);
         check_feature!(DYNAMIC_ARRAY_SIZE, 430, 310);
         check_feature!(DUAL_SOURCE_BLENDING, 330, 300 /* with extension */);
         check_feature!(SUBGROUP_OPERATIONS, 430, 310);
+        check_feature!(SUBGROUP_CLUSTERED, 430, 310);
+        check_feature!(SUBGROUP_QUAD, 430, 310);
         match version {
             Version::Embedded { is_webgl: true, .. } => check_feature!(MULTI_VIEW, 140, 300),
             _ => check_feature!(MULTI_VIEW, 140, 310),
@@ -164,6 +187,29 @@ impl FeaturesManager {
             writeln!(out, "#extension GL_ARB_gpu_shader_fp64 : require")?;
         }
 
+        if self.0.contains(Features::INT64_TYPE) {
+            // https://registry.khronos.org/OpenGL/extensions/ARB/ARB_gpu_shader_int64.txt
+            writeln!(out, "#extension GL_ARB_gpu_shader_int64 : require")?;
+        }
+
+        if self.0.contains(Features::MESH_SHADER) {
+            // https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt
+            writeln!(out, "#extension GL_EXT_mesh_shader : require")?;
+        }
+
+        if self.0.contains(Features::COMPUTE_DERIVATIVES) {
+            // https://github.com/KhronosGroup/GLSL/blob/main/extensions/nv/GLSL_NV_compute_shader_derivatives.txt
+            writeln!(out, "#extension GL_NV_compute_shader_derivatives : require")?;
+        }
+
+        if self.0.contains(Features::BARYCENTRIC) {
+            // https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_fragment_shader_barycentric.txt
+            writeln!(
+                out,
+                "#extension GL_EXT_fragment_shader_barycentric : require"
+            )?;
+        }
+
         if self.0.contains(Features::CUBE_TEXTURES_ARRAY) {
             if options.version.is_es() {
                 // https://www.khronos.org/registry/OpenGL/extensions/EXT/EXT_texture_cube_map_array.txt
@@ -218,6 +264,23 @@ impl FeaturesManager {
             writeln!(out, "#extension GL_EXT_clip_cull_distance : require")?;
         }
 
+        if self.0.contains(Features::CULL_DISTANCE) && options.version < Version::Desktop(450) {
+            // https://registry.khronos.org/OpenGL/extensions/ARB/ARB_cull_distance.txt
+            writeln!(out, "#extension GL_ARB_cull_distance : require")?;
+        }
+
+        if self.0.contains(Features::PRIMITIVE_INDEX)
+            && options.version.is_es()
+            && options.version < Version::new_gles(320)
+        {
+            // `gl_PrimitiveID` is only core in fragment shaders from GLSL ES 3.20,
+            // before that it's exposed by either geometry shader extension.
+            // https://registry.khronos.org/OpenGL/extensions/EXT/EXT_geometry_shader.txt
+            // https://registry.khronos.org/OpenGL/extensions/OES/OES_geometry_shader.txt
+            writeln!(out, "#extension GL_EXT_geometry_shader : enable")?;
+            writeln!(out, "#extension GL_OES_geometry_shader : enable")?;
+        }
+
         if self.0.contains(Features::SAMPLE_VARIABLES) && options.version.is_es() {
             // https://www.khronos.org/registry/OpenGL/extensions/OES/OES_sample_variables.txt
             writeln!(out, "#extension GL_OES_sample_variables : require")?;
@@ -257,6 +320,14 @@ impl FeaturesManager {
             }
         }
 
+        if self.0.contains(Features::IMAGE_ATOMICS)
+            && options.version.is_es()
+            && options.version < Version::new_gles(320)
+        {
+            // https://registry.khronos.org/OpenGL/extensions/OES/OES_shader_image_atomic.txt
+            writeln!(out, "#extension GL_OES_shader_image_atomic : require")?;
+        }
+
         if self.0.contains(Features::TEXTURE_SHADOW_LOD) {
             // https://registry.khronos.org/OpenGL/extensions/EXT/EXT_texture_shadow_lod.txt
             writeln!(out, "#extension GL_EXT_texture_shadow_lod : require")?;
@@ -278,6 +349,14 @@ impl FeaturesManager {
             )?;
         }
 
+        if self.0.contains(Features::SUBGROUP_CLUSTERED) {
+            writeln!(out, "#extension GL_KHR_shader_subgroup_clustered : require")?;
+        }
+
+        if self.0.contains(Features::SUBGROUP_QUAD) {
+            writeln!(out, "#extension GL_KHR_shader_subgroup_quad : require")?;
+        }
+
         Ok(())
     }
 }
@@ -310,7 +389,10 @@ impl<'a, W> Writer<'a, W> {
         }
 
         if let ShaderStage::Compute = self.entry_point.stage {
-            self.features.request(Features::COMPUTE_SHADER)
+            self.features.request(Features::COMPUTE_SHADER);
+            if ep_info.uses_derivatives {
+                self.features.request(Features::COMPUTE_DERIVATIVES);
+            }
         }
 
         if self.multiview.is_some() {
@@ -382,31 +464,36 @@ impl<'a, W> Writer<'a, W> {
                                 self.features.request(Features::MULTISAMPLED_TEXTURE_ARRAYS);
                             }
                         }
-                        ImageClass::Storage { format, .. } => match format {
-                            StorageFormat::R8Unorm
-                            | StorageFormat::R8Snorm
-                            | StorageFormat::R8Uint
-                            | StorageFormat::R8Sint
-                            | StorageFormat::R16Uint
-                            | StorageFormat::R16Sint
-                            | StorageFormat::R16Float
-                            | StorageFormat::Rg8Unorm
-                            | StorageFormat::Rg8Snorm
-                            | StorageFormat::Rg8Uint
-                            | StorageFormat::Rg8Sint
-                            | StorageFormat::Rg16Uint
-                            | StorageFormat::Rg16Sint
-                            | StorageFormat::Rg16Float
-                            | StorageFormat::Rgb10a2Uint
-                            | StorageFormat::Rgb10a2Unorm
-                            | StorageFormat::Rg11b10Float
-                            | StorageFormat::Rg32Uint
-                            | StorageFormat::Rg32Sint
-                            | StorageFormat::Rg32Float => {
-                                self.features.request(Features::FULL_IMAGE_FORMATS)
+                        ImageClass::Storage { format, access } => {
+                            if access.contains(crate::StorageAccess::ATOMIC) {
+                                self.features.request(Features::IMAGE_ATOMICS)
                             }
-                            _ => {}
-                        },
+                            match format {
+                                StorageFormat::R8Unorm
+                                | StorageFormat::R8Snorm
+                                | StorageFormat::R8Uint
+                                | StorageFormat::R8Sint
+                                | StorageFormat::R16Uint
+                                | StorageFormat::R16Sint
+                                | StorageFormat::R16Float
+                                | StorageFormat::Rg8Unorm
+                                | StorageFormat::Rg8Snorm
+                                | StorageFormat::Rg8Uint
+                                | StorageFormat::Rg8Sint
+                                | StorageFormat::Rg16Uint
+                                | StorageFormat::Rg16Sint
+                                | StorageFormat::Rg16Float
+                                | StorageFormat::Rgb10a2Uint
+                                | StorageFormat::Rgb10a2Unorm
+                                | StorageFormat::Rg11b10Float
+                                | StorageFormat::Rg32Uint
+                                | StorageFormat::Rg32Sint
+                                | StorageFormat::Rg32Float => {
+                                    self.features.request(Features::FULL_IMAGE_FORMATS)
+                                }
+                                _ => {}
+                            }
+                        }
                         ImageClass::Sampled { multi: false, .. }
                         | ImageClass::Depth { multi: false } => {}
                     }
@@ -447,6 +534,17 @@ impl<'a, W> Writer<'a, W> {
             ..
         } = self;
 
+        // Loop trough all statements in both functions and the entry point
+        // to check for needed features
+        for body in module
+            .functions
+            .iter()
+            .map(|(_, f)| &f.body)
+            .chain(std::iter::once(&entry_point.function.body))
+        {
+            statements_required_features(features, body);
+        }
+
         // Loop trough all expressions in both functions and the entry point
         // to check for needed features
         for (expressions, info) in module
@@ -541,6 +639,9 @@ impl<'a, W> Writer<'a, W> {
                 Expression::SubgroupOperationResult { .. } => {
                     features.request(Features::SUBGROUP_OPERATIONS)
                 }
+                Expression::Literal(crate::Literal::I64(_) | crate::Literal::U64(_)) => {
+                    features.request(Features::INT64_TYPE)
+                }
                 _ => {}
             }
             }
@@ -551,8 +652,10 @@ impl<'a, W> Writer<'a, W> {
 
     /// Helper method that checks the [`Features`] needed by a scalar
     fn scalar_required_features(&mut self, scalar: Scalar) {
-        if scalar.kind == ScalarKind::Float && scalar.width == 8 {
-            self.features.request(Features::DOUBLE_TYPE);
+        match (scalar.kind, scalar.width) {
+            (ScalarKind::Float, 8) => self.features.request(Features::DOUBLE_TYPE),
+            (ScalarKind::Sint | ScalarKind::Uint, 8) => self.features.request(Features::INT64_TYPE),
+            _ => {}
         }
     }
 
@@ -582,6 +685,12 @@ impl<'a, W> Writer<'a, W> {
                             crate::BuiltIn::InstanceIndex => {
                                 self.features.request(Features::INSTANCE_INDEX)
                             }
+                            crate::BuiltIn::PrimitiveIndex => {
+                                self.features.request(Features::PRIMITIVE_INDEX)
+                            }
+                            crate::BuiltIn::Barycentric => {
+                                self.features.request(Features::BARYCENTRIC)
+                            }
                             _ => {}
                         },
                         Binding::Location {
@@ -589,6 +698,7 @@ impl<'a, W> Writer<'a, W> {
                             interpolation,
                             sampling,
                             second_blend_source,
+                            per_primitive,
                         } => {
                             if interpolation == Some(Interpolation::Linear) {
                                 self.features.request(Features::NOPERSPECTIVE_QUALIFIER);
@@ -599,6 +709,9 @@ impl<'a, W> Writer<'a, W> {
                             if second_blend_source {
                                 self.features.request(Features::DUAL_SOURCE_BLENDING);
                             }
+                            if per_primitive {
+                                self.features.request(Features::MESH_SHADER);
+                            }
                         }
                     }
                 }
@@ -606,3 +719,42 @@ impl<'a, W> Writer<'a, W> {
         }
     }
 }
+
+/// Helper function that checks the [`Features`] needed by the statements in `block`
+fn statements_required_features(features: &mut FeaturesManager, block: &crate::Block) {
+    for statement in block.iter() {
+        match *statement {
+            crate::Statement::Block(ref block) => statements_required_features(features, block),
+            crate::Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                statements_required_features(features, accept);
+                statements_required_features(features, reject);
+            }
+            crate::Statement::Switch { ref cases, .. } => {
+                for case in cases.iter() {
+                    statements_required_features(features, &case.body);
+                }
+            }
+            crate::Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                statements_required_features(features, body);
+                statements_required_features(features, continuing);
+            }
+            crate::Statement::SubgroupCollectiveOperation {
+                collective_op: crate::CollectiveOperation::ClusteredReduce(_),
+                ..
+            } => features.request(Features::SUBGROUP_CLUSTERED),
+            crate::Statement::SubgroupGather {
+                mode: crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_),
+                ..
+            } => features.request(Features::SUBGROUP_QUAD),
+            _ => {}
+        }
+    }
+}
@@ -113,7 +113,11 @@ impl crate::AddressSpace {
             | crate::AddressSpace::Uniform
             | crate::AddressSpace::Storage { .. }
             | crate::AddressSpace::Handle
-            | crate::AddressSpace::PushConstant => false,
+            | crate::AddressSpace::PushConstant
+            | crate::AddressSpace::RayPayload
+            | crate::AddressSpace::IncomingRayPayload
+            | crate::AddressSpace::HitAttribute
+            | crate::AddressSpace::TaskPayload => false,
         }
     }
 }
@@ -435,7 +439,17 @@ impl fmt::Display for VaryingName<'_> {
             }
             crate::Binding::Location { location, .. } => {
                 let prefix = match (self.stage, self.options.output) {
-                    (ShaderStage::Compute, _) => unreachable!(),
+                    (
+                        ShaderStage::Compute
+                        | ShaderStage::RayGeneration
+                        | ShaderStage::Intersection
+                        | ShaderStage::AnyHit
+                        | ShaderStage::ClosestHit
+                        | ShaderStage::Miss
+                        | ShaderStage::Task
+                        | ShaderStage::Mesh,
+                        _,
+                    ) => unreachable!(),
                     // pipeline to vertex
                     (ShaderStage::Vertex, false) => "p2vs",
                     // vertex to fragment
@@ -458,6 +472,13 @@ impl ShaderStage {
             ShaderStage::Compute => "cs",
             ShaderStage::Fragment => "fs",
             ShaderStage::Vertex => "vs",
+            ShaderStage::RayGeneration => "rgen",
+            ShaderStage::Intersection => "rint",
+            ShaderStage::AnyHit => "rahit",
+            ShaderStage::ClosestHit => "rchit",
+            ShaderStage::Miss => "rmiss",
+            ShaderStage::Task => "task",
+            ShaderStage::Mesh => "mesh",
         }
     }
 }
@@ -486,6 +507,9 @@ pub enum Error {
     /// The entry point couldn't be found.
     #[error("The requested entry point couldn't be found")]
     EntryPointNotFound,
+    /// The entry point's shader stage isn't supported.
+    #[error("The {0:?} shader stage isn't supported")]
+    UnsupportedShaderStage(ShaderStage),
     /// A call was made to an unsupported external.
     #[error("A call was made to an unsupported external: {0}")]
     UnsupportedExternal(String),
@@ -585,6 +609,14 @@ impl<'a, W: Write> Writer<'a, W> {
                 pipeline_options.shader_stage == ep.stage && pipeline_options.entry_point == ep.name
             })
             .ok_or(Error::EntryPointNotFound)?;
+        if pipeline_options.shader_stage.is_ray_tracing()
+            || matches!(
+                pipeline_options.shader_stage,
+                ShaderStage::Task | ShaderStage::Mesh
+            )
+        {
+            return Err(Error::UnsupportedShaderStage(pipeline_options.shader_stage));
+        }
 
         // Generate a map with names required to write the module
         let mut names = crate::FastHashMap::default();
@@ -668,6 +700,9 @@ impl<'a, W: Write> Writer<'a, W> {
                 "layout(local_size_x = {}, local_size_y = {}, local_size_z = {}) in;",
                 workgroup_size[0], workgroup_size[1], workgroup_size[2]
             )?;
+            if self.features.contains(Features::COMPUTE_DERIVATIVES) {
+                writeln!(self.out, "layout(derivative_group_quadsNV) in;")?;
+            }
             writeln!(self.out)?;
         }
 
@@ -790,7 +825,8 @@ impl<'a, W: Write> Writer<'a, W> {
                         )?;
                     }
                 }
-                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. } => {}
+                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. }
+                | &crate::PredeclaredType::SparseSampleResult => {}
             }
         }
 
@@ -969,6 +1005,7 @@ impl<'a, W: Write> Writer<'a, W> {
             crate::ArraySize::Constant(size) => {
                 write!(self.out, "{size}")?;
             }
+            crate::ArraySize::Pending(_) => return Err(Error::Override),
             crate::ArraySize::Dynamic => (),
         }
 
@@ -1187,6 +1224,12 @@ impl<'a, W: Write> Writer<'a, W> {
             crate::AddressSpace::Function => unreachable!(),
             // Textures and samplers are handled directly in `Writer::write`.
             crate::AddressSpace::Handle => unreachable!(),
+            // Ray tracing pipeline and mesh shading stages are rejected in
+            // `Writer::new`.
+            crate::AddressSpace::RayPayload
+            | crate::AddressSpace::IncomingRayPayload
+            | crate::AddressSpace::HitAttribute
+            | crate::AddressSpace::TaskPayload => unreachable!(),
         }
 
         Ok(())
@@ -1441,13 +1484,21 @@ impl<'a, W: Write> Writer<'a, W> {
             Some(binding) => binding,
         };
 
-        let (location, interpolation, sampling, second_blend_source) = match *binding {
+        let (location, interpolation, sampling, second_blend_source, per_primitive) = match *binding
+        {
             crate::Binding::Location {
                 location,
                 interpolation,
                 sampling,
                 second_blend_source,
-            } => (location, interpolation, sampling, second_blend_source),
+                per_primitive,
+            } => (
+                location,
+                interpolation,
+                sampling,
+                second_blend_source,
+                per_primitive,
+            ),
             crate::Binding::BuiltIn(built_in) => {
                 if let crate::BuiltIn::Position { invariant: true } = built_in {
                     match (self.options.version, self.entry_point.stage) {
@@ -1475,6 +1526,25 @@ impl<'a, W: Write> Writer<'a, W> {
                         }
                     }
                 }
+                if let crate::BuiltIn::ClipDistance | crate::BuiltIn::CullDistance = built_in {
+                    // The arrays are unsized until redeclared, and unsized arrays
+                    // can't be assigned as a whole
+                    if let TypeInner::Array {
+                        size: crate::ArraySize::Constant(size),
+                        ..
+                    } = self.module.types[ty].inner
+                    {
+                        writeln!(
+                            self.out,
+                            "{} float {}[{size}];",
+                            if output { "out" } else { "in" },
+                            glsl_built_in(
+                                built_in,
+                                VaryingOptions::from_writer_options(self.options, output)
+                            )
+                        )?;
+                    }
+                }
                 return Ok(());
             }
         };
@@ -1486,7 +1556,7 @@ impl<'a, W: Write> Writer<'a, W> {
         let emit_interpolation_and_auxiliary = match self.entry_point.stage {
             ShaderStage::Vertex => output,
             ShaderStage::Fragment => !output,
-            ShaderStage::Compute => false,
+            _ => false,
         };
 
         // Write the I/O locations, if allowed
@@ -1530,6 +1600,10 @@ impl<'a, W: Write> Writer<'a, W> {
             }
         }
 
+        if per_primitive {
+            write!(self.out, "perprimitiveEXT ")?;
+        }
+
         // Write the input/output qualifier.
         write!(self.out, "{} ", if output { "out" } else { "in" })?;
 
@@ -1545,6 +1619,7 @@ impl<'a, W: Write> Writer<'a, W> {
                 interpolation: None,
                 sampling: None,
                 second_blend_source,
+                per_primitive: false,
             },
             stage: self.entry_point.stage,
             options: VaryingOptions::from_writer_options(self.options, output),
@@ -1651,6 +1726,17 @@ impl<'a, W: Write> Writer<'a, W> {
                     // any spaces at the beginning or end
                     this.write_image_type(dim, arrayed, class)?;
                 }
+                TypeInner::Pointer {
+                    space: crate::AddressSpace::Storage { .. } | crate::AddressSpace::WorkGroup,
+                    ..
+                } => {
+                    // `inout` parameters are copied in and out, which doesn't
+                    // preserve the semantics of a pointer into shared memory
+                    return Err(Error::Custom(
+                        "Storage or workgroup pointer function arguments are not supported"
+                            .to_string(),
+                    ));
+                }
                 TypeInner::Pointer { base, .. } => {
                     // write parameter qualifiers
                     write!(this.out, "inout ")?;
@@ -2322,6 +2408,17 @@ impl<'a, W: Write> Writer<'a, W> {
                 write!(self.out, "{level}")?;
                 self.write_image_store(ctx, image, coordinate, array_index, value)?
             }
+            // Performs an atomic operation on a texel of an image.
+            Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                fun,
+                value,
+            } => {
+                write!(self.out, "{level}")?;
+                self.write_image_atomic(ctx, image, coordinate, array_index, fun, value)?
+            }
             // A `Call` is written `name(arguments)` where `arguments` is a comma separated expressions list
             Statement::Call {
                 function,
@@ -2390,6 +2487,8 @@ impl<'a, W: Write> Writer<'a, W> {
                 writeln!(self.out, ");")?;
             }
             Statement::RayQuery { .. } => unreachable!(),
+            Statement::RayPipelineFunction(_) => unreachable!(),
+            Statement::MeshFunction(_) => unreachable!(),
             Statement::SubgroupBallot { result, predicate } => {
                 write!(self.out, "{level}")?;
                 let res_name = format!("{}{}", back::BAKE_PREFIX, result.index());
@@ -2458,9 +2557,40 @@ impl<'a, W: Write> Writer<'a, W> {
                     (crate::CollectiveOperation::InclusiveScan, crate::SubgroupOperation::Mul) => {
                         write!(self.out, "subgroupInclusiveMul(")?
                     }
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Add,
+                    ) => write!(self.out, "subgroupClusteredAdd(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Mul,
+                    ) => write!(self.out, "subgroupClusteredMul(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Max,
+                    ) => write!(self.out, "subgroupClusteredMax(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Min,
+                    ) => write!(self.out, "subgroupClusteredMin(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::And,
+                    ) => write!(self.out, "subgroupClusteredAnd(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Or,
+                    ) => write!(self.out, "subgroupClusteredOr(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Xor,
+                    ) => write!(self.out, "subgroupClusteredXor(")?,
                     _ => unimplemented!(),
                 }
                 self.write_expr(argument, ctx)?;
+                if let crate::CollectiveOperation::ClusteredReduce(cluster_size) = collective_op {
+                    write!(self.out, ", {cluster_size}u")?;
+                }
                 writeln!(self.out, ");")?;
             }
             Statement::SubgroupGather {
@@ -2494,15 +2624,28 @@ impl<'a, W: Write> Writer<'a, W> {
                     crate::GatherMode::ShuffleXor(_) => {
                         write!(self.out, "subgroupShuffleXor(")?;
                     }
+                    crate::GatherMode::QuadBroadcast(_) => {
+                        write!(self.out, "subgroupQuadBroadcast(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::X) => {
+                        write!(self.out, "subgroupQuadSwapHorizontal(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::Y) => {
+                        write!(self.out, "subgroupQuadSwapVertical(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::Diagonal) => {
+                        write!(self.out, "subgroupQuadSwapDiagonal(")?;
+                    }
                 }
                 self.write_expr(argument, ctx)?;
                 match mode {
-                    crate::GatherMode::BroadcastFirst => {}
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                     crate::GatherMode::Broadcast(index)
                     | crate::GatherMode::Shuffle(index)
                     | crate::GatherMode::ShuffleDown(index)
                     | crate::GatherMode::ShuffleUp(index)
-                    | crate::GatherMode::ShuffleXor(index) => {
+                    | crate::GatherMode::ShuffleXor(index)
+                    | crate::GatherMode::QuadBroadcast(index) => {
                         write!(self.out, ", ")?;
                         self.write_expr(index, ctx)?;
                     }
@@ -2578,12 +2721,17 @@ impl<'a, W: Write> Writer<'a, W> {
                     crate::Literal::U32(value) => write!(self.out, "{}u", value)?,
                     crate::Literal::I32(value) => write!(self.out, "{}", value)?,
                     crate::Literal::Bool(value) => write!(self.out, "{}", value)?,
-                    crate::Literal::I64(_) => {
-                        return Err(Error::Custom("GLSL has no 64-bit integer type".into()));
-                    }
-                    crate::Literal::U64(_) => {
-                        return Err(Error::Custom("GLSL has no 64-bit integer type".into()));
+                    // 64-bit integers use the `L`/`UL` suffixes from `GL_ARB_gpu_shader_int64`
+                    crate::Literal::I64(value) => {
+                        // `-9223372036854775808L` would be parsed as the negation of a
+                        // literal that doesn't fit in `int64_t`
+                        if value == i64::MIN {
+                            write!(self.out, "({}L - 1L)", value + 1)?;
+                        } else {
+                            write!(self.out, "{}L", value)?;
+                        }
                     }
+                    crate::Literal::U64(value) => write!(self.out, "{}UL", value)?,
                     crate::Literal::AbstractInt(_) | crate::Literal::AbstractFloat(_) => {
                         return Err(Error::Custom(
                             "Abstract types should not appear in IR presented to backends".into(),
@@ -2753,7 +2901,13 @@ impl<'a, W: Write> Writer<'a, W> {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => {
+                if residency {
+                    return Err(Error::Custom(
+                        "Sampling with residency feedback is not supported".to_string(),
+                    ));
+                }
                 let (dim, class, arrayed) = match *ctx.resolve_type(image, &self.module.types) {
                     TypeInner::Image {
                         dim,
@@ -3603,29 +3757,31 @@ impl<'a, W: Write> Writer<'a, W> {
                 let arg_might_need_uint_to_int = matches!(fun, Mf::Abs);
 
                 // Check if the argument is an unsigned integer and return the vector size
-                // in case it's a vector
+                // in case it's a vector, along with the width of the integer
                 let maybe_uint_size = match *ctx.resolve_type(arg, &self.module.types) {
                     TypeInner::Scalar(crate::Scalar {
                         kind: crate::ScalarKind::Uint,
-                        ..
-                    }) => Some(None),
+                        width,
+                    }) => Some((None, width)),
                     TypeInner::Vector {
                         scalar:
                             crate::Scalar {
                                 kind: crate::ScalarKind::Uint,
-                                ..
+                                width,
                             },
                         size,
-                    } => Some(Some(size)),
+                    } => Some((Some(size), width)),
                     _ => None,
                 };
 
                 // Cast to uint if the function needs it
                 if ret_might_need_int_to_uint {
-                    if let Some(maybe_size) = maybe_uint_size {
-                        match maybe_size {
-                            Some(size) => write!(self.out, "uvec{}(", size as u8)?,
-                            None => write!(self.out, "uint(")?,
+                    if let Some((maybe_size, width)) = maybe_uint_size {
+                        match (maybe_size, width) {
+                            (Some(size), 8) => write!(self.out, "u64vec{}(", size as u8)?,
+                            (Some(size), _) => write!(self.out, "uvec{}(", size as u8)?,
+                            (None, 8) => write!(self.out, "uint64_t(")?,
+                            (None, _) => write!(self.out, "uint(")?,
                         }
                     }
                 }
@@ -3634,10 +3790,12 @@ impl<'a, W: Write> Writer<'a, W> {
 
                 // Cast to int if the function needs it
                 if arg_might_need_uint_to_int {
-                    if let Some(maybe_size) = maybe_uint_size {
-                        match maybe_size {
-                            Some(size) => write!(self.out, "ivec{}(", size as u8)?,
-                            None => write!(self.out, "int(")?,
+                    if let Some((maybe_size, width)) = maybe_uint_size {
+                        match (maybe_size, width) {
+                            (Some(size), 8) => write!(self.out, "i64vec{}(", size as u8)?,
+                            (Some(size), _) => write!(self.out, "ivec{}(", size as u8)?,
+                            (None, 8) => write!(self.out, "int64_t(")?,
+                            (None, _) => write!(self.out, "int(")?,
                         }
                     }
                 }
@@ -3733,6 +3891,7 @@ impl<'a, W: Write> Writer<'a, W> {
                         };
 
                         let source_kind = inner.scalar_kind().unwrap();
+                        let is_64_bit = inner.scalar_width() == Some(8);
 
                         match (source_kind, target_kind, target_vector_type) {
                             // No conversion needed
@@ -3744,6 +3903,20 @@ impl<'a, W: Write> Writer<'a, W> {
                                 return Ok(());
                             }
 
+                            // Cast to/from doubles
+                            (Sk::Float, Sk::Sint, _) if is_64_bit => {
+                                write!(self.out, "doubleBitsToInt64")?
+                            }
+                            (Sk::Float, Sk::Uint, _) if is_64_bit => {
+                                write!(self.out, "doubleBitsToUint64")?
+                            }
+                            (Sk::Sint, Sk::Float, _) if is_64_bit => {
+                                write!(self.out, "int64BitsToDouble")?
+                            }
+                            (Sk::Uint, Sk::Float, _) if is_64_bit => {
+                                write!(self.out, "uint64BitsToDouble")?
+                            }
+
                             // Cast to/from floats
                             (Sk::Float, Sk::Sint, _) => write!(self.out, "floatBitsToInt")?,
                             (Sk::Float, Sk::Uint, _) => write!(self.out, "floatBitsToUint")?,
@@ -3756,6 +3929,10 @@ impl<'a, W: Write> Writer<'a, W> {
                             }
 
                             // There is no way to bitcast between Uint/Sint in glsl. Use constructor conversion
+                            (Sk::Uint, Sk::Sint, None) if is_64_bit => write!(self.out, "int64_t")?,
+                            (Sk::Sint, Sk::Uint, None) if is_64_bit => {
+                                write!(self.out, "uint64_t")?
+                            }
                             (Sk::Uint | Sk::Bool, Sk::Sint, None) => write!(self.out, "int")?,
                             (Sk::Sint | Sk::Bool, Sk::Uint, None) => write!(self.out, "uint")?,
                             (Sk::Bool, Sk::Float, None) => write!(self.out, "float")?,
@@ -3954,6 +4131,46 @@ impl<'a, W: Write> Writer<'a, W> {
         Ok(())
     }
 
+    /// Helper method to write the `ImageAtomic` statement
+    fn write_image_atomic(
+        &mut self,
+        ctx: &back::FunctionCtx,
+        image: Handle<crate::Expression>,
+        coordinate: Handle<crate::Expression>,
+        array_index: Option<Handle<crate::Expression>>,
+        fun: crate::AtomicFunction,
+        value: Handle<crate::Expression>,
+    ) -> Result<(), Error> {
+        use crate::ImageDimension as IDim;
+
+        // This will only panic if the module is invalid
+        let dim = match *ctx.resolve_type(image, &self.module.types) {
+            TypeInner::Image { dim, .. } => dim,
+            _ => unreachable!(),
+        };
+
+        let fun_str = fun.to_glsl();
+        write!(self.out, "imageAtomic{fun_str}(")?;
+        self.write_expr(image, ctx)?;
+        write!(self.out, ", ")?;
+
+        // openGL es doesn't have 1D images so we need workaround it
+        let tex_1d_hack = dim == IDim::D1 && self.options.version.is_es();
+        self.write_texture_coord(
+            ctx,
+            self.get_coordinate_vector_size(dim, array_index.is_some()),
+            coordinate,
+            array_index,
+            tex_1d_hack,
+        )?;
+
+        write!(self.out, ", ")?;
+        self.write_expr(value, ctx)?;
+        writeln!(self.out, ");")?;
+
+        Ok(())
+    }
+
     /// Helper method for writing an `ImageLoad` expression.
     #[allow(clippy::too_many_arguments)]
     fn write_image_load(
@@ -4292,6 +4509,7 @@ impl<'a, W: Write> Writer<'a, W> {
                     .expect("Bad array size")
                 {
                     proc::IndexableLength::Known(count) => count,
+                    proc::IndexableLength::Pending => return Err(Error::Override),
                     proc::IndexableLength::Dynamic => return Ok(()),
                 };
                 self.write_type(base)?;
@@ -4542,13 +4760,27 @@ const fn glsl_scalar(scalar: crate::Scalar) -> Result<ScalarString<'static>, Err
     use crate::ScalarKind as Sk;
 
     Ok(match scalar.kind {
-        Sk::Sint => ScalarString {
-            prefix: "i",
-            full: "int",
+        Sk::Sint => match scalar.width {
+            4 => ScalarString {
+                prefix: "i",
+                full: "int",
+            },
+            8 => ScalarString {
+                prefix: "i64",
+                full: "int64_t",
+            },
+            _ => return Err(Error::UnsupportedScalar(scalar)),
         },
-        Sk::Uint => ScalarString {
-            prefix: "u",
-            full: "uint",
+        Sk::Uint => match scalar.width {
+            4 => ScalarString {
+                prefix: "u",
+                full: "uint",
+            },
+            8 => ScalarString {
+                prefix: "u64",
+                full: "uint64_t",
+            },
+            _ => return Err(Error::UnsupportedScalar(scalar)),
         },
         Sk::Float => match scalar.width {
             4 => ScalarString {
@@ -4613,6 +4845,7 @@ const fn glsl_built_in(built_in: crate::BuiltIn, options: VaryingOptions) -> &'s
                 "gl_SampleMaskIn"
             }
         }
+        Bi::Barycentric => "gl_BaryCoordEXT",
         // compute
         Bi::GlobalInvocationId => "gl_GlobalInvocationID",
         Bi::LocalInvocationId => "gl_LocalInvocationID",
@@ -4625,6 +4858,27 @@ const fn glsl_built_in(built_in: crate::BuiltIn, options: VaryingOptions) -> &'s
         Bi::SubgroupId => "gl_SubgroupID",
         Bi::SubgroupSize => "gl_SubgroupSize",
         Bi::SubgroupInvocationId => "gl_SubgroupInvocationID",
+        // ray tracing pipeline
+        Bi::LaunchId => "gl_LaunchIDEXT",
+        Bi::LaunchSize => "gl_LaunchSizeEXT",
+        Bi::WorldRayOrigin => "gl_WorldRayOriginEXT",
+        Bi::WorldRayDirection => "gl_WorldRayDirectionEXT",
+        Bi::ObjectRayOrigin => "gl_ObjectRayOriginEXT",
+        Bi::ObjectRayDirection => "gl_ObjectRayDirectionEXT",
+        Bi::RayTmin => "gl_RayTminEXT",
+        Bi::RayTCurrent => "gl_RayTmaxEXT",
+        Bi::InstanceId => "gl_InstanceID",
+        Bi::InstanceCustomIndex => "gl_InstanceCustomIndexEXT",
+        Bi::GeometryIndex => "gl_GeometryIndexEXT",
+        Bi::HitKind => "gl_HitKindEXT",
+        Bi::IncomingRayFlags => "gl_IncomingRayFlagsEXT",
+        Bi::ObjectToWorld => "gl_ObjectToWorldEXT",
+        Bi::WorldToObject => "gl_WorldToObjectEXT",
+        // mesh shading
+        Bi::TriangleIndices => "gl_PrimitiveTriangleIndicesEXT",
+        Bi::LineIndices => "gl_PrimitiveLineIndicesEXT",
+        Bi::PointIndex => "gl_PrimitivePointIndicesEXT",
+        Bi::CullPrimitive => "gl_CullPrimitiveEXT",
     }
 }
 
@@ -4640,6 +4894,10 @@ const fn glsl_storage_qualifier(space: crate::AddressSpace) -> Option<&'static s
         As::Handle => Some("uniform"),
         As::WorkGroup => Some("shared"),
         As::PushConstant => Some("uniform"),
+        As::RayPayload => Some("rayPayloadEXT"),
+        As::IncomingRayPayload => Some("rayPayloadInEXT"),
+        As::HitAttribute => Some("hitAttributeEXT"),
+        As::TaskPayload => Some("taskPayloadSharedEXT"),
     }
 }
 
@@ -67,6 +67,8 @@ impl crate::TypeInner {
             Self::Array { base, size, stride } => {
                 let count = match size {
                     crate::ArraySize::Constant(size) => size.get(),
+                    // Overrides are processed before writing HLSL
+                    crate::ArraySize::Pending(_) => unreachable!(),
                     // A dynamically-sized array has to have at least one element
                     crate::ArraySize::Dynamic => 1,
                 };
@@ -170,6 +172,7 @@ impl crate::BuiltIn {
             Self::PrimitiveIndex => "SV_PrimitiveID",
             Self::SampleIndex => "SV_SampleIndex",
             Self::SampleMask => "SV_Coverage",
+            Self::Barycentric => "SV_Barycentrics",
             // compute
             Self::GlobalInvocationId => "SV_DispatchThreadID",
             Self::LocalInvocationId => "SV_GroupThreadID",
@@ -190,6 +193,25 @@ impl crate::BuiltIn {
             Self::PointSize | Self::ViewIndex | Self::PointCoord => {
                 return Err(Error::Custom(format!("Unsupported builtin {self:?}")))
             }
+            Self::LaunchId
+            | Self::LaunchSize
+            | Self::WorldRayOrigin
+            | Self::WorldRayDirection
+            | Self::ObjectRayOrigin
+            | Self::ObjectRayDirection
+            | Self::RayTmin
+            | Self::RayTCurrent
+            | Self::InstanceId
+            | Self::InstanceCustomIndex
+            | Self::GeometryIndex
+            | Self::HitKind
+            | Self::IncomingRayFlags
+            | Self::ObjectToWorld
+            | Self::WorldToObject
+            | Self::TriangleIndices
+            | Self::LineIndices
+            | Self::PointIndex
+            | Self::CullPrimitive => return Err(Error::Unimplemented(format!("builtin {self:?}"))),
         })
     }
 }
@@ -152,8 +152,17 @@ impl<'a, W: Write> super::Writer<'a, W> {
                 let scalar_kind_str = crate::Scalar { kind, width: 4 }.to_hlsl_str()?;
                 write!(self.out, "{multi_str}<{scalar_kind_str}4>")?
             }
-            crate::ImageClass::Storage { format, .. } => {
-                let storage_format_str = format.to_hlsl_str();
+            crate::ImageClass::Storage { format, access } => {
+                // Atomic operations need a scalar texel type.
+                let storage_format_str = if access.contains(crate::StorageAccess::ATOMIC) {
+                    crate::Scalar {
+                        kind: format.into(),
+                        width: 4,
+                    }
+                    .to_hlsl_str()?
+                } else {
+                    format.to_hlsl_str()
+                };
                 write!(self.out, "<{storage_format_str}>")?
             }
         }
@@ -838,7 +847,8 @@ impl<'a, W: Write> super::Writer<'a, W> {
                     )?;
                     writeln!(self.out)?;
                 }
-                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. } => {}
+                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. }
+                | &crate::PredeclaredType::SparseSampleResult => {}
             }
         }
 
@@ -1035,16 +1045,17 @@ impl<'a, W: Write> super::Writer<'a, W> {
         for (handle, _) in func_ctx.expressions.iter() {
             match func_ctx.expressions[handle] {
                 crate::Expression::ArrayLength(expr) => {
-                    let global_expr = match func_ctx.expressions[expr] {
-                        crate::Expression::GlobalVariable(_) => expr,
-                        crate::Expression::AccessIndex { base, index: _ } => base,
-                        ref other => unreachable!("Array length of {:?}", other),
-                    };
-                    let global_var = match func_ctx.expressions[global_expr] {
-                        crate::Expression::GlobalVariable(var_handle) => {
-                            &module.global_variables[var_handle]
+                    let mut global_expr = expr;
+                    let global_var = loop {
+                        match func_ctx.expressions[global_expr] {
+                            crate::Expression::GlobalVariable(var_handle) => {
+                                break &module.global_variables[var_handle]
+                            }
+                            // Struct members and elements of binding arrays.
+                            crate::Expression::AccessIndex { base, index: _ }
+                            | crate::Expression::Access { base, index: _ } => global_expr = base,
+                            ref other => unreachable!("Array length of {:?}", other),
                         }
-                        ref other => unreachable!("Array length of base {:?}", other),
                     };
                     let storage_access = match global_var.space {
                         crate::AddressSpace::Storage { access } => access,
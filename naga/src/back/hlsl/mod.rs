@@ -163,6 +163,11 @@ impl crate::ShaderStage {
             Self::Vertex => "vs",
             Self::Fragment => "ps",
             Self::Compute => "cs",
+            Self::RayGeneration
+            | Self::Intersection
+            | Self::AnyHit
+            | Self::ClosestHit
+            | Self::Miss => "lib",
         }
     }
 }
@@ -207,6 +212,10 @@ pub struct Options {
     pub push_constants_target: Option<BindTarget>,
     /// Should workgroup variables be zero initialized (by polyfilling)?
     pub zero_initialize_workgroup_memory: bool,
+    /// Original source of the module, to map the generated code back to it
+    /// with `#line` directives.
+    #[cfg_attr(feature = "deserialize", serde(default))]
+    pub debug_info: Option<super::DebugInfo>,
 }
 
 impl Default for Options {
@@ -218,6 +227,7 @@ impl Default for Options {
             special_constants_binding: None,
             push_constants_target: None,
             zero_initialize_workgroup_memory: true,
+            debug_info: None,
         }
     }
 }
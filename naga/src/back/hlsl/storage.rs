@@ -19,6 +19,10 @@ To generate code for a [`Storage`] access:
 - Call [`Writer::write_storage_address`] to emit an HLSL expression
   for a given slice of [`SubAccess`] values.
 
+- Call [`Writer::write_storage_buffer`] to emit the buffer that
+  [`fill_access_chain`] returned. This is the global itself, or one of
+  its elements when the global is a binding array of buffers.
+
 Naga IR expressions can operate on composite values of any type, but
 [`ByteAddressBuffer`] and [`RWByteAddressBuffer`] have only a fixed
 set of `Load` and `Store` methods, to access one through four
@@ -49,6 +53,8 @@ correct type if necessary.
 [`AccessIndex`]: crate::Expression::AccessIndex
 [`Writer::fill_access_chain`]: super::Writer::fill_access_chain
 [`Writer::write_storage_address`]: super::Writer::write_storage_address
+[`Writer::write_storage_buffer`]: super::Writer::write_storage_buffer
+[`fill_access_chain`]: super::Writer::fill_access_chain
 [`Writer::temp_access_chain`]: super::Writer::temp_access_chain
 [`temp_access_chain`]: super::Writer::temp_access_chain
 [`Writer`]: super::Writer
@@ -90,6 +96,22 @@ pub(super) enum SubAccess {
     },
 }
 
+/// The buffer holding a [`Storage`] global's component or element.
+///
+/// [`Storage`]: crate::AddressSpace::Storage
+#[derive(Clone, Copy, Debug)]
+pub(super) struct StorageBuffer {
+    pub global: Handle<crate::GlobalVariable>,
+    /// The element of `global` to use, if it is a binding array of buffers.
+    pub index: Option<BufferIndex>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) enum BufferIndex {
+    Expression(Handle<crate::Expression>),
+    Constant(u32),
+}
+
 pub(super) enum StoreValue {
     Expression(Handle<crate::Expression>),
     TempIndex {
@@ -105,6 +127,34 @@ pub(super) enum StoreValue {
 }
 
 impl<W: fmt::Write> super::Writer<'_, W> {
+    /// Emit the `ByteAddressBuffer` or `RWByteAddressBuffer` for `buffer`.
+    pub(super) fn write_storage_buffer(
+        &mut self,
+        module: &crate::Module,
+        buffer: StorageBuffer,
+        func_ctx: &FunctionCtx,
+    ) -> BackendResult {
+        let var_name = &self.names[&NameKey::GlobalVariable(buffer.global)];
+        write!(self.out, "{var_name}")?;
+        match buffer.index {
+            None => {}
+            Some(BufferIndex::Constant(index)) => write!(self.out, "[{index}]")?,
+            Some(BufferIndex::Expression(index)) => {
+                let non_uniform = func_ctx.info[index].uniformity.non_uniform_result.is_some();
+                write!(self.out, "[")?;
+                if non_uniform {
+                    write!(self.out, "NonUniformResourceIndex(")?;
+                }
+                self.write_expr(module, index, func_ctx)?;
+                if non_uniform {
+                    write!(self.out, ")")?;
+                }
+                write!(self.out, "]")?;
+            }
+        }
+        Ok(())
+    }
+
     pub(super) fn write_storage_address(
         &mut self,
         module: &crate::Module,
@@ -134,7 +184,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
     fn write_storage_load_sequence<I: Iterator<Item = (TypeResolution, u32)>>(
         &mut self,
         module: &crate::Module,
-        var_handle: Handle<crate::GlobalVariable>,
+        buffer: StorageBuffer,
         sequence: I,
         func_ctx: &FunctionCtx,
     ) -> BackendResult {
@@ -144,7 +194,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             if i != 0 {
                 write!(self.out, ", ")?;
             };
-            self.write_storage_load(module, var_handle, ty_resolution, func_ctx)?;
+            self.write_storage_load(module, buffer, ty_resolution, func_ctx)?;
             self.temp_access_chain.pop();
         }
         Ok(())
@@ -152,7 +202,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
 
     /// Emit code to access a [`Storage`] global's component.
     ///
-    /// Emit HLSL to access the component of `var_handle`, a global
+    /// Emit HLSL to access the component of `buffer`, a global
     /// variable in the [`Storage`] address space, whose type is
     /// `result_ty` and whose location within the global is given by
     /// [`self.temp_access_chain`]. See the [`storage`] module's
@@ -163,7 +213,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
     pub(super) fn write_storage_load(
         &mut self,
         module: &crate::Module,
-        var_handle: Handle<crate::GlobalVariable>,
+        buffer: StorageBuffer,
         result_ty: TypeResolution,
         func_ctx: &FunctionCtx,
     ) -> BackendResult {
@@ -171,14 +221,16 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             crate::TypeInner::Scalar(scalar) => {
                 // working around the borrow checker in `self.write_expr`
                 let chain = mem::take(&mut self.temp_access_chain);
-                let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
                 // See note about DXC and Load/Store in the module's documentation.
                 if scalar.width == 4 {
                     let cast = scalar.kind.to_hlsl_cast();
-                    write!(self.out, "{cast}({var_name}.Load(")?;
+                    write!(self.out, "{cast}(")?;
+                    self.write_storage_buffer(module, buffer, func_ctx)?;
+                    write!(self.out, ".Load(")?;
                 } else {
                     let ty = scalar.to_hlsl_str()?;
-                    write!(self.out, "{var_name}.Load<{ty}>(")?;
+                    self.write_storage_buffer(module, buffer, func_ctx)?;
+                    write!(self.out, ".Load<{ty}>(")?;
                 };
                 self.write_storage_address(module, &chain, func_ctx)?;
                 write!(self.out, ")")?;
@@ -190,15 +242,17 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             crate::TypeInner::Vector { size, scalar } => {
                 // working around the borrow checker in `self.write_expr`
                 let chain = mem::take(&mut self.temp_access_chain);
-                let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
                 let size = size as u8;
                 // See note about DXC and Load/Store in the module's documentation.
                 if scalar.width == 4 {
                     let cast = scalar.kind.to_hlsl_cast();
-                    write!(self.out, "{cast}({var_name}.Load{size}(")?;
+                    write!(self.out, "{cast}(")?;
+                    self.write_storage_buffer(module, buffer, func_ctx)?;
+                    write!(self.out, ".Load{size}(")?;
                 } else {
                     let ty = scalar.to_hlsl_str()?;
-                    write!(self.out, "{var_name}.Load<{ty}{size}>(")?;
+                    self.write_storage_buffer(module, buffer, func_ctx)?;
+                    write!(self.out, ".Load<{ty}{size}>(")?;
                 };
                 self.write_storage_address(module, &chain, func_ctx)?;
                 write!(self.out, ")")?;
@@ -226,7 +280,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                     let ty_inner = crate::TypeInner::Vector { size: rows, scalar };
                     (TypeResolution::Value(ty_inner), i * row_stride)
                 });
-                self.write_storage_load_sequence(module, var_handle, iter, func_ctx)?;
+                self.write_storage_load_sequence(module, buffer, iter, func_ctx)?;
                 write!(self.out, ")")?;
             }
             crate::TypeInner::Array {
@@ -240,7 +294,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                 self.write_wrapped_constructor_function_name(module, constructor)?;
                 write!(self.out, "(")?;
                 let iter = (0..size.get()).map(|i| (TypeResolution::Handle(base), stride * i));
-                self.write_storage_load_sequence(module, var_handle, iter, func_ctx)?;
+                self.write_storage_load_sequence(module, buffer, iter, func_ctx)?;
                 write!(self.out, ")")?;
             }
             crate::TypeInner::Struct { ref members, .. } => {
@@ -252,7 +306,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                 let iter = members
                     .iter()
                     .map(|m| (TypeResolution::Handle(m.ty), m.offset));
-                self.write_storage_load_sequence(module, var_handle, iter, func_ctx)?;
+                self.write_storage_load_sequence(module, buffer, iter, func_ctx)?;
                 write!(self.out, ")")?;
             }
             _ => unreachable!(),
@@ -289,7 +343,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
     pub(super) fn write_storage_store(
         &mut self,
         module: &crate::Module,
-        var_handle: Handle<crate::GlobalVariable>,
+        buffer: StorageBuffer,
         value: StoreValue,
         func_ctx: &FunctionCtx,
         level: crate::back::Level,
@@ -321,16 +375,17 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             crate::TypeInner::Scalar(scalar) => {
                 // working around the borrow checker in `self.write_expr`
                 let chain = mem::take(&mut self.temp_access_chain);
-                let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
+                write!(self.out, "{level}")?;
+                self.write_storage_buffer(module, buffer, func_ctx)?;
                 // See note about DXC and Load/Store in the module's documentation.
                 if scalar.width == 4 {
-                    write!(self.out, "{level}{var_name}.Store(")?;
+                    write!(self.out, ".Store(")?;
                     self.write_storage_address(module, &chain, func_ctx)?;
                     write!(self.out, ", asuint(")?;
                     self.write_store_value(module, &value, func_ctx)?;
                     writeln!(self.out, "));")?;
                 } else {
-                    write!(self.out, "{level}{var_name}.Store(")?;
+                    write!(self.out, ".Store(")?;
                     self.write_storage_address(module, &chain, func_ctx)?;
                     write!(self.out, ", ")?;
                     self.write_store_value(module, &value, func_ctx)?;
@@ -341,16 +396,17 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             crate::TypeInner::Vector { size, scalar } => {
                 // working around the borrow checker in `self.write_expr`
                 let chain = mem::take(&mut self.temp_access_chain);
-                let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
+                write!(self.out, "{level}")?;
+                self.write_storage_buffer(module, buffer, func_ctx)?;
                 // See note about DXC and Load/Store in the module's documentation.
                 if scalar.width == 4 {
-                    write!(self.out, "{}{}.Store{}(", level, var_name, size as u8)?;
+                    write!(self.out, ".Store{}(", size as u8)?;
                     self.write_storage_address(module, &chain, func_ctx)?;
                     write!(self.out, ", asuint(")?;
                     self.write_store_value(module, &value, func_ctx)?;
                     writeln!(self.out, "));")?;
                 } else {
-                    write!(self.out, "{}{}.Store(", level, var_name)?;
+                    write!(self.out, ".Store(")?;
                     self.write_storage_address(module, &chain, func_ctx)?;
                     write!(self.out, ", ")?;
                     self.write_store_value(module, &value, func_ctx)?;
@@ -392,7 +448,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                         index: i,
                         ty: TypeResolution::Value(ty_inner),
                     };
-                    self.write_storage_store(module, var_handle, sv, func_ctx, level.next())?;
+                    self.write_storage_store(module, buffer, sv, func_ctx, level.next())?;
                     self.temp_access_chain.pop();
                 }
                 // done
@@ -421,7 +477,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                         index: i,
                         ty: TypeResolution::Handle(base),
                     };
-                    self.write_storage_store(module, var_handle, sv, func_ctx, level.next())?;
+                    self.write_storage_store(module, buffer, sv, func_ctx, level.next())?;
                     self.temp_access_chain.pop();
                 }
                 // done
@@ -452,7 +508,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
                         base: struct_ty,
                         member_index: i as u32,
                     };
-                    self.write_storage_store(module, var_handle, sv, func_ctx, level.next())?;
+                    self.write_storage_store(module, buffer, sv, func_ctx, level.next())?;
                     self.temp_access_chain.pop();
                 }
                 // done
@@ -470,6 +526,8 @@ impl<W: fmt::Write> super::Writer<'_, W> {
     /// [`Access`] and [`AccessIndex`] expressions referring to some
     /// component of such a global.
     ///
+    /// Returns the buffer the chain starts from.
+    ///
     /// [`temp_access_chain`]: super::Writer::temp_access_chain
     /// [`Storage`]: crate::AddressSpace::Storage
     /// [`Access`]: crate::Expression::Access
@@ -479,7 +537,7 @@ impl<W: fmt::Write> super::Writer<'_, W> {
         module: &crate::Module,
         mut cur_expr: Handle<crate::Expression>,
         func_ctx: &FunctionCtx,
-    ) -> Result<Handle<crate::GlobalVariable>, Error> {
+    ) -> Result<StorageBuffer, Error> {
         enum AccessIndex {
             Expression(Handle<crate::Expression>),
             Constant(u32),
@@ -489,10 +547,13 @@ impl<W: fmt::Write> super::Writer<'_, W> {
             Struct(&'a [crate::StructMember]),
         }
         self.temp_access_chain.clear();
+        let mut index = None;
 
         loop {
             let (next_expr, access_index) = match func_ctx.expressions[cur_expr] {
-                crate::Expression::GlobalVariable(handle) => return Ok(handle),
+                crate::Expression::GlobalVariable(global) => {
+                    return Ok(StorageBuffer { global, index })
+                }
                 crate::Expression::Access { base, index } => (base, AccessIndex::Expression(index)),
                 crate::Expression::AccessIndex { base, index } => {
                     (base, AccessIndex::Constant(index))
@@ -504,6 +565,16 @@ impl<W: fmt::Write> super::Writer<'_, W> {
 
             let parent = match *func_ctx.resolve_type(next_expr, &module.types) {
                 crate::TypeInner::Pointer { base, .. } => match module.types[base].inner {
+                    // Indexing a binding array selects the buffer, and
+                    // doesn't contribute to the offset within it.
+                    crate::TypeInner::BindingArray { .. } => {
+                        index = Some(match access_index {
+                            AccessIndex::Expression(value) => BufferIndex::Expression(value),
+                            AccessIndex::Constant(value) => BufferIndex::Constant(value),
+                        });
+                        cur_expr = next_expr;
+                        continue;
+                    }
                     crate::TypeInner::Struct { ref members, .. } => Parent::Struct(members),
                     crate::TypeInner::Array { stride, .. } => Parent::Array { stride },
                     crate::TypeInner::Vector { scalar, .. } => Parent::Array {
@@ -4,7 +4,7 @@ use super::{
         WrappedZeroValue,
     },
     storage::StoreValue,
-    BackendResult, Error, Options,
+    BackendResult, Error, Options, ShaderModel,
 };
 use crate::{
     back,
@@ -201,6 +201,12 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             return Err(Error::Override);
         }
 
+        if let Some(ep) = module.entry_points.iter().find(|ep| {
+            ep.stage.is_ray_tracing() || matches!(ep.stage, ShaderStage::Task | ShaderStage::Mesh)
+        }) {
+            return Err(Error::Unimplemented(format!("{:?} shader stage", ep.stage)));
+        }
+
         self.reset(module);
 
         // Write special constants, if needed
@@ -377,6 +383,12 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             self.write_wrapped_functions(module, &ctx)?;
 
             if ep.stage == ShaderStage::Compute {
+                if info.uses_derivatives && self.options.shader_model < ShaderModel::V6_6 {
+                    return Err(Error::Custom(
+                        "Derivatives in compute shaders require shader model 6.6".to_string(),
+                    ));
+                }
+
                 // HLSL is calling workgroup size "num threads"
                 let num_threads = ep.workgroup_size;
                 writeln!(
@@ -747,6 +759,14 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         // https://docs.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl-variable-register
         let register_ty = match global.space {
             crate::AddressSpace::Function => unreachable!("Function address space"),
+            crate::AddressSpace::RayPayload
+            | crate::AddressSpace::IncomingRayPayload
+            | crate::AddressSpace::HitAttribute => {
+                unreachable!("ray tracing pipeline stages are rejected up front")
+            }
+            crate::AddressSpace::TaskPayload => {
+                unreachable!("mesh shading stages are rejected up front")
+            }
             crate::AddressSpace::Private => {
                 write!(self.out, "static ")?;
                 self.write_type(module, global.ty)?;
@@ -758,9 +778,17 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 ""
             }
             crate::AddressSpace::Uniform => {
-                // constant buffer declarations are expected to be inlined, e.g.
-                // `cbuffer foo: register(b0) { field1: type1; }`
-                write!(self.out, "cbuffer")?;
+                if let TypeInner::BindingArray { base, .. } = *inner {
+                    // `cbuffer` blocks can't be arrays, so arrays of uniform
+                    // buffers are declared as `ConstantBuffer<T> foo[N]` instead.
+                    write!(self.out, "ConstantBuffer<")?;
+                    self.write_global_type(module, base)?;
+                    write!(self.out, ">")?;
+                } else {
+                    // constant buffer declarations are expected to be inlined, e.g.
+                    // `cbuffer foo: register(b0) { field1: type1; }`
+                    write!(self.out, "cbuffer")?;
+                }
                 "b"
             }
             crate::AddressSpace::Storage { access } => {
@@ -837,6 +865,14 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             if let TypeInner::BindingArray { base, size, .. } = module.types[global.ty].inner {
                 if let Some(overridden_size) = bt.binding_array_size {
                     write!(self.out, "[{overridden_size}]")?;
+                } else if let crate::AddressSpace::Storage { .. } = global.space {
+                    // Arrays within the buffers are laid out in their bytes,
+                    // they are not part of the declaration.
+                    match size {
+                        crate::ArraySize::Constant(size) => write!(self.out, "[{size}]")?,
+                        crate::ArraySize::Pending(_) => return Err(Error::Override),
+                        crate::ArraySize::Dynamic => unreachable!(),
+                    }
                 } else {
                     self.write_array_size(module, base, size)?;
                 }
@@ -862,7 +898,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             }
         }
 
-        if global.space == crate::AddressSpace::Uniform {
+        if global.space == crate::AddressSpace::Uniform
+            && !matches!(*inner, TypeInner::BindingArray { .. })
+        {
             write!(self.out, " {{ ")?;
 
             self.write_global_type(module, global.ty)?;
@@ -922,6 +960,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             crate::ArraySize::Constant(size) => {
                 write!(self.out, "{size}")?;
             }
+            crate::ArraySize::Pending(_) => return Err(Error::Override),
             crate::ArraySize::Dynamic => unreachable!(),
         }
 
@@ -1219,6 +1258,15 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     }
                     // Write argument type
                     let arg_ty = match module.types[arg.ty].inner {
+                        TypeInner::Pointer {
+                            space:
+                                crate::AddressSpace::Storage { .. } | crate::AddressSpace::WorkGroup,
+                            ..
+                        } => {
+                            return Err(Error::Unimplemented(
+                                "storage or workgroup pointer function arguments".to_string(),
+                            ));
+                        }
                         // pointers in function arguments are expected and resolve to `inout`
                         TypeInner::Pointer { base, .. } => {
                             //TODO: can we narrow this down to just `in` when possible?
@@ -1326,7 +1374,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         }
 
         // Write the function body (statement list)
-        for sta in func.body.iter() {
+        for (sta, span) in func.body.span_iter() {
+            self.write_line_directive(*span)?;
             // The indentation should always be 1 when writing the function body
             self.write_stmt(module, sta, func_ctx, back::Level(1))?;
         }
@@ -1377,6 +1426,14 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         self.write_barrier(crate::Barrier::WORK_GROUP, level)
     }
 
+    /// Write a `#line` directive for a statement, if debug info was requested
+    fn write_line_directive(&mut self, span: crate::Span) -> BackendResult {
+        if let Some(ref debug_info) = self.options.debug_info {
+            debug_info.write_line_directive(&mut self.out, span)?;
+        }
+        Ok(())
+    }
+
     /// Helper method used to write statements
     ///
     /// # Notes
@@ -1421,7 +1478,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             Statement::Block(ref block) => {
                 write!(self.out, "{level}")?;
                 writeln!(self.out, "{{")?;
-                for sta in block.iter() {
+                for (sta, span) in block.span_iter() {
+                    self.write_line_directive(*span)?;
                     // Increase the indentation to help with readability
                     self.write_stmt(module, sta, func_ctx, level.next())?
                 }
@@ -1439,7 +1497,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 writeln!(self.out, ") {{")?;
 
                 let l2 = level.next();
-                for sta in accept {
+                for (sta, span) in accept.span_iter() {
+                    self.write_line_directive(*span)?;
                     // Increase indentation to help with readability
                     self.write_stmt(module, sta, func_ctx, l2)?;
                 }
@@ -1449,7 +1508,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 if !reject.is_empty() {
                     writeln!(self.out, "{level}}} else {{")?;
 
-                    for sta in reject {
+                    for (sta, span) in reject.span_iter() {
+                        self.write_line_directive(*span)?;
                         // Increase indentation to help with readability
                         self.write_stmt(module, sta, func_ctx, l2)?;
                     }
@@ -1515,10 +1575,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             Statement::Store { pointer, value } => {
                 let ty_inner = func_ctx.resolve_type(pointer, &module.types);
                 if let Some(crate::AddressSpace::Storage { .. }) = ty_inner.pointer_space() {
-                    let var_handle = self.fill_access_chain(module, pointer, func_ctx)?;
+                    let buffer = self.fill_access_chain(module, pointer, func_ctx)?;
                     self.write_storage_store(
                         module,
-                        var_handle,
+                        buffer,
                         StoreValue::Expression(value),
                         func_ctx,
                         level,
@@ -1827,7 +1887,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     writeln!(self.out, "{level}while(true) {{")?;
                     writeln!(self.out, "{l2}if (!{gate_name}) {{")?;
                     let l3 = l2.next();
-                    for sta in continuing.iter() {
+                    for (sta, span) in continuing.span_iter() {
+                        self.write_line_directive(*span)?;
                         self.write_stmt(module, sta, func_ctx, l3)?;
                     }
                     if let Some(condition) = break_if {
@@ -1843,7 +1904,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     writeln!(self.out, "{level}while(true) {{")?;
                 }
 
-                for sta in body.iter() {
+                for (sta, span) in body.span_iter() {
+                    self.write_line_directive(*span)?;
                     self.write_stmt(module, sta, func_ctx, l2)?;
                 }
                 writeln!(self.out, "{level}}}")?
@@ -1879,6 +1941,32 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 self.write_expr(module, value, func_ctx)?;
                 writeln!(self.out, ";")?;
             }
+            Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                fun,
+                value,
+            } => {
+                let fun_str = fun.to_hlsl_suffix();
+                write!(self.out, "{level}Interlocked{fun_str}(")?;
+                self.write_expr(module, image, func_ctx)?;
+
+                write!(self.out, "[")?;
+                if let Some(index) = array_index {
+                    write!(self.out, "int3(")?;
+                    self.write_expr(module, coordinate, func_ctx)?;
+                    write!(self.out, ", ")?;
+                    self.write_expr(module, index, func_ctx)?;
+                    write!(self.out, ")")?;
+                } else {
+                    self.write_expr(module, coordinate, func_ctx)?;
+                }
+                write!(self.out, "], ")?;
+
+                self.write_expr(module, value, func_ctx)?;
+                writeln!(self.out, ");")?;
+            }
             Statement::Call {
                 function,
                 ref arguments,
@@ -1937,13 +2025,13 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         self.write_expr(module, pointer, func_ctx)?;
                     }
                     crate::AddressSpace::Storage { .. } => {
-                        let var_handle = self.fill_access_chain(module, pointer, func_ctx)?;
+                        let buffer = self.fill_access_chain(module, pointer, func_ctx)?;
                         // The call to `self.write_storage_address` wants
                         // mutable access to all of `self`, so temporarily take
                         // ownership of our reusable access chain buffer.
                         let chain = mem::take(&mut self.temp_access_chain);
-                        let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
-                        write!(self.out, "{var_name}.Interlocked{fun_str}(")?;
+                        self.write_storage_buffer(module, buffer, func_ctx)?;
+                        write!(self.out, ".Interlocked{fun_str}(")?;
                         self.write_storage_address(module, &chain, func_ctx)?;
                         self.temp_access_chain = chain;
                     }
@@ -2046,7 +2134,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         for case in &cases[i..=end_case_idx] {
                             writeln!(self.out, "{indent_level_2}{{")?;
                             let prev_len = self.named_expressions.len();
-                            for sta in case.body.iter() {
+                            for (sta, span) in case.body.span_iter() {
+                                self.write_line_directive(*span)?;
                                 self.write_stmt(module, sta, func_ctx, indent_level_3)?;
                             }
                             // Clear all named expressions that were previously inserted by the statements in the block
@@ -2059,7 +2148,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                             writeln!(self.out, "{indent_level_2}break;")?;
                         }
                     } else {
-                        for sta in case.body.iter() {
+                        for (sta, span) in case.body.span_iter() {
+                            self.write_line_directive(*span)?;
                             self.write_stmt(module, sta, func_ctx, indent_level_2)?;
                         }
                         if !case.fall_through
@@ -2077,6 +2167,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 writeln!(self.out, "{level}}}")?
             }
             Statement::RayQuery { .. } => unreachable!(),
+            Statement::RayPipelineFunction(_) => unreachable!(),
+            Statement::MeshFunction(_) => unreachable!(),
             Statement::SubgroupBallot { result, predicate } => {
                 write!(self.out, "{level}")?;
                 let name = format!("{}{}", back::BAKE_PREFIX, result.index());
@@ -2090,6 +2182,60 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 }
                 writeln!(self.out, ");")?;
             }
+            Statement::SubgroupCollectiveOperation {
+                op,
+                collective_op: crate::CollectiveOperation::ClusteredReduce(cluster_size),
+                argument,
+                result,
+            } => {
+                // HLSL has no clustered wave intrinsics, so reduce within each
+                // cluster with a butterfly of lane reads instead.
+                write!(self.out, "{level}")?;
+                let name = format!("{}{}", back::BAKE_PREFIX, result.index());
+                match func_ctx.info[result].ty {
+                    proc::TypeResolution::Handle(handle) => self.write_type(module, handle)?,
+                    proc::TypeResolution::Value(ref value) => {
+                        self.write_value_type(module, value)?
+                    }
+                };
+                write!(self.out, " {name} = ")?;
+                self.write_expr(module, argument, func_ctx)?;
+                writeln!(self.out, ";")?;
+
+                let mut lane_mask = 1;
+                while lane_mask < cluster_size {
+                    let other =
+                        format!("WaveReadLaneAt({name}, WaveGetLaneIndex() ^ {lane_mask}u)");
+                    match op {
+                        crate::SubgroupOperation::Add => {
+                            writeln!(self.out, "{level}{name} = {name} + {other};")?
+                        }
+                        crate::SubgroupOperation::Mul => {
+                            writeln!(self.out, "{level}{name} = {name} * {other};")?
+                        }
+                        crate::SubgroupOperation::Max => {
+                            writeln!(self.out, "{level}{name} = max({name}, {other});")?
+                        }
+                        crate::SubgroupOperation::Min => {
+                            writeln!(self.out, "{level}{name} = min({name}, {other});")?
+                        }
+                        crate::SubgroupOperation::And => {
+                            writeln!(self.out, "{level}{name} = {name} & {other};")?
+                        }
+                        crate::SubgroupOperation::Or => {
+                            writeln!(self.out, "{level}{name} = {name} | {other};")?
+                        }
+                        crate::SubgroupOperation::Xor => {
+                            writeln!(self.out, "{level}{name} = {name} ^ {other};")?
+                        }
+                        crate::SubgroupOperation::All | crate::SubgroupOperation::Any => {
+                            unreachable!()
+                        }
+                    }
+                    lane_mask <<= 1;
+                }
+                self.named_expressions.insert(result, name);
+            }
             Statement::SubgroupCollectiveOperation {
                 op,
                 collective_op,
@@ -2172,29 +2318,51 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 write!(self.out, " {name} = ")?;
                 self.named_expressions.insert(result, name);
 
-                if matches!(mode, crate::GatherMode::BroadcastFirst) {
-                    write!(self.out, "WaveReadLaneFirst(")?;
-                    self.write_expr(module, argument, func_ctx)?;
-                } else {
-                    write!(self.out, "WaveReadLaneAt(")?;
-                    self.write_expr(module, argument, func_ctx)?;
-                    write!(self.out, ", ")?;
-                    match mode {
-                        crate::GatherMode::BroadcastFirst => unreachable!(),
-                        crate::GatherMode::Broadcast(index) | crate::GatherMode::Shuffle(index) => {
-                            self.write_expr(module, index, func_ctx)?;
-                        }
-                        crate::GatherMode::ShuffleDown(index) => {
-                            write!(self.out, "WaveGetLaneIndex() + ")?;
-                            self.write_expr(module, index, func_ctx)?;
-                        }
-                        crate::GatherMode::ShuffleUp(index) => {
-                            write!(self.out, "WaveGetLaneIndex() - ")?;
-                            self.write_expr(module, index, func_ctx)?;
+                match mode {
+                    crate::GatherMode::BroadcastFirst => {
+                        write!(self.out, "WaveReadLaneFirst(")?;
+                        self.write_expr(module, argument, func_ctx)?;
+                    }
+                    crate::GatherMode::QuadBroadcast(index) => {
+                        write!(self.out, "QuadReadLaneAt(")?;
+                        self.write_expr(module, argument, func_ctx)?;
+                        write!(self.out, ", ")?;
+                        self.write_expr(module, index, func_ctx)?;
+                    }
+                    crate::GatherMode::QuadSwap(direction) => {
+                        match direction {
+                            crate::Direction::X => write!(self.out, "QuadReadAcrossX(")?,
+                            crate::Direction::Y => write!(self.out, "QuadReadAcrossY(")?,
+                            crate::Direction::Diagonal => {
+                                write!(self.out, "QuadReadAcrossDiagonal(")?
+                            }
                         }
-                        crate::GatherMode::ShuffleXor(index) => {
-                            write!(self.out, "WaveGetLaneIndex() ^ ")?;
-                            self.write_expr(module, index, func_ctx)?;
+                        self.write_expr(module, argument, func_ctx)?;
+                    }
+                    _ => {
+                        write!(self.out, "WaveReadLaneAt(")?;
+                        self.write_expr(module, argument, func_ctx)?;
+                        write!(self.out, ", ")?;
+                        match mode {
+                            crate::GatherMode::BroadcastFirst
+                            | crate::GatherMode::QuadBroadcast(_)
+                            | crate::GatherMode::QuadSwap(_) => unreachable!(),
+                            crate::GatherMode::Broadcast(index)
+                            | crate::GatherMode::Shuffle(index) => {
+                                self.write_expr(module, index, func_ctx)?;
+                            }
+                            crate::GatherMode::ShuffleDown(index) => {
+                                write!(self.out, "WaveGetLaneIndex() + ")?;
+                                self.write_expr(module, index, func_ctx)?;
+                            }
+                            crate::GatherMode::ShuffleUp(index) => {
+                                write!(self.out, "WaveGetLaneIndex() - ")?;
+                                self.write_expr(module, index, func_ctx)?;
+                            }
+                            crate::GatherMode::ShuffleXor(index) => {
+                                write!(self.out, "WaveGetLaneIndex() ^ ")?;
+                                self.write_expr(module, index, func_ctx)?;
+                            }
                         }
                     }
                 }
@@ -2571,10 +2739,17 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => {
                 use crate::SampleLevel as Sl;
                 const COMPONENTS: [&str; 4] = ["", "Green", "Blue", "Alpha"];
 
+                if residency {
+                    return Err(Error::Unimplemented(
+                        "sampling with residency feedback".to_string(),
+                    ));
+                }
+
                 let (base_str, component_str) = match gather {
                     Some(component) => ("Gather", COMPONENTS[component as usize]),
                     None => ("Sample", ""),
@@ -2669,6 +2844,22 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 sample,
                 level,
             } => {
+                // Images used with atomics are declared with a scalar texel type,
+                // so widen the loaded value back to a vector.
+                let atomic_scalar = match *func_ctx.resolve_type(image, &module.types) {
+                    TypeInner::Image {
+                        class: crate::ImageClass::Storage { format, access },
+                        ..
+                    } if access.contains(crate::StorageAccess::ATOMIC) => Some(crate::Scalar {
+                        kind: format.into(),
+                        width: 4,
+                    }),
+                    _ => None,
+                };
+                if let Some(scalar) = atomic_scalar {
+                    write!(self.out, "{}4(", scalar.to_hlsl_str()?)?;
+                }
+
                 // https://docs.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl-to-load
                 self.write_expr(module, image, func_ctx)?;
                 write!(self.out, ".Load(")?;
@@ -2690,8 +2881,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 // close bracket for Load function
                 write!(self.out, ")")?;
 
-                // return x component if return type is scalar
-                if let TypeInner::Scalar(_) = *func_ctx.resolve_type(expr, &module.types) {
+                if atomic_scalar.is_some() {
+                    write!(self.out, ", 0, 0, 1)")?;
+                } else if let TypeInner::Scalar(_) = *func_ctx.resolve_type(expr, &module.types) {
+                    // return x component if return type is scalar
                     write!(self.out, ".x")?;
                 }
             }
@@ -2711,9 +2904,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     .pointer_space()
                 {
                     Some(crate::AddressSpace::Storage { .. }) => {
-                        let var_handle = self.fill_access_chain(module, pointer, func_ctx)?;
+                        let buffer = self.fill_access_chain(module, pointer, func_ctx)?;
                         let result_ty = func_ctx.info[expr].ty.clone();
-                        self.write_storage_load(module, var_handle, result_ty, func_ctx)?;
+                        self.write_storage_load(module, buffer, result_ty, func_ctx)?;
                     }
                     _ => {
                         let mut close_paren = false;
@@ -3250,19 +3443,15 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 }
             }
             Expression::ArrayLength(expr) => {
-                let var_handle = match func_ctx.expressions[expr] {
-                    Expression::AccessIndex { base, index: _ } => {
-                        match func_ctx.expressions[base] {
-                            Expression::GlobalVariable(handle) => handle,
-                            _ => unreachable!(),
-                        }
-                    }
-                    Expression::GlobalVariable(handle) => handle,
-                    _ => unreachable!(),
-                };
+                // Only the buffer matters here, not the offset of the array.
+                let buffer = self.fill_access_chain(module, expr, func_ctx)?;
 
-                let var = &module.global_variables[var_handle];
-                let (offset, stride) = match module.types[var.ty].inner {
+                let var = &module.global_variables[buffer.global];
+                let buffer_ty = match module.types[var.ty].inner {
+                    TypeInner::BindingArray { base, .. } => base,
+                    _ => var.ty,
+                };
+                let (offset, stride) = match module.types[buffer_ty].inner {
                     TypeInner::Array { stride, .. } => (0, stride),
                     TypeInner::Struct { ref members, .. } => {
                         let last = members.last().unwrap();
@@ -3285,8 +3474,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
 
                 write!(self.out, "((")?;
                 self.write_wrapped_array_length_function_name(wrapped_array_length)?;
-                let var_name = &self.names[&NameKey::GlobalVariable(var_handle)];
-                write!(self.out, "({var_name}) - {offset}) / {stride})")?
+                write!(self.out, "(")?;
+                self.write_storage_buffer(module, buffer, func_ctx)?;
+                write!(self.out, ") - {offset}) / {stride})")?
             }
             Expression::Derivative { axis, ctrl, expr } => {
                 use crate::{DerivativeAxis as Axis, DerivativeControl as Ctrl};
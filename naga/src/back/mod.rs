@@ -43,6 +43,45 @@ pub type NeedBakeExpressions = crate::FastHashSet<crate::Handle<crate::Expressio
 /// The value may represent any of WGSL's concrete scalar types.
 pub type PipelineConstants = std::collections::HashMap<String, f64>;
 
+/// Original source of a module, used by the textual backends to emit `#line`
+/// directives that map the generated code back to it, so that shader debuggers
+/// can step through the source the module was parsed from.
+#[cfg(any(feature = "hlsl-out", feature = "msl-out"))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+pub struct DebugInfo {
+    /// Name of the source file, as reported by debuggers.
+    pub file_name: String,
+    /// Source code that the module's spans refer to.
+    pub source_code: String,
+}
+
+#[cfg(any(feature = "hlsl-out", feature = "msl-out"))]
+impl DebugInfo {
+    /// Write a `#line` directive pointing at the start of `span`.
+    ///
+    /// Nothing is written if `span` is undefined.
+    fn write_line_directive(
+        &self,
+        out: &mut impl std::fmt::Write,
+        span: crate::Span,
+    ) -> std::fmt::Result {
+        if !span.is_defined() {
+            return Ok(());
+        }
+        let location = span.location(&self.source_code);
+        write!(out, "#line {} \"", location.line_number)?;
+        for c in self.file_name.chars() {
+            if c == '"' || c == '\\' {
+                out.write_char('\\')?;
+            }
+            out.write_char(c)?;
+        }
+        writeln!(out, "\"")
+    }
+}
+
 /// Indentation level.
 #[derive(Clone, Copy)]
 pub struct Level(pub usize);
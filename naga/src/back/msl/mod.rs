@@ -143,6 +143,8 @@ pub enum Error {
     UnsupportedArrayOfType(Handle<crate::Type>),
     #[error("ray tracing is not supported prior to MSL 2.3")]
     UnsupportedRayTracing,
+    #[error("{0:?} shader stage is not supported")]
+    UnsupportedShaderStage(crate::ShaderStage),
     #[error("overrides should not be present at this stage")]
     Override,
 }
@@ -206,6 +208,10 @@ pub struct Options {
     pub bounds_check_policies: index::BoundsCheckPolicies,
     /// Should workgroup variables be zero initialized (by polyfilling)?
     pub zero_initialize_workgroup_memory: bool,
+    /// Original source of the module, to map the generated code back to it
+    /// with `#line` directives.
+    #[cfg_attr(feature = "deserialize", serde(default))]
+    pub debug_info: Option<super::DebugInfo>,
 }
 
 impl Default for Options {
@@ -218,6 +224,7 @@ impl Default for Options {
             fake_missing_bindings: true,
             bounds_check_policies: index::BoundsCheckPolicies::default(),
             zero_initialize_workgroup_memory: true,
+            debug_info: None,
         }
     }
 }
@@ -267,6 +274,9 @@ impl Options {
                     crate::BuiltIn::PrimitiveIndex if self.lang_version < (2, 2) => {
                         return Err(Error::UnsupportedAttribute("primitive_id".to_string()));
                     }
+                    crate::BuiltIn::Barycentric if self.lang_version < (2, 2) => {
+                        return Err(Error::UnsupportedAttribute("barycentric_coord".to_string()));
+                    }
                     _ => {}
                 }
 
@@ -277,6 +287,7 @@ impl Options {
                 interpolation,
                 sampling,
                 second_blend_source,
+                ..
             } => match mode {
                 LocationMode::VertexInput => Ok(ResolvedBinding::Attribute(location)),
                 LocationMode::FragmentOutput => {
@@ -429,6 +440,7 @@ impl ResolvedBinding {
                     Bi::PrimitiveIndex => "primitive_id",
                     Bi::SampleIndex => "sample_id",
                     Bi::SampleMask => "sample_mask",
+                    Bi::Barycentric => "barycentric_coord",
                     // compute
                     Bi::GlobalInvocationId => "thread_position_in_grid",
                     Bi::LocalInvocationId => "thread_position_in_threadgroup",
@@ -441,9 +453,28 @@ impl ResolvedBinding {
                     Bi::SubgroupId => "simdgroup_index_in_threadgroup",
                     Bi::SubgroupSize => "threads_per_simdgroup",
                     Bi::SubgroupInvocationId => "thread_index_in_simdgroup",
-                    Bi::CullDistance | Bi::ViewIndex => {
-                        return Err(Error::UnsupportedBuiltIn(built_in))
-                    }
+                    // mesh shading
+                    Bi::CullPrimitive => "primitive_culled",
+                    Bi::CullDistance
+                    | Bi::ViewIndex
+                    | Bi::LaunchId
+                    | Bi::LaunchSize
+                    | Bi::WorldRayOrigin
+                    | Bi::WorldRayDirection
+                    | Bi::ObjectRayOrigin
+                    | Bi::ObjectRayDirection
+                    | Bi::RayTmin
+                    | Bi::RayTCurrent
+                    | Bi::InstanceId
+                    | Bi::InstanceCustomIndex
+                    | Bi::GeometryIndex
+                    | Bi::HitKind
+                    | Bi::IncomingRayFlags
+                    | Bi::ObjectToWorld
+                    | Bi::WorldToObject
+                    | Bi::TriangleIndices
+                    | Bi::LineIndices
+                    | Bi::PointIndex => return Err(Error::UnsupportedBuiltIn(built_in)),
                 };
                 write!(out, "{name}")?;
             }
@@ -388,6 +388,18 @@ fn should_pack_struct_member(
     }
 }
 
+/// Returns true if `binding` is one of a mesh shader's primitive index built-ins.
+const fn is_mesh_primitive_indices(binding: Option<&crate::Binding>) -> bool {
+    matches!(
+        binding,
+        Some(&crate::Binding::BuiltIn(
+            crate::BuiltIn::TriangleIndices
+                | crate::BuiltIn::LineIndices
+                | crate::BuiltIn::PointIndex
+        ))
+    )
+}
+
 fn needs_array_length(ty: Handle<crate::Type>, arena: &crate::UniqueArena<crate::Type>) -> bool {
     match arena[ty].inner {
         crate::TypeInner::Struct { ref members, .. } => {
@@ -421,7 +433,11 @@ impl crate::AddressSpace {
             | Self::Private
             | Self::WorkGroup
             | Self::PushConstant
-            | Self::Handle => true,
+            | Self::Handle
+            | Self::RayPayload
+            | Self::IncomingRayPayload
+            | Self::HitAttribute
+            | Self::TaskPayload => true,
             Self::Function => false,
         }
     }
@@ -433,9 +449,13 @@ impl crate::AddressSpace {
             // rely on the actual use of a global by functions. This means we
             // may end up with "const" even if the binding is read-write,
             // and that should be OK.
-            Self::Storage { .. } => true,
+            Self::Storage { .. } | Self::TaskPayload => true,
             // These should always be read-write.
-            Self::Private | Self::WorkGroup => false,
+            Self::Private
+            | Self::WorkGroup
+            | Self::RayPayload
+            | Self::IncomingRayPayload
+            | Self::HitAttribute => false,
             // These translate to `constant` address space, no need for qualifiers.
             Self::Uniform | Self::PushConstant => false,
             // Not applicable.
@@ -450,6 +470,8 @@ impl crate::AddressSpace {
             Self::Storage { .. } => Some("device"),
             Self::Private | Self::Function => Some("thread"),
             Self::WorkGroup => Some("threadgroup"),
+            Self::RayPayload | Self::IncomingRayPayload | Self::HitAttribute => Some("ray_data"),
+            Self::TaskPayload => Some("object_data"),
         }
     }
 }
@@ -590,6 +612,24 @@ impl<'a> ExpressionContext<'a> {
 struct StatementContext<'a> {
     expression: ExpressionContext<'a>,
     result_struct: Option<&'a str>,
+    mesh_stage: Option<&'a MeshStageNames>,
+    /// Source to map statements back to with `#line` directives.
+    debug_info: Option<&'a back::DebugInfo>,
+}
+
+/// The names an object or mesh entry point's body writes its outputs through.
+enum MeshStageNames {
+    Object {
+        /// The `metal::mesh_grid_properties` argument.
+        grid: String,
+    },
+    Mesh {
+        /// The `metal::mesh` argument.
+        output: String,
+        vertex_struct: String,
+        /// Absent if the primitive output has no members besides its indices.
+        primitive_struct: Option<String>,
+    },
 }
 
 impl<W: Write> Writer<W> {
@@ -1062,6 +1102,73 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
+    fn put_image_atomic(
+        &mut self,
+        level: back::Level,
+        image: Handle<crate::Expression>,
+        address: &TexelAddress,
+        fun: crate::AtomicFunction,
+        value: Handle<crate::Expression>,
+        context: &StatementContext,
+    ) -> BackendResult {
+        let fun_str = fun.to_msl()?;
+        match context.expression.policies.image_store {
+            proc::BoundsCheckPolicy::Restrict => {
+                write!(self.out, "{level}")?;
+                self.put_expression(image, &context.expression, false)?;
+                write!(self.out, ".atomic_{fun_str}(")?;
+                self.put_restricted_texel_address(image, address, &context.expression)?;
+                write!(self.out, ", ")?;
+                self.put_expression(value, &context.expression, true)?;
+                writeln!(self.out, ");")?;
+            }
+            proc::BoundsCheckPolicy::ReadZeroSkipWrite => {
+                write!(self.out, "{level}if (")?;
+                self.put_image_access_bounds_check(image, address, &context.expression)?;
+                writeln!(self.out, ") {{")?;
+                self.put_unchecked_image_atomic(
+                    level.next(),
+                    image,
+                    address,
+                    fun_str,
+                    value,
+                    context,
+                )?;
+                writeln!(self.out, "{level}}}")?;
+            }
+            proc::BoundsCheckPolicy::Unchecked => {
+                self.put_unchecked_image_atomic(level, image, address, fun_str, value, context)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn put_unchecked_image_atomic(
+        &mut self,
+        level: back::Level,
+        image: Handle<crate::Expression>,
+        address: &TexelAddress,
+        fun_str: &str,
+        value: Handle<crate::Expression>,
+        context: &StatementContext,
+    ) -> BackendResult {
+        write!(self.out, "{level}")?;
+        self.put_expression(image, &context.expression, false)?;
+        write!(self.out, ".atomic_{fun_str}(")?;
+        // coordinates in IR are int, but Metal expects uint
+        self.put_cast_to_uint_scalar_or_vector(address.coordinate, &context.expression)?;
+        if let Some(expr) = address.array_index {
+            write!(self.out, ", ")?;
+            self.put_expression(expr, &context.expression, true)?;
+        }
+        write!(self.out, ", ")?;
+        self.put_expression(value, &context.expression, true)?;
+        writeln!(self.out, ");")?;
+
+        Ok(())
+    }
+
     /// Write the maximum valid index of the dynamically sized array at the end of `handle`.
     ///
     /// The 'maximum valid index' is simply one less than the array's length.
@@ -1495,7 +1602,13 @@ impl<W: Write> Writer<W> {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => {
+                if residency {
+                    return Err(Error::FeatureNotImplemented(
+                        "sampling with residency feedback".to_string(),
+                    ));
+                }
                 let main_op = match gather {
                     Some(_) => "gather",
                     None => "sample",
@@ -2219,6 +2332,7 @@ impl<W: Write> Writer<W> {
                     self.out.write_str(") < ")?;
                     match length {
                         index::IndexableLength::Known(value) => write!(self.out, "{value}")?,
+                        index::IndexableLength::Pending => return Err(Error::Override),
                         index::IndexableLength::Dynamic => {
                             let global =
                                 context.function.originating_global(base).ok_or_else(|| {
@@ -2381,6 +2495,7 @@ impl<W: Write> Writer<W> {
                 index::IndexableLength::Known(limit) => {
                     write!(self.out, "{}u", limit - 1)?;
                 }
+                index::IndexableLength::Pending => return Err(Error::Override),
                 index::IndexableLength::Dynamic => {
                     let global = context.function.originating_global(base).ok_or_else(|| {
                         Error::GenericValidation("Could not find originating global".into())
@@ -2723,7 +2838,7 @@ impl<W: Write> Writer<W> {
     fn put_block(
         &mut self,
         level: back::Level,
-        statements: &[crate::Statement],
+        statements: &crate::Block,
         context: &StatementContext,
     ) -> BackendResult {
         // Add to the set in order to track the stack size.
@@ -2732,8 +2847,11 @@ impl<W: Write> Writer<W> {
         self.put_block_stack_pointers
             .insert(&level as *const _ as *const ());
 
-        for statement in statements {
+        for (statement, span) in statements.span_iter() {
             log::trace!("statement[{}] {:?}", level.0, statement);
+            if let Some(debug_info) = context.debug_info {
+                debug_info.write_line_directive(&mut self.out, *span)?;
+            }
             match *statement {
                 crate::Statement::Emit(ref range) => {
                     for handle in range.clone() {
@@ -2926,6 +3044,24 @@ impl<W: Write> Writer<W> {
                     };
                     self.put_image_store(level, image, &address, value, context)?
                 }
+                crate::Statement::ImageAtomic {
+                    image,
+                    coordinate,
+                    array_index,
+                    fun,
+                    value,
+                } => {
+                    if context.expression.lang_version < (3, 1) {
+                        return Err(Error::UnsupportedFunction("texture atomics".to_string()));
+                    }
+                    let address = TexelAddress {
+                        coordinate,
+                        array_index,
+                        sample: None,
+                        level: None,
+                    };
+                    self.put_image_atomic(level, image, &address, fun, value, context)?
+                }
                 crate::Statement::Call {
                     function,
                     ref arguments,
@@ -3003,6 +3139,14 @@ impl<W: Write> Writer<W> {
                     writeln!(self.out, ";")?;
                     self.write_barrier(crate::Barrier::WORK_GROUP, level)?;
                 }
+                crate::Statement::RayPipelineFunction(_) => {
+                    return Err(Error::FeatureNotImplemented(
+                        "ray tracing pipeline functions".to_string(),
+                    ));
+                }
+                crate::Statement::MeshFunction(ref fun) => {
+                    self.put_mesh_function(fun, level, context)?;
+                }
                 crate::Statement::RayQuery { query, ref fun } => {
                     if context.expression.lang_version < (2, 4) {
                         return Err(Error::UnsupportedRayTracing);
@@ -3114,6 +3258,55 @@ impl<W: Write> Writer<W> {
                     }
                     writeln!(self.out, "), 0, 0, 0);")?;
                 }
+                crate::Statement::SubgroupCollectiveOperation {
+                    op,
+                    collective_op: crate::CollectiveOperation::ClusteredReduce(cluster_size),
+                    argument,
+                    result,
+                } => {
+                    // MSL has no clustered SIMD-group reductions, so reduce
+                    // within each cluster with a butterfly of shuffles instead.
+                    write!(self.out, "{level}")?;
+                    let name = self.namer.call("");
+                    self.start_baking_expression(result, &context.expression, &name)?;
+                    self.put_expression(argument, &context.expression, true)?;
+                    writeln!(self.out, ";")?;
+
+                    let mut lane_mask = 1;
+                    while lane_mask < cluster_size {
+                        let other = format!("{NAMESPACE}::simd_shuffle_xor({name}, {lane_mask}u)");
+                        match op {
+                            crate::SubgroupOperation::Add => {
+                                writeln!(self.out, "{level}{name} = {name} + {other};")?
+                            }
+                            crate::SubgroupOperation::Mul => {
+                                writeln!(self.out, "{level}{name} = {name} * {other};")?
+                            }
+                            crate::SubgroupOperation::Max => writeln!(
+                                self.out,
+                                "{level}{name} = {NAMESPACE}::max({name}, {other});"
+                            )?,
+                            crate::SubgroupOperation::Min => writeln!(
+                                self.out,
+                                "{level}{name} = {NAMESPACE}::min({name}, {other});"
+                            )?,
+                            crate::SubgroupOperation::And => {
+                                writeln!(self.out, "{level}{name} = {name} & {other};")?
+                            }
+                            crate::SubgroupOperation::Or => {
+                                writeln!(self.out, "{level}{name} = {name} | {other};")?
+                            }
+                            crate::SubgroupOperation::Xor => {
+                                writeln!(self.out, "{level}{name} = {name} ^ {other};")?
+                            }
+                            crate::SubgroupOperation::All | crate::SubgroupOperation::Any => {
+                                unreachable!()
+                            }
+                        }
+                        lane_mask <<= 1;
+                    }
+                    self.named_expressions.insert(result, name);
+                }
                 crate::Statement::SubgroupCollectiveOperation {
                     op,
                     collective_op,
@@ -3201,6 +3394,12 @@ impl<W: Write> Writer<W> {
                         crate::GatherMode::ShuffleXor(_) => {
                             write!(self.out, "{NAMESPACE}::simd_shuffle_xor(")?;
                         }
+                        crate::GatherMode::QuadBroadcast(_) => {
+                            write!(self.out, "{NAMESPACE}::quad_broadcast(")?;
+                        }
+                        crate::GatherMode::QuadSwap(_) => {
+                            write!(self.out, "{NAMESPACE}::quad_shuffle_xor(")?;
+                        }
                     }
                     self.put_expression(argument, &context.expression, true)?;
                     match mode {
@@ -3209,10 +3408,21 @@ impl<W: Write> Writer<W> {
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             write!(self.out, ", ")?;
                             self.put_expression(index, &context.expression, true)?;
                         }
+                        crate::GatherMode::QuadSwap(direction) => {
+                            // Quad lanes are laid out row-major, so swapping
+                            // across X, Y or the diagonal flips bit 0, 1 or both.
+                            let lane_mask = match direction {
+                                crate::Direction::X => 1,
+                                crate::Direction::Y => 2,
+                                crate::Direction::Diagonal => 3,
+                            };
+                            write!(self.out, ", {lane_mask}u")?;
+                        }
                     }
                     writeln!(self.out, ");")?;
                 }
@@ -3295,6 +3505,12 @@ impl<W: Write> Writer<W> {
         if !module.overrides.is_empty() {
             return Err(Error::Override);
         }
+        if let Some(ep) = module.entry_points.iter().find(|ep| match ep.stage {
+            crate::ShaderStage::Task | crate::ShaderStage::Mesh => options.lang_version < (3, 0),
+            stage => stage.is_ray_tracing(),
+        }) {
+            return Err(Error::UnsupportedShaderStage(ep.stage));
+        }
 
         self.names.clear();
         self.namer.reset(
@@ -3478,6 +3694,7 @@ impl<W: Write> Writer<W> {
                             )?;
                             writeln!(self.out, "}};")?;
                         }
+                        crate::ArraySize::Pending(_) => return Err(Error::Override),
                         crate::ArraySize::Dynamic => {
                             writeln!(self.out, "typedef {base_name} {name}[1];")?;
                         }
@@ -3601,7 +3818,8 @@ impl<W: Write> Writer<W> {
                         struct_name, struct_name
                     )?;
                 }
-                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. } => {}
+                &crate::PredeclaredType::AtomicCompareExchangeWeakResult { .. }
+                | &crate::PredeclaredType::SparseSampleResult => {}
             }
         }
 
@@ -3826,6 +4044,8 @@ impl<W: Write> Writer<W> {
                     pipeline_options,
                 },
                 result_struct: None,
+                mesh_stage: None,
+                debug_info: options.debug_info.as_ref(),
             };
 
             for (local_handle, local) in fun.local_variables.iter() {
@@ -3931,7 +4151,11 @@ impl<W: Write> Writer<W> {
                         }
                         crate::AddressSpace::Function
                         | crate::AddressSpace::Private
-                        | crate::AddressSpace::WorkGroup => {}
+                        | crate::AddressSpace::WorkGroup
+                        | crate::AddressSpace::RayPayload
+                        | crate::AddressSpace::IncomingRayPayload
+                        | crate::AddressSpace::HitAttribute
+                        | crate::AddressSpace::TaskPayload => {}
                     }
                 }
                 if supports_array_length {
@@ -3945,7 +4169,7 @@ impl<W: Write> Writer<W> {
                 info.entry_point_names.push(Err(err));
                 continue;
             }
-            let fun_name = &self.names[&NameKey::EntryPoint(ep_index as _)];
+            let fun_name = self.names[&NameKey::EntryPoint(ep_index as _)].clone();
             info.entry_point_names.push(Ok(fun_name.clone()));
 
             writeln!(self.out)?;
@@ -3962,8 +4186,22 @@ impl<W: Write> Writer<W> {
                     LocationMode::FragmentOutput,
                 ),
                 crate::ShaderStage::Compute { .. } => {
+                    if fun_info.uses_derivatives {
+                        return Err(Error::FeatureNotImplemented(
+                            "derivatives in compute shaders".to_string(),
+                        ));
+                    }
                     ("kernel", LocationMode::Uniform, LocationMode::Uniform)
                 }
+                crate::ShaderStage::Task => {
+                    ("object", LocationMode::Uniform, LocationMode::Uniform)
+                }
+                crate::ShaderStage::Mesh => ("mesh", LocationMode::Uniform, LocationMode::Uniform),
+                crate::ShaderStage::RayGeneration
+                | crate::ShaderStage::Intersection
+                | crate::ShaderStage::AnyHit
+                | crate::ShaderStage::ClosestHit
+                | crate::ShaderStage::Miss => unreachable!(),
             };
 
             // Since `Namer.reset` wasn't expecting struct members to be
@@ -4121,6 +4359,37 @@ impl<W: Write> Writer<W> {
                 None => "void",
             };
 
+            // Object and mesh shaders write their outputs through a
+            // `metal::mesh_grid_properties` or `metal::mesh` argument instead.
+            let mesh_stage = match ep.stage {
+                crate::ShaderStage::Task => Some(MeshStageNames::Object {
+                    grid: self.namer.call("mesh_grid"),
+                }),
+                crate::ShaderStage::Mesh => {
+                    let mesh_info = ep.mesh_info.as_ref().unwrap();
+                    let vertex_struct = format!("{fun_name}Vertex");
+                    self.write_mesh_output_struct(
+                        module,
+                        options,
+                        &vertex_struct,
+                        mesh_info.vertex_output,
+                    )?;
+                    let primitive_struct = format!("{fun_name}Primitive");
+                    let has_primitive_struct = self.write_mesh_output_struct(
+                        module,
+                        options,
+                        &primitive_struct,
+                        mesh_info.primitive_output,
+                    )?;
+                    Some(MeshStageNames::Mesh {
+                        output: self.namer.call("mesh_output"),
+                        vertex_struct,
+                        primitive_struct: has_primitive_struct.then_some(primitive_struct),
+                    })
+                }
+                _ => None,
+            };
+
             // Write the entry point function's name, and begin its argument list.
             writeln!(self.out, "{em_str} {result_type_name} {fun_name}(")?;
             let mut is_first_argument = true;
@@ -4173,6 +4442,39 @@ impl<W: Write> Writer<W> {
                 writeln!(self.out)?;
             }
 
+            match mesh_stage {
+                Some(MeshStageNames::Object { ref grid }) => {
+                    let separator = separate(!is_first_argument);
+                    is_first_argument = false;
+                    writeln!(
+                        self.out,
+                        "{separator} {NAMESPACE}::mesh_grid_properties {grid}"
+                    )?;
+                }
+                Some(MeshStageNames::Mesh {
+                    ref output,
+                    ref vertex_struct,
+                    ref primitive_struct,
+                }) => {
+                    let mesh_info = ep.mesh_info.as_ref().unwrap();
+                    let topology = match mesh_info.topology {
+                        crate::MeshOutputTopology::Points => "point",
+                        crate::MeshOutputTopology::Lines => "line",
+                        crate::MeshOutputTopology::Triangles => "triangle",
+                    };
+                    let separator = separate(!is_first_argument);
+                    is_first_argument = false;
+                    writeln!(
+                        self.out,
+                        "{separator} {NAMESPACE}::mesh<{vertex_struct}, {}, {}, {}, {NAMESPACE}::topology::{topology}> {output}",
+                        primitive_struct.as_deref().unwrap_or("void"),
+                        mesh_info.max_vertices,
+                        mesh_info.max_primitives,
+                    )?;
+                }
+                None => {}
+            }
+
             let need_workgroup_variables_initialization =
                 self.need_workgroup_variables_initialization(options, ep, module, fun_info);
 
@@ -4309,13 +4611,19 @@ impl<W: Write> Writer<W> {
                         }
                         _ => {}
                     },
+                    // Arrays of buffers would need argument buffers.
+                    crate::AddressSpace::Uniform | crate::AddressSpace::Storage { .. } => {
+                        if let crate::TypeInner::BindingArray { .. } = module.types[var.ty].inner {
+                            return Err(Error::UnsupportedArrayOf("buffers".to_string()));
+                        }
+                    }
                     _ => {}
                 }
 
                 // the resolves have already been checked for `!fake_missing_bindings` case
                 let resolved = match var.space {
                     crate::AddressSpace::PushConstant => options.resolve_push_constants(ep).ok(),
-                    crate::AddressSpace::WorkGroup => None,
+                    crate::AddressSpace::WorkGroup | crate::AddressSpace::TaskPayload => None,
                     _ => options
                         .resolve_resource_binding(ep, var.binding.as_ref().unwrap())
                         .ok(),
@@ -4346,6 +4654,9 @@ impl<W: Write> Writer<W> {
                 if let Some(resolved) = resolved {
                     resolved.try_fmt(&mut self.out)?;
                 }
+                if var.space == crate::AddressSpace::TaskPayload {
+                    write!(self.out, " [[payload]]")?;
+                }
                 if let Some(value) = var.init {
                     write!(self.out, " = ")?;
                     self.put_const_expression(value, module, mod_info)?;
@@ -4503,6 +4814,8 @@ impl<W: Write> Writer<W> {
                     pipeline_options,
                 },
                 result_struct: Some(&stage_out_name),
+                mesh_stage: mesh_stage.as_ref(),
+                debug_info: options.debug_info.as_ref(),
             };
 
             // Finally, declare all the local variables that we need
@@ -4542,6 +4855,183 @@ impl<W: Write> Writer<W> {
         Ok(info)
     }
 
+    /// Write the attributed struct `name` that a mesh shader passes to
+    /// `set_vertex` or `set_primitive` for the output struct `ty`.
+    ///
+    /// Primitive indices are written with `set_index` instead, so if they are
+    /// the only members no struct is written and this returns `false`.
+    fn write_mesh_output_struct(
+        &mut self,
+        module: &crate::Module,
+        options: &Options,
+        name: &str,
+        ty: Handle<crate::Type>,
+    ) -> Result<bool, Error> {
+        let members = match module.types[ty].inner {
+            crate::TypeInner::Struct { ref members, .. } => members,
+            _ => unreachable!("mesh outputs must be structs"),
+        };
+        let members: Vec<_> = members
+            .iter()
+            .enumerate()
+            .filter(|&(_, member)| !is_mesh_primitive_indices(member.binding.as_ref()))
+            .collect();
+        if members.is_empty() {
+            return Ok(false);
+        }
+
+        writeln!(self.out, "struct {name} {{")?;
+        for (member_index, member) in members {
+            let ty_name = TypeContext {
+                handle: member.ty,
+                gctx: module.to_ctx(),
+                names: &self.names,
+                access: crate::StorageAccess::empty(),
+                binding: None,
+                first_time: true,
+            };
+            let member_name = &self.names[&NameKey::StructMember(ty, member_index as u32)];
+            let binding = member.binding.as_ref().unwrap();
+            let resolved = options.resolve_local_binding(binding, LocationMode::VertexOutput)?;
+            write!(self.out, "{}{} {}", back::INDENT, ty_name, member_name)?;
+            if let crate::TypeInner::Array {
+                size: crate::ArraySize::Constant(size),
+                ..
+            } = module.types[member.ty].inner
+            {
+                write!(self.out, " [{size}]")?;
+            }
+            resolved.try_fmt(&mut self.out)?;
+            writeln!(self.out, ";")?;
+        }
+        writeln!(self.out, "}};")?;
+        Ok(true)
+    }
+
+    fn put_mesh_function(
+        &mut self,
+        function: &crate::MeshFunction,
+        level: back::Level,
+        context: &StatementContext,
+    ) -> BackendResult {
+        let module = context.expression.module;
+        match (function, context.mesh_stage) {
+            (
+                &crate::MeshFunction::EmitMeshTasks { x, y, z },
+                Some(&MeshStageNames::Object { ref grid }),
+            ) => {
+                write!(
+                    self.out,
+                    "{level}{grid}.set_threadgroups_per_grid({NAMESPACE}::uint3("
+                )?;
+                self.put_expression(x, &context.expression, true)?;
+                write!(self.out, ", ")?;
+                self.put_expression(y, &context.expression, true)?;
+                write!(self.out, ", ")?;
+                self.put_expression(z, &context.expression, true)?;
+                writeln!(self.out, "));")?;
+                writeln!(self.out, "{level}return;")?;
+            }
+            (
+                &crate::MeshFunction::SetMeshOutputs {
+                    primitive_count, ..
+                },
+                Some(&MeshStageNames::Mesh { ref output, .. }),
+            ) => {
+                // Metal only bounds the vertex count by the `metal::mesh` type.
+                write!(self.out, "{level}{output}.set_primitive_count(")?;
+                self.put_expression(primitive_count, &context.expression, true)?;
+                writeln!(self.out, ");")?;
+            }
+            (
+                &crate::MeshFunction::SetVertex { index, value }
+                | &crate::MeshFunction::SetPrimitive { index, value },
+                Some(&MeshStageNames::Mesh {
+                    ref output,
+                    ref vertex_struct,
+                    ref primitive_struct,
+                }),
+            ) => {
+                let mesh_info = match context.expression.origin {
+                    FunctionOrigin::EntryPoint(ep_index) => module.entry_points[ep_index as usize]
+                        .mesh_info
+                        .as_ref()
+                        .unwrap(),
+                    FunctionOrigin::Handle(_) => unreachable!(),
+                };
+                let (ty, setter, struct_name) = match *function {
+                    crate::MeshFunction::SetVertex { .. } => {
+                        (mesh_info.vertex_output, "set_vertex", Some(vertex_struct))
+                    }
+                    _ => (
+                        mesh_info.primitive_output,
+                        "set_primitive",
+                        primitive_struct.as_ref(),
+                    ),
+                };
+                let members = match module.types[ty].inner {
+                    crate::TypeInner::Struct { ref members, .. } => members,
+                    _ => unreachable!("mesh outputs must be structs"),
+                };
+
+                let index_name = self.namer.call("mesh_index");
+                let value_name = self.namer.call("mesh_value");
+                let inner = level.next();
+                writeln!(self.out, "{level}{{")?;
+                write!(self.out, "{inner}const uint {index_name} = ")?;
+                self.put_expression(index, &context.expression, true)?;
+                writeln!(self.out, ";")?;
+                write!(self.out, "{inner}const auto {value_name} = ")?;
+                self.put_expression(value, &context.expression, true)?;
+                writeln!(self.out, ";")?;
+
+                if let Some(struct_name) = struct_name {
+                    write!(
+                        self.out,
+                        "{inner}{output}.{setter}({index_name}, {struct_name} {{ "
+                    )?;
+                    let mut is_first = true;
+                    for (member_index, member) in members.iter().enumerate() {
+                        if is_mesh_primitive_indices(member.binding.as_ref()) {
+                            continue;
+                        }
+                        let member_name =
+                            &self.names[&NameKey::StructMember(ty, member_index as u32)];
+                        write!(
+                            self.out,
+                            "{}{value_name}.{member_name}",
+                            if is_first { "" } else { ", " }
+                        )?;
+                        is_first = false;
+                    }
+                    writeln!(self.out, " }});")?;
+                }
+
+                for (member_index, member) in members.iter().enumerate() {
+                    let components: &[&str] = match member.binding {
+                        Some(crate::Binding::BuiltIn(crate::BuiltIn::TriangleIndices)) => {
+                            &[".x", ".y", ".z"]
+                        }
+                        Some(crate::Binding::BuiltIn(crate::BuiltIn::LineIndices)) => &[".x", ".y"],
+                        Some(crate::Binding::BuiltIn(crate::BuiltIn::PointIndex)) => &[""],
+                        _ => continue,
+                    };
+                    let member_name = &self.names[&NameKey::StructMember(ty, member_index as u32)];
+                    for (component_index, component) in components.iter().enumerate() {
+                        writeln!(
+                            self.out,
+                            "{inner}{output}.set_index({index_name} * {}u + {component_index}u, {value_name}.{member_name}{component});",
+                            components.len(),
+                        )?;
+                    }
+                }
+                writeln!(self.out, "{level}}}")?;
+            }
+            _ => unreachable!("mesh shading statement outside of its stage"),
+        }
+        Ok(())
+    }
+
     fn write_barrier(&mut self, flags: crate::Barrier, level: back::Level) -> BackendResult {
         // Note: OR-ring bitflags requires `__HAVE_MEMFLAG_OPERATORS__`,
         // so we try to avoid it here.
@@ -4655,7 +5145,7 @@ mod workgroup_mem_init {
             fun_info: &valid::FunctionInfo,
         ) -> bool {
             options.zero_initialize_workgroup_memory
-                && ep.stage == crate::ShaderStage::Compute
+                && ep.stage.uses_workgroups()
                 && module.global_variables.iter().any(|(handle, var)| {
                     !fun_info[handle].is_empty() && var.space == crate::AddressSpace::WorkGroup
                 })
@@ -4729,6 +5219,7 @@ mod workgroup_mem_init {
                         let count = match size.to_indexable_length(module).expect("Bad array size")
                         {
                             proc::IndexableLength::Known(count) => count,
+                            proc::IndexableLength::Pending => return Err(Error::Override),
                             proc::IndexableLength::Dynamic => unreachable!(),
                         };
 
@@ -4773,6 +5264,34 @@ mod workgroup_mem_init {
     }
 }
 
+#[cfg(feature = "wgsl-in")]
+#[test]
+fn test_line_directives() {
+    use crate::valid::{Capabilities, ValidationFlags};
+
+    let source = "\
+@compute @workgroup_size(1)
+fn main() {
+    var x = 1;
+
+    x += 2;
+}
+";
+    let module = crate::front::wgsl::parse_str(source).unwrap();
+    let info = valid::Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .unwrap();
+    let options = Options {
+        debug_info: Some(back::DebugInfo {
+            file_name: r#"shaders\"main".wgsl"#.to_string(),
+            source_code: source.to_string(),
+        }),
+        ..Default::default()
+    };
+    let (msl, _) = super::write_string(&module, &info, &options, &Default::default()).unwrap();
+    assert!(msl.contains(r#"#line 5 "shaders\\\"main\".wgsl""#), "{msl}");
+}
+
 #[test]
 fn test_stack_size() {
     use crate::valid::{Capabilities, ValidationFlags};
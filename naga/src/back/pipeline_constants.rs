@@ -2,10 +2,10 @@ use super::PipelineConstants;
 use crate::{
     proc::{ConstantEvaluator, ConstantEvaluatorError, Emitter},
     valid::{Capabilities, ModuleInfo, ValidationError, ValidationFlags, Validator},
-    Arena, Block, Constant, Expression, Function, Handle, Literal, Module, Override, Range, Scalar,
-    Span, Statement, TypeInner, WithSpan,
+    Arena, ArraySize, Block, Constant, Expression, Function, Handle, Literal, Module, Override,
+    Range, Scalar, Span, Statement, Type, TypeInner, WithSpan,
 };
-use std::{borrow::Cow, collections::HashSet, mem};
+use std::{borrow::Cow, collections::HashSet, mem, num::NonZeroU32};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
@@ -17,6 +17,10 @@ pub enum PipelineConstantError {
     SrcNeedsToBeFinite,
     #[error("Source f64 value doesn't fit in destination")]
     DstRangeTooSmall,
+    #[error("Array length override '{0}' must be positive")]
+    ArrayLengthNotPositive(String),
+    #[error("Workgroup size of entry point '{0}' must be a non-negative integer")]
+    NegativeWorkgroupSize(String),
     #[error(transparent)]
     ConstantEvaluatorError(#[from] ConstantEvaluatorError),
     #[error(transparent)]
@@ -178,6 +182,8 @@ pub fn process_overrides<'a>(
         }
     }
 
+    process_pending_arrays(&mut module, &override_map)?;
+
     let mut functions = mem::take(&mut module.functions);
     for (_, function) in functions.iter_mut() {
         process_function(&mut module, &override_map, function)?;
@@ -186,6 +192,7 @@ pub fn process_overrides<'a>(
 
     let mut entry_points = mem::take(&mut module.entry_points);
     for ep in entry_points.iter_mut() {
+        process_workgroup_size_overrides(&module, &adjusted_global_expressions, ep)?;
         process_function(&mut module, &override_map, &mut ep.function)?;
     }
     module.entry_points = entry_points;
@@ -252,6 +259,82 @@ fn process_override(
     Ok(h)
 }
 
+/// Replace all override-sized arrays in `module.types` with fixed-size ones.
+///
+/// The replacement type takes over the handle of the original, so nothing
+/// referring to it needs to be adjusted.
+fn process_pending_arrays(
+    module: &mut Module,
+    override_map: &[Handle<Constant>],
+) -> Result<(), PipelineConstantError> {
+    let pending: Vec<_> = module
+        .types
+        .iter()
+        .filter_map(|(handle, ty)| match ty.inner {
+            TypeInner::Array {
+                base,
+                size: ArraySize::Pending(o_h),
+                stride,
+            } => Some((handle, base, o_h, stride)),
+            _ => None,
+        })
+        .collect();
+
+    for (handle, base, o_h, stride) in pending {
+        let constant = &module.constants[override_map[o_h.index()]];
+        let length = module
+            .to_ctx()
+            .eval_expr_to_u32(constant.init)
+            .ok()
+            .and_then(NonZeroU32::new)
+            .ok_or_else(|| {
+                PipelineConstantError::ArrayLengthNotPositive(
+                    constant.name.clone().unwrap_or_default(),
+                )
+            })?;
+        let inner = TypeInner::Array {
+            base,
+            size: ArraySize::Constant(length),
+            stride,
+        };
+        // `UniqueArena::replace` requires the new type to be unique. If an
+        // identical fixed-size array already exists, name the new type after
+        // the override to keep the two apart.
+        let mut ty = Type { name: None, inner };
+        if module.types.get(&ty).is_some() {
+            ty.name = constant
+                .name
+                .clone()
+                .or_else(|| Some(format!("array_{length}")));
+        }
+        module.types.replace(handle, ty);
+    }
+
+    Ok(())
+}
+
+/// Fill in the workgroup size dimensions that `ep` takes from overrides.
+fn process_workgroup_size_overrides(
+    module: &Module,
+    adjusted_global_expressions: &[Handle<Expression>],
+    ep: &mut crate::EntryPoint,
+) -> Result<(), PipelineConstantError> {
+    let Some(overrides) = ep.workgroup_size_overrides.take() else {
+        return Ok(());
+    };
+
+    for (i, expr) in overrides.into_iter().enumerate() {
+        if let Some(expr) = expr {
+            ep.workgroup_size[i] = module
+                .to_ctx()
+                .eval_expr_to_u32(adjusted_global_expressions[expr.index()])
+                .map_err(|_| PipelineConstantError::NegativeWorkgroupSize(ep.name.clone()))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Replace all override expressions in `function` with fully-evaluated constants.
 ///
 /// Replace all `Expression::Override`s in `function`'s expression arena with
@@ -378,6 +461,7 @@ fn adjust_expr(new_pos: &[Handle<Expression>], expr: &mut Expression) {
             ref mut level,
             ref mut depth_ref,
             gather: _,
+            residency: _,
         } => {
             adjust(image);
             adjust(sampler);
@@ -609,6 +693,26 @@ fn adjust_stmt(new_pos: &[Handle<Expression>], stmt: &mut Statement) {
             }
             adjust(value);
         }
+        Statement::ImageAtomic {
+            ref mut image,
+            ref mut coordinate,
+            ref mut array_index,
+            ref mut fun,
+            ref mut value,
+        } => {
+            adjust(image);
+            adjust(coordinate);
+            if let Some(e) = array_index.as_mut() {
+                adjust(e);
+            }
+            if let crate::AtomicFunction::Exchange {
+                compare: Some(ref mut compare),
+            } = *fun
+            {
+                adjust(compare);
+            }
+            adjust(value);
+        }
         Statement::Atomic {
             ref mut pointer,
             ref mut value,
@@ -664,12 +768,13 @@ fn adjust_stmt(new_pos: &[Handle<Expression>], stmt: &mut Statement) {
             ref mut result,
         } => {
             match *mode {
-                crate::GatherMode::BroadcastFirst => {}
+                crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                 crate::GatherMode::Broadcast(ref mut index)
                 | crate::GatherMode::Shuffle(ref mut index)
                 | crate::GatherMode::ShuffleDown(ref mut index)
                 | crate::GatherMode::ShuffleUp(ref mut index)
-                | crate::GatherMode::ShuffleXor(ref mut index) => {
+                | crate::GatherMode::ShuffleXor(ref mut index)
+                | crate::GatherMode::QuadBroadcast(ref mut index) => {
                     adjust(index);
                 }
             }
@@ -707,6 +812,61 @@ fn adjust_stmt(new_pos: &[Handle<Expression>], stmt: &mut Statement) {
                 crate::RayQueryFunction::Terminate => {}
             }
         }
+        Statement::RayPipelineFunction(ref mut fun) => match *fun {
+            crate::RayPipelineFunction::TraceRay {
+                ref mut acceleration_structure,
+                ref mut descriptor,
+                ref mut sbt_record_offset,
+                ref mut sbt_record_stride,
+                ref mut miss_index,
+                ref mut payload,
+            } => {
+                adjust(acceleration_structure);
+                adjust(descriptor);
+                adjust(sbt_record_offset);
+                adjust(sbt_record_stride);
+                adjust(miss_index);
+                adjust(payload);
+            }
+            crate::RayPipelineFunction::ReportIntersection {
+                ref mut hit_t,
+                ref mut hit_kind,
+            } => {
+                adjust(hit_t);
+                adjust(hit_kind);
+            }
+            crate::RayPipelineFunction::IgnoreIntersection
+            | crate::RayPipelineFunction::TerminateRay => {}
+        },
+        Statement::MeshFunction(ref mut fun) => match *fun {
+            crate::MeshFunction::EmitMeshTasks {
+                ref mut x,
+                ref mut y,
+                ref mut z,
+            } => {
+                adjust(x);
+                adjust(y);
+                adjust(z);
+            }
+            crate::MeshFunction::SetMeshOutputs {
+                ref mut vertex_count,
+                ref mut primitive_count,
+            } => {
+                adjust(vertex_count);
+                adjust(primitive_count);
+            }
+            crate::MeshFunction::SetVertex {
+                ref mut index,
+                ref mut value,
+            }
+            | crate::MeshFunction::SetPrimitive {
+                ref mut index,
+                ref mut value,
+            } => {
+                adjust(index);
+                adjust(value);
+            }
+        },
         Statement::Break | Statement::Continue | Statement::Kill | Statement::Barrier(_) => {}
     }
 }
@@ -861,6 +1021,54 @@ fn map_value_to_literal(value: f64, scalar: Scalar) -> Result<Literal, PipelineC
     }
 }
 
+#[cfg(feature = "wgsl-in")]
+#[test]
+fn test_process_override_sizes() {
+    let module = crate::front::wgsl::parse_str(
+        "
+        override block_size: u32 = 64;
+        override rows: i32;
+
+        var<workgroup> tile: array<f32, block_size>;
+        var<workgroup> fixed: array<f32, 16>;
+
+        @compute @workgroup_size(block_size, rows - 1, 2)
+        fn main() {
+            tile[0] = fixed[0];
+        }
+        ",
+    )
+    .unwrap();
+    let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .unwrap();
+
+    let constants = [("block_size".to_string(), 16.0), ("rows".to_string(), 5.0)]
+        .into_iter()
+        .collect();
+    let (processed, _) = process_overrides(&module, &info, &constants).unwrap();
+    assert_eq!(processed.entry_points[0].workgroup_size, [16, 4, 2]);
+    assert!(processed.entry_points[0].workgroup_size_overrides.is_none());
+
+    // `tile` now has the same type as `fixed`, apart from its name.
+    let (_, tile) = processed.global_variables.iter().next().unwrap();
+    assert!(matches!(
+        processed.types[tile.ty].inner,
+        TypeInner::Array {
+            size: ArraySize::Constant(size),
+            ..
+        } if size.get() == 16
+    ));
+
+    let constants = [("block_size".to_string(), 0.0), ("rows".to_string(), 5.0)]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        process_overrides(&module, &info, &constants).unwrap_err(),
+        PipelineConstantError::ArrayLengthNotPositive("block_size".to_string())
+    );
+}
+
 #[test]
 fn test_map_value_to_literal() {
     let bool_test_cases = [
@@ -1465,6 +1465,7 @@ impl<'w> BlockContext<'w> {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => self.write_image_sample(
                 result_type_id,
                 image,
@@ -1475,6 +1476,7 @@ impl<'w> BlockContext<'w> {
                 offset,
                 level,
                 depth_ref,
+                residency,
                 block,
             )?,
             crate::Expression::Select {
@@ -1586,6 +1588,44 @@ impl<'w> BlockContext<'w> {
         Ok(())
     }
 
+    /// Return the id of a pointer to the image the expression `image` refers to.
+    ///
+    /// Atomic operations need the image variable itself rather than the
+    /// loaded image value that [`get_handle_id`] returns.
+    ///
+    /// [`get_handle_id`]: BlockContext::get_handle_id
+    pub(super) fn get_image_pointer_id(
+        &mut self,
+        image: Handle<crate::Expression>,
+        block: &mut Block,
+    ) -> Result<Word, Error> {
+        match self.ir_function.expressions[image] {
+            crate::Expression::GlobalVariable(handle) => {
+                Ok(self.writer.global_variables[handle.index()].var_id)
+            }
+            crate::Expression::Access { base, .. }
+            | crate::Expression::AccessIndex { base, .. } => {
+                let binding_type = match *self.fun_info[base].ty.inner_with(&self.ir_module.types) {
+                    crate::TypeInner::BindingArray { base, .. } => base,
+                    _ => unreachable!(),
+                };
+                let pointer_type = LookupType::Local(LocalType::Pointer {
+                    base: binding_type,
+                    class: spirv::StorageClass::UniformConstant,
+                });
+                match self.write_expression_pointer(image, block, Some(pointer_type))? {
+                    ExpressionPointer::Ready { pointer_id } => Ok(pointer_id),
+                    ExpressionPointer::Conditional { .. } => Err(Error::FeatureNotImplemented(
+                        "Texture array out-of-bounds handling",
+                    )),
+                }
+            }
+            _ => Err(Error::FeatureNotImplemented(
+                "image atomics on function arguments",
+            )),
+        }
+    }
+
     /// Build an `OpAccessChain` instruction.
     ///
     /// Emit any needed bounds-checking expressions to `block`.
@@ -1890,6 +1930,68 @@ impl<'w> BlockContext<'w> {
         }
     }
 
+    /// Write the mesh shader output statements other than `EmitMeshTasks`,
+    /// which terminates its block.
+    fn write_mesh_function(
+        &mut self,
+        function: &crate::MeshFunction,
+        block: &mut Block,
+    ) -> Result<(), Error> {
+        let (index, value, primitive) = match *function {
+            crate::MeshFunction::SetMeshOutputs {
+                vertex_count,
+                primitive_count,
+            } => {
+                block.body.push(Instruction::set_mesh_outputs(
+                    self.cached[vertex_count],
+                    self.cached[primitive_count],
+                ));
+                return Ok(());
+            }
+            crate::MeshFunction::SetVertex { index, value } => (index, value, false),
+            crate::MeshFunction::SetPrimitive { index, value } => (index, value, true),
+            crate::MeshFunction::EmitMeshTasks { .. } => unreachable!(),
+        };
+
+        // Scatter the members of the output struct into the per-member
+        // output arrays.
+        let context = self.function.entry_point_context.as_ref().unwrap();
+        let outputs: Vec<_> = match primitive {
+            false => &context.mesh_vertex_outputs,
+            true => &context.mesh_primitive_outputs,
+        }
+        .iter()
+        .map(|output| (output.id, output.ty))
+        .collect();
+
+        let index_id = self.cached[index];
+        let value_id = self.cached[value];
+        for (member_index, (variable_id, ty)) in outputs.into_iter().enumerate() {
+            let member_type_id = self.get_type_id(LookupType::Handle(ty));
+            let member_id = self.gen_id();
+            block.body.push(Instruction::composite_extract(
+                member_type_id,
+                member_id,
+                value_id,
+                &[member_index as u32],
+            ));
+
+            let pointer_type_id = self.get_pointer_id(ty, spirv::StorageClass::Output)?;
+            let pointer_id = self.gen_id();
+            block.body.push(Instruction::access_chain(
+                pointer_type_id,
+                pointer_id,
+                variable_id,
+                &[index_id],
+            ));
+            block
+                .body
+                .push(Instruction::store(pointer_id, member_id, None));
+        }
+
+        Ok(())
+    }
+
     pub(super) fn write_block(
         &mut self,
         label_id: Word,
@@ -1908,6 +2010,11 @@ impl<'w> BlockContext<'w> {
                         | Statement::Break
                         | Statement::Continue
                         | Statement::Kill
+                        | Statement::RayPipelineFunction(
+                            crate::RayPipelineFunction::IgnoreIntersection
+                                | crate::RayPipelineFunction::TerminateRay
+                        )
+                        | Statement::MeshFunction(crate::MeshFunction::EmitMeshTasks { .. })
                         | Statement::Return { .. }
                         | Statement::Loop { .. })
                 ),
@@ -2243,6 +2350,15 @@ impl<'w> BlockContext<'w> {
                     array_index,
                     value,
                 } => self.write_image_store(image, coordinate, array_index, value, &mut block)?,
+                Statement::ImageAtomic {
+                    image,
+                    coordinate,
+                    array_index,
+                    fun,
+                    value,
+                } => {
+                    self.write_image_atomic(image, coordinate, array_index, fun, value, &mut block)?
+                }
                 Statement::Call {
                     function: local_function,
                     ref arguments,
@@ -2492,6 +2608,41 @@ impl<'w> BlockContext<'w> {
                 Statement::RayQuery { query, ref fun } => {
                     self.write_ray_query_function(query, fun, &mut block);
                 }
+                Statement::RayPipelineFunction(ref fun) => match *fun {
+                    crate::RayPipelineFunction::IgnoreIntersection => {
+                        self.function
+                            .consume(block, Instruction::ignore_intersection());
+                        return Ok(());
+                    }
+                    crate::RayPipelineFunction::TerminateRay => {
+                        self.function.consume(block, Instruction::terminate_ray());
+                        return Ok(());
+                    }
+                    _ => self.write_ray_pipeline_function(fun, &mut block),
+                },
+                Statement::MeshFunction(crate::MeshFunction::EmitMeshTasks { x, y, z }) => {
+                    // The payload operand must be the `OpVariable` itself.
+                    let payload_id = self
+                        .ir_module
+                        .global_variables
+                        .iter()
+                        .find(|&(handle, var)| {
+                            var.space == crate::AddressSpace::TaskPayload
+                                && !self.fun_info[handle].is_empty()
+                        })
+                        .map(|(handle, _)| self.writer.global_variables[handle.index()].var_id);
+                    self.function.consume(
+                        block,
+                        Instruction::emit_mesh_tasks(
+                            self.cached[x],
+                            self.cached[y],
+                            self.cached[z],
+                            payload_id,
+                        ),
+                    );
+                    return Ok(());
+                }
+                Statement::MeshFunction(ref fun) => self.write_mesh_function(fun, &mut block)?,
                 Statement::SubgroupBallot {
                     result,
                     ref predicate,
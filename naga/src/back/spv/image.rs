@@ -229,6 +229,70 @@ impl Access for Store {
     fn out_of_bounds_value(&self, _ctx: &mut BlockContext<'_>) {}
 }
 
+/// Texel access information for an [`ImageAtomic`] statement.
+///
+/// [`ImageAtomic`]: crate::Statement::ImageAtomic
+struct Atomic {
+    /// The atomic opcode to apply to the texel.
+    op: spirv::Op,
+
+    /// The id of the pointer type for a single texel in the `Image` storage class.
+    texel_pointer_type_id: Word,
+
+    /// The id of the pointer to the image variable, not the image itself.
+    image_pointer_id: Word,
+
+    /// The id of the constant sample index `0`.
+    sample_id: Word,
+
+    /// The scalar type id of the texel.
+    scalar_type_id: Word,
+
+    scope_id: Word,
+    semantics_id: Word,
+
+    /// The operand of the atomic operation.
+    value_id: Word,
+}
+
+impl Access for Atomic {
+    /// Image atomics don't produce a value in Naga IR.
+    type Output = ();
+
+    fn generate(
+        &self,
+        id_gen: &mut IdGenerator,
+        coordinates_id: Word,
+        _level_id: Option<Word>,
+        _sample_id: Option<Word>,
+        block: &mut Block,
+    ) {
+        let pointer_id = id_gen.next();
+        block.body.push(Instruction::image_texel_pointer(
+            self.texel_pointer_type_id,
+            pointer_id,
+            self.image_pointer_id,
+            coordinates_id,
+            self.sample_id,
+        ));
+        block.body.push(Instruction::atomic_binary(
+            self.op,
+            self.scalar_type_id,
+            id_gen.next(),
+            pointer_id,
+            self.scope_id,
+            self.semantics_id,
+            self.value_id,
+        ));
+    }
+
+    /// Image atomics don't produce a value, so this just returns `()`.
+    fn result_type(&self) {}
+
+    /// Image atomics don't produce a value, so this just returns `()`.
+    fn out_of_bounds_value(&self, _ctx: &mut BlockContext<'_>) {}
+}
+
 impl<'w> BlockContext<'w> {
     /// Extend image coordinates with an array index, if necessary.
     ///
@@ -822,6 +886,7 @@ impl<'w> BlockContext<'w> {
         offset: Option<Handle<crate::Expression>>,
         level: crate::SampleLevel,
         depth_ref: Option<Handle<crate::Expression>>,
+        residency: bool,
         block: &mut Block,
     ) -> Result<Word, Error> {
         use super::instructions::SampleLod;
@@ -837,7 +902,13 @@ impl<'w> BlockContext<'w> {
             } => depth_ref.is_none() && gather.is_none(),
             _ => false,
         };
-        let sample_result_type_id = if needs_sub_access {
+        let sample_result_type_id = if residency {
+            self.writer.require_any(
+                "sampling with residency feedback",
+                &[spirv::Capability::SparseResidency],
+            )?;
+            self.get_type_id(LookupType::Local(LocalType::SparseSampleResult))
+        } else if needs_sub_access {
             self.get_type_id(LookupType::Local(LocalType::Value {
                 vector_size: Some(crate::VectorSize::Quad),
                 scalar: crate::Scalar::F32,
@@ -894,6 +965,7 @@ impl<'w> BlockContext<'w> {
                     sampled_image_id,
                     coordinates_id,
                     depth_id,
+                    residency,
                 );
 
                 let zero_id = self.writer.get_constant_scalar(crate::Literal::F32(0.0));
@@ -912,6 +984,7 @@ impl<'w> BlockContext<'w> {
                     sampled_image_id,
                     coordinates_id,
                     depth_id,
+                    residency,
                 );
                 if !mask.is_empty() {
                     inst.add_operand(mask.bits());
@@ -926,6 +999,7 @@ impl<'w> BlockContext<'w> {
                     sampled_image_id,
                     coordinates_id,
                     depth_id,
+                    residency,
                 );
 
                 let lod_id = self.cached[lod_handle];
@@ -943,6 +1017,7 @@ impl<'w> BlockContext<'w> {
                     sampled_image_id,
                     coordinates_id,
                     depth_id,
+                    residency,
                 );
 
                 let bias_id = self.cached[bias_handle];
@@ -960,6 +1035,7 @@ impl<'w> BlockContext<'w> {
                     sampled_image_id,
                     coordinates_id,
                     depth_id,
+                    residency,
                 );
 
                 let x_id = self.cached[x];
@@ -980,7 +1056,53 @@ impl<'w> BlockContext<'w> {
 
         block.body.push(main_instruction);
 
-        let id = if needs_sub_access {
+        let id = if residency {
+            // Repack the residency code and texel into the IR's result struct,
+            // turning the code into a `bool` along the way.
+            let code_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+                vector_size: None,
+                scalar: crate::Scalar::I32,
+                pointer_space: None,
+            }));
+            let texel_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+                vector_size: Some(crate::VectorSize::Quad),
+                scalar: crate::Scalar::F32,
+                pointer_space: None,
+            }));
+            let bool_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+                vector_size: None,
+                scalar: crate::Scalar::BOOL,
+                pointer_space: None,
+            }));
+
+            let code_id = self.gen_id();
+            block.body.push(Instruction::composite_extract(
+                code_type_id,
+                code_id,
+                id,
+                &[0],
+            ));
+            let texel_id = self.gen_id();
+            block.body.push(Instruction::composite_extract(
+                texel_type_id,
+                texel_id,
+                id,
+                &[1],
+            ));
+            let resident_id = self.gen_id();
+            block.body.push(Instruction::image_sparse_texels_resident(
+                bool_type_id,
+                resident_id,
+                code_id,
+            ));
+            let result_id = self.gen_id();
+            block.body.push(Instruction::composite_construct(
+                result_type_id,
+                result_id,
+                &[texel_id, resident_id],
+            ));
+            result_id
+        } else if needs_sub_access {
             let sub_id = self.gen_id();
             block.body.push(Instruction::composite_extract(
                 result_type_id,
@@ -1207,4 +1329,94 @@ impl<'w> BlockContext<'w> {
 
         Ok(())
     }
+
+    pub(super) fn write_image_atomic(
+        &mut self,
+        image: Handle<crate::Expression>,
+        coordinate: Handle<crate::Expression>,
+        array_index: Option<Handle<crate::Expression>>,
+        fun: crate::AtomicFunction,
+        value: Handle<crate::Expression>,
+        block: &mut Block,
+    ) -> Result<(), Error> {
+        let image_id = self.get_handle_id(image);
+        let image_pointer_id = self.get_image_pointer_id(image, block)?;
+        let coordinates = self.write_image_coordinates(coordinate, array_index, block)?;
+
+        let scalar = match *self.fun_info[image].ty.inner_with(&self.ir_module.types) {
+            crate::TypeInner::Image {
+                class: crate::ImageClass::Storage { format, .. },
+                ..
+            } => crate::Scalar {
+                kind: format.into(),
+                width: 4,
+            },
+            _ => unreachable!(),
+        };
+
+        let op = match (fun, scalar.kind) {
+            (crate::AtomicFunction::Add, _) => spirv::Op::AtomicIAdd,
+            (crate::AtomicFunction::And, _) => spirv::Op::AtomicAnd,
+            (crate::AtomicFunction::InclusiveOr, _) => spirv::Op::AtomicOr,
+            (crate::AtomicFunction::ExclusiveOr, _) => spirv::Op::AtomicXor,
+            (crate::AtomicFunction::Min, crate::ScalarKind::Sint) => spirv::Op::AtomicSMin,
+            (crate::AtomicFunction::Min, _) => spirv::Op::AtomicUMin,
+            (crate::AtomicFunction::Max, crate::ScalarKind::Sint) => spirv::Op::AtomicSMax,
+            (crate::AtomicFunction::Max, _) => spirv::Op::AtomicUMax,
+            (crate::AtomicFunction::Subtract | crate::AtomicFunction::Exchange { .. }, _) => {
+                unreachable!()
+            }
+        };
+
+        let scalar_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+            vector_size: None,
+            scalar,
+            pointer_space: None,
+        }));
+        let texel_pointer_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+            vector_size: None,
+            scalar,
+            pointer_space: Some(spirv::StorageClass::Image),
+        }));
+        let semantics = spirv::MemorySemantics::IMAGE_MEMORY;
+        let atomic = Atomic {
+            op,
+            texel_pointer_type_id,
+            image_pointer_id,
+            sample_id: self.get_index_constant(0),
+            scalar_type_id,
+            scope_id: self.get_scope_constant(spirv::Scope::Device as u32),
+            semantics_id: self.get_index_constant(semantics.bits()),
+            value_id: self.cached[value],
+        };
+
+        match self.writer.bounds_check_policies.image_store {
+            crate::proc::BoundsCheckPolicy::Restrict => {
+                let (coords, _, _) =
+                    self.write_restricted_coordinates(image_id, coordinates, None, None, block)?;
+                atomic.generate(&mut self.writer.id_gen, coords, None, None, block);
+            }
+            crate::proc::BoundsCheckPolicy::ReadZeroSkipWrite => {
+                self.write_conditional_image_access(
+                    image_id,
+                    coordinates,
+                    None,
+                    None,
+                    block,
+                    &atomic,
+                )?;
+            }
+            crate::proc::BoundsCheckPolicy::Unchecked => {
+                atomic.generate(
+                    &mut self.writer.id_gen,
+                    coordinates.value_id,
+                    None,
+                    None,
+                    block,
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -632,12 +632,17 @@ impl super::Instruction {
         sampled_image: Word,
         coordinates: Word,
         depth_ref: Option<Word>,
+        sparse: bool,
     ) -> Self {
-        let op = match (lod, depth_ref) {
-            (SampleLod::Explicit, None) => Op::ImageSampleExplicitLod,
-            (SampleLod::Implicit, None) => Op::ImageSampleImplicitLod,
-            (SampleLod::Explicit, Some(_)) => Op::ImageSampleDrefExplicitLod,
-            (SampleLod::Implicit, Some(_)) => Op::ImageSampleDrefImplicitLod,
+        let op = match (lod, depth_ref, sparse) {
+            (SampleLod::Explicit, None, false) => Op::ImageSampleExplicitLod,
+            (SampleLod::Implicit, None, false) => Op::ImageSampleImplicitLod,
+            (SampleLod::Explicit, Some(_), false) => Op::ImageSampleDrefExplicitLod,
+            (SampleLod::Implicit, Some(_), false) => Op::ImageSampleDrefImplicitLod,
+            (SampleLod::Explicit, None, true) => Op::ImageSparseSampleExplicitLod,
+            (SampleLod::Implicit, None, true) => Op::ImageSparseSampleImplicitLod,
+            (SampleLod::Explicit, Some(_), true) => Op::ImageSparseSampleDrefExplicitLod,
+            (SampleLod::Implicit, Some(_), true) => Op::ImageSparseSampleDrefImplicitLod,
         };
 
         let mut instruction = Self::new(op);
@@ -652,6 +657,18 @@ impl super::Instruction {
         instruction
     }
 
+    pub(super) fn image_sparse_texels_resident(
+        result_type_id: Word,
+        id: Word,
+        resident_code: Word,
+    ) -> Self {
+        let mut instruction = Self::new(Op::ImageSparseTexelsResident);
+        instruction.set_type(result_type_id);
+        instruction.set_result(id);
+        instruction.add_operand(resident_code);
+        instruction
+    }
+
     pub(super) fn image_gather(
         result_type_id: Word,
         id: Word,
@@ -702,6 +719,22 @@ impl super::Instruction {
         instruction
     }
 
+    pub(super) fn image_texel_pointer(
+        result_type_id: Word,
+        id: Word,
+        image: Word,
+        coordinates: Word,
+        sample: Word,
+    ) -> Self {
+        let mut instruction = Self::new(Op::ImageTexelPointer);
+        instruction.set_type(result_type_id);
+        instruction.set_result(id);
+        instruction.add_operand(image);
+        instruction.add_operand(coordinates);
+        instruction.add_operand(sample);
+        instruction
+    }
+
     pub(super) fn image_query(op: Op, result_type_id: Word, id: Word, image: Word) -> Self {
         let mut instruction = Self::new(op);
         instruction.set_type(result_type_id);
@@ -759,6 +792,81 @@ impl super::Instruction {
         instruction
     }
 
+    //
+    //  Ray Tracing Pipeline Instructions
+    //
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn trace_ray(
+        acceleration_structure: Word,
+        ray_flags: Word,
+        cull_mask: Word,
+        sbt_record_offset: Word,
+        sbt_record_stride: Word,
+        miss_index: Word,
+        ray_origin: Word,
+        ray_tmin: Word,
+        ray_dir: Word,
+        ray_tmax: Word,
+        payload: Word,
+    ) -> Self {
+        let mut instruction = Self::new(Op::TraceRayKHR);
+        instruction.add_operand(acceleration_structure);
+        instruction.add_operand(ray_flags);
+        instruction.add_operand(cull_mask);
+        instruction.add_operand(sbt_record_offset);
+        instruction.add_operand(sbt_record_stride);
+        instruction.add_operand(miss_index);
+        instruction.add_operand(ray_origin);
+        instruction.add_operand(ray_tmin);
+        instruction.add_operand(ray_dir);
+        instruction.add_operand(ray_tmax);
+        instruction.add_operand(payload);
+        instruction
+    }
+
+    pub(super) fn report_intersection(
+        result_type_id: Word,
+        id: Word,
+        hit: Word,
+        hit_kind: Word,
+    ) -> Self {
+        let mut instruction = Self::new(Op::ReportIntersectionKHR);
+        instruction.set_type(result_type_id);
+        instruction.set_result(id);
+        instruction.add_operand(hit);
+        instruction.add_operand(hit_kind);
+        instruction
+    }
+
+    pub(super) const fn ignore_intersection() -> Self {
+        Self::new(Op::IgnoreIntersectionKHR)
+    }
+
+    pub(super) const fn terminate_ray() -> Self {
+        Self::new(Op::TerminateRayKHR)
+    }
+
+    //
+    //  Mesh Shading Instructions
+    //
+    pub(super) fn emit_mesh_tasks(x: Word, y: Word, z: Word, payload: Option<Word>) -> Self {
+        let mut instruction = Self::new(Op::EmitMeshTasksEXT);
+        instruction.add_operand(x);
+        instruction.add_operand(y);
+        instruction.add_operand(z);
+        if let Some(payload) = payload {
+            instruction.add_operand(payload);
+        }
+        instruction
+    }
+
+    pub(super) fn set_mesh_outputs(vertex_count: Word, primitive_count: Word) -> Self {
+        let mut instruction = Self::new(Op::SetMeshOutputsEXT);
+        instruction.add_operand(vertex_count);
+        instruction.add_operand(primitive_count);
+        instruction
+    }
+
     //
     //  Conversion Instructions
     //
@@ -1128,6 +1236,7 @@ impl super::Instruction {
         exec_scope_id: Word,
         group_op: Option<spirv::GroupOperation>,
         value: Word,
+        cluster_size: Option<Word>,
     ) -> Self {
         let mut instruction = Self::new(op);
         instruction.set_type(result_type_id);
@@ -1137,6 +1246,9 @@ impl super::Instruction {
             instruction.add_operand(group_op as u32);
         }
         instruction.add_operand(value);
+        if let Some(cluster_size) = cluster_size {
+            instruction.add_operand(cluster_size);
+        }
 
         instruction
     }
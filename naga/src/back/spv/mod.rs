@@ -133,9 +133,18 @@ struct ResultMember {
     built_in: Option<crate::BuiltIn>,
 }
 
+/// An `Output` array variable of a mesh shader, holding one member of its
+/// vertex or primitive output struct for each vertex or primitive.
+struct MeshOutputMember {
+    id: Word,
+    ty: Handle<crate::Type>,
+}
+
 struct EntryPointContext {
     argument_ids: Vec<Word>,
     results: Vec<ResultMember>,
+    mesh_vertex_outputs: Vec<MeshOutputMember>,
+    mesh_primitive_outputs: Vec<MeshOutputMember>,
 }
 
 #[derive(Default)]
@@ -313,6 +322,9 @@ enum LocalType {
     },
     AccelerationStructure,
     RayQuery,
+    /// The result of an `OpImageSparseSample*` instruction: a struct of the
+    /// `i32` residency code and the `vec4<f32>` texel.
+    SparseSampleResult,
 }
 
 /// A type encountered during SPIR-V generation.
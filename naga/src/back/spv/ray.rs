@@ -1,11 +1,102 @@
 /*!
-Generating SPIR-V for ray query operations.
+Generating SPIR-V for ray query and ray tracing pipeline operations.
 */
 
 use super::{Block, BlockContext, Instruction, LocalType, LookupType};
 use crate::arena::Handle;
 
+/// The components of a ray descriptor, as extracted by
+/// [`BlockContext::write_ray_desc_extract`].
+struct RayDescIds {
+    ray_flags_id: spirv::Word,
+    cull_mask_id: spirv::Word,
+    tmin_id: spirv::Word,
+    tmax_id: spirv::Word,
+    ray_origin_id: spirv::Word,
+    ray_dir_id: spirv::Word,
+}
+
 impl<'w> BlockContext<'w> {
+    /// Split a ray descriptor into the operands expected by
+    /// `OpRayQueryInitializeKHR` and `OpTraceRayKHR`.
+    fn write_ray_desc_extract(
+        &mut self,
+        descriptor: Handle<crate::Expression>,
+        block: &mut Block,
+    ) -> RayDescIds {
+        //Note: composite extract indices and types must match `generate_ray_desc_type`
+        let desc_id = self.cached[descriptor];
+
+        let flag_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+            vector_size: None,
+            scalar: crate::Scalar::U32,
+            pointer_space: None,
+        }));
+        let ray_flags_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            flag_type_id,
+            ray_flags_id,
+            desc_id,
+            &[0],
+        ));
+        let cull_mask_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            flag_type_id,
+            cull_mask_id,
+            desc_id,
+            &[1],
+        ));
+
+        let scalar_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+            vector_size: None,
+            scalar: crate::Scalar::F32,
+            pointer_space: None,
+        }));
+        let tmin_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            scalar_type_id,
+            tmin_id,
+            desc_id,
+            &[2],
+        ));
+        let tmax_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            scalar_type_id,
+            tmax_id,
+            desc_id,
+            &[3],
+        ));
+
+        let vector_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
+            vector_size: Some(crate::VectorSize::Tri),
+            scalar: crate::Scalar::F32,
+            pointer_space: None,
+        }));
+        let ray_origin_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            vector_type_id,
+            ray_origin_id,
+            desc_id,
+            &[4],
+        ));
+        let ray_dir_id = self.gen_id();
+        block.body.push(Instruction::composite_extract(
+            vector_type_id,
+            ray_dir_id,
+            desc_id,
+            &[5],
+        ));
+
+        RayDescIds {
+            ray_flags_id,
+            cull_mask_id,
+            tmin_id,
+            tmax_id,
+            ray_origin_id,
+            ray_dir_id,
+        }
+    }
+
     pub(super) fn write_ray_query_function(
         &mut self,
         query: Handle<crate::Expression>,
@@ -18,79 +109,18 @@ impl<'w> BlockContext<'w> {
                 acceleration_structure,
                 descriptor,
             } => {
-                //Note: composite extract indices and types must match `generate_ray_desc_type`
-                let desc_id = self.cached[descriptor];
+                let desc = self.write_ray_desc_extract(descriptor, block);
                 let acc_struct_id = self.get_handle_id(acceleration_structure);
 
-                let flag_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
-                    vector_size: None,
-                    scalar: crate::Scalar::U32,
-                    pointer_space: None,
-                }));
-                let ray_flags_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    flag_type_id,
-                    ray_flags_id,
-                    desc_id,
-                    &[0],
-                ));
-                let cull_mask_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    flag_type_id,
-                    cull_mask_id,
-                    desc_id,
-                    &[1],
-                ));
-
-                let scalar_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
-                    vector_size: None,
-                    scalar: crate::Scalar::F32,
-                    pointer_space: None,
-                }));
-                let tmin_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    scalar_type_id,
-                    tmin_id,
-                    desc_id,
-                    &[2],
-                ));
-                let tmax_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    scalar_type_id,
-                    tmax_id,
-                    desc_id,
-                    &[3],
-                ));
-
-                let vector_type_id = self.get_type_id(LookupType::Local(LocalType::Value {
-                    vector_size: Some(crate::VectorSize::Tri),
-                    scalar: crate::Scalar::F32,
-                    pointer_space: None,
-                }));
-                let ray_origin_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    vector_type_id,
-                    ray_origin_id,
-                    desc_id,
-                    &[4],
-                ));
-                let ray_dir_id = self.gen_id();
-                block.body.push(Instruction::composite_extract(
-                    vector_type_id,
-                    ray_dir_id,
-                    desc_id,
-                    &[5],
-                ));
-
                 block.body.push(Instruction::ray_query_initialize(
                     query_id,
                     acc_struct_id,
-                    ray_flags_id,
-                    cull_mask_id,
-                    ray_origin_id,
-                    tmin_id,
-                    ray_dir_id,
-                    tmax_id,
+                    desc.ray_flags_id,
+                    desc.cull_mask_id,
+                    desc.ray_origin_id,
+                    desc.tmin_id,
+                    desc.ray_dir_id,
+                    desc.tmax_id,
                 ));
             }
             crate::RayQueryFunction::Proceed { result } => {
@@ -258,4 +288,60 @@ impl<'w> BlockContext<'w> {
         ));
         id
     }
+
+    pub(super) fn write_ray_pipeline_function(
+        &mut self,
+        function: &crate::RayPipelineFunction,
+        block: &mut Block,
+    ) {
+        match *function {
+            crate::RayPipelineFunction::TraceRay {
+                acceleration_structure,
+                descriptor,
+                sbt_record_offset,
+                sbt_record_stride,
+                miss_index,
+                payload,
+            } => {
+                let desc = self.write_ray_desc_extract(descriptor, block);
+                let acc_struct_id = self.get_handle_id(acceleration_structure);
+                // The payload operand must be the `OpVariable` itself.
+                let payload_id = match self.ir_function.expressions[payload] {
+                    crate::Expression::GlobalVariable(handle) => {
+                        self.writer.global_variables[handle.index()].var_id
+                    }
+                    _ => unreachable!("ray payload must be a global variable"),
+                };
+
+                block.body.push(Instruction::trace_ray(
+                    acc_struct_id,
+                    desc.ray_flags_id,
+                    desc.cull_mask_id,
+                    self.cached[sbt_record_offset],
+                    self.cached[sbt_record_stride],
+                    self.cached[miss_index],
+                    desc.ray_origin_id,
+                    desc.tmin_id,
+                    desc.ray_dir_id,
+                    desc.tmax_id,
+                    payload_id,
+                ));
+            }
+            crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                // Whether the intersection was accepted isn't exposed in the IR.
+                let bool_type_id = self.writer.get_bool_type_id();
+                let id = self.gen_id();
+                block.body.push(Instruction::report_intersection(
+                    bool_type_id,
+                    id,
+                    self.cached[hit_t],
+                    self.cached[hit_kind],
+                ));
+            }
+            // These terminate the block, see `Statement::RayPipelineFunction`
+            // in `BlockContext::write_block`.
+            crate::RayPipelineFunction::IgnoreIntersection
+            | crate::RayPipelineFunction::TerminateRay => unreachable!(),
+        }
+    }
 }
@@ -60,6 +60,12 @@ impl<'w> BlockContext<'w> {
                 )?;
             }
         }
+        if let crate::CollectiveOperation::ClusteredReduce(_) = *collective_op {
+            self.writer.require_any(
+                "GroupNonUniformClustered",
+                &[spirv::Capability::GroupNonUniformClustered],
+            )?;
+        }
 
         let id = self.gen_id();
         let result_ty = &self.fun_info[result].ty;
@@ -108,8 +114,13 @@ impl<'w> BlockContext<'w> {
                 c::Reduce => spirv::GroupOperation::Reduce,
                 c::InclusiveScan => spirv::GroupOperation::InclusiveScan,
                 c::ExclusiveScan => spirv::GroupOperation::ExclusiveScan,
+                c::ClusteredReduce(_) => spirv::GroupOperation::ClusteredReduce,
             }),
         };
+        let cluster_size_id = match *collective_op {
+            c::ClusteredReduce(cluster_size) => Some(self.get_index_constant(cluster_size)),
+            c::Reduce | c::InclusiveScan | c::ExclusiveScan => None,
+        };
 
         let arg_id = self.cached[argument];
         block.body.push(Instruction::group_non_uniform_arithmetic(
@@ -119,6 +130,7 @@ impl<'w> BlockContext<'w> {
             exec_scope_id,
             group_op,
             arg_id,
+            cluster_size_id,
         ));
         self.cached[result] = id;
         Ok(())
@@ -153,6 +165,12 @@ impl<'w> BlockContext<'w> {
                     &[spirv::Capability::GroupNonUniformShuffleRelative],
                 )?;
             }
+            crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_) => {
+                self.writer.require_any(
+                    "GroupNonUniformQuad",
+                    &[spirv::Capability::GroupNonUniformQuad],
+                )?;
+            }
         }
 
         let id = self.gen_id();
@@ -180,7 +198,9 @@ impl<'w> BlockContext<'w> {
             | crate::GatherMode::ShuffleXor(index) => {
                 let index_id = self.cached[index];
                 let op = match *mode {
-                    crate::GatherMode::BroadcastFirst => unreachable!(),
+                    crate::GatherMode::BroadcastFirst
+                    | crate::GatherMode::QuadBroadcast(_)
+                    | crate::GatherMode::QuadSwap(_) => unreachable!(),
                     // Use shuffle to emit broadcast to allow the index to
                     // be dynamically uniform on Vulkan 1.1. The argument to
                     // OpGroupNonUniformBroadcast must be a constant pre SPIR-V
@@ -200,6 +220,32 @@ impl<'w> BlockContext<'w> {
                     index_id,
                 ));
             }
+            crate::GatherMode::QuadBroadcast(index) => {
+                let index_id = self.cached[index];
+                block.body.push(Instruction::group_non_uniform_gather(
+                    spirv::Op::GroupNonUniformQuadBroadcast,
+                    result_type_id,
+                    id,
+                    exec_scope_id,
+                    arg_id,
+                    index_id,
+                ));
+            }
+            crate::GatherMode::QuadSwap(direction) => {
+                let direction_id = self.get_index_constant(match direction {
+                    crate::Direction::X => 0,
+                    crate::Direction::Y => 1,
+                    crate::Direction::Diagonal => 2,
+                });
+                block.body.push(Instruction::group_non_uniform_gather(
+                    spirv::Op::GroupNonUniformQuadSwap,
+                    result_type_id,
+                    id,
+                    exec_scope_id,
+                    arg_id,
+                    direction_id,
+                ));
+            }
         }
         self.cached[result] = id;
         Ok(())
@@ -3,8 +3,9 @@ use super::{
     helpers::{contains_builtin, global_needs_wrapper, map_storage_class},
     make_local, Block, BlockContext, CachedConstant, CachedExpressions, DebugInfo,
     EntryPointContext, Error, Function, FunctionArgument, GlobalVariable, IdGenerator, Instruction,
-    LocalType, LocalVariable, LogicalLayout, LookupFunctionType, LookupType, LoopContext, Options,
-    PhysicalLayout, PipelineOptions, ResultMember, Writer, WriterFlags, BITS_PER_BYTE,
+    LocalType, LocalVariable, LogicalLayout, LookupFunctionType, LookupType, LoopContext,
+    MeshOutputMember, Options, PhysicalLayout, PipelineOptions, ResultMember, Writer, WriterFlags,
+    BITS_PER_BYTE,
 };
 use crate::{
     arena::{Handle, UniqueArena},
@@ -18,6 +19,7 @@ use std::collections::hash_map::Entry;
 struct FunctionInterface<'a> {
     varying_ids: &'a mut Vec<Word>,
     stage: crate::ShaderStage,
+    mesh_info: Option<&'a crate::MeshStageInfo>,
 }
 
 impl Function {
@@ -336,6 +338,8 @@ impl Writer {
         let mut ep_context = EntryPointContext {
             argument_ids: Vec::new(),
             results: Vec::new(),
+            mesh_vertex_outputs: Vec::new(),
+            mesh_primitive_outputs: Vec::new(),
         };
 
         let mut local_invocation_id = None;
@@ -415,6 +419,25 @@ impl Writer {
                 };
                 ep_context.argument_ids.push(id);
             } else {
+                // Pointers into buffers or workgroup memory can only be passed
+                // to functions with variable pointers.
+                match ir_module.types[argument.ty].inner.pointer_space() {
+                    Some(crate::AddressSpace::Storage { .. }) => {
+                        self.require_any(
+                            "storage buffer pointer parameters",
+                            &[spirv::Capability::VariablePointersStorageBuffer],
+                        )?;
+                        self.use_extension("SPV_KHR_variable_pointers");
+                    }
+                    Some(crate::AddressSpace::WorkGroup) => {
+                        self.require_any(
+                            "workgroup pointer parameters",
+                            &[spirv::Capability::VariablePointers],
+                        )?;
+                        self.use_extension("SPV_KHR_variable_pointers");
+                    }
+                    _ => {}
+                }
                 let argument_id = self.id_gen.next();
                 let instruction = Instruction::function_parameter(argument_type_id, argument_id);
                 if self.flags.contains(WriterFlags::DEBUG) {
@@ -521,6 +544,23 @@ impl Writer {
             None => self.void_type,
         };
 
+        if let Some(ref mut iface) = interface {
+            if let Some(mesh_info) = iface.mesh_info {
+                ep_context.mesh_vertex_outputs = self.write_mesh_output_varyings(
+                    ir_module,
+                    iface,
+                    mesh_info.vertex_output,
+                    mesh_info.max_vertices,
+                )?;
+                ep_context.mesh_primitive_outputs = self.write_mesh_output_varyings(
+                    ir_module,
+                    iface,
+                    mesh_info.primitive_output,
+                    mesh_info.max_primitives,
+                )?;
+            }
+        }
+
         let lookup_function_type = LookupFunctionType {
             parameter_type_ids,
             return_type_id,
@@ -678,22 +718,18 @@ impl Writer {
 
         let workgroup_vars_init_exit_block_id =
             match (context.writer.zero_initialize_workgroup_memory, interface) {
-                (
-                    super::ZeroInitializeWorkgroupMemoryMode::Polyfill,
-                    Some(
-                        ref mut interface @ FunctionInterface {
-                            stage: crate::ShaderStage::Compute,
-                            ..
-                        },
-                    ),
-                ) => context.writer.generate_workgroup_vars_init_block(
-                    next_id,
-                    ir_module,
-                    info,
-                    local_invocation_id,
-                    interface,
-                    context.function,
-                ),
+                (super::ZeroInitializeWorkgroupMemoryMode::Polyfill, Some(ref mut interface))
+                    if interface.stage.uses_workgroups() =>
+                {
+                    context.writer.generate_workgroup_vars_init_block(
+                        next_id,
+                        ir_module,
+                        info,
+                        local_invocation_id,
+                        interface,
+                        context.function,
+                    )
+                }
                 _ => None,
             };
 
@@ -752,6 +788,7 @@ impl Writer {
             Some(FunctionInterface {
                 varying_ids: &mut interface_ids,
                 stage: entry_point.stage,
+                mesh_info: entry_point.mesh_info.as_ref(),
             }),
             debug_info,
         )?;
@@ -784,8 +821,68 @@ impl Writer {
                     &entry_point.workgroup_size,
                 )
                 .to_words(&mut self.logical_layout.execution_modes);
+                if info.uses_derivatives {
+                    self.require_any(
+                        "derivatives in compute shaders",
+                        &[spirv::Capability::ComputeDerivativeGroupQuadsNV],
+                    )?;
+                    self.use_extension("SPV_NV_compute_shader_derivatives");
+                    self.write_execution_mode(
+                        function_id,
+                        spirv::ExecutionMode::DerivativeGroupQuadsNV,
+                    )?;
+                }
                 spirv::ExecutionModel::GLCompute
             }
+            crate::ShaderStage::RayGeneration => spirv::ExecutionModel::RayGenerationKHR,
+            crate::ShaderStage::Intersection => spirv::ExecutionModel::IntersectionKHR,
+            crate::ShaderStage::AnyHit => spirv::ExecutionModel::AnyHitKHR,
+            crate::ShaderStage::ClosestHit => spirv::ExecutionModel::ClosestHitKHR,
+            crate::ShaderStage::Miss => spirv::ExecutionModel::MissKHR,
+            crate::ShaderStage::Task => {
+                Instruction::execution_mode(
+                    function_id,
+                    spirv::ExecutionMode::LocalSize,
+                    &entry_point.workgroup_size,
+                )
+                .to_words(&mut self.logical_layout.execution_modes);
+                spirv::ExecutionModel::TaskEXT
+            }
+            crate::ShaderStage::Mesh => {
+                Instruction::execution_mode(
+                    function_id,
+                    spirv::ExecutionMode::LocalSize,
+                    &entry_point.workgroup_size,
+                )
+                .to_words(&mut self.logical_layout.execution_modes);
+                if let Some(ref mesh_info) = entry_point.mesh_info {
+                    Instruction::execution_mode(
+                        function_id,
+                        spirv::ExecutionMode::OutputVertices,
+                        &[mesh_info.max_vertices],
+                    )
+                    .to_words(&mut self.logical_layout.execution_modes);
+                    Instruction::execution_mode(
+                        function_id,
+                        spirv::ExecutionMode::OutputPrimitivesEXT,
+                        &[mesh_info.max_primitives],
+                    )
+                    .to_words(&mut self.logical_layout.execution_modes);
+                    self.write_execution_mode(
+                        function_id,
+                        match mesh_info.topology {
+                            crate::MeshOutputTopology::Points => spirv::ExecutionMode::OutputPoints,
+                            crate::MeshOutputTopology::Lines => {
+                                spirv::ExecutionMode::OutputLinesEXT
+                            }
+                            crate::MeshOutputTopology::Triangles => {
+                                spirv::ExecutionMode::OutputTrianglesEXT
+                            }
+                        },
+                    )?;
+                }
+                spirv::ExecutionModel::MeshEXT
+            }
         };
         //self.check(exec_model.required_capabilities())?;
 
@@ -873,7 +970,13 @@ impl Writer {
                 }
             }
             crate::TypeInner::AccelerationStructure => {
-                self.require_any("Acceleration Structure", &[spirv::Capability::RayQueryKHR])?;
+                // Ray tracing pipelines can trace rays without ray queries.
+                if !self
+                    .capabilities_used
+                    .contains(&spirv::Capability::RayTracingKHR)
+                {
+                    self.require_any("Acceleration Structure", &[spirv::Capability::RayQueryKHR])?;
+                }
             }
             crate::TypeInner::RayQuery => {
                 self.require_any("Ray Query", &[spirv::Capability::RayQueryKHR])?;
@@ -959,6 +1062,19 @@ impl Writer {
             }
             LocalType::AccelerationStructure => Instruction::type_acceleration_structure(id),
             LocalType::RayQuery => Instruction::type_ray_query(id),
+            LocalType::SparseSampleResult => {
+                let code_id = self.get_type_id(LookupType::Local(LocalType::Value {
+                    vector_size: None,
+                    scalar: crate::Scalar::I32,
+                    pointer_space: None,
+                }));
+                let texel_id = self.get_type_id(LookupType::Local(LocalType::Value {
+                    vector_size: Some(crate::VectorSize::Quad),
+                    scalar: crate::Scalar::F32,
+                    pointer_space: None,
+                }));
+                Instruction::type_struct(id, &[code_id, texel_id])
+            }
         };
 
         instruction.to_words(&mut self.logical_layout.declarations);
@@ -1007,6 +1123,7 @@ impl Writer {
                             let length_id = self.get_index_constant(length.get());
                             Instruction::type_array(id, type_id, length_id)
                         }
+                        crate::ArraySize::Pending(_) => return Err(Error::Override),
                         crate::ArraySize::Dynamic => Instruction::type_runtime_array(id, type_id),
                     }
                 }
@@ -1017,6 +1134,7 @@ impl Writer {
                             let length_id = self.get_index_constant(length.get());
                             Instruction::type_array(id, type_id, length_id)
                         }
+                        crate::ArraySize::Pending(_) => return Err(Error::Override),
                         crate::ArraySize::Dynamic => Instruction::type_runtime_array(id, type_id),
                     }
                 }
@@ -1474,6 +1592,71 @@ impl Writer {
             }
         }
 
+        self.decorate_varying(ir_module, stage, class, id, ty, binding)?;
+        Ok(id)
+    }
+
+    /// Generate one `Output` array variable for each member of a mesh
+    /// shader's vertex or primitive output struct `ty`, with `count` elements.
+    ///
+    /// Mesh shaders write their outputs for each vertex and primitive at an
+    /// index, so unlike other interface values these are arrays rather than a
+    /// single value per invocation.
+    fn write_mesh_output_varyings(
+        &mut self,
+        ir_module: &crate::Module,
+        iface: &mut FunctionInterface,
+        ty: Handle<crate::Type>,
+        count: u32,
+    ) -> Result<Vec<MeshOutputMember>, Error> {
+        let members = match ir_module.types[ty].inner {
+            crate::TypeInner::Struct { ref members, .. } => members,
+            _ => unreachable!("mesh outputs must be structs"),
+        };
+
+        let class = spirv::StorageClass::Output;
+        let length_id = self.get_index_constant(count);
+        let mut outputs = Vec::with_capacity(members.len());
+        for member in members {
+            let binding = member.binding.as_ref().unwrap();
+            let element_type_id = self.get_type_id(LookupType::Handle(member.ty));
+            let array_type_id = self.id_gen.next();
+            Instruction::type_array(array_type_id, element_type_id, length_id)
+                .to_words(&mut self.logical_layout.declarations);
+            let pointer_type_id = self.id_gen.next();
+            Instruction::type_pointer(pointer_type_id, class, array_type_id)
+                .to_words(&mut self.logical_layout.declarations);
+
+            let id = self.id_gen.next();
+            Instruction::variable(pointer_type_id, id, class, None)
+                .to_words(&mut self.logical_layout.declarations);
+            if self
+                .flags
+                .contains(WriterFlags::DEBUG | WriterFlags::LABEL_VARYINGS)
+            {
+                if let Some(ref name) = member.name {
+                    self.debugs.push(Instruction::name(id, name));
+                }
+            }
+
+            self.decorate_varying(ir_module, iface.stage, class, id, member.ty, binding)?;
+            iface.varying_ids.push(id);
+            outputs.push(MeshOutputMember { id, ty: member.ty });
+        }
+
+        Ok(outputs)
+    }
+
+    /// Decorate the interface variable `id` of type `ty` according to `binding`.
+    fn decorate_varying(
+        &mut self,
+        ir_module: &crate::Module,
+        stage: crate::ShaderStage,
+        class: spirv::StorageClass,
+        id: Word,
+        ty: Handle<crate::Type>,
+        binding: &crate::Binding,
+    ) -> Result<(), Error> {
         use spirv::{BuiltIn, Decoration};
 
         match *binding {
@@ -1482,9 +1665,19 @@ impl Writer {
                 interpolation,
                 sampling,
                 second_blend_source,
+                per_primitive,
             } => {
                 self.decorate(id, Decoration::Location, &[location]);
 
+                if per_primitive {
+                    self.require_any(
+                        "per-primitive interface values",
+                        &[spirv::Capability::MeshShadingEXT],
+                    )?;
+                    self.use_extension("SPV_EXT_mesh_shader");
+                    self.decorate(id, Decoration::PerPrimitiveEXT, &[]);
+                }
+
                 let no_decorations =
                     // VUID-StandaloneSpirv-Flat-06202
                     // > The Flat, NoPerspective, Sample, and Centroid decorations
@@ -1567,6 +1760,10 @@ impl Writer {
                     Bi::FragDepth => BuiltIn::FragDepth,
                     Bi::PointCoord => BuiltIn::PointCoord,
                     Bi::FrontFacing => BuiltIn::FrontFacing,
+                    Bi::PrimitiveIndex if stage == crate::ShaderStage::Mesh => {
+                        self.decorate(id, Decoration::PerPrimitiveEXT, &[]);
+                        BuiltIn::PrimitiveId
+                    }
                     Bi::PrimitiveIndex => {
                         self.require_any(
                             "`primitive_index` built-in",
@@ -1583,6 +1780,14 @@ impl Writer {
                         BuiltIn::SampleId
                     }
                     Bi::SampleMask => BuiltIn::SampleMask,
+                    Bi::Barycentric => {
+                        self.require_any(
+                            "`barycentric_coords` built-in",
+                            &[spirv::Capability::FragmentBarycentricKHR],
+                        )?;
+                        self.use_extension("SPV_KHR_fragment_shader_barycentric");
+                        BuiltIn::BaryCoordKHR
+                    }
                     // compute
                     Bi::GlobalInvocationId => BuiltIn::GlobalInvocationId,
                     Bi::LocalInvocationId => BuiltIn::LocalInvocationId,
@@ -1625,6 +1830,30 @@ impl Writer {
                         )?;
                         BuiltIn::SubgroupLocalInvocationId
                     }
+                    // ray tracing pipeline
+                    Bi::LaunchId => BuiltIn::LaunchIdKHR,
+                    Bi::LaunchSize => BuiltIn::LaunchSizeKHR,
+                    Bi::WorldRayOrigin => BuiltIn::WorldRayOriginKHR,
+                    Bi::WorldRayDirection => BuiltIn::WorldRayDirectionKHR,
+                    Bi::ObjectRayOrigin => BuiltIn::ObjectRayOriginKHR,
+                    Bi::ObjectRayDirection => BuiltIn::ObjectRayDirectionKHR,
+                    Bi::RayTmin => BuiltIn::RayTminKHR,
+                    Bi::RayTCurrent => BuiltIn::RayTmaxKHR,
+                    Bi::InstanceId => BuiltIn::InstanceId,
+                    Bi::InstanceCustomIndex => BuiltIn::InstanceCustomIndexKHR,
+                    Bi::GeometryIndex => BuiltIn::RayGeometryIndexKHR,
+                    Bi::HitKind => BuiltIn::HitKindKHR,
+                    Bi::IncomingRayFlags => BuiltIn::IncomingRayFlagsKHR,
+                    Bi::ObjectToWorld => BuiltIn::ObjectToWorldKHR,
+                    Bi::WorldToObject => BuiltIn::WorldToObjectKHR,
+                    // mesh shading
+                    Bi::TriangleIndices => BuiltIn::PrimitiveTriangleIndicesEXT,
+                    Bi::LineIndices => BuiltIn::PrimitiveLineIndicesEXT,
+                    Bi::PointIndex => BuiltIn::PrimitivePointIndicesEXT,
+                    Bi::CullPrimitive => {
+                        self.decorate(id, Decoration::PerPrimitiveEXT, &[]);
+                        BuiltIn::CullPrimitiveEXT
+                    }
                 };
 
                 self.decorate(id, Decoration::BuiltIn, &[built_in as u32]);
@@ -1658,7 +1887,7 @@ impl Writer {
             }
         }
 
-        Ok(id)
+        Ok(())
     }
 
     fn write_global_variable(
@@ -1900,13 +2129,49 @@ impl Writer {
             .iter()
             .flat_map(|entry| entry.function.arguments.iter())
             .any(|arg| has_view_index_check(ir_module, arg.binding.as_ref(), arg.ty));
-        let mut has_ray_query = ir_module.special_types.ray_desc.is_some()
-            | ir_module.special_types.ray_intersection.is_some();
+        let has_ray_tracing_pipeline = ir_module
+            .entry_points
+            .iter()
+            .any(|entry| entry.stage.is_ray_tracing());
+        // Ray tracing pipelines use ray descriptors and acceleration
+        // structures too, but only ray query objects need `SPV_KHR_ray_query`.
+        let mut has_ray_query = ir_module.special_types.ray_intersection.is_some()
+            | (!has_ray_tracing_pipeline && ir_module.special_types.ray_desc.is_some());
 
         for (_, &crate::Type { ref inner, .. }) in ir_module.types.iter() {
-            if let &crate::TypeInner::AccelerationStructure | &crate::TypeInner::RayQuery = inner {
-                has_ray_query = true
+            match *inner {
+                crate::TypeInner::AccelerationStructure if !has_ray_tracing_pipeline => {
+                    has_ray_query = true
+                }
+                crate::TypeInner::RayQuery => has_ray_query = true,
+                _ => {}
+            }
+        }
+
+        if has_ray_tracing_pipeline {
+            self.require_any(
+                "ray tracing pipeline stages",
+                &[spirv::Capability::RayTracingKHR],
+            )?;
+            self.use_extension("SPV_KHR_ray_tracing");
+        }
+
+        if ir_module.entry_points.iter().any(|entry| {
+            matches!(
+                entry.stage,
+                crate::ShaderStage::Task | crate::ShaderStage::Mesh
+            )
+        }) {
+            // `SPV_EXT_mesh_shader` requires SPIR-V 1.4.
+            if self.physical_layout.version < 0x10400 {
+                let version = self.physical_layout.version;
+                return Err(Error::UnsupportedVersion(
+                    (version >> 16) as u8,
+                    (version >> 8) as u8,
+                ));
             }
+            self.require_any("mesh shading", &[spirv::Capability::MeshShadingEXT])?;
+            self.use_extension("SPV_EXT_mesh_shader");
         }
 
         if self.physical_layout.version < 0x10300 && has_storage_buffers {
@@ -18,8 +18,11 @@ enum Attribute {
     Interpolate(Option<crate::Interpolation>, Option<crate::Sampling>),
     Location(u32),
     SecondBlendSource,
+    PerPrimitive,
     Stage(ShaderStage),
     WorkGroupSize([u32; 3]),
+    VertexOutput(Handle<crate::Type>, u32),
+    PrimitiveOutput(Handle<crate::Type>, u32),
 }
 
 /// The WGSL form that `write_expr_with_indirection` should use to render a Naga
@@ -177,11 +180,28 @@ impl<W: Write> Writer<W> {
         // Write all entry points
         for (index, ep) in module.entry_points.iter().enumerate() {
             let attributes = match ep.stage {
-                ShaderStage::Vertex | ShaderStage::Fragment => vec![Attribute::Stage(ep.stage)],
-                ShaderStage::Compute => vec![
-                    Attribute::Stage(ShaderStage::Compute),
+                ShaderStage::Compute | ShaderStage::Task => vec![
+                    Attribute::Stage(ep.stage),
                     Attribute::WorkGroupSize(ep.workgroup_size),
                 ],
+                ShaderStage::Mesh => {
+                    let mut attributes = vec![
+                        Attribute::Stage(ShaderStage::Mesh),
+                        Attribute::WorkGroupSize(ep.workgroup_size),
+                    ];
+                    if let Some(ref mesh_info) = ep.mesh_info {
+                        attributes.push(Attribute::VertexOutput(
+                            mesh_info.vertex_output,
+                            mesh_info.max_vertices,
+                        ));
+                        attributes.push(Attribute::PrimitiveOutput(
+                            mesh_info.primitive_output,
+                            mesh_info.max_primitives,
+                        ));
+                    }
+                    attributes
+                }
+                _ => vec![Attribute::Stage(ep.stage)],
             };
 
             self.write_attributes(&attributes)?;
@@ -215,6 +235,13 @@ impl<W: Write> Writer<W> {
                     ShaderStage::Compute => "ComputeOutput",
                     ShaderStage::Fragment => "FragmentOutput",
                     ShaderStage::Vertex => "VertexOutput",
+                    ShaderStage::RayGeneration => "RayGenerationOutput",
+                    ShaderStage::Intersection => "IntersectionOutput",
+                    ShaderStage::AnyHit => "AnyHitOutput",
+                    ShaderStage::ClosestHit => "ClosestHitOutput",
+                    ShaderStage::Miss => "MissOutput",
+                    ShaderStage::Task => "TaskOutput",
+                    ShaderStage::Mesh => "MeshOutput",
                 };
 
                 write!(self.out, "{name}")?;
@@ -328,6 +355,7 @@ impl<W: Write> Writer<W> {
             match *attribute {
                 Attribute::Location(id) => write!(self.out, "@location({id}) ")?,
                 Attribute::SecondBlendSource => write!(self.out, "@second_blend_source ")?,
+                Attribute::PerPrimitive => write!(self.out, "@per_primitive ")?,
                 Attribute::BuiltIn(builtin_attrib) => {
                     let builtin = builtin_str(builtin_attrib)?;
                     write!(self.out, "@builtin({builtin}) ")?;
@@ -337,9 +365,24 @@ impl<W: Write> Writer<W> {
                         ShaderStage::Vertex => "vertex",
                         ShaderStage::Fragment => "fragment",
                         ShaderStage::Compute => "compute",
+                        ShaderStage::RayGeneration => "ray_generation",
+                        ShaderStage::Intersection => "intersection",
+                        ShaderStage::AnyHit => "any_hit",
+                        ShaderStage::ClosestHit => "closest_hit",
+                        ShaderStage::Miss => "miss",
+                        ShaderStage::Task => "task",
+                        ShaderStage::Mesh => "mesh",
                     };
                     write!(self.out, "@{stage_str} ")?;
                 }
+                Attribute::VertexOutput(ty, max_vertices) => {
+                    let ty_name = &self.names[&NameKey::Type(ty)];
+                    write!(self.out, "@vertex_output({ty_name}, {max_vertices}) ")?;
+                }
+                Attribute::PrimitiveOutput(ty, max_primitives) => {
+                    let ty_name = &self.names[&NameKey::Type(ty)];
+                    write!(self.out, "@primitive_output({ty_name}, {max_primitives}) ")?;
+                }
                 Attribute::WorkGroupSize(size) => {
                     write!(
                         self.out,
@@ -468,7 +511,10 @@ impl<W: Write> Writer<W> {
                         "storage_",
                         "",
                         storage_format_str(format),
-                        if access.contains(crate::StorageAccess::LOAD | crate::StorageAccess::STORE)
+                        if access.contains(crate::StorageAccess::ATOMIC) {
+                            ",atomic"
+                        } else if access
+                            .contains(crate::StorageAccess::LOAD | crate::StorageAccess::STORE)
                         {
                             ",read_write"
                         } else if access.contains(crate::StorageAccess::LOAD) {
@@ -507,6 +553,8 @@ impl<W: Write> Writer<W> {
                         self.write_type(module, base)?;
                         write!(self.out, ", {len}")?;
                     }
+                    // Modules with overrides are rejected up front
+                    crate::ArraySize::Pending(_) => unreachable!(),
                     crate::ArraySize::Dynamic => {
                         self.write_type(module, base)?;
                     }
@@ -521,6 +569,7 @@ impl<W: Write> Writer<W> {
                         self.write_type(module, base)?;
                         write!(self.out, ", {len}")?;
                     }
+                    crate::ArraySize::Pending(_) => unreachable!(),
                     crate::ArraySize::Dynamic => {
                         self.write_type(module, base)?;
                     }
@@ -798,6 +847,27 @@ impl<W: Write> Writer<W> {
                 self.write_expr(module, value, func_ctx)?;
                 writeln!(self.out, ");")?;
             }
+            Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                fun,
+                value,
+            } => {
+                write!(self.out, "{level}")?;
+                let fun_str = fun.to_wgsl();
+                write!(self.out, "textureAtomic{fun_str}(")?;
+                self.write_expr(module, image, func_ctx)?;
+                write!(self.out, ", ")?;
+                self.write_expr(module, coordinate, func_ctx)?;
+                if let Some(array_index_expr) = array_index {
+                    write!(self.out, ", ")?;
+                    self.write_expr(module, array_index_expr, func_ctx)?;
+                }
+                write!(self.out, ", ")?;
+                self.write_expr(module, value, func_ctx)?;
+                writeln!(self.out, ");")?;
+            }
             // TODO: copy-paste from glsl-out
             Statement::Block(ref block) => {
                 write!(self.out, "{level}")?;
@@ -930,6 +1000,76 @@ impl<W: Write> Writer<W> {
                 }
             }
             Statement::RayQuery { .. } => unreachable!(),
+            Statement::RayPipelineFunction(ref fun) => {
+                write!(self.out, "{level}")?;
+                match *fun {
+                    crate::RayPipelineFunction::TraceRay {
+                        acceleration_structure,
+                        descriptor,
+                        sbt_record_offset,
+                        sbt_record_stride,
+                        miss_index,
+                        payload,
+                    } => {
+                        write!(self.out, "traceRay(")?;
+                        for (i, arg) in [
+                            acceleration_structure,
+                            descriptor,
+                            sbt_record_offset,
+                            sbt_record_stride,
+                            miss_index,
+                            payload,
+                        ]
+                        .into_iter()
+                        .enumerate()
+                        {
+                            if i != 0 {
+                                write!(self.out, ", ")?;
+                            }
+                            self.write_expr(module, arg, func_ctx)?;
+                        }
+                    }
+                    crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                        write!(self.out, "reportIntersection(")?;
+                        self.write_expr(module, hit_t, func_ctx)?;
+                        write!(self.out, ", ")?;
+                        self.write_expr(module, hit_kind, func_ctx)?;
+                    }
+                    crate::RayPipelineFunction::IgnoreIntersection => {
+                        write!(self.out, "ignoreIntersection(")?;
+                    }
+                    crate::RayPipelineFunction::TerminateRay => {
+                        write!(self.out, "terminateRay(")?;
+                    }
+                }
+                writeln!(self.out, ");")?;
+            }
+            Statement::MeshFunction(ref fun) => {
+                write!(self.out, "{level}")?;
+                let (name, args) = match *fun {
+                    crate::MeshFunction::EmitMeshTasks { x, y, z } => {
+                        ("emitMeshTasks", vec![x, y, z])
+                    }
+                    crate::MeshFunction::SetMeshOutputs {
+                        vertex_count,
+                        primitive_count,
+                    } => ("setMeshOutputs", vec![vertex_count, primitive_count]),
+                    crate::MeshFunction::SetVertex { index, value } => {
+                        ("setVertex", vec![index, value])
+                    }
+                    crate::MeshFunction::SetPrimitive { index, value } => {
+                        ("setPrimitive", vec![index, value])
+                    }
+                };
+                write!(self.out, "{name}(")?;
+                for (i, arg) in args.into_iter().enumerate() {
+                    if i != 0 {
+                        write!(self.out, ", ")?;
+                    }
+                    self.write_expr(module, arg, func_ctx)?;
+                }
+                writeln!(self.out, ");")?;
+            }
             Statement::SubgroupBallot { result, predicate } => {
                 write!(self.out, "{level}")?;
                 let res_name = format!("{}{}", back::BAKE_PREFIX, result.index());
@@ -993,9 +1133,40 @@ impl<W: Write> Writer<W> {
                     (crate::CollectiveOperation::InclusiveScan, crate::SubgroupOperation::Mul) => {
                         write!(self.out, "subgroupInclusiveMul(")?
                     }
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Add,
+                    ) => write!(self.out, "subgroupClusteredAdd(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Mul,
+                    ) => write!(self.out, "subgroupClusteredMul(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Max,
+                    ) => write!(self.out, "subgroupClusteredMax(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Min,
+                    ) => write!(self.out, "subgroupClusteredMin(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::And,
+                    ) => write!(self.out, "subgroupClusteredAnd(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Or,
+                    ) => write!(self.out, "subgroupClusteredOr(")?,
+                    (
+                        crate::CollectiveOperation::ClusteredReduce(_),
+                        crate::SubgroupOperation::Xor,
+                    ) => write!(self.out, "subgroupClusteredXor(")?,
                     _ => unimplemented!(),
                 }
                 self.write_expr(module, argument, func_ctx)?;
+                if let crate::CollectiveOperation::ClusteredReduce(cluster_size) = collective_op {
+                    write!(self.out, ", {cluster_size}u")?;
+                }
                 writeln!(self.out, ");")?;
             }
             Statement::SubgroupGather {
@@ -1027,15 +1198,28 @@ impl<W: Write> Writer<W> {
                     crate::GatherMode::ShuffleXor(_) => {
                         write!(self.out, "subgroupShuffleXor(")?;
                     }
+                    crate::GatherMode::QuadBroadcast(_) => {
+                        write!(self.out, "quadBroadcast(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::X) => {
+                        write!(self.out, "quadSwapX(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::Y) => {
+                        write!(self.out, "quadSwapY(")?;
+                    }
+                    crate::GatherMode::QuadSwap(crate::Direction::Diagonal) => {
+                        write!(self.out, "quadSwapDiagonal(")?;
+                    }
                 }
                 self.write_expr(module, argument, func_ctx)?;
                 match mode {
-                    crate::GatherMode::BroadcastFirst => {}
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                     crate::GatherMode::Broadcast(index)
                     | crate::GatherMode::Shuffle(index)
                     | crate::GatherMode::ShuffleDown(index)
                     | crate::GatherMode::ShuffleUp(index)
-                    | crate::GatherMode::ShuffleXor(index) => {
+                    | crate::GatherMode::ShuffleXor(index)
+                    | crate::GatherMode::QuadBroadcast(index) => {
                         write!(self.out, ", ")?;
                         self.write_expr(module, index, func_ctx)?;
                     }
@@ -1386,6 +1570,7 @@ impl<W: Write> Writer<W> {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => {
                 use crate::SampleLevel as Sl;
 
@@ -1399,8 +1584,12 @@ impl<W: Write> Writer<W> {
                     Sl::Bias(_) => "Bias",
                     Sl::Gradient { .. } => "Grad",
                 };
+                let suffix_residency = if residency { "Residency" } else { "" };
 
-                write!(self.out, "textureSample{suffix_cmp}{suffix_level}(")?;
+                write!(
+                    self.out,
+                    "textureSample{suffix_cmp}{suffix_level}{suffix_residency}("
+                )?;
                 self.write_expr(module, image, func_ctx)?;
                 write!(self.out, ", ")?;
                 self.write_expr(module, sampler, func_ctx)?;
@@ -1458,6 +1647,7 @@ impl<W: Write> Writer<W> {
                 offset,
                 level: _,
                 depth_ref,
+                residency: _,
             } => {
                 let suffix_cmp = match depth_ref {
                     Some(_) => "Compare",
@@ -1915,19 +2105,35 @@ fn builtin_str(built_in: crate::BuiltIn) -> Result<&'static str, Error> {
         Bi::NumWorkGroups => "num_workgroups",
         Bi::SampleIndex => "sample_index",
         Bi::SampleMask => "sample_mask",
+        Bi::Barycentric => "barycentric_coords",
         Bi::PrimitiveIndex => "primitive_index",
         Bi::ViewIndex => "view_index",
         Bi::NumSubgroups => "num_subgroups",
         Bi::SubgroupId => "subgroup_id",
         Bi::SubgroupSize => "subgroup_size",
         Bi::SubgroupInvocationId => "subgroup_invocation_id",
-        Bi::BaseInstance
-        | Bi::BaseVertex
-        | Bi::ClipDistance
-        | Bi::CullDistance
-        | Bi::PointSize
-        | Bi::PointCoord
-        | Bi::WorkGroupSize => {
+        Bi::LaunchId => "launch_id",
+        Bi::LaunchSize => "launch_size",
+        Bi::WorldRayOrigin => "world_ray_origin",
+        Bi::WorldRayDirection => "world_ray_direction",
+        Bi::ObjectRayOrigin => "object_ray_origin",
+        Bi::ObjectRayDirection => "object_ray_direction",
+        Bi::RayTmin => "ray_t_min",
+        Bi::RayTCurrent => "ray_t_current",
+        Bi::InstanceId => "instance_id",
+        Bi::InstanceCustomIndex => "instance_custom_index",
+        Bi::GeometryIndex => "geometry_index",
+        Bi::HitKind => "hit_kind",
+        Bi::IncomingRayFlags => "incoming_ray_flags",
+        Bi::ObjectToWorld => "object_to_world",
+        Bi::WorldToObject => "world_to_object",
+        Bi::TriangleIndices => "triangle_indices",
+        Bi::LineIndices => "line_indices",
+        Bi::PointIndex => "point_index",
+        Bi::CullPrimitive => "cull_primitive",
+        Bi::ClipDistance => "clip_distances",
+        Bi::CullDistance => "cull_distances",
+        Bi::BaseInstance | Bi::BaseVertex | Bi::PointSize | Bi::PointCoord | Bi::WorkGroupSize => {
             return Err(Error::Custom(format!("Unsupported builtin {built_in:?}")))
         }
     })
@@ -2070,6 +2276,10 @@ const fn address_space_str(
             As::WorkGroup => "workgroup",
             As::Handle => return (None, None),
             As::Function => "function",
+            As::RayPayload => "ray_payload",
+            As::IncomingRayPayload => "incoming_ray_payload",
+            As::HitAttribute => "hit_attribute",
+            As::TaskPayload => "task_payload",
         }),
         None,
     )
@@ -2088,20 +2298,18 @@ fn map_binding_to_attribute(binding: &crate::Binding) -> Vec<Attribute> {
             location,
             interpolation,
             sampling,
-            second_blend_source: false,
-        } => vec![
-            Attribute::Location(location),
-            Attribute::Interpolate(interpolation, sampling),
-        ],
-        crate::Binding::Location {
-            location,
-            interpolation,
-            sampling,
-            second_blend_source: true,
-        } => vec![
-            Attribute::Location(location),
-            Attribute::SecondBlendSource,
-            Attribute::Interpolate(interpolation, sampling),
-        ],
+            second_blend_source,
+            per_primitive,
+        } => {
+            let mut attributes = vec![Attribute::Location(location)];
+            if second_blend_source {
+                attributes.push(Attribute::SecondBlendSource);
+            }
+            if per_primitive {
+                attributes.push(Attribute::PerPrimitive);
+            }
+            attributes.push(Attribute::Interpolate(interpolation, sampling));
+            attributes
+        }
     }
 }
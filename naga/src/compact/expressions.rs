@@ -119,6 +119,7 @@ impl<'tracer> ExpressionTracer<'tracer> {
                     offset,
                     ref level,
                     depth_ref,
+                    residency: _,
                 } => {
                     self.expressions_used
                         .insert_iter([image, sampler, coordinate]);
@@ -273,6 +274,7 @@ impl ModuleMap {
                 ref mut offset,
                 ref mut level,
                 ref mut depth_ref,
+                residency: _,
             } => {
                 adjust(image);
                 adjust(sampler);
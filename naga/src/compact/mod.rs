@@ -87,6 +87,15 @@ pub fn compact(module: &mut crate::Module) {
         .iter()
         .map(|e| {
             log::trace!("tracing entry point {:?}", e.function.name);
+            if let Some(ref mesh_info) = e.mesh_info {
+                module_tracer.types_used.insert(mesh_info.vertex_output);
+                module_tracer.types_used.insert(mesh_info.primitive_output);
+            }
+            if let Some(overrides) = e.workgroup_size_overrides {
+                for expr in overrides.into_iter().flatten() {
+                    module_tracer.global_expressions_used.insert(expr);
+                }
+            }
             let mut used = module_tracer.as_function(&e.function);
             used.trace();
             FunctionMap::from(used)
@@ -198,6 +207,15 @@ pub fn compact(module: &mut crate::Module) {
     // Compact each entry point.
     for (entry, map) in module.entry_points.iter_mut().zip(entry_point_maps.iter()) {
         log::trace!("compacting entry point {:?}", entry.function.name);
+        if let Some(ref mut mesh_info) = entry.mesh_info {
+            module_map.types.adjust(&mut mesh_info.vertex_output);
+            module_map.types.adjust(&mut mesh_info.primitive_output);
+        }
+        if let Some(ref mut overrides) = entry.workgroup_size_overrides {
+            for expr in overrides.iter_mut().flatten() {
+                module_map.global_expressions.adjust(expr);
+            }
+        }
         map.compact(
             &mut entry.function,
             &module_map,
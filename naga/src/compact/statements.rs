@@ -66,6 +66,21 @@ impl FunctionTracer<'_> {
                         }
                         self.expressions_used.insert(value);
                     }
+                    St::ImageAtomic {
+                        image,
+                        coordinate,
+                        array_index,
+                        ref fun,
+                        value,
+                    } => {
+                        self.expressions_used.insert(image);
+                        self.expressions_used.insert(coordinate);
+                        if let Some(array_index) = array_index {
+                            self.expressions_used.insert(array_index);
+                        }
+                        self.trace_atomic_function(fun);
+                        self.expressions_used.insert(value);
+                    }
                     St::Atomic {
                         pointer,
                         ref fun,
@@ -97,6 +112,8 @@ impl FunctionTracer<'_> {
                         self.expressions_used.insert(query);
                         self.trace_ray_query_function(fun);
                     }
+                    St::RayPipelineFunction(ref fun) => self.trace_ray_pipeline_function(fun),
+                    St::MeshFunction(ref fun) => self.trace_mesh_function(fun),
                     St::SubgroupBallot { result, predicate } => {
                         if let Some(predicate) = predicate {
                             self.expressions_used.insert(predicate)
@@ -118,12 +135,13 @@ impl FunctionTracer<'_> {
                         result,
                     } => {
                         match mode {
-                            crate::GatherMode::BroadcastFirst => {}
+                            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                             crate::GatherMode::Broadcast(index)
                             | crate::GatherMode::Shuffle(index)
                             | crate::GatherMode::ShuffleDown(index)
                             | crate::GatherMode::ShuffleUp(index)
-                            | crate::GatherMode::ShuffleXor(index) => {
+                            | crate::GatherMode::ShuffleXor(index)
+                            | crate::GatherMode::QuadBroadcast(index) => {
                                 self.expressions_used.insert(index)
                             }
                         }
@@ -177,6 +195,54 @@ impl FunctionTracer<'_> {
             Qf::Terminate => {}
         }
     }
+
+    fn trace_ray_pipeline_function(&mut self, fun: &crate::RayPipelineFunction) {
+        use crate::RayPipelineFunction as Pf;
+        match *fun {
+            Pf::TraceRay {
+                acceleration_structure,
+                descriptor,
+                sbt_record_offset,
+                sbt_record_stride,
+                miss_index,
+                payload,
+            } => {
+                self.expressions_used.insert(acceleration_structure);
+                self.expressions_used.insert(descriptor);
+                self.expressions_used.insert(sbt_record_offset);
+                self.expressions_used.insert(sbt_record_stride);
+                self.expressions_used.insert(miss_index);
+                self.expressions_used.insert(payload);
+            }
+            Pf::ReportIntersection { hit_t, hit_kind } => {
+                self.expressions_used.insert(hit_t);
+                self.expressions_used.insert(hit_kind);
+            }
+            Pf::IgnoreIntersection | Pf::TerminateRay => {}
+        }
+    }
+
+    fn trace_mesh_function(&mut self, fun: &crate::MeshFunction) {
+        use crate::MeshFunction as Mf;
+        match *fun {
+            Mf::EmitMeshTasks { x, y, z } => {
+                self.expressions_used.insert(x);
+                self.expressions_used.insert(y);
+                self.expressions_used.insert(z);
+            }
+            Mf::SetMeshOutputs {
+                vertex_count,
+                primitive_count,
+            } => {
+                self.expressions_used.insert(vertex_count);
+                self.expressions_used.insert(primitive_count);
+            }
+            Mf::SetVertex { index, value } | Mf::SetPrimitive { index, value } => {
+                self.expressions_used.insert(index);
+                self.expressions_used.insert(value);
+            }
+        }
+    }
 }
 
 impl FunctionMap {
@@ -246,6 +312,21 @@ impl FunctionMap {
                         }
                         adjust(value);
                     }
+                    St::ImageAtomic {
+                        ref mut image,
+                        ref mut coordinate,
+                        ref mut array_index,
+                        ref mut fun,
+                        ref mut value,
+                    } => {
+                        adjust(image);
+                        adjust(coordinate);
+                        if let Some(ref mut array_index) = *array_index {
+                            adjust(array_index);
+                        }
+                        self.adjust_atomic_function(fun);
+                        adjust(value);
+                    }
                     St::Atomic {
                         ref mut pointer,
                         ref mut fun,
@@ -283,6 +364,8 @@ impl FunctionMap {
                         adjust(query);
                         self.adjust_ray_query_function(fun);
                     }
+                    St::RayPipelineFunction(ref mut fun) => self.adjust_ray_pipeline_function(fun),
+                    St::MeshFunction(ref mut fun) => self.adjust_mesh_function(fun),
                     St::SubgroupBallot {
                         ref mut result,
                         ref mut predicate,
@@ -307,12 +390,13 @@ impl FunctionMap {
                         ref mut result,
                     } => {
                         match *mode {
-                            crate::GatherMode::BroadcastFirst => {}
+                            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                             crate::GatherMode::Broadcast(ref mut index)
                             | crate::GatherMode::Shuffle(ref mut index)
                             | crate::GatherMode::ShuffleDown(ref mut index)
                             | crate::GatherMode::ShuffleUp(ref mut index)
-                            | crate::GatherMode::ShuffleXor(ref mut index) => adjust(index),
+                            | crate::GatherMode::ShuffleXor(ref mut index)
+                            | crate::GatherMode::QuadBroadcast(ref mut index) => adjust(index),
                         }
                         adjust(argument);
                         adjust(result);
@@ -364,4 +448,66 @@ impl FunctionMap {
             Qf::Terminate => {}
         }
     }
+
+    fn adjust_ray_pipeline_function(&self, fun: &mut crate::RayPipelineFunction) {
+        use crate::RayPipelineFunction as Pf;
+        match *fun {
+            Pf::TraceRay {
+                ref mut acceleration_structure,
+                ref mut descriptor,
+                ref mut sbt_record_offset,
+                ref mut sbt_record_stride,
+                ref mut miss_index,
+                ref mut payload,
+            } => {
+                self.expressions.adjust(acceleration_structure);
+                self.expressions.adjust(descriptor);
+                self.expressions.adjust(sbt_record_offset);
+                self.expressions.adjust(sbt_record_stride);
+                self.expressions.adjust(miss_index);
+                self.expressions.adjust(payload);
+            }
+            Pf::ReportIntersection {
+                ref mut hit_t,
+                ref mut hit_kind,
+            } => {
+                self.expressions.adjust(hit_t);
+                self.expressions.adjust(hit_kind);
+            }
+            Pf::IgnoreIntersection | Pf::TerminateRay => {}
+        }
+    }
+
+    fn adjust_mesh_function(&self, fun: &mut crate::MeshFunction) {
+        use crate::MeshFunction as Mf;
+        match *fun {
+            Mf::EmitMeshTasks {
+                ref mut x,
+                ref mut y,
+                ref mut z,
+            } => {
+                self.expressions.adjust(x);
+                self.expressions.adjust(y);
+                self.expressions.adjust(z);
+            }
+            Mf::SetMeshOutputs {
+                ref mut vertex_count,
+                ref mut primitive_count,
+            } => {
+                self.expressions.adjust(vertex_count);
+                self.expressions.adjust(primitive_count);
+            }
+            Mf::SetVertex {
+                ref mut index,
+                ref mut value,
+            }
+            | Mf::SetPrimitive {
+                ref mut index,
+                ref mut value,
+            } => {
+                self.expressions.adjust(index);
+                self.expressions.adjust(value);
+            }
+        }
+    }
 }
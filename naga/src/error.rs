@@ -24,6 +24,14 @@ impl fmt::Display for ShaderError<crate::front::glsl::ParseErrors> {
         write!(f, "\nShader '{label}' parsing {string}")
     }
 }
+#[cfg(feature = "hlsl-in")]
+impl fmt::Display for ShaderError<crate::front::hlsl::Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = self.label.as_deref().unwrap_or_default();
+        let string = self.inner.emit_to_string(&self.source);
+        write!(f, "\nShader '{label}' parsing {string}")
+    }
+}
 #[cfg(feature = "spv-in")]
 impl fmt::Display for ShaderError<crate::front::spv::Error> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -32,6 +40,20 @@ impl fmt::Display for ShaderError<crate::front::spv::Error> {
         write!(f, "\nShader '{label}' parsing {string}")
     }
 }
+#[cfg(feature = "spv-in")]
+impl fmt::Display for ShaderError<crate::WithSpan<crate::front::spv::Error>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = self.label.as_deref().unwrap_or_default();
+        // SPIR-V is a binary format, so spans are reported as byte offsets
+        // into the module rather than rendered against the source.
+        let string = self.inner.as_inner().emit_to_string("");
+        write!(f, "\nShader '{label}' parsing {string}")?;
+        for &(span, ref desc) in self.inner.spans() {
+            write!(f, "\n  {desc} (at byte offset {})", span.start)?;
+        }
+        Ok(())
+    }
+}
 impl fmt::Display for ShaderError<crate::WithSpan<crate::valid::ValidationError>> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use codespan_reporting::{
@@ -45,11 +67,15 @@ impl fmt::Display for ShaderError<crate::WithSpan<crate::valid::ValidationError>
         let config = term::Config::default();
         let mut writer = termcolor::NoColor::new(Vec::new());
 
+        // Modules parsed from binary sources (e.g. SPIR-V) have no text to
+        // point into, so only keep labels that fall within the source.
         let diagnostic = Diagnostic::error().with_labels(
             self.inner
                 .spans()
-                .map(|&(span, ref desc)| {
-                    Label::primary((), span.to_range().unwrap()).with_message(desc.to_owned())
+                .filter_map(|&(span, ref desc)| {
+                    let range = span.to_range()?;
+                    (range.end <= self.source.len())
+                        .then(|| Label::primary((), range).with_message(desc.to_owned()))
                 })
                 .collect(),
         );
@@ -11,6 +11,8 @@ pub enum GlobalLookupKind {
     Variable(Handle<GlobalVariable>),
     Constant(Handle<Constant>, Handle<Type>),
     BlockSelect(Handle<GlobalVariable>, u32),
+    /// A combined image sampler, split into a texture and a sampler.
+    CombinedSampler(Handle<GlobalVariable>, Handle<GlobalVariable>),
 }
 
 #[derive(Debug, Clone, Copy)]
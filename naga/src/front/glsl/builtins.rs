@@ -2027,6 +2027,7 @@ fn texture_call(
                 offset,
                 level,
                 depth_ref: comps.depth_ref,
+                residency: false,
             },
             meta,
         )?)
@@ -199,6 +199,15 @@ impl<'a> Context<'a> {
                     None,
                 )
             }
+            GlobalLookupKind::CombinedSampler(texture, sampler) => {
+                let span = self.module.global_variables.get_span(texture);
+                let expr = self.add_expression(Expression::GlobalVariable(texture), span)?;
+                let span = self.module.global_variables.get_span(sampler);
+                let sampler = self.add_expression(Expression::GlobalVariable(sampler), span)?;
+                self.samplers.insert(expr, sampler);
+
+                (expr, false, None)
+            }
             GlobalLookupKind::Constant(v, ty) => {
                 let span = self.module.constants.get_span(v);
                 (
@@ -140,8 +140,10 @@ pub struct Error {
 
 impl Error {
     /// Returns a [`SourceLocation`] for the error message.
+    ///
+    /// Returns `None` if the error isn't associated with any part of the source.
     pub fn location(&self, source: &str) -> Option<SourceLocation> {
-        Some(self.meta.location(source))
+        self.meta.is_defined().then(|| self.meta.location(source))
     }
 }
 
@@ -1362,6 +1362,8 @@ impl Frontend {
             early_depth_test: Some(crate::EarlyDepthTest { conservative: None })
                 .filter(|_| self.meta.early_fragment_tests),
             workgroup_size: self.meta.workgroup_size,
+            workgroup_size_overrides: None,
+            mesh_info: None,
             function: Function {
                 arguments,
                 expressions,
@@ -1438,6 +1440,7 @@ impl Context<'_> {
                         interpolation,
                         sampling: None,
                         second_blend_source: false,
+                        per_primitive: false,
                     };
                     location += 1;
 
@@ -1474,6 +1477,7 @@ impl Context<'_> {
                                 interpolation,
                                 sampling: None,
                                 second_blend_source: false,
+                                per_primitive: false,
                             };
                             location += 1;
                             binding
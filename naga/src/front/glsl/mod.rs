@@ -16,7 +16,10 @@ pub use ast::{Precision, Profile};
 pub use error::{Error, ErrorKind, ExpectedToken, ParseErrors};
 pub use token::TokenValue;
 
-use crate::{proc::Layouter, FastHashMap, FastHashSet, Handle, Module, ShaderStage, Span, Type};
+use crate::{
+    proc::Layouter, FastHashMap, FastHashSet, Handle, Module, ResourceBinding, ShaderStage, Span,
+    Type,
+};
 use ast::{EntryArg, FunctionDeclaration, GlobalLookup};
 use parser::ParsingContext;
 
@@ -56,6 +59,9 @@ pub struct Options {
     /// ```
     /// for each key value pair in the map.
     pub defines: FastHashMap<String, String>,
+    /// How combined image samplers like `sampler2D` are split into a texture
+    /// and a sampler.
+    pub combined_samplers: CombinedSamplerMapping,
 }
 
 impl From<ShaderStage> for Options {
@@ -63,10 +69,106 @@ impl From<ShaderStage> for Options {
         Options {
             stage,
             defines: FastHashMap::default(),
+            combined_samplers: CombinedSamplerMapping::default(),
         }
     }
 }
 
+/// Chooses the bindings of the texture and sampler that a combined image
+/// sampler uniform is split into.
+///
+/// Naga's IR has no combined image samplers, so a declaration like
+/// ```glsl
+/// layout(set = 0, binding = 3) uniform sampler2D tex;
+/// ```
+/// becomes a `tex` texture global and a `tex_sampler` sampler global, each of
+/// which needs its own binding. Uses of `tex` in texture functions sample
+/// the texture with the sampler.
+///
+/// Bindings listed in [`explicit`](Self::explicit) are used as given; the
+/// [`policy`](Self::policy) numbers all the others.
+#[derive(Clone, Debug, Default)]
+pub struct CombinedSamplerMapping {
+    /// The bindings to use for the combined sampler declared with a given
+    /// binding.
+    pub explicit: FastHashMap<ResourceBinding, SplitBinding>,
+    /// How to number combined samplers not found in `explicit`.
+    pub policy: CombinedSamplerPolicy,
+}
+
+impl CombinedSamplerMapping {
+    /// Returns the bindings for the combined sampler declared with `binding`,
+    /// or `None` if it isn't mapped or the policy's numbering overflows.
+    pub fn split(&self, binding: &ResourceBinding) -> Option<SplitBinding> {
+        if let Some(split) = self.explicit.get(binding) {
+            return Some(split.clone());
+        }
+
+        let ResourceBinding { group, binding } = *binding;
+        let (texture, sampler) = match self.policy {
+            CombinedSamplerPolicy::Reject => return None,
+            CombinedSamplerPolicy::SamplerOffset(offset) => (
+                ResourceBinding { group, binding },
+                ResourceBinding {
+                    group,
+                    binding: binding.checked_add(offset)?,
+                },
+            ),
+            CombinedSamplerPolicy::SamplerGroup(sampler_group) => (
+                ResourceBinding { group, binding },
+                ResourceBinding {
+                    group: sampler_group,
+                    binding,
+                },
+            ),
+            CombinedSamplerPolicy::Interleaved => {
+                let texture = binding.checked_mul(2)?;
+                (
+                    ResourceBinding {
+                        group,
+                        binding: texture,
+                    },
+                    ResourceBinding {
+                        group,
+                        binding: texture.checked_add(1)?,
+                    },
+                )
+            }
+        };
+
+        Some(SplitBinding { texture, sampler })
+    }
+}
+
+/// The bindings of the two globals a combined image sampler is split into.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SplitBinding {
+    pub texture: ResourceBinding,
+    pub sampler: ResourceBinding,
+}
+
+/// How [`CombinedSamplerMapping`] numbers combined image samplers that don't
+/// have an explicit mapping.
+///
+/// The texture and sampler both stay in the declared set unless stated
+/// otherwise.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CombinedSamplerPolicy {
+    /// Combined image samplers must be mapped explicitly; any other is an
+    /// error.
+    #[default]
+    Reject,
+    /// The texture keeps the declared binding and the sampler's binding is
+    /// the declared one plus the given offset.
+    SamplerOffset(u32),
+    /// The texture keeps the declared binding and the sampler uses the same
+    /// binding in the given set.
+    SamplerGroup(u32),
+    /// Declared binding `n` becomes texture binding `2n` and sampler binding
+    /// `2n + 1`.
+    Interleaved,
+}
+
 /// Additional information about the GLSL shader.
 ///
 /// Stores additional information about the GLSL shader which might not be
@@ -173,6 +275,7 @@ pub struct Frontend {
     entry_args: Vec<EntryArg>,
 
     layouter: Layouter,
+    combined_samplers: CombinedSamplerMapping,
 
     errors: Vec<Error>,
 }
@@ -198,6 +301,8 @@ impl Frontend {
         source: &str,
     ) -> std::result::Result<Module, ParseErrors> {
         self.reset(options.stage);
+        self.combined_samplers
+            .clone_from(&options.combined_samplers);
 
         let lexer = lex::Lexer::new(source, &options.defines);
         let mut ctx = ParsingContext::new(lexer);
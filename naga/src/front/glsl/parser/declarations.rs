@@ -8,7 +8,7 @@ use crate::{
         error::ExpectedToken,
         offset,
         token::{Token, TokenValue},
-        types::scalar_components,
+        types::{parse_combined_sampler, scalar_components},
         variables::{GlobalOrConstant, VarDeclaration},
         Error, ErrorKind, Frontend, Span,
     },
@@ -312,6 +312,35 @@ impl<'source> ParsingContext<'source> {
         if self.peek_type_qualifier(frontend) || self.peek_type_name(frontend) {
             let mut qualifiers = self.parse_type_qualifiers(frontend, ctx)?;
 
+            let combined_sampler = match self.peek(frontend) {
+                Some(&Token {
+                    value: TokenValue::Identifier(ref ident),
+                    ..
+                }) if external => parse_combined_sampler(ident),
+                _ => None,
+            };
+            if let Some((image, comparison)) = combined_sampler {
+                // This branch handles combined image samplers like
+                // ```glsl
+                // layout(binding = 0) uniform sampler2D tex;
+                // ```
+                let mut meta = self.bump(frontend)?.meta;
+                let (name, end_meta) = self.expect_ident(frontend)?;
+                meta.subsume(end_meta);
+                self.expect(frontend, TokenValue::Semicolon)?;
+
+                frontend.add_combined_sampler(
+                    ctx,
+                    &mut qualifiers,
+                    image,
+                    comparison,
+                    name,
+                    meta,
+                )?;
+
+                return Ok(Some(meta));
+            }
+
             if self.peek_type_name(frontend) {
                 // This branch handles variables and function prototypes and if
                 // external is true also function definitions
@@ -228,7 +228,7 @@ impl<'source> ParsingContext<'source> {
                         }
                         TokenValue::Buffer => {
                             StorageQualifier::AddressSpace(AddressSpace::Storage {
-                                access: crate::StorageAccess::all(),
+                                access: crate::StorageAccess::LOAD | crate::StorageAccess::STORE,
                             })
                         }
                         _ => unreachable!(),
@@ -274,10 +274,12 @@ impl<'source> ParsingContext<'source> {
                     qualifiers.precision = Some((p, token.meta));
                 }
                 TokenValue::MemoryQualifier(access) => {
+                    const LOAD_STORE: crate::StorageAccess =
+                        crate::StorageAccess::LOAD.union(crate::StorageAccess::STORE);
                     let storage_access = qualifiers
                         .storage_access
-                        .get_or_insert((crate::StorageAccess::all(), Span::default()));
-                    if !storage_access.0.contains(!access) {
+                        .get_or_insert((LOAD_STORE, Span::default()));
+                    if !storage_access.0.contains(LOAD_STORE.difference(access)) {
                         frontend.errors.push(Error {
                             kind: ErrorKind::SemanticError(
                                 "The same memory qualifier can only be used once".into(),
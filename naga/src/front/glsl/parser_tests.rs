@@ -3,9 +3,9 @@ use super::{
     error::ExpectedToken,
     error::{Error, ErrorKind, ParseErrors},
     token::TokenValue,
-    Frontend, Options, Span,
+    CombinedSamplerMapping, CombinedSamplerPolicy, Frontend, Options, Span, SplitBinding,
 };
-use crate::ShaderStage;
+use crate::{FastHashMap, ResourceBinding, ShaderStage};
 use pp_rs::token::PreprocessorError;
 
 #[test]
@@ -352,6 +352,96 @@ fn textures() {
         .unwrap();
 }
 
+#[test]
+fn combined_samplers() {
+    let source = r#"
+        #version 450
+        layout(location = 0) in vec2 v_uv;
+        layout(location = 0) out vec4 o_color;
+        layout(set = 1, binding = 1) uniform sampler2D tex;
+        layout(set = 1, binding = 2) uniform sampler2DShadow shadow;
+        void main() {
+            o_color = texture(tex, v_uv);
+            o_color.a *= texture(shadow, vec3(v_uv, 0.5));
+        }
+        "#;
+
+    let bindings = |combined_samplers| {
+        let mut frontend = Frontend::default();
+        let options = Options {
+            combined_samplers,
+            ..Options::from(ShaderStage::Fragment)
+        };
+        let module = frontend.parse(&options, source).unwrap();
+        module
+            .global_variables
+            .iter()
+            .filter_map(|(_, var)| {
+                let binding = var.binding.as_ref()?;
+                Some((var.name.clone().unwrap(), binding.group, binding.binding))
+            })
+            .collect::<Vec<_>>()
+    };
+    let names = |bindings: &[(u32, u32); 4]| {
+        ["tex", "tex_sampler", "shadow", "shadow_sampler"]
+            .iter()
+            .zip(bindings)
+            .map(|(&name, &(group, binding))| (name.to_string(), group, binding))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        bindings(CombinedSamplerMapping {
+            explicit: FastHashMap::default(),
+            policy: CombinedSamplerPolicy::SamplerGroup(3),
+        }),
+        names(&[(1, 1), (3, 1), (1, 2), (3, 2)])
+    );
+    assert_eq!(
+        bindings(CombinedSamplerMapping {
+            explicit: FastHashMap::default(),
+            policy: CombinedSamplerPolicy::Interleaved,
+        }),
+        names(&[(1, 2), (1, 3), (1, 4), (1, 5)])
+    );
+
+    let mut explicit = FastHashMap::default();
+    explicit.insert(
+        ResourceBinding {
+            group: 1,
+            binding: 2,
+        },
+        SplitBinding {
+            texture: ResourceBinding {
+                group: 0,
+                binding: 7,
+            },
+            sampler: ResourceBinding {
+                group: 0,
+                binding: 8,
+            },
+        },
+    );
+    assert_eq!(
+        bindings(CombinedSamplerMapping {
+            explicit,
+            policy: CombinedSamplerPolicy::SamplerOffset(10),
+        }),
+        names(&[(1, 1), (1, 11), (0, 7), (0, 8)])
+    );
+
+    // Without a mapping, combined samplers are rejected.
+    let errors = Frontend::default()
+        .parse(&Options::from(ShaderStage::Fragment), source)
+        .err()
+        .unwrap()
+        .errors;
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|error| matches!(error.kind, ErrorKind::SemanticError(_))));
+}
+
 #[test]
 fn functions() {
     let mut frontend = Frontend::default();
@@ -154,7 +154,7 @@ pub fn parse_type(type_name: &str) -> Option<Type> {
 
                 let class = ImageClass::Storage {
                     format: crate::StorageFormat::R8Uint,
-                    access: crate::StorageAccess::all(),
+                    access: crate::StorageAccess::LOAD | crate::StorageAccess::STORE,
                 };
 
                 // TODO: glsl support multisampled storage images, naga doesn't
@@ -189,6 +189,42 @@ pub fn parse_type(type_name: &str) -> Option<Type> {
     }
 }
 
+/// Parses the name of a combined image sampler type like `sampler2D`,
+/// returning the image type it samples and whether its sampler is a
+/// comparison sampler.
+pub fn parse_combined_sampler(type_name: &str) -> Option<(TypeInner, bool)> {
+    let (prefix, rest) = type_name.split_once("sampler")?;
+    let (rest, comparison) = match rest.strip_suffix("Shadow") {
+        Some(rest) if prefix.is_empty() => (rest, true),
+        Some(_) => return None,
+        None => (rest, false),
+    };
+
+    let inner = parse_type(&format!("{prefix}texture{rest}"))?.inner;
+    match inner {
+        TypeInner::Image {
+            dim,
+            arrayed,
+            class: ImageClass::Sampled { multi, .. },
+        } if comparison => {
+            if multi || dim == ImageDimension::D3 {
+                return None;
+            }
+            let class = ImageClass::Depth { multi: false };
+            Some((
+                TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                },
+                true,
+            ))
+        }
+        TypeInner::Image { .. } => Some((inner, false)),
+        _ => None,
+    }
+}
+
 pub const fn scalar_components(ty: &TypeInner) -> Option<Scalar> {
     match *ty {
         TypeInner::Scalar(scalar)
@@ -451,6 +451,7 @@ impl Frontend {
                         interpolation,
                         sampling,
                         second_blend_source: false,
+                        per_primitive: false,
                     },
                     handle,
                     storage,
@@ -599,6 +600,108 @@ impl Frontend {
         Ok(ret)
     }
 
+    /// Declares a combined image sampler uniform, like `sampler2D`, as a
+    /// texture and a sampler with the bindings chosen by the
+    /// [`CombinedSamplerMapping`](super::CombinedSamplerMapping).
+    pub(crate) fn add_combined_sampler(
+        &mut self,
+        ctx: &mut Context,
+        qualifiers: &mut TypeQualifiers,
+        image: TypeInner,
+        comparison: bool,
+        name: String,
+        meta: Span,
+    ) -> Result<()> {
+        if qualifiers.storage.0 != StorageQualifier::AddressSpace(AddressSpace::Uniform) {
+            self.errors.push(Error {
+                kind: ErrorKind::SemanticError(
+                    "combined image samplers must be declared as uniforms".into(),
+                ),
+                meta,
+            });
+        }
+
+        let binding = qualifiers.uint_layout_qualifier("binding", &mut self.errors);
+        if binding.is_none() {
+            self.errors.push(Error {
+                kind: ErrorKind::SemanticError(
+                    "uniform/buffer blocks require layout(binding=X)".into(),
+                ),
+                meta,
+            });
+        }
+        let set = qualifiers.uint_layout_qualifier("set", &mut self.errors);
+        let split = binding.and_then(|binding| {
+            let declared = ResourceBinding {
+                group: set.unwrap_or(0),
+                binding,
+            };
+            let split = self.combined_samplers.split(&declared);
+            if split.is_none() {
+                self.errors.push(Error {
+                    kind: ErrorKind::SemanticError(
+                        format!(
+                            "Combined image sampler \"{name}\" (set {}, binding {}) has no \
+                            mapping to a texture and sampler binding",
+                            declared.group, declared.binding
+                        )
+                        .into(),
+                    ),
+                    meta,
+                });
+            }
+            split
+        });
+
+        let texture_ty = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: image,
+            },
+            meta,
+        );
+        let sampler_ty = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Sampler { comparison },
+            },
+            meta,
+        );
+
+        let texture = ctx.module.global_variables.append(
+            GlobalVariable {
+                name: Some(name.clone()),
+                space: AddressSpace::Handle,
+                binding: split.as_ref().map(|split| split.texture.clone()),
+                ty: texture_ty,
+                init: None,
+            },
+            meta,
+        );
+        let sampler = ctx.module.global_variables.append(
+            GlobalVariable {
+                name: Some(format!("{name}_sampler")),
+                space: AddressSpace::Handle,
+                binding: split.map(|split| split.sampler),
+                ty: sampler_ty,
+                init: None,
+            },
+            meta,
+        );
+
+        let lookup = GlobalLookup {
+            kind: GlobalLookupKind::CombinedSampler(texture, sampler),
+            entry_arg: None,
+            mutable: false,
+        };
+        ctx.add_global(&name, lookup)?;
+        self.global_variables.push((name, lookup));
+
+        qualifiers.unused_errors(&mut self.errors);
+
+        Ok(())
+    }
+
     pub(crate) fn add_local_var(
         &mut self,
         ctx: &mut Context,
@@ -0,0 +1,293 @@
+/*!
+Syntax trees for HLSL function bodies.
+
+Global declarations are lowered as soon as they're parsed, but function
+bodies are kept around as syntax trees until the whole source has been seen,
+since how some resources are declared in the IR depends on how the functions
+use them. See [`ResourceUsage`](super::lower::ResourceUsage).
+*/
+
+use crate::{Handle, ImageDimension, Interpolation, Sampling, ShaderStage, Span, Type};
+
+/// The type of an HLSL declaration.
+///
+/// Resources have no counterpart in the IR until the global variable
+/// declaring them is created, so they're kept separate from value types.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ty {
+    Void,
+    Value(Handle<Type>),
+    /// `Texture2D<T>` and friends. `element` is the type given as the
+    /// template argument, `float4` if omitted.
+    Texture {
+        dim: ImageDimension,
+        arrayed: bool,
+        multi: bool,
+        element: Handle<Type>,
+    },
+    /// `RWTexture2D<T>` and friends.
+    RwTexture {
+        dim: ImageDimension,
+        arrayed: bool,
+        element: Handle<Type>,
+    },
+    Sampler {
+        comparison: bool,
+    },
+    /// `StructuredBuffer<T>` or `RWStructuredBuffer<T>`.
+    StructuredBuffer {
+        element: Handle<Type>,
+        writable: bool,
+    },
+    /// `ByteAddressBuffer` or `RWByteAddressBuffer`.
+    ByteAddressBuffer {
+        writable: bool,
+    },
+    /// `ConstantBuffer<T>`.
+    ConstantBuffer {
+        element: Handle<Type>,
+    },
+}
+
+impl Ty {
+    pub const fn value(self) -> Option<Handle<Type>> {
+        match self {
+            Ty::Value(ty) => Some(ty),
+            _ => None,
+        }
+    }
+
+    pub const fn is_resource(self) -> bool {
+        !matches!(self, Ty::Void | Ty::Value(_))
+    }
+}
+
+/// A semantic such as `SV_Position` or `TEXCOORD3`.
+#[derive(Clone, Debug)]
+pub struct Semantic {
+    /// The semantic's name without its index, in upper case.
+    pub name: String,
+    /// The semantic's index, `0` if none was written.
+    pub index: u32,
+    pub meta: Span,
+}
+
+/// How a value crosses a shader stage boundary.
+#[derive(Clone, Debug, Default)]
+pub struct IoAttributes {
+    pub semantic: Option<Semantic>,
+    pub interpolation: Option<Interpolation>,
+    pub sampling: Option<Sampling>,
+    /// An explicit location from `[[vk::location(N)]]`.
+    pub location: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterQualifier {
+    In,
+    Out,
+    InOut,
+}
+
+impl ParameterQualifier {
+    pub const fn is_output(self) -> bool {
+        matches!(self, ParameterQualifier::Out | ParameterQualifier::InOut)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub ty: Ty,
+    pub qualifier: ParameterQualifier,
+    pub io: IoAttributes,
+    pub meta: Span,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FunctionAttributes {
+    /// From `[numthreads(x, y, z)]`.
+    pub workgroup_size: Option<[u32; 3]>,
+    /// From `[shader("stage")]`.
+    pub stage: Option<ShaderStage>,
+    /// From `[earlydepthstencil]`.
+    pub early_depth_test: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionDecl {
+    pub name: String,
+    pub result: Ty,
+    pub result_io: IoAttributes,
+    pub parameters: Vec<Parameter>,
+    pub attributes: FunctionAttributes,
+    pub body: Vec<Stmt>,
+    pub meta: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    ExclusiveOr,
+    InclusiveOr,
+    LogicalAnd,
+    LogicalOr,
+    ShiftLeft,
+    ShiftRight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    LogicalNot,
+    BitwiseNot,
+}
+
+#[derive(Clone, Debug)]
+pub enum Literal {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Double(f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub meta: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExprKind {
+    Literal(Literal),
+    Ident(String),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    /// `++x`, `x--` and friends.
+    IncDec {
+        increment: bool,
+        postfix: bool,
+        expr: Box<Expr>,
+    },
+    /// `lhs = rhs`, or `lhs op= rhs` if `op` is given.
+    Assign {
+        op: Option<BinaryOp>,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Select {
+        condition: Box<Expr>,
+        accept: Box<Expr>,
+        reject: Box<Expr>,
+    },
+    /// A call to a user function or an intrinsic.
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// A constructor like `float4(a, b)`.
+    Construct {
+        ty: Handle<Type>,
+        args: Vec<Expr>,
+    },
+    /// A C-style cast, `(float3)x`.
+    Cast {
+        ty: Handle<Type>,
+        expr: Box<Expr>,
+    },
+    /// Field access or swizzle.
+    Member {
+        base: Box<Expr>,
+        field: String,
+    },
+    Index {
+        base: Box<Expr>,
+        index: Box<Expr>,
+    },
+    /// A method call on a resource, like `texture.Sample(sampler, uv)`.
+    Method {
+        base: Box<Expr>,
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// A brace-enclosed initializer list, `{ 1, 2, 3 }`.
+    InitList(Vec<Expr>),
+    /// The comma operator, `a, b`.
+    Sequence(Vec<Expr>),
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalDecl {
+    pub name: String,
+    pub ty: Handle<Type>,
+    pub init: Option<Expr>,
+    pub meta: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct SwitchCase {
+    /// `None` for the `default` case.
+    pub value: Option<Expr>,
+    pub body: Vec<Stmt>,
+    pub meta: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub meta: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum StmtKind {
+    Declare(Vec<LocalDecl>),
+    Expr(Expr),
+    Block(Vec<Stmt>),
+    If {
+        condition: Expr,
+        accept: Box<Stmt>,
+        reject: Option<Box<Stmt>>,
+    },
+    For {
+        init: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        update: Option<Expr>,
+        body: Box<Stmt>,
+    },
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
+    DoWhile {
+        body: Box<Stmt>,
+        condition: Expr,
+    },
+    Switch {
+        selector: Expr,
+        cases: Vec<SwitchCase>,
+    },
+    Break,
+    Continue,
+    Return(Option<Expr>),
+    Discard,
+    Empty,
+}
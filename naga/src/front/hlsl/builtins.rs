@@ -0,0 +1,1248 @@
+/*!
+HLSL intrinsic functions and resource methods.
+*/
+
+use super::{
+    ast::{Expr, Ty},
+    error::{Error, ErrorKind},
+    lower::{Context, Value},
+    types::scalar_components,
+    Result,
+};
+use crate::{
+    AtomicFunction, Barrier, BinaryOperator, Block, DerivativeAxis, DerivativeControl, Expression,
+    Handle, ImageClass, ImageDimension, MathFunction, RelationalFunction, SampleLevel, Scalar,
+    ScalarKind, Span, Statement, SwizzleComponent, Type, TypeInner, VectorSize,
+};
+
+/// How the arguments of a math intrinsic are converted before the call.
+#[derive(Clone, Copy, PartialEq)]
+enum Args {
+    /// All arguments are converted to a common type, and scalars are
+    /// splatted to match any vector arguments.
+    Uniform,
+    /// Like `Uniform`, but the common type is always floating point.
+    Float,
+    /// The arguments are passed through unchanged.
+    AsIs,
+}
+
+fn math_function(name: &str) -> Option<(MathFunction, Args)> {
+    use MathFunction as Mf;
+
+    Some(match name {
+        "abs" => (Mf::Abs, Args::Uniform),
+        "min" => (Mf::Min, Args::Uniform),
+        "max" => (Mf::Max, Args::Uniform),
+        "clamp" => (Mf::Clamp, Args::Uniform),
+        "sign" => (Mf::Sign, Args::Uniform),
+        "saturate" => (Mf::Saturate, Args::Float),
+        "cos" => (Mf::Cos, Args::Float),
+        "cosh" => (Mf::Cosh, Args::Float),
+        "sin" => (Mf::Sin, Args::Float),
+        "sinh" => (Mf::Sinh, Args::Float),
+        "tan" => (Mf::Tan, Args::Float),
+        "tanh" => (Mf::Tanh, Args::Float),
+        "acos" => (Mf::Acos, Args::Float),
+        "asin" => (Mf::Asin, Args::Float),
+        "atan" => (Mf::Atan, Args::Float),
+        "atan2" => (Mf::Atan2, Args::Float),
+        "radians" => (Mf::Radians, Args::Float),
+        "degrees" => (Mf::Degrees, Args::Float),
+        "ceil" => (Mf::Ceil, Args::Float),
+        "floor" => (Mf::Floor, Args::Float),
+        "round" => (Mf::Round, Args::Float),
+        "frac" => (Mf::Fract, Args::Float),
+        "trunc" => (Mf::Trunc, Args::Float),
+        "exp" => (Mf::Exp, Args::Float),
+        "exp2" => (Mf::Exp2, Args::Float),
+        "log" => (Mf::Log, Args::Float),
+        "log2" => (Mf::Log2, Args::Float),
+        "pow" => (Mf::Pow, Args::Float),
+        "sqrt" => (Mf::Sqrt, Args::Float),
+        "rsqrt" => (Mf::InverseSqrt, Args::Float),
+        "lerp" => (Mf::Mix, Args::Float),
+        "step" => (Mf::Step, Args::Float),
+        "smoothstep" => (Mf::SmoothStep, Args::Float),
+        "fma" => (Mf::Fma, Args::Float),
+        "dot" => (Mf::Dot, Args::Uniform),
+        "cross" => (Mf::Cross, Args::Float),
+        "distance" => (Mf::Distance, Args::Float),
+        "length" => (Mf::Length, Args::Float),
+        "normalize" => (Mf::Normalize, Args::Float),
+        "faceforward" => (Mf::FaceForward, Args::Float),
+        "reflect" => (Mf::Reflect, Args::Float),
+        "refract" => (Mf::Refract, Args::AsIs),
+        "ldexp" => (Mf::Ldexp, Args::AsIs),
+        "determinant" => (Mf::Determinant, Args::AsIs),
+        "transpose" => (Mf::Transpose, Args::AsIs),
+        "countbits" => (Mf::CountOneBits, Args::AsIs),
+        "reversebits" => (Mf::ReverseBits, Args::AsIs),
+        "firstbithigh" => (Mf::FindMsb, Args::AsIs),
+        "firstbitlow" => (Mf::FindLsb, Args::AsIs),
+        _ => return None,
+    })
+}
+
+fn atomic_function(name: &str) -> Option<AtomicFunction> {
+    Some(match name {
+        "Add" => AtomicFunction::Add,
+        "And" => AtomicFunction::And,
+        "Or" => AtomicFunction::InclusiveOr,
+        "Xor" => AtomicFunction::ExclusiveOr,
+        "Min" => AtomicFunction::Min,
+        "Max" => AtomicFunction::Max,
+        "Exchange" => AtomicFunction::Exchange { compare: None },
+        _ => return None,
+    })
+}
+
+/// The number of coordinates needed to address a texel of an image with
+/// dimension `dim`, not counting the array layer.
+const fn coordinate_count(dim: ImageDimension) -> u32 {
+    match dim {
+        ImageDimension::D1 => 1,
+        ImageDimension::D2 => 2,
+        ImageDimension::D3 | ImageDimension::Cube => 3,
+    }
+}
+
+impl<'a> Context<'a> {
+    fn check_arg_count(
+        &self,
+        name: &str,
+        args: &[Expr],
+        range: std::ops::RangeInclusive<usize>,
+        meta: Span,
+    ) -> Result<()> {
+        if range.contains(&args.len()) {
+            return Ok(());
+        }
+        let expected = if range.start() == range.end() {
+            range.start().to_string()
+        } else {
+            format!("{} to {}", range.start(), range.end())
+        };
+        Err(Error::semantic(
+            format!(
+                "`{name}` takes {expected} arguments, but {} were given",
+                args.len()
+            ),
+            meta,
+        ))
+    }
+
+    fn lower_args(&mut self, args: &[Expr]) -> Result<Vec<Handle<Expression>>> {
+        args.iter().map(|arg| self.lower_rvalue(arg)).collect()
+    }
+
+    /// Convert `values` to a common scalar type and vector size.
+    fn unify_all(
+        &mut self,
+        values: &mut [Handle<Expression>],
+        float: bool,
+        meta: Span,
+    ) -> Result<()> {
+        let mut scalar = None::<Scalar>;
+        let mut size = None;
+        for &value in values.iter() {
+            let inner = self.resolve_type(value, meta)?;
+            let Some(current) = scalar_components(&inner) else {
+                continue;
+            };
+            scalar = Some(match scalar {
+                Some(scalar)
+                    if super::types::type_power(scalar) >= super::types::type_power(current) =>
+                {
+                    scalar
+                }
+                _ => current,
+            });
+            if let TypeInner::Vector { size: current, .. } = inner {
+                size = Some(match size {
+                    Some(size) if size as u8 >= current as u8 => size,
+                    _ => current,
+                });
+            }
+        }
+        let Some(mut scalar) = scalar else {
+            return Ok(());
+        };
+        if float && scalar.kind != ScalarKind::Float {
+            scalar = Scalar::F32;
+        }
+        for value in values.iter_mut() {
+            *value = self.convert_scalar(*value, scalar, meta)?;
+            if let Some(size) = size {
+                *value = self.splat(*value, size, meta)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn math(
+        &mut self,
+        fun: MathFunction,
+        args: &[Handle<Expression>],
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        self.add_expression(
+            Expression::Math {
+                fun,
+                arg: args[0],
+                arg1: args.get(1).copied(),
+                arg2: args.get(2).copied(),
+                arg3: args.get(3).copied(),
+            },
+            meta,
+        )
+    }
+
+    fn multiply(
+        &mut self,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        self.add_expression(
+            Expression::Binary {
+                op: BinaryOperator::Multiply,
+                left,
+                right,
+            },
+            meta,
+        )
+    }
+
+    /// Lower a call to the intrinsic `name`.
+    ///
+    /// Returns `None` if `name` isn't an intrinsic, or `Some` with the
+    /// call's value, if any.
+    pub fn builtin_call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Option<Handle<Expression>>>> {
+        if let Some(op) = name.strip_prefix("Interlocked") {
+            let dest = args
+                .first()
+                .ok_or_else(|| Error::semantic(format!("`{name}` needs a destination"), meta))?;
+            let pointer = self.lower_lvalue(dest)?;
+            return self
+                .interlocked(name, op, pointer, &args[1..], meta)
+                .map(Some);
+        }
+
+        let barrier = match name {
+            "GroupMemoryBarrier" | "GroupMemoryBarrierWithGroupSync" => Some(Barrier::WORK_GROUP),
+            "DeviceMemoryBarrier" | "DeviceMemoryBarrierWithGroupSync" => Some(Barrier::STORAGE),
+            "AllMemoryBarrier" | "AllMemoryBarrierWithGroupSync" => {
+                Some(Barrier::STORAGE | Barrier::WORK_GROUP)
+            }
+            _ => None,
+        };
+        if let Some(barrier) = barrier {
+            // Naga only has control barriers, which also order memory.
+            self.check_arg_count(name, args, 0..=0, meta)?;
+            self.add_statement(Statement::Barrier(barrier), meta);
+            return Ok(Some(None));
+        }
+
+        if let Some((fun, kind)) = math_function(name) {
+            let mut values = self.lower_args(args)?;
+            if values.is_empty() {
+                return Err(Error::semantic(format!("`{name}` needs arguments"), meta));
+            }
+            if kind != Args::AsIs {
+                self.unify_all(&mut values, kind == Args::Float, meta)?;
+            }
+            // `dot` is only defined on vectors in naga.
+            if fun == MathFunction::Dot {
+                if let TypeInner::Scalar(_) = self.resolve_type(values[0], meta)? {
+                    return Ok(Some(Some(self.multiply(values[0], values[1], meta)?)));
+                }
+            }
+            return Ok(Some(Some(self.math(fun, &values, meta)?)));
+        }
+
+        let mut values = self.lower_args(args)?;
+        let value = match name {
+            "mad" => {
+                self.check_arg_count(name, args, 3..=3, meta)?;
+                self.unify_all(&mut values, false, meta)?;
+                let scalar = scalar_components(&self.resolve_type(values[0], meta)?);
+                if let Some(Scalar {
+                    kind: ScalarKind::Float,
+                    ..
+                }) = scalar
+                {
+                    self.math(MathFunction::Fma, &values, meta)?
+                } else {
+                    let product = self.multiply(values[0], values[1], meta)?;
+                    self.add_expression(
+                        Expression::Binary {
+                            op: BinaryOperator::Add,
+                            left: product,
+                            right: values[2],
+                        },
+                        meta,
+                    )?
+                }
+            }
+            "fmod" => {
+                self.check_arg_count(name, args, 2..=2, meta)?;
+                // Naga's `%` on floats truncates, like HLSL's `fmod`.
+                self.unify_all(&mut values, true, meta)?;
+                self.add_expression(
+                    Expression::Binary {
+                        op: BinaryOperator::Modulo,
+                        left: values[0],
+                        right: values[1],
+                    },
+                    meta,
+                )?
+            }
+            "log10" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                self.unify_all(&mut values, true, meta)?;
+                let log = self.math(MathFunction::Log, &values, meta)?;
+                let factor = self.scalar_literal(Scalar::F32, std::f64::consts::LOG10_E, meta)?;
+                self.multiply(log, factor, meta)?
+            }
+            "rcp" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                self.unify_all(&mut values, true, meta)?;
+                let scalar = scalar_components(&self.resolve_type(values[0], meta)?)
+                    .ok_or_else(|| Error::semantic("`rcp` needs a numeric argument", meta))?;
+                let one = self.scalar_literal(scalar, 1.0, meta)?;
+                let mut pair = [one, values[0]];
+                self.unify_all(&mut pair, true, meta)?;
+                self.add_expression(
+                    Expression::Binary {
+                        op: BinaryOperator::Divide,
+                        left: pair[0],
+                        right: pair[1],
+                    },
+                    meta,
+                )?
+            }
+            "isnan" | "isinf" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                let fun = if name == "isnan" {
+                    RelationalFunction::IsNan
+                } else {
+                    RelationalFunction::IsInf
+                };
+                self.add_expression(
+                    Expression::Relational {
+                        fun,
+                        argument: values[0],
+                    },
+                    meta,
+                )?
+            }
+            "all" | "any" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                let value = self.to_bool(values[0], meta)?;
+                match self.resolve_type(value, meta)? {
+                    TypeInner::Vector { .. } => {
+                        let fun = if name == "all" {
+                            RelationalFunction::All
+                        } else {
+                            RelationalFunction::Any
+                        };
+                        self.add_expression(
+                            Expression::Relational {
+                                fun,
+                                argument: value,
+                            },
+                            meta,
+                        )?
+                    }
+                    _ => value,
+                }
+            }
+            "asfloat" | "asint" | "asuint" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                let kind = match name {
+                    "asfloat" => ScalarKind::Float,
+                    "asint" => ScalarKind::Sint,
+                    _ => ScalarKind::Uint,
+                };
+                self.add_expression(
+                    Expression::As {
+                        expr: values[0],
+                        kind,
+                        convert: None,
+                    },
+                    meta,
+                )?
+            }
+            "ddx" | "ddy" | "fwidth" | "ddx_coarse" | "ddy_coarse" | "ddx_fine" | "ddy_fine" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                self.unify_all(&mut values, true, meta)?;
+                let axis = match name {
+                    "fwidth" => DerivativeAxis::Width,
+                    _ if name.starts_with("ddx") => DerivativeAxis::X,
+                    _ => DerivativeAxis::Y,
+                };
+                let ctrl = if name.ends_with("_coarse") {
+                    DerivativeControl::Coarse
+                } else if name.ends_with("_fine") {
+                    DerivativeControl::Fine
+                } else {
+                    DerivativeControl::None
+                };
+                self.add_expression(
+                    Expression::Derivative {
+                        axis,
+                        ctrl,
+                        expr: values[0],
+                    },
+                    meta,
+                )?
+            }
+            "mul" => {
+                self.check_arg_count(name, args, 2..=2, meta)?;
+                let left = self.resolve_type(values[0], meta)?;
+                let right = self.resolve_type(values[1], meta)?;
+                match (left, right) {
+                    (TypeInner::Vector { .. }, TypeInner::Vector { .. }) => {
+                        self.unify_all(&mut values, false, meta)?;
+                        self.math(MathFunction::Dot, &values, meta)?
+                    }
+                    (TypeInner::Scalar(_), _) | (_, TypeInner::Scalar(_)) => {
+                        let (left, right) = self.unify(values[0], values[1], meta)?;
+                        self.multiply(left, right, meta)?
+                    }
+                    // With HLSL's rows stored as naga's columns, `mul(a, b)`
+                    // is naga's `a * b` for all other combinations.
+                    _ => self.multiply(values[0], values[1], meta)?,
+                }
+            }
+            "clip" => {
+                self.check_arg_count(name, args, 1..=1, meta)?;
+                let value = values[0];
+                let inner = self.resolve_type(value, meta)?;
+                let scalar = scalar_components(&inner)
+                    .ok_or_else(|| Error::semantic("`clip` needs a numeric argument", meta))?;
+                let zero = self.scalar_literal(scalar, 0.0, meta)?;
+                let zero = match inner {
+                    TypeInner::Vector { size, .. } => self.splat(zero, size, meta)?,
+                    _ => zero,
+                };
+                let mut condition = self.add_expression(
+                    Expression::Binary {
+                        op: BinaryOperator::Less,
+                        left: value,
+                        right: zero,
+                    },
+                    meta,
+                )?;
+                if let TypeInner::Vector { .. } = inner {
+                    condition = self.add_expression(
+                        Expression::Relational {
+                            fun: RelationalFunction::Any,
+                            argument: condition,
+                        },
+                        meta,
+                    )?;
+                }
+                let mut accept = Block::new();
+                accept.push(Statement::Kill, meta);
+                self.add_statement(
+                    Statement::If {
+                        condition,
+                        accept,
+                        reject: Block::new(),
+                    },
+                    meta,
+                );
+                return Ok(Some(None));
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(Some(value)))
+    }
+
+    /// Lower `Interlocked<op>(dest, ...)`, where `pointer` points to `dest`.
+    fn interlocked(
+        &mut self,
+        name: &str,
+        op: &str,
+        pointer: Handle<Expression>,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let scalar = match self.resolve_type(pointer, meta)? {
+            TypeInner::Pointer { base, .. } => match self.module.types[base].inner {
+                TypeInner::Atomic(scalar) => Some(scalar),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::semantic(
+                format!(
+                    "the destination of `{name}` must be an int or uint in a \
+                     RWStructuredBuffer, RWByteAddressBuffer or groupshared variable"
+                ),
+                meta,
+            )
+        })?;
+
+        let (fun, value, original, comparison) = match op {
+            "CompareExchange" | "CompareStore" => {
+                let count = if op == "CompareStore" { 2 } else { 3 };
+                self.check_arg_count(name, args, count..=count, meta)?;
+                let compare = self.lower_rvalue(&args[0])?;
+                let compare = self.convert_scalar(compare, scalar, meta)?;
+                let value = self.lower_rvalue(&args[1])?;
+                let original = args.get(2);
+                let fun = AtomicFunction::Exchange {
+                    compare: Some(compare),
+                };
+                (fun, value, original, true)
+            }
+            _ => {
+                let fun = atomic_function(op).ok_or_else(|| Error {
+                    kind: ErrorKind::UnknownFunction(name.to_string()),
+                    meta,
+                })?;
+                let count = if op == "Exchange" { 2..=2 } else { 1..=2 };
+                self.check_arg_count(name, args, count, meta)?;
+                let value = self.lower_rvalue(&args[0])?;
+                (fun, value, args.get(1), false)
+            }
+        };
+        let value = self.convert_scalar(value, scalar, meta)?;
+
+        let ty = if comparison {
+            self.module.generate_predeclared_type(
+                crate::PredeclaredType::AtomicCompareExchangeWeakResult(scalar),
+            )
+        } else {
+            self.type_handle(TypeInner::Scalar(scalar))
+        };
+        let result = self
+            .add_statement_with_result(
+                Some(Expression::AtomicResult { ty, comparison }),
+                |result| Statement::Atomic {
+                    pointer,
+                    fun,
+                    value,
+                    result: result.unwrap(),
+                },
+                meta,
+            )
+            .unwrap();
+
+        if let Some(original) = original {
+            let target = self.lower_lvalue(original)?;
+            let old = if comparison {
+                self.add_expression(
+                    Expression::AccessIndex {
+                        base: result,
+                        index: 0,
+                    },
+                    meta,
+                )?
+            } else {
+                result
+            };
+            self.store(target, old, meta)?;
+        }
+        Ok(None)
+    }
+
+    /// Lower a method call on a resource.
+    pub fn method(
+        &mut self,
+        base: &Expr,
+        name: &str,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let ty = self.resource(base).ok_or_else(|| {
+            Error::semantic(format!("`{name}` can only be called on a resource"), meta)
+        })?;
+        match ty {
+            Ty::Texture { element, .. } => {
+                let image = self.lower_rvalue(base)?;
+                self.texture_method(image, element, name, args, meta)
+            }
+            Ty::RwTexture { element, .. } => {
+                let image = self.lower_rvalue(base)?;
+                match name {
+                    "Load" => {
+                        self.check_arg_count(name, args, 1..=1, meta)?;
+                        let coordinate = self.lower_rvalue(&args[0])?;
+                        Ok(Some(self.image_load(image, coordinate, element, meta)?))
+                    }
+                    "GetDimensions" => self.get_dimensions(image, args, meta).map(|()| None),
+                    _ => Err(Error {
+                        kind: ErrorKind::UnknownFunction(name.to_string()),
+                        meta,
+                    }),
+                }
+            }
+            Ty::StructuredBuffer { .. } | Ty::ByteAddressBuffer { .. } => {
+                let Value::Reference(pointer) = self.lower(base)? else {
+                    unreachable!()
+                };
+                self.buffer_method(pointer, ty, name, args, meta)
+            }
+            Ty::Sampler { .. } | Ty::ConstantBuffer { .. } | Ty::Void | Ty::Value(_) => {
+                Err(Error {
+                    kind: ErrorKind::UnknownFunction(name.to_string()),
+                    meta,
+                })
+            }
+        }
+    }
+
+    /// Split the coordinate of a texel of `image` into its coordinates
+    /// proper and its array layer, if the image is arrayed.
+    fn split_coordinate(
+        &mut self,
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        meta: Span,
+    ) -> Result<(Handle<Expression>, Option<Handle<Expression>>)> {
+        let TypeInner::Image { dim, arrayed, .. } = self.resolve_type(image, meta)? else {
+            unreachable!()
+        };
+        let count = coordinate_count(dim);
+        let components = match self.resolve_type(coordinate, meta)? {
+            TypeInner::Vector { size, .. } => size as u32,
+            _ => 1,
+        };
+        if components != count + arrayed as u32 {
+            return Err(Error::semantic(
+                format!(
+                    "expected a coordinate with {} components, found {components}",
+                    count + arrayed as u32
+                ),
+                meta,
+            ));
+        }
+        if !arrayed {
+            return Ok((coordinate, None));
+        }
+
+        let layer = self.add_expression(
+            Expression::AccessIndex {
+                base: coordinate,
+                index: count,
+            },
+            meta,
+        )?;
+        let coordinate = match count {
+            1 => self.add_expression(
+                Expression::AccessIndex {
+                    base: coordinate,
+                    index: 0,
+                },
+                meta,
+            )?,
+            _ => self.add_expression(
+                Expression::Swizzle {
+                    size: super::types::vector_size(count).unwrap(),
+                    vector: coordinate,
+                    pattern: SwizzleComponent::XYZW,
+                },
+                meta,
+            )?,
+        };
+        Ok((coordinate, Some(layer)))
+    }
+
+    /// Convert an integer coordinate or index to `i32` if it's neither
+    /// signed nor unsigned.
+    fn to_integer(&mut self, value: Handle<Expression>, meta: Span) -> Result<Handle<Expression>> {
+        match scalar_components(&self.resolve_type(value, meta)?) {
+            Some(Scalar {
+                kind: ScalarKind::Sint | ScalarKind::Uint,
+                width: 4,
+            }) => Ok(value),
+            _ => self.convert_scalar(value, Scalar::I32, meta),
+        }
+    }
+
+    /// Convert the value of a texel read from an image to `element`.
+    fn texel_result(
+        &mut self,
+        value: Handle<Expression>,
+        element: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let target = self.module.types[element].inner.clone();
+        match (self.resolve_type(value, meta)?, target) {
+            // Naga gives texels the scalar type of the image, so only the
+            // number of components needs adjusting.
+            (TypeInner::Vector { .. }, TypeInner::Scalar(_)) => self.add_expression(
+                Expression::AccessIndex {
+                    base: value,
+                    index: 0,
+                },
+                meta,
+            ),
+            (TypeInner::Vector { .. }, TypeInner::Vector { size, .. })
+                if size != VectorSize::Quad =>
+            {
+                self.add_expression(
+                    Expression::Swizzle {
+                        size,
+                        vector: value,
+                        pattern: SwizzleComponent::XYZW,
+                    },
+                    meta,
+                )
+            }
+            _ => self.convert(value, element, meta),
+        }
+    }
+
+    /// Read the texel at `coordinate`, which includes the array layer, from
+    /// mip level zero of `image`.
+    pub fn image_load(
+        &mut self,
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        element: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        self.image_fetch(image, coordinate, None, None, element, meta)
+    }
+
+    fn image_fetch(
+        &mut self,
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        level: Option<Handle<Expression>>,
+        sample: Option<Handle<Expression>>,
+        element: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let TypeInner::Image { class, .. } = self.resolve_type(image, meta)? else {
+            unreachable!()
+        };
+        let coordinate = self.to_integer(coordinate, meta)?;
+        let (coordinate, array_index) = self.split_coordinate(image, coordinate, meta)?;
+        let level = match class {
+            ImageClass::Storage { .. } => None,
+            ImageClass::Sampled { multi: true, .. } | ImageClass::Depth { multi: true } => None,
+            ImageClass::Sampled { .. } | ImageClass::Depth { .. } => Some(match level {
+                Some(level) => self.to_integer(level, meta)?,
+                None => self.scalar_literal(Scalar::I32, 0.0, meta)?,
+            }),
+        };
+        let sample = match (class, sample) {
+            (ImageClass::Sampled { multi: true, .. } | ImageClass::Depth { multi: true }, None) => {
+                return Err(Error::semantic(
+                    "multisampled textures must be loaded with a sample index",
+                    meta,
+                ))
+            }
+            (_, Some(sample)) => Some(self.to_integer(sample, meta)?),
+            (_, None) => None,
+        };
+        let value = self.add_expression(
+            Expression::ImageLoad {
+                image,
+                coordinate,
+                array_index,
+                sample,
+                level,
+            },
+            meta,
+        )?;
+        self.texel_result(value, element, meta)
+    }
+
+    /// Write `value` to the texel at `coordinate` of a storage image.
+    pub fn image_store(
+        &mut self,
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        value: Handle<Expression>,
+        meta: Span,
+    ) -> Result<()> {
+        let coordinate = self.to_integer(coordinate, meta)?;
+        let (coordinate, array_index) = self.split_coordinate(image, coordinate, meta)?;
+
+        // Storage images are always written four components at a time.
+        let inner = self.resolve_type(value, meta)?;
+        let value = match inner {
+            TypeInner::Vector {
+                size: VectorSize::Quad,
+                ..
+            } => value,
+            TypeInner::Scalar(scalar) | TypeInner::Vector { scalar, .. } => {
+                let mut components = Vec::with_capacity(4);
+                self.flatten(value, meta, &mut components)?;
+                while components.len() < 4 {
+                    components.push(self.scalar_literal(scalar, 0.0, meta)?);
+                }
+                let ty = self.type_handle(TypeInner::Vector {
+                    size: VectorSize::Quad,
+                    scalar,
+                });
+                self.add_expression(Expression::Compose { ty, components }, meta)?
+            }
+            _ => return Err(Error::semantic("texels must be scalars or vectors", meta)),
+        };
+        self.add_statement(
+            Statement::ImageStore {
+                image,
+                coordinate,
+                array_index,
+                value,
+            },
+            meta,
+        );
+        Ok(())
+    }
+
+    /// Lower a sampler argument.
+    fn sampler(&mut self, arg: &Expr, comparison: bool, meta: Span) -> Result<Handle<Expression>> {
+        match self.resource(arg) {
+            Some(Ty::Sampler { comparison: actual }) if actual == comparison => {
+                self.lower_rvalue(arg)
+            }
+            _ if comparison => Err(Error::semantic("expected a SamplerComparisonState", meta)),
+            _ => Err(Error::semantic("expected a SamplerState", meta)),
+        }
+    }
+
+    fn texture_method(
+        &mut self,
+        image: Handle<Expression>,
+        element: Handle<Type>,
+        name: &str,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let TypeInner::Image { dim, class, .. } = self.resolve_type(image, meta)? else {
+            unreachable!()
+        };
+        let multi = matches!(
+            class,
+            ImageClass::Sampled { multi: true, .. } | ImageClass::Depth { multi: true }
+        );
+
+        match name {
+            "Load" => {
+                let count = if multi { 2..=2 } else { 1..=1 };
+                self.check_arg_count(name, args, count, meta)?;
+                let location = self.lower_rvalue(&args[0])?;
+                if multi {
+                    let sample = self.lower_rvalue(&args[1])?;
+                    return Ok(Some(self.image_fetch(
+                        image,
+                        location,
+                        None,
+                        Some(sample),
+                        element,
+                        meta,
+                    )?));
+                }
+                // The last component of the location is the mip level.
+                let components = match self.resolve_type(location, meta)? {
+                    TypeInner::Vector { size, .. } => size as u32,
+                    _ => 1,
+                };
+                let level = self.add_expression(
+                    Expression::AccessIndex {
+                        base: location,
+                        index: components - 1,
+                    },
+                    meta,
+                )?;
+                let coordinate = match components {
+                    2 => self.add_expression(
+                        Expression::AccessIndex {
+                            base: location,
+                            index: 0,
+                        },
+                        meta,
+                    )?,
+                    _ => self.add_expression(
+                        Expression::Swizzle {
+                            size: super::types::vector_size(components - 1)
+                                .ok_or_else(|| Error::semantic("invalid texture location", meta))?,
+                            vector: location,
+                            pattern: SwizzleComponent::XYZW,
+                        },
+                        meta,
+                    )?,
+                };
+                Ok(Some(self.image_fetch(
+                    image,
+                    coordinate,
+                    Some(level),
+                    None,
+                    element,
+                    meta,
+                )?))
+            }
+            "GetDimensions" => self.get_dimensions(image, args, meta).map(|()| None),
+            _ => {
+                let (gather, compare, extra) = match name {
+                    "Sample" => (None, false, 0),
+                    "SampleLevel" | "SampleBias" => (None, false, 1),
+                    "SampleGrad" => (None, false, 2),
+                    "SampleCmp" | "SampleCmpLevelZero" => (None, true, 1),
+                    "Gather" | "GatherRed" => (Some(SwizzleComponent::X), false, 0),
+                    "GatherGreen" => (Some(SwizzleComponent::Y), false, 0),
+                    "GatherBlue" => (Some(SwizzleComponent::Z), false, 0),
+                    "GatherAlpha" => (Some(SwizzleComponent::W), false, 0),
+                    "GatherCmp" | "GatherCmpRed" => (Some(SwizzleComponent::X), true, 1),
+                    _ => {
+                        return Err(Error {
+                            kind: ErrorKind::UnknownFunction(name.to_string()),
+                            meta,
+                        })
+                    }
+                };
+                self.check_arg_count(name, args, 2 + extra..=3 + extra, meta)?;
+                let sampler = self.sampler(&args[0], compare, meta)?;
+                let coordinate = self.lower_rvalue(&args[1])?;
+                let coordinate = self.convert_scalar(coordinate, Scalar::F32, meta)?;
+                let (coordinate, array_index) = self.split_coordinate(image, coordinate, meta)?;
+                let array_index = match array_index {
+                    Some(layer) => {
+                        let layer = self.math(MathFunction::Round, &[layer], meta)?;
+                        Some(self.convert_scalar(layer, Scalar::I32, meta)?)
+                    }
+                    None => None,
+                };
+
+                let mut extras = Vec::with_capacity(extra);
+                for arg in &args[2..2 + extra] {
+                    let value = self.lower_rvalue(arg)?;
+                    extras.push(self.convert_scalar(value, Scalar::F32, meta)?);
+                }
+                let (level, depth_ref) = match name {
+                    "SampleLevel" => (SampleLevel::Exact(extras[0]), None),
+                    "SampleBias" => (SampleLevel::Bias(extras[0]), None),
+                    "SampleGrad" => (
+                        SampleLevel::Gradient {
+                            x: extras[0],
+                            y: extras[1],
+                        },
+                        None,
+                    ),
+                    "SampleCmp" => (SampleLevel::Auto, Some(extras[0])),
+                    "SampleCmpLevelZero" => (SampleLevel::Zero, Some(extras[0])),
+                    _ if compare => (SampleLevel::Zero, Some(extras[0])),
+                    _ if gather.is_some() => (SampleLevel::Zero, None),
+                    _ => (SampleLevel::Auto, None),
+                };
+
+                // Offsets must be constant expressions.
+                let offset = match args.get(2 + extra) {
+                    Some(offset) => {
+                        if dim == ImageDimension::Cube {
+                            return Err(Error::semantic(
+                                "cube textures can't be sampled with an offset",
+                                offset.meta,
+                            ));
+                        }
+                        Some(self.lower_const_as(offset, Scalar::I32)?)
+                    }
+                    None => None,
+                };
+
+                let value = self.add_expression(
+                    Expression::ImageSample {
+                        image,
+                        sampler,
+                        gather,
+                        coordinate,
+                        array_index,
+                        offset,
+                        level,
+                        depth_ref,
+                        residency: false,
+                    },
+                    meta,
+                )?;
+                if gather.is_some() {
+                    Ok(Some(value))
+                } else {
+                    Ok(Some(self.texel_result(value, element, meta)?))
+                }
+            }
+        }
+    }
+
+    /// Lower `GetDimensions` on `image`, which stores its results through
+    /// its arguments.
+    fn get_dimensions(
+        &mut self,
+        image: Handle<Expression>,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<()> {
+        let TypeInner::Image {
+            dim,
+            arrayed,
+            class,
+        } = self.resolve_type(image, meta)?
+        else {
+            unreachable!()
+        };
+        let size_count = match dim {
+            ImageDimension::Cube => 2,
+            dim => coordinate_count(dim),
+        } as usize;
+        let multi = matches!(
+            class,
+            ImageClass::Sampled { multi: true, .. } | ImageClass::Depth { multi: true }
+        );
+        let sized = size_count + arrayed as usize;
+        let mipmapped = !multi && !matches!(class, ImageClass::Storage { .. });
+
+        let (level, outputs) = match args.len() {
+            n if n == sized + multi as usize => (None, args),
+            n if mipmapped && n == sized + 2 => {
+                let level = self.lower_rvalue(&args[0])?;
+                (Some(self.to_integer(level, meta)?), &args[1..])
+            }
+            _ => {
+                return Err(Error::semantic(
+                    "wrong number of arguments to `GetDimensions`",
+                    meta,
+                ))
+            }
+        };
+
+        let size = self.add_expression(
+            Expression::ImageQuery {
+                image,
+                query: crate::ImageQuery::Size { level },
+            },
+            meta,
+        )?;
+        let mut values = Vec::with_capacity(outputs.len());
+        match size_count {
+            1 => values.push(size),
+            _ => {
+                for index in 0..size_count as u32 {
+                    values.push(
+                        self.add_expression(Expression::AccessIndex { base: size, index }, meta)?,
+                    );
+                }
+            }
+        }
+        if arrayed {
+            values.push(self.add_expression(
+                Expression::ImageQuery {
+                    image,
+                    query: crate::ImageQuery::NumLayers,
+                },
+                meta,
+            )?);
+        }
+        if multi {
+            values.push(self.add_expression(
+                Expression::ImageQuery {
+                    image,
+                    query: crate::ImageQuery::NumSamples,
+                },
+                meta,
+            )?);
+        } else if level.is_some() {
+            values.push(self.add_expression(
+                Expression::ImageQuery {
+                    image,
+                    query: crate::ImageQuery::NumLevels,
+                },
+                meta,
+            )?);
+        }
+
+        for (output, value) in outputs.iter().zip(values) {
+            let target = self.lower_lvalue(output)?;
+            self.store(target, value, output.meta)?;
+        }
+        Ok(())
+    }
+
+    fn buffer_method(
+        &mut self,
+        pointer: Handle<Expression>,
+        ty: Ty,
+        name: &str,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let byte_address = matches!(ty, Ty::ByteAddressBuffer { .. });
+        let writable = matches!(
+            ty,
+            Ty::ByteAddressBuffer { writable: true } | Ty::StructuredBuffer { writable: true, .. }
+        );
+
+        if name == "GetDimensions" {
+            self.check_arg_count(
+                name,
+                args,
+                (2 - byte_address as usize)..=(2 - byte_address as usize),
+                meta,
+            )?;
+            let length = self.add_expression(Expression::ArrayLength(pointer), meta)?;
+            let TypeInner::Pointer { base, .. } = self.resolve_type(pointer, meta)? else {
+                unreachable!()
+            };
+            let TypeInner::Array { stride, .. } = self.module.types[base].inner else {
+                unreachable!()
+            };
+            let stride = self.scalar_literal(Scalar::U32, stride as f64, meta)?;
+            let first = if byte_address {
+                self.multiply(length, stride, meta)?
+            } else {
+                length
+            };
+            let target = self.lower_lvalue(&args[0])?;
+            self.store(target, first, meta)?;
+            if let Some(arg) = args.get(1) {
+                let target = self.lower_lvalue(arg)?;
+                self.store(target, stride, meta)?;
+            }
+            return Ok(None);
+        }
+
+        if !byte_address {
+            return Err(Error {
+                kind: ErrorKind::UnknownFunction(name.to_string()),
+                meta,
+            });
+        }
+
+        if let Some(op) = name.strip_prefix("Interlocked") {
+            if !writable {
+                return Err(Error::semantic(
+                    format!("`{name}` needs a RWByteAddressBuffer"),
+                    meta,
+                ));
+            }
+            self.check_arg_count(name, args, 1..=4, meta)?;
+            let element = self.word_pointer(pointer, &args[0], 0, meta)?;
+            return self.interlocked(name, op, element, &args[1..], meta);
+        }
+
+        let (count, store) = match name {
+            "Load" => (1, false),
+            "Load2" => (2, false),
+            "Load3" => (3, false),
+            "Load4" => (4, false),
+            "Store" => (1, true),
+            "Store2" => (2, true),
+            "Store3" => (3, true),
+            "Store4" => (4, true),
+            _ => {
+                return Err(Error {
+                    kind: ErrorKind::UnknownFunction(name.to_string()),
+                    meta,
+                })
+            }
+        };
+
+        if store {
+            if !writable {
+                return Err(Error::semantic(
+                    format!("`{name}` needs a RWByteAddressBuffer"),
+                    meta,
+                ));
+            }
+            self.check_arg_count(name, args, 2..=2, meta)?;
+            let value = self.lower_rvalue(&args[1])?;
+            let value = self.convert_scalar(value, Scalar::U32, meta)?;
+            let mut words = Vec::with_capacity(count);
+            match count {
+                1 => words.push(value),
+                _ => self.flatten(value, meta, &mut words)?,
+            }
+            if words.len() != count {
+                return Err(Error::semantic(
+                    format!("`{name}` stores {count} components"),
+                    meta,
+                ));
+            }
+            for (index, word) in words.into_iter().enumerate() {
+                let element = self.word_pointer(pointer, &args[0], index as u32, meta)?;
+                self.add_statement(
+                    Statement::Store {
+                        pointer: element,
+                        value: word,
+                    },
+                    meta,
+                );
+            }
+            return Ok(None);
+        }
+
+        self.check_arg_count(name, args, 1..=1, meta)?;
+        let mut words = Vec::with_capacity(count);
+        for index in 0..count as u32 {
+            let element = self.word_pointer(pointer, &args[0], index, meta)?;
+            words.push(self.add_expression(Expression::Load { pointer: element }, meta)?);
+        }
+        if count == 1 {
+            return Ok(Some(words[0]));
+        }
+        let ty = self.type_handle(TypeInner::Vector {
+            size: super::types::vector_size(count as u32).unwrap(),
+            scalar: Scalar::U32,
+        });
+        Ok(Some(self.add_expression(
+            Expression::Compose {
+                ty,
+                components: words,
+            },
+            meta,
+        )?))
+    }
+
+    /// A pointer to the word `offset` words after the byte `address` in the
+    /// byte address buffer `pointer`.
+    fn word_pointer(
+        &mut self,
+        pointer: Handle<Expression>,
+        address: &Expr,
+        offset: u32,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let address = self.lower_rvalue(address)?;
+        let address = self.convert_scalar(address, Scalar::U32, meta)?;
+        let two = self.scalar_literal(Scalar::U32, 2.0, meta)?;
+        let mut index = self.add_expression(
+            Expression::Binary {
+                op: BinaryOperator::ShiftRight,
+                left: address,
+                right: two,
+            },
+            meta,
+        )?;
+        if offset != 0 {
+            let offset = self.scalar_literal(Scalar::U32, offset as f64, meta)?;
+            index = self.add_expression(
+                Expression::Binary {
+                    op: BinaryOperator::Add,
+                    left: index,
+                    right: offset,
+                },
+                meta,
+            )?;
+        }
+        self.add_expression(
+            Expression::Access {
+                base: pointer,
+                index,
+            },
+            meta,
+        )
+    }
+}
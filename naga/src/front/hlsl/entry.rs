@@ -0,0 +1,564 @@
+/*!
+Entry points.
+
+HLSL entry points are ordinary functions whose parameters, results and
+struct members carry semantics. Each one is lowered to a wrapper naga entry
+point that flattens its inputs and outputs into individual bindings, converts
+them to and from the types naga requires for built-ins, and calls the
+function.
+*/
+
+use super::{
+    ast::{FunctionDecl, IoAttributes, ParameterQualifier, Ty},
+    error::{Error, ErrorKind},
+    lower::{Context, ResourceUsage, Signature},
+    Frontend, Result,
+};
+use crate::{
+    Binding, BuiltIn, EarlyDepthTest, EntryPoint, Expression, Function, FunctionArgument,
+    FunctionResult, Handle, Interpolation, Scalar, ShaderStage, Span, Statement, StructMember,
+    Type, TypeInner, VectorSize,
+};
+
+/// Whether a value is read by or written by an entry point.
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Input,
+    Output,
+}
+
+/// A single scalar or vector crossing the entry point's interface.
+struct Leaf {
+    name: String,
+    /// The type of the value in the HLSL function.
+    ty: Handle<Type>,
+    /// The type naga requires for the binding.
+    binding_ty: Handle<Type>,
+    binding: Binding,
+}
+
+/// A built-in semantic, which must be called `SV_<name>`, its naga
+/// counterpart and the type naga gives it.
+fn builtin(name: &str, stage: ShaderStage, direction: Direction) -> Option<(BuiltIn, TypeInner)> {
+    use Direction::{Input, Output};
+    use ShaderStage as Ss;
+
+    let u32 = TypeInner::Scalar(Scalar::U32);
+    let uvec3 = TypeInner::Vector {
+        size: VectorSize::Tri,
+        scalar: Scalar::U32,
+    };
+    Some(match (name, stage, direction) {
+        ("POSITION", Ss::Vertex, Output) | ("POSITION", Ss::Fragment, Input) => (
+            BuiltIn::Position { invariant: false },
+            TypeInner::Vector {
+                size: VectorSize::Quad,
+                scalar: Scalar::F32,
+            },
+        ),
+        ("VERTEXID", Ss::Vertex, Input) => (BuiltIn::VertexIndex, u32),
+        ("INSTANCEID", Ss::Vertex, Input) => (BuiltIn::InstanceIndex, u32),
+        ("ISFRONTFACE", Ss::Fragment, Input) => {
+            (BuiltIn::FrontFacing, TypeInner::Scalar(Scalar::BOOL))
+        }
+        ("SAMPLEINDEX", Ss::Fragment, Input) => (BuiltIn::SampleIndex, u32),
+        ("COVERAGE", Ss::Fragment, _) => (BuiltIn::SampleMask, u32),
+        ("DEPTH", Ss::Fragment, Output) => (BuiltIn::FragDepth, TypeInner::Scalar(Scalar::F32)),
+        ("PRIMITIVEID", Ss::Fragment, Input) => (BuiltIn::PrimitiveIndex, u32),
+        ("BARYCENTRICS", Ss::Fragment, Input) => (
+            BuiltIn::Barycentric,
+            TypeInner::Vector {
+                size: VectorSize::Tri,
+                scalar: Scalar::F32,
+            },
+        ),
+        ("DISPATCHTHREADID", Ss::Compute, Input) => (BuiltIn::GlobalInvocationId, uvec3),
+        ("GROUPID", Ss::Compute, Input) => (BuiltIn::WorkGroupId, uvec3),
+        ("GROUPTHREADID", Ss::Compute, Input) => (BuiltIn::LocalInvocationId, uvec3),
+        ("GROUPINDEX", Ss::Compute, Input) => (BuiltIn::LocalInvocationIndex, u32),
+        _ => return None,
+    })
+}
+
+/// The semantics of `ty`'s leaves, including those of struct members.
+fn semantics<'a>(
+    frontend: &'a Frontend,
+    ty: Handle<Type>,
+    io: &'a IoAttributes,
+    out: &mut Vec<&'a str>,
+) {
+    match frontend.struct_io.get(&ty) {
+        Some(members) => {
+            let TypeInner::Struct {
+                members: ref fields,
+                ..
+            } = frontend.module.types[ty].inner
+            else {
+                unreachable!()
+            };
+            for (field, io) in fields.iter().zip(members) {
+                semantics(frontend, field.ty, io, out);
+            }
+        }
+        None => out.extend(io.semantic.as_ref().map(|semantic| semantic.name.as_str())),
+    }
+}
+
+fn location_binding(
+    location: u32,
+    mut interpolation: Option<Interpolation>,
+    sampling: Option<crate::Sampling>,
+) -> Binding {
+    // A sampling qualifier on its own implies perspective interpolation.
+    if sampling.is_some() && interpolation.is_none() {
+        interpolation = Some(Interpolation::Perspective);
+    }
+    Binding::Location {
+        location,
+        second_blend_source: false,
+        interpolation,
+        sampling,
+        per_primitive: false,
+    }
+}
+
+struct Interface {
+    stage: ShaderStage,
+    next_location: [u32; 2],
+    inputs: Vec<Leaf>,
+    outputs: Vec<Leaf>,
+}
+
+impl Frontend {
+    /// Work out the stage of `decl` if it's an entry point.
+    ///
+    /// A function is an entry point if it has a `[shader]` or `[numthreads]`
+    /// attribute. A function that no other function calls is also taken to be
+    /// a vertex shader if it outputs `SV_Position`, or a fragment shader if it
+    /// outputs `SV_Target` or `SV_Depth`.
+    fn entry_point_stage(&self, decl: &FunctionDecl, usage: &ResourceUsage) -> Option<ShaderStage> {
+        if let Some(stage) = decl.attributes.stage {
+            return Some(stage);
+        }
+        if decl.attributes.workgroup_size.is_some() {
+            return Some(ShaderStage::Compute);
+        }
+        if usage.called.contains(&decl.name) {
+            return None;
+        }
+
+        let mut outputs = Vec::new();
+        if let Ty::Value(ty) = decl.result {
+            semantics(self, ty, &decl.result_io, &mut outputs);
+        }
+        for param in &decl.parameters {
+            if let (Ty::Value(ty), true) = (param.ty, param.qualifier.is_output()) {
+                semantics(self, ty, &param.io, &mut outputs);
+            }
+        }
+        if outputs
+            .iter()
+            .any(|&name| name == "SV_TARGET" || name == "SV_DEPTH")
+        {
+            Some(ShaderStage::Fragment)
+        } else if outputs.contains(&"SV_POSITION") {
+            Some(ShaderStage::Vertex)
+        } else {
+            None
+        }
+    }
+
+    /// Flatten a value of type `ty` into the leaves of `interface`.
+    fn add_leaves(
+        &mut self,
+        interface: &mut Interface,
+        direction: Direction,
+        name: &str,
+        ty: Handle<Type>,
+        io: &IoAttributes,
+        meta: Span,
+    ) -> Result<()> {
+        if let Some(members) = self.struct_io.get(&ty).cloned() {
+            let TypeInner::Struct {
+                members: ref fields,
+                ..
+            } = self.module.types[ty].inner
+            else {
+                unreachable!()
+            };
+            let fields = fields.clone();
+            for (field, io) in fields.iter().zip(&members) {
+                let name = field.name.clone().unwrap_or_default();
+                self.add_leaves(interface, direction, &name, field.ty, io, meta)?;
+            }
+            return Ok(());
+        }
+
+        let semantic = io.semantic.as_ref().ok_or_else(|| {
+            Error::semantic(
+                format!("entry point parameter `{name}` needs a semantic"),
+                meta,
+            )
+        })?;
+        let stage = interface.stage;
+        let (binding, binding_ty) = if let Some(sv) = semantic.name.strip_prefix("SV_") {
+            if let ("TARGET", ShaderStage::Fragment, Direction::Output) = (sv, stage, direction) {
+                (location_binding(semantic.index, None, None), ty)
+            } else {
+                let (builtin, inner) = builtin(sv, stage, direction).ok_or_else(|| Error {
+                    kind: ErrorKind::UnsupportedSemantic(format!(
+                        "{}{} as a {direction} of a {stage:?} shader",
+                        semantic.name,
+                        if semantic.index > 0 {
+                            semantic.index.to_string()
+                        } else {
+                            String::new()
+                        },
+                        direction = match direction {
+                            Direction::Input => "input",
+                            Direction::Output => "output",
+                        },
+                    )),
+                    meta: semantic.meta,
+                })?;
+                (Binding::BuiltIn(builtin), self.value_type(inner, meta))
+            }
+        } else {
+            if stage == ShaderStage::Compute {
+                return Err(Error {
+                    kind: ErrorKind::UnsupportedSemantic(format!(
+                        "{} in a compute shader",
+                        semantic.name
+                    )),
+                    meta: semantic.meta,
+                });
+            }
+            let counter = &mut interface.next_location[direction as usize];
+            let location = io.location.unwrap_or(*counter);
+            *counter = location + 1;
+            let mut binding = location_binding(location, io.interpolation, io.sampling);
+            let interpolated = matches!(
+                (stage, direction),
+                (ShaderStage::Vertex, Direction::Output)
+                    | (ShaderStage::Fragment, Direction::Input)
+            );
+            if interpolated {
+                binding.apply_default_interpolation(&self.module.types[ty].inner);
+            } else if let Binding::Location {
+                ref mut interpolation,
+                ref mut sampling,
+                ..
+            } = binding
+            {
+                *interpolation = None;
+                *sampling = None;
+            }
+            (binding, ty)
+        };
+
+        let leaf = Leaf {
+            name: name.to_string(),
+            ty,
+            binding_ty,
+            binding,
+        };
+        match direction {
+            Direction::Input => interface.inputs.push(leaf),
+            Direction::Output => interface.outputs.push(leaf),
+        }
+        Ok(())
+    }
+
+    pub(super) fn add_entry_points(
+        &mut self,
+        functions: &[FunctionDecl],
+        signatures: &[Signature],
+        usage: &ResourceUsage,
+    ) -> Result<()> {
+        for (decl, signature) in functions.iter().zip(signatures) {
+            if let Some(stage) = self.entry_point_stage(decl, usage) {
+                self.add_entry_point(decl, signature, stage)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_entry_point(
+        &mut self,
+        decl: &FunctionDecl,
+        signature: &Signature,
+        stage: ShaderStage,
+    ) -> Result<()> {
+        let meta = decl.meta;
+        if self
+            .module
+            .entry_points
+            .iter()
+            .any(|entry| entry.name == decl.name)
+        {
+            return Err(Error::semantic(
+                format!("entry point `{}` is overloaded", decl.name),
+                meta,
+            ));
+        }
+        let workgroup_size = match (stage, decl.attributes.workgroup_size) {
+            (ShaderStage::Compute, Some(size)) => size,
+            (ShaderStage::Compute, None) => {
+                return Err(Error::semantic(
+                    "compute shaders need a `[numthreads(x, y, z)]` attribute",
+                    meta,
+                ))
+            }
+            _ => [0; 3],
+        };
+
+        let mut interface = Interface {
+            stage,
+            next_location: [0; 2],
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+        // The leaves of each parameter, as ranges of the inputs and outputs.
+        let mut ranges = Vec::with_capacity(decl.parameters.len());
+        for (param, &(ty, qualifier)) in decl.parameters.iter().zip(&signature.parameters) {
+            if !matches!(param.ty, Ty::Value(_)) {
+                return Err(Error::semantic(
+                    "entry points can't take resources as parameters",
+                    param.meta,
+                ));
+            }
+            let inputs = interface.inputs.len();
+            if qualifier != ParameterQualifier::Out {
+                self.add_leaves(
+                    &mut interface,
+                    Direction::Input,
+                    &param.name,
+                    ty,
+                    &param.io,
+                    param.meta,
+                )?;
+            }
+            let outputs = interface.outputs.len();
+            if qualifier.is_output() {
+                self.add_leaves(
+                    &mut interface,
+                    Direction::Output,
+                    &param.name,
+                    ty,
+                    &param.io,
+                    param.meta,
+                )?;
+            }
+            ranges.push((
+                inputs..interface.inputs.len(),
+                outputs..interface.outputs.len(),
+            ));
+        }
+        if let Some(ty) = signature.result {
+            self.add_leaves(
+                &mut interface,
+                Direction::Output,
+                &decl.name,
+                ty,
+                &decl.result_io,
+                meta,
+            )?;
+        }
+
+        let Interface {
+            inputs, outputs, ..
+        } = interface;
+        let mut ctx = Context::new(
+            &mut self.module,
+            &mut self.global_expression_kind_tracker,
+            &self.globals,
+            &[],
+            false,
+        );
+        ctx.arguments = inputs
+            .iter()
+            .map(|leaf| FunctionArgument {
+                name: Some(leaf.name.clone()),
+                ty: leaf.binding_ty,
+                binding: Some(leaf.binding.clone()),
+            })
+            .collect();
+
+        let mut input_values = Vec::with_capacity(inputs.len());
+        for (index, leaf) in inputs.iter().enumerate() {
+            let arg = ctx.add_expression(Expression::FunctionArgument(index as u32), meta)?;
+            input_values.push(ctx.convert(arg, leaf.ty, meta)?);
+        }
+
+        let mut arguments = Vec::with_capacity(decl.parameters.len());
+        let mut out_locals = Vec::new();
+        for (&(ty, qualifier), (inputs, outputs)) in signature.parameters.iter().zip(&ranges) {
+            let value = match qualifier {
+                ParameterQualifier::Out => None,
+                _ => {
+                    let mut leaves = input_values[inputs.clone()].iter().copied();
+                    Some(compose_leaves(&mut ctx, ty, &mut leaves, meta)?)
+                }
+            };
+            if !qualifier.is_output() {
+                arguments.push(value.unwrap());
+                continue;
+            }
+            let pointer = ctx.add_local(None, ty, meta)?;
+            if let Some(value) = value {
+                ctx.add_statement(Statement::Store { pointer, value }, meta);
+            }
+            arguments.push(pointer);
+            out_locals.push((pointer, outputs.clone()));
+        }
+
+        let function = signature.handle;
+        let result = ctx.add_statement_with_result(
+            signature.result.map(|_| Expression::CallResult(function)),
+            |result| Statement::Call {
+                function,
+                arguments,
+                result,
+            },
+            meta,
+        );
+
+        let mut output_values = Vec::with_capacity(outputs.len());
+        for (pointer, _) in out_locals {
+            let value = ctx.add_expression(Expression::Load { pointer }, meta)?;
+            split_leaves(&mut ctx, value, meta, &mut output_values)?;
+        }
+        if let Some(result) = result {
+            split_leaves(&mut ctx, result, meta, &mut output_values)?;
+        }
+        debug_assert_eq!(output_values.len(), outputs.len());
+        for (value, leaf) in output_values.iter_mut().zip(&outputs) {
+            *value = ctx.convert(*value, leaf.binding_ty, meta)?;
+        }
+
+        let (value, result) = match outputs.len() {
+            0 => (None, None),
+            1 => (
+                Some(output_values[0]),
+                Some(FunctionResult {
+                    ty: outputs[0].binding_ty,
+                    binding: Some(outputs[0].binding.clone()),
+                }),
+            ),
+            _ => {
+                let ty = output_struct(&mut ctx, &decl.name, &outputs);
+                let value = ctx.add_expression(
+                    Expression::Compose {
+                        ty,
+                        components: output_values,
+                    },
+                    meta,
+                )?;
+                (Some(value), Some(FunctionResult { ty, binding: None }))
+            }
+        };
+        ctx.add_statement(Statement::Return { value }, meta);
+
+        let Context {
+            expressions,
+            locals,
+            arguments,
+            body,
+            ..
+        } = ctx;
+        self.module.entry_points.push(EntryPoint {
+            name: decl.name.clone(),
+            stage,
+            early_depth_test: decl
+                .attributes
+                .early_depth_test
+                .then_some(EarlyDepthTest { conservative: None }),
+            workgroup_size,
+            workgroup_size_overrides: None,
+            mesh_info: None,
+            function: Function {
+                name: Some(decl.name.clone()),
+                arguments,
+                result,
+                local_variables: locals,
+                expressions,
+                body,
+                ..Default::default()
+            },
+        });
+        Ok(())
+    }
+}
+
+/// Build a value of type `ty` from the leaves of its flattened interface.
+fn compose_leaves(
+    ctx: &mut Context,
+    ty: Handle<Type>,
+    leaves: &mut impl Iterator<Item = Handle<Expression>>,
+    meta: Span,
+) -> Result<Handle<Expression>> {
+    match ctx.module.types[ty].inner {
+        TypeInner::Struct { ref members, .. } => {
+            let member_types: Vec<_> = members.iter().map(|member| member.ty).collect();
+            let components = member_types
+                .into_iter()
+                .map(|member| compose_leaves(ctx, member, leaves, meta))
+                .collect::<Result<_>>()?;
+            ctx.add_expression(Expression::Compose { ty, components }, meta)
+        }
+        _ => Ok(leaves.next().unwrap()),
+    }
+}
+
+/// The leaves of `value`, the inverse of [`compose_leaves`].
+fn split_leaves(
+    ctx: &mut Context,
+    value: Handle<Expression>,
+    meta: Span,
+    out: &mut Vec<Handle<Expression>>,
+) -> Result<()> {
+    match ctx.resolve_type(value, meta)? {
+        TypeInner::Struct { members, .. } => {
+            for index in 0..members.len() as u32 {
+                let member =
+                    ctx.add_expression(Expression::AccessIndex { base: value, index }, meta)?;
+                split_leaves(ctx, member, meta, out)?;
+            }
+        }
+        _ => out.push(value),
+    }
+    Ok(())
+}
+
+/// The struct an entry point with several outputs returns.
+fn output_struct(ctx: &mut Context, name: &str, outputs: &[Leaf]) -> Handle<Type> {
+    let mut layouter = crate::proc::Layouter::default();
+    layouter.update(ctx.module.to_ctx()).unwrap();
+    let mut offset = 0;
+    let mut alignment = crate::proc::Alignment::ONE;
+    let members = outputs
+        .iter()
+        .map(|leaf| {
+            let layout = layouter[leaf.binding_ty];
+            alignment = alignment.max(layout.alignment);
+            let member_offset = layout.alignment.round_up(offset);
+            offset = member_offset + layout.size;
+            StructMember {
+                name: Some(leaf.name.clone()),
+                ty: leaf.binding_ty,
+                binding: Some(leaf.binding.clone()),
+                offset: member_offset,
+            }
+        })
+        .collect();
+    ctx.module.types.insert(
+        Type {
+            name: Some(format!("{name}Output")),
+            inner: TypeInner::Struct {
+                members,
+                span: alignment.round_up(offset),
+            },
+        },
+        Span::UNDEFINED,
+    )
+}
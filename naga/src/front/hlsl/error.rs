@@ -0,0 +1,109 @@
+use crate::{proc::ConstantEvaluatorError, SourceLocation, Span};
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFile;
+use codespan_reporting::term;
+use pp_rs::token::PreprocessorError;
+use std::borrow::Cow;
+use termcolor::{NoColor, WriteColor};
+use thiserror::Error;
+
+/// Information about the cause of an error.
+#[derive(Clone, Debug, Error)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ErrorKind {
+    /// Whilst parsing as encountered an unexpected EOF.
+    #[error("Unexpected end of file")]
+    EndOfFile,
+    /// Whilst parsing an unexpected token was encountered.
+    #[error("Expected {expected}, found {found}")]
+    InvalidToken {
+        /// A description of what the parser was looking for.
+        expected: Cow<'static, str>,
+        /// The token that was found instead.
+        found: String,
+    },
+    /// A specific feature is not yet implemented.
+    ///
+    /// To help prioritize work please open an issue in the github issue tracker
+    /// if none exist already or react to the already existing one.
+    #[error("Not implemented: {0}")]
+    NotImplemented(&'static str),
+    /// A reference to a variable that wasn't declared was used.
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+    /// A reference to a type that wasn't declared was used.
+    #[error("Unknown type: {0}")]
+    UnknownType(String),
+    /// A call to a function or intrinsic that doesn't exist was made.
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+    /// A reference to a non existent member of a type was made.
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+    /// A semantic that naga doesn't know how to map was used.
+    #[error("Unsupported semantic: {0}")]
+    UnsupportedSemantic(String),
+    /// A variable with the same name already exists in the current scope.
+    #[error("Variable already declared: {0}")]
+    VariableAlreadyDeclared(String),
+    /// A semantic error was detected in the shader.
+    #[error("{0}")]
+    SemanticError(Cow<'static, str>),
+    /// An error was returned by the preprocessor.
+    #[error("{0:?}")]
+    PreprocessorError(PreprocessorError),
+}
+
+impl From<ConstantEvaluatorError> for ErrorKind {
+    fn from(err: ConstantEvaluatorError) -> Self {
+        ErrorKind::SemanticError(err.to_string().into())
+    }
+}
+
+/// Error returned during shader parsing.
+#[derive(Clone, Debug, Error)]
+#[error("{kind}")]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Error {
+    /// Holds the information about the error itself.
+    pub kind: ErrorKind,
+    /// Holds information about the range of the source code where the error happened.
+    pub meta: Span,
+}
+
+impl Error {
+    pub(super) fn semantic(message: impl Into<Cow<'static, str>>, meta: Span) -> Self {
+        Error {
+            kind: ErrorKind::SemanticError(message.into()),
+            meta,
+        }
+    }
+
+    /// Returns a [`SourceLocation`] for the error message.
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        Some(self.meta.location(source))
+    }
+
+    pub fn emit_to_writer(&self, writer: &mut impl WriteColor, source: &str) {
+        self.emit_to_writer_with_path(writer, source, "hlsl");
+    }
+
+    pub fn emit_to_writer_with_path(&self, writer: &mut impl WriteColor, source: &str, path: &str) {
+        let path = path.to_string();
+        let files = SimpleFile::new(path, source);
+        let config = term::Config::default();
+
+        let mut diagnostic = Diagnostic::error().with_message(self.kind.to_string());
+        if let Some(range) = self.meta.to_range() {
+            diagnostic = diagnostic.with_labels(vec![Label::primary((), range)]);
+        }
+
+        term::emit(writer, &config, &files, &diagnostic).expect("cannot write error");
+    }
+
+    pub fn emit_to_string(&self, source: &str) -> String {
+        let mut writer = NoColor::new(Vec::new());
+        self.emit_to_writer(&mut writer, source);
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+}
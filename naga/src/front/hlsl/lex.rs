@@ -0,0 +1,98 @@
+use super::error::{Error, ErrorKind};
+use crate::{FastHashMap, Span};
+use pp_rs::{
+    pp::Preprocessor,
+    token::{Float, Integer, Location, Punct, TokenValue as PPTokenValue},
+};
+
+pub fn span(location: Location) -> Span {
+    Span::new(location.start, location.end)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenValue {
+    Ident(String),
+    Int(Integer),
+    Float(Float),
+    Punct(Punct),
+}
+
+impl std::fmt::Display for TokenValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            TokenValue::Ident(ref ident) => write!(f, "`{ident}`"),
+            TokenValue::Int(ref int) => write!(f, "`{}`", int.value),
+            TokenValue::Float(ref float) => write!(f, "`{}`", float.value),
+            TokenValue::Punct(punct) => write!(f, "{punct:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub value: TokenValue,
+    pub meta: Span,
+}
+
+/// The span of the first `#include` directive in `source`, if any.
+///
+/// The front end has no way to load other files, so includes are rejected up
+/// front rather than left to the preprocessor. This must happen before the
+/// quotes around the path are blanked out. Directives inside comments or
+/// disabled `#if` blocks are found too.
+pub fn find_include(source: &str) -> Option<Span> {
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n') {
+        let directive = line.trim_start();
+        let is_include = directive
+            .strip_prefix('#')
+            .and_then(|rest| rest.trim_start().strip_prefix("include"))
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'));
+        if is_include {
+            let start = line_start + line.len() - directive.len();
+            let end = line_start + line.trim_end().len();
+            return Some(Span::new(start as u32, end as u32));
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+/// Preprocess and tokenize `source`.
+///
+/// HLSL uses the same preprocessor as GLSL, so this is built on [`pp_rs`].
+/// `#pragma` directives and the GLSL-only `#version` and `#extension`
+/// directives are ignored.
+pub fn tokenize(source: &str, defines: &FastHashMap<String, String>) -> Result<Vec<Token>, Error> {
+    let mut pp = Preprocessor::new(source);
+    for (define, value) in defines {
+        // Defines aren't part of `source`, so their errors have no span.
+        pp.add_define(define, value).map_err(|(err, _)| Error {
+            kind: ErrorKind::PreprocessorError(err),
+            meta: Span::default(),
+        })?;
+    }
+
+    let mut tokens = Vec::new();
+    for token in pp {
+        let token = token.map_err(|(err, location)| Error {
+            kind: ErrorKind::PreprocessorError(err),
+            meta: span(location),
+        })?;
+        let value = match token.value {
+            PPTokenValue::Ident(ident) => TokenValue::Ident(ident),
+            PPTokenValue::Integer(integer) => TokenValue::Int(integer),
+            PPTokenValue::Float(float) => TokenValue::Float(float),
+            PPTokenValue::Punct(punct) => TokenValue::Punct(punct),
+            PPTokenValue::Pragma(_) | PPTokenValue::Version(_) | PPTokenValue::Extension(_) => {
+                continue
+            }
+        };
+        tokens.push(Token {
+            value,
+            meta: span(token.location),
+        });
+    }
+
+    Ok(tokens)
+}
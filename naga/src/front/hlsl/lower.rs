@@ -0,0 +1,2336 @@
+/*!
+Lowering of HLSL function bodies to naga IR.
+
+This mirrors the GLSL front end's `Context`: expressions are added through
+the constant evaluator so that constant subexpressions are folded, and a
+[`Typifier`] tracks the type of everything added so far.
+*/
+
+use super::{
+    ast::{
+        BinaryOp, Expr, ExprKind, FunctionDecl, Literal, LocalDecl, ParameterQualifier, Stmt,
+        StmtKind, SwitchCase, Ty, UnaryOp,
+    },
+    error::{Error, ErrorKind},
+    types::{scalar_components, type_power},
+    Frontend, GlobalSymbol, Result,
+};
+use crate::{
+    front::Typifier,
+    proc::{ensure_block_returns, Emitter, ExpressionKind, ExpressionKindTracker, ResolveContext},
+    AddressSpace, Arena, BinaryOperator, Block, Expression, FastHashMap, FastHashSet, Function,
+    FunctionArgument, FunctionResult, Handle, LocalVariable, Module, Scalar, ScalarKind, Span,
+    Statement, SwizzleComponent, Type, TypeInner, UnaryOperator, VectorSize,
+};
+
+/// What the function bodies do with each global, by name.
+///
+/// How some globals are declared in the IR depends on how they're used: a
+/// texture sampled with a comparison sampler must be a depth texture, the
+/// destination of an `Interlocked*` intrinsic must be atomic, and the access
+/// of a storage texture depends on whether it's read, written or both. HLSL
+/// spells none of this out in the declaration, so all function bodies are
+/// scanned before their globals are given their final types.
+///
+/// Names are matched without regard to scoping, so a local that shadows a
+/// global counts as a use of the global.
+#[derive(Debug, Default)]
+pub struct ResourceUsage {
+    /// Textures sampled with `SampleCmp` and friends.
+    pub compared: FastHashSet<String>,
+    /// Globals used as the destination of an `Interlocked*` intrinsic.
+    pub atomic: FastHashSet<String>,
+    /// Resources read by indexing or `Load`.
+    pub loaded: FastHashSet<String>,
+    /// Resources written by indexing or `Store`.
+    pub stored: FastHashSet<String>,
+    /// Functions called from other functions.
+    pub called: FastHashSet<String>,
+}
+
+impl ResourceUsage {
+    pub fn scan(functions: &[FunctionDecl]) -> Self {
+        let mut usage = Self::default();
+        for function in functions {
+            for stmt in &function.body {
+                usage.visit_stmt(stmt);
+            }
+        }
+        usage
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt.kind {
+            StmtKind::Declare(ref decls) => {
+                for decl in decls {
+                    if let Some(ref init) = decl.init {
+                        self.visit_expr(init, false);
+                    }
+                }
+            }
+            StmtKind::Expr(ref expr) | StmtKind::Return(Some(ref expr)) => {
+                self.visit_expr(expr, false)
+            }
+            StmtKind::Block(ref stmts) => stmts.iter().for_each(|stmt| self.visit_stmt(stmt)),
+            StmtKind::If {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                self.visit_expr(condition, false);
+                self.visit_stmt(accept);
+                if let Some(ref reject) = *reject {
+                    self.visit_stmt(reject);
+                }
+            }
+            StmtKind::For {
+                ref init,
+                ref condition,
+                ref update,
+                ref body,
+            } => {
+                if let Some(ref init) = *init {
+                    self.visit_stmt(init);
+                }
+                for expr in condition.iter().chain(update) {
+                    self.visit_expr(expr, false);
+                }
+                self.visit_stmt(body);
+            }
+            StmtKind::While {
+                ref condition,
+                ref body,
+            }
+            | StmtKind::DoWhile {
+                ref body,
+                ref condition,
+            } => {
+                self.visit_expr(condition, false);
+                self.visit_stmt(body);
+            }
+            StmtKind::Switch {
+                ref selector,
+                ref cases,
+            } => {
+                self.visit_expr(selector, false);
+                for case in cases {
+                    case.body.iter().for_each(|stmt| self.visit_stmt(stmt));
+                }
+            }
+            StmtKind::Break
+            | StmtKind::Continue
+            | StmtKind::Return(None)
+            | StmtKind::Discard
+            | StmtKind::Empty => {}
+        }
+    }
+
+    /// Visit `expr`. If `store` is true, `expr` is being assigned to.
+    fn visit_expr(&mut self, expr: &Expr, store: bool) {
+        match expr.kind {
+            ExprKind::Literal(_) | ExprKind::Ident(_) => {}
+            ExprKind::Binary {
+                ref left,
+                ref right,
+                ..
+            } => {
+                self.visit_expr(left, false);
+                self.visit_expr(right, false);
+            }
+            ExprKind::Unary {
+                expr: ref inner, ..
+            }
+            | ExprKind::Cast {
+                expr: ref inner, ..
+            } => self.visit_expr(inner, false),
+            ExprKind::IncDec {
+                expr: ref inner, ..
+            } => {
+                self.visit_expr(inner, true);
+                self.visit_expr(inner, false);
+            }
+            ExprKind::Assign {
+                op,
+                ref lhs,
+                ref rhs,
+            } => {
+                self.visit_expr(lhs, true);
+                if op.is_some() {
+                    self.visit_expr(lhs, false);
+                }
+                self.visit_expr(rhs, false);
+            }
+            ExprKind::Select {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                self.visit_expr(condition, false);
+                self.visit_expr(accept, false);
+                self.visit_expr(reject, false);
+            }
+            ExprKind::Call { ref name, ref args } => {
+                self.called.insert(name.clone());
+                if name.starts_with("Interlocked") {
+                    if let Some(root) = args.first().and_then(root_name) {
+                        self.atomic.insert(root.to_string());
+                    }
+                }
+                args.iter().for_each(|arg| self.visit_expr(arg, false));
+            }
+            ExprKind::Construct { ref args, .. }
+            | ExprKind::InitList(ref args)
+            | ExprKind::Sequence(ref args) => {
+                args.iter().for_each(|arg| self.visit_expr(arg, false))
+            }
+            ExprKind::Member { ref base, .. } => self.visit_expr(base, store),
+            ExprKind::Index {
+                ref base,
+                ref index,
+            } => {
+                if let Some(root) = root_name(base) {
+                    match store {
+                        true => self.stored.insert(root.to_string()),
+                        false => self.loaded.insert(root.to_string()),
+                    };
+                }
+                self.visit_expr(base, store);
+                self.visit_expr(index, false);
+            }
+            ExprKind::Method {
+                ref base,
+                ref name,
+                ref args,
+            } => {
+                if let Some(root) = root_name(base) {
+                    let set = match name.as_str() {
+                        "SampleCmp" | "SampleCmpLevelZero" | "GatherCmp" | "GatherCmpRed" => {
+                            Some(&mut self.compared)
+                        }
+                        "Load" | "Load2" | "Load3" | "Load4" => Some(&mut self.loaded),
+                        "Store" | "Store2" | "Store3" | "Store4" => Some(&mut self.stored),
+                        _ if name.starts_with("Interlocked") => Some(&mut self.atomic),
+                        _ => None,
+                    };
+                    if let Some(set) = set {
+                        set.insert(root.to_string());
+                    }
+                }
+                self.visit_expr(base, false);
+                args.iter().for_each(|arg| self.visit_expr(arg, false));
+            }
+        }
+    }
+}
+
+/// The variable at the root of an access chain like `a.b[i].c`.
+fn root_name(expr: &Expr) -> Option<&str> {
+    match expr.kind {
+        ExprKind::Ident(ref name) => Some(name),
+        ExprKind::Member { ref base, .. } | ExprKind::Index { ref base, .. } => root_name(base),
+        _ => None,
+    }
+}
+
+/// The signature of a user function, used to resolve calls to it.
+#[derive(Debug)]
+pub struct Signature {
+    pub name: String,
+    pub handle: Handle<Function>,
+    /// The parameter types as written in the source, so `out` and `inout`
+    /// parameters aren't pointers here.
+    pub parameters: Vec<(Handle<Type>, ParameterQualifier)>,
+    pub result: Option<Handle<Type>>,
+}
+
+/// The result of lowering an expression.
+#[derive(Clone, Copy, Debug)]
+pub enum Value {
+    /// A pointer to the value, which can be assigned to.
+    Reference(Handle<Expression>),
+    Plain(Handle<Expression>),
+}
+
+/// What a name in a function body refers to.
+#[derive(Clone, Copy, Debug)]
+enum Symbol {
+    /// A pointer to a local variable or an `out` parameter.
+    Variable(Handle<Expression>),
+    /// A texture or sampler parameter.
+    Resource { expr: Handle<Expression>, ty: Ty },
+}
+
+pub struct Context<'a> {
+    pub module: &'a mut Module,
+    global_expression_kind_tracker: &'a mut ExpressionKindTracker,
+    globals: &'a FastHashMap<String, GlobalSymbol>,
+    signatures: &'a [Signature],
+    /// If true, expressions are added to the module's global expression
+    /// arena and must be constant.
+    is_const: bool,
+
+    pub expressions: Arena<Expression>,
+    pub locals: Arena<LocalVariable>,
+    pub arguments: Vec<FunctionArgument>,
+    local_expression_kind_tracker: ExpressionKindTracker,
+    typifier: Typifier,
+    emitter: Emitter,
+    pub body: Block,
+    scopes: Vec<FastHashMap<String, Symbol>>,
+    /// The result type of the function being lowered.
+    result: Option<Handle<Type>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(
+        module: &'a mut Module,
+        global_expression_kind_tracker: &'a mut ExpressionKindTracker,
+        globals: &'a FastHashMap<String, GlobalSymbol>,
+        signatures: &'a [Signature],
+        is_const: bool,
+    ) -> Self {
+        let mut ctx = Context {
+            module,
+            global_expression_kind_tracker,
+            globals,
+            signatures,
+            is_const,
+            expressions: Arena::new(),
+            locals: Arena::new(),
+            arguments: Vec::new(),
+            local_expression_kind_tracker: ExpressionKindTracker::new(),
+            typifier: Typifier::new(),
+            emitter: Emitter::default(),
+            body: Block::new(),
+            scopes: vec![FastHashMap::default()],
+            result: None,
+        };
+        if !is_const {
+            ctx.emit_start();
+        }
+        ctx
+    }
+
+    /// A context for evaluating constant expressions in the module's global
+    /// expression arena.
+    pub fn new_const(frontend: &'a mut Frontend) -> Self {
+        Self::new(
+            &mut frontend.module,
+            &mut frontend.global_expression_kind_tracker,
+            &frontend.globals,
+            &[],
+            true,
+        )
+    }
+
+    fn emit_start(&mut self) {
+        self.emitter.start(&self.expressions)
+    }
+
+    fn emit_end(&mut self) {
+        self.body.extend(self.emitter.finish(&self.expressions))
+    }
+
+    pub fn add_expression(&mut self, expr: Expression, meta: Span) -> Result<Handle<Expression>> {
+        let mut eval = if self.is_const {
+            crate::proc::ConstantEvaluator::for_glsl_module(
+                self.module,
+                self.global_expression_kind_tracker,
+            )
+        } else {
+            crate::proc::ConstantEvaluator::for_glsl_function(
+                self.module,
+                &mut self.expressions,
+                &mut self.local_expression_kind_tracker,
+                &mut self.emitter,
+                &mut self.body,
+            )
+        };
+
+        eval.try_eval_and_append(expr, meta).map_err(|e| Error {
+            kind: e.into(),
+            meta,
+        })
+    }
+
+    /// Push `stmt` to the current block, flushing any pending expressions
+    /// first.
+    pub fn add_statement(&mut self, stmt: Statement, meta: Span) {
+        self.emit_end();
+        self.body.push(stmt, meta);
+        self.emit_start();
+    }
+
+    /// Push a statement whose result is an expression that must not be
+    /// covered by an `Emit`, like a [`CallResult`] or [`AtomicResult`].
+    ///
+    /// [`CallResult`]: Expression::CallResult
+    /// [`AtomicResult`]: Expression::AtomicResult
+    pub fn add_statement_with_result(
+        &mut self,
+        result: Option<Expression>,
+        stmt: impl FnOnce(Option<Handle<Expression>>) -> Statement,
+        meta: Span,
+    ) -> Option<Handle<Expression>> {
+        self.emit_end();
+        let result = result.map(|expr| {
+            let handle = self.expressions.append(expr, meta);
+            self.local_expression_kind_tracker
+                .insert(handle, ExpressionKind::Runtime);
+            handle
+        });
+        self.body.push(stmt(result), meta);
+        self.emit_start();
+        result
+    }
+
+    /// Lower a block of code in a new scope.
+    pub fn new_body<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<(Block, R)> {
+        self.emit_end();
+        let outer = std::mem::take(&mut self.body);
+        self.scopes.push(FastHashMap::default());
+        self.emit_start();
+        let result = f(self);
+        self.emit_end();
+        self.scopes.pop();
+        let block = std::mem::replace(&mut self.body, outer);
+        self.emit_start();
+        result.map(|result| (block, result))
+    }
+
+    fn grow(&mut self, expr: Handle<Expression>, meta: Span) -> Result<()> {
+        let resolve_ctx = ResolveContext::with_locals(self.module, &self.locals, &self.arguments);
+        let expressions = if self.is_const {
+            &self.module.global_expressions
+        } else {
+            &self.expressions
+        };
+        self.typifier
+            .grow(expr, expressions, &resolve_ctx)
+            .map_err(|error| Error {
+                kind: ErrorKind::SemanticError(format!("Can't resolve type: {error:?}").into()),
+                meta,
+            })
+    }
+
+    pub fn resolve_type(&mut self, expr: Handle<Expression>, meta: Span) -> Result<TypeInner> {
+        self.grow(expr, meta)?;
+        Ok(self.typifier.get(expr, &self.module.types).clone())
+    }
+
+    pub fn resolve_type_handle(
+        &mut self,
+        expr: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Type>> {
+        self.grow(expr, meta)?;
+        Ok(self.typifier.register_type(expr, &mut self.module.types))
+    }
+
+    pub fn type_handle(&mut self, inner: TypeInner) -> Handle<Type> {
+        self.module
+            .types
+            .insert(Type { name: None, inner }, Span::UNDEFINED)
+    }
+
+    fn expression(&self, handle: Handle<Expression>) -> &Expression {
+        if self.is_const {
+            &self.module.global_expressions[handle]
+        } else {
+            &self.expressions[handle]
+        }
+    }
+
+    /// The value of `expr` if it's a non-negative integer literal.
+    pub fn const_index(&self, expr: Handle<Expression>) -> Option<u32> {
+        match *self.expression(expr) {
+            Expression::Literal(crate::Literal::U32(value)) => Some(value),
+            Expression::Literal(crate::Literal::I32(value)) => u32::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn add_local(
+        &mut self,
+        name: Option<String>,
+        ty: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let local = self.locals.append(
+            LocalVariable {
+                name,
+                ty,
+                init: None,
+            },
+            meta,
+        );
+        self.add_expression(Expression::LocalVariable(local), meta)
+    }
+
+    fn declare(&mut self, name: String, symbol: Symbol, meta: Span) -> Result<()> {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.contains_key(&name) {
+            return Err(Error {
+                kind: ErrorKind::VariableAlreadyDeclared(name),
+                meta,
+            });
+        }
+        scope.insert(name, symbol);
+        Ok(())
+    }
+
+    fn lookup(&mut self, name: &str, meta: Span) -> Result<Value> {
+        for scope in self.scopes.iter().rev() {
+            match scope.get(name) {
+                Some(&Symbol::Variable(pointer)) => return Ok(Value::Reference(pointer)),
+                Some(&Symbol::Resource { expr, .. }) => return Ok(Value::Plain(expr)),
+                None => {}
+            }
+        }
+
+        let symbol = self.globals.get(name).copied().ok_or_else(|| Error {
+            kind: ErrorKind::UnknownVariable(name.to_string()),
+            meta,
+        })?;
+        if self.is_const && !matches!(symbol, GlobalSymbol::Constant(_)) {
+            return Err(Error::semantic(
+                format!("`{name}` can't be used in a constant expression"),
+                meta,
+            ));
+        }
+        Ok(match symbol {
+            GlobalSymbol::Constant(handle) => {
+                Value::Plain(self.add_expression(Expression::Constant(handle), meta)?)
+            }
+            GlobalSymbol::Variable(handle) => {
+                Value::Reference(self.add_expression(Expression::GlobalVariable(handle), meta)?)
+            }
+            GlobalSymbol::BlockMember { handle, index } => {
+                let base = self.add_expression(Expression::GlobalVariable(handle), meta)?;
+                Value::Reference(
+                    self.add_expression(Expression::AccessIndex { base, index }, meta)?,
+                )
+            }
+            GlobalSymbol::Resource { handle, .. } => {
+                let expr = self.add_expression(Expression::GlobalVariable(handle), meta)?;
+                match self.module.global_variables[handle].space {
+                    AddressSpace::Handle => Value::Plain(expr),
+                    _ => Value::Reference(expr),
+                }
+            }
+        })
+    }
+
+    /// The resource type of `expr`, if it names a resource.
+    pub fn resource(&self, expr: &Expr) -> Option<Ty> {
+        let ExprKind::Ident(ref name) = expr.kind else {
+            return None;
+        };
+        for scope in self.scopes.iter().rev() {
+            match scope.get(name) {
+                Some(&Symbol::Resource { ty, .. }) => return Some(ty),
+                Some(&Symbol::Variable(_)) => return None,
+                None => {}
+            }
+        }
+        match self.globals.get(name) {
+            Some(&GlobalSymbol::Resource { ty, .. }) => Some(ty),
+            _ => None,
+        }
+    }
+
+    // Expressions
+
+    pub fn load(&mut self, value: Value, meta: Span) -> Result<Handle<Expression>> {
+        match value {
+            Value::Plain(expr) => Ok(expr),
+            Value::Reference(pointer) => self.add_expression(Expression::Load { pointer }, meta),
+        }
+    }
+
+    pub fn lower_rvalue(&mut self, expr: &Expr) -> Result<Handle<Expression>> {
+        let value = self.lower(expr)?;
+        self.load(value, expr.meta)
+    }
+
+    pub fn lower_lvalue(&mut self, expr: &Expr) -> Result<Handle<Expression>> {
+        match self.lower(expr)? {
+            Value::Reference(pointer) => Ok(pointer),
+            Value::Plain(_) => Err(Error::semantic(
+                "expression can't be assigned to",
+                expr.meta,
+            )),
+        }
+    }
+
+    /// Lower `expr` as a constant expression in the module's global
+    /// expression arena, converting it to `scalar`.
+    pub fn lower_const_as(&mut self, expr: &Expr, scalar: Scalar) -> Result<Handle<Expression>> {
+        if self.is_const {
+            let value = self.lower_rvalue(expr)?;
+            return self.convert_scalar(value, scalar, expr.meta);
+        }
+        let mut ctx = Context::new(
+            self.module,
+            self.global_expression_kind_tracker,
+            self.globals,
+            &[],
+            true,
+        );
+        let value = ctx.lower_rvalue(expr)?;
+        ctx.convert_scalar(value, scalar, expr.meta)
+    }
+
+    /// Lower `expr` and convert it to `ty`.
+    pub fn lower_as(&mut self, expr: &Expr, ty: Handle<Type>) -> Result<Handle<Expression>> {
+        if let ExprKind::InitList(ref items) = expr.kind {
+            return self.init_list(ty, items, expr.meta);
+        }
+        let value = self.lower_rvalue(expr)?;
+        self.convert(value, ty, expr.meta)
+    }
+
+    pub fn lower(&mut self, expr: &Expr) -> Result<Value> {
+        let meta = expr.meta;
+        let plain = |handle| Ok(Value::Plain(handle));
+        match expr.kind {
+            ExprKind::Literal(ref literal) => {
+                let literal = match *literal {
+                    Literal::Bool(value) => crate::Literal::Bool(value),
+                    Literal::Int(value) => crate::Literal::I32(value as i32),
+                    Literal::Uint(value) => crate::Literal::U32(value as u32),
+                    Literal::Float(value) => crate::Literal::F32(value as f32),
+                    Literal::Double(value) => crate::Literal::F64(value),
+                };
+                plain(self.add_expression(Expression::Literal(literal), meta)?)
+            }
+            ExprKind::Ident(ref name) => self.lookup(name, meta),
+            ExprKind::Binary {
+                op,
+                ref left,
+                ref right,
+            } => {
+                let left = self.lower_rvalue(left)?;
+                let right = self.lower_rvalue(right)?;
+                plain(self.binary(op, left, right, meta)?)
+            }
+            ExprKind::Unary {
+                op,
+                expr: ref inner,
+            } => {
+                let value = self.lower_rvalue(inner)?;
+                let (op, value) = match op {
+                    UnaryOp::Negate => (UnaryOperator::Negate, value),
+                    UnaryOp::LogicalNot => (UnaryOperator::LogicalNot, self.to_bool(value, meta)?),
+                    UnaryOp::BitwiseNot => (UnaryOperator::BitwiseNot, value),
+                };
+                plain(self.add_expression(Expression::Unary { op, expr: value }, meta)?)
+            }
+            ExprKind::IncDec {
+                increment,
+                postfix,
+                expr: ref inner,
+            } => {
+                let pointer = self.lower_lvalue(inner)?;
+                let old = self.add_expression(Expression::Load { pointer }, meta)?;
+                let one = self.one_like(old, meta)?;
+                let op = if increment {
+                    BinaryOperator::Add
+                } else {
+                    BinaryOperator::Subtract
+                };
+                let new = self.add_expression(
+                    Expression::Binary {
+                        op,
+                        left: old,
+                        right: one,
+                    },
+                    meta,
+                )?;
+                self.add_statement(
+                    Statement::Store {
+                        pointer,
+                        value: new,
+                    },
+                    meta,
+                );
+                plain(if postfix { old } else { new })
+            }
+            ExprKind::Assign {
+                op,
+                ref lhs,
+                ref rhs,
+            } => plain(self.assign(lhs, op, rhs, meta)?),
+            ExprKind::Select {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.lower_rvalue(condition)?;
+                let condition = self.to_bool(condition, meta)?;
+                let accept = self.lower_rvalue(accept)?;
+                let reject = self.lower_rvalue(reject)?;
+                let (mut accept, mut reject) = self.unify(accept, reject, meta)?;
+                if let TypeInner::Vector { size, .. } = self.resolve_type(condition, meta)? {
+                    accept = self.splat(accept, size, meta)?;
+                    reject = self.splat(reject, size, meta)?;
+                }
+                plain(self.add_expression(
+                    Expression::Select {
+                        condition,
+                        accept,
+                        reject,
+                    },
+                    meta,
+                )?)
+            }
+            ExprKind::Call { ref name, ref args } => {
+                let result = self.call(name, args, meta)?;
+                plain(result.ok_or_else(|| {
+                    Error::semantic(format!("`{name}` doesn't return a value"), meta)
+                })?)
+            }
+            ExprKind::Method {
+                ref base,
+                ref name,
+                ref args,
+            } => {
+                let result = self.method(base, name, args, meta)?;
+                plain(result.ok_or_else(|| {
+                    Error::semantic(format!("`{name}` doesn't return a value"), meta)
+                })?)
+            }
+            ExprKind::Construct { ty, ref args } => plain(self.construct(ty, args, meta)?),
+            ExprKind::Cast {
+                ty,
+                expr: ref inner,
+            } => {
+                let is_zero = matches!(
+                    inner.kind,
+                    ExprKind::Literal(Literal::Int(0) | Literal::Uint(0))
+                );
+                match self.module.types[ty].inner {
+                    TypeInner::Struct { .. } | TypeInner::Array { .. } if is_zero => {
+                        plain(self.add_expression(Expression::ZeroValue(ty), meta)?)
+                    }
+                    _ => {
+                        let value = self.lower_rvalue(inner)?;
+                        plain(self.convert(value, ty, meta)?)
+                    }
+                }
+            }
+            ExprKind::Member {
+                ref base,
+                ref field,
+            } => {
+                let base = self.lower(base)?;
+                self.member(base, field, meta)
+            }
+            ExprKind::Index {
+                ref base,
+                ref index,
+            } => self.index(base, index, meta),
+            ExprKind::InitList(_) => Err(Error::semantic(
+                "initializer lists can only be used to initialize variables",
+                meta,
+            )),
+            ExprKind::Sequence(ref items) => {
+                let (last, rest) = items.split_last().unwrap();
+                for item in rest {
+                    self.lower_effect(item)?;
+                }
+                self.lower(last)
+            }
+        }
+    }
+
+    /// Lower `expr` for its side effects only.
+    fn lower_effect(&mut self, expr: &Expr) -> Result<()> {
+        match expr.kind {
+            ExprKind::Call { ref name, ref args } => self.call(name, args, expr.meta).map(drop),
+            ExprKind::Method {
+                ref base,
+                ref name,
+                ref args,
+            } => self.method(base, name, args, expr.meta).map(drop),
+            ExprKind::Sequence(ref items) => {
+                items.iter().try_for_each(|item| self.lower_effect(item))
+            }
+            _ => self.lower(expr).map(drop),
+        }
+    }
+
+    /// A value of one with the same type as `expr`.
+    fn one_like(&mut self, expr: Handle<Expression>, meta: Span) -> Result<Handle<Expression>> {
+        let inner = self.resolve_type(expr, meta)?;
+        let scalar = scalar_components(&inner)
+            .filter(|scalar| scalar.kind != ScalarKind::Bool)
+            .ok_or_else(|| Error::semantic("can't increment a non-numeric value", meta))?;
+        let one = self.scalar_literal(scalar, 1.0, meta)?;
+        match inner {
+            TypeInner::Vector { size, .. } => {
+                self.add_expression(Expression::Splat { size, value: one }, meta)
+            }
+            _ => Ok(one),
+        }
+    }
+
+    pub fn scalar_literal(
+        &mut self,
+        scalar: Scalar,
+        value: f64,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let literal = match scalar {
+            Scalar::F64 => crate::Literal::F64(value),
+            Scalar::I32 => crate::Literal::I32(value as i32),
+            Scalar::U32 => crate::Literal::U32(value as u32),
+            Scalar::I64 => crate::Literal::I64(value as i64),
+            Scalar::U64 => crate::Literal::U64(value as u64),
+            Scalar::BOOL => crate::Literal::Bool(value != 0.0),
+            _ => crate::Literal::F32(value as f32),
+        };
+        self.add_expression(Expression::Literal(literal), meta)
+    }
+
+    fn member(&mut self, base: Value, field: &str, meta: Span) -> Result<Value> {
+        let (base_expr, is_ref) = match base {
+            Value::Reference(pointer) => (pointer, true),
+            Value::Plain(expr) => (expr, false),
+        };
+        let inner = match self.resolve_type(base_expr, meta)? {
+            TypeInner::Pointer { base, .. } => self.module.types[base].inner.clone(),
+            TypeInner::ValuePointer {
+                size: Some(size),
+                scalar,
+                ..
+            } => TypeInner::Vector { size, scalar },
+            TypeInner::ValuePointer {
+                size: None, scalar, ..
+            } => TypeInner::Scalar(scalar),
+            inner => inner,
+        };
+        let wrap = |expr| {
+            if is_ref {
+                Value::Reference(expr)
+            } else {
+                Value::Plain(expr)
+            }
+        };
+
+        match inner {
+            TypeInner::Struct { ref members, .. } => {
+                let index = members
+                    .iter()
+                    .position(|member| member.name.as_deref() == Some(field))
+                    .ok_or_else(|| Error {
+                        kind: ErrorKind::UnknownField(field.to_string()),
+                        meta,
+                    })?;
+                let expr = self.add_expression(
+                    Expression::AccessIndex {
+                        base: base_expr,
+                        index: index as u32,
+                    },
+                    meta,
+                )?;
+                Ok(wrap(expr))
+            }
+            TypeInner::Scalar(_) | TypeInner::Vector { .. } => {
+                let size = match inner {
+                    TypeInner::Vector { size, .. } => size as u32,
+                    _ => 1,
+                };
+                let pattern = parse_swizzle(field, size).ok_or_else(|| Error {
+                    kind: ErrorKind::UnknownField(field.to_string()),
+                    meta,
+                })?;
+                if let [component] = pattern[..] {
+                    if size == 1 {
+                        return Ok(base);
+                    }
+                    let expr = self.add_expression(
+                        Expression::AccessIndex {
+                            base: base_expr,
+                            index: component as u32,
+                        },
+                        meta,
+                    )?;
+                    return Ok(wrap(expr));
+                }
+
+                let vector = self.load(base, meta)?;
+                let result_size = match pattern.len() {
+                    2 => VectorSize::Bi,
+                    3 => VectorSize::Tri,
+                    _ => VectorSize::Quad,
+                };
+                if size == 1 {
+                    return Ok(Value::Plain(self.add_expression(
+                        Expression::Splat {
+                            size: result_size,
+                            value: vector,
+                        },
+                        meta,
+                    )?));
+                }
+                let mut components = [SwizzleComponent::X; 4];
+                components[..pattern.len()].copy_from_slice(&pattern);
+                Ok(Value::Plain(self.add_expression(
+                    Expression::Swizzle {
+                        size: result_size,
+                        vector,
+                        pattern: components,
+                    },
+                    meta,
+                )?))
+            }
+            TypeInner::Matrix { .. } => {
+                let (row, column) = parse_matrix_element(field).ok_or_else(|| Error {
+                    kind: ErrorKind::UnknownField(field.to_string()),
+                    meta,
+                })?;
+                let column = self.add_expression(
+                    Expression::AccessIndex {
+                        base: base_expr,
+                        index: column,
+                    },
+                    meta,
+                )?;
+                let expr = self.add_expression(
+                    Expression::AccessIndex {
+                        base: column,
+                        index: row,
+                    },
+                    meta,
+                )?;
+                Ok(wrap(expr))
+            }
+            _ => Err(Error {
+                kind: ErrorKind::UnknownField(field.to_string()),
+                meta,
+            }),
+        }
+    }
+
+    fn index(&mut self, base_expr: &Expr, index_expr: &Expr, meta: Span) -> Result<Value> {
+        if let Some(Ty::Texture { element, .. } | Ty::RwTexture { element, .. }) =
+            self.resource(base_expr)
+        {
+            let image = self.lower_rvalue(base_expr)?;
+            let coordinate = self.lower_rvalue(index_expr)?;
+            return Ok(Value::Plain(
+                self.image_load(image, coordinate, element, meta)?,
+            ));
+        }
+
+        // `m[i][j]` is the element in row `i` and column `j`, which naga
+        // writes as `m[j][i]`.
+        if let ExprKind::Index {
+            base: ref matrix,
+            index: ref row,
+        } = base_expr.kind
+        {
+            let value = self.lower(matrix)?;
+            if self.is_matrix(value, meta)? {
+                let row = self.lower_rvalue(row)?;
+                let column = self.lower_rvalue(index_expr)?;
+                let column = self.access(value, column, meta)?;
+                return self.access(column, row, meta);
+            }
+            let outer = self.access_lowered(value, row, meta)?;
+            let index = self.lower_rvalue(index_expr)?;
+            return self.access(outer, index, meta);
+        }
+
+        let base = self.lower(base_expr)?;
+        self.access_lowered(base, index_expr, meta)
+    }
+
+    /// Index `base`, treating matrices the HLSL way.
+    fn access_lowered(&mut self, base: Value, index_expr: &Expr, meta: Span) -> Result<Value> {
+        let index = self.lower_rvalue(index_expr)?;
+        if !self.is_matrix(base, meta)? {
+            return self.access(base, index, meta);
+        }
+
+        // Gather row `index` from each column.
+        let matrix = self.load(base, meta)?;
+        let TypeInner::Matrix {
+            columns, scalar, ..
+        } = self.resolve_type(matrix, meta)?
+        else {
+            unreachable!()
+        };
+        let mut components = Vec::with_capacity(columns as usize);
+        for column in 0..columns as u32 {
+            let column = self.add_expression(
+                Expression::AccessIndex {
+                    base: matrix,
+                    index: column,
+                },
+                meta,
+            )?;
+            let element = self.access(Value::Plain(column), index, meta)?;
+            components.push(self.load(element, meta)?);
+        }
+        let ty = self.type_handle(TypeInner::Vector {
+            size: columns,
+            scalar,
+        });
+        Ok(Value::Plain(self.add_expression(
+            Expression::Compose { ty, components },
+            meta,
+        )?))
+    }
+
+    fn is_matrix(&mut self, value: Value, meta: Span) -> Result<bool> {
+        Ok(match value {
+            Value::Reference(pointer) => match self.resolve_type(pointer, meta)? {
+                TypeInner::Pointer { base, .. } => {
+                    matches!(self.module.types[base].inner, TypeInner::Matrix { .. })
+                }
+                _ => false,
+            },
+            Value::Plain(expr) => {
+                matches!(self.resolve_type(expr, meta)?, TypeInner::Matrix { .. })
+            }
+        })
+    }
+
+    /// Index `base` with naga semantics.
+    fn access(&mut self, base: Value, index: Handle<Expression>, meta: Span) -> Result<Value> {
+        let index = match self.resolve_type(index, meta)? {
+            TypeInner::Scalar(Scalar {
+                kind: ScalarKind::Sint | ScalarKind::Uint,
+                ..
+            }) => index,
+            TypeInner::Scalar(_) => self.add_expression(
+                Expression::As {
+                    expr: index,
+                    kind: ScalarKind::Sint,
+                    convert: Some(4),
+                },
+                meta,
+            )?,
+            _ => return Err(Error::semantic("index must be a scalar", meta)),
+        };
+
+        match base {
+            Value::Reference(pointer) => {
+                let expr = match self.const_index(index) {
+                    Some(index) => Expression::AccessIndex {
+                        base: pointer,
+                        index,
+                    },
+                    None => Expression::Access {
+                        base: pointer,
+                        index,
+                    },
+                };
+                Ok(Value::Reference(self.add_expression(expr, meta)?))
+            }
+            Value::Plain(value) => {
+                if let Some(index) = self.const_index(index) {
+                    let expr = Expression::AccessIndex { base: value, index };
+                    return Ok(Value::Plain(self.add_expression(expr, meta)?));
+                }
+                match self.resolve_type(value, meta)? {
+                    TypeInner::Vector { .. } => Ok(Value::Plain(
+                        self.add_expression(Expression::Access { base: value, index }, meta)?,
+                    )),
+                    _ if self.is_const => Err(Error::semantic(
+                        "arrays can only be indexed with constants here",
+                        meta,
+                    )),
+                    // Arrays and matrices can only be indexed dynamically
+                    // behind a pointer, so spill the value to a local.
+                    _ => {
+                        let ty = self.resolve_type_handle(value, meta)?;
+                        let pointer = self.add_local(None, ty, meta)?;
+                        self.add_statement(Statement::Store { pointer, value }, meta);
+                        let element = self.add_expression(
+                            Expression::Access {
+                                base: pointer,
+                                index,
+                            },
+                            meta,
+                        )?;
+                        Ok(Value::Plain(self.add_expression(
+                            Expression::Load { pointer: element },
+                            meta,
+                        )?))
+                    }
+                }
+            }
+        }
+    }
+
+    /// The type stored behind `pointer`, with atomics replaced by their
+    /// contents.
+    pub fn pointee_type(
+        &mut self,
+        pointer: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Type>> {
+        let base = match self.resolve_type(pointer, meta)? {
+            TypeInner::Pointer { base, .. } => base,
+            TypeInner::ValuePointer {
+                size: Some(size),
+                scalar,
+                ..
+            } => self.type_handle(TypeInner::Vector { size, scalar }),
+            TypeInner::ValuePointer {
+                size: None, scalar, ..
+            } => self.type_handle(TypeInner::Scalar(scalar)),
+            _ => return Err(Error::semantic("expression can't be assigned to", meta)),
+        };
+        Ok(match self.module.types[base].inner {
+            TypeInner::Atomic(scalar) => self.type_handle(TypeInner::Scalar(scalar)),
+            _ => base,
+        })
+    }
+
+    /// Store `value` through `pointer`, converting it to the pointee type.
+    pub fn store(
+        &mut self,
+        pointer: Handle<Expression>,
+        value: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let ty = self.pointee_type(pointer, meta)?;
+        let value = self.convert(value, ty, meta)?;
+        self.add_statement(Statement::Store { pointer, value }, meta);
+        Ok(value)
+    }
+
+    fn assign(
+        &mut self,
+        lhs: &Expr,
+        op: Option<BinaryOp>,
+        rhs: &Expr,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        // Writes to storage textures.
+        if let ExprKind::Index {
+            ref base,
+            ref index,
+        } = lhs.kind
+        {
+            if let Some(Ty::RwTexture { element, .. }) = self.resource(base) {
+                let image = self.lower_rvalue(base)?;
+                let coordinate = self.lower_rvalue(index)?;
+                let mut value = self.lower_rvalue(rhs)?;
+                if let Some(op) = op {
+                    let current = self.image_load(image, coordinate, element, meta)?;
+                    value = self.binary(op, current, value, meta)?;
+                }
+                let value = self.convert(value, element, meta)?;
+                self.image_store(image, coordinate, value, meta)?;
+                return Ok(value);
+            }
+        }
+
+        // Writes to several components of a vector, or a row of a matrix.
+        let components = match lhs.kind {
+            ExprKind::Member {
+                ref base,
+                ref field,
+            } if field.len() > 1 && !field.starts_with('_') => {
+                let pointer = self.lower_lvalue(base)?;
+                match self.pointee_type(pointer, meta)? {
+                    ty if matches!(self.module.types[ty].inner, TypeInner::Vector { .. }) => {
+                        let TypeInner::Vector { size, .. } = self.module.types[ty].inner else {
+                            unreachable!()
+                        };
+                        let pattern = parse_swizzle(field, size as u32).ok_or_else(|| Error {
+                            kind: ErrorKind::UnknownField(field.clone()),
+                            meta,
+                        })?;
+                        let mut components = Vec::with_capacity(pattern.len());
+                        for component in pattern {
+                            components.push(self.add_expression(
+                                Expression::AccessIndex {
+                                    base: pointer,
+                                    index: component as u32,
+                                },
+                                meta,
+                            )?);
+                        }
+                        Some(components)
+                    }
+                    _ => None,
+                }
+            }
+            ExprKind::Index {
+                ref base,
+                ref index,
+            } => {
+                let value = self.lower(base)?;
+                match value {
+                    Value::Reference(pointer) if self.is_matrix(value, meta)? => {
+                        let row = self.lower_rvalue(index)?;
+                        let ty = self.pointee_type(pointer, meta)?;
+                        let TypeInner::Matrix { columns, .. } = self.module.types[ty].inner else {
+                            unreachable!()
+                        };
+                        let mut components = Vec::with_capacity(columns as usize);
+                        for column in 0..columns as u32 {
+                            let column = self.add_expression(
+                                Expression::AccessIndex {
+                                    base: pointer,
+                                    index: column,
+                                },
+                                meta,
+                            )?;
+                            let Value::Reference(element) =
+                                self.access(Value::Reference(column), row, meta)?
+                            else {
+                                unreachable!()
+                            };
+                            components.push(element);
+                        }
+                        Some(components)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(pointers) = components {
+            let mut loaded = Vec::with_capacity(pointers.len());
+            if op.is_some() {
+                for &pointer in &pointers {
+                    loaded.push(self.add_expression(Expression::Load { pointer }, meta)?);
+                }
+            }
+            let first = self.pointee_type(pointers[0], meta)?;
+            let TypeInner::Scalar(scalar) = self.module.types[first].inner else {
+                unreachable!()
+            };
+            let size = match pointers.len() {
+                2 => VectorSize::Bi,
+                3 => VectorSize::Tri,
+                _ => VectorSize::Quad,
+            };
+            let ty = self.type_handle(TypeInner::Vector { size, scalar });
+            let mut value = self.lower_rvalue(rhs)?;
+            if let Some(op) = op {
+                let current = self.add_expression(
+                    Expression::Compose {
+                        ty,
+                        components: loaded,
+                    },
+                    meta,
+                )?;
+                value = self.binary(op, current, value, meta)?;
+            }
+            let value = self.convert(value, ty, meta)?;
+            for (index, pointer) in pointers.into_iter().enumerate() {
+                let component = self.add_expression(
+                    Expression::AccessIndex {
+                        base: value,
+                        index: index as u32,
+                    },
+                    meta,
+                )?;
+                self.add_statement(
+                    Statement::Store {
+                        pointer,
+                        value: component,
+                    },
+                    meta,
+                );
+            }
+            return Ok(value);
+        }
+
+        let pointer = self.lower_lvalue(lhs)?;
+        let mut value = self.lower_rvalue(rhs)?;
+        if let Some(op) = op {
+            let current = self.add_expression(Expression::Load { pointer }, meta)?;
+            value = self.binary(op, current, value, meta)?;
+        }
+        self.store(pointer, value, meta)
+    }
+
+    pub fn to_bool(&mut self, value: Handle<Expression>, meta: Span) -> Result<Handle<Expression>> {
+        match scalar_components(&self.resolve_type(value, meta)?) {
+            Some(Scalar::BOOL) => Ok(value),
+            Some(_) => self.add_expression(
+                Expression::As {
+                    expr: value,
+                    kind: ScalarKind::Bool,
+                    convert: Some(crate::BOOL_WIDTH),
+                },
+                meta,
+            ),
+            None => Err(Error::semantic("expected a scalar or vector", meta)),
+        }
+    }
+
+    pub fn splat(
+        &mut self,
+        value: Handle<Expression>,
+        size: VectorSize,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        match self.resolve_type(value, meta)? {
+            TypeInner::Scalar(_) => self.add_expression(Expression::Splat { size, value }, meta),
+            _ => Ok(value),
+        }
+    }
+
+    /// Convert `value` to the scalar type `scalar`, keeping its shape.
+    pub fn convert_scalar(
+        &mut self,
+        value: Handle<Expression>,
+        scalar: Scalar,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        match scalar_components(&self.resolve_type(value, meta)?) {
+            Some(current) if current == scalar => Ok(value),
+            Some(_) => self.add_expression(
+                Expression::As {
+                    expr: value,
+                    kind: scalar.kind,
+                    convert: Some(scalar.width),
+                },
+                meta,
+            ),
+            None => Err(Error::semantic("expected a numeric value", meta)),
+        }
+    }
+
+    /// Resize the vector `value` to `size` components, dropping the extra ones.
+    fn truncate(
+        &mut self,
+        value: Handle<Expression>,
+        size: VectorSize,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        match self.resolve_type(value, meta)? {
+            TypeInner::Vector { size: current, .. } if (current as u8) > (size as u8) => self
+                .add_expression(
+                    Expression::Swizzle {
+                        size,
+                        vector: value,
+                        pattern: SwizzleComponent::XYZW,
+                    },
+                    meta,
+                ),
+            TypeInner::Vector { size: current, .. } if current != size => Err(Error::semantic(
+                "can't convert a vector to a larger vector",
+                meta,
+            )),
+            _ => Ok(value),
+        }
+    }
+
+    /// Convert `value` to `ty` using HLSL's conversion rules.
+    pub fn convert(
+        &mut self,
+        value: Handle<Expression>,
+        ty: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let source = self.resolve_type(value, meta)?;
+        let target = self.module.types[ty].inner.clone();
+        if source.equivalent(&target, &self.module.types) {
+            return Ok(value);
+        }
+
+        match (source, target) {
+            (TypeInner::Scalar(_), TypeInner::Scalar(scalar)) => {
+                self.convert_scalar(value, scalar, meta)
+            }
+            (TypeInner::Scalar(_), TypeInner::Vector { size, scalar }) => {
+                let value = self.convert_scalar(value, scalar, meta)?;
+                self.add_expression(Expression::Splat { size, value }, meta)
+            }
+            (
+                TypeInner::Scalar(_),
+                TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                },
+            ) => {
+                let value = self.convert_scalar(value, scalar, meta)?;
+                let column = self.add_expression(Expression::Splat { size: rows, value }, meta)?;
+                let components = vec![column; columns as usize];
+                self.add_expression(Expression::Compose { ty, components }, meta)
+            }
+            (TypeInner::Vector { .. }, TypeInner::Scalar(scalar)) => {
+                let value = self.add_expression(
+                    Expression::AccessIndex {
+                        base: value,
+                        index: 0,
+                    },
+                    meta,
+                )?;
+                self.convert_scalar(value, scalar, meta)
+            }
+            (TypeInner::Vector { .. }, TypeInner::Vector { size, scalar }) => {
+                let value = self.truncate(value, size, meta)?;
+                self.convert_scalar(value, scalar, meta)
+            }
+            (
+                TypeInner::Matrix {
+                    columns: source_columns,
+                    rows: source_rows,
+                    ..
+                },
+                TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                },
+            ) if source_columns == columns && source_rows == rows => {
+                self.convert_scalar(value, scalar, meta)
+            }
+            (source, target) => Err(Error::semantic(
+                format!("can't convert a value of type {source:?} to {target:?}"),
+                meta,
+            )),
+        }
+    }
+
+    /// Convert `left` and `right` to a common type for a binary operation.
+    pub fn unify(
+        &mut self,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        meta: Span,
+    ) -> Result<(Handle<Expression>, Handle<Expression>)> {
+        let left_inner = self.resolve_type(left, meta)?;
+        let right_inner = self.resolve_type(right, meta)?;
+        let (Some(left_scalar), Some(right_scalar)) = (
+            scalar_components(&left_inner),
+            scalar_components(&right_inner),
+        ) else {
+            return Ok((left, right));
+        };
+
+        let scalar = match (type_power(left_scalar), type_power(right_scalar)) {
+            (Some(l), Some(r)) if r > l => right_scalar,
+            _ => left_scalar,
+        };
+        let mut left = self.convert_scalar(left, scalar, meta)?;
+        let mut right = self.convert_scalar(right, scalar, meta)?;
+
+        match (left_inner, right_inner) {
+            (TypeInner::Vector { size: l, .. }, TypeInner::Vector { size: r, .. }) if l != r => {
+                let size = std::cmp::min(l as u8, r as u8);
+                let size = if size == VectorSize::Bi as u8 {
+                    VectorSize::Bi
+                } else {
+                    VectorSize::Tri
+                };
+                left = self.truncate(left, size, meta)?;
+                right = self.truncate(right, size, meta)?;
+            }
+            (TypeInner::Vector { size, .. }, TypeInner::Scalar(_)) => {
+                right = self.splat(right, size, meta)?;
+            }
+            (TypeInner::Scalar(_), TypeInner::Vector { size, .. }) => {
+                left = self.splat(left, size, meta)?;
+            }
+            _ => {}
+        }
+        Ok((left, right))
+    }
+
+    pub fn binary(
+        &mut self,
+        op: BinaryOp,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let op = match op {
+            BinaryOp::Add => BinaryOperator::Add,
+            BinaryOp::Subtract => BinaryOperator::Subtract,
+            BinaryOp::Multiply => BinaryOperator::Multiply,
+            BinaryOp::Divide => BinaryOperator::Divide,
+            BinaryOp::Modulo => BinaryOperator::Modulo,
+            BinaryOp::Equal => BinaryOperator::Equal,
+            BinaryOp::NotEqual => BinaryOperator::NotEqual,
+            BinaryOp::Less => BinaryOperator::Less,
+            BinaryOp::LessEqual => BinaryOperator::LessEqual,
+            BinaryOp::Greater => BinaryOperator::Greater,
+            BinaryOp::GreaterEqual => BinaryOperator::GreaterEqual,
+            BinaryOp::And => BinaryOperator::And,
+            BinaryOp::ExclusiveOr => BinaryOperator::ExclusiveOr,
+            BinaryOp::InclusiveOr => BinaryOperator::InclusiveOr,
+            BinaryOp::LogicalAnd | BinaryOp::LogicalOr => {
+                let left = self.to_bool(left, meta)?;
+                let right = self.to_bool(right, meta)?;
+                let (left, right) = self.unify(left, right, meta)?;
+                let op = if op == BinaryOp::LogicalAnd {
+                    BinaryOperator::LogicalAnd
+                } else {
+                    BinaryOperator::LogicalOr
+                };
+                return self.add_expression(Expression::Binary { op, left, right }, meta);
+            }
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+                let op = if op == BinaryOp::ShiftLeft {
+                    BinaryOperator::ShiftLeft
+                } else {
+                    BinaryOperator::ShiftRight
+                };
+                let right = self.convert_scalar(right, Scalar::U32, meta)?;
+                let right = match self.resolve_type(left, meta)? {
+                    TypeInner::Vector { size, .. } => self.splat(right, size, meta)?,
+                    _ => right,
+                };
+                return self.add_expression(Expression::Binary { op, left, right }, meta);
+            }
+        };
+
+        let (left, right) = self.unify(left, right, meta)?;
+        let left_inner = self.resolve_type(left, meta)?;
+        let right_inner = self.resolve_type(right, meta)?;
+        match (&left_inner, &right_inner) {
+            (&TypeInner::Matrix { .. }, _) | (_, &TypeInner::Matrix { .. }) => {
+                // Multiplying a matrix by a scalar works the same either way.
+                let scalar_factor = op == BinaryOperator::Multiply
+                    && (matches!(left_inner, TypeInner::Scalar(_))
+                        || matches!(right_inner, TypeInner::Scalar(_)));
+                if scalar_factor {
+                    return self.add_expression(Expression::Binary { op, left, right }, meta);
+                }
+                self.matrix_componentwise(op, left, right, meta)
+            }
+            _ => self.add_expression(Expression::Binary { op, left, right }, meta),
+        }
+    }
+
+    /// Apply `op` to each column of the operands. HLSL's `*` and `/` on
+    /// matrices are component-wise, unlike naga's.
+    fn matrix_componentwise(
+        &mut self,
+        op: BinaryOperator,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let ty = match self.resolve_type(left, meta)? {
+            TypeInner::Matrix { .. } => self.resolve_type_handle(left, meta)?,
+            _ => self.resolve_type_handle(right, meta)?,
+        };
+        let TypeInner::Matrix { columns, rows, .. } = self.module.types[ty].inner else {
+            unreachable!()
+        };
+
+        if !matches!(
+            op,
+            BinaryOperator::Add
+                | BinaryOperator::Subtract
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo
+        ) {
+            return Err(Error {
+                kind: ErrorKind::NotImplemented("comparison and bitwise operators on matrices"),
+                meta,
+            });
+        }
+
+        let mut components = Vec::with_capacity(columns as usize);
+        for column in 0..columns as u32 {
+            let mut operand = |ctx: &mut Self, value| -> Result<_> {
+                match ctx.resolve_type(value, meta)? {
+                    TypeInner::Matrix { .. } => ctx.add_expression(
+                        Expression::AccessIndex {
+                            base: value,
+                            index: column,
+                        },
+                        meta,
+                    ),
+                    _ => ctx.splat(value, rows, meta),
+                }
+            };
+            let left = operand(self, left)?;
+            let right = operand(self, right)?;
+            components.push(self.add_expression(Expression::Binary { op, left, right }, meta)?);
+        }
+        self.add_expression(Expression::Compose { ty, components }, meta)
+    }
+
+    /// The scalar components of `value` in HLSL order, so matrices are
+    /// flattened row by row.
+    pub fn flatten(
+        &mut self,
+        value: Handle<Expression>,
+        meta: Span,
+        out: &mut Vec<Handle<Expression>>,
+    ) -> Result<()> {
+        let access = |ctx: &mut Self, base, index| {
+            ctx.add_expression(Expression::AccessIndex { base, index }, meta)
+        };
+        match self.resolve_type(value, meta)? {
+            TypeInner::Scalar(_) => out.push(value),
+            TypeInner::Vector { size, .. } => {
+                for index in 0..size as u32 {
+                    out.push(access(self, value, index)?);
+                }
+            }
+            TypeInner::Matrix { columns, rows, .. } => {
+                for row in 0..rows as u32 {
+                    for column in 0..columns as u32 {
+                        let column = access(self, value, column)?;
+                        out.push(access(self, column, row)?);
+                    }
+                }
+            }
+            TypeInner::Array {
+                size: crate::ArraySize::Constant(size),
+                ..
+            } => {
+                for index in 0..size.get() {
+                    let element = access(self, value, index)?;
+                    self.flatten(element, meta, out)?;
+                }
+            }
+            TypeInner::Struct { ref members, .. } => {
+                for index in 0..members.len() as u32 {
+                    let member = access(self, value, index)?;
+                    self.flatten(member, meta, out)?;
+                }
+            }
+            _ => {
+                return Err(Error::semantic(
+                    "value can't be used in an initializer",
+                    meta,
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Build a value of type `ty` from the front of `scalars`.
+    fn build_from_scalars(
+        &mut self,
+        ty: Handle<Type>,
+        scalars: &mut std::vec::IntoIter<Handle<Expression>>,
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let mut next = |ctx: &mut Self, scalar: Scalar| -> Result<_> {
+            let value = scalars
+                .next()
+                .ok_or_else(|| Error::semantic("too few values in initializer", meta))?;
+            ctx.convert_scalar(value, scalar, meta)
+        };
+        let components = match self.module.types[ty].inner.clone() {
+            TypeInner::Scalar(scalar) => return next(self, scalar),
+            TypeInner::Vector { size, scalar } => (0..size as u32)
+                .map(|_| next(self, scalar))
+                .collect::<Result<Vec<_>>>()?,
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => {
+                let elements = (0..columns as usize * rows as usize)
+                    .map(|_| next(self, scalar))
+                    .collect::<Result<Vec<_>>>()?;
+                return self.matrix_from_rows(ty, &elements, meta);
+            }
+            TypeInner::Array {
+                base,
+                size: crate::ArraySize::Constant(size),
+                ..
+            } => (0..size.get())
+                .map(|_| self.build_from_scalars(base, scalars, meta))
+                .collect::<Result<Vec<_>>>()?,
+            TypeInner::Struct { members, .. } => members
+                .iter()
+                .map(|member| self.build_from_scalars(member.ty, scalars, meta))
+                .collect::<Result<Vec<_>>>()?,
+            _ => {
+                return Err(Error::semantic(
+                    "type can't be initialized with a list",
+                    meta,
+                ))
+            }
+        };
+        self.add_expression(Expression::Compose { ty, components }, meta)
+    }
+
+    /// Build a matrix of type `ty` from its elements in row-major order.
+    fn matrix_from_rows(
+        &mut self,
+        ty: Handle<Type>,
+        elements: &[Handle<Expression>],
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } = self.module.types[ty].inner
+        else {
+            unreachable!()
+        };
+        let column_ty = self.type_handle(TypeInner::Vector { size: rows, scalar });
+        let mut components = Vec::with_capacity(columns as usize);
+        for column in 0..columns as usize {
+            let column_components = (0..rows as usize)
+                .map(|row| elements[row * columns as usize + column])
+                .collect();
+            components.push(self.add_expression(
+                Expression::Compose {
+                    ty: column_ty,
+                    components: column_components,
+                },
+                meta,
+            )?);
+        }
+        self.add_expression(Expression::Compose { ty, components }, meta)
+    }
+
+    /// Lower a brace-enclosed initializer for a value of type `ty`.
+    ///
+    /// HLSL flattens initializer lists completely, so `{ 1, 2, 3, 4 }` can
+    /// initialize a `float2 a[2]` as well as a `float4`.
+    pub fn init_list(
+        &mut self,
+        ty: Handle<Type>,
+        items: &[Expr],
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let mut scalars = Vec::new();
+        self.flatten_items(items, &mut scalars)?;
+        let count = scalars.len();
+        let mut scalars = scalars.into_iter();
+        let value = self.build_from_scalars(ty, &mut scalars, meta)?;
+        if scalars.next().is_some() {
+            return Err(Error::semantic(
+                format!("too many values in initializer ({count})"),
+                meta,
+            ));
+        }
+        Ok(value)
+    }
+
+    fn flatten_items(&mut self, items: &[Expr], out: &mut Vec<Handle<Expression>>) -> Result<()> {
+        for item in items {
+            match item.kind {
+                ExprKind::InitList(ref inner) => self.flatten_items(inner, out)?,
+                _ => {
+                    let value = self.lower_rvalue(item)?;
+                    self.flatten(value, item.meta, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn construct(
+        &mut self,
+        ty: Handle<Type>,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Handle<Expression>> {
+        let values = args
+            .iter()
+            .map(|arg| self.lower_rvalue(arg))
+            .collect::<Result<Vec<_>>>()?;
+        match self.module.types[ty].inner.clone() {
+            TypeInner::Scalar(_) if values.len() == 1 => self.convert(values[0], ty, meta),
+            TypeInner::Vector { size, scalar } => {
+                if let [value] = values[..] {
+                    if let TypeInner::Scalar(_) = self.resolve_type(value, meta)? {
+                        return self.convert(value, ty, meta);
+                    }
+                }
+                let mut components = Vec::with_capacity(values.len());
+                let mut count = 0;
+                for value in values {
+                    let value = self.convert_scalar(value, scalar, meta)?;
+                    match self.resolve_type(value, meta)? {
+                        TypeInner::Scalar(_) => count += 1,
+                        TypeInner::Vector { size, .. } => count += size as u32,
+                        _ => {
+                            return Err(Error::semantic(
+                                "vectors can only be built from scalars and vectors",
+                                meta,
+                            ))
+                        }
+                    }
+                    components.push(value);
+                }
+                if count != size as u32 {
+                    return Err(Error::semantic(
+                        format!("expected {} components, found {count}", size as u32),
+                        meta,
+                    ));
+                }
+                self.add_expression(Expression::Compose { ty, components }, meta)
+            }
+            TypeInner::Matrix { columns, rows, .. } => {
+                if let [value] = values[..] {
+                    if let TypeInner::Matrix { .. } = self.resolve_type(value, meta)? {
+                        return self.convert(value, ty, meta);
+                    }
+                }
+                let mut elements = Vec::new();
+                for value in values {
+                    self.flatten(value, meta, &mut elements)?;
+                }
+                if elements.len() != columns as usize * rows as usize {
+                    return Err(Error::semantic(
+                        format!(
+                            "expected {} components, found {}",
+                            columns as usize * rows as usize,
+                            elements.len()
+                        ),
+                        meta,
+                    ));
+                }
+                let TypeInner::Matrix { scalar, .. } = self.module.types[ty].inner else {
+                    unreachable!()
+                };
+                for element in &mut elements {
+                    *element = self.convert_scalar(*element, scalar, meta)?;
+                }
+                self.matrix_from_rows(ty, &elements, meta)
+            }
+            _ => Err(Error::semantic("type can't be constructed", meta)),
+        }
+    }
+
+    // Calls
+
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        meta: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        if !self
+            .signatures
+            .iter()
+            .any(|signature| signature.name == name)
+        {
+            return match self.builtin_call(name, args, meta)? {
+                Some(result) => Ok(result),
+                None => Err(Error {
+                    kind: ErrorKind::UnknownFunction(name.to_string()),
+                    meta,
+                }),
+            };
+        }
+        if self.is_const {
+            return Err(Error::semantic(
+                "functions can't be called in a constant expression",
+                meta,
+            ));
+        }
+
+        let values = args
+            .iter()
+            .map(|arg| self.lower(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let mut types = Vec::with_capacity(values.len());
+        for &value in &values {
+            types.push(match value {
+                Value::Reference(pointer) => self.pointee_type(pointer, meta)?,
+                Value::Plain(expr) => self.resolve_type_handle(expr, meta)?,
+            });
+        }
+
+        let signatures = self.signatures;
+        let candidates = || {
+            signatures.iter().filter(|signature| {
+                signature.name == name && signature.parameters.len() == values.len()
+            })
+        };
+        let signature = candidates()
+            .find(|signature| {
+                signature
+                    .parameters
+                    .iter()
+                    .zip(&types)
+                    .all(|(&(ty, _), &arg_ty)| ty == arg_ty)
+            })
+            .or_else(|| candidates().next())
+            .ok_or_else(|| {
+                Error::semantic(
+                    format!("no overload of `{name}` takes {} arguments", values.len()),
+                    meta,
+                )
+            })?;
+
+        let mut arguments = Vec::with_capacity(values.len());
+        let mut copy_back = Vec::new();
+        for (&value, &(ty, qualifier)) in values.iter().zip(&signature.parameters) {
+            if !qualifier.is_output() {
+                let value = self.load(value, meta)?;
+                arguments.push(self.convert(value, ty, meta)?);
+                continue;
+            }
+            let Value::Reference(target) = value else {
+                return Err(Error::semantic(
+                    "`out` and `inout` arguments must be assignable",
+                    meta,
+                ));
+            };
+            let temporary = self.add_local(None, ty, meta)?;
+            if qualifier == ParameterQualifier::InOut {
+                let current = self.add_expression(Expression::Load { pointer: target }, meta)?;
+                let current = self.convert(current, ty, meta)?;
+                self.add_statement(
+                    Statement::Store {
+                        pointer: temporary,
+                        value: current,
+                    },
+                    meta,
+                );
+            }
+            arguments.push(temporary);
+            copy_back.push((temporary, target));
+        }
+
+        let function = signature.handle;
+        let result = self.add_statement_with_result(
+            signature.result.map(|_| Expression::CallResult(function)),
+            |result| Statement::Call {
+                function,
+                arguments,
+                result,
+            },
+            meta,
+        );
+
+        for (temporary, target) in copy_back {
+            let value = self.add_expression(Expression::Load { pointer: temporary }, meta)?;
+            self.store(target, value, meta)?;
+        }
+        Ok(result)
+    }
+
+    // Statements
+
+    pub fn lower_block(&mut self, stmts: &[Stmt]) -> Result<()> {
+        stmts.iter().try_for_each(|stmt| self.lower_stmt(stmt))
+    }
+
+    fn lower_stmt(&mut self, stmt: &Stmt) -> Result<()> {
+        let meta = stmt.meta;
+        match stmt.kind {
+            StmtKind::Declare(ref decls) => {
+                for decl in decls {
+                    self.lower_decl(decl)?;
+                }
+            }
+            StmtKind::Expr(ref expr) => self.lower_effect(expr)?,
+            StmtKind::Block(ref stmts) => {
+                let (block, ()) = self.new_body(|ctx| ctx.lower_block(stmts))?;
+                self.add_statement(Statement::Block(block), meta);
+            }
+            StmtKind::If {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.lower_condition(condition)?;
+                let (accept, ()) = self.new_body(|ctx| ctx.lower_stmt(accept))?;
+                let (reject, ()) = self.new_body(|ctx| match *reject {
+                    Some(ref reject) => ctx.lower_stmt(reject),
+                    None => Ok(()),
+                })?;
+                self.add_statement(
+                    Statement::If {
+                        condition,
+                        accept,
+                        reject,
+                    },
+                    meta,
+                );
+            }
+            StmtKind::For {
+                ref init,
+                ref condition,
+                ref update,
+                ref body,
+            } => {
+                self.scopes.push(FastHashMap::default());
+                let result = (|| {
+                    if let Some(ref init) = *init {
+                        self.lower_stmt(init)?;
+                    }
+                    self.lower_loop(condition.as_ref(), body, update.as_ref(), meta)
+                })();
+                self.scopes.pop();
+                result?;
+            }
+            StmtKind::While {
+                ref condition,
+                ref body,
+            } => self.lower_loop(Some(condition), body, None, meta)?,
+            StmtKind::DoWhile {
+                ref body,
+                ref condition,
+            } => {
+                let (body, ()) = self.new_body(|ctx| ctx.lower_stmt(body))?;
+                let (continuing, break_if) = self.new_body(|ctx| {
+                    let condition = ctx.lower_condition(condition)?;
+                    ctx.add_expression(
+                        Expression::Unary {
+                            op: UnaryOperator::LogicalNot,
+                            expr: condition,
+                        },
+                        meta,
+                    )
+                })?;
+                self.add_statement(
+                    Statement::Loop {
+                        body,
+                        continuing,
+                        break_if: Some(break_if),
+                    },
+                    meta,
+                );
+            }
+            StmtKind::Switch {
+                ref selector,
+                ref cases,
+            } => self.lower_switch(selector, cases, meta)?,
+            StmtKind::Break => self.add_statement(Statement::Break, meta),
+            StmtKind::Continue => self.add_statement(Statement::Continue, meta),
+            StmtKind::Discard => self.add_statement(Statement::Kill, meta),
+            StmtKind::Return(ref value) => {
+                let value = match (value, self.result) {
+                    (&Some(ref value), Some(ty)) => Some(self.lower_as(value, ty)?),
+                    (&None, None) => None,
+                    (&Some(_), None) => {
+                        return Err(Error::semantic(
+                            "a void function can't return a value",
+                            meta,
+                        ))
+                    }
+                    (&None, Some(_)) => {
+                        return Err(Error::semantic("expected a return value", meta))
+                    }
+                };
+                self.add_statement(Statement::Return { value }, meta);
+            }
+            StmtKind::Empty => {}
+        }
+        Ok(())
+    }
+
+    fn lower_decl(&mut self, decl: &LocalDecl) -> Result<()> {
+        let pointer = self.add_local(Some(decl.name.clone()), decl.ty, decl.meta)?;
+        if let Some(ref init) = decl.init {
+            let value = self.lower_as(init, decl.ty)?;
+            self.add_statement(Statement::Store { pointer, value }, decl.meta);
+        }
+        self.declare(decl.name.clone(), Symbol::Variable(pointer), decl.meta)
+    }
+
+    fn lower_condition(&mut self, condition: &Expr) -> Result<Handle<Expression>> {
+        let value = self.lower_rvalue(condition)?;
+        match self.resolve_type(value, condition.meta)? {
+            TypeInner::Scalar(_) => self.to_bool(value, condition.meta),
+            _ => Err(Error::semantic(
+                "condition must be a scalar",
+                condition.meta,
+            )),
+        }
+    }
+
+    fn lower_loop(
+        &mut self,
+        condition: Option<&Expr>,
+        body: &Stmt,
+        update: Option<&Expr>,
+        meta: Span,
+    ) -> Result<()> {
+        let (body, ()) = self.new_body(|ctx| {
+            if let Some(condition) = condition {
+                let condition = ctx.lower_condition(condition)?;
+                let condition = ctx.add_expression(
+                    Expression::Unary {
+                        op: UnaryOperator::LogicalNot,
+                        expr: condition,
+                    },
+                    meta,
+                )?;
+                let mut accept = Block::new();
+                accept.push(Statement::Break, meta);
+                ctx.add_statement(
+                    Statement::If {
+                        condition,
+                        accept,
+                        reject: Block::new(),
+                    },
+                    meta,
+                );
+            }
+            ctx.lower_stmt(body)
+        })?;
+        let (continuing, ()) = self.new_body(|ctx| match update {
+            Some(update) => ctx.lower_effect(update),
+            None => Ok(()),
+        })?;
+        self.add_statement(
+            Statement::Loop {
+                body,
+                continuing,
+                break_if: None,
+            },
+            meta,
+        );
+        Ok(())
+    }
+
+    fn lower_switch(&mut self, selector: &Expr, cases: &[SwitchCase], meta: Span) -> Result<()> {
+        let selector_meta = selector.meta;
+        let selector = self.lower_rvalue(selector)?;
+        let scalar = match self.resolve_type(selector, selector_meta)? {
+            TypeInner::Scalar(Scalar::U32) => Scalar::U32,
+            TypeInner::Scalar(_) => Scalar::I32,
+            _ => {
+                return Err(Error::semantic(
+                    "switch selector must be a scalar",
+                    selector_meta,
+                ))
+            }
+        };
+        let selector = self.convert_scalar(selector, scalar, selector_meta)?;
+
+        let mut lowered = Vec::with_capacity(cases.len() + 1);
+        for case in cases {
+            let value = match case.value {
+                Some(ref value) => {
+                    let handle = self.lower_rvalue(value)?;
+                    let handle = self.convert_scalar(handle, scalar, value.meta)?;
+                    match *self.expression(handle) {
+                        Expression::Literal(crate::Literal::I32(value)) => {
+                            crate::SwitchValue::I32(value)
+                        }
+                        Expression::Literal(crate::Literal::U32(value)) => {
+                            crate::SwitchValue::U32(value)
+                        }
+                        _ => {
+                            return Err(Error::semantic(
+                                "case labels must be integer constants",
+                                value.meta,
+                            ))
+                        }
+                    }
+                }
+                None => crate::SwitchValue::Default,
+            };
+            let (body, ()) = self.new_body(|ctx| ctx.lower_block(&case.body))?;
+            let fall_through = !matches!(
+                case.body.last().map(|stmt| &stmt.kind),
+                Some(
+                    StmtKind::Break | StmtKind::Continue | StmtKind::Return(_) | StmtKind::Discard
+                )
+            );
+            lowered.push(crate::SwitchCase {
+                value,
+                body,
+                fall_through,
+            });
+        }
+
+        if !lowered
+            .iter()
+            .any(|case| case.value == crate::SwitchValue::Default)
+        {
+            lowered.push(crate::SwitchCase {
+                value: crate::SwitchValue::Default,
+                body: Block::new(),
+                fall_through: false,
+            });
+        }
+        if let Some(last) = lowered.last_mut() {
+            last.fall_through = false;
+        }
+
+        self.add_statement(
+            Statement::Switch {
+                selector,
+                cases: lowered,
+            },
+            meta,
+        );
+        Ok(())
+    }
+}
+
+/// Parse a swizzle like `xyz` or `bgra` on a vector with `size` components.
+fn parse_swizzle(field: &str, size: u32) -> Option<Vec<SwizzleComponent>> {
+    const XYZW: &str = "xyzw";
+    const RGBA: &str = "rgba";
+    let set = if field.chars().all(|c| XYZW.contains(c)) {
+        XYZW
+    } else if field.chars().all(|c| RGBA.contains(c)) {
+        RGBA
+    } else {
+        return None;
+    };
+    if field.is_empty() || field.len() > 4 {
+        return None;
+    }
+    field
+        .chars()
+        .map(|c| {
+            let index = set.find(c)? as u32;
+            (index < size).then(|| SwizzleComponent::XYZW[index as usize])
+        })
+        .collect()
+}
+
+/// Parse a matrix element name like `_m01` (zero-based) or `_12` (one-based),
+/// returning the row and column.
+fn parse_matrix_element(field: &str) -> Option<(u32, u32)> {
+    let (digits, base) = match field.strip_prefix("_m") {
+        Some(digits) => (digits, 0),
+        None => (field.strip_prefix('_')?, 1),
+    };
+    let &[row, column] = digits.as_bytes() else {
+        return None;
+    };
+    let digit = |byte: u8| {
+        byte.is_ascii_digit()
+            .then(|| u32::from(byte - b'0'))
+            .and_then(|value| value.checked_sub(base))
+    };
+    Some((digit(row)?, digit(column)?))
+}
+
+impl Frontend {
+    /// Lower the bodies of all the functions parsed so far.
+    pub(super) fn lower_functions(&mut self, usage: &ResourceUsage) -> Result<()> {
+        let functions = std::mem::take(&mut self.functions);
+
+        let mut signatures = Vec::with_capacity(functions.len());
+        for decl in &functions {
+            let mut parameters = Vec::with_capacity(decl.parameters.len());
+            let mut arguments = Vec::with_capacity(decl.parameters.len());
+            for param in &decl.parameters {
+                let ty = match param.ty {
+                    Ty::Value(ty) => ty,
+                    Ty::Void => {
+                        return Err(Error::semantic("parameters can't be void", param.meta))
+                    }
+                    ty @ (Ty::Texture { .. } | Ty::Sampler { .. }) => {
+                        self.resource_type(ty, &param.name, usage, None, param.meta)?
+                            .0
+                    }
+                    _ => {
+                        return Err(Error {
+                            kind: ErrorKind::NotImplemented("buffers as function parameters"),
+                            meta: param.meta,
+                        })
+                    }
+                };
+                let arg_ty = if param.qualifier.is_output() {
+                    self.value_type(
+                        TypeInner::Pointer {
+                            base: ty,
+                            space: AddressSpace::Function,
+                        },
+                        param.meta,
+                    )
+                } else {
+                    ty
+                };
+                parameters.push((ty, param.qualifier));
+                arguments.push(FunctionArgument {
+                    name: Some(param.name.clone()),
+                    ty: arg_ty,
+                    binding: None,
+                });
+            }
+            let result = match decl.result {
+                Ty::Void => None,
+                Ty::Value(ty) => Some(ty),
+                _ => {
+                    return Err(Error::semantic(
+                        "functions can't return resources",
+                        decl.meta,
+                    ))
+                }
+            };
+            let handle = self.module.functions.append(
+                Function {
+                    name: Some(decl.name.clone()),
+                    arguments,
+                    result: result.map(|ty| FunctionResult { ty, binding: None }),
+                    ..Default::default()
+                },
+                decl.meta,
+            );
+            signatures.push(Signature {
+                name: decl.name.clone(),
+                handle,
+                parameters,
+                result,
+            });
+        }
+
+        for (decl, signature) in functions.iter().zip(&signatures) {
+            let mut ctx = Context::new(
+                &mut self.module,
+                &mut self.global_expression_kind_tracker,
+                &self.globals,
+                &signatures,
+                false,
+            );
+            ctx.arguments = ctx.module.functions[signature.handle].arguments.clone();
+            ctx.result = signature.result;
+
+            for (index, (param, &(ty, qualifier))) in decl
+                .parameters
+                .iter()
+                .zip(&signature.parameters)
+                .enumerate()
+            {
+                let arg =
+                    ctx.add_expression(Expression::FunctionArgument(index as u32), param.meta)?;
+                let symbol = if let Ty::Value(_) = param.ty {
+                    if qualifier.is_output() {
+                        Symbol::Variable(arg)
+                    } else {
+                        // Parameters can be assigned to in HLSL.
+                        let pointer = ctx.add_local(Some(param.name.clone()), ty, param.meta)?;
+                        ctx.add_statement(
+                            Statement::Store {
+                                pointer,
+                                value: arg,
+                            },
+                            param.meta,
+                        );
+                        Symbol::Variable(pointer)
+                    }
+                } else {
+                    Symbol::Resource {
+                        expr: arg,
+                        ty: param.ty,
+                    }
+                };
+                ctx.declare(param.name.clone(), symbol, param.meta)?;
+            }
+
+            ctx.lower_block(&decl.body)?;
+            ctx.emit_end();
+            ensure_block_returns(&mut ctx.body);
+
+            let function = &mut ctx.module.functions[signature.handle];
+            function.expressions = ctx.expressions;
+            function.local_variables = ctx.locals;
+            function.body = ctx.body;
+        }
+
+        self.add_entry_points(&functions, &signatures, usage)
+    }
+}
@@ -0,0 +1,191 @@
+/*!
+Frontend for [HLSL][hlsl] (High Level Shading Language).
+
+The subset accepted is roughly Shader Model 5.1 as compiled by DXC for
+Vulkan: vertex, pixel and compute shaders using textures, samplers,
+constant, structured and byte address buffers and `groupshared` memory.
+Resources need an explicit `register` or `[[vk::binding]]`, which becomes the
+binding's group (from the register space) and index. Registers of different
+types are separate in HLSL but not in naga, so `t0` and `s0` in the same space
+are both binding 0: give resources distinct indices, or shift each type of
+register apart with [`Options::register_shifts`]. Resources with the same
+binding are an error. The preprocessor handles macros and conditionals, but
+not `#include`, which is an error.
+
+Entry points are the functions with a `[shader("...")]` or `[numthreads]`
+attribute, and functions that no other function calls that output
+`SV_Position` (vertex shaders) or `SV_Target` or `SV_Depth` (pixel shaders).
+
+[hlsl]: https://learn.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl
+*/
+
+pub use error::{Error, ErrorKind};
+
+use crate::{
+    proc::{ExpressionKindTracker, Layouter},
+    FastHashMap, Handle, Module, ResourceBinding, Span, StorageFormat, Type,
+};
+use ast::{FunctionDecl, IoAttributes, Ty};
+
+mod ast;
+mod builtins;
+mod entry;
+mod error;
+mod lex;
+mod lower;
+mod parser;
+#[cfg(test)]
+mod tests;
+mod types;
+mod variables;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Per-shader options passed to [`parse`](Frontend::parse).
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Preprocessor definitions to be used, akin to having
+    /// ```hlsl
+    /// #define key value
+    /// ```
+    /// for each key value pair in the map.
+    pub defines: FastHashMap<String, String>,
+    /// The binding indices added to each type of register.
+    pub register_shifts: RegisterShifts,
+}
+
+/// Binding indices added to the registers of each type, like DXC's
+/// `-fvk-b-shift`, `-fvk-t-shift`, `-fvk-s-shift` and `-fvk-u-shift`.
+///
+/// `[[vk::binding]]` attributes aren't shifted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegisterShifts {
+    /// Added to constant buffer registers, `b#`.
+    pub constant_buffer: u32,
+    /// Added to shader resource registers, `t#`.
+    pub shader_resource: u32,
+    /// Added to sampler registers, `s#`.
+    pub sampler: u32,
+    /// Added to unordered access registers, `u#`.
+    pub unordered_access: u32,
+}
+
+/// What a global name refers to.
+#[derive(Clone, Copy, Debug)]
+enum GlobalSymbol {
+    Constant(Handle<crate::Constant>),
+    Variable(Handle<crate::GlobalVariable>),
+    /// A member of a `cbuffer`.
+    BlockMember {
+        handle: Handle<crate::GlobalVariable>,
+        index: u32,
+    },
+    Resource {
+        handle: Handle<crate::GlobalVariable>,
+        ty: Ty,
+    },
+}
+
+/// A global whose IR type depends on how the function bodies use it, which
+/// is declared once they've all been parsed.
+#[derive(Debug)]
+struct PendingResource {
+    name: String,
+    ty: Ty,
+    binding: Option<ResourceBinding>,
+    format: Option<StorageFormat>,
+    meta: Span,
+}
+
+/// The `Frontend` is the central structure of the HLSL frontend.
+///
+/// To parse a shader, call [`parse`](Frontend::parse) with an [`Options`]
+/// struct and the HLSL source.
+///
+/// # Example usage
+/// ```rust
+/// use naga::front::hlsl::{Frontend, Options};
+///
+/// let hlsl = r#"
+///     [numthreads(1, 1, 1)]
+///     void main() {}
+/// "#;
+///
+/// let mut frontend = Frontend::default();
+/// let module = frontend.parse(&Options::default(), hlsl).unwrap();
+/// assert_eq!(module.entry_points.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct Frontend {
+    module: Module,
+    layouter: Layouter,
+    global_expression_kind_tracker: ExpressionKindTracker,
+
+    /// Type names declared by `struct` and `typedef`.
+    types: FastHashMap<String, Ty>,
+    /// The semantics and interpolation qualifiers of each struct's members.
+    struct_io: FastHashMap<Handle<Type>, Vec<IoAttributes>>,
+    globals: FastHashMap<String, GlobalSymbol>,
+    resources: Vec<PendingResource>,
+    functions: Vec<FunctionDecl>,
+}
+
+impl Default for Frontend {
+    fn default() -> Self {
+        Frontend {
+            module: Module::default(),
+            layouter: Layouter::default(),
+            global_expression_kind_tracker: ExpressionKindTracker::new(),
+            types: FastHashMap::default(),
+            struct_io: FastHashMap::default(),
+            globals: FastHashMap::default(),
+            resources: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+}
+
+impl Frontend {
+    fn reset(&mut self) {
+        self.module = Module::default();
+        self.layouter.clear();
+        self.global_expression_kind_tracker = ExpressionKindTracker::new();
+        self.types.clear();
+        self.struct_io.clear();
+        self.globals.clear();
+        self.resources.clear();
+        self.functions.clear();
+    }
+
+    /// Parses a shader either outputting a shader [`Module`] or an [`Error`].
+    ///
+    /// Multiple calls using the same `Frontend` and different shaders are supported.
+    ///
+    /// `#include` directives aren't supported and are reported as
+    /// [`ErrorKind::NotImplemented`]; callers must splice included files into
+    /// `source` themselves.
+    pub fn parse(&mut self, options: &Options, source: &str) -> Result<Module> {
+        self.reset();
+
+        if let Some(meta) = lex::find_include(source) {
+            return Err(Error {
+                kind: ErrorKind::NotImplemented("`#include` directives"),
+                meta,
+            });
+        }
+
+        // The preprocessor doesn't know about string literals, which HLSL
+        // only uses as attribute arguments like `[shader("vertex")]`. Blank
+        // out the quotes so the contents are lexed as identifiers; this keeps
+        // spans pointing at the right place in `source`.
+        let source = source.replace('"', " ");
+        let tokens = lex::tokenize(&source, &options.defines)?;
+        parser::Parser::new(self, tokens, options.register_shifts).parse()?;
+
+        let usage = lower::ResourceUsage::scan(&self.functions);
+        self.declare_resources(&usage)?;
+        self.lower_functions(&usage)?;
+
+        Ok(std::mem::take(&mut self.module))
+    }
+}
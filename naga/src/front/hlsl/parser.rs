@@ -0,0 +1,1421 @@
+use super::{
+    ast::{
+        BinaryOp, Expr, ExprKind, FunctionAttributes, FunctionDecl, IoAttributes, Literal,
+        LocalDecl, Parameter, ParameterQualifier, Semantic, Stmt, StmtKind, SwitchCase, Ty,
+        UnaryOp,
+    },
+    error::{Error, ErrorKind},
+    lex::{Token, TokenValue},
+    Frontend, RegisterShifts, Result,
+};
+use crate::{
+    ImageDimension, Interpolation, ResourceBinding, Sampling, Scalar, ShaderStage, Span,
+    StorageFormat, Type, TypeInner, VectorSize,
+};
+use pp_rs::token::Punct;
+use std::borrow::Cow;
+
+/// Qualifiers that may precede the type of a declaration.
+#[derive(Debug, Default)]
+pub struct Qualifiers {
+    pub is_static: bool,
+    pub is_const: bool,
+    pub groupshared: bool,
+    pub qualifier: Option<ParameterQualifier>,
+    pub interpolation: Option<Interpolation>,
+    pub sampling: Option<Sampling>,
+}
+
+/// Attributes written in `[...]` or `[[...]]` before a declaration or statement.
+#[derive(Debug, Default)]
+pub struct Attributes {
+    pub function: FunctionAttributes,
+    pub binding: Option<ResourceBinding>,
+    pub location: Option<u32>,
+    pub format: Option<StorageFormat>,
+}
+
+/// A declarator's annotations after a `:`.
+#[derive(Debug, Default)]
+pub struct Annotations {
+    pub semantic: Option<Semantic>,
+    pub register: Option<ResourceBinding>,
+}
+
+/// The binding index of a register like `t3`, shifted by its type.
+fn register_binding(register: &str, shifts: RegisterShifts) -> Option<u32> {
+    let shift = match register.get(..1)? {
+        "b" => shifts.constant_buffer,
+        "t" => shifts.shader_resource,
+        "s" => shifts.sampler,
+        "u" => shifts.unordered_access,
+        _ => return None,
+    };
+    register[1..].parse::<u32>().ok()?.checked_add(shift)
+}
+
+pub struct Parser<'a> {
+    frontend: &'a mut Frontend,
+    tokens: Vec<Token>,
+    pos: usize,
+    register_shifts: RegisterShifts,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(
+        frontend: &'a mut Frontend,
+        tokens: Vec<Token>,
+        register_shifts: RegisterShifts,
+    ) -> Self {
+        Parser {
+            frontend,
+            tokens,
+            pos: 0,
+            register_shifts,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    /// The span of the current token, or of the end of the source.
+    fn meta(&self) -> Span {
+        match self.peek() {
+            Some(token) => token.meta,
+            None => {
+                let end = self
+                    .tokens
+                    .last()
+                    .and_then(|token| token.meta.to_range())
+                    .map_or(0, |range| range.end as u32);
+                Span::new(end, end)
+            }
+        }
+    }
+
+    fn prev_meta(&self) -> Span {
+        self.tokens
+            .get(self.pos.wrapping_sub(1))
+            .map_or(Span::default(), |token| token.meta)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.peek().cloned().ok_or_else(|| Error {
+            kind: ErrorKind::EndOfFile,
+            meta: self.meta(),
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn error_expected(&self, expected: impl Into<Cow<'static, str>>) -> Error {
+        match self.peek() {
+            Some(token) => Error {
+                kind: ErrorKind::InvalidToken {
+                    expected: expected.into(),
+                    found: token.value.to_string(),
+                },
+                meta: token.meta,
+            },
+            None => Error {
+                kind: ErrorKind::EndOfFile,
+                meta: self.meta(),
+            },
+        }
+    }
+
+    fn peek_punct(&self, punct: Punct) -> bool {
+        matches!(self.peek(), Some(&Token { value: TokenValue::Punct(p), .. }) if p == punct)
+    }
+
+    fn peek_punct_at(&self, offset: usize, punct: Punct) -> bool {
+        matches!(self.peek_at(offset), Some(&Token { value: TokenValue::Punct(p), .. }) if p == punct)
+    }
+
+    fn bump_if(&mut self, punct: Punct) -> bool {
+        let found = self.peek_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: Punct) -> Result<Span> {
+        // `a<b<c>>` closes two template argument lists.
+        // Split the `>>` token in two, so that backtracking sees the same
+        // tokens again.
+        if punct == Punct::RightAngle && self.peek_punct(Punct::RightShift) {
+            let range = self.tokens[self.pos].meta.to_range().unwrap_or_default();
+            let (start, end) = (range.start as u32, range.end as u32);
+            self.tokens[self.pos] = Token {
+                value: TokenValue::Punct(Punct::RightAngle),
+                meta: Span::new(start, start + 1),
+            };
+            self.tokens.insert(
+                self.pos + 1,
+                Token {
+                    value: TokenValue::Punct(Punct::RightAngle),
+                    meta: Span::new(start + 1, end),
+                },
+            );
+        }
+        if self.peek_punct(punct) {
+            Ok(self.next()?.meta)
+        } else {
+            Err(self.error_expected(format!("{punct:?}")))
+        }
+    }
+
+    fn peek_ident(&self) -> Option<&str> {
+        match self.peek() {
+            Some(&Token {
+                value: TokenValue::Ident(ref ident),
+                ..
+            }) => Some(ident),
+            _ => None,
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek_ident() == Some(keyword)
+    }
+
+    fn bump_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_ident(&mut self) -> Result<(String, Span)> {
+        match self.peek() {
+            Some(&Token {
+                value: TokenValue::Ident(ref ident),
+                meta,
+            }) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok((ident, meta))
+            }
+            _ => Err(self.error_expected("identifier")),
+        }
+    }
+
+    fn expect_uint(&mut self) -> Result<u32> {
+        match self.peek() {
+            Some(&Token {
+                value: TokenValue::Int(ref int),
+                ..
+            }) => {
+                let value = int.value as u32;
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(self.error_expected("integer literal")),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<()> {
+        while self.peek().is_some() {
+            self.parse_global_decl()?;
+        }
+        Ok(())
+    }
+
+    fn parse_global_decl(&mut self) -> Result<()> {
+        if self.bump_if(Punct::Semicolon) {
+            return Ok(());
+        }
+        if self.peek_keyword("struct") && self.peek_punct_at(2, Punct::LeftBrace) {
+            self.parse_struct()?;
+            self.expect(Punct::Semicolon)?;
+            return Ok(());
+        }
+        if self.bump_keyword("typedef") {
+            let ty = self.parse_value_type()?;
+            let (name, meta) = self.expect_ident()?;
+            let ty = self.parse_array_suffix(ty)?;
+            self.expect(Punct::Semicolon)?;
+            self.frontend.declare_type(name, ty, meta)?;
+            return Ok(());
+        }
+        if self.peek_keyword("cbuffer") || self.peek_keyword("tbuffer") {
+            return self.parse_cbuffer(Attributes::default());
+        }
+
+        let meta = self.meta();
+        let attributes = self.parse_attributes()?;
+        if self.peek_keyword("cbuffer") || self.peek_keyword("tbuffer") {
+            return self.parse_cbuffer(attributes);
+        }
+        let qualifiers = self.parse_qualifiers()?;
+        let ty = self.parse_type()?;
+        let (name, name_meta) = self.expect_ident()?;
+
+        if self.peek_punct(Punct::LeftParen) {
+            return self.parse_function(attributes, ty, name, meta);
+        }
+
+        let mut declarator = Some((name, name_meta));
+        while let Some((name, name_meta)) = declarator.take() {
+            let decl_ty = match ty {
+                Ty::Value(base) => Ty::Value(self.parse_array_suffix(base)?),
+                _ => {
+                    if self.peek_punct(Punct::LeftBracket) {
+                        return Err(Error {
+                            kind: ErrorKind::NotImplemented("arrays of resources"),
+                            meta: self.meta(),
+                        });
+                    }
+                    ty
+                }
+            };
+            let annotations = self.parse_annotations()?;
+            let init = if self.bump_if(Punct::Equal) {
+                Some(self.parse_initializer()?)
+            } else {
+                None
+            };
+            let meta = name_meta.until(&self.prev_meta());
+            self.frontend.declare_global(
+                name,
+                decl_ty,
+                &qualifiers,
+                &attributes,
+                annotations,
+                init,
+                meta,
+            )?;
+            if self.bump_if(Punct::Comma) {
+                declarator = Some(self.expect_ident()?);
+            }
+        }
+        self.expect(Punct::Semicolon)?;
+        Ok(())
+    }
+
+    /// Parse a struct declaration, returning the new type.
+    fn parse_struct(&mut self) -> Result<crate::Handle<Type>> {
+        let start = self.meta();
+        self.bump_keyword("struct");
+        let (name, _) = self.expect_ident()?;
+        self.expect(Punct::LeftBrace)?;
+
+        let mut members = Vec::new();
+        while !self.bump_if(Punct::RightBrace) {
+            let attributes = self.parse_attributes()?;
+            let qualifiers = self.parse_qualifiers()?;
+            let ty = self.parse_type()?;
+            let Ty::Value(base) = ty else {
+                return Err(Error::semantic(
+                    "resources can't be members of structs",
+                    self.prev_meta(),
+                ));
+            };
+            loop {
+                let (member_name, meta) = self.expect_ident()?;
+                let ty = self.parse_array_suffix(base)?;
+                let annotations = self.parse_annotations()?;
+                let io = IoAttributes {
+                    semantic: annotations.semantic,
+                    interpolation: qualifiers.interpolation,
+                    sampling: qualifiers.sampling,
+                    location: attributes.location,
+                };
+                members.push((member_name, ty, io, meta));
+                if !self.bump_if(Punct::Comma) {
+                    break;
+                }
+            }
+            self.expect(Punct::Semicolon)?;
+        }
+
+        let meta = start.until(&self.prev_meta());
+        self.frontend.declare_struct(name, members, meta)
+    }
+
+    fn parse_cbuffer(&mut self, attributes: Attributes) -> Result<()> {
+        let start = self.meta();
+        let is_tbuffer = self.peek_keyword("tbuffer");
+        self.pos += 1;
+        let (name, _) = self.expect_ident()?;
+        let annotations = self.parse_annotations()?;
+        self.expect(Punct::LeftBrace)?;
+
+        let mut members = Vec::new();
+        while !self.bump_if(Punct::RightBrace) {
+            let _ = self.parse_qualifiers()?;
+            let ty = self.parse_type()?;
+            let Ty::Value(base) = ty else {
+                return Err(Error::semantic(
+                    "resources can't be declared in a cbuffer",
+                    self.prev_meta(),
+                ));
+            };
+            loop {
+                let (member_name, meta) = self.expect_ident()?;
+                let ty = self.parse_array_suffix(base)?;
+                if self.bump_if(Punct::Colon) {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("packoffset"),
+                        meta: self.meta(),
+                    });
+                }
+                members.push((member_name, ty, meta));
+                if !self.bump_if(Punct::Comma) {
+                    break;
+                }
+            }
+            self.expect(Punct::Semicolon)?;
+        }
+        self.bump_if(Punct::Semicolon);
+
+        if is_tbuffer {
+            return Err(Error {
+                kind: ErrorKind::NotImplemented("tbuffer"),
+                meta: start,
+            });
+        }
+        let binding = attributes
+            .binding
+            .or(annotations.register)
+            .ok_or_else(|| Error::semantic("a cbuffer needs an explicit register", start))?;
+        let meta = start.until(&self.prev_meta());
+        self.frontend.declare_cbuffer(name, members, binding, meta)
+    }
+
+    fn parse_function(
+        &mut self,
+        attributes: Attributes,
+        result: Ty,
+        name: String,
+        start: Span,
+    ) -> Result<()> {
+        self.expect(Punct::LeftParen)?;
+        let mut parameters = Vec::new();
+        if !(self.peek_keyword("void") && self.peek_punct_at(1, Punct::RightParen)) {
+            while !self.peek_punct(Punct::RightParen) {
+                let meta = self.meta();
+                let param_attributes = self.parse_attributes()?;
+                let qualifiers = self.parse_qualifiers()?;
+                let ty = self.parse_type()?;
+                let (param_name, _) = self.expect_ident()?;
+                let ty = match ty {
+                    Ty::Value(base) => Ty::Value(self.parse_array_suffix(base)?),
+                    other => other,
+                };
+                let annotations = self.parse_annotations()?;
+                if self.bump_if(Punct::Equal) {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("default parameter values"),
+                        meta: self.prev_meta(),
+                    });
+                }
+                parameters.push(Parameter {
+                    name: param_name,
+                    ty,
+                    qualifier: qualifiers.qualifier.unwrap_or(ParameterQualifier::In),
+                    io: IoAttributes {
+                        semantic: annotations.semantic,
+                        interpolation: qualifiers.interpolation,
+                        sampling: qualifiers.sampling,
+                        location: param_attributes.location,
+                    },
+                    meta: meta.until(&self.prev_meta()),
+                });
+                if !self.bump_if(Punct::Comma) {
+                    break;
+                }
+            }
+        } else {
+            self.pos += 1;
+        }
+        self.expect(Punct::RightParen)?;
+        let annotations = self.parse_annotations()?;
+        let result_io = IoAttributes {
+            semantic: annotations.semantic,
+            location: attributes.location,
+            ..Default::default()
+        };
+
+        // A prototype; the definition comes later.
+        if self.bump_if(Punct::Semicolon) {
+            return Ok(());
+        }
+
+        self.expect(Punct::LeftBrace)?;
+        let body = self.parse_block_contents()?;
+        let meta = start.until(&self.prev_meta());
+        self.frontend.functions.push(FunctionDecl {
+            name,
+            result,
+            result_io,
+            parameters,
+            attributes: attributes.function,
+            body,
+            meta,
+        });
+        Ok(())
+    }
+
+    /// Parse any number of `[attribute]` and `[[vk::attribute]]` lists.
+    fn parse_attributes(&mut self) -> Result<Attributes> {
+        let mut attributes = Attributes::default();
+        while self.peek_punct(Punct::LeftBracket) {
+            self.pos += 1;
+            let vulkan = self.bump_if(Punct::LeftBracket);
+            loop {
+                let (mut name, meta) = self.expect_ident()?;
+                if self.peek_punct(Punct::Colon) && self.peek_punct_at(1, Punct::Colon) {
+                    self.pos += 2;
+                    let (scoped, _) = self.expect_ident()?;
+                    name = format!("{name}::{scoped}");
+                }
+                let mut args = Vec::new();
+                if self.bump_if(Punct::LeftParen) {
+                    while !self.bump_if(Punct::RightParen) {
+                        args.push(self.next()?);
+                        self.bump_if(Punct::Comma);
+                    }
+                }
+                self.apply_attribute(&mut attributes, &name, &args, meta)?;
+                if !self.bump_if(Punct::Comma) {
+                    break;
+                }
+            }
+            self.expect(Punct::RightBracket)?;
+            if vulkan {
+                self.expect(Punct::RightBracket)?;
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn apply_attribute(
+        &mut self,
+        attributes: &mut Attributes,
+        name: &str,
+        args: &[Token],
+        meta: Span,
+    ) -> Result<()> {
+        let uint = |index: usize| -> Result<u32> {
+            match args.get(index) {
+                Some(&Token {
+                    value: TokenValue::Int(ref int),
+                    ..
+                }) => Ok(int.value as u32),
+                Some(token) => Err(Error::semantic(
+                    format!("expected an integer argument to `{name}`"),
+                    token.meta,
+                )),
+                None => Err(Error::semantic(
+                    format!("missing argument to `{name}`"),
+                    meta,
+                )),
+            }
+        };
+        let word = |index: usize| -> Result<&str> {
+            match args.get(index) {
+                Some(&Token {
+                    value: TokenValue::Ident(ref ident),
+                    ..
+                }) => Ok(ident),
+                _ => Err(Error::semantic(
+                    format!("expected a string argument to `{name}`"),
+                    meta,
+                )),
+            }
+        };
+
+        match name {
+            "numthreads" => {
+                attributes.function.workgroup_size = Some([uint(0)?, uint(1)?, uint(2)?]);
+            }
+            "shader" => {
+                attributes.function.stage = Some(match word(0)? {
+                    "vertex" => ShaderStage::Vertex,
+                    "pixel" => ShaderStage::Fragment,
+                    "compute" => ShaderStage::Compute,
+                    other => {
+                        return Err(Error::semantic(
+                            format!("unsupported shader stage `{other}`"),
+                            meta,
+                        ))
+                    }
+                });
+            }
+            "earlydepthstencil" => attributes.function.early_depth_test = true,
+            "vk::binding" => {
+                attributes.binding = Some(ResourceBinding {
+                    binding: uint(0)?,
+                    group: if args.len() > 1 { uint(1)? } else { 0 },
+                });
+            }
+            "vk::location" => attributes.location = Some(uint(0)?),
+            "vk::image_format" => {
+                let format = word(0)?;
+                attributes.format =
+                    Some(super::types::parse_storage_format(format).ok_or_else(|| {
+                        Error::semantic(format!("unknown image format `{format}`"), meta)
+                    })?);
+            }
+            // Optimization hints that don't affect the meaning of the code.
+            "unroll"
+            | "loop"
+            | "fastopt"
+            | "allow_uav_condition"
+            | "branch"
+            | "flatten"
+            | "forcecase"
+            | "call"
+            | "maxvertexcount"
+            | "domain"
+            | "partitioning"
+            | "outputtopology"
+            | "outputcontrolpoints"
+            | "patchconstantfunc"
+            | "maxtessfactor"
+            | "instance" => {}
+            _ if name.starts_with("vk::") => {
+                return Err(Error::semantic(
+                    format!("unsupported attribute `{name}`"),
+                    meta,
+                ))
+            }
+            _ => log::warn!("Ignoring unknown HLSL attribute `{}`", name),
+        }
+        Ok(())
+    }
+
+    fn parse_qualifiers(&mut self) -> Result<Qualifiers> {
+        let mut qualifiers = Qualifiers::default();
+        loop {
+            let Some(word) = self.peek_ident() else {
+                break;
+            };
+            match word {
+                "static" => qualifiers.is_static = true,
+                "const" => qualifiers.is_const = true,
+                "groupshared" => qualifiers.groupshared = true,
+                "in" => qualifiers.qualifier = Some(ParameterQualifier::In),
+                "out" => qualifiers.qualifier = Some(ParameterQualifier::Out),
+                "inout" => qualifiers.qualifier = Some(ParameterQualifier::InOut),
+                "nointerpolation" => qualifiers.interpolation = Some(Interpolation::Flat),
+                "noperspective" => qualifiers.interpolation = Some(Interpolation::Linear),
+                "linear" => qualifiers.interpolation = Some(Interpolation::Perspective),
+                "centroid" => qualifiers.sampling = Some(Sampling::Centroid),
+                "sample" => qualifiers.sampling = Some(Sampling::Sample),
+                // These don't change the meaning of the code.
+                "uniform" | "extern" | "volatile" | "precise" | "shared" | "column_major"
+                | "snorm" | "unorm" | "globallycoherent" => {}
+                "row_major" => {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("row_major matrices"),
+                        meta: self.meta(),
+                    })
+                }
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        Ok(qualifiers)
+    }
+
+    /// Parse `: SEMANTIC` and `: register(...)` annotations.
+    fn parse_annotations(&mut self) -> Result<Annotations> {
+        let mut annotations = Annotations::default();
+        while self.bump_if(Punct::Colon) {
+            let (word, meta) = self.expect_ident()?;
+            match word.as_str() {
+                "register" => {
+                    self.expect(Punct::LeftParen)?;
+                    let (register, register_meta) = self.expect_ident()?;
+                    let mut group = 0;
+                    if self.bump_if(Punct::Comma) {
+                        let (space, space_meta) = self.expect_ident()?;
+                        group = space
+                            .strip_prefix("space")
+                            .and_then(|index| index.parse().ok())
+                            .ok_or_else(|| {
+                                Error::semantic(format!("invalid space `{space}`"), space_meta)
+                            })?;
+                    }
+                    self.expect(Punct::RightParen)?;
+                    let binding =
+                        register_binding(&register, self.register_shifts).ok_or_else(|| {
+                            Error::semantic(format!("invalid register `{register}`"), register_meta)
+                        })?;
+                    annotations.register = Some(ResourceBinding { group, binding });
+                }
+                "packoffset" => {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("packoffset"),
+                        meta,
+                    })
+                }
+                _ => {
+                    let digits = word
+                        .bytes()
+                        .rev()
+                        .take_while(|byte| byte.is_ascii_digit())
+                        .count();
+                    let (name, index) = word.split_at(word.len() - digits);
+                    annotations.semantic = Some(Semantic {
+                        name: name.to_ascii_uppercase(),
+                        index: index.parse().unwrap_or(0),
+                        meta,
+                    });
+                }
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// Parse `[N]` suffixes after a declarator name.
+    fn parse_array_suffix(&mut self, base: crate::Handle<Type>) -> Result<crate::Handle<Type>> {
+        let mut sizes = Vec::new();
+        while self.bump_if(Punct::LeftBracket) {
+            let meta = self.meta();
+            if self.bump_if(Punct::RightBracket) {
+                return Err(Error::semantic("array declarations need a size", meta));
+            }
+            let size = self.parse_expression()?;
+            self.expect(Punct::RightBracket)?;
+            sizes.push(size);
+        }
+
+        // `float a[2][3]` is an array of two arrays of three floats.
+        let mut ty = base;
+        for size in sizes.into_iter().rev() {
+            let meta = size.meta;
+            let size = self.frontend.const_u32(&size)?;
+            let size = std::num::NonZeroU32::new(size)
+                .ok_or_else(|| Error::semantic("array size must be greater than zero", meta))?;
+            ty = self
+                .frontend
+                .array_type(ty, crate::ArraySize::Constant(size), meta);
+        }
+        Ok(ty)
+    }
+
+    /// Returns true if the upcoming tokens begin a type.
+    fn at_type(&self) -> bool {
+        match self.peek_ident() {
+            Some("struct" | "const") => true,
+            Some(word) => self.frontend.is_type_name(word),
+            None => false,
+        }
+    }
+
+    fn parse_value_type(&mut self) -> Result<crate::Handle<Type>> {
+        let meta = self.meta();
+        match self.parse_type()? {
+            Ty::Value(ty) => Ok(ty),
+            _ => Err(Error::semantic("expected a value type", meta)),
+        }
+    }
+
+    pub(super) fn parse_type(&mut self) -> Result<Ty> {
+        self.bump_keyword("const");
+        self.bump_keyword("struct");
+        let (word, meta) = self.expect_ident()?;
+
+        if let Some(ty) = self.frontend.types.get(&word) {
+            return Ok(*ty);
+        }
+        if word == "void" {
+            return Ok(Ty::Void);
+        }
+        if let Some(inner) = super::types::parse_numeric_type(&word) {
+            return Ok(Ty::Value(self.frontend.value_type(inner, meta)));
+        }
+
+        let dims = |name: &str| -> Option<(ImageDimension, bool, bool)> {
+            Some(match name {
+                "1D" => (ImageDimension::D1, false, false),
+                "1DArray" => (ImageDimension::D1, true, false),
+                "2D" => (ImageDimension::D2, false, false),
+                "2DArray" => (ImageDimension::D2, true, false),
+                "2DMS" => (ImageDimension::D2, false, true),
+                "2DMSArray" => (ImageDimension::D2, true, true),
+                "3D" => (ImageDimension::D3, false, false),
+                "Cube" => (ImageDimension::Cube, false, false),
+                "CubeArray" => (ImageDimension::Cube, true, false),
+                _ => return None,
+            })
+        };
+
+        match word.as_str() {
+            "vector" => {
+                let (scalar, size) = if self.bump_if(Punct::LeftAngle) {
+                    let scalar = self.parse_scalar_argument()?;
+                    self.expect(Punct::Comma)?;
+                    let size = super::types::vector_size(self.expect_uint()?)
+                        .ok_or_else(|| Error::semantic("invalid vector size", meta))?;
+                    self.expect(Punct::RightAngle)?;
+                    (scalar, size)
+                } else {
+                    (Scalar::F32, VectorSize::Quad)
+                };
+                Ok(Ty::Value(
+                    self.frontend
+                        .value_type(TypeInner::Vector { size, scalar }, meta),
+                ))
+            }
+            "matrix" => {
+                let (scalar, rows, columns) = if self.bump_if(Punct::LeftAngle) {
+                    let scalar = self.parse_scalar_argument()?;
+                    self.expect(Punct::Comma)?;
+                    let rows = super::types::vector_size(self.expect_uint()?);
+                    self.expect(Punct::Comma)?;
+                    let columns = super::types::vector_size(self.expect_uint()?);
+                    self.expect(Punct::RightAngle)?;
+                    match (rows, columns) {
+                        (Some(rows), Some(columns)) => (scalar, rows, columns),
+                        _ => return Err(Error::semantic("invalid matrix size", meta)),
+                    }
+                } else {
+                    (Scalar::F32, VectorSize::Quad, VectorSize::Quad)
+                };
+                if scalar.kind != crate::ScalarKind::Float {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("non-float matrices"),
+                        meta,
+                    });
+                }
+                Ok(Ty::Value(self.frontend.value_type(
+                    TypeInner::Matrix {
+                        columns,
+                        rows,
+                        scalar,
+                    },
+                    meta,
+                )))
+            }
+            "SamplerState" | "sampler" => Ok(Ty::Sampler { comparison: false }),
+            "SamplerComparisonState" => Ok(Ty::Sampler { comparison: true }),
+            "ByteAddressBuffer" => Ok(Ty::ByteAddressBuffer { writable: false }),
+            "RWByteAddressBuffer" => Ok(Ty::ByteAddressBuffer { writable: true }),
+            "StructuredBuffer" | "RWStructuredBuffer" | "ConstantBuffer" => {
+                self.expect(Punct::LeftAngle)?;
+                let element = self.parse_value_type()?;
+                self.expect(Punct::RightAngle)?;
+                Ok(match word.as_str() {
+                    "ConstantBuffer" => Ty::ConstantBuffer { element },
+                    writable => Ty::StructuredBuffer {
+                        element,
+                        writable: writable == "RWStructuredBuffer",
+                    },
+                })
+            }
+            _ => {
+                if let Some((dim, arrayed, multi)) = word.strip_prefix("Texture").and_then(dims) {
+                    let (element, opened) = self.parse_texture_element(meta)?;
+                    if opened {
+                        if multi && self.bump_if(Punct::Comma) {
+                            // The sample count is only a hint.
+                            self.expect_uint()?;
+                        }
+                        self.expect(Punct::RightAngle)?;
+                    }
+                    return Ok(Ty::Texture {
+                        dim,
+                        arrayed,
+                        multi,
+                        element,
+                    });
+                }
+                if let Some((dim, arrayed, multi)) = word.strip_prefix("RWTexture").and_then(dims) {
+                    if multi || dim == ImageDimension::Cube {
+                        return Err(Error::semantic(
+                            format!("`{word}` is not a valid resource type"),
+                            meta,
+                        ));
+                    }
+                    let (element, opened) = self.parse_texture_element(meta)?;
+                    if opened {
+                        self.expect(Punct::RightAngle)?;
+                    }
+                    return Ok(Ty::RwTexture {
+                        dim,
+                        arrayed,
+                        element,
+                    });
+                }
+                Err(Error {
+                    kind: ErrorKind::UnknownType(word),
+                    meta,
+                })
+            }
+        }
+    }
+
+    /// Parse the optional `<T` opening a texture's template arguments,
+    /// returning the element type and whether a `<` was found.
+    ///
+    /// The caller closes the list, since multisampled textures take a second
+    /// argument.
+    fn parse_texture_element(&mut self, meta: Span) -> Result<(crate::Handle<Type>, bool)> {
+        if self.bump_if(Punct::LeftAngle) {
+            let element = self.parse_value_type()?;
+            match self.frontend.module.types[element].inner {
+                TypeInner::Scalar(_) | TypeInner::Vector { .. } => Ok((element, true)),
+                _ => Err(Error::semantic(
+                    "texture elements must be scalars or vectors",
+                    meta,
+                )),
+            }
+        } else {
+            let element = self.frontend.value_type(
+                TypeInner::Vector {
+                    size: VectorSize::Quad,
+                    scalar: Scalar::F32,
+                },
+                meta,
+            );
+            Ok((element, false))
+        }
+    }
+
+    fn parse_scalar_argument(&mut self) -> Result<Scalar> {
+        let (word, meta) = self.expect_ident()?;
+        match super::types::parse_numeric_type(&word) {
+            Some(TypeInner::Scalar(scalar)) => Ok(scalar),
+            _ => Err(Error::semantic("expected a scalar type", meta)),
+        }
+    }
+
+    // Statements
+
+    /// Parse statements up to and including the closing `}`.
+    fn parse_block_contents(&mut self) -> Result<Vec<Stmt>> {
+        let mut statements = Vec::new();
+        while !self.bump_if(Punct::RightBrace) {
+            statements.push(self.parse_statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn parse_statement(&mut self) -> Result<Stmt> {
+        let start = self.meta();
+        // Loop and branch hints.
+        if self.peek_punct(Punct::LeftBracket) {
+            self.parse_attributes()?;
+        }
+
+        let kind = if self.bump_if(Punct::LeftBrace) {
+            StmtKind::Block(self.parse_block_contents()?)
+        } else if self.bump_if(Punct::Semicolon) {
+            StmtKind::Empty
+        } else if self.bump_keyword("if") {
+            self.expect(Punct::LeftParen)?;
+            let condition = self.parse_expression()?;
+            self.expect(Punct::RightParen)?;
+            let accept = Box::new(self.parse_statement()?);
+            let reject = if self.bump_keyword("else") {
+                Some(Box::new(self.parse_statement()?))
+            } else {
+                None
+            };
+            StmtKind::If {
+                condition,
+                accept,
+                reject,
+            }
+        } else if self.bump_keyword("for") {
+            self.expect(Punct::LeftParen)?;
+            let init = if self.bump_if(Punct::Semicolon) {
+                None
+            } else {
+                Some(Box::new(self.parse_simple_statement()?))
+            };
+            let condition = if self.peek_punct(Punct::Semicolon) {
+                None
+            } else {
+                Some(self.parse_expression()?)
+            };
+            self.expect(Punct::Semicolon)?;
+            let update = if self.peek_punct(Punct::RightParen) {
+                None
+            } else {
+                Some(self.parse_expression()?)
+            };
+            self.expect(Punct::RightParen)?;
+            let body = Box::new(self.parse_statement()?);
+            StmtKind::For {
+                init,
+                condition,
+                update,
+                body,
+            }
+        } else if self.bump_keyword("while") {
+            self.expect(Punct::LeftParen)?;
+            let condition = self.parse_expression()?;
+            self.expect(Punct::RightParen)?;
+            let body = Box::new(self.parse_statement()?);
+            StmtKind::While { condition, body }
+        } else if self.bump_keyword("do") {
+            let body = Box::new(self.parse_statement()?);
+            if !self.bump_keyword("while") {
+                return Err(self.error_expected("`while`"));
+            }
+            self.expect(Punct::LeftParen)?;
+            let condition = self.parse_expression()?;
+            self.expect(Punct::RightParen)?;
+            self.expect(Punct::Semicolon)?;
+            StmtKind::DoWhile { body, condition }
+        } else if self.bump_keyword("switch") {
+            self.expect(Punct::LeftParen)?;
+            let selector = self.parse_expression()?;
+            self.expect(Punct::RightParen)?;
+            self.expect(Punct::LeftBrace)?;
+            let mut cases = Vec::new();
+            while !self.bump_if(Punct::RightBrace) {
+                let meta = self.meta();
+                let value = if self.bump_keyword("case") {
+                    Some(self.parse_conditional()?)
+                } else if self.bump_keyword("default") {
+                    None
+                } else {
+                    return Err(self.error_expected("`case` or `default`"));
+                };
+                self.expect(Punct::Colon)?;
+                let mut body = Vec::new();
+                while !(self.peek_keyword("case")
+                    || self.peek_keyword("default")
+                    || self.peek_punct(Punct::RightBrace))
+                {
+                    body.push(self.parse_statement()?);
+                }
+                cases.push(SwitchCase { value, body, meta });
+            }
+            StmtKind::Switch { selector, cases }
+        } else if self.bump_keyword("break") {
+            self.expect(Punct::Semicolon)?;
+            StmtKind::Break
+        } else if self.bump_keyword("continue") {
+            self.expect(Punct::Semicolon)?;
+            StmtKind::Continue
+        } else if self.bump_keyword("discard") {
+            self.expect(Punct::Semicolon)?;
+            StmtKind::Discard
+        } else if self.bump_keyword("return") {
+            let value = if self.peek_punct(Punct::Semicolon) {
+                None
+            } else {
+                Some(self.parse_expression()?)
+            };
+            self.expect(Punct::Semicolon)?;
+            StmtKind::Return(value)
+        } else {
+            return self.parse_simple_statement();
+        };
+
+        Ok(Stmt {
+            kind,
+            meta: start.until(&self.prev_meta()),
+        })
+    }
+
+    /// Parse a declaration or expression statement, including its `;`.
+    fn parse_simple_statement(&mut self) -> Result<Stmt> {
+        let start = self.meta();
+        let kind = if self.is_declaration() {
+            let _ = self.parse_qualifiers()?;
+            let base = self.parse_value_type()?;
+            let mut decls = Vec::new();
+            loop {
+                let (name, meta) = self.expect_ident()?;
+                let ty = self.parse_array_suffix(base)?;
+                let init = if self.bump_if(Punct::Equal) {
+                    Some(self.parse_initializer()?)
+                } else {
+                    None
+                };
+                decls.push(LocalDecl {
+                    name,
+                    ty,
+                    init,
+                    meta: meta.until(&self.prev_meta()),
+                });
+                if !self.bump_if(Punct::Comma) {
+                    break;
+                }
+            }
+            StmtKind::Declare(decls)
+        } else {
+            StmtKind::Expr(self.parse_expression()?)
+        };
+        self.expect(Punct::Semicolon)?;
+        Ok(Stmt {
+            kind,
+            meta: start.until(&self.prev_meta()),
+        })
+    }
+
+    /// Returns true if the upcoming tokens are a local variable declaration.
+    fn is_declaration(&mut self) -> bool {
+        if matches!(self.peek_ident(), Some("static" | "const" | "precise")) {
+            return true;
+        }
+        if !self.at_type() {
+            return false;
+        }
+        // Tell `float4 x = ...` apart from a constructor like `float4(...)`.
+        let saved = self.pos;
+        let result = self.parse_type().is_ok() && self.peek_ident().is_some();
+        self.pos = saved;
+        result
+    }
+
+    // Expressions
+
+    fn parse_initializer(&mut self) -> Result<Expr> {
+        let start = self.meta();
+        if self.bump_if(Punct::LeftBrace) {
+            let mut items = Vec::new();
+            while !self.bump_if(Punct::RightBrace) {
+                items.push(self.parse_initializer()?);
+                if !self.bump_if(Punct::Comma) {
+                    self.expect(Punct::RightBrace)?;
+                    break;
+                }
+            }
+            Ok(Expr {
+                kind: ExprKind::InitList(items),
+                meta: start.until(&self.prev_meta()),
+            })
+        } else {
+            self.parse_assignment()
+        }
+    }
+
+    pub(super) fn parse_expression(&mut self) -> Result<Expr> {
+        let first = self.parse_assignment()?;
+        if !self.peek_punct(Punct::Comma) {
+            return Ok(first);
+        }
+        let mut meta = first.meta;
+        let mut items = vec![first];
+        while self.bump_if(Punct::Comma) {
+            let item = self.parse_assignment()?;
+            meta.subsume(item.meta);
+            items.push(item);
+        }
+        Ok(Expr {
+            kind: ExprKind::Sequence(items),
+            meta,
+        })
+    }
+
+    fn parse_assignment(&mut self) -> Result<Expr> {
+        let lhs = self.parse_conditional()?;
+        let op = match self.peek() {
+            Some(&Token {
+                value: TokenValue::Punct(punct),
+                ..
+            }) => match punct {
+                Punct::Equal => None,
+                Punct::AddAssign => Some(BinaryOp::Add),
+                Punct::SubAssign => Some(BinaryOp::Subtract),
+                Punct::MulAssign => Some(BinaryOp::Multiply),
+                Punct::DivAssign => Some(BinaryOp::Divide),
+                Punct::ModAssign => Some(BinaryOp::Modulo),
+                Punct::LeftShiftAssign => Some(BinaryOp::ShiftLeft),
+                Punct::RightShiftAssign => Some(BinaryOp::ShiftRight),
+                Punct::AndAssign => Some(BinaryOp::And),
+                Punct::XorAssign => Some(BinaryOp::ExclusiveOr),
+                Punct::OrAssign => Some(BinaryOp::InclusiveOr),
+                _ => return Ok(lhs),
+            },
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        let rhs = self.parse_assignment()?;
+        let meta = lhs.meta.until(&rhs.meta);
+        Ok(Expr {
+            kind: ExprKind::Assign {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            meta,
+        })
+    }
+
+    fn parse_conditional(&mut self) -> Result<Expr> {
+        let condition = self.parse_binary(0)?;
+        if !self.bump_if(Punct::Question) {
+            return Ok(condition);
+        }
+        let accept = self.parse_assignment()?;
+        self.expect(Punct::Colon)?;
+        let reject = self.parse_assignment()?;
+        let meta = condition.meta.until(&reject.meta);
+        Ok(Expr {
+            kind: ExprKind::Select {
+                condition: Box::new(condition),
+                accept: Box::new(accept),
+                reject: Box::new(reject),
+            },
+            meta,
+        })
+    }
+
+    /// Precedence climbing over the binary operators, loosest first.
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let Some((op, precedence)) = self.peek_binary_op() else {
+                break;
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.parse_binary(precedence + 1)?;
+            let meta = left.meta.until(&right.meta);
+            left = Expr {
+                kind: ExprKind::Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                meta,
+            };
+        }
+        Ok(left)
+    }
+
+    fn peek_binary_op(&self) -> Option<(BinaryOp, u8)> {
+        let Some(&Token {
+            value: TokenValue::Punct(punct),
+            ..
+        }) = self.peek()
+        else {
+            return None;
+        };
+        Some(match punct {
+            Punct::LogicalOr => (BinaryOp::LogicalOr, 0),
+            Punct::LogicalAnd => (BinaryOp::LogicalAnd, 1),
+            Punct::Pipe => (BinaryOp::InclusiveOr, 2),
+            Punct::Caret => (BinaryOp::ExclusiveOr, 3),
+            Punct::Ampersand => (BinaryOp::And, 4),
+            Punct::EqualEqual => (BinaryOp::Equal, 5),
+            Punct::NotEqual => (BinaryOp::NotEqual, 5),
+            Punct::LeftAngle => (BinaryOp::Less, 6),
+            Punct::RightAngle => (BinaryOp::Greater, 6),
+            Punct::LessEqual => (BinaryOp::LessEqual, 6),
+            Punct::GreaterEqual => (BinaryOp::GreaterEqual, 6),
+            Punct::LeftShift => (BinaryOp::ShiftLeft, 7),
+            Punct::RightShift => (BinaryOp::ShiftRight, 7),
+            Punct::Plus => (BinaryOp::Add, 8),
+            Punct::Minus => (BinaryOp::Subtract, 8),
+            Punct::Star => (BinaryOp::Multiply, 9),
+            Punct::Slash => (BinaryOp::Divide, 9),
+            Punct::Percent => (BinaryOp::Modulo, 9),
+            _ => return None,
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let start = self.meta();
+        let op = match self.peek() {
+            Some(&Token {
+                value: TokenValue::Punct(punct),
+                ..
+            }) => match punct {
+                Punct::Minus => Some(UnaryOp::Negate),
+                Punct::Bang => Some(UnaryOp::LogicalNot),
+                Punct::Tilde => Some(UnaryOp::BitwiseNot),
+                Punct::Plus => {
+                    self.pos += 1;
+                    return self.parse_unary();
+                }
+                Punct::Increment | Punct::Decrement => {
+                    self.pos += 1;
+                    let expr = self.parse_unary()?;
+                    let meta = start.until(&expr.meta);
+                    return Ok(Expr {
+                        kind: ExprKind::IncDec {
+                            increment: punct == Punct::Increment,
+                            postfix: false,
+                            expr: Box::new(expr),
+                        },
+                        meta,
+                    });
+                }
+                Punct::LeftParen if self.is_cast() => {
+                    self.pos += 1;
+                    let ty = self.parse_value_type()?;
+                    let ty = self.parse_array_suffix(ty)?;
+                    self.expect(Punct::RightParen)?;
+                    let expr = self.parse_unary()?;
+                    let meta = start.until(&expr.meta);
+                    return Ok(Expr {
+                        kind: ExprKind::Cast {
+                            ty,
+                            expr: Box::new(expr),
+                        },
+                        meta,
+                    });
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match op {
+            Some(op) => {
+                self.pos += 1;
+                let expr = self.parse_unary()?;
+                let meta = start.until(&expr.meta);
+                Ok(Expr {
+                    kind: ExprKind::Unary {
+                        op,
+                        expr: Box::new(expr),
+                    },
+                    meta,
+                })
+            }
+            None => self.parse_postfix(),
+        }
+    }
+
+    /// Returns true if a `(` starts a C-style cast.
+    fn is_cast(&self) -> bool {
+        match self.peek_at(1) {
+            Some(&Token {
+                value: TokenValue::Ident(ref word),
+                ..
+            }) => matches!(word.as_str(), "const" | "struct") || self.frontend.is_type_name(word),
+            _ => false,
+        }
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if self.bump_if(Punct::LeftBracket) {
+                let index = self.parse_expression()?;
+                self.expect(Punct::RightBracket)?;
+                let meta = expr.meta.until(&self.prev_meta());
+                expr = Expr {
+                    kind: ExprKind::Index {
+                        base: Box::new(expr),
+                        index: Box::new(index),
+                    },
+                    meta,
+                };
+            } else if self.bump_if(Punct::Dot) {
+                let (field, _) = self.expect_ident()?;
+                if self.bump_if(Punct::LeftParen) {
+                    let args = self.parse_arguments()?;
+                    let meta = expr.meta.until(&self.prev_meta());
+                    expr = Expr {
+                        kind: ExprKind::Method {
+                            base: Box::new(expr),
+                            name: field,
+                            args,
+                        },
+                        meta,
+                    };
+                } else {
+                    let meta = expr.meta.until(&self.prev_meta());
+                    expr = Expr {
+                        kind: ExprKind::Member {
+                            base: Box::new(expr),
+                            field,
+                        },
+                        meta,
+                    };
+                }
+            } else if self.peek_punct(Punct::Increment) || self.peek_punct(Punct::Decrement) {
+                let increment = self.peek_punct(Punct::Increment);
+                self.pos += 1;
+                let meta = expr.meta.until(&self.prev_meta());
+                expr = Expr {
+                    kind: ExprKind::IncDec {
+                        increment,
+                        postfix: true,
+                        expr: Box::new(expr),
+                    },
+                    meta,
+                };
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    /// Parse arguments up to and including the closing `)`.
+    fn parse_arguments(&mut self) -> Result<Vec<Expr>> {
+        let mut args = Vec::new();
+        while !self.bump_if(Punct::RightParen) {
+            args.push(self.parse_assignment()?);
+            if !self.bump_if(Punct::Comma) {
+                self.expect(Punct::RightParen)?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let token = self.next()?;
+        let meta = token.meta;
+        let kind = match token.value {
+            TokenValue::Int(int) => ExprKind::Literal(if int.signed {
+                Literal::Int(int.value as i64)
+            } else {
+                Literal::Uint(int.value)
+            }),
+            TokenValue::Float(float) => ExprKind::Literal(if float.width == 64 {
+                Literal::Double(float.value as f64)
+            } else {
+                Literal::Float(float.value as f64)
+            }),
+            TokenValue::Punct(Punct::LeftParen) => {
+                let expr = self.parse_expression()?;
+                self.expect(Punct::RightParen)?;
+                return Ok(Expr {
+                    kind: expr.kind,
+                    meta: meta.until(&self.prev_meta()),
+                });
+            }
+            TokenValue::Ident(ref word) if word == "true" || word == "false" => {
+                ExprKind::Literal(Literal::Bool(word == "true"))
+            }
+            TokenValue::Ident(word) => {
+                if self.frontend.is_type_name(&word) {
+                    // A constructor like `float3(...)`.
+                    self.pos -= 1;
+                    let ty = self.parse_value_type()?;
+                    self.expect(Punct::LeftParen)?;
+                    let args = self.parse_arguments()?;
+                    ExprKind::Construct { ty, args }
+                } else if self.bump_if(Punct::LeftParen) {
+                    let args = self.parse_arguments()?;
+                    ExprKind::Call { name: word, args }
+                } else {
+                    ExprKind::Ident(word)
+                }
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error_expected("expression"));
+            }
+        };
+        Ok(Expr {
+            kind,
+            meta: meta.until(&self.prev_meta()),
+        })
+    }
+}
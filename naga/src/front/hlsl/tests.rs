@@ -0,0 +1,376 @@
+use super::{ErrorKind, Frontend, Options, RegisterShifts};
+use crate::{
+    valid::{Capabilities, ValidationFlags, Validator},
+    AddressSpace, Binding, BuiltIn, ImageClass, Module, ShaderStage, TypeInner,
+};
+
+fn parse(source: &str) -> Module {
+    let module = Frontend::default()
+        .parse(&Options::default(), source)
+        .unwrap_or_else(|error| panic!("{}", error.emit_to_string(source)));
+    Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .unwrap_or_else(|error| panic!("{:?}", error.into_inner()));
+    module
+}
+
+fn parse_error(source: &str) -> ErrorKind {
+    Frontend::default()
+        .parse(&Options::default(), source)
+        .err()
+        .unwrap()
+        .kind
+}
+
+#[test]
+fn vertex_and_pixel() {
+    let module = parse(
+        r#"
+        cbuffer Globals : register(b0) {
+            float4x4 view_proj;
+            float4 tint;
+        };
+
+        Texture2D<float4> color_texture : register(t1);
+        SamplerState color_sampler : register(s2);
+
+        struct VsOutput {
+            float4 position : SV_Position;
+            float2 uv : TEXCOORD0;
+            nointerpolation uint index : TEXCOORD1;
+        };
+
+        VsOutput vs_main(float3 position : POSITION, float2 uv : TEXCOORD0,
+                         uint index : SV_VertexID) {
+            VsOutput output;
+            output.position = mul(view_proj, float4(position, 1.0));
+            output.uv = uv;
+            output.index = index;
+            return output;
+        }
+
+        float4 ps_main(VsOutput input) : SV_Target {
+            float4 color = color_texture.Sample(color_sampler, input.uv);
+            clip(color.a - 0.5);
+            return color * tint;
+        }
+        "#,
+    );
+
+    assert_eq!(module.entry_points.len(), 2);
+    let vs = &module.entry_points[0];
+    assert_eq!(vs.stage, ShaderStage::Vertex);
+    assert_eq!(vs.function.arguments.len(), 3);
+    assert_eq!(
+        vs.function.arguments[2].binding,
+        Some(Binding::BuiltIn(BuiltIn::VertexIndex))
+    );
+
+    let ps = &module.entry_points[1];
+    assert_eq!(ps.stage, ShaderStage::Fragment);
+    let result = ps.function.result.as_ref().unwrap();
+    assert!(matches!(
+        result.binding,
+        Some(Binding::Location { location: 0, .. })
+    ));
+    // The inputs are the flattened members of `VsOutput`.
+    assert_eq!(ps.function.arguments.len(), 3);
+    assert!(matches!(
+        ps.function.arguments[2].binding,
+        Some(Binding::Location {
+            location: 1,
+            interpolation: Some(crate::Interpolation::Flat),
+            ..
+        })
+    ));
+
+    let cbuffer = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.space == AddressSpace::Uniform)
+        .unwrap()
+        .1;
+    assert_eq!(cbuffer.binding.as_ref().unwrap().binding, 0);
+}
+
+#[test]
+fn compute() {
+    let module = parse(
+        r#"
+        RWStructuredBuffer<uint> counters : register(u0, space1);
+        RWByteAddressBuffer output : register(u1, space1);
+        groupshared uint shared_total;
+
+        [numthreads(64, 1, 1)]
+        void main(uint3 id : SV_DispatchThreadID, uint local : SV_GroupIndex) {
+            if (local == 0) {
+                shared_total = 0;
+            }
+            GroupMemoryBarrierWithGroupSync();
+
+            uint previous;
+            InterlockedAdd(shared_total, id.x, previous);
+            InterlockedMax(counters[id.x % 4], previous);
+            GroupMemoryBarrierWithGroupSync();
+
+            if (local == 0) {
+                output.Store2(id.x * 8, uint2(shared_total, previous));
+            }
+        }
+        "#,
+    );
+
+    let entry = &module.entry_points[0];
+    assert_eq!(entry.stage, ShaderStage::Compute);
+    assert_eq!(entry.workgroup_size, [64, 1, 1]);
+
+    // Both destinations of `Interlocked*` calls became atomic.
+    for (_, var) in module.global_variables.iter() {
+        let mut ty = var.ty;
+        if let TypeInner::Array { base, .. } = module.types[ty].inner {
+            if var.name.as_deref() == Some("output") {
+                continue;
+            }
+            ty = base;
+        }
+        assert!(
+            matches!(module.types[ty].inner, TypeInner::Atomic(_)),
+            "{:?} isn't atomic",
+            var.name
+        );
+    }
+}
+
+#[test]
+fn statements_and_operators() {
+    parse(
+        r#"
+        static const int COUNT = 4;
+        static float weights[COUNT] = { 0.1, 0.2, 0.3, 0.4 };
+
+        float sum(float values[COUNT], out int nonzero) {
+            float total = 0;
+            nonzero = 0;
+            for (int i = 0; i < COUNT; ++i) {
+                if (values[i] == 0.0)
+                    continue;
+                total += values[i];
+                nonzero++;
+            }
+            return total;
+        }
+
+        void adjust(inout float3 value) {
+            int mode = (int)value.x;
+            switch (mode) {
+                case 0:
+                    value.xy *= 2;
+                    break;
+                case 1:
+                case 2:
+                    value = value.zyx;
+                    break;
+                default:
+                    value.z = -value.z;
+            }
+            do {
+                value.x -= 1;
+            } while (value.x > 0);
+        }
+
+        float4 main(float4 position : SV_Position) : SV_Target {
+            int nonzero;
+            float total = sum(weights, nonzero);
+            float3 color = position.xyz;
+            adjust(color);
+            float3x3 m = float3x3(1, 0, 0, 0, 1, 0, 0, 0, 1);
+            m[1] = color;
+            color = mul(color, m) + m._m11;
+            return float4(color * (nonzero > 2 ? total : 1.0), 1);
+        }
+        "#,
+    );
+}
+
+#[test]
+fn matrix_rows() {
+    let module = parse(
+        r#"
+        static const float2x3 m = { 1, 2, 3, 4, 5, 6 };
+
+        [numthreads(1, 1, 1)]
+        void main() {}
+        "#,
+    );
+
+    // `float2x3` has two rows of three columns, so naga stores it as three
+    // columns of two components, built from the elements in row order.
+    let (_, constant) = module.constants.iter().next().unwrap();
+    assert_eq!(
+        module.types[constant.ty].inner,
+        TypeInner::Matrix {
+            columns: crate::VectorSize::Tri,
+            rows: crate::VectorSize::Bi,
+            scalar: crate::Scalar::F32,
+        }
+    );
+    let crate::Expression::Compose { ref components, .. } =
+        module.global_expressions[constant.init]
+    else {
+        panic!("expected a composed matrix");
+    };
+    let crate::Expression::Compose {
+        components: ref first_column,
+        ..
+    } = module.global_expressions[components[0]]
+    else {
+        panic!("expected a composed column");
+    };
+    assert!(matches!(
+        module.global_expressions[first_column[1]],
+        crate::Expression::Literal(crate::Literal::F32(value)) if value == 4.0
+    ));
+}
+
+#[test]
+fn comparison_textures() {
+    let module = parse(
+        r#"
+        Texture2D shadow_map : register(t0);
+        SamplerComparisonState shadow_sampler : register(s1);
+        RWTexture2D<float4> output : register(u2);
+
+        [numthreads(8, 8, 1)]
+        void main(uint3 id : SV_DispatchThreadID) {
+            uint width, height;
+            shadow_map.GetDimensions(width, height);
+            float2 uv = float2(id.xy) / float2(width, height);
+            float lit = shadow_map.SampleCmpLevelZero(shadow_sampler, uv, 0.5);
+            output[id.xy] = lit;
+        }
+        "#,
+    );
+
+    let classes: Vec<_> = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, var)| match module.types[var.ty].inner {
+            TypeInner::Image { class, .. } => Some(class),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        classes,
+        [
+            ImageClass::Depth { multi: false },
+            ImageClass::Storage {
+                format: crate::StorageFormat::Rgba32Float,
+                access: crate::StorageAccess::STORE,
+            },
+        ]
+    );
+}
+
+#[test]
+fn register_shifts() {
+    let options = Options {
+        register_shifts: RegisterShifts {
+            constant_buffer: 0,
+            shader_resource: 10,
+            sampler: 20,
+            unordered_access: 30,
+        },
+        ..Options::default()
+    };
+    let module = Frontend::default()
+        .parse(
+            &options,
+            r#"
+            cbuffer Globals : register(b0) { float4 tint; };
+            Texture2D<float4> color : register(t0);
+            SamplerState color_sampler : register(s0);
+            RWTexture2D<float4> output : register(u0, space1);
+            [[vk::binding(5)]] Texture2D<float4> unshifted;
+
+            [numthreads(1, 1, 1)]
+            void main() {
+                output[uint2(0, 0)] = color.SampleLevel(color_sampler, float2(0.0, 0.0), 0.0)
+                    * tint + unshifted.Load(int3(0, 0, 0));
+            }
+            "#,
+        )
+        .unwrap();
+
+    let bindings: Vec<_> = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, var)| {
+            let binding = var.binding.as_ref()?;
+            Some((var.name.as_deref().unwrap(), binding.group, binding.binding))
+        })
+        .collect();
+    assert_eq!(
+        bindings,
+        [
+            ("Globals", 0, 0),
+            ("color", 0, 10),
+            ("color_sampler", 0, 20),
+            ("output", 1, 30),
+            ("unshifted", 0, 5),
+        ]
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        parse_error("[numthreads(1, 1, 1)] void main() { x = 1; }"),
+        ErrorKind::UnknownVariable("x".to_string())
+    );
+    assert!(matches!(
+        parse_error("Texture2D t; [numthreads(1, 1, 1)] void main() {}"),
+        ErrorKind::SemanticError(_)
+    ));
+    assert!(matches!(
+        parse_error("float4 main(float4 x : SV_Dummy) : SV_Target { return x; }"),
+        ErrorKind::UnsupportedSemantic(_)
+    ));
+    // HLSL packs `b` right after `a`, but naga aligns it to 16 bytes.
+    assert!(matches!(
+        parse_error(
+            "cbuffer C : register(b0) { float a; float3 b; }; [numthreads(1, 1, 1)] void main() {}"
+        ),
+        ErrorKind::SemanticError(_)
+    ));
+    // Registers of different types collide without shifts.
+    assert!(matches!(
+        parse_error(
+            "Texture2D t : register(t0); SamplerState s : register(s0); \
+             [numthreads(1, 1, 1)] void main() {}"
+        ),
+        ErrorKind::SemanticError(_)
+    ));
+    // Non-numeric arguments to numeric intrinsics are errors, not panics.
+    assert!(matches!(
+        parse_error("struct S { float x; }; [numthreads(1, 1, 1)] void main() { S s; rcp(s); }"),
+        ErrorKind::SemanticError(_)
+    ));
+    // There are no files to include.
+    assert_eq!(
+        parse_error("#include \"common.hlsl\"\n[numthreads(1, 1, 1)] void main() {}"),
+        ErrorKind::NotImplemented("`#include` directives")
+    );
+    let defines = [("BIG".to_string(), "99999999999999999999999".to_string())];
+    let options = Options {
+        defines: defines.into_iter().collect(),
+        ..Options::default()
+    };
+    assert!(matches!(
+        Frontend::default()
+            .parse(&options, "[numthreads(1, 1, 1)] void main() {}")
+            .err()
+            .unwrap()
+            .kind,
+        ErrorKind::PreprocessorError(_)
+    ));
+}
@@ -0,0 +1,225 @@
+use super::{
+    ast::{Expr, Ty},
+    lower::Context,
+    Error, Frontend, Result,
+};
+use crate::{
+    ArraySize, Handle, Scalar, ScalarKind, Span, StorageFormat, Type, TypeInner, VectorSize,
+};
+
+fn parse_scalar(word: &str) -> Option<Scalar> {
+    Some(match word {
+        "bool" => Scalar::BOOL,
+        "int" | "int32_t" | "min16int" | "min12int" => Scalar::I32,
+        "uint" | "uint32_t" | "dword" | "min16uint" => Scalar::U32,
+        "int64_t" => Scalar::I64,
+        "uint64_t" => Scalar::U64,
+        // Naga has no 16-bit types, and the minimum precision types are only
+        // a hint, so they're all widened to 32 bits.
+        "float" | "float32_t" | "half" | "min16float" | "min10float" => Scalar::F32,
+        "double" | "float64_t" => Scalar::F64,
+        _ => return None,
+    })
+}
+
+pub const fn vector_size(size: u32) -> Option<VectorSize> {
+    Some(match size {
+        2 => VectorSize::Bi,
+        3 => VectorSize::Tri,
+        4 => VectorSize::Quad,
+        _ => return None,
+    })
+}
+
+/// Parse a built-in numeric type name like `float`, `uint3` or `float4x3`.
+///
+/// HLSL names matrices by their rows first, so `floatRxC` has `R` rows and
+/// `C` columns. Naga matrices are column-major, which gives `float4x3` the
+/// same memory layout as a naga matrix with 3 columns of `vec4`. Indexing a
+/// matrix yields a row in HLSL but a column in naga, so the lowering code
+/// swaps the roles accordingly.
+pub fn parse_numeric_type(word: &str) -> Option<TypeInner> {
+    if let Some(scalar) = parse_scalar(word) {
+        return Some(TypeInner::Scalar(scalar));
+    }
+
+    let digits = word
+        .bytes()
+        .position(|byte| byte.is_ascii_digit())
+        .filter(|&index| index > 0)?;
+    let (base, dims) = word.split_at(digits);
+    let scalar = parse_scalar(base)?;
+    // Names like `int64_t` and `float32_t` contain digits too.
+    let dims = dims.as_bytes();
+    let dim = |byte: u8| u32::from(byte - b'0');
+    match *dims {
+        [b'1'] => Some(TypeInner::Scalar(scalar)),
+        [size] if size.is_ascii_digit() => Some(TypeInner::Vector {
+            size: vector_size(dim(size))?,
+            scalar,
+        }),
+        [rows, b'x', columns] if rows.is_ascii_digit() && columns.is_ascii_digit() => {
+            if scalar.kind != ScalarKind::Float {
+                return None;
+            }
+            Some(TypeInner::Matrix {
+                columns: vector_size(dim(columns))?,
+                rows: vector_size(dim(rows))?,
+                scalar,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Map the argument of `[[vk::image_format("...")]]` to a storage format.
+///
+/// These are the same names GLSL uses in its layout qualifiers.
+pub fn parse_storage_format(word: &str) -> Option<StorageFormat> {
+    use crate::StorageFormat as Sf;
+
+    Some(match word {
+        "rgba32f" => Sf::Rgba32Float,
+        "rgba16f" => Sf::Rgba16Float,
+        "rg32f" => Sf::Rg32Float,
+        "rg16f" => Sf::Rg16Float,
+        "r11g11b10f" => Sf::Rg11b10Float,
+        "r32f" => Sf::R32Float,
+        "r16f" => Sf::R16Float,
+        "rgba16" => Sf::Rgba16Unorm,
+        "rgb10a2ui" => Sf::Rgb10a2Uint,
+        "rgb10a2" => Sf::Rgb10a2Unorm,
+        "rgba8" => Sf::Rgba8Unorm,
+        "rg16" => Sf::Rg16Unorm,
+        "rg8" => Sf::Rg8Unorm,
+        "r16" => Sf::R16Unorm,
+        "r8" => Sf::R8Unorm,
+        "rgba16snorm" => Sf::Rgba16Snorm,
+        "rgba8snorm" => Sf::Rgba8Snorm,
+        "rg16snorm" => Sf::Rg16Snorm,
+        "rg8snorm" => Sf::Rg8Snorm,
+        "r16snorm" => Sf::R16Snorm,
+        "r8snorm" => Sf::R8Snorm,
+        "rgba32i" => Sf::Rgba32Sint,
+        "rgba16i" => Sf::Rgba16Sint,
+        "rgba8i" => Sf::Rgba8Sint,
+        "rg32i" => Sf::Rg32Sint,
+        "rg16i" => Sf::Rg16Sint,
+        "rg8i" => Sf::Rg8Sint,
+        "r32i" => Sf::R32Sint,
+        "r16i" => Sf::R16Sint,
+        "r8i" => Sf::R8Sint,
+        "rgba32ui" => Sf::Rgba32Uint,
+        "rgba16ui" => Sf::Rgba16Uint,
+        "rgba8ui" => Sf::Rgba8Uint,
+        "rg32ui" => Sf::Rg32Uint,
+        "rg16ui" => Sf::Rg16Uint,
+        "rg8ui" => Sf::Rg8Uint,
+        "r32ui" => Sf::R32Uint,
+        "r16ui" => Sf::R16Uint,
+        "r8ui" => Sf::R8Uint,
+        _ => return None,
+    })
+}
+
+/// The storage format used for a `RWTexture*<T>` without an explicit
+/// `[[vk::image_format]]`, which always holds four 32-bit channels of `T`.
+pub const fn default_storage_format(scalar: Scalar) -> Option<StorageFormat> {
+    Some(match scalar.kind {
+        ScalarKind::Float if scalar.width == 4 => StorageFormat::Rgba32Float,
+        ScalarKind::Sint if scalar.width == 4 => StorageFormat::Rgba32Sint,
+        ScalarKind::Uint if scalar.width == 4 => StorageFormat::Rgba32Uint,
+        _ => return None,
+    })
+}
+
+pub const fn scalar_components(ty: &TypeInner) -> Option<Scalar> {
+    match *ty {
+        TypeInner::Scalar(scalar)
+        | TypeInner::Vector { scalar, .. }
+        | TypeInner::ValuePointer { scalar, .. }
+        | TypeInner::Matrix { scalar, .. } => Some(scalar),
+        _ => None,
+    }
+}
+
+/// The rank of `scalar` in HLSL's usual arithmetic conversions.
+pub const fn type_power(scalar: Scalar) -> Option<u32> {
+    Some(match scalar.kind {
+        ScalarKind::Bool => 0,
+        ScalarKind::Sint if scalar.width == 4 => 1,
+        ScalarKind::Uint if scalar.width == 4 => 2,
+        ScalarKind::Sint => 3,
+        ScalarKind::Uint => 4,
+        ScalarKind::Float if scalar.width == 4 => 5,
+        ScalarKind::Float => 6,
+        ScalarKind::AbstractInt | ScalarKind::AbstractFloat => return None,
+    })
+}
+
+impl Frontend {
+    /// Returns true if `word` names a type.
+    pub(super) fn is_type_name(&self, word: &str) -> bool {
+        self.types.contains_key(word)
+            || parse_numeric_type(word).is_some()
+            || matches!(
+                word,
+                "void"
+                    | "vector"
+                    | "matrix"
+                    | "SamplerState"
+                    | "SamplerComparisonState"
+                    | "sampler"
+                    | "ByteAddressBuffer"
+                    | "RWByteAddressBuffer"
+                    | "StructuredBuffer"
+                    | "RWStructuredBuffer"
+                    | "ConstantBuffer"
+            )
+            || word.starts_with("Texture")
+            || word.starts_with("RWTexture")
+    }
+
+    pub(super) fn value_type(&mut self, inner: TypeInner, meta: Span) -> Handle<Type> {
+        self.module.types.insert(Type { name: None, inner }, meta)
+    }
+
+    pub(super) fn array_type(
+        &mut self,
+        base: Handle<Type>,
+        size: ArraySize,
+        meta: Span,
+    ) -> Handle<Type> {
+        self.layouter.update(self.module.to_ctx()).unwrap();
+        let stride = self.layouter[base].to_stride();
+        self.value_type(TypeInner::Array { base, size, stride }, meta)
+    }
+
+    /// Declare `name` as another name for `ty`.
+    pub(super) fn declare_type(
+        &mut self,
+        name: String,
+        ty: Handle<Type>,
+        meta: Span,
+    ) -> Result<()> {
+        match self.types.entry(name) {
+            std::collections::hash_map::Entry::Occupied(entry) => Err(Error::semantic(
+                format!("type `{}` is already declared", entry.key()),
+                meta,
+            )),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(Ty::Value(ty));
+                Ok(())
+            }
+        }
+    }
+
+    /// Evaluate `expr` as a constant `uint`, for array sizes.
+    pub(super) fn const_u32(&mut self, expr: &Expr) -> Result<u32> {
+        let mut ctx = Context::new_const(self);
+        let handle = ctx.lower_rvalue(expr)?;
+        let meta = expr.meta;
+        let value = ctx.module.to_ctx().eval_expr_to_u32(handle);
+        value.map_err(|_| Error::semantic("expected a positive integer constant", meta))
+    }
+}
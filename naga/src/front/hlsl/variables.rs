@@ -0,0 +1,480 @@
+/*!
+Declarations of structs, constants, global variables and resources.
+*/
+
+use super::{
+    ast::{Expr, IoAttributes, Ty},
+    error::{Error, ErrorKind},
+    lower::{Context, ResourceUsage},
+    parser::{Annotations, Attributes, Qualifiers},
+    types::{default_storage_format, scalar_components},
+    Frontend, GlobalSymbol, PendingResource, Result,
+};
+use crate::{
+    AddressSpace, ArraySize, Constant, GlobalVariable, Handle, ImageClass, ResourceBinding, Scalar,
+    ScalarKind, Span, StorageAccess, StorageFormat, StructMember, Type, TypeInner, VectorSize,
+};
+
+impl Frontend {
+    fn declare_symbol(&mut self, name: String, symbol: GlobalSymbol, meta: Span) -> Result<()> {
+        if self.globals.contains_key(&name) {
+            return Err(Error {
+                kind: ErrorKind::VariableAlreadyDeclared(name),
+                meta,
+            });
+        }
+        self.globals.insert(name, symbol);
+        Ok(())
+    }
+
+    /// Build a struct type laid out by naga's rules.
+    fn struct_type(
+        &mut self,
+        name: String,
+        members: impl IntoIterator<Item = (String, Handle<Type>)>,
+        meta: Span,
+    ) -> Handle<Type> {
+        self.layouter.update(self.module.to_ctx()).unwrap();
+        let mut offset = 0;
+        let mut alignment = crate::proc::Alignment::ONE;
+        let members = members
+            .into_iter()
+            .map(|(name, ty)| {
+                let layout = self.layouter[ty];
+                alignment = alignment.max(layout.alignment);
+                let member_offset = layout.alignment.round_up(offset);
+                offset = member_offset + layout.size;
+                StructMember {
+                    name: Some(name),
+                    ty,
+                    binding: None,
+                    offset: member_offset,
+                }
+            })
+            .collect();
+        let span = alignment.round_up(offset);
+        self.module.types.insert(
+            Type {
+                name: Some(name),
+                inner: TypeInner::Struct { members, span },
+            },
+            meta,
+        )
+    }
+
+    pub(super) fn declare_struct(
+        &mut self,
+        name: String,
+        members: Vec<(String, Handle<Type>, IoAttributes, Span)>,
+        meta: Span,
+    ) -> Result<Handle<Type>> {
+        let mut io = Vec::with_capacity(members.len());
+        let mut fields = Vec::with_capacity(members.len());
+        for (member_name, ty, attributes, _) in members {
+            fields.push((member_name, ty));
+            io.push(attributes);
+        }
+        let ty = self.struct_type(name.clone(), fields, meta);
+        self.struct_io.insert(ty, io);
+        self.declare_type(name, ty, meta)?;
+        Ok(ty)
+    }
+
+    /// Fail if a global already uses `binding`, as registers of different
+    /// types would without [`RegisterShifts`](super::RegisterShifts).
+    fn check_binding_unused(
+        &self,
+        name: &str,
+        binding: &ResourceBinding,
+        meta: Span,
+    ) -> Result<()> {
+        let other = self
+            .module
+            .global_variables
+            .iter()
+            .find(|&(_, global)| global.binding.as_ref() == Some(binding));
+        match other {
+            Some((_, global)) => Err(Error::semantic(
+                format!(
+                    "`{name}` and `{}` both use binding {} of group {}",
+                    global.name.as_deref().unwrap_or_default(),
+                    binding.binding,
+                    binding.group
+                ),
+                meta,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Declare a `cbuffer`, whose members are visible as globals.
+    pub(super) fn declare_cbuffer(
+        &mut self,
+        name: String,
+        members: Vec<(String, Handle<Type>, Span)>,
+        binding: ResourceBinding,
+        meta: Span,
+    ) -> Result<()> {
+        let names: Vec<_> = members
+            .iter()
+            .map(|&(ref name, _, meta)| (name.clone(), meta))
+            .collect();
+        let ty = self.struct_type(
+            name.clone(),
+            members.into_iter().map(|(name, ty, _)| (name, ty)),
+            meta,
+        );
+        self.check_cbuffer_layout(ty, meta)?;
+        self.check_binding_unused(&name, &binding, meta)?;
+
+        let handle = self.module.global_variables.append(
+            GlobalVariable {
+                name: Some(name),
+                space: AddressSpace::Uniform,
+                binding: Some(binding),
+                ty,
+                init: None,
+            },
+            meta,
+        );
+        for (index, (name, meta)) in names.into_iter().enumerate() {
+            let symbol = GlobalSymbol::BlockMember {
+                handle,
+                index: index as u32,
+            };
+            self.declare_symbol(name, symbol, meta)?;
+        }
+        Ok(())
+    }
+
+    /// Check that naga lays out `ty` the way HLSL packs constant buffers.
+    ///
+    /// HLSL packs constant buffers into 16-byte registers: vectors can't
+    /// straddle a register boundary, and arrays, structs and matrix columns
+    /// always start a new register. Naga has no way to express this packing
+    /// directly, so rather than silently reading the wrong bytes, buffers
+    /// whose layouts differ are rejected. Padding the members explicitly to
+    /// 16 bytes, as most shaders already do, avoids the problem.
+    fn check_cbuffer_layout(&mut self, ty: Handle<Type>, meta: Span) -> Result<u32> {
+        let inner = self.module.types[ty].inner.clone();
+        Ok(match inner {
+            TypeInner::Scalar(scalar) => scalar.width as u32,
+            TypeInner::Vector { size, scalar } => size as u32 * scalar.width as u32,
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => {
+                if rows == VectorSize::Bi {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("matrices with two rows in a cbuffer"),
+                        meta,
+                    });
+                }
+                (columns as u32 - 1) * 16 + rows as u32 * scalar.width as u32
+            }
+            TypeInner::Array {
+                base,
+                size: ArraySize::Constant(size),
+                stride,
+            } => {
+                let element_size = self.check_cbuffer_layout(base, meta)?;
+                if stride != 16 && !(stride > 16 && stride % 16 == 0) {
+                    return Err(Error::semantic(
+                        "arrays in a cbuffer must have elements padded to 16 bytes, \
+                         like `float4` or a struct ending in one",
+                        meta,
+                    ));
+                }
+                (size.get() - 1) * stride + element_size
+            }
+            TypeInner::Struct { members, .. } => {
+                let mut offset = 0;
+                for member in &members {
+                    let size = self.check_cbuffer_layout(member.ty, meta)?;
+                    let starts_register = matches!(
+                        self.module.types[member.ty].inner,
+                        TypeInner::Array { .. }
+                            | TypeInner::Struct { .. }
+                            | TypeInner::Matrix { .. }
+                    );
+                    let mut packed = offset;
+                    if starts_register || packed / 16 != (packed + size - 1) / 16 {
+                        packed = packed.next_multiple_of(16);
+                    }
+                    if packed != member.offset {
+                        let name = member.name.as_deref().unwrap_or_default();
+                        return Err(Error::semantic(
+                            format!(
+                                "cbuffer member `{name}` is at offset {packed} in HLSL but \
+                                 would be at offset {} here; add explicit padding before it",
+                                member.offset
+                            ),
+                            meta,
+                        ));
+                    }
+                    offset = packed + size;
+                }
+                offset
+            }
+            _ => return Err(Error::semantic("type can't be used in a cbuffer", meta)),
+        })
+    }
+
+    pub(super) fn declare_global(
+        &mut self,
+        name: String,
+        ty: Ty,
+        qualifiers: &Qualifiers,
+        attributes: &Attributes,
+        annotations: Annotations,
+        init: Option<Expr>,
+        meta: Span,
+    ) -> Result<()> {
+        if ty.is_resource() {
+            let binding = attributes.binding.or(annotations.register).ok_or_else(|| {
+                Error::semantic(
+                    format!("resource `{name}` needs an explicit register"),
+                    meta,
+                )
+            })?;
+            self.resources.push(PendingResource {
+                name,
+                ty,
+                binding: Some(binding),
+                format: attributes.format,
+                meta,
+            });
+            return Ok(());
+        }
+        let Ty::Value(ty) = ty else {
+            return Err(Error::semantic("variables can't be void", meta));
+        };
+
+        if qualifiers.groupshared {
+            if init.is_some() {
+                return Err(Error::semantic(
+                    "groupshared variables can't be initialized",
+                    meta,
+                ));
+            }
+            // Whether this is atomic isn't known until the functions are
+            // parsed.
+            self.resources.push(PendingResource {
+                name,
+                ty: Ty::Value(ty),
+                binding: None,
+                format: None,
+                meta,
+            });
+            return Ok(());
+        }
+        if !qualifiers.is_static {
+            return Err(Error {
+                kind: ErrorKind::NotImplemented(
+                    "global variables outside a cbuffer that aren't `static` or `groupshared`",
+                ),
+                meta,
+            });
+        }
+
+        let init = match init {
+            Some(ref init) => {
+                let mut ctx = Context::new_const(self);
+                Some(ctx.lower_as(init, ty)?)
+            }
+            None => None,
+        };
+        let symbol = if qualifiers.is_const {
+            let init =
+                init.ok_or_else(|| Error::semantic("constants must be initialized", meta))?;
+            GlobalSymbol::Constant(self.module.constants.append(
+                Constant {
+                    name: Some(name.clone()),
+                    ty,
+                    init,
+                },
+                meta,
+            ))
+        } else {
+            GlobalSymbol::Variable(self.module.global_variables.append(
+                GlobalVariable {
+                    name: Some(name.clone()),
+                    space: AddressSpace::Private,
+                    binding: None,
+                    ty,
+                    init,
+                },
+                meta,
+            ))
+        };
+        self.declare_symbol(name, symbol, meta)
+    }
+
+    /// Replace the 32-bit integers in `ty` with atomics of the same type.
+    fn atomic_type(&mut self, ty: Handle<Type>, meta: Span) -> Result<Handle<Type>> {
+        match self.module.types[ty].inner {
+            TypeInner::Scalar(
+                scalar @ Scalar {
+                    kind: ScalarKind::Sint | ScalarKind::Uint,
+                    width: 4,
+                },
+            ) => Ok(self.value_type(TypeInner::Atomic(scalar), meta)),
+            TypeInner::Array { base, size, .. } => {
+                let base = self.atomic_type(base, meta)?;
+                Ok(self.array_type(base, size, meta))
+            }
+            _ => Err(Error::semantic(
+                "the destination of an Interlocked operation must be an int or uint",
+                meta,
+            )),
+        }
+    }
+
+    /// The IR type and address space of the resource `name`.
+    pub(super) fn resource_type(
+        &mut self,
+        ty: Ty,
+        name: &str,
+        usage: &ResourceUsage,
+        format: Option<StorageFormat>,
+        meta: Span,
+    ) -> Result<(Handle<Type>, AddressSpace)> {
+        let atomic = usage.atomic.contains(name);
+        let storage = |writable| AddressSpace::Storage {
+            access: if writable {
+                StorageAccess::LOAD | StorageAccess::STORE
+            } else {
+                StorageAccess::LOAD
+            },
+        };
+        let element_scalar = |frontend: &Self, element: Handle<Type>| {
+            scalar_components(&frontend.module.types[element].inner)
+                .ok_or_else(|| Error::semantic("texture elements must be scalars or vectors", meta))
+        };
+
+        Ok(match ty {
+            Ty::Value(ty) if atomic => (self.atomic_type(ty, meta)?, AddressSpace::WorkGroup),
+            Ty::Value(ty) => (ty, AddressSpace::WorkGroup),
+            Ty::Texture {
+                dim,
+                arrayed,
+                multi,
+                element,
+            } => {
+                let scalar = element_scalar(self, element)?;
+                let class = if usage.compared.contains(name) {
+                    if scalar.kind != ScalarKind::Float {
+                        return Err(Error::semantic(
+                            "only float textures can be sampled with a comparison",
+                            meta,
+                        ));
+                    }
+                    ImageClass::Depth { multi }
+                } else {
+                    ImageClass::Sampled {
+                        kind: scalar.kind,
+                        multi,
+                    }
+                };
+                let inner = TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                };
+                (self.value_type(inner, meta), AddressSpace::Handle)
+            }
+            Ty::RwTexture {
+                dim,
+                arrayed,
+                element,
+            } => {
+                let scalar = element_scalar(self, element)?;
+                let format = format
+                    .or_else(|| default_storage_format(scalar))
+                    .ok_or_else(|| {
+                        Error::semantic("unsupported storage texture element type", meta)
+                    })?;
+                let access = match (usage.loaded.contains(name), usage.stored.contains(name)) {
+                    (true, true) => StorageAccess::LOAD | StorageAccess::STORE,
+                    (true, false) => StorageAccess::LOAD,
+                    (false, _) => StorageAccess::STORE,
+                };
+                let inner = TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class: ImageClass::Storage { format, access },
+                };
+                (self.value_type(inner, meta), AddressSpace::Handle)
+            }
+            Ty::Sampler { comparison } => (
+                self.value_type(TypeInner::Sampler { comparison }, meta),
+                AddressSpace::Handle,
+            ),
+            // Structured buffers are laid out by naga's rules for storage
+            // buffers, which match the std430 layout DXC uses for Vulkan.
+            Ty::StructuredBuffer { element, writable } => {
+                let element = if atomic && writable {
+                    self.atomic_type(element, meta)?
+                } else {
+                    element
+                };
+                let array = self.array_type(element, ArraySize::Dynamic, meta);
+                (array, storage(writable))
+            }
+            Ty::ByteAddressBuffer { writable } => {
+                let word = self.value_type(TypeInner::Scalar(Scalar::U32), meta);
+                let word = if atomic && writable {
+                    self.atomic_type(word, meta)?
+                } else {
+                    word
+                };
+                let array = self.array_type(word, ArraySize::Dynamic, meta);
+                (array, storage(writable))
+            }
+            Ty::ConstantBuffer { element } => {
+                if !matches!(self.module.types[element].inner, TypeInner::Struct { .. }) {
+                    return Err(Error::semantic(
+                        "the element of a ConstantBuffer must be a struct",
+                        meta,
+                    ));
+                }
+                self.check_cbuffer_layout(element, meta)?;
+                (element, AddressSpace::Uniform)
+            }
+            Ty::Void => return Err(Error::semantic("variables can't be void", meta)),
+        })
+    }
+
+    /// Declare the globals whose types depend on how the functions use them.
+    pub(super) fn declare_resources(&mut self, usage: &ResourceUsage) -> Result<()> {
+        for resource in std::mem::take(&mut self.resources) {
+            if let Some(ref binding) = resource.binding {
+                self.check_binding_unused(&resource.name, binding, resource.meta)?;
+            }
+            let (ty, space) = self.resource_type(
+                resource.ty,
+                &resource.name,
+                usage,
+                resource.format,
+                resource.meta,
+            )?;
+            let handle = self.module.global_variables.append(
+                GlobalVariable {
+                    name: Some(resource.name.clone()),
+                    space,
+                    binding: resource.binding,
+                    ty,
+                    init: None,
+                },
+                resource.meta,
+            );
+            let symbol = match resource.ty {
+                Ty::Value(_) => GlobalSymbol::Variable(handle),
+                ty => GlobalSymbol::Resource { handle, ty },
+            };
+            self.declare_symbol(resource.name, symbol, resource.meta)?;
+        }
+        Ok(())
+    }
+}
@@ -23,8 +23,9 @@ impl crate::Binding {
     /// - If `ty` is a floating-point scalar, vector, or matrix type, then
     ///   default to [`Perspective`] interpolation and [`Center`] sampling.
     ///
-    /// - If `ty` is an integral scalar or vector, then default to [`Flat`]
-    ///   interpolation, which has no associated sampling.
+    /// - If `ty` is an integral scalar or vector, or the binding is
+    ///   [`per_primitive`], then default to [`Flat`] interpolation, which has
+    ///   no associated sampling.
     ///
     /// - For any other types, make no change. Such types are not permitted as
     ///   user-defined IO values, and will probably be flagged by the verifier
@@ -35,6 +36,7 @@ impl crate::Binding {
     /// [`Binding`]: crate::Binding
     /// [`Location`]: crate::Binding::Location
     /// [`interpolation`]: crate::Binding::Location::interpolation
+    /// [`per_primitive`]: crate::Binding::Location::per_primitive
     /// [`Perspective`]: crate::Interpolation::Perspective
     /// [`Flat`]: crate::Interpolation::Flat
     /// [`Center`]: crate::Sampling::Center
@@ -44,14 +46,17 @@ impl crate::Binding {
             interpolation: ref mut interpolation @ None,
             ref mut sampling,
             second_blend_source: _,
+            per_primitive,
         } = *self
         {
             match ty.scalar_kind() {
-                Some(crate::ScalarKind::Float) => {
+                Some(crate::ScalarKind::Float) if !per_primitive => {
                     *interpolation = Some(crate::Interpolation::Perspective);
                     *sampling = Some(crate::Sampling::Center);
                 }
-                Some(crate::ScalarKind::Sint | crate::ScalarKind::Uint) => {
+                Some(
+                    crate::ScalarKind::Float | crate::ScalarKind::Sint | crate::ScalarKind::Uint,
+                ) => {
                     *interpolation = Some(crate::Interpolation::Flat);
                     *sampling = None;
                 }
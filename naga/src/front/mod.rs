@@ -7,6 +7,8 @@ mod type_gen;
 
 #[cfg(feature = "glsl-in")]
 pub mod glsl;
+#[cfg(feature = "hlsl-in")]
+pub mod hlsl;
 #[cfg(feature = "spv-in")]
 pub mod spv;
 #[cfg(feature = "wgsl-in")]
@@ -124,6 +124,8 @@ pub enum Error {
     InvalidArraySize(spirv::Word),
     #[error("invalid barrier scope %{0}")]
     InvalidBarrierScope(spirv::Word),
+    #[error("invalid cluster size %{0}")]
+    InvalidClusterSize(spirv::Word),
     #[error("invalid barrier memory semantics %{0}")]
     InvalidBarrierMemorySemantics(spirv::Word),
     #[error(
@@ -655,6 +655,7 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             offset,
             level,
             depth_ref,
+            residency: false,
         };
         self.lookup_expression.insert(
             result_id,
@@ -172,7 +172,7 @@ bitflags::bitflags! {
 
 impl DecorationFlags {
     fn to_storage_access(self) -> crate::StorageAccess {
-        let mut access = crate::StorageAccess::all();
+        let mut access = crate::StorageAccess::LOAD | crate::StorageAccess::STORE;
         if self.contains(DecorationFlags::NON_READABLE) {
             access &= !crate::StorageAccess::LOAD;
         }
@@ -246,6 +246,7 @@ impl Decoration {
                 interpolation,
                 sampling,
                 second_blend_source: false,
+                per_primitive: false,
             }),
             _ => Err(Error::MissingDecoration(spirv::Decoration::Location)),
         }
@@ -567,6 +568,8 @@ enum SignAnchor {
 pub struct Frontend<I> {
     data: I,
     data_offset: usize,
+    /// The byte range of the instruction being parsed, for error reporting.
+    instruction_span: crate::Span,
     state: ModuleState,
     layouter: Layouter,
     temp_bytes: Vec<u8>,
@@ -623,6 +626,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Frontend {
             data,
             data_offset: 0,
+            instruction_span: crate::Span::default(),
             state: ModuleState::Empty,
             layouter: Layouter::default(),
             temp_bytes: Vec::new(),
@@ -670,8 +674,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
     }
 
     fn next_inst(&mut self) -> Result<Instruction, Error> {
+        let start = self.data_offset;
         let word = self.next()?;
         let (wc, opcode) = ((word >> 16) as u16, (word & 0xffff) as u16);
+        self.instruction_span = crate::Span::from(start..start + 4 * (wc.max(1) as usize));
         if wc == 0 {
             return Err(Error::InvalidWordCount);
         }
@@ -1230,8 +1236,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             crate::TypeInner::Array { size, .. } => {
                 let size = match size {
                     crate::ArraySize::Constant(size) => size.get(),
-                    // A runtime sized array is not a composite type
-                    crate::ArraySize::Dynamic => {
+                    // A runtime sized array is not a composite type, and the
+                    // SPIR-V front end never produces override-sized arrays
+                    crate::ArraySize::Pending(_) | crate::ArraySize::Dynamic => {
                         return Err(Error::InvalidAccessType(root_type_id))
                     }
                 };
@@ -3773,37 +3780,42 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 | Op::GroupNonUniformLogicalOr
                 | Op::GroupNonUniformLogicalXor => {
                     block.extend(emitter.finish(ctx.expressions));
-                    inst.expect(
-                        if matches!(inst.op, Op::GroupNonUniformAll | Op::GroupNonUniformAny) {
-                            5
-                        } else {
-                            6
-                        },
-                    )?;
+                    let is_vote =
+                        matches!(inst.op, Op::GroupNonUniformAll | Op::GroupNonUniformAny);
+                    let extra = inst.expect_at_least(if is_vote { 5 } else { 6 })?;
                     let result_type_id = self.next()?;
                     let result_id = self.next()?;
                     let exec_scope_id = self.next()?;
-                    let collective_op_id = match inst.op {
-                        Op::GroupNonUniformAll | Op::GroupNonUniformAny => {
+                    let group_op_id = if is_vote { None } else { Some(self.next()?) };
+                    let argument_id = self.next()?;
+                    let collective_op_id = match group_op_id {
+                        None => {
+                            inst.expect(5)?;
                             crate::CollectiveOperation::Reduce
                         }
-                        _ => {
-                            let group_op_id = self.next()?;
-                            match spirv::GroupOperation::from_u32(group_op_id) {
-                                Some(spirv::GroupOperation::Reduce) => {
-                                    crate::CollectiveOperation::Reduce
-                                }
-                                Some(spirv::GroupOperation::InclusiveScan) => {
-                                    crate::CollectiveOperation::InclusiveScan
-                                }
-                                Some(spirv::GroupOperation::ExclusiveScan) => {
-                                    crate::CollectiveOperation::ExclusiveScan
-                                }
-                                _ => return Err(Error::UnsupportedGroupOperation(group_op_id)),
+                        Some(group_op_id) => match spirv::GroupOperation::from_u32(group_op_id) {
+                            Some(spirv::GroupOperation::Reduce) if extra == 0 => {
+                                crate::CollectiveOperation::Reduce
                             }
-                        }
+                            Some(spirv::GroupOperation::InclusiveScan) if extra == 0 => {
+                                crate::CollectiveOperation::InclusiveScan
+                            }
+                            Some(spirv::GroupOperation::ExclusiveScan) if extra == 0 => {
+                                crate::CollectiveOperation::ExclusiveScan
+                            }
+                            Some(spirv::GroupOperation::ClusteredReduce) if extra == 1 => {
+                                let cluster_size_id = self.next()?;
+                                let cluster_size_const =
+                                    self.lookup_constant.lookup(cluster_size_id)?;
+                                let cluster_size =
+                                    resolve_constant(ctx.gctx(), &cluster_size_const.inner)
+                                        .ok_or(Error::InvalidClusterSize(cluster_size_id))?;
+                                crate::CollectiveOperation::ClusteredReduce(cluster_size)
+                            }
+                            Some(_) => return Err(Error::InvalidOperandCount(inst.op, inst.wc)),
+                            None => return Err(Error::UnsupportedGroupOperation(group_op_id)),
+                        },
                     };
-                    let argument_id = self.next()?;
 
                     let argument_lookup = self.lookup_expression.lookup(argument_id)?;
                     let argument_handle = get_expr_handle!(argument_id, argument_lookup);
@@ -3873,7 +3885,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 | Op::GroupNonUniformShuffle
                 | Op::GroupNonUniformShuffleDown
                 | Op::GroupNonUniformShuffleUp
-                | Op::GroupNonUniformShuffleXor => {
+                | Op::GroupNonUniformShuffleXor
+                | Op::GroupNonUniformQuadBroadcast
+                | Op::GroupNonUniformQuadSwap => {
                     inst.expect(if matches!(inst.op, Op::GroupNonUniformBroadcastFirst) {
                         5
                     } else {
@@ -3895,6 +3909,16 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
 
                     let mode = if matches!(inst.op, Op::GroupNonUniformBroadcastFirst) {
                         crate::GatherMode::BroadcastFirst
+                    } else if matches!(inst.op, Op::GroupNonUniformQuadSwap) {
+                        let direction_id = self.next()?;
+                        let direction_const = self.lookup_constant.lookup(direction_id)?;
+                        let direction = match resolve_constant(ctx.gctx(), &direction_const.inner) {
+                            Some(0) => crate::Direction::X,
+                            Some(1) => crate::Direction::Y,
+                            Some(2) => crate::Direction::Diagonal,
+                            _ => return Err(Error::InvalidOperand),
+                        };
+                        crate::GatherMode::QuadSwap(direction)
                     } else {
                         let index_id = self.next()?;
                         let index_lookup = self.lookup_expression.lookup(index_id)?;
@@ -3913,6 +3937,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                             Op::GroupNonUniformShuffleXor => {
                                 crate::GatherMode::ShuffleXor(index_handle)
                             }
+                            Op::GroupNonUniformQuadBroadcast => {
+                                crate::GatherMode::QuadBroadcast(index_handle)
+                            }
                             _ => unreachable!(),
                         }
                     };
@@ -4067,8 +4094,11 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 | S::Barrier(_)
                 | S::Store { .. }
                 | S::ImageStore { .. }
+                | S::ImageAtomic { .. }
                 | S::Atomic { .. }
                 | S::RayQuery { .. }
+                | S::RayPipelineFunction(_)
+                | S::MeshFunction(_)
                 | S::SubgroupBallot { .. }
                 | S::SubgroupCollectiveOperation { .. }
                 | S::SubgroupGather { .. } => {}
@@ -4145,7 +4175,20 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
-    pub fn parse(mut self) -> Result<crate::Module, Error> {
+    pub fn parse(self) -> Result<crate::Module, Error> {
+        self.parse_with_span().map_err(crate::WithSpan::into_inner)
+    }
+
+    /// Like [`parse`](Self::parse), but errors found while parsing an
+    /// instruction carry its span, as a byte range into the SPIR-V binary.
+    pub fn parse_with_span(mut self) -> Result<crate::Module, crate::WithSpan<Error>> {
+        self.parse_module().map_err(|error| {
+            crate::WithSpan::new(error)
+                .with_span(self.instruction_span, "while parsing this instruction")
+        })
+    }
+
+    fn parse_module(&mut self) -> Result<crate::Module, Error> {
         let mut module = {
             if self.next()? != spirv::MAGIC_NUMBER {
                 return Err(Error::InvalidHeader);
@@ -4222,6 +4265,8 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 _ => Err(Error::UnsupportedInstruction(self.state, inst.op)), //TODO
             }?;
         }
+        // The remaining errors aren't about a single instruction.
+        self.instruction_span = crate::Span::default();
 
         // Do entry point specific processing after all functions are parsed so that we can
         // cull unused problematic builtins of gl_PerVertex.
@@ -5594,4 +5639,20 @@ mod test {
         ];
         let _ = super::parse_u8_slice(&bin, &Default::default()).unwrap();
     }
+
+    #[test]
+    fn instruction_span() {
+        let words = [
+            // Magic number, version 1.0, generator, bound, reserved word.
+            0x07230203, 0x00010000, 0, 0, 0,
+            // OpMemoryModel with a bogus word count of 4.
+            0x0004000e, 0, 1, 0,
+        ];
+        let options = Default::default();
+        let err = super::Frontend::new(words.into_iter(), &options)
+            .parse_with_span()
+            .unwrap_err();
+        let spans: Vec<_> = err.spans().map(|&(span, _)| span).collect();
+        assert_eq!(spans, [crate::Span::new(20, 36)]);
+    }
 }
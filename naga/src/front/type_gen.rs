@@ -426,6 +426,46 @@ impl crate::Module {
                     },
                 }
             }
+            crate::PredeclaredType::SparseSampleResult => {
+                let texel_ty = self.types.insert(
+                    crate::Type {
+                        name: None,
+                        inner: crate::TypeInner::Vector {
+                            size: crate::VectorSize::Quad,
+                            scalar: crate::Scalar::F32,
+                        },
+                    },
+                    Span::UNDEFINED,
+                );
+                let bool_ty = self.types.insert(
+                    crate::Type {
+                        name: None,
+                        inner: crate::TypeInner::Scalar(crate::Scalar::BOOL),
+                    },
+                    Span::UNDEFINED,
+                );
+
+                crate::Type {
+                    name: Some("__sparse_sample_result".to_string()),
+                    inner: crate::TypeInner::Struct {
+                        members: vec![
+                            crate::StructMember {
+                                name: Some("texel".to_string()),
+                                ty: texel_ty,
+                                binding: None,
+                                offset: 0,
+                            },
+                            crate::StructMember {
+                                name: Some("resident".to_string()),
+                                ty: bool_ty,
+                                binding: None,
+                                offset: 16,
+                            },
+                        ],
+                        span: 32,
+                    },
+                }
+            }
         };
 
         let handle = self.types.insert(ty, Span::UNDEFINED);
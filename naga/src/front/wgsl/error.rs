@@ -12,10 +12,10 @@ use thiserror::Error;
 
 #[derive(Clone, Debug)]
 pub struct ParseError {
-    message: String,
+    pub(super) message: String,
     // The first span should be the primary span, and the other ones should be complementary.
-    labels: Vec<(Span, Cow<'static, str>)>,
-    notes: Vec<String>,
+    pub(super) labels: Vec<(Span, Cow<'static, str>)>,
+    pub(super) notes: Vec<String>,
 }
 
 impl ParseError {
@@ -29,6 +29,11 @@ impl ParseError {
         &self.message
     }
 
+    /// Additional notes that accompany the error message.
+    pub fn notes(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.notes.iter().map(String::as_str)
+    }
+
     fn diagnostic(&self) -> Diagnostic<()> {
         let diagnostic = Diagnostic::error()
             .with_message(self.message.to_string())
@@ -251,6 +256,7 @@ pub enum Error<'a> {
     ExpectedNonNegative(Span),
     ExpectedPositiveArrayLength(Span),
     MissingWorkgroupSize(Span),
+    MissingMeshOutput(&'static str, Span),
     ConstantEvaluatorError(ConstantEvaluatorError, Span),
     AutoConversion {
         dest_span: Span,
@@ -736,6 +742,14 @@ impl<'a> Error<'a> {
                 )],
                 notes: vec![],
             },
+            Error::MissingMeshOutput(attribute, span) => ParseError {
+                message: format!("`@{attribute}` is missing on mesh shader entry point"),
+                labels: vec![(
+                    span,
+                    format!("must be paired with a @{attribute} attribute").into(),
+                )],
+                notes: vec![],
+            },
             Error::AutoConversion { dest_span, ref dest_type, source_span, ref source_type } => ParseError {
                 message: format!("automatic conversions cannot convert `{source_type}` to `{dest_type}`"),
                 labels: vec![
@@ -0,0 +1,345 @@
+/*!
+Resolution of `#include` directives in WGSL source.
+
+WGSL has no notion of modules, so projects spread over several files
+typically splice them together before handing the result to the parser.
+[`ExpandedSource`] does this for directives of the form:
+
+```text
+// #include "path/to/file.wgsl"
+```
+
+Since the directive is a line comment, a file using it is still valid WGSL on
+its own. Each path is included at most once, so shared files may be included
+from several places and cycles are harmless.
+
+The expanded source keeps track of where each byte came from, so that
+[`ParseError`]s produced while parsing it can be reported against the file
+that actually contains the offending code.
+*/
+
+use super::ParseError;
+use crate::{Span, WithSpan};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// One of the files making up an [`ExpandedSource`].
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    /// The name of the file, as written in the `#include` directive.
+    ///
+    /// For the root file, this is the name given to [`ExpandedSource::new`].
+    pub name: String,
+    /// The contents of the file.
+    pub source: String,
+}
+
+/// A [`ParseError`] whose spans refer to a single [`SourceFile`].
+#[derive(Clone, Debug)]
+pub struct FileParseError {
+    /// The file the error's spans refer to.
+    pub file: SourceFile,
+    /// The error itself.
+    pub error: ParseError,
+}
+
+impl FileParseError {
+    /// Emits a summary of the error to standard error stream.
+    pub fn emit_to_stderr(&self) {
+        self.error
+            .emit_to_stderr_with_path(&self.file.source, &self.file.name)
+    }
+
+    /// Emits a summary of the error to a string.
+    pub fn emit_to_string(&self) -> String {
+        self.error
+            .emit_to_string_with_path(&self.file.source, &self.file.name)
+    }
+}
+
+impl std::fmt::Display for FileParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for FileParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A contiguous run of bytes copied from one file into the expanded source.
+#[derive(Clone, Debug)]
+struct Segment {
+    /// The offset of the run in the expanded source.
+    start: usize,
+    /// The offset of the run in the file it was copied from.
+    file_start: usize,
+    len: usize,
+    /// The index of the file in [`ExpandedSource::files`].
+    file: usize,
+}
+
+/// WGSL source with all its `#include` directives replaced by the contents of
+/// the files they name.
+#[derive(Clone, Debug)]
+pub struct ExpandedSource {
+    source: String,
+    /// All files that went into `source`. The root file comes first.
+    files: Vec<SourceFile>,
+    /// The origin of every byte in `source`, in order.
+    segments: Vec<Segment>,
+}
+
+impl ExpandedSource {
+    /// Expand the `#include` directives in `source`.
+    ///
+    /// The contents of each included path are obtained by calling `resolve`,
+    /// which returns either the file's source or a message explaining why it
+    /// couldn't be loaded. Paths are passed to `resolve` exactly as written in
+    /// the directive, and each distinct path is resolved only once.
+    ///
+    /// `name` identifies the root file in error messages.
+    pub fn new<F>(name: &str, source: &str, mut resolve: F) -> Result<Self, FileParseError>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        let mut expanded = Self {
+            source: String::with_capacity(source.len()),
+            files: vec![SourceFile {
+                name: name.to_string(),
+                source: source.to_string(),
+            }],
+            segments: Vec::new(),
+        };
+        expanded.expand_file(0, &mut resolve)?;
+        Ok(expanded)
+    }
+
+    /// The expanded source, ready to be parsed.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// All the files that were spliced into the expanded source. The root
+    /// file comes first.
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Map `span` in the expanded source back to the file it came from.
+    ///
+    /// Returns the index of the file in [`files`] and the corresponding span
+    /// within it. A span that straddles several files is truncated at the end
+    /// of the first one.
+    ///
+    /// [`files`]: ExpandedSource::files
+    pub fn locate(&self, span: Span) -> Option<(usize, Span)> {
+        let Range { start, end } = span.to_range()?;
+        // Find the last segment starting at or before `start`. A span may
+        // start right at the end of a segment, for example at the end of
+        // the source.
+        let index = self
+            .segments
+            .partition_point(|segment| segment.start <= start)
+            .checked_sub(1)?;
+        let segment = &self.segments[index];
+        let offset = start - segment.start;
+        if offset > segment.len {
+            return None;
+        }
+        let len = (end - start).min(segment.len - offset);
+        let file_start = segment.file_start + offset;
+        Some((
+            segment.file,
+            Span::new(file_start as u32, (file_start + len) as u32),
+        ))
+    }
+
+    /// Convert `error`, produced by parsing [`source`], into an error
+    /// referring to the file that contains its primary span.
+    ///
+    /// Labels pointing into other files are turned into notes.
+    ///
+    /// [`source`]: ExpandedSource::source
+    pub fn locate_error(&self, error: ParseError) -> FileParseError {
+        let file = error
+            .labels
+            .first()
+            .and_then(|&(span, _)| self.locate(span))
+            .map_or(0, |(file, _)| file);
+
+        let mut labels = Vec::with_capacity(error.labels.len());
+        let mut notes = Vec::new();
+        for (span, message) in error.labels {
+            match self.locate(span) {
+                Some((label_file, span)) if label_file == file => labels.push((span, message)),
+                Some((label_file, span)) => {
+                    let other = &self.files[label_file];
+                    let location = span.location(&other.source);
+                    notes.push(format!(
+                        "{message} at {}:{}:{}",
+                        other.name, location.line_number, location.line_position
+                    ));
+                }
+                None => labels.push((Span::UNDEFINED, message)),
+            }
+        }
+        notes.extend(error.notes);
+        if file != 0 {
+            notes.push(format!("in file included as `{}`", self.files[file].name));
+        }
+
+        FileParseError {
+            file: self.files[file].clone(),
+            error: ParseError {
+                message: error.message,
+                labels,
+                notes,
+            },
+        }
+    }
+
+    /// Convert `error`, whose spans refer to [`source`], into an error whose
+    /// spans refer to the file containing its first span.
+    ///
+    /// This is the counterpart of [`locate_error`] for errors produced after
+    /// parsing, such as validation errors. Returns the index of the file in
+    /// [`files`] along with the converted error. Spans pointing into other
+    /// files are dropped. If the file is an included one, the description of
+    /// the first span names it.
+    ///
+    /// [`source`]: ExpandedSource::source
+    /// [`locate_error`]: ExpandedSource::locate_error
+    /// [`files`]: ExpandedSource::files
+    pub fn locate_spans<E>(&self, error: WithSpan<E>) -> (usize, WithSpan<E>) {
+        let spans: Vec<_> = error
+            .spans()
+            .filter_map(|&(span, ref description)| {
+                let (file, span) = self.locate(span)?;
+                Some((file, span, description.clone()))
+            })
+            .collect();
+        let file = spans.first().map_or(0, |&(file, _, _)| file);
+
+        let mut located = WithSpan::new(error.into_inner());
+        for (index, (span_file, span, description)) in spans.into_iter().enumerate() {
+            if span_file != file {
+                continue;
+            }
+            located = if index == 0 && file != 0 {
+                let name = &self.files[file].name;
+                located.with_span(span, format!("{description} in file included as `{name}`"))
+            } else {
+                located.with_span(span, description)
+            };
+        }
+        (file, located)
+    }
+
+    fn expand_file<F>(&mut self, file: usize, resolve: &mut F) -> Result<(), FileParseError>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        // Included files are pushed onto `self.files` as we go, so take our
+        // own copy of the text to walk over.
+        let source = self.files[file].source.clone();
+
+        let mut copied = 0;
+        let mut line_start = 0;
+        for line in source.split_inclusive('\n') {
+            let line_end = line_start + line.len();
+            let directive = parse_directive(line);
+            let line_range = line_start..line_end;
+            line_start = line_end;
+
+            let path = match directive {
+                None => continue,
+                Some(Ok(path)) => path,
+                Some(Err(label)) => {
+                    return Err(self.error(
+                        file,
+                        line_range,
+                        "invalid `#include` directive",
+                        label.into(),
+                    ));
+                }
+            };
+
+            self.copy(file, copied..line_range.start);
+            copied = line_range.end;
+
+            if self.files.iter().any(|included| included.name == path) {
+                continue;
+            }
+            let contents = resolve(path).map_err(|message| {
+                self.error(
+                    file,
+                    line_range.clone(),
+                    &format!("failed to resolve include `{path}`"),
+                    message.into(),
+                )
+            })?;
+            self.files.push(SourceFile {
+                name: path.to_string(),
+                source: contents,
+            });
+            self.expand_file(self.files.len() - 1, resolve)?;
+            if !self.source.is_empty() && !self.source.ends_with('\n') {
+                self.source.push('\n');
+            }
+        }
+        self.copy(file, copied..source.len());
+
+        Ok(())
+    }
+
+    /// Append `range` of `file` to the expanded source.
+    fn copy(&mut self, file: usize, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        self.segments.push(Segment {
+            start: self.source.len(),
+            file_start: range.start,
+            len: range.len(),
+            file,
+        });
+        self.source.push_str(&self.files[file].source[range]);
+    }
+
+    fn error(
+        &self,
+        file: usize,
+        line: Range<usize>,
+        message: &str,
+        label: Cow<'static, str>,
+    ) -> FileParseError {
+        let file = self.files[file].clone();
+        let end = line.start + file.source[line.clone()].trim_end().len();
+        FileParseError {
+            error: ParseError {
+                message: message.to_string(),
+                labels: vec![(Span::new(line.start as u32, end as u32), label)],
+                notes: Vec::new(),
+            },
+            file,
+        }
+    }
+}
+
+/// If `line` is an `#include` directive, return the path it names.
+fn parse_directive(line: &str) -> Option<Result<&str, &'static str>> {
+    let rest = line
+        .trim()
+        .strip_prefix("//")?
+        .trim_start()
+        .strip_prefix("#include")?;
+    let path = rest
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|path| !path.is_empty() && !path.contains('"'));
+    Some(path.ok_or("expected a quoted path"))
+}
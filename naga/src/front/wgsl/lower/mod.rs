@@ -818,6 +818,12 @@ impl Components {
     }
 }
 
+/// A lowered `@workgroup_size` dimension.
+enum WorkgroupSizeDim {
+    Constant(u32),
+    Override(Handle<crate::Expression>),
+}
+
 /// An `ast::GlobalDecl` for which we have built the Naga IR equivalent.
 enum LoweredGlobalDecl {
     Function(Handle<crate::Function>),
@@ -842,7 +848,21 @@ enum Texture {
 }
 
 impl Texture {
-    pub fn map(word: &str) -> Option<Self> {
+    /// Map a texture builtin's name to the function, and whether it is the
+    /// `Residency` variant that also reports whether the texels are resident.
+    pub fn map(word: &str) -> Option<(Self, bool)> {
+        if let Some(word) = word.strip_suffix("Residency") {
+            return match Self::map_plain(word)? {
+                fun @ (Self::Sample | Self::SampleBias | Self::SampleGrad | Self::SampleLevel) => {
+                    Some((fun, true))
+                }
+                _ => None,
+            };
+        }
+        Self::map_plain(word).map(|fun| (fun, false))
+    }
+
+    fn map_plain(word: &str) -> Option<Self> {
         Some(match word {
             "textureGather" => Self::Gather,
             "textureGatherCompare" => Self::GatherCompare,
@@ -881,6 +901,10 @@ enum SubgroupGather {
     ShuffleDown,
     ShuffleUp,
     ShuffleXor,
+    QuadBroadcast,
+    QuadSwapX,
+    QuadSwapY,
+    QuadSwapDiagonal,
 }
 
 impl SubgroupGather {
@@ -892,6 +916,10 @@ impl SubgroupGather {
             "subgroupShuffleDown" => Self::ShuffleDown,
             "subgroupShuffleUp" => Self::ShuffleUp,
             "subgroupShuffleXor" => Self::ShuffleXor,
+            "quadBroadcast" => Self::QuadBroadcast,
+            "quadSwapX" => Self::QuadSwapX,
+            "quadSwapY" => Self::QuadSwapY,
+            "quadSwapDiagonal" => Self::QuadSwapDiagonal,
             _ => return None,
         })
     }
@@ -1188,24 +1216,75 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             .collect();
 
         if let Some(ref entry) = f.entry_point {
+            let mut workgroup_size_overrides = None;
             let workgroup_size = if let Some(workgroup_size) = entry.workgroup_size {
                 // TODO: replace with try_map once stabilized
                 let mut workgroup_size_out = [1; 3];
+                let mut overrides_out = [None; 3];
                 for (i, size) in workgroup_size.into_iter().enumerate() {
                     if let Some(size_expr) = size {
-                        workgroup_size_out[i] = self.const_u32(size_expr, &mut ctx.as_const())?.0;
+                        match self.workgroup_size_dim(size_expr, ctx)? {
+                            WorkgroupSizeDim::Constant(size) => workgroup_size_out[i] = size,
+                            WorkgroupSizeDim::Override(expr) => {
+                                workgroup_size_out[i] = 0;
+                                overrides_out[i] = Some(expr);
+                            }
+                        }
                     }
                 }
+                if overrides_out.iter().any(Option::is_some) {
+                    workgroup_size_overrides = Some(overrides_out);
+                }
                 workgroup_size_out
             } else {
                 [0; 3]
             };
 
+            let mesh_info = if let Some(ref mesh_output) = entry.mesh_output {
+                let (vertex_ty, max_vertices) = mesh_output.vertex_output;
+                let (primitive_ty, max_primitives) = mesh_output.primitive_output;
+                let vertex_output = self.resolve_ast_type(vertex_ty, ctx)?;
+                let primitive_output = self.resolve_ast_type(primitive_ty, ctx)?;
+                let max_vertices = self.const_u32(max_vertices, &mut ctx.as_const())?.0;
+                let max_primitives = self.const_u32(max_primitives, &mut ctx.as_const())?.0;
+
+                // The topology follows from the kind of vertex indices each
+                // primitive holds. If there are none, the validator complains.
+                let mut topology = crate::MeshOutputTopology::Triangles;
+                if let crate::TypeInner::Struct { ref members, .. } =
+                    ctx.module.types[primitive_output].inner
+                {
+                    for member in members {
+                        match member.binding {
+                            Some(crate::Binding::BuiltIn(crate::BuiltIn::LineIndices)) => {
+                                topology = crate::MeshOutputTopology::Lines;
+                            }
+                            Some(crate::Binding::BuiltIn(crate::BuiltIn::PointIndex)) => {
+                                topology = crate::MeshOutputTopology::Points;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                Some(crate::MeshStageInfo {
+                    topology,
+                    max_vertices,
+                    max_primitives,
+                    vertex_output,
+                    primitive_output,
+                })
+            } else {
+                None
+            };
+
             ctx.module.entry_points.push(crate::EntryPoint {
                 name: f.name.name.to_string(),
                 stage: entry.stage,
                 early_depth_test: entry.early_depth_test,
                 workgroup_size,
+                workgroup_size_overrides,
+                mesh_info,
                 function,
             });
             Ok(LoweredGlobalDecl::EntryPoint)
@@ -2075,18 +2154,25 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                         arg2,
                         arg3,
                     }
-                } else if let Some(fun) = Texture::map(function.name) {
-                    self.texture_sample_helper(fun, arguments, span, ctx)?
+                } else if let Some((fun, residency)) = Texture::map(function.name) {
+                    self.texture_sample_helper(fun, residency, arguments, span, ctx)?
                 } else if let Some((op, cop)) = conv::map_subgroup_operation(function.name) {
                     return Ok(Some(
                         self.subgroup_operation_helper(span, op, cop, arguments, ctx)?,
                     ));
+                } else if let Some(op) = conv::map_subgroup_clustered_operation(function.name) {
+                    return Ok(Some(
+                        self.subgroup_clustered_operation_helper(span, op, arguments, ctx)?,
+                    ));
                 } else if let Some(mode) = SubgroupGather::map(function.name) {
                     return Ok(Some(
                         self.subgroup_gather_helper(span, mode, arguments, ctx)?,
                     ));
                 } else if let Some(fun) = crate::AtomicFunction::map(function.name) {
                     return Ok(Some(self.atomic_helper(span, fun, arguments, ctx)?));
+                } else if let Some(fun) = conv::map_texture_atomic_fun(function.name) {
+                    self.texture_atomic_helper(span, fun, arguments, ctx)?;
+                    return Ok(None);
                 } else {
                     match function.name {
                         "select" => {
@@ -2394,6 +2480,112 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                                 committed: true,
                             }
                         }
+                        "traceRay" => {
+                            let mut args = ctx.prepare_args(arguments, 6, span);
+                            let acceleration_structure = self.expression(args.next()?, ctx)?;
+                            let descriptor = self.expression(args.next()?, ctx)?;
+                            let sbt_record_offset = self.expression(args.next()?, ctx)?;
+                            let sbt_record_stride = self.expression(args.next()?, ctx)?;
+                            let miss_index = self.expression(args.next()?, ctx)?;
+                            let payload = self.expression(args.next()?, ctx)?;
+                            args.finish()?;
+
+                            let _ = ctx.module.generate_ray_desc_type();
+                            let fun = crate::RayPipelineFunction::TraceRay {
+                                acceleration_structure,
+                                descriptor,
+                                sbt_record_offset,
+                                sbt_record_stride,
+                                miss_index,
+                                payload,
+                            };
+                            self.push_stage_statement(
+                                crate::Statement::RayPipelineFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
+                        "reportIntersection" => {
+                            let mut args = ctx.prepare_args(arguments, 2, span);
+                            let hit_t = self.expression(args.next()?, ctx)?;
+                            let hit_kind = self.expression(args.next()?, ctx)?;
+                            args.finish()?;
+
+                            let fun =
+                                crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind };
+                            self.push_stage_statement(
+                                crate::Statement::RayPipelineFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
+                        "ignoreIntersection" | "terminateRay" => {
+                            ctx.prepare_args(arguments, 0, span).finish()?;
+
+                            let fun = if function.name == "ignoreIntersection" {
+                                crate::RayPipelineFunction::IgnoreIntersection
+                            } else {
+                                crate::RayPipelineFunction::TerminateRay
+                            };
+                            self.push_stage_statement(
+                                crate::Statement::RayPipelineFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
+                        "emitMeshTasks" => {
+                            let mut args = ctx.prepare_args(arguments, 3, span);
+                            let x = self.expression(args.next()?, ctx)?;
+                            let y = self.expression(args.next()?, ctx)?;
+                            let z = self.expression(args.next()?, ctx)?;
+                            args.finish()?;
+
+                            let fun = crate::MeshFunction::EmitMeshTasks { x, y, z };
+                            self.push_stage_statement(
+                                crate::Statement::MeshFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
+                        "setMeshOutputs" => {
+                            let mut args = ctx.prepare_args(arguments, 2, span);
+                            let vertex_count = self.expression(args.next()?, ctx)?;
+                            let primitive_count = self.expression(args.next()?, ctx)?;
+                            args.finish()?;
+
+                            let fun = crate::MeshFunction::SetMeshOutputs {
+                                vertex_count,
+                                primitive_count,
+                            };
+                            self.push_stage_statement(
+                                crate::Statement::MeshFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
+                        "setVertex" | "setPrimitive" => {
+                            let mut args = ctx.prepare_args(arguments, 2, span);
+                            let index = self.expression(args.next()?, ctx)?;
+                            let value = self.expression(args.next()?, ctx)?;
+                            args.finish()?;
+
+                            let fun = if function.name == "setVertex" {
+                                crate::MeshFunction::SetVertex { index, value }
+                            } else {
+                                crate::MeshFunction::SetPrimitive { index, value }
+                            };
+                            self.push_stage_statement(
+                                crate::Statement::MeshFunction(fun),
+                                span,
+                                ctx,
+                            )?;
+                            return Ok(None);
+                        }
                         "RayDesc" => {
                             let ty = ctx.module.generate_ray_desc_type();
                             let handle = self.construct(
@@ -2491,9 +2683,52 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Ok(result)
     }
 
+    fn texture_atomic_helper(
+        &mut self,
+        span: Span,
+        fun: crate::AtomicFunction,
+        args: &[Handle<ast::Expression<'source>>],
+        ctx: &mut ExpressionContext<'source, '_, '_>,
+    ) -> Result<(), Error<'source>> {
+        let mut args = ctx.prepare_args(args, 3, span);
+
+        let image = args.next()?;
+        let image_span = ctx.ast_expressions.get_span(image);
+        let image = self.expression(image, ctx)?;
+
+        let coordinate = self.expression(args.next()?, ctx)?;
+
+        let (_, arrayed) = ctx.image_data(image, image_span)?;
+        let array_index = arrayed
+            .then(|| {
+                args.min_args += 1;
+                self.expression(args.next()?, ctx)
+            })
+            .transpose()?;
+
+        let value = self.expression(args.next()?, ctx)?;
+
+        args.finish()?;
+
+        let rctx = ctx.runtime_expression_ctx(span)?;
+        rctx.block
+            .extend(rctx.emitter.finish(&rctx.function.expressions));
+        rctx.emitter.start(&rctx.function.expressions);
+        let stmt = crate::Statement::ImageAtomic {
+            image,
+            coordinate,
+            array_index,
+            fun,
+            value,
+        };
+        rctx.block.push(stmt, span);
+        Ok(())
+    }
+
     fn texture_sample_helper(
         &mut self,
         fun: Texture,
+        residency: bool,
         args: &[Handle<ast::Expression<'source>>],
         span: Span,
         ctx: &mut ExpressionContext<'source, '_, '_>,
@@ -2600,6 +2835,11 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
 
         args.finish()?;
 
+        if residency {
+            ctx.module
+                .generate_predeclared_type(crate::PredeclaredType::SparseSampleResult);
+        }
+
         Ok(crate::Expression::ImageSample {
             image,
             sampler,
@@ -2609,6 +2849,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             offset,
             level,
             depth_ref,
+            residency,
         })
     }
 
@@ -2642,6 +2883,36 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Ok(result)
     }
 
+    fn subgroup_clustered_operation_helper(
+        &mut self,
+        span: Span,
+        op: crate::SubgroupOperation,
+        arguments: &[Handle<ast::Expression<'source>>],
+        ctx: &mut ExpressionContext<'source, '_, '_>,
+    ) -> Result<Handle<crate::Expression>, Error<'source>> {
+        let mut args = ctx.prepare_args(arguments, 2, span);
+
+        let argument = self.expression(args.next()?, ctx)?;
+        let (cluster_size, _) = self.const_u32(args.next()?, &mut ctx.as_const())?;
+        args.finish()?;
+
+        let ty = ctx.register_type(argument)?;
+
+        let result =
+            ctx.interrupt_emitter(crate::Expression::SubgroupOperationResult { ty }, span)?;
+        let rctx = ctx.runtime_expression_ctx(span)?;
+        rctx.block.push(
+            crate::Statement::SubgroupCollectiveOperation {
+                op,
+                collective_op: crate::CollectiveOperation::ClusteredReduce(cluster_size),
+                argument,
+                result,
+            },
+            span,
+        );
+        Ok(result)
+    }
+
     fn subgroup_gather_helper(
         &mut self,
         span: Span,
@@ -2654,17 +2925,24 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         let argument = self.expression(args.next()?, ctx)?;
 
         use SubgroupGather as Sg;
-        let mode = if let Sg::BroadcastFirst = mode {
-            crate::GatherMode::BroadcastFirst
-        } else {
-            let index = self.expression(args.next()?, ctx)?;
-            match mode {
-                Sg::Broadcast => crate::GatherMode::Broadcast(index),
-                Sg::Shuffle => crate::GatherMode::Shuffle(index),
-                Sg::ShuffleDown => crate::GatherMode::ShuffleDown(index),
-                Sg::ShuffleUp => crate::GatherMode::ShuffleUp(index),
-                Sg::ShuffleXor => crate::GatherMode::ShuffleXor(index),
-                Sg::BroadcastFirst => unreachable!(),
+        let mode = match mode {
+            Sg::BroadcastFirst => crate::GatherMode::BroadcastFirst,
+            Sg::QuadSwapX => crate::GatherMode::QuadSwap(crate::Direction::X),
+            Sg::QuadSwapY => crate::GatherMode::QuadSwap(crate::Direction::Y),
+            Sg::QuadSwapDiagonal => crate::GatherMode::QuadSwap(crate::Direction::Diagonal),
+            _ => {
+                let index = self.expression(args.next()?, ctx)?;
+                match mode {
+                    Sg::Broadcast => crate::GatherMode::Broadcast(index),
+                    Sg::Shuffle => crate::GatherMode::Shuffle(index),
+                    Sg::ShuffleDown => crate::GatherMode::ShuffleDown(index),
+                    Sg::ShuffleUp => crate::GatherMode::ShuffleUp(index),
+                    Sg::ShuffleXor => crate::GatherMode::ShuffleXor(index),
+                    Sg::QuadBroadcast => crate::GatherMode::QuadBroadcast(index),
+                    Sg::BroadcastFirst | Sg::QuadSwapX | Sg::QuadSwapY | Sg::QuadSwapDiagonal => {
+                        unreachable!()
+                    }
+                }
             }
         };
 
@@ -2781,6 +3059,41 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Ok((value, span))
     }
 
+    /// Lower one dimension of a `@workgroup_size` attribute.
+    ///
+    /// Dimensions that are override-expressions are kept as expressions,
+    /// to be evaluated when the pipeline's overrides are processed.
+    fn workgroup_size_dim(
+        &mut self,
+        expr: Handle<ast::Expression<'source>>,
+        ctx: &mut GlobalContext<'source, '_, '_>,
+    ) -> Result<WorkgroupSizeDim, Error<'source>> {
+        let span = ctx.ast_expressions.get_span(expr);
+        let mut ectx = ctx.as_override();
+        let expr = self.expression(expr, &mut ectx)?;
+        if ectx.global_expression_kind_tracker.is_const(expr) {
+            let value = ectx
+                .module
+                .to_ctx()
+                .eval_expr_to_u32(expr)
+                .map_err(|err| match err {
+                    crate::proc::U32EvalError::NonConst => {
+                        Error::ExpectedConstExprConcreteIntegerScalar(span)
+                    }
+                    crate::proc::U32EvalError::Negative => Error::ExpectedNonNegative(span),
+                })?;
+            return Ok(WorkgroupSizeDim::Constant(value));
+        }
+
+        match *resolve_inner!(ectx, expr) {
+            crate::TypeInner::Scalar(crate::Scalar {
+                kind: crate::ScalarKind::Sint | crate::ScalarKind::Uint,
+                width: 4,
+            }) => Ok(WorkgroupSizeDim::Override(expr)),
+            _ => Err(Error::ExpectedConstExprConcreteIntegerScalar(span)),
+        }
+    }
+
     fn array_size(
         &mut self,
         size: ast::ArraySize<'source>,
@@ -2789,6 +3102,13 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Ok(match size {
             ast::ArraySize::Constant(expr) => {
                 let span = ctx.ast_expressions.get_span(expr);
+                if let ast::Expression::Ident(ast::IdentExpr::Unresolved(name)) =
+                    ctx.ast_expressions[expr]
+                {
+                    if let Some(&LoweredGlobalDecl::Override(handle)) = ctx.globals.get(name) {
+                        return Ok(crate::ArraySize::Pending(handle));
+                    }
+                }
                 let const_expr = self.expression(expr, &mut ctx.as_const())?;
                 let len =
                     ctx.module
@@ -2907,12 +3227,14 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                 second_blend_source,
                 interpolation,
                 sampling,
+                per_primitive,
             }) => {
                 let mut binding = crate::Binding::Location {
                     location: self.const_u32(location, &mut ctx.as_const())?.0,
                     second_blend_source,
                     interpolation,
                     sampling,
+                    per_primitive,
                 };
                 binding.apply_default_interpolation(&ctx.module.types[ty].inner);
                 Some(binding)
@@ -2921,6 +3243,22 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         })
     }
 
+    /// Push a statement with no result, flushing the pending emits first so
+    /// that its operands are evaluated before it.
+    fn push_stage_statement(
+        &mut self,
+        statement: crate::Statement,
+        span: Span,
+        ctx: &mut ExpressionContext<'source, '_, '_>,
+    ) -> Result<(), Error<'source>> {
+        let rctx = ctx.runtime_expression_ctx(span)?;
+        rctx.block
+            .extend(rctx.emitter.finish(&rctx.function.expressions));
+        rctx.emitter.start(&rctx.function.expressions);
+        rctx.block.push(statement, span);
+        Ok(())
+    }
+
     fn ray_query_pointer(
         &mut self,
         expr: Handle<ast::Expression<'source>>,
@@ -5,6 +5,7 @@ Frontend for [WGSL][wgsl] (WebGPU Shading Language).
 */
 
 mod error;
+mod include;
 mod index;
 mod lower;
 mod parse;
@@ -17,6 +18,7 @@ use crate::front::wgsl::parse::Parser;
 use thiserror::Error;
 
 pub use crate::front::wgsl::error::ParseError;
+pub use crate::front::wgsl::include::{ExpandedSource, FileParseError, SourceFile};
 use crate::front::wgsl::lower::Lowerer;
 use crate::Scalar;
 
@@ -58,3 +60,26 @@ impl Frontend {
 pub fn parse_str(source: &str) -> Result<crate::Module, ParseError> {
     Frontend::new().parse(source)
 }
+
+/// Parse `source`, first splicing in the files named by its
+/// `// #include "path"` directives.
+///
+/// See [`ExpandedSource::new`] for how includes are resolved. The spans in the
+/// returned module refer to [`ExpandedSource::source`], while errors refer to
+/// the file containing the offending code.
+///
+/// The stack space caveats of [`parse_str`] apply here as well.
+pub fn parse_str_with_includes<F>(
+    name: &str,
+    source: &str,
+    resolve: F,
+) -> Result<(crate::Module, ExpandedSource), FileParseError>
+where
+    F: FnMut(&str) -> Result<String, String>,
+{
+    let expanded = ExpandedSource::new(name, source, resolve)?;
+    match parse_str(expanded.source()) {
+        Ok(module) => Ok((module, expanded)),
+        Err(error) => Err(expanded.locate_error(error)),
+    }
+}
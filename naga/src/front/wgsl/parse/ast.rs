@@ -106,6 +106,15 @@ pub struct EntryPoint<'a> {
     pub stage: crate::ShaderStage,
     pub early_depth_test: Option<crate::EarlyDepthTest>,
     pub workgroup_size: Option<[Option<Handle<Expression<'a>>>; 3]>,
+    pub mesh_output: Option<MeshOutput<'a>>,
+}
+
+/// The `@vertex_output` and `@primitive_output` attributes of a mesh shader,
+/// each holding the output struct type and the maximum number of outputs.
+#[derive(Debug)]
+pub struct MeshOutput<'a> {
+    pub vertex_output: (Handle<Type<'a>>, Handle<Expression<'a>>),
+    pub primitive_output: (Handle<Type<'a>>, Handle<Expression<'a>>),
 }
 
 #[cfg(doc)]
@@ -155,6 +164,7 @@ pub enum Binding<'a> {
         second_blend_source: bool,
         interpolation: Option<crate::Interpolation>,
         sampling: Option<crate::Sampling>,
+        per_primitive: bool,
     },
 }
 
@@ -12,6 +12,10 @@ pub fn map_address_space(word: &str, span: Span) -> Result<crate::AddressSpace,
         }),
         "push_constant" => Ok(crate::AddressSpace::PushConstant),
         "function" => Ok(crate::AddressSpace::Function),
+        "ray_payload" => Ok(crate::AddressSpace::RayPayload),
+        "incoming_ray_payload" => Ok(crate::AddressSpace::IncomingRayPayload),
+        "hit_attribute" => Ok(crate::AddressSpace::HitAttribute),
+        "task_payload" => Ok(crate::AddressSpace::TaskPayload),
         _ => Err(Error::UnknownAddressSpace(span)),
     }
 }
@@ -23,12 +27,15 @@ pub fn map_built_in(word: &str, span: Span) -> Result<crate::BuiltIn, Error<'_>>
         "vertex_index" => crate::BuiltIn::VertexIndex,
         "instance_index" => crate::BuiltIn::InstanceIndex,
         "view_index" => crate::BuiltIn::ViewIndex,
+        "clip_distances" => crate::BuiltIn::ClipDistance,
+        "cull_distances" => crate::BuiltIn::CullDistance,
         // fragment
         "front_facing" => crate::BuiltIn::FrontFacing,
         "frag_depth" => crate::BuiltIn::FragDepth,
         "primitive_index" => crate::BuiltIn::PrimitiveIndex,
         "sample_index" => crate::BuiltIn::SampleIndex,
         "sample_mask" => crate::BuiltIn::SampleMask,
+        "barycentric_coords" => crate::BuiltIn::Barycentric,
         // compute
         "global_invocation_id" => crate::BuiltIn::GlobalInvocationId,
         "local_invocation_id" => crate::BuiltIn::LocalInvocationId,
@@ -40,6 +47,27 @@ pub fn map_built_in(word: &str, span: Span) -> Result<crate::BuiltIn, Error<'_>>
         "subgroup_id" => crate::BuiltIn::SubgroupId,
         "subgroup_size" => crate::BuiltIn::SubgroupSize,
         "subgroup_invocation_id" => crate::BuiltIn::SubgroupInvocationId,
+        // ray tracing pipeline
+        "launch_id" => crate::BuiltIn::LaunchId,
+        "launch_size" => crate::BuiltIn::LaunchSize,
+        "world_ray_origin" => crate::BuiltIn::WorldRayOrigin,
+        "world_ray_direction" => crate::BuiltIn::WorldRayDirection,
+        "object_ray_origin" => crate::BuiltIn::ObjectRayOrigin,
+        "object_ray_direction" => crate::BuiltIn::ObjectRayDirection,
+        "ray_t_min" => crate::BuiltIn::RayTmin,
+        "ray_t_current" => crate::BuiltIn::RayTCurrent,
+        "instance_id" => crate::BuiltIn::InstanceId,
+        "instance_custom_index" => crate::BuiltIn::InstanceCustomIndex,
+        "geometry_index" => crate::BuiltIn::GeometryIndex,
+        "hit_kind" => crate::BuiltIn::HitKind,
+        "incoming_ray_flags" => crate::BuiltIn::IncomingRayFlags,
+        "object_to_world" => crate::BuiltIn::ObjectToWorld,
+        "world_to_object" => crate::BuiltIn::WorldToObject,
+        // mesh shading
+        "triangle_indices" => crate::BuiltIn::TriangleIndices,
+        "line_indices" => crate::BuiltIn::LineIndices,
+        "point_index" => crate::BuiltIn::PointIndex,
+        "cull_primitive" => crate::BuiltIn::CullPrimitive,
         _ => return Err(Error::UnknownBuiltin(span)),
     })
 }
@@ -288,3 +316,30 @@ pub fn map_subgroup_operation(
         _ => return None,
     })
 }
+
+pub fn map_subgroup_clustered_operation(word: &str) -> Option<crate::SubgroupOperation> {
+    use crate::SubgroupOperation as sg;
+    Some(match word {
+        "subgroupClusteredAdd" => sg::Add,
+        "subgroupClusteredMul" => sg::Mul,
+        "subgroupClusteredMin" => sg::Min,
+        "subgroupClusteredMax" => sg::Max,
+        "subgroupClusteredAnd" => sg::And,
+        "subgroupClusteredOr" => sg::Or,
+        "subgroupClusteredXor" => sg::Xor,
+        _ => return None,
+    })
+}
+
+pub fn map_texture_atomic_fun(word: &str) -> Option<crate::AtomicFunction> {
+    use crate::AtomicFunction as Af;
+    Some(match word {
+        "textureAtomicAdd" => Af::Add,
+        "textureAtomicAnd" => Af::And,
+        "textureAtomicOr" => Af::InclusiveOr,
+        "textureAtomicXor" => Af::ExclusiveOr,
+        "textureAtomicMin" => Af::Min,
+        "textureAtomicMax" => Af::Max,
+        _ => return None,
+    })
+}
@@ -422,7 +422,12 @@ impl<'a> Lexer<'a> {
         let (ident, ident_span) = self.next_ident_with_span()?;
         let format = conv::map_storage_format(ident, ident_span)?;
         self.expect(Token::Separator(','))?;
-        let access = self.next_storage_access()?;
+        // Storage textures additionally accept the `atomic` access mode.
+        let access = if self.skip(Token::Word("atomic")) {
+            crate::StorageAccess::LOAD | crate::StorageAccess::STORE | crate::StorageAccess::ATOMIC
+        } else {
+            self.next_storage_access()?
+        };
         self.expect(Token::Paren('>'))?;
         Ok((format, access))
     }
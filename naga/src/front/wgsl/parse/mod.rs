@@ -135,6 +135,7 @@ impl<T> ParsedAttribute<T> {
 struct BindingParser<'a> {
     location: ParsedAttribute<Handle<ast::Expression<'a>>>,
     second_blend_source: ParsedAttribute<bool>,
+    per_primitive: ParsedAttribute<bool>,
     built_in: ParsedAttribute<crate::BuiltIn>,
     interpolation: ParsedAttribute<crate::Interpolation>,
     sampling: ParsedAttribute<crate::Sampling>,
@@ -179,6 +180,9 @@ impl<'a> BindingParser<'a> {
             "second_blend_source" => {
                 self.second_blend_source.set(true, name_span)?;
             }
+            "per_primitive" => {
+                self.per_primitive.set(true, name_span)?;
+            }
             "invariant" => {
                 self.invariant.set(true, name_span)?;
             }
@@ -206,6 +210,7 @@ impl<'a> BindingParser<'a> {
                     interpolation,
                     sampling,
                     second_blend_source: self.second_blend_source.value.unwrap_or(false),
+                    per_primitive: self.per_primitive.value.unwrap_or(false),
                 }))
             }
             (None, Some(crate::BuiltIn::Position { .. }), None, None, invariant) => {
@@ -2177,6 +2182,8 @@ impl Parser {
         let mut stage = ParsedAttribute::default();
         let mut compute_span = Span::new(0, 0);
         let mut workgroup_size = ParsedAttribute::default();
+        let mut vertex_output = ParsedAttribute::default();
+        let mut primitive_output = ParsedAttribute::default();
         let mut early_depth_test = ParsedAttribute::default();
         let (mut bind_index, mut bind_group) =
             (ParsedAttribute::default(), ParsedAttribute::default());
@@ -2219,6 +2226,45 @@ impl Parser {
                     stage.set(ShaderStage::Compute, name_span)?;
                     compute_span = name_span;
                 }
+                ("ray_generation", name_span) => {
+                    stage.set(ShaderStage::RayGeneration, name_span)?;
+                }
+                ("intersection", name_span) => {
+                    stage.set(ShaderStage::Intersection, name_span)?;
+                }
+                ("any_hit", name_span) => {
+                    stage.set(ShaderStage::AnyHit, name_span)?;
+                }
+                ("closest_hit", name_span) => {
+                    stage.set(ShaderStage::ClosestHit, name_span)?;
+                }
+                ("miss", name_span) => {
+                    stage.set(ShaderStage::Miss, name_span)?;
+                }
+                ("task", name_span) => {
+                    stage.set(ShaderStage::Task, name_span)?;
+                    compute_span = name_span;
+                }
+                ("mesh", name_span) => {
+                    stage.set(ShaderStage::Mesh, name_span)?;
+                    compute_span = name_span;
+                }
+                ("vertex_output", name_span) => {
+                    lexer.expect(Token::Paren('('))?;
+                    let ty = self.type_decl(lexer, &mut ctx)?;
+                    lexer.expect(Token::Separator(','))?;
+                    let max_vertices = self.general_expression(lexer, &mut ctx)?;
+                    lexer.expect(Token::Paren(')'))?;
+                    vertex_output.set((ty, max_vertices), name_span)?;
+                }
+                ("primitive_output", name_span) => {
+                    lexer.expect(Token::Paren('('))?;
+                    let ty = self.type_decl(lexer, &mut ctx)?;
+                    lexer.expect(Token::Separator(','))?;
+                    let max_primitives = self.general_expression(lexer, &mut ctx)?;
+                    lexer.expect(Token::Paren(')'))?;
+                    primitive_output.set((ty, max_primitives), name_span)?;
+                }
                 ("workgroup_size", name_span) => {
                     lexer.expect(Token::Paren('('))?;
                     let mut new_workgroup_size = [None; 3];
@@ -2332,13 +2378,32 @@ impl Parser {
                 let function = self.function_decl(lexer, out, &mut dependencies)?;
                 Some(ast::GlobalDeclKind::Fn(ast::Function {
                     entry_point: if let Some(stage) = stage.value {
-                        if stage == ShaderStage::Compute && workgroup_size.value.is_none() {
+                        if stage.uses_workgroups() && workgroup_size.value.is_none() {
                             return Err(Error::MissingWorkgroupSize(compute_span));
                         }
+                        let mesh_output = match (vertex_output.value, primitive_output.value) {
+                            (Some(vertex_output), Some(primitive_output)) => {
+                                Some(ast::MeshOutput {
+                                    vertex_output,
+                                    primitive_output,
+                                })
+                            }
+                            (None, None) if stage != ShaderStage::Mesh => None,
+                            (_, None) => {
+                                return Err(Error::MissingMeshOutput(
+                                    "primitive_output",
+                                    compute_span,
+                                ))
+                            }
+                            (None, _) => {
+                                return Err(Error::MissingMeshOutput("vertex_output", compute_span))
+                            }
+                        };
                         Some(ast::EntryPoint {
                             stage,
                             early_depth_test: early_depth_test.value,
                             workgroup_size: workgroup_size.value,
+                            mesh_output,
                         })
                     } else {
                         None
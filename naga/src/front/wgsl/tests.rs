@@ -636,3 +636,377 @@ fn parse_missing_workgroup_size() {
         Error::MissingWorkgroupSize(span) if span == Span::new(1, 8)
     ));
 }
+
+#[test]
+fn parse_subgroup_extended_operations() {
+    use crate::{CollectiveOperation, Direction, GatherMode, Statement};
+
+    let module = parse_str(
+        "
+        @compute @workgroup_size(64)
+        fn main(@builtin(subgroup_invocation_id) id: u32) {
+            let a = subgroupClusteredAdd(id, 4u);
+            let b = subgroupClusteredMax(id, 2 * 4);
+            let c = quadBroadcast(id, 1u);
+            let d = quadSwapX(id);
+            let e = quadSwapY(id);
+            let f = quadSwapDiagonal(id);
+        }
+        ",
+    )
+    .unwrap();
+
+    let body = &module.entry_points[0].function.body;
+    let cluster_sizes: Vec<_> = body
+        .iter()
+        .filter_map(|statement| match *statement {
+            Statement::SubgroupCollectiveOperation {
+                collective_op: CollectiveOperation::ClusteredReduce(cluster_size),
+                ..
+            } => Some(cluster_size),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(cluster_sizes, [4, 8]);
+
+    let directions: Vec<_> = body
+        .iter()
+        .filter_map(|statement| match *statement {
+            Statement::SubgroupGather {
+                mode: GatherMode::QuadSwap(direction),
+                ..
+            } => Some(direction),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        directions,
+        [Direction::X, Direction::Y, Direction::Diagonal]
+    );
+    assert!(body.iter().any(|statement| matches!(
+        *statement,
+        Statement::SubgroupGather {
+            mode: GatherMode::QuadBroadcast(_),
+            ..
+        }
+    )));
+
+    assert!(parse_str(
+        "
+        @compute @workgroup_size(64)
+        fn main(@builtin(subgroup_invocation_id) id: u32) {
+            let a = subgroupClusteredAdd(id, id);
+        }
+        ",
+    )
+    .is_err());
+}
+
+#[test]
+fn parse_texture_atomics() {
+    use crate::{AtomicFunction, Statement};
+
+    let module = parse_str(
+        "
+        @group(0) @binding(0)
+        var image: texture_storage_2d<r32uint, atomic>;
+        @group(0) @binding(1)
+        var image_array: texture_storage_2d_array<r32sint, atomic>;
+
+        @compute @workgroup_size(1)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            textureAtomicMax(image, id.xy, 1u);
+            textureAtomicAdd(image_array, id.xy, 0, -1);
+        }
+        ",
+    )
+    .unwrap();
+
+    let funs: Vec<_> = module.entry_points[0]
+        .function
+        .body
+        .iter()
+        .filter_map(|statement| match *statement {
+            Statement::ImageAtomic {
+                array_index, fun, ..
+            } => Some((fun, array_index.is_some())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        funs,
+        [(AtomicFunction::Max, false), (AtomicFunction::Add, true)]
+    );
+
+    assert!(parse_str(
+        "
+        @group(0) @binding(0)
+        var image: texture_storage_2d<r32uint, atomic>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let a = textureAtomicAdd(image, vec2(0), 1u);
+        }
+        ",
+    )
+    .is_err());
+}
+
+#[test]
+fn parse_ray_tracing_pipeline() {
+    use crate::{RayPipelineFunction, ShaderStage, Statement};
+
+    let module = parse_str(
+        "
+        @group(0) @binding(0)
+        var acc_struct: acceleration_structure;
+
+        var<ray_payload> payload: vec4<f32>;
+        var<incoming_ray_payload> incoming: vec4<f32>;
+        var<hit_attribute> attribs: vec2<f32>;
+
+        @ray_generation
+        fn raygen(@builtin(launch_id) id: vec3<u32>) {
+            let desc = RayDesc(0u, 0xffu, 0.1, 100.0, vec3(0.0), vec3(0.0, 0.0, 1.0));
+            traceRay(acc_struct, desc, 0u, 1u, 0u, &payload);
+        }
+
+        @intersection
+        fn intersect() {
+            attribs = vec2(0.5);
+            reportIntersection(1.0, 0u);
+        }
+
+        @any_hit
+        fn any_hit(@builtin(hit_kind) kind: u32) {
+            if kind == 0u {
+                ignoreIntersection();
+            }
+            terminateRay();
+        }
+
+        @closest_hit
+        fn closest_hit(@builtin(ray_t_current) t: f32) {
+            incoming = vec4(attribs, t, 1.0);
+        }
+
+        @miss
+        fn miss() {
+            incoming = vec4(0.0);
+        }
+        ",
+    )
+    .unwrap();
+
+    let stages: Vec<_> = module.entry_points.iter().map(|ep| ep.stage).collect();
+    assert_eq!(
+        stages,
+        [
+            ShaderStage::RayGeneration,
+            ShaderStage::Intersection,
+            ShaderStage::AnyHit,
+            ShaderStage::ClosestHit,
+            ShaderStage::Miss,
+        ]
+    );
+    assert!(module.special_types.ray_desc.is_some());
+    assert!(module.entry_points[0]
+        .function
+        .body
+        .iter()
+        .any(|statement| matches!(
+            *statement,
+            Statement::RayPipelineFunction(RayPipelineFunction::TraceRay { .. })
+        )));
+    assert!(module.entry_points[1]
+        .function
+        .body
+        .iter()
+        .any(|statement| matches!(
+            *statement,
+            Statement::RayPipelineFunction(RayPipelineFunction::ReportIntersection { .. })
+        )));
+    assert!(matches!(
+        module.entry_points[2].function.body.last(),
+        Some(&Statement::RayPipelineFunction(
+            RayPipelineFunction::TerminateRay
+        ))
+    ));
+}
+
+#[test]
+fn parse_mesh_shader() {
+    use crate::{MeshFunction, MeshOutputTopology, ShaderStage, Statement};
+
+    let module = parse_str(
+        "
+        struct Vertex {
+            @builtin(position) position: vec4<f32>,
+            @location(0) color: vec4<f32>,
+        }
+
+        struct Primitive {
+            @builtin(line_indices) indices: vec2<u32>,
+            @location(1) @per_primitive @interpolate(flat) id: u32,
+        }
+
+        var<task_payload> payload: vec4<f32>;
+
+        @task @workgroup_size(1)
+        fn ts() {
+            payload = vec4(1.0);
+            emitMeshTasks(1u, 1u, 1u);
+        }
+
+        @mesh @workgroup_size(1) @vertex_output(Vertex, 2) @primitive_output(Primitive, 1)
+        fn ms() {
+            setMeshOutputs(2u, 1u);
+            setVertex(0u, Vertex(vec4(0.0), payload));
+            setVertex(1u, Vertex(vec4(1.0), payload));
+            setPrimitive(0u, Primitive(vec2(0u, 1u), 0u));
+        }
+        ",
+    )
+    .unwrap();
+
+    let stages: Vec<_> = module.entry_points.iter().map(|ep| ep.stage).collect();
+    assert_eq!(stages, [ShaderStage::Task, ShaderStage::Mesh]);
+    assert!(module.entry_points[0].mesh_info.is_none());
+    assert!(matches!(
+        module.entry_points[0].function.body.last(),
+        Some(&Statement::MeshFunction(MeshFunction::EmitMeshTasks { .. }))
+    ));
+
+    let mesh_info = module.entry_points[1].mesh_info.as_ref().unwrap();
+    assert_eq!(mesh_info.topology, MeshOutputTopology::Lines);
+    assert_eq!(mesh_info.max_vertices, 2);
+    assert_eq!(mesh_info.max_primitives, 1);
+    assert_eq!(
+        module.types[mesh_info.vertex_output].name.as_deref(),
+        Some("Vertex")
+    );
+    let set_vertex_count = module.entry_points[1]
+        .function
+        .body
+        .iter()
+        .filter(|statement| {
+            matches!(
+                **statement,
+                Statement::MeshFunction(MeshFunction::SetVertex { .. })
+            )
+        })
+        .count();
+    assert_eq!(set_vertex_count, 2);
+
+    // Mesh shaders must declare their outputs.
+    assert!(parse_str(
+        "
+        @mesh @workgroup_size(1)
+        fn ms() {}
+        ",
+    )
+    .is_err());
+}
+
+#[test]
+fn parse_includes() {
+    use super::parse_str_with_includes;
+
+    let resolve = |path: &str| match path {
+        "common.wgsl" => Ok("const SCALE: f32 = 2.0;\n".to_string()),
+        "math.wgsl" => Ok(
+            "// #include \"common.wgsl\"\nfn scale(x: f32) -> f32 { return x * SCALE; }\n"
+                .to_string(),
+        ),
+        "broken.wgsl" => Ok("fn broken() -> f32 { return 1.0 + ; }".to_string()),
+        _ => Err("not found".to_string()),
+    };
+
+    let (module, expanded) = parse_str_with_includes(
+        "main.wgsl",
+        "// #include \"math.wgsl\"\n// #include \"common.wgsl\"\nfn main() -> f32 { return scale(SCALE); }\n",
+        resolve,
+    )
+    .unwrap();
+    assert_eq!(module.functions.len(), 2);
+    assert_eq!(module.constants.len(), 1);
+    let names: Vec<_> = expanded
+        .files()
+        .iter()
+        .map(|file| file.name.as_str())
+        .collect();
+    assert_eq!(names, ["main.wgsl", "math.wgsl", "common.wgsl"]);
+
+    // Errors are reported against the file containing the offending code.
+    let err =
+        parse_str_with_includes("main.wgsl", "// #include \"broken.wgsl\"\n", resolve).unwrap_err();
+    assert_eq!(err.file.name, "broken.wgsl");
+    let location = err.error.location(&err.file.source).unwrap();
+    assert_eq!(location.line_number, 1);
+    assert_eq!(location.offset, 34);
+
+    // So are failures to resolve an include.
+    let err = parse_str_with_includes("main.wgsl", "\n// #include \"missing.wgsl\"\n", resolve)
+        .unwrap_err();
+    assert_eq!(err.file.name, "main.wgsl");
+    assert_eq!(err.error.location(&err.file.source).unwrap().line_number, 2);
+    assert!(err.emit_to_string().contains("not found"));
+
+    // Validation errors can be located the same way.
+    let resolve = |_: &str| {
+        Ok::<_, String>("// Needs `Capabilities::FLOAT64`.\nvar<private> x: f64;\n".to_string())
+    };
+    let (module, expanded) =
+        parse_str_with_includes("main.wgsl", "// #include \"doubles.wgsl\"\n", resolve).unwrap();
+    let err = crate::valid::Validator::new(
+        crate::valid::ValidationFlags::all(),
+        crate::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .unwrap_err();
+    let (file, err) = expanded.locate_spans(err);
+    assert_eq!(file, 1);
+    let location = err.location(&expanded.files()[file].source).unwrap();
+    assert_eq!(location.line_number, 2);
+    let (_, description) = err.spans().next().unwrap();
+    assert!(description.ends_with("in file included as `doubles.wgsl`"));
+}
+
+#[test]
+fn parse_override_sized_arrays() {
+    use crate::{ArraySize, TypeInner};
+
+    let module = parse_str(
+        "
+        override block_size: u32 = 64;
+        override rows: i32;
+
+        var<workgroup> tile: array<f32, block_size>;
+
+        @compute @workgroup_size(block_size, rows - 1, 2)
+        fn main() {
+            tile[0] = 1.0;
+        }
+        ",
+    )
+    .unwrap();
+
+    let tile = module.global_variables.iter().next().unwrap().1;
+    let TypeInner::Array {
+        size: ArraySize::Pending(handle),
+        ..
+    } = module.types[tile.ty].inner
+    else {
+        panic!("expected an override-sized array");
+    };
+    assert_eq!(module.overrides[handle].name.as_deref(), Some("block_size"));
+
+    let ep = &module.entry_points[0];
+    assert_eq!(ep.workgroup_size, [0, 0, 2]);
+    let overrides = ep.workgroup_size_overrides.unwrap();
+    assert!(overrides[0].is_some() && overrides[1].is_some() && overrides[2].is_none());
+
+    crate::valid::Validator::new(Default::default(), crate::valid::Capabilities::all())
+        .validate(&module)
+        .unwrap();
+}
@@ -67,6 +67,10 @@ impl crate::TypeInner {
                 let base = base.to_wgsl(gctx);
                 match size {
                     crate::ArraySize::Constant(size) => format!("array<{base}, {size}>"),
+                    crate::ArraySize::Pending(handle) => {
+                        let size = gctx.overrides[handle].name.as_deref().unwrap_or("override");
+                        format!("array<{base}, {size}>")
+                    }
                     crate::ArraySize::Dynamic => format!("array<{base}>"),
                 }
             }
@@ -105,7 +109,9 @@ impl crate::TypeInner {
                     }
                     crate::ImageClass::Depth { multi: _ } => String::new(),
                     crate::ImageClass::Storage { format, access } => {
-                        if access.contains(crate::StorageAccess::STORE) {
+                        if access.contains(crate::StorageAccess::ATOMIC) {
+                            format!("<{},atomic>", format.to_wgsl())
+                        } else if access.contains(crate::StorageAccess::STORE) {
                             format!("<{},write>", format.to_wgsl())
                         } else {
                             format!("<{}>", format.to_wgsl())
@@ -123,6 +129,10 @@ impl crate::TypeInner {
                 let base = member_type.name.as_deref().unwrap_or("unknown");
                 match size {
                     crate::ArraySize::Constant(size) => format!("binding_array<{base}, {size}>"),
+                    crate::ArraySize::Pending(handle) => {
+                        let size = gctx.overrides[handle].name.as_deref().unwrap_or("override");
+                        format!("binding_array<{base}, {size}>")
+                    }
                     crate::ArraySize::Dynamic => format!("binding_array<{base}>"),
                 }
             }
@@ -277,6 +277,8 @@ pub mod compact;
 pub mod error;
 pub mod front;
 pub mod keywords;
+#[cfg(feature = "precompiled")]
+pub mod precompiled;
 pub mod proc;
 mod span;
 pub mod valid;
@@ -297,6 +299,9 @@ pub const BOOL_WIDTH: Bytes = 1;
 /// Width of abstract types, in bytes.
 pub const ABSTRACT_WIDTH: Bytes = 8;
 
+/// The version of Naga, whose IR can change from one version to the next.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Hash map that is faster but not resilient to DoS attacks.
 pub type FastHashMap<K, T> = rustc_hash::FxHashMap<K, T>;
 /// Hash set that is faster but not resilient to DoS attacks.
@@ -378,6 +383,15 @@ pub enum ShaderStage {
     Vertex,
     Fragment,
     Compute,
+    // ray tracing pipeline
+    RayGeneration,
+    Intersection,
+    AnyHit,
+    ClosestHit,
+    Miss,
+    // mesh shading
+    Task,
+    Mesh,
 }
 
 /// Addressing space of variables.
@@ -400,6 +414,20 @@ pub enum AddressSpace {
     Handle,
     /// Push constants.
     PushConstant,
+    /// Ray payload passed to [`TraceRay`], owned by the invocation that traces the ray.
+    ///
+    /// [`TraceRay`]: RayPipelineFunction::TraceRay
+    RayPayload,
+    /// The payload of the ray that invoked an any-hit, closest-hit or miss shader.
+    IncomingRayPayload,
+    /// Attributes of an intersection, written by an intersection shader and
+    /// read by any-hit and closest-hit shaders.
+    HitAttribute,
+    /// Data written by a task shader and read by the mesh shader workgroups
+    /// it launches with [`EmitMeshTasks`].
+    ///
+    /// [`EmitMeshTasks`]: MeshFunction::EmitMeshTasks
+    TaskPayload,
 }
 
 /// Built-in inputs and outputs.
@@ -408,7 +436,9 @@ pub enum AddressSpace {
 #[cfg_attr(feature = "deserialize", derive(Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub enum BuiltIn {
-    Position { invariant: bool },
+    Position {
+        invariant: bool,
+    },
     ViewIndex,
     // vertex
     BaseInstance,
@@ -425,6 +455,8 @@ pub enum BuiltIn {
     PrimitiveIndex,
     SampleIndex,
     SampleMask,
+    /// Perspective-correct barycentric coordinates of the fragment.
+    Barycentric,
     // compute
     GlobalInvocationId,
     LocalInvocationId,
@@ -437,6 +469,27 @@ pub enum BuiltIn {
     SubgroupId,
     SubgroupSize,
     SubgroupInvocationId,
+    // ray tracing pipeline
+    LaunchId,
+    LaunchSize,
+    WorldRayOrigin,
+    WorldRayDirection,
+    ObjectRayOrigin,
+    ObjectRayDirection,
+    RayTmin,
+    RayTCurrent,
+    InstanceId,
+    InstanceCustomIndex,
+    GeometryIndex,
+    HitKind,
+    IncomingRayFlags,
+    ObjectToWorld,
+    WorldToObject,
+    // mesh shading
+    TriangleIndices,
+    LineIndices,
+    PointIndex,
+    CullPrimitive,
 }
 
 /// Number of bytes per scalar.
@@ -510,6 +563,14 @@ pub struct Scalar {
 pub enum ArraySize {
     /// The array size is constant.
     Constant(std::num::NonZeroU32),
+    /// The array size is the value of an override, known at pipeline creation.
+    ///
+    /// Such arrays may only be the type of a `workgroup` [`GlobalVariable`],
+    /// and are replaced with [`Constant`] ones by
+    /// [`process_overrides`](crate::back::pipeline_constants::process_overrides).
+    ///
+    /// [`Constant`]: ArraySize::Constant
+    Pending(Handle<Override>),
     /// The array size can change at runtime.
     Dynamic,
 }
@@ -593,6 +654,8 @@ bitflags::bitflags! {
         const LOAD = 0x1;
         /// Storage can be used as a target for store ops.
         const STORE = 0x2;
+        /// Storage can be used as a target for atomic ops.
+        const ATOMIC = 0x4;
     }
 }
 
@@ -955,6 +1018,11 @@ pub enum Binding {
         second_blend_source: bool,
         interpolation: Option<Interpolation>,
         sampling: Option<Sampling>,
+        /// Indicates a fragment input that is written once per primitive by
+        /// a [`Mesh`] shader, rather than interpolated between vertices.
+        ///
+        /// [`Mesh`]: crate::ShaderStage::Mesh
+        per_primitive: bool,
     },
 }
 
@@ -1296,6 +1364,20 @@ pub enum GatherMode {
     ShuffleUp(Handle<Expression>),
     /// Each gathers from their lane xored with the given by the expression
     ShuffleXor(Handle<Expression>),
+    /// All gather from the same quad lane at the index given by the expression
+    QuadBroadcast(Handle<Expression>),
+    /// Each gathers from the opposite quad lane along the given direction
+    QuadSwap(Direction),
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum Direction {
+    X = 0,
+    Y = 1,
+    Diagonal = 2,
 }
 
 #[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
@@ -1319,9 +1401,13 @@ pub enum SubgroupOperation {
 #[cfg_attr(feature = "deserialize", derive(Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub enum CollectiveOperation {
-    Reduce = 0,
-    InclusiveScan = 1,
-    ExclusiveScan = 2,
+    Reduce,
+    InclusiveScan,
+    ExclusiveScan,
+    /// Reduce over clusters of consecutive invocations of the given size.
+    ///
+    /// The cluster size must be a power of two, and at most the subgroup size.
+    ClusteredReduce(u32),
 }
 
 bitflags::bitflags! {
@@ -1483,6 +1569,10 @@ pub enum Expression {
         offset: Option<Handle<Expression>>,
         level: SampleLevel,
         depth_ref: Option<Handle<Expression>>,
+        /// If true, the result also reports whether all the texels the
+        /// sample accessed are resident, as a
+        /// [`PredeclaredType::SparseSampleResult`] struct.
+        residency: bool,
     },
 
     /// Load a texel from an image.
@@ -1719,6 +1809,137 @@ pub enum RayQueryFunction {
     Terminate,
 }
 
+/// An operation available only in ray tracing pipeline stages.
+///
+/// See [`Statement::RayPipelineFunction`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum RayPipelineFunction {
+    /// Trace a ray through an acceleration structure, invoking the hit
+    /// and miss shaders selected by the shader binding table.
+    ///
+    /// Allowed in ray generation, closest-hit and miss shaders.
+    TraceRay {
+        /// The acceleration structure to trace the ray through.
+        ///
+        /// The expression must be an [`AccelerationStructure`].
+        ///
+        /// [`AccelerationStructure`]: TypeInner::AccelerationStructure
+        acceleration_structure: Handle<Expression>,
+
+        #[allow(rustdoc::private_intra_doc_links)]
+        /// A struct describing the ray.
+        ///
+        /// This expression should have the struct type given in
+        /// [`SpecialTypes::ray_desc`], the same one used by ray queries.
+        descriptor: Handle<Expression>,
+
+        /// Offset added to the hit group index of the shader binding table.
+        ///
+        /// Must be a `u32` scalar.
+        sbt_record_offset: Handle<Expression>,
+
+        /// Stride between hit groups of different geometries in the shader
+        /// binding table.
+        ///
+        /// Must be a `u32` scalar.
+        sbt_record_stride: Handle<Expression>,
+
+        /// Index of the miss shader to invoke if the ray hits nothing.
+        ///
+        /// Must be a `u32` scalar.
+        miss_index: Handle<Expression>,
+
+        /// Pointer to a global in the [`RayPayload`] address space, which the
+        /// invoked shaders see as their [`IncomingRayPayload`].
+        ///
+        /// [`RayPayload`]: AddressSpace::RayPayload
+        /// [`IncomingRayPayload`]: AddressSpace::IncomingRayPayload
+        payload: Handle<Expression>,
+    },
+
+    /// Report a candidate intersection at distance `hit_t` along the ray,
+    /// from an intersection shader.
+    ///
+    /// The [`HitAttribute`] globals must be written before this statement.
+    ///
+    /// [`HitAttribute`]: AddressSpace::HitAttribute
+    ReportIntersection {
+        /// The parametric distance of the intersection. Must be an `f32` scalar.
+        hit_t: Handle<Expression>,
+        /// An application defined value, visible to hit shaders through
+        /// [`BuiltIn::HitKind`]. Must be a `u32` scalar.
+        hit_kind: Handle<Expression>,
+    },
+
+    /// Reject the current candidate intersection and end the invocation.
+    ///
+    /// Only allowed in any-hit shaders.
+    IgnoreIntersection,
+
+    /// Accept the current candidate intersection, stop traversal, and end
+    /// the invocation.
+    ///
+    /// Only allowed in any-hit shaders.
+    TerminateRay,
+}
+
+/// An operation available only in task and mesh shaders.
+///
+/// See [`Statement::MeshFunction`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum MeshFunction {
+    /// Launch a grid of mesh shader workgroups and end the task shader
+    /// invocation.
+    ///
+    /// The [`TaskPayload`] global used by the entry point, if any, is passed
+    /// to the launched workgroups. Each dimension must be a `u32` scalar.
+    ///
+    /// Only allowed in the body of a task shader entry point.
+    ///
+    /// [`TaskPayload`]: AddressSpace::TaskPayload
+    EmitMeshTasks {
+        x: Handle<Expression>,
+        y: Handle<Expression>,
+        z: Handle<Expression>,
+    },
+
+    /// Set the number of vertices and primitives the mesh shader workgroup
+    /// produces. Both must be `u32` scalars.
+    ///
+    /// Only allowed in the body of a mesh shader entry point.
+    SetMeshOutputs {
+        vertex_count: Handle<Expression>,
+        primitive_count: Handle<Expression>,
+    },
+
+    /// Write the vertex at `index` of the mesh shader output.
+    ///
+    /// The `value` must have the type given by [`MeshStageInfo::vertex_output`].
+    ///
+    /// Only allowed in the body of a mesh shader entry point.
+    SetVertex {
+        index: Handle<Expression>,
+        value: Handle<Expression>,
+    },
+
+    /// Write the primitive at `index` of the mesh shader output.
+    ///
+    /// The `value` must have the type given by
+    /// [`MeshStageInfo::primitive_output`].
+    ///
+    /// Only allowed in the body of a mesh shader entry point.
+    SetPrimitive {
+        index: Handle<Expression>,
+        value: Handle<Expression>,
+    },
+}
+
 //TODO: consider removing `Clone`. It's not valid to clone `Statement::Emit` anyway.
 /// Instructions which make up an executable block.
 // Clone is used only for error reporting and is not intended for end users
@@ -1883,6 +2104,33 @@ pub enum Statement {
         array_index: Option<Handle<Expression>>,
         value: Handle<Expression>,
     },
+    /// Performs an atomic operation on a texel value of an image.
+    ///
+    /// The `image`, `coordinate`, and `array_index` fields have the same
+    /// meanings as the corresponding operands of an [`ImageLoad`] expression;
+    /// see that documentation for details. Atomics on multisampled images or
+    /// images with mipmaps are not supported, so there are no `level` or
+    /// `sample` operands.
+    ///
+    /// The image must be a [`Storage`] image with the [`ATOMIC`] access flag
+    /// and an [`R32Uint`] or [`R32Sint`] format. Only the arithmetic and
+    /// bitwise [`AtomicFunction`]s are allowed; there is no result.
+    ///
+    /// This statement is a barrier for any operations on the corresponding
+    /// [`Expression::GlobalVariable`] for this image.
+    ///
+    /// [`ImageLoad`]: Expression::ImageLoad
+    /// [`Storage`]: ImageClass::Storage
+    /// [`ATOMIC`]: StorageAccess::ATOMIC
+    /// [`R32Uint`]: StorageFormat::R32Uint
+    /// [`R32Sint`]: StorageFormat::R32Sint
+    ImageAtomic {
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        array_index: Option<Handle<Expression>>,
+        fun: AtomicFunction,
+        value: Handle<Expression>,
+    },
     /// Atomic function.
     Atomic {
         /// Pointer to an atomic value.
@@ -1930,6 +2178,21 @@ pub enum Statement {
         /// The specific operation we're performing on `query`.
         fun: RayQueryFunction,
     },
+    /// Perform an operation specific to ray tracing pipeline stages.
+    ///
+    /// [`IgnoreIntersection`] and [`TerminateRay`] end the invocation, like
+    /// [`Kill`] does for fragment shaders.
+    ///
+    /// [`IgnoreIntersection`]: RayPipelineFunction::IgnoreIntersection
+    /// [`TerminateRay`]: RayPipelineFunction::TerminateRay
+    /// [`Kill`]: Statement::Kill
+    RayPipelineFunction(RayPipelineFunction),
+    /// Perform an operation specific to task and mesh shaders.
+    ///
+    /// [`EmitMeshTasks`] ends the invocation.
+    ///
+    /// [`EmitMeshTasks`]: MeshFunction::EmitMeshTasks
+    MeshFunction(MeshFunction),
     /// Calculate a bitmask using a boolean from each active thread in the subgroup
     SubgroupBallot {
         /// The [`SubgroupBallotResult`] expression representing this load's result.
@@ -2071,13 +2334,67 @@ pub struct EntryPoint {
     pub stage: ShaderStage,
     /// Early depth test for fragment stages.
     pub early_depth_test: Option<EarlyDepthTest>,
-    /// Workgroup size for compute stages
+    /// Workgroup size for compute, task and mesh stages
+    ///
+    /// Dimensions given by [`workgroup_size_overrides`] are zero.
+    ///
+    /// [`workgroup_size_overrides`]: EntryPoint::workgroup_size_overrides
     pub workgroup_size: [u32; 3],
+    /// Workgroup size dimensions that depend on overrides.
+    ///
+    /// Each is an override-expression in [`Module::global_expressions`], whose
+    /// value replaces the corresponding `workgroup_size` entry when the
+    /// overrides are processed.
+    pub workgroup_size_overrides: Option<[Option<Handle<Expression>>; 3]>,
+    /// Output declarations for mesh stages.
+    pub mesh_info: Option<MeshStageInfo>,
     /// The entrance function.
     pub function: Function,
 }
 
-/// Return types predeclared for the frexp, modf, and atomicCompareExchangeWeak built-in functions.
+/// Kind of primitives produced by a mesh shader.
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum MeshOutputTopology {
+    Points,
+    Lines,
+    Triangles,
+}
+
+/// The outputs declared by a mesh shader entry point.
+///
+/// A mesh shader doesn't return its outputs. Instead, it writes vertices and
+/// primitives with [`SetVertex`] and [`SetPrimitive`], whose values must have
+/// the struct types given here. Every member of these structs must have a
+/// [`Binding`]. The primitive struct holds the vertex indices of each primitive
+/// in a [`TriangleIndices`], [`LineIndices`] or [`PointIndex`] member,
+/// matching the `topology`.
+///
+/// [`SetVertex`]: MeshFunction::SetVertex
+/// [`SetPrimitive`]: MeshFunction::SetPrimitive
+/// [`TriangleIndices`]: BuiltIn::TriangleIndices
+/// [`LineIndices`]: BuiltIn::LineIndices
+/// [`PointIndex`]: BuiltIn::PointIndex
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct MeshStageInfo {
+    pub topology: MeshOutputTopology,
+    /// The maximum number of vertices a workgroup may produce.
+    pub max_vertices: u32,
+    /// The maximum number of primitives a workgroup may produce.
+    pub max_primitives: u32,
+    /// The struct type of each output vertex.
+    pub vertex_output: Handle<Type>,
+    /// The struct type of each output primitive.
+    pub primitive_output: Handle<Type>,
+}
+
+/// Return types predeclared for the frexp, modf, atomicCompareExchangeWeak and
+/// texture sampling with residency built-in functions.
 ///
 /// These cannot be spelled in WGSL source.
 ///
@@ -2096,6 +2413,12 @@ pub enum PredeclaredType {
         size: Option<VectorSize>,
         width: Bytes,
     },
+    /// The result of an [`ImageSample`] with `residency` set: a struct
+    /// with a `texel` member of type `vec4<f32>` and a `resident` member of
+    /// type `bool`.
+    ///
+    /// [`ImageSample`]: Expression::ImageSample
+    SparseSampleResult,
 }
 
 /// Set of special types that can be optionally generated by the frontends.
@@ -0,0 +1,107 @@
+/*!
+Modules that were parsed and validated ahead of time.
+
+Parsing shader source is usually the most expensive part of creating a
+shader module. Applications that know their shaders at build time can instead
+parse and validate them once, for example from a build script or with the
+`naga` command line tool, store the result with [`write`], and only [`read`] it
+back at runtime.
+
+The format is tied to the exact version of Naga that wrote it, since the IR
+changes between releases; [`read`] rejects data written by any other version.
+Spans are not preserved, so errors found when validating a read module again
+can't point at the original source.
+*/
+
+use crate::{valid, Module, VERSION};
+
+const MAGIC: &[u8; 8] = b"naga-ir\0";
+
+/// A module read by [`read`].
+#[derive(Debug)]
+pub struct Precompiled {
+    pub module: Module,
+    /// The capabilities the module was validated with when it was written.
+    ///
+    /// Validating the module again with these capabilities, or more, is
+    /// expected to succeed.
+    pub capabilities: valid::Capabilities,
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Data is not a precompiled Naga module")]
+    NotPrecompiled,
+    #[error("Module was precompiled by Naga {0}, but this is Naga {VERSION}")]
+    VersionMismatch(String),
+    #[error("Failed to encode or decode the module: {0}")]
+    Encoding(String),
+}
+
+impl From<bincode::Error> for Error {
+    fn from(error: bincode::Error) -> Self {
+        Self::Encoding(error.to_string())
+    }
+}
+
+/// Encode `module`, which was validated with `capabilities`.
+pub fn write(module: &Module, capabilities: valid::Capabilities) -> Result<Vec<u8>, Error> {
+    let mut bytes = MAGIC.to_vec();
+    bincode::serialize_into(&mut bytes, VERSION)?;
+    bincode::serialize_into(&mut bytes, &capabilities.bits())?;
+    bincode::serialize_into(&mut bytes, module)?;
+    Ok(bytes)
+}
+
+/// Validate `module` with `validator` and encode it.
+pub fn compile(module: &Module, validator: &mut valid::Validator) -> Result<Vec<u8>, CompileError> {
+    validator.validate(module)?;
+    Ok(write(module, validator.capabilities())?)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompileError {
+    #[error(transparent)]
+    Validation(#[from] crate::WithSpan<valid::ValidationError>),
+    #[error(transparent)]
+    Encoding(#[from] Error),
+}
+
+/// Decode a module encoded by [`write`].
+pub fn read(bytes: &[u8]) -> Result<Precompiled, Error> {
+    let mut reader = bytes.strip_prefix(MAGIC).ok_or(Error::NotPrecompiled)?;
+    let version: String = bincode::deserialize_from(&mut reader)?;
+    if version != VERSION {
+        return Err(Error::VersionMismatch(version));
+    }
+    let capabilities = bincode::deserialize_from(&mut reader)?;
+    let module = bincode::deserialize_from(&mut reader)?;
+    Ok(Precompiled {
+        module,
+        capabilities: valid::Capabilities::from_bits_truncate(capabilities),
+    })
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+#[test]
+fn round_trip() {
+    let module = crate::front::wgsl::parse_str(
+        "
+        @compute @workgroup_size(1)
+        fn main() {}
+        ",
+    )
+    .unwrap();
+    let mut validator = valid::Validator::new(
+        valid::ValidationFlags::all(),
+        valid::Capabilities::default(),
+    );
+    let bytes = compile(&module, &mut validator).unwrap();
+
+    let precompiled = read(&bytes).unwrap();
+    assert_eq!(precompiled.capabilities, valid::Capabilities::default());
+    assert_eq!(precompiled.module.entry_points.len(), 1);
+    assert_eq!(precompiled.module.entry_points[0].name, "main");
+
+    assert!(matches!(read(&bytes[1..]), Err(Error::NotPrecompiled)));
+}
@@ -418,6 +418,10 @@ pub enum IndexableLength {
     /// Values of this type always have the given number of elements.
     Known(u32),
 
+    /// The number of elements is the value of an override, which is only
+    /// known once the overrides are processed.
+    Pending,
+
     /// The number of elements is determined at runtime.
     Dynamic,
 }
@@ -429,6 +433,7 @@ impl crate::ArraySize {
     ) -> Result<IndexableLength, IndexableLengthError> {
         Ok(match self {
             Self::Constant(length) => IndexableLength::Known(length.get()),
+            Self::Pending(_) => IndexableLength::Pending,
             Self::Dynamic => IndexableLength::Dynamic,
         })
     }
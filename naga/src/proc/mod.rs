@@ -296,6 +296,8 @@ impl super::TypeInner {
             } => {
                 let count = match size {
                     super::ArraySize::Constant(count) => count.get(),
+                    // The length is unknown until the overrides are processed
+                    super::ArraySize::Pending(_) => 0,
                     // A dynamically-sized array has to have at least one element
                     super::ArraySize::Dynamic => 1,
                 };
@@ -371,6 +373,17 @@ impl super::TypeInner {
         }
     }
 
+    /// Return `true` if `self` is an array whose length is an override.
+    pub const fn is_pending_array(&self) -> bool {
+        matches!(
+            *self,
+            Self::Array {
+                size: crate::ArraySize::Pending(_),
+                ..
+            }
+        )
+    }
+
     pub fn components(&self) -> Option<u32> {
         Some(match *self {
             Self::Vector { size, .. } => size as u32,
@@ -407,7 +420,11 @@ impl super::AddressSpace {
         match self {
             crate::AddressSpace::Function
             | crate::AddressSpace::Private
-            | crate::AddressSpace::WorkGroup => Sa::LOAD | Sa::STORE,
+            | crate::AddressSpace::WorkGroup
+            | crate::AddressSpace::RayPayload
+            | crate::AddressSpace::IncomingRayPayload
+            | crate::AddressSpace::HitAttribute
+            | crate::AddressSpace::TaskPayload => Sa::LOAD | Sa::STORE,
             crate::AddressSpace::Uniform => Sa::LOAD,
             crate::AddressSpace::Storage { access } => access,
             crate::AddressSpace::Handle => Sa::LOAD,
@@ -416,6 +433,26 @@ impl super::AddressSpace {
     }
 }
 
+impl super::ShaderStage {
+    /// Returns true if this is one of the ray tracing pipeline stages.
+    pub const fn is_ray_tracing(self) -> bool {
+        match self {
+            Self::Vertex | Self::Fragment | Self::Compute | Self::Task | Self::Mesh => false,
+            Self::RayGeneration
+            | Self::Intersection
+            | Self::AnyHit
+            | Self::ClosestHit
+            | Self::Miss => true,
+        }
+    }
+
+    /// Returns true if this stage is dispatched in workgroups, and so has a
+    /// workgroup size: compute, task and mesh shaders.
+    pub const fn uses_workgroups(self) -> bool {
+        matches!(self, Self::Compute | Self::Task | Self::Mesh)
+    }
+}
+
 impl super::MathFunction {
     pub const fn argument_count(&self) -> usize {
         match *self {
@@ -28,13 +28,27 @@ pub fn ensure_block_returns(block: &mut crate::Block) {
                 }
             }
         }
-        Some(&mut (S::Emit(_) | S::Break | S::Continue | S::Return { .. } | S::Kill)) => (),
+        Some(
+            &mut (S::Emit(_)
+            | S::Break
+            | S::Continue
+            | S::Return { .. }
+            | S::Kill
+            | S::RayPipelineFunction(
+                crate::RayPipelineFunction::IgnoreIntersection
+                | crate::RayPipelineFunction::TerminateRay,
+            )
+            | S::MeshFunction(crate::MeshFunction::EmitMeshTasks { .. })),
+        ) => (),
         Some(
             &mut (S::Loop { .. }
             | S::Store { .. }
             | S::ImageStore { .. }
+            | S::ImageAtomic { .. }
             | S::Call { .. }
             | S::RayQuery { .. }
+            | S::RayPipelineFunction(_)
+            | S::MeshFunction(_)
             | S::Atomic { .. }
             | S::WorkGroupUniformLoad { .. }
             | S::SubgroupBallot { .. }
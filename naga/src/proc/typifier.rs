@@ -458,6 +458,15 @@ impl<'a> ResolveContext<'a> {
                     return Err(ResolveError::InvalidPointer(pointer));
                 }
             },
+            crate::Expression::ImageSample {
+                residency: true, ..
+            } => TypeResolution::Handle(
+                *self
+                    .special_types
+                    .predeclared_types
+                    .get(&crate::PredeclaredType::SparseSampleResult)
+                    .ok_or(ResolveError::MissingSpecialType)?,
+            ),
             crate::Expression::ImageSample {
                 image,
                 gather: Some(_),
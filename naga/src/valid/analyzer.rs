@@ -131,6 +131,8 @@ bitflags::bitflags! {
         const WRITE = 0x2;
         /// The information about the data is queried.
         const QUERY = 0x4;
+        /// Atomic operations will be performed on the variable.
+        const ATOMIC = 0x8;
     }
 }
 
@@ -158,20 +160,23 @@ pub struct ExpressionInfo {
     /// expression's value.
     pub ref_count: usize,
 
-    /// The global variable into which this expression produces a pointer.
+    /// The global variable or pointer argument into which this expression
+    /// produces a pointer.
     ///
     /// This is `None` unless this expression is either a
-    /// [`GlobalVariable`], or an [`Access`] or [`AccessIndex`] that
-    /// ultimately refers to some part of a global.
+    /// [`GlobalVariable`], a pointer-typed [`FunctionArgument`], or an
+    /// [`Access`] or [`AccessIndex`] that ultimately refers to some part of
+    /// one of those.
     ///
     /// [`Load`] expressions applied to pointer-typed arguments could
     /// refer to globals, but we leave this as `None` for them.
     ///
     /// [`GlobalVariable`]: crate::Expression::GlobalVariable
+    /// [`FunctionArgument`]: crate::Expression::FunctionArgument
     /// [`Access`]: crate::Expression::Access
     /// [`AccessIndex`]: crate::Expression::AccessIndex
     /// [`Load`]: crate::Expression::Load
-    assignable_global: Option<Handle<crate::GlobalVariable>>,
+    assignable_global: Option<GlobalOrArgument>,
 
     /// The type of this expression.
     pub ty: TypeResolution,
@@ -264,6 +269,13 @@ pub struct FunctionInfo {
     /// its usage information.
     global_uses: Box<[GlobalUse]>,
 
+    /// How this function and its callees use the values its pointer
+    /// arguments point to, indexed by argument.
+    ///
+    /// When analyzing a function call, these uses are added to whatever the
+    /// caller's arguments point to.
+    argument_uses: Box<[GlobalUse]>,
+
     /// Information about each expression in this function's body.
     ///
     /// This is indexed by `Handle<Expression>` indices. However, `FunctionInfo`
@@ -288,6 +300,11 @@ pub struct FunctionInfo {
 
     /// Indicates that the function is using dual source blending.
     pub dual_source_blending: bool,
+
+    /// Indicates that the function or one of its callees computes
+    /// derivatives, either explicitly or by sampling an image with an implicit
+    /// level of detail.
+    pub uses_derivatives: bool,
 }
 
 impl FunctionInfo {
@@ -351,11 +368,25 @@ impl FunctionInfo {
     ) -> NonUniformResult {
         let info = &mut self.expressions[expr.index()];
         info.ref_count += 1;
-        // mark the used global as read
-        if let Some(global) = info.assignable_global {
-            self.global_uses[global.index()] |= global_use;
+        let non_uniform_result = info.uniformity.non_uniform_result;
+        // mark the global or argument this points into as used
+        let target = info.assignable_global;
+        self.add_target_use(target, global_use);
+        non_uniform_result
+    }
+
+    /// Add `global_use` to the uses of the global or pointer argument
+    /// `target`, if any.
+    fn add_target_use(&mut self, target: Option<GlobalOrArgument>, global_use: GlobalUse) {
+        match target {
+            Some(GlobalOrArgument::Global(global)) => {
+                self.global_uses[global.index()] |= global_use
+            }
+            Some(GlobalOrArgument::Argument(index)) => {
+                self.argument_uses[index as usize] |= global_use
+            }
+            None => {}
         }
-        info.uniformity.non_uniform_result
     }
 
     /// Record a use of `expr` for its value.
@@ -387,7 +418,7 @@ impl FunctionInfo {
     fn add_assignable_ref(
         &mut self,
         expr: Handle<crate::Expression>,
-        assignable_global: &mut Option<Handle<crate::GlobalVariable>>,
+        assignable_global: &mut Option<GlobalOrArgument>,
     ) -> NonUniformResult {
         let info = &mut self.expressions[expr.index()];
         info.ref_count += 1;
@@ -453,11 +484,20 @@ impl FunctionInfo {
             }
         }
 
+        self.uses_derivatives |= callee.uses_derivatives;
+
         // Inherit global use from our callees.
         for (mine, other) in self.global_uses.iter_mut().zip(callee.global_uses.iter()) {
             *mine |= *other;
         }
 
+        // Whatever our callees do through their pointer arguments, they do
+        // to whatever we passed them.
+        for (&argument, &argument_use) in arguments.iter().zip(callee.argument_uses.iter()) {
+            let target = self.expressions[argument.index()].assignable_global;
+            self.add_target_use(target, argument_use);
+        }
+
         Ok(FunctionUniformity {
             result: callee.uniformity.clone(),
             exit: if callee.may_kill {
@@ -587,6 +627,13 @@ impl FunctionInfo {
             // depends on the builtin or interpolation
             E::FunctionArgument(index) => {
                 let arg = &resolve_context.arguments[index as usize];
+                if resolve_context.types[arg.ty]
+                    .inner
+                    .pointer_space()
+                    .is_some()
+                {
+                    assignable_global = Some(GlobalOrArgument::Argument(index));
+                }
                 let uniform = match arg.binding {
                     Some(crate::Binding::BuiltIn(built_in)) => match built_in {
                         // per-polygon built-ins are uniform
@@ -612,18 +659,20 @@ impl FunctionInfo {
             // depends on the address space
             E::GlobalVariable(gh) => {
                 use crate::AddressSpace as As;
-                assignable_global = Some(gh);
+                assignable_global = Some(GlobalOrArgument::Global(gh));
                 let var = &resolve_context.global_vars[gh];
                 let uniform = match var.space {
                     // local data is non-uniform
                     As::Function | As::Private => false,
                     // workgroup memory is exclusively accessed by the group
-                    As::WorkGroup => true,
+                    As::WorkGroup | As::TaskPayload => true,
                     // uniform data
                     As::Uniform | As::PushConstant => true,
                     // storage data is only uniform when read-only
                     As::Storage { access } => !access.contains(crate::StorageAccess::STORE),
                     As::Handle => false,
+                    // payloads and attributes are private to each invocation
+                    As::RayPayload | As::IncomingRayPayload | As::HitAttribute => false,
                 };
                 Uniformity {
                     non_uniform_result: if uniform { None } else { Some(handle) },
@@ -647,6 +696,7 @@ impl FunctionInfo {
                 offset: _,
                 level,
                 depth_ref,
+                residency: _,
             } => {
                 let image_storage = GlobalOrArgument::from_expression(expression_arena, image)?;
                 let sampler_storage = GlobalOrArgument::from_expression(expression_arena, sampler)?;
@@ -671,6 +721,9 @@ impl FunctionInfo {
                     Sl::Gradient { x, y } => self.add_ref(x).or(self.add_ref(y)),
                 };
                 let dref_nur = depth_ref.and_then(|h| self.add_ref(h));
+                if level.implicit_derivatives() {
+                    self.uses_derivatives = true;
+                }
                 Uniformity {
                     non_uniform_result: self
                         .add_ref(image)
@@ -736,11 +789,14 @@ impl FunctionInfo {
                 requirements: UniformityRequirements::empty(),
             },
             // explicit derivatives require uniform
-            E::Derivative { expr, .. } => Uniformity {
-                //Note: taking a derivative of a uniform doesn't make it non-uniform
-                non_uniform_result: self.add_ref(expr),
-                requirements: UniformityRequirements::DERIVATIVE,
-            },
+            E::Derivative { expr, .. } => {
+                self.uses_derivatives = true;
+                Uniformity {
+                    //Note: taking a derivative of a uniform doesn't make it non-uniform
+                    non_uniform_result: self.add_ref(expr),
+                    requirements: UniformityRequirements::DERIVATIVE,
+                }
+            }
             E::Relational { argument, .. } => Uniformity {
                 non_uniform_result: self.add_ref(argument),
                 requirements: UniformityRequirements::empty(),
@@ -1000,6 +1056,24 @@ impl FunctionInfo {
                     let _ = self.add_ref(value);
                     FunctionUniformity::new()
                 }
+                S::ImageAtomic {
+                    image,
+                    coordinate,
+                    array_index,
+                    fun,
+                    value,
+                } => {
+                    let _ = self.add_ref_impl(image, GlobalUse::ATOMIC);
+                    if let Some(expr) = array_index {
+                        let _ = self.add_ref(expr);
+                    }
+                    let _ = self.add_ref(coordinate);
+                    if let crate::AtomicFunction::Exchange { compare: Some(cmp) } = fun {
+                        let _ = self.add_ref(cmp);
+                    }
+                    let _ = self.add_ref(value);
+                    FunctionUniformity::new()
+                }
                 S::Call {
                     function,
                     ref arguments,
@@ -1037,6 +1111,65 @@ impl FunctionInfo {
                     }
                     FunctionUniformity::new()
                 }
+                S::RayPipelineFunction(ref fun) => {
+                    match *fun {
+                        crate::RayPipelineFunction::TraceRay {
+                            acceleration_structure,
+                            descriptor,
+                            sbt_record_offset,
+                            sbt_record_stride,
+                            miss_index,
+                            payload,
+                        } => {
+                            let _ = self.add_ref(acceleration_structure);
+                            let _ = self.add_ref(descriptor);
+                            let _ = self.add_ref(sbt_record_offset);
+                            let _ = self.add_ref(sbt_record_stride);
+                            let _ = self.add_ref(miss_index);
+                            let _ = self.add_ref_impl(payload, GlobalUse::READ | GlobalUse::WRITE);
+                        }
+                        crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                            let _ = self.add_ref(hit_t);
+                            let _ = self.add_ref(hit_kind);
+                        }
+                        crate::RayPipelineFunction::IgnoreIntersection
+                        | crate::RayPipelineFunction::TerminateRay => {}
+                    }
+                    FunctionUniformity::new()
+                }
+                S::MeshFunction(ref fun) => {
+                    // Emitting tasks and sizing the outputs are workgroup-wide
+                    // operations, and need uniform control flow like barriers.
+                    let requirements = match *fun {
+                        crate::MeshFunction::EmitMeshTasks { x, y, z } => {
+                            let _ = self.add_ref(x);
+                            let _ = self.add_ref(y);
+                            let _ = self.add_ref(z);
+                            UniformityRequirements::WORK_GROUP_BARRIER
+                        }
+                        crate::MeshFunction::SetMeshOutputs {
+                            vertex_count,
+                            primitive_count,
+                        } => {
+                            let _ = self.add_ref(vertex_count);
+                            let _ = self.add_ref(primitive_count);
+                            UniformityRequirements::WORK_GROUP_BARRIER
+                        }
+                        crate::MeshFunction::SetVertex { index, value }
+                        | crate::MeshFunction::SetPrimitive { index, value } => {
+                            let _ = self.add_ref(index);
+                            let _ = self.add_ref(value);
+                            UniformityRequirements::empty()
+                        }
+                    };
+                    FunctionUniformity {
+                        result: Uniformity {
+                            non_uniform_result: None,
+                            requirements,
+                        },
+                        exit: ExitFlags::empty(),
+                    }
+                }
                 S::SubgroupBallot {
                     result: _,
                     predicate,
@@ -1062,12 +1195,13 @@ impl FunctionInfo {
                 } => {
                     let _ = self.add_ref(argument);
                     match mode {
-                        crate::GatherMode::BroadcastFirst => {}
+                        crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                         crate::GatherMode::Broadcast(index)
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             let _ = self.add_ref(index);
                         }
                     }
@@ -1111,9 +1245,11 @@ impl ModuleInfo {
             may_kill: false,
             sampling_set: crate::FastHashSet::default(),
             global_uses: vec![GlobalUse::empty(); module.global_variables.len()].into_boxed_slice(),
+            argument_uses: vec![GlobalUse::empty(); fun.arguments.len()].into_boxed_slice(),
             expressions: vec![ExpressionInfo::new(); fun.expressions.len()].into_boxed_slice(),
             sampling: crate::FastHashSet::default(),
             dual_source_blending: false,
+            uses_derivatives: false,
         };
         let resolve_context =
             ResolveContext::with_locals(module, &fun.local_variables, &fun.arguments);
@@ -1224,9 +1360,11 @@ fn uniform_control_flow() {
         may_kill: false,
         sampling_set: crate::FastHashSet::default(),
         global_uses: vec![GlobalUse::empty(); global_var_arena.len()].into_boxed_slice(),
+        argument_uses: Box::new([]),
         expressions: vec![ExpressionInfo::new(); expressions.len()].into_boxed_slice(),
         sampling: crate::FastHashSet::default(),
         dual_source_blending: false,
+        uses_derivatives: false,
     };
     let resolve_context = ResolveContext {
         constants: &Arena::new(),
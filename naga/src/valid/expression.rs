@@ -100,6 +100,8 @@ pub enum ExpressionError {
     InvalidDepthSampleLevel,
     #[error("Gather level can only be Zero")]
     InvalidGatherLevel,
+    #[error("Residency can only be reported when sampling a non-depth image without gathering")]
+    InvalidResidencySample,
     #[error("Gather component {0:?} doesn't exist in the image")]
     InvalidGatherComponent(crate::SwizzleComponent),
     #[error("Gather can't be done for image dimension {0:?}")]
@@ -401,6 +403,7 @@ impl super::Validator {
                 offset,
                 level,
                 depth_ref,
+                residency,
             } => {
                 // check the validity of expressions
                 let image_ty = Self::global_var_ty(module, function, image)?;
@@ -508,6 +511,20 @@ impl super::Validator {
                     }
                 }
 
+                if residency {
+                    if !self
+                        .capabilities
+                        .contains(super::Capabilities::SPARSE_RESIDENCY)
+                    {
+                        return Err(ExpressionError::MissingCapabilities(
+                            super::Capabilities::SPARSE_RESIDENCY,
+                        ));
+                    }
+                    if image_depth || gather.is_some() {
+                        return Err(ExpressionError::InvalidResidencySample);
+                    }
+                }
+
                 if let Some(component) = gather {
                     match dim {
                         crate::ImageDimension::D2 | crate::ImageDimension::Cube => {}
@@ -530,7 +547,7 @@ impl super::Validator {
 
                 // check level properties
                 match level {
-                    crate::SampleLevel::Auto => ShaderStages::FRAGMENT,
+                    crate::SampleLevel::Auto => self.derivative_stages(),
                     crate::SampleLevel::Zero => ShaderStages::all(),
                     crate::SampleLevel::Exact(expr) => {
                         match resolver[expr] {
@@ -548,7 +565,7 @@ impl super::Validator {
                             }) => {}
                             _ => return Err(ExpressionError::InvalidSampleLevelBiasType(expr)),
                         }
-                        ShaderStages::FRAGMENT
+                        self.derivative_stages()
                     }
                     crate::SampleLevel::Gradient { x, y } => {
                         match resolver[x] {
@@ -916,7 +933,7 @@ impl super::Validator {
                     } => {}
                     _ => return Err(ExpressionError::InvalidDerivative),
                 }
-                ShaderStages::FRAGMENT
+                self.derivative_stages()
             }
             E::Relational { fun, argument } => {
                 use crate::RelationalFunction as Rf;
@@ -1598,7 +1615,7 @@ impl super::Validator {
                     // WorkGroupUniformLoad
                     .contains(TypeFlags::SIZED | TypeFlags::CONSTRUCTIBLE)
                 {
-                    ShaderStages::COMPUTE
+                    ShaderStages::COMPUTE | ShaderStages::TASK | ShaderStages::MESH
                 } else {
                     return Err(ExpressionError::InvalidWorkGroupUniformLoadResultType(ty));
                 }
@@ -1646,6 +1663,18 @@ impl super::Validator {
         Ok(stages)
     }
 
+    /// The stages in which derivatives can be computed.
+    fn derivative_stages(&self) -> ShaderStages {
+        if self
+            .capabilities
+            .contains(super::Capabilities::COMPUTE_DERIVATIVES)
+        {
+            ShaderStages::FRAGMENT | ShaderStages::COMPUTE
+        } else {
+            ShaderStages::FRAGMENT
+        }
+    }
+
     fn global_var_ty(
         module: &crate::Module,
         function: &crate::Function,
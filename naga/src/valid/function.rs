@@ -58,6 +58,8 @@ pub enum SubgroupError {
     UnsupportedOperation(super::SubgroupOperationSet),
     #[error("Unknown operation")]
     UnknownOperation,
+    #[error("Cluster size {0} is not a power of two")]
+    InvalidClusterSize(u32),
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -132,6 +134,12 @@ pub enum FunctionError {
     },
     #[error("Image store parameters are invalid")]
     InvalidImageStore(#[source] ExpressionError),
+    #[error("Image atomic parameters are invalid")]
+    InvalidImageAtomic(#[source] ExpressionError),
+    #[error("Image atomic function {0:?} is not supported")]
+    InvalidImageAtomicFunction(crate::AtomicFunction),
+    #[error("Image atomic value {0:?} does not match the image format")]
+    InvalidImageAtomicValue(Handle<crate::Expression>),
     #[error("Call to {function:?} is invalid")]
     InvalidCall {
         function: Handle<crate::Function>,
@@ -148,6 +156,14 @@ pub enum FunctionError {
     InvalidRayDescriptor(Handle<crate::Expression>),
     #[error("Ray Query {0:?} does not have a matching type")]
     InvalidRayQueryType(Handle<crate::Type>),
+    #[error("Ray pipeline operand {0:?} does not have the expected type")]
+    InvalidRayPipelineOperand(Handle<crate::Expression>),
+    #[error("Ray payload {0:?} is not a pointer to a global in the `RayPayload` address space")]
+    InvalidRayPayload(Handle<crate::Expression>),
+    #[error("Mesh shader operand {0:?} does not have the expected type")]
+    InvalidMeshFunctionOperand(Handle<crate::Expression>),
+    #[error("Task and mesh shader operations are only allowed in entry point functions")]
+    MeshFunctionOutsideEntryPoint,
     #[error("Shader requires capability {0:?}")]
     MissingCapability(super::Capabilities),
     #[error(
@@ -203,6 +219,7 @@ struct BlockContext<'a> {
     special_types: &'a crate::SpecialTypes,
     prev_infos: &'a [FunctionInfo],
     return_type: Option<Handle<crate::Type>>,
+    entry_point: bool,
 }
 
 impl<'a> BlockContext<'a> {
@@ -211,6 +228,7 @@ impl<'a> BlockContext<'a> {
         module: &'a crate::Module,
         info: &'a FunctionInfo,
         prev_infos: &'a [FunctionInfo],
+        entry_point: bool,
     ) -> Self {
         Self {
             abilities: ControlFlowAbility::RETURN,
@@ -223,6 +241,7 @@ impl<'a> BlockContext<'a> {
             special_types: &module.special_types,
             prev_infos,
             return_type: fun.result.as_ref().map(|fr| fr.ty),
+            entry_point,
         }
     }
 
@@ -467,6 +486,16 @@ impl super::Validator {
                 | sg::Xor,
             ) => {}
             (co::InclusiveScan | co::ExclusiveScan, sg::Add | sg::Mul) => {}
+            (
+                co::ClusteredReduce(cluster_size),
+                sg::Add | sg::Mul | sg::Min | sg::Max | sg::And | sg::Or | sg::Xor,
+            ) => {
+                if !cluster_size.is_power_of_two() {
+                    return Err(SubgroupError::InvalidClusterSize(cluster_size)
+                        .with_span()
+                        .into_other());
+                }
+            }
 
             (_, _) => {
                 return Err(SubgroupError::UnknownOperation.with_span().into_other());
@@ -493,12 +522,13 @@ impl super::Validator {
         context: &BlockContext,
     ) -> Result<(), WithSpan<FunctionError>> {
         match *mode {
-            crate::GatherMode::BroadcastFirst => {}
+            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
             crate::GatherMode::Broadcast(index)
             | crate::GatherMode::Shuffle(index)
             | crate::GatherMode::ShuffleDown(index)
             | crate::GatherMode::ShuffleUp(index)
-            | crate::GatherMode::ShuffleXor(index) => {
+            | crate::GatherMode::ShuffleXor(index)
+            | crate::GatherMode::QuadBroadcast(index) => {
                 let index_ty = context.resolve_type(index, &self.valid_expression_set)?;
                 match *index_ty {
                     crate::TypeInner::Scalar(crate::Scalar::U32) => {}
@@ -752,7 +782,9 @@ impl super::Validator {
                     finished = true;
                 }
                 S::Barrier(barrier) => {
-                    stages &= super::ShaderStages::COMPUTE;
+                    stages &= super::ShaderStages::COMPUTE
+                        | super::ShaderStages::TASK
+                        | super::ShaderStages::MESH;
                     if barrier.contains(crate::Barrier::SUB_GROUP) {
                         if !self.capabilities.contains(
                             super::Capabilities::SUBGROUP | super::Capabilities::SUBGROUP_BARRIER,
@@ -956,6 +988,148 @@ impl super::Validator {
                             .with_span_handle(value, context.expressions));
                     }
                 }
+                S::ImageAtomic {
+                    image,
+                    coordinate,
+                    array_index,
+                    fun,
+                    value,
+                } => {
+                    if !self
+                        .capabilities
+                        .contains(super::Capabilities::TEXTURE_ATOMIC)
+                    {
+                        return Err(FunctionError::MissingCapability(
+                            super::Capabilities::TEXTURE_ATOMIC,
+                        )
+                        .with_span_static(span, "missing capability for this operation"));
+                    }
+                    match fun {
+                        crate::AtomicFunction::Add
+                        | crate::AtomicFunction::And
+                        | crate::AtomicFunction::ExclusiveOr
+                        | crate::AtomicFunction::InclusiveOr
+                        | crate::AtomicFunction::Min
+                        | crate::AtomicFunction::Max => {}
+                        crate::AtomicFunction::Subtract
+                        | crate::AtomicFunction::Exchange { .. } => {
+                            return Err(FunctionError::InvalidImageAtomicFunction(fun)
+                                .with_span_static(span, "unsupported image atomic function"));
+                        }
+                    }
+
+                    let var = match *context.get_expression(image) {
+                        crate::Expression::GlobalVariable(var_handle) => {
+                            &context.global_vars[var_handle]
+                        }
+                        // Punch through a binding array index to the global behind it.
+                        crate::Expression::Access { base, .. }
+                        | crate::Expression::AccessIndex { base, .. } => {
+                            match *context.get_expression(base) {
+                                crate::Expression::GlobalVariable(var_handle) => {
+                                    &context.global_vars[var_handle]
+                                }
+                                _ => {
+                                    return Err(FunctionError::InvalidImageAtomic(
+                                        ExpressionError::ExpectedGlobalVariable,
+                                    )
+                                    .with_span_handle(image, context.expressions))
+                                }
+                            }
+                        }
+                        _ => {
+                            return Err(FunctionError::InvalidImageAtomic(
+                                ExpressionError::ExpectedGlobalVariable,
+                            )
+                            .with_span_handle(image, context.expressions))
+                        }
+                    };
+
+                    let global_ty = match context.types[var.ty].inner {
+                        Ti::BindingArray { base, .. } => &context.types[base].inner,
+                        ref inner => inner,
+                    };
+
+                    let (class, arrayed, dim) = match *global_ty {
+                        Ti::Image {
+                            class,
+                            arrayed,
+                            dim,
+                        } => (class, arrayed, dim),
+                        _ => {
+                            return Err(FunctionError::InvalidImageAtomic(
+                                ExpressionError::ExpectedImageType(var.ty),
+                            )
+                            .with_span()
+                            .with_handle(var.ty, context.types)
+                            .with_handle(image, context.expressions))
+                        }
+                    };
+
+                    match context
+                        .resolve_type(coordinate, &self.valid_expression_set)?
+                        .image_storage_coordinates()
+                    {
+                        Some(coord_dim) if coord_dim == dim => {}
+                        _ => {
+                            return Err(FunctionError::InvalidImageAtomic(
+                                ExpressionError::InvalidImageCoordinateType(dim, coordinate),
+                            )
+                            .with_span_handle(coordinate, context.expressions));
+                        }
+                    };
+                    if arrayed != array_index.is_some() {
+                        return Err(FunctionError::InvalidImageAtomic(
+                            ExpressionError::InvalidImageArrayIndex,
+                        )
+                        .with_span_handle(coordinate, context.expressions));
+                    }
+                    if let Some(expr) = array_index {
+                        match *context.resolve_type(expr, &self.valid_expression_set)? {
+                            Ti::Scalar(crate::Scalar {
+                                kind: crate::ScalarKind::Sint | crate::ScalarKind::Uint,
+                                width: _,
+                            }) => {}
+                            _ => {
+                                return Err(FunctionError::InvalidImageAtomic(
+                                    ExpressionError::InvalidImageArrayIndexType(expr),
+                                )
+                                .with_span_handle(expr, context.expressions));
+                            }
+                        }
+                    }
+
+                    let value_kind = match class {
+                        crate::ImageClass::Storage {
+                            format: crate::StorageFormat::R32Uint,
+                            access,
+                        } if access.contains(crate::StorageAccess::ATOMIC) => {
+                            crate::ScalarKind::Uint
+                        }
+                        crate::ImageClass::Storage {
+                            format: crate::StorageFormat::R32Sint,
+                            access,
+                        } if access.contains(crate::StorageAccess::ATOMIC) => {
+                            crate::ScalarKind::Sint
+                        }
+                        _ => {
+                            return Err(FunctionError::InvalidImageAtomic(
+                                ExpressionError::InvalidImageClass(class),
+                            )
+                            .with_span_handle(image, context.expressions));
+                        }
+                    };
+
+                    if *context.resolve_type(value, &self.valid_expression_set)?
+                        != Ti::Scalar(crate::Scalar {
+                            kind: value_kind,
+                            width: 4,
+                        })
+                    {
+                        return Err(FunctionError::InvalidImageAtomicValue(value)
+                            .with_span_handle(value, context.expressions));
+                    }
+                }
                 S::Call {
                     function,
                     ref arguments,
@@ -978,7 +1152,9 @@ impl super::Validator {
                     self.validate_atomic(pointer, fun, value, result, context)?;
                 }
                 S::WorkGroupUniformLoad { pointer, result } => {
-                    stages &= super::ShaderStages::COMPUTE;
+                    stages &= super::ShaderStages::COMPUTE
+                        | super::ShaderStages::TASK
+                        | super::ShaderStages::MESH;
                     let pointer_inner =
                         context.resolve_type(pointer, &self.valid_expression_set)?;
                     match *pointer_inner {
@@ -1064,6 +1240,141 @@ impl super::Validator {
                         crate::RayQueryFunction::Terminate => {}
                     }
                 }
+                S::RayPipelineFunction(ref fun) => {
+                    if !self
+                        .capabilities
+                        .contains(super::Capabilities::RAY_TRACING_PIPELINE)
+                    {
+                        return Err(FunctionError::MissingCapability(
+                            super::Capabilities::RAY_TRACING_PIPELINE,
+                        )
+                        .with_span_static(span, "missing capability for this operation"));
+                    }
+                    match *fun {
+                        crate::RayPipelineFunction::TraceRay {
+                            acceleration_structure,
+                            descriptor,
+                            sbt_record_offset,
+                            sbt_record_stride,
+                            miss_index,
+                            payload,
+                        } => {
+                            stages &= super::ShaderStages::RAY_GENERATION
+                                | super::ShaderStages::CLOSEST_HIT
+                                | super::ShaderStages::MISS;
+                            match *context
+                                .resolve_type(acceleration_structure, &self.valid_expression_set)?
+                            {
+                                Ti::AccelerationStructure => {}
+                                _ => {
+                                    return Err(FunctionError::InvalidAccelerationStructure(
+                                        acceleration_structure,
+                                    )
+                                    .with_span_static(span, "invalid acceleration structure"))
+                                }
+                            }
+                            let desc_ty_given =
+                                context.resolve_type(descriptor, &self.valid_expression_set)?;
+                            let desc_ty_expected = context
+                                .special_types
+                                .ray_desc
+                                .map(|handle| &context.types[handle].inner);
+                            if Some(desc_ty_given) != desc_ty_expected {
+                                return Err(FunctionError::InvalidRayDescriptor(descriptor)
+                                    .with_span_static(span, "invalid ray descriptor"));
+                            }
+                            for operand in [sbt_record_offset, sbt_record_stride, miss_index] {
+                                match *context.resolve_type(operand, &self.valid_expression_set)? {
+                                    Ti::Scalar(crate::Scalar::U32) => {}
+                                    _ => {
+                                        return Err(FunctionError::InvalidRayPipelineOperand(
+                                            operand,
+                                        )
+                                        .with_span_handle(operand, context.expressions))
+                                    }
+                                }
+                            }
+                            let payload_space =
+                                match *context.resolve_type(payload, &self.valid_expression_set)? {
+                                    Ti::Pointer { space, .. } => Some(space),
+                                    _ => None,
+                                };
+                            let is_global = matches!(
+                                *context.get_expression(payload),
+                                crate::Expression::GlobalVariable(_)
+                            );
+                            if !is_global || payload_space != Some(AddressSpace::RayPayload) {
+                                return Err(FunctionError::InvalidRayPayload(payload)
+                                    .with_span_handle(payload, context.expressions));
+                            }
+                        }
+                        crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                            stages &= super::ShaderStages::INTERSECTION;
+                            match *context.resolve_type(hit_t, &self.valid_expression_set)? {
+                                Ti::Scalar(crate::Scalar::F32) => {}
+                                _ => {
+                                    return Err(FunctionError::InvalidRayPipelineOperand(hit_t)
+                                        .with_span_handle(hit_t, context.expressions))
+                                }
+                            }
+                            match *context.resolve_type(hit_kind, &self.valid_expression_set)? {
+                                Ti::Scalar(crate::Scalar::U32) => {}
+                                _ => {
+                                    return Err(FunctionError::InvalidRayPipelineOperand(hit_kind)
+                                        .with_span_handle(hit_kind, context.expressions))
+                                }
+                            }
+                        }
+                        crate::RayPipelineFunction::IgnoreIntersection
+                        | crate::RayPipelineFunction::TerminateRay => {
+                            stages &= super::ShaderStages::ANY_HIT;
+                            finished = true;
+                        }
+                    }
+                }
+                S::MeshFunction(ref fun) => {
+                    if !self.capabilities.contains(super::Capabilities::MESH_SHADER) {
+                        return Err(FunctionError::MissingCapability(
+                            super::Capabilities::MESH_SHADER,
+                        )
+                        .with_span_static(span, "missing capability for this operation"));
+                    }
+                    if !context.entry_point {
+                        return Err(FunctionError::MeshFunctionOutsideEntryPoint
+                            .with_span_static(span, "not in an entry point"));
+                    }
+                    let check_operand = |operand| -> Result<(), WithSpan<FunctionError>> {
+                        match *context.resolve_type(operand, &self.valid_expression_set)? {
+                            Ti::Scalar(crate::Scalar::U32) => Ok(()),
+                            _ => Err(FunctionError::InvalidMeshFunctionOperand(operand)
+                                .with_span_handle(operand, context.expressions)),
+                        }
+                    };
+                    // The types of the `SetVertex` and `SetPrimitive` values
+                    // depend on the entry point, and are checked along with it.
+                    match *fun {
+                        crate::MeshFunction::EmitMeshTasks { x, y, z } => {
+                            stages &= super::ShaderStages::TASK;
+                            finished = true;
+                            check_operand(x)?;
+                            check_operand(y)?;
+                            check_operand(z)?;
+                        }
+                        crate::MeshFunction::SetMeshOutputs {
+                            vertex_count,
+                            primitive_count,
+                        } => {
+                            stages &= super::ShaderStages::MESH;
+                            check_operand(vertex_count)?;
+                            check_operand(primitive_count)?;
+                        }
+                        crate::MeshFunction::SetVertex { index, value: _ }
+                        | crate::MeshFunction::SetPrimitive { index, value: _ } => {
+                            stages &= super::ShaderStages::MESH;
+                            check_operand(index)?;
+                        }
+                    }
+                }
                 S::SubgroupBallot { result, predicate } => {
                     stages &= self.subgroup_stages;
                     if !self.capabilities.contains(super::Capabilities::SUBGROUP) {
@@ -1114,7 +1425,7 @@ impl super::Validator {
                         )
                         .with_span_static(span, "missing capability for this operation"));
                     }
-                    let operation = op.required_operations();
+                    let operation = op.required_operations() | collective_op.required_operations();
                     if !self.subgroup_operations.contains(operation) {
                         return Err(FunctionError::InvalidSubgroup(
                             SubgroupError::UnsupportedOperation(operation),
@@ -1129,6 +1440,11 @@ impl super::Validator {
                     result,
                 } => {
                     stages &= self.subgroup_stages;
+                    if let crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_) =
+                        *mode
+                    {
+                        stages &= super::ShaderStages::FRAGMENT | super::ShaderStages::COMPUTE;
+                    }
                     if !self.capabilities.contains(super::Capabilities::SUBGROUP) {
                         return Err(FunctionError::MissingCapability(
                             super::Capabilities::SUBGROUP,
@@ -1221,6 +1537,10 @@ impl super::Validator {
         for (index, argument) in fun.arguments.iter().enumerate() {
             match module.types[argument.ty].inner.pointer_space() {
                 Some(crate::AddressSpace::Private | crate::AddressSpace::Function) | None => {}
+                Some(crate::AddressSpace::Storage { .. } | crate::AddressSpace::WorkGroup)
+                    if self
+                        .capabilities
+                        .contains(super::Capabilities::UNRESTRICTED_POINTER_PARAMETERS) => {}
                 Some(other) => {
                     return Err(FunctionError::InvalidArgumentPointerSpace {
                         index,
@@ -1294,7 +1614,7 @@ impl super::Validator {
             let stages = self
                 .validate_block(
                     &fun.body,
-                    &BlockContext::new(fun, module, &info, &mod_info.functions),
+                    &BlockContext::new(fun, module, &info, &mod_info.functions, entry_point),
                 )?
                 .stages;
             info.available_stages &= stages;
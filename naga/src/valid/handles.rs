@@ -55,9 +55,12 @@ impl super::Validator {
                 crate::TypeInner::Pointer { base, space: _ } => {
                     this_handle.check_dep(base)?;
                 }
-                crate::TypeInner::Array { base, .. }
-                | crate::TypeInner::BindingArray { base, .. } => {
+                crate::TypeInner::Array { base, size, .. }
+                | crate::TypeInner::BindingArray { base, size } => {
                     this_handle.check_dep(base)?;
+                    if let crate::ArraySize::Pending(override_) = size {
+                        Self::validate_override_handle(override_, overrides)?;
+                    }
                 }
                 crate::TypeInner::Struct {
                     ref members,
@@ -165,6 +168,15 @@ impl super::Validator {
         };
 
         for entry_point in entry_points.iter() {
+            if let Some(overrides) = entry_point.workgroup_size_overrides {
+                for expr in overrides.into_iter().flatten() {
+                    validate_const_expr(expr)?;
+                }
+            }
+            if let Some(ref mesh_info) = entry_point.mesh_info {
+                validate_type(mesh_info.vertex_output)?;
+                validate_type(mesh_info.primitive_output)?;
+            }
             validate_function(None, &entry_point.function)?;
         }
 
@@ -316,6 +328,7 @@ impl super::Validator {
                 offset,
                 level,
                 depth_ref,
+                residency: _,
             } => {
                 if let Some(offset) = offset {
                     validate_const_expr(offset)?;
@@ -512,6 +525,22 @@ impl super::Validator {
                 validate_expr(value)?;
                 Ok(())
             }
+            crate::Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                fun,
+                value,
+            } => {
+                validate_expr(image)?;
+                validate_expr(coordinate)?;
+                validate_expr_opt(array_index)?;
+                if let crate::AtomicFunction::Exchange { compare } = fun {
+                    validate_expr_opt(compare)?;
+                }
+                validate_expr(value)?;
+                Ok(())
+            }
             crate::Statement::Atomic {
                 pointer,
                 fun,
@@ -567,6 +596,54 @@ impl super::Validator {
                 }
                 Ok(())
             }
+            crate::Statement::RayPipelineFunction(ref fun) => {
+                match *fun {
+                    crate::RayPipelineFunction::TraceRay {
+                        acceleration_structure,
+                        descriptor,
+                        sbt_record_offset,
+                        sbt_record_stride,
+                        miss_index,
+                        payload,
+                    } => {
+                        validate_expr(acceleration_structure)?;
+                        validate_expr(descriptor)?;
+                        validate_expr(sbt_record_offset)?;
+                        validate_expr(sbt_record_stride)?;
+                        validate_expr(miss_index)?;
+                        validate_expr(payload)?;
+                    }
+                    crate::RayPipelineFunction::ReportIntersection { hit_t, hit_kind } => {
+                        validate_expr(hit_t)?;
+                        validate_expr(hit_kind)?;
+                    }
+                    crate::RayPipelineFunction::IgnoreIntersection
+                    | crate::RayPipelineFunction::TerminateRay => {}
+                }
+                Ok(())
+            }
+            crate::Statement::MeshFunction(ref fun) => {
+                match *fun {
+                    crate::MeshFunction::EmitMeshTasks { x, y, z } => {
+                        validate_expr(x)?;
+                        validate_expr(y)?;
+                        validate_expr(z)?;
+                    }
+                    crate::MeshFunction::SetMeshOutputs {
+                        vertex_count,
+                        primitive_count,
+                    } => {
+                        validate_expr(vertex_count)?;
+                        validate_expr(primitive_count)?;
+                    }
+                    crate::MeshFunction::SetVertex { index, value }
+                    | crate::MeshFunction::SetPrimitive { index, value } => {
+                        validate_expr(index)?;
+                        validate_expr(value)?;
+                    }
+                }
+                Ok(())
+            }
             crate::Statement::SubgroupBallot { result, predicate } => {
                 validate_expr_opt(predicate)?;
                 validate_expr(result)?;
@@ -589,12 +666,13 @@ impl super::Validator {
             } => {
                 validate_expr(argument)?;
                 match mode {
-                    crate::GatherMode::BroadcastFirst => {}
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                     crate::GatherMode::Broadcast(index)
                     | crate::GatherMode::Shuffle(index)
                     | crate::GatherMode::ShuffleDown(index)
                     | crate::GatherMode::ShuffleUp(index)
-                    | crate::GatherMode::ShuffleXor(index) => validate_expr(index)?,
+                    | crate::GatherMode::ShuffleXor(index)
+                    | crate::GatherMode::QuadBroadcast(index) => validate_expr(index)?,
                 }
                 validate_expr(result)?;
                 Ok(())
@@ -8,6 +8,8 @@ use crate::span::{AddSpan as _, MapErrWithSpan as _, SpanProvider as _, WithSpan
 use bit_set::BitSet;
 
 const MAX_WORKGROUP_SIZE: u32 = 0x4000;
+/// The most clip or cull distances a vertex may have, as in WGSL.
+const MAX_CLIP_DISTANCES: u32 = 8;
 
 #[derive(Clone, Debug, thiserror::Error)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -79,6 +81,8 @@ pub enum VaryingError {
     },
     #[error("Workgroup size is multi dimensional, @builtin(subgroup_id) and @builtin(subgroup_invocation_id) are not supported.")]
     InvalidMultiDimensionalSubgroupBuiltIn,
+    #[error("Mesh shader primitive outputs at location {location} must be per-primitive")]
+    MissingPerPrimitive { location: u32 },
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -94,12 +98,40 @@ pub enum EntryPointError {
     UnexpectedWorkgroupSize,
     #[error("Workgroup size is out of range")]
     OutOfRangeWorkgroupSize,
+    #[error("Workgroup size override {0:?} must be a 32-bit integer override-expression")]
+    InvalidWorkgroupSizeOverride(Handle<crate::Expression>),
     #[error("Uses operations forbidden at this stage")]
     ForbiddenStageOperations,
+    #[error("Computing derivatives requires the workgroup width and height to be multiples of 2")]
+    InvalidDerivativeWorkgroupSize,
     #[error("Global variable {0:?} is used incorrectly as {1:?}")]
     InvalidGlobalUsage(Handle<crate::GlobalVariable>, GlobalUse),
     #[error("More than 1 push constant variable is used")]
     MoreThanOnePushConstantUsed,
+    #[error("More than 1 variable in the {0:?} address space is used")]
+    MoreThanOneRayInterfaceVariableUsed(crate::AddressSpace),
+    #[error("Global variable {0:?} in the {1:?} address space is not accessible at this stage")]
+    ForbiddenRayInterfaceUsage(Handle<crate::GlobalVariable>, crate::AddressSpace),
+    #[error("More than 1 task payload variable is used")]
+    MoreThanOneTaskPayloadUsed,
+    #[error("Mesh shaders must declare their vertex and primitive outputs")]
+    MissingMeshOutputs,
+    #[error("Mesh outputs are not applicable")]
+    UnexpectedMeshOutputs,
+    #[error("Maximum mesh output vertex and primitive counts must be non-zero")]
+    OutOfRangeMeshOutputCount,
+    #[error("Task and mesh shaders can't return a value")]
+    UnexpectedResult,
+    #[error("Mesh output type {0:?} must be a struct")]
+    InvalidMeshOutputType(Handle<crate::Type>),
+    #[error("Mesh vertex output varying error")]
+    MeshVertexOutput(#[source] VaryingError),
+    #[error("Mesh primitive output varying error")]
+    MeshPrimitiveOutput(#[source] VaryingError),
+    #[error("Mesh shader primitives must have a {0:?} member matching the output topology")]
+    MissingMeshPrimitiveIndices(crate::BuiltIn),
+    #[error("Mesh output value {0:?} does not have the declared vertex or primitive type")]
+    InvalidMeshOutputValue(Handle<crate::Expression>),
     #[error("Bindings for {0:?} conflict with other resource")]
     BindingCollision(Handle<crate::GlobalVariable>),
     #[error("Argument {0} varying error")]
@@ -124,12 +156,18 @@ fn storage_usage(access: crate::StorageAccess) -> GlobalUse {
     if access.contains(crate::StorageAccess::STORE) {
         storage_usage |= GlobalUse::WRITE;
     }
+    if access.contains(crate::StorageAccess::ATOMIC) {
+        storage_usage |= GlobalUse::ATOMIC;
+    }
     storage_usage
 }
 
 struct VaryingContext<'a> {
     stage: crate::ShaderStage,
     output: bool,
+    /// Whether this is the primitive output of a mesh shader, rather than the
+    /// vertex output.
+    mesh_primitive: bool,
     second_blend_source: bool,
     types: &'a UniqueArena<crate::Type>,
     type_info: &'a Vec<super::r#type::TypeInfo>,
@@ -140,6 +178,16 @@ struct VaryingContext<'a> {
 }
 
 impl VaryingContext<'_> {
+    /// Whether this context describes the per-vertex outputs of a vertex or
+    /// mesh shader.
+    fn is_vertex_output(&self) -> bool {
+        match self.stage {
+            crate::ShaderStage::Vertex => self.output,
+            crate::ShaderStage::Mesh => self.output && !self.mesh_primitive,
+            _ => false,
+        }
+    }
+
     fn validate_impl(
         &mut self,
         ep: &crate::EntryPoint,
@@ -168,12 +216,31 @@ impl VaryingContext<'_> {
                     Bi::ClipDistance => Capabilities::CLIP_DISTANCE,
                     Bi::CullDistance => Capabilities::CULL_DISTANCE,
                     Bi::PrimitiveIndex => Capabilities::PRIMITIVE_INDEX,
+                    Bi::Barycentric => Capabilities::SHADER_BARYCENTRICS,
                     Bi::ViewIndex => Capabilities::MULTIVIEW,
                     Bi::SampleIndex => Capabilities::MULTISAMPLED_SHADING,
                     Bi::NumSubgroups
                     | Bi::SubgroupId
                     | Bi::SubgroupSize
                     | Bi::SubgroupInvocationId => Capabilities::SUBGROUP,
+                    Bi::LaunchId
+                    | Bi::LaunchSize
+                    | Bi::WorldRayOrigin
+                    | Bi::WorldRayDirection
+                    | Bi::ObjectRayOrigin
+                    | Bi::ObjectRayDirection
+                    | Bi::RayTmin
+                    | Bi::RayTCurrent
+                    | Bi::InstanceId
+                    | Bi::InstanceCustomIndex
+                    | Bi::GeometryIndex
+                    | Bi::HitKind
+                    | Bi::IncomingRayFlags
+                    | Bi::ObjectToWorld
+                    | Bi::WorldToObject => Capabilities::RAY_TRACING_PIPELINE,
+                    Bi::TriangleIndices | Bi::LineIndices | Bi::PointIndex | Bi::CullPrimitive => {
+                        Capabilities::MESH_SHADER
+                    }
                     _ => Capabilities::empty(),
                 };
                 if !self.capabilities.contains(required) {
@@ -194,16 +261,22 @@ impl VaryingContext<'_> {
                         *ty_inner == Ti::Scalar(crate::Scalar::U32),
                     ),
                     Bi::ClipDistance | Bi::CullDistance => (
-                        self.stage == St::Vertex && self.output,
+                        self.is_vertex_output(),
                         match *ty_inner {
-                            Ti::Array { base, .. } => {
+                            Ti::Array { base, size, .. } => {
                                 self.types[base].inner == Ti::Scalar(crate::Scalar::F32)
+                                    && match size {
+                                        crate::ArraySize::Constant(size) => {
+                                            size.get() <= MAX_CLIP_DISTANCES
+                                        }
+                                        _ => true,
+                                    }
                             }
                             _ => false,
                         },
                     ),
                     Bi::PointSize => (
-                        self.stage == St::Vertex && self.output,
+                        self.is_vertex_output(),
                         *ty_inner == Ti::Scalar(crate::Scalar::F32),
                     ),
                     Bi::PointCoord => (
@@ -216,9 +289,9 @@ impl VaryingContext<'_> {
                     ),
                     Bi::Position { .. } => (
                         match self.stage {
-                            St::Vertex => self.output,
+                            St::Vertex | St::Mesh => self.is_vertex_output(),
                             St::Fragment => !self.output,
-                            St::Compute => false,
+                            _ => false,
                         },
                         *ty_inner
                             == Ti::Vector {
@@ -229,7 +302,7 @@ impl VaryingContext<'_> {
                     Bi::ViewIndex => (
                         match self.stage {
                             St::Vertex | St::Fragment => !self.output,
-                            St::Compute => false,
+                            _ => false,
                         },
                         *ty_inner == Ti::Scalar(crate::Scalar::I32),
                     ),
@@ -242,7 +315,7 @@ impl VaryingContext<'_> {
                         *ty_inner == Ti::Scalar(crate::Scalar::BOOL),
                     ),
                     Bi::PrimitiveIndex => (
-                        self.stage == St::Fragment && !self.output,
+                        (self.stage == St::Fragment && !self.output) || self.mesh_primitive,
                         *ty_inner == Ti::Scalar(crate::Scalar::U32),
                     ),
                     Bi::SampleIndex => (
@@ -253,8 +326,16 @@ impl VaryingContext<'_> {
                         self.stage == St::Fragment,
                         *ty_inner == Ti::Scalar(crate::Scalar::U32),
                     ),
+                    Bi::Barycentric => (
+                        self.stage == St::Fragment && !self.output,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Tri,
+                                scalar: crate::Scalar::F32,
+                            },
+                    ),
                     Bi::LocalInvocationIndex => (
-                        self.stage == St::Compute && !self.output,
+                        self.stage.uses_workgroups() && !self.output,
                         *ty_inner == Ti::Scalar(crate::Scalar::U32),
                     ),
                     Bi::GlobalInvocationId
@@ -262,7 +343,7 @@ impl VaryingContext<'_> {
                     | Bi::WorkGroupId
                     | Bi::WorkGroupSize
                     | Bi::NumWorkGroups => (
-                        self.stage == St::Compute && !self.output,
+                        self.stage.uses_workgroups() && !self.output,
                         *ty_inner
                             == Ti::Vector {
                                 size: Vs::Tri,
@@ -276,10 +357,95 @@ impl VaryingContext<'_> {
                     Bi::SubgroupSize | Bi::SubgroupInvocationId => (
                         match self.stage {
                             St::Compute | St::Fragment => !self.output,
-                            St::Vertex => false,
+                            _ => false,
                         },
                         *ty_inner == Ti::Scalar(crate::Scalar::U32),
                     ),
+                    Bi::LaunchId | Bi::LaunchSize => (
+                        self.stage.is_ray_tracing() && !self.output,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Tri,
+                                scalar: crate::Scalar::U32,
+                            },
+                    ),
+                    Bi::WorldRayOrigin | Bi::WorldRayDirection => (
+                        matches!(
+                            self.stage,
+                            St::Intersection | St::AnyHit | St::ClosestHit | St::Miss
+                        ) && !self.output,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Tri,
+                                scalar: crate::Scalar::F32,
+                            },
+                    ),
+                    Bi::ObjectRayOrigin | Bi::ObjectRayDirection => (
+                        matches!(self.stage, St::Intersection | St::AnyHit | St::ClosestHit)
+                            && !self.output,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Tri,
+                                scalar: crate::Scalar::F32,
+                            },
+                    ),
+                    Bi::RayTmin | Bi::RayTCurrent => (
+                        matches!(
+                            self.stage,
+                            St::Intersection | St::AnyHit | St::ClosestHit | St::Miss
+                        ) && !self.output,
+                        *ty_inner == Ti::Scalar(crate::Scalar::F32),
+                    ),
+                    Bi::IncomingRayFlags => (
+                        matches!(
+                            self.stage,
+                            St::Intersection | St::AnyHit | St::ClosestHit | St::Miss
+                        ) && !self.output,
+                        *ty_inner == Ti::Scalar(crate::Scalar::U32),
+                    ),
+                    Bi::InstanceId | Bi::InstanceCustomIndex | Bi::GeometryIndex => (
+                        matches!(self.stage, St::Intersection | St::AnyHit | St::ClosestHit)
+                            && !self.output,
+                        *ty_inner == Ti::Scalar(crate::Scalar::U32),
+                    ),
+                    Bi::HitKind => (
+                        matches!(self.stage, St::AnyHit | St::ClosestHit) && !self.output,
+                        *ty_inner == Ti::Scalar(crate::Scalar::U32),
+                    ),
+                    Bi::ObjectToWorld | Bi::WorldToObject => (
+                        matches!(self.stage, St::Intersection | St::AnyHit | St::ClosestHit)
+                            && !self.output,
+                        *ty_inner
+                            == Ti::Matrix {
+                                columns: Vs::Quad,
+                                rows: Vs::Tri,
+                                scalar: crate::Scalar::F32,
+                            },
+                    ),
+                    Bi::TriangleIndices => (
+                        self.mesh_primitive,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Tri,
+                                scalar: crate::Scalar::U32,
+                            },
+                    ),
+                    Bi::LineIndices => (
+                        self.mesh_primitive,
+                        *ty_inner
+                            == Ti::Vector {
+                                size: Vs::Bi,
+                                scalar: crate::Scalar::U32,
+                            },
+                    ),
+                    Bi::PointIndex => (
+                        self.mesh_primitive,
+                        *ty_inner == Ti::Scalar(crate::Scalar::U32),
+                    ),
+                    Bi::CullPrimitive => (
+                        self.mesh_primitive,
+                        *ty_inner == Ti::Scalar(crate::Scalar::BOOL),
+                    ),
                 };
 
                 if !visible {
@@ -295,7 +461,39 @@ impl VaryingContext<'_> {
                 interpolation,
                 sampling,
                 second_blend_source,
+                per_primitive,
             } => {
+                let has_locations = match self.stage {
+                    St::Vertex | St::Fragment | St::Compute => true,
+                    St::Mesh => self.output,
+                    _ => false,
+                };
+                if !has_locations {
+                    return Err(VaryingError::InvalidAttributeInStage(
+                        "location", self.stage,
+                    ));
+                }
+
+                if per_primitive {
+                    if !self.capabilities.contains(Capabilities::MESH_SHADER) {
+                        return Err(VaryingError::UnsupportedCapability(
+                            Capabilities::MESH_SHADER,
+                        ));
+                    }
+                    if !self.mesh_primitive && (self.stage != St::Fragment || self.output) {
+                        return Err(VaryingError::InvalidAttributeInStage(
+                            "per_primitive",
+                            self.stage,
+                        ));
+                    }
+                    // Nothing is interpolated across a primitive.
+                    if interpolation != Some(crate::Interpolation::Flat) {
+                        return Err(VaryingError::InvalidInterpolation);
+                    }
+                } else if self.mesh_primitive {
+                    return Err(VaryingError::MissingPerPrimitive { location });
+                }
+
                 // Only IO-shareable types may be stored in locations.
                 if !self.type_info[ty.index()]
                     .flags
@@ -342,7 +540,8 @@ impl VaryingContext<'_> {
                 let needs_interpolation = match self.stage {
                     crate::ShaderStage::Vertex => self.output,
                     crate::ShaderStage::Fragment => !self.output,
-                    crate::ShaderStage::Compute => false,
+                    crate::ShaderStage::Mesh => self.is_vertex_output(),
+                    _ => false,
                 };
 
                 // It doesn't make sense to specify a sampling when `interpolation` is `Flat`, but
@@ -533,6 +732,27 @@ impl super::Validator {
                     false,
                 )
             }
+            crate::AddressSpace::RayPayload
+            | crate::AddressSpace::IncomingRayPayload
+            | crate::AddressSpace::HitAttribute => {
+                if !self
+                    .capabilities
+                    .contains(Capabilities::RAY_TRACING_PIPELINE)
+                {
+                    return Err(GlobalVariableError::UnsupportedCapability(
+                        Capabilities::RAY_TRACING_PIPELINE,
+                    ));
+                }
+                (TypeFlags::DATA | TypeFlags::SIZED, false)
+            }
+            crate::AddressSpace::TaskPayload => {
+                if !self.capabilities.contains(Capabilities::MESH_SHADER) {
+                    return Err(GlobalVariableError::UnsupportedCapability(
+                        Capabilities::MESH_SHADER,
+                    ));
+                }
+                (TypeFlags::DATA | TypeFlags::SIZED, false)
+            }
         };
 
         if !type_info.flags.contains(required_type_flags) {
@@ -591,15 +811,28 @@ impl super::Validator {
             }
         }
 
-        if ep.stage == crate::ShaderStage::Compute {
+        if ep.stage.uses_workgroups() {
+            let overrides = ep.workgroup_size_overrides.unwrap_or_default();
+            for expr in overrides.into_iter().flatten() {
+                let valid = global_expr_kind.is_const_or_override(expr)
+                    && matches!(
+                        *mod_info[expr].inner_with(&module.types),
+                        crate::TypeInner::Scalar(crate::Scalar::I32 | crate::Scalar::U32)
+                    );
+                if !valid {
+                    return Err(EntryPointError::InvalidWorkgroupSizeOverride(expr).with_span());
+                }
+            }
+            // Overridden dimensions are checked once the overrides are processed.
             if ep
                 .workgroup_size
                 .iter()
-                .any(|&s| s == 0 || s > MAX_WORKGROUP_SIZE)
+                .zip(overrides)
+                .any(|(&s, o)| o.is_none() && (s == 0 || s > MAX_WORKGROUP_SIZE))
             {
                 return Err(EntryPointError::OutOfRangeWorkgroupSize.with_span());
             }
-        } else if ep.workgroup_size != [0; 3] {
+        } else if ep.workgroup_size != [0; 3] || ep.workgroup_size_overrides.is_some() {
             return Err(EntryPointError::UnexpectedWorkgroupSize.with_span());
         }
 
@@ -614,6 +847,13 @@ impl super::Validator {
                 crate::ShaderStage::Vertex => ShaderStages::VERTEX,
                 crate::ShaderStage::Fragment => ShaderStages::FRAGMENT,
                 crate::ShaderStage::Compute => ShaderStages::COMPUTE,
+                crate::ShaderStage::RayGeneration => ShaderStages::RAY_GENERATION,
+                crate::ShaderStage::Intersection => ShaderStages::INTERSECTION,
+                crate::ShaderStage::AnyHit => ShaderStages::ANY_HIT,
+                crate::ShaderStage::ClosestHit => ShaderStages::CLOSEST_HIT,
+                crate::ShaderStage::Miss => ShaderStages::MISS,
+                crate::ShaderStage::Task => ShaderStages::TASK,
+                crate::ShaderStage::Mesh => ShaderStages::MESH,
             };
 
             if !info.available_stages.contains(stage_bit) {
@@ -621,6 +861,14 @@ impl super::Validator {
             }
         }
 
+        // Derivatives in compute shaders are computed over 2x2 quads of invocations.
+        if ep.stage == crate::ShaderStage::Compute
+            && info.uses_derivatives
+            && ep.workgroup_size[..2].iter().any(|&s| s % 2 != 0)
+        {
+            return Err(EntryPointError::InvalidDerivativeWorkgroupSize.with_span());
+        }
+
         self.location_mask.clear();
         let mut argument_built_ins = crate::FastHashSet::default();
         // TODO: add span info to function arguments
@@ -628,6 +876,7 @@ impl super::Validator {
             let mut ctx = VaryingContext {
                 stage: ep.stage,
                 output: false,
+                mesh_primitive: false,
                 second_blend_source: false,
                 types: &module.types,
                 type_info: &self.types,
@@ -642,10 +891,17 @@ impl super::Validator {
 
         self.location_mask.clear();
         if let Some(ref fr) = ep.function.result {
+            if matches!(
+                ep.stage,
+                crate::ShaderStage::Task | crate::ShaderStage::Mesh
+            ) {
+                return Err(EntryPointError::UnexpectedResult.with_span());
+            }
             let mut result_built_ins = crate::FastHashSet::default();
             let mut ctx = VaryingContext {
                 stage: ep.stage,
                 output: true,
+                mesh_primitive: false,
                 second_blend_source: false,
                 types: &module.types,
                 type_info: &self.types,
@@ -677,6 +933,17 @@ impl super::Validator {
             return Err(EntryPointError::MissingVertexOutputPosition.with_span());
         }
 
+        match (ep.stage, ep.mesh_info.as_ref()) {
+            (crate::ShaderStage::Mesh, Some(mesh_info)) => {
+                self.validate_mesh_outputs(ep, mesh_info, module, &info)?
+            }
+            (crate::ShaderStage::Mesh, None) => {
+                return Err(EntryPointError::MissingMeshOutputs.with_span())
+            }
+            (_, Some(_)) => return Err(EntryPointError::UnexpectedMeshOutputs.with_span()),
+            (_, None) => {}
+        }
+
         {
             let used_push_constants = module
                 .global_variables
@@ -691,6 +958,39 @@ impl super::Validator {
                 return Err(EntryPointError::MoreThanOnePushConstantUsed
                     .with_span_handle(handle, &module.global_variables));
             }
+
+            // Each ray tracing stage sees at most one incoming payload and
+            // one set of hit attributes.
+            for space in [
+                crate::AddressSpace::IncomingRayPayload,
+                crate::AddressSpace::HitAttribute,
+            ] {
+                let used = module
+                    .global_variables
+                    .iter()
+                    .filter(|&(_, var)| var.space == space)
+                    .map(|(handle, _)| handle)
+                    .filter(|&handle| !info[handle].is_empty());
+                #[allow(clippy::never_loop)]
+                for handle in used.skip(1) {
+                    return Err(EntryPointError::MoreThanOneRayInterfaceVariableUsed(space)
+                        .with_span_handle(handle, &module.global_variables));
+                }
+            }
+
+            // A task shader passes a single payload to the mesh shaders it
+            // launches.
+            let used_task_payloads = module
+                .global_variables
+                .iter()
+                .filter(|&(_, var)| var.space == crate::AddressSpace::TaskPayload)
+                .map(|(handle, _)| handle)
+                .filter(|&handle| !info[handle].is_empty());
+            #[allow(clippy::never_loop)]
+            for handle in used_task_payloads.skip(1) {
+                return Err(EntryPointError::MoreThanOneTaskPayloadUsed
+                    .with_span_handle(handle, &module.global_variables));
+            }
         }
 
         self.ep_resource_bindings.clear();
@@ -720,6 +1020,33 @@ impl super::Validator {
                 },
                 crate::AddressSpace::Private | crate::AddressSpace::WorkGroup => GlobalUse::all(),
                 crate::AddressSpace::PushConstant => GlobalUse::READ,
+                crate::AddressSpace::RayPayload
+                | crate::AddressSpace::IncomingRayPayload
+                | crate::AddressSpace::HitAttribute => {
+                    use crate::ShaderStage as St;
+                    let visible = match var.space {
+                        crate::AddressSpace::RayPayload => {
+                            matches!(ep.stage, St::RayGeneration | St::ClosestHit | St::Miss)
+                        }
+                        crate::AddressSpace::IncomingRayPayload => {
+                            matches!(ep.stage, St::AnyHit | St::ClosestHit | St::Miss)
+                        }
+                        _ => matches!(ep.stage, St::Intersection | St::AnyHit | St::ClosestHit),
+                    };
+                    if !visible {
+                        return Err(EntryPointError::ForbiddenRayInterfaceUsage(
+                            var_handle, var.space,
+                        )
+                        .with_span_handle(var_handle, &module.global_variables));
+                    }
+                    GlobalUse::READ | GlobalUse::WRITE
+                }
+                // Written by the task shader, read by the mesh shader.
+                crate::AddressSpace::TaskPayload => match ep.stage {
+                    crate::ShaderStage::Task => GlobalUse::READ | GlobalUse::WRITE,
+                    crate::ShaderStage::Mesh => GlobalUse::READ,
+                    _ => GlobalUse::empty(),
+                },
             };
             if !allowed_usage.contains(usage) {
                 log::warn!("\tUsage error for: {:?}", var);
@@ -744,4 +1071,132 @@ impl super::Validator {
 
         Ok(info)
     }
+
+    /// Validate the vertex and primitive outputs declared by a mesh shader,
+    /// and the values its entry point writes to them.
+    fn validate_mesh_outputs(
+        &mut self,
+        ep: &crate::EntryPoint,
+        mesh_info: &crate::MeshStageInfo,
+        module: &crate::Module,
+        info: &FunctionInfo,
+    ) -> Result<(), WithSpan<EntryPointError>> {
+        if mesh_info.max_vertices == 0 || mesh_info.max_primitives == 0 {
+            return Err(EntryPointError::OutOfRangeMeshOutputCount.with_span());
+        }
+
+        for (ty, mesh_primitive) in [
+            (mesh_info.vertex_output, false),
+            (mesh_info.primitive_output, true),
+        ] {
+            if !matches!(module.types[ty].inner, crate::TypeInner::Struct { .. }) {
+                return Err(
+                    EntryPointError::InvalidMeshOutputType(ty).with_span_handle(ty, &module.types)
+                );
+            }
+
+            self.location_mask.clear();
+            let mut built_ins = crate::FastHashSet::default();
+            let mut ctx = VaryingContext {
+                stage: ep.stage,
+                output: true,
+                mesh_primitive,
+                second_blend_source: false,
+                types: &module.types,
+                type_info: &self.types,
+                location_mask: &mut self.location_mask,
+                built_ins: &mut built_ins,
+                capabilities: self.capabilities,
+                flags: self.flags,
+            };
+            ctx.validate(ep, ty, None).map_err_inner(|e| {
+                if mesh_primitive {
+                    EntryPointError::MeshPrimitiveOutput(e).with_span()
+                } else {
+                    EntryPointError::MeshVertexOutput(e).with_span()
+                }
+            })?;
+
+            if mesh_primitive {
+                let indices = match mesh_info.topology {
+                    crate::MeshOutputTopology::Points => crate::BuiltIn::PointIndex,
+                    crate::MeshOutputTopology::Lines => crate::BuiltIn::LineIndices,
+                    crate::MeshOutputTopology::Triangles => crate::BuiltIn::TriangleIndices,
+                };
+                let mut other_indices = [
+                    crate::BuiltIn::PointIndex,
+                    crate::BuiltIn::LineIndices,
+                    crate::BuiltIn::TriangleIndices,
+                ]
+                .into_iter()
+                .filter(|&bi| bi != indices);
+                if !built_ins.contains(&indices) || other_indices.any(|bi| built_ins.contains(&bi))
+                {
+                    return Err(EntryPointError::MissingMeshPrimitiveIndices(indices)
+                        .with_span_handle(ty, &module.types));
+                }
+            } else if !built_ins.contains(&crate::BuiltIn::Position { invariant: false }) {
+                return Err(EntryPointError::MissingVertexOutputPosition
+                    .with_span_handle(ty, &module.types));
+            }
+        }
+
+        check_mesh_output_values(&ep.function.body, mesh_info, info, &ep.function.expressions)
+    }
+}
+
+/// Check that the values of all `SetVertex` and `SetPrimitive` statements in
+/// `block` have the types declared in `mesh_info`.
+fn check_mesh_output_values(
+    block: &crate::Block,
+    mesh_info: &crate::MeshStageInfo,
+    info: &FunctionInfo,
+    expressions: &crate::Arena<crate::Expression>,
+) -> Result<(), WithSpan<EntryPointError>> {
+    for statement in block.iter() {
+        match *statement {
+            crate::Statement::MeshFunction(ref fun) => {
+                let (value, expected) = match *fun {
+                    crate::MeshFunction::SetVertex { value, .. } => {
+                        (value, mesh_info.vertex_output)
+                    }
+                    crate::MeshFunction::SetPrimitive { value, .. } => {
+                        (value, mesh_info.primitive_output)
+                    }
+                    crate::MeshFunction::EmitMeshTasks { .. }
+                    | crate::MeshFunction::SetMeshOutputs { .. } => continue,
+                };
+                if info[value].ty.handle() != Some(expected) {
+                    return Err(EntryPointError::InvalidMeshOutputValue(value)
+                        .with_span_handle(value, expressions));
+                }
+            }
+            crate::Statement::Block(ref block) => {
+                check_mesh_output_values(block, mesh_info, info, expressions)?
+            }
+            crate::Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                check_mesh_output_values(accept, mesh_info, info, expressions)?;
+                check_mesh_output_values(reject, mesh_info, info, expressions)?;
+            }
+            crate::Statement::Switch { ref cases, .. } => {
+                for case in cases {
+                    check_mesh_output_values(&case.body, mesh_info, info, expressions)?;
+                }
+            }
+            crate::Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                check_mesh_output_values(body, mesh_info, info, expressions)?;
+                check_mesh_output_values(continuing, mesh_info, info, expressions)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
@@ -26,7 +26,7 @@ pub use analyzer::{ExpressionInfo, FunctionInfo, GlobalUse, Uniformity, Uniformi
 pub use compose::ComposeError;
 pub use expression::{check_literal_value, LiteralError};
 pub use expression::{ConstExpressionError, ExpressionError};
-pub use function::{CallError, FunctionError, LocalVariableError};
+pub use function::{CallError, FunctionError, LocalVariableError, SubgroupError};
 pub use interface::{EntryPointError, GlobalVariableError, VaryingError};
 pub use r#type::{Disalignment, TypeError, TypeFlags, WidthError};
 
@@ -114,6 +114,30 @@ bitflags::bitflags! {
         const SUBGROUP = 0x10000;
         /// Support for subgroup barriers.
         const SUBGROUP_BARRIER = 0x20000;
+        /// Support for atomic operations on storage textures.
+        const TEXTURE_ATOMIC = 0x40000;
+        /// Support for ray tracing pipeline stages, ray payloads and hit attributes.
+        const RAY_TRACING_PIPELINE = 0x80000;
+        /// Support for task and mesh shader stages.
+        const MESH_SHADER = 0x100000;
+        /// Support for passing pointers in the `Storage` and `WorkGroup`
+        /// address spaces to functions.
+        ///
+        /// Pointers still can't be stored in local variables.
+        const UNRESTRICTED_POINTER_PARAMETERS = 0x200000;
+        /// Support for [`BuiltIn::Barycentric`].
+        ///
+        /// [`BuiltIn::Barycentric`]: crate::BuiltIn::Barycentric
+        const SHADER_BARYCENTRICS = 0x400000;
+        /// Support for sampling with residency feedback.
+        ///
+        /// See the `residency` flag of [`Expression::ImageSample`].
+        ///
+        /// [`Expression::ImageSample`]: crate::Expression::ImageSample
+        const SPARSE_RESIDENCY = 0x800000;
+        /// Support for derivatives and implicit level of detail sampling in
+        /// compute shaders, within 2x2 quads of invocations.
+        const COMPUTE_DERIVATIVES = 0x1000000;
     }
 }
 
@@ -141,11 +165,11 @@ bitflags::bitflags! {
         const SHUFFLE = 1 << 4;
         /// shuffle up, down
         const SHUFFLE_RELATIVE = 1 << 5;
+        /// clustered reductions
+        const CLUSTERED = 1 << 6;
+        /// quad broadcast, quad swap in fragment and compute shaders
+        const QUAD_FRAGMENT_COMPUTE = 1 << 7;
         // We don't support these operations yet
-        // /// Clustered
-        // const CLUSTERED = 1 << 6;
-        // /// Quad supported
-        // const QUAD_FRAGMENT_COMPUTE = 1 << 7;
         // /// Quad supported in all stages
         // const QUAD_ALL_STAGES = 1 << 8;
     }
@@ -170,6 +194,17 @@ impl super::GatherMode {
             Self::BroadcastFirst | Self::Broadcast(_) => S::BALLOT,
             Self::Shuffle(_) | Self::ShuffleXor(_) => S::SHUFFLE,
             Self::ShuffleUp(_) | Self::ShuffleDown(_) => S::SHUFFLE_RELATIVE,
+            Self::QuadBroadcast(_) | Self::QuadSwap(_) => S::QUAD_FRAGMENT_COMPUTE,
+        }
+    }
+}
+
+impl super::CollectiveOperation {
+    const fn required_operations(&self) -> SubgroupOperationSet {
+        use SubgroupOperationSet as S;
+        match *self {
+            Self::Reduce | Self::InclusiveScan | Self::ExclusiveScan => S::empty(),
+            Self::ClusteredReduce(_) => S::CLUSTERED,
         }
     }
 }
@@ -179,10 +214,17 @@ bitflags::bitflags! {
     #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
     #[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-    pub struct ShaderStages: u8 {
+    pub struct ShaderStages: u16 {
         const VERTEX = 0x1;
         const FRAGMENT = 0x2;
         const COMPUTE = 0x4;
+        const RAY_GENERATION = 0x8;
+        const INTERSECTION = 0x10;
+        const ANY_HIT = 0x20;
+        const CLOSEST_HIT = 0x40;
+        const MISS = 0x80;
+        const TASK = 0x100;
+        const MESH = 0x200;
     }
 }
 
@@ -398,6 +440,11 @@ impl Validator {
         self
     }
 
+    /// The capabilities this validator accepts.
+    pub const fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     /// Reset the validator internals
     pub fn reset(&mut self) {
         self.types.clear();
@@ -109,6 +109,10 @@ pub enum TypeError {
     MatrixElementNotFloat,
     #[error("The constant {0:?} is specialized, and cannot be used as an array size")]
     UnsupportedSpecializedArrayLength(Handle<crate::Constant>),
+    #[error("Override-sized array {0:?} may only be the type of a `workgroup` variable")]
+    InvalidPendingArray(Handle<crate::Type>),
+    #[error("The override {0:?} must be a 32-bit integer to be used as an array size")]
+    InvalidArrayLengthOverride(Handle<crate::Override>),
     #[error("{} of dimensionality {dim:?} and class {class:?} are not supported", if *.arrayed {"Arrayed images"} else {"Images"})]
     UnsupportedImageType {
         dim: crate::ImageDimension,
@@ -189,14 +193,23 @@ fn check_member_layout(
 /// `TypeFlags::empty()`.
 ///
 /// Pointers passed as arguments to user-defined functions must be in the
-/// `Function` or `Private` address space.
-const fn ptr_space_argument_flag(space: crate::AddressSpace) -> TypeFlags {
+/// `Function` or `Private` address space, or with
+/// [`Capabilities::UNRESTRICTED_POINTER_PARAMETERS`], the `Storage` or
+/// `WorkGroup` address space.
+const fn ptr_space_argument_flag(space: crate::AddressSpace, unrestricted: bool) -> TypeFlags {
     use crate::AddressSpace as As;
     match space {
         As::Function | As::Private => TypeFlags::ARGUMENT,
-        As::Uniform | As::Storage { .. } | As::Handle | As::PushConstant | As::WorkGroup => {
-            TypeFlags::empty()
-        }
+        As::Storage { .. } | As::WorkGroup if unrestricted => TypeFlags::ARGUMENT,
+        As::Uniform
+        | As::Storage { .. }
+        | As::Handle
+        | As::PushConstant
+        | As::WorkGroup
+        | As::RayPayload
+        | As::IncomingRayPayload
+        | As::HitAttribute
+        | As::TaskPayload => TypeFlags::empty(),
     }
 }
 
@@ -401,7 +414,11 @@ impl super::Validator {
                 // space of pointer arguments explicitly before checking the
                 // `ARGUMENT` flag, to give better error messages. But it seems
                 // best to set `ARGUMENT` accurately anyway.
-                let argument_flag = ptr_space_argument_flag(space);
+                let argument_flag = ptr_space_argument_flag(
+                    space,
+                    self.capabilities
+                        .contains(Capabilities::UNRESTRICTED_POINTER_PARAMETERS),
+                );
 
                 // Pointers cannot be stored in variables, structure members, or
                 // array elements, so we do not mark them as `DATA`.
@@ -428,7 +445,11 @@ impl super::Validator {
                 // space of pointer arguments explicitly before checking the
                 // `ARGUMENT` flag, to give better error messages. But it seems
                 // best to set `ARGUMENT` accurately anyway.
-                let argument_flag = ptr_space_argument_flag(space);
+                let argument_flag = ptr_space_argument_flag(
+                    space,
+                    self.capabilities
+                        .contains(Capabilities::UNRESTRICTED_POINTER_PARAMETERS),
+                );
 
                 // Pointers cannot be stored in variables, structure members, or
                 // array elements, so we do not mark them as `DATA`.
@@ -442,6 +463,9 @@ impl super::Validator {
                 if !base_info.flags.contains(TypeFlags::DATA | TypeFlags::SIZED) {
                     return Err(TypeError::InvalidArrayBaseType(base));
                 }
+                if gctx.types[base].inner.is_pending_array() {
+                    return Err(TypeError::InvalidPendingArray(base));
+                }
 
                 let base_layout = self.layouter[base];
                 let general_alignment = base_layout.alignment;
@@ -479,6 +503,16 @@ impl super::Validator {
                             | TypeFlags::ARGUMENT
                             | TypeFlags::CONSTRUCTIBLE
                     }
+                    crate::ArraySize::Pending(override_) => {
+                        match gctx.types[gctx.overrides[override_].ty].inner {
+                            Ti::Scalar(crate::Scalar::I32 | crate::Scalar::U32) => {}
+                            _ => return Err(TypeError::InvalidArrayLengthOverride(override_)),
+                        }
+                        // The length isn't known until the overrides are
+                        // processed, so these can't be constructed or laid out
+                        // in host-shareable memory.
+                        TypeFlags::DATA | TypeFlags::SIZED | TypeFlags::COPY
+                    }
                     crate::ArraySize::Dynamic => {
                         // Non-SIZED types may only appear as the last element of a structure.
                         // This is enforced by checks for SIZED-ness for all compound types,
@@ -586,6 +620,10 @@ impl super::Validator {
                         }
                     };
 
+                    if gctx.types[member.ty].inner.is_pending_array() {
+                        return Err(TypeError::InvalidPendingArray(member.ty));
+                    }
+
                     prev_struct_data = match gctx.types[member.ty].inner {
                         crate::TypeInner::Struct { span, .. } => Some((span, member.offset)),
                         _ => None,
@@ -635,7 +673,12 @@ impl super::Validator {
             }
             Ti::Sampler { .. } => TypeInfo::new(TypeFlags::ARGUMENT, Alignment::ONE),
             Ti::AccelerationStructure => {
-                self.require_type_capability(Capabilities::RAY_QUERY)?;
+                if !self
+                    .capabilities
+                    .contains(Capabilities::RAY_TRACING_PIPELINE)
+                {
+                    self.require_type_capability(Capabilities::RAY_QUERY)?;
+                }
                 TypeInfo::new(TypeFlags::ARGUMENT, Alignment::ONE)
             }
             Ti::RayQuery => {
@@ -651,6 +694,9 @@ impl super::Validator {
                 }
                 let type_info_mask = match size {
                     crate::ArraySize::Constant(_) => TypeFlags::SIZED | TypeFlags::HOST_SHAREABLE,
+                    crate::ArraySize::Pending(_) => {
+                        return Err(TypeError::InvalidPendingArray(handle));
+                    }
                     crate::ArraySize::Dynamic => {
                         // Final type is non-sized
                         TypeFlags::HOST_SHAREABLE
@@ -0,0 +1,10 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @builtin(clip_distances) clip_distances: array<f32, 1>,
+    @builtin(cull_distances) cull_distances: array<f32, 2>,
+}
+
+@vertex
+fn main() -> VertexOutput {
+    return VertexOutput(vec4(0.0, 0.0, 0.0, 1.0), array(0.5), array(1.0, -1.0));
+}
@@ -0,0 +1,4 @@
+@fragment
+fn main(@builtin(primitive_index) primitive_index: u32) -> @location(0) vec4<f32> {
+    return vec4(f32(primitive_index), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,18 @@
+#version 330 core
+#extension GL_ARB_cull_distance : require
+struct VertexOutput {
+    vec4 position;
+    float clip_distances[1];
+    float cull_distances[2];
+};
+out float gl_ClipDistance[1];
+out float gl_CullDistance[2];
+
+void main() {
+    VertexOutput _tmp_return = VertexOutput(vec4(0.0, 0.0, 0.0, 1.0), float[1](0.5), float[2](1.0, -1.0));
+    gl_Position = _tmp_return.position;
+    gl_ClipDistance = _tmp_return.clip_distances;
+    gl_CullDistance = _tmp_return.cull_distances;
+    return;
+}
+
@@ -0,0 +1,195 @@
+#version 450 core
+#extension GL_ARB_compute_shader : require
+#extension GL_ARB_shader_storage_buffer_object : require
+#extension GL_ARB_gpu_shader_int64 : require
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+struct UniformCompatible {
+    uint val_u32_;
+    int val_i32_;
+    float val_f32_;
+    uint64_t val_u64_;
+    u64vec2 val_u64_2_;
+    u64vec3 val_u64_3_;
+    u64vec4 val_u64_4_;
+    int64_t val_i64_;
+    i64vec2 val_i64_2_;
+    i64vec3 val_i64_3_;
+    i64vec4 val_i64_4_;
+    uint64_t final_value;
+};
+struct StorageCompatible {
+    uint64_t val_u64_array_2_[2];
+    int64_t val_i64_array_2_[2];
+};
+const uint64_t constant_variable = 20UL;
+
+uniform UniformCompatible_block_0Compute { UniformCompatible _group_0_binding_0_cs; };
+
+layout(std430) readonly buffer UniformCompatible_block_1Compute { UniformCompatible _group_0_binding_1_cs; };
+
+layout(std430) readonly buffer StorageCompatible_block_2Compute { StorageCompatible _group_0_binding_2_cs; };
+
+layout(std430) buffer UniformCompatible_block_3Compute { UniformCompatible _group_0_binding_3_cs; };
+
+layout(std430) buffer StorageCompatible_block_4Compute { StorageCompatible _group_0_binding_4_cs; };
+
+
+int64_t int64_function(int64_t x) {
+    int64_t val = 20L;
+    int64_t _e6 = val;
+    val = (_e6 + (31L - 1002003004005006L));
+    int64_t _e8 = val;
+    int64_t _e11 = val;
+    val = (_e11 + (_e8 + 5L));
+    uint _e15 = _group_0_binding_0_cs.val_u32_;
+    int64_t _e16 = val;
+    int64_t _e20 = val;
+    val = (_e20 + int64_t((_e15 + uint(_e16))));
+    int _e24 = _group_0_binding_0_cs.val_i32_;
+    int64_t _e25 = val;
+    int64_t _e29 = val;
+    val = (_e29 + int64_t((_e24 + int(_e25))));
+    float _e33 = _group_0_binding_0_cs.val_f32_;
+    int64_t _e34 = val;
+    int64_t _e38 = val;
+    val = (_e38 + int64_t((_e33 + float(_e34))));
+    int64_t _e42 = _group_0_binding_0_cs.val_i64_;
+    int64_t _e45 = val;
+    val = (_e45 + i64vec3(_e42).z);
+    uint64_t _e49 = _group_0_binding_0_cs.val_u64_;
+    int64_t _e51 = val;
+    val = (_e51 + int64_t(_e49));
+    u64vec2 _e55 = _group_0_binding_0_cs.val_u64_2_;
+    int64_t _e58 = val;
+    val = (_e58 + i64vec2(_e55).y);
+    u64vec3 _e62 = _group_0_binding_0_cs.val_u64_3_;
+    int64_t _e65 = val;
+    val = (_e65 + i64vec3(_e62).z);
+    u64vec4 _e69 = _group_0_binding_0_cs.val_u64_4_;
+    int64_t _e72 = val;
+    val = (_e72 + i64vec4(_e69).w);
+    int64_t _e78 = _group_0_binding_0_cs.val_i64_;
+    int64_t _e81 = _group_0_binding_1_cs.val_i64_;
+    _group_0_binding_3_cs.val_i64_ = (_e78 + _e81);
+    i64vec2 _e87 = _group_0_binding_0_cs.val_i64_2_;
+    i64vec2 _e90 = _group_0_binding_1_cs.val_i64_2_;
+    _group_0_binding_3_cs.val_i64_2_ = (_e87 + _e90);
+    i64vec3 _e96 = _group_0_binding_0_cs.val_i64_3_;
+    i64vec3 _e99 = _group_0_binding_1_cs.val_i64_3_;
+    _group_0_binding_3_cs.val_i64_3_ = (_e96 + _e99);
+    i64vec4 _e105 = _group_0_binding_0_cs.val_i64_4_;
+    i64vec4 _e108 = _group_0_binding_1_cs.val_i64_4_;
+    _group_0_binding_3_cs.val_i64_4_ = (_e105 + _e108);
+    int64_t _e114[2] = _group_0_binding_2_cs.val_i64_array_2_;
+    _group_0_binding_4_cs.val_i64_array_2_ = _e114;
+    int64_t _e115 = val;
+    int64_t _e117 = val;
+    val = (_e117 + abs(_e115));
+    int64_t _e119 = val;
+    int64_t _e120 = val;
+    int64_t _e121 = val;
+    int64_t _e123 = val;
+    val = (_e123 + min(max(_e119, _e120), _e121));
+    int64_t _e125 = val;
+    i64vec2 _e126 = i64vec2(_e125);
+    int64_t _e127 = val;
+    i64vec2 _e128 = i64vec2(_e127);
+    int64_t _e130 = val;
+    val = (_e130 + ( + _e126.x * _e128.x + _e126.y * _e128.y));
+    int64_t _e132 = val;
+    int64_t _e133 = val;
+    int64_t _e135 = val;
+    val = (_e135 + max(_e132, _e133));
+    int64_t _e137 = val;
+    int64_t _e138 = val;
+    int64_t _e140 = val;
+    val = (_e140 + min(_e137, _e138));
+    int64_t _e142 = val;
+    int64_t _e144 = val;
+    val = (_e144 + sign(_e142));
+    int64_t _e146 = val;
+    return _e146;
+}
+
+uint64_t uint64_function(uint64_t x_1) {
+    uint64_t val_1 = 20UL;
+    uint64_t _e6 = val_1;
+    val_1 = (_e6 + (31UL + 1002003004005006UL));
+    uint64_t _e8 = val_1;
+    uint64_t _e11 = val_1;
+    val_1 = (_e11 + (_e8 + 5UL));
+    uint _e15 = _group_0_binding_0_cs.val_u32_;
+    uint64_t _e16 = val_1;
+    uint64_t _e20 = val_1;
+    val_1 = (_e20 + uint64_t((_e15 + uint(_e16))));
+    int _e24 = _group_0_binding_0_cs.val_i32_;
+    uint64_t _e25 = val_1;
+    uint64_t _e29 = val_1;
+    val_1 = (_e29 + uint64_t((_e24 + int(_e25))));
+    float _e33 = _group_0_binding_0_cs.val_f32_;
+    uint64_t _e34 = val_1;
+    uint64_t _e38 = val_1;
+    val_1 = (_e38 + uint64_t((_e33 + float(_e34))));
+    uint64_t _e42 = _group_0_binding_0_cs.val_u64_;
+    uint64_t _e45 = val_1;
+    val_1 = (_e45 + u64vec3(_e42).z);
+    int64_t _e49 = _group_0_binding_0_cs.val_i64_;
+    uint64_t _e51 = val_1;
+    val_1 = (_e51 + uint64_t(_e49));
+    i64vec2 _e55 = _group_0_binding_0_cs.val_i64_2_;
+    uint64_t _e58 = val_1;
+    val_1 = (_e58 + u64vec2(_e55).y);
+    i64vec3 _e62 = _group_0_binding_0_cs.val_i64_3_;
+    uint64_t _e65 = val_1;
+    val_1 = (_e65 + u64vec3(_e62).z);
+    i64vec4 _e69 = _group_0_binding_0_cs.val_i64_4_;
+    uint64_t _e72 = val_1;
+    val_1 = (_e72 + u64vec4(_e69).w);
+    uint64_t _e78 = _group_0_binding_0_cs.val_u64_;
+    uint64_t _e81 = _group_0_binding_1_cs.val_u64_;
+    _group_0_binding_3_cs.val_u64_ = (_e78 + _e81);
+    u64vec2 _e87 = _group_0_binding_0_cs.val_u64_2_;
+    u64vec2 _e90 = _group_0_binding_1_cs.val_u64_2_;
+    _group_0_binding_3_cs.val_u64_2_ = (_e87 + _e90);
+    u64vec3 _e96 = _group_0_binding_0_cs.val_u64_3_;
+    u64vec3 _e99 = _group_0_binding_1_cs.val_u64_3_;
+    _group_0_binding_3_cs.val_u64_3_ = (_e96 + _e99);
+    u64vec4 _e105 = _group_0_binding_0_cs.val_u64_4_;
+    u64vec4 _e108 = _group_0_binding_1_cs.val_u64_4_;
+    _group_0_binding_3_cs.val_u64_4_ = (_e105 + _e108);
+    uint64_t _e114[2] = _group_0_binding_2_cs.val_u64_array_2_;
+    _group_0_binding_4_cs.val_u64_array_2_ = _e114;
+    uint64_t _e115 = val_1;
+    uint64_t _e117 = val_1;
+    val_1 = (_e117 + uint64_t(abs(int64_t(_e115))));
+    uint64_t _e119 = val_1;
+    uint64_t _e120 = val_1;
+    uint64_t _e121 = val_1;
+    uint64_t _e123 = val_1;
+    val_1 = (_e123 + min(max(_e119, _e120), _e121));
+    uint64_t _e125 = val_1;
+    u64vec2 _e126 = u64vec2(_e125);
+    uint64_t _e127 = val_1;
+    u64vec2 _e128 = u64vec2(_e127);
+    uint64_t _e130 = val_1;
+    val_1 = (_e130 + ( + _e126.x * _e128.x + _e126.y * _e128.y));
+    uint64_t _e132 = val_1;
+    uint64_t _e133 = val_1;
+    uint64_t _e135 = val_1;
+    val_1 = (_e135 + max(_e132, _e133));
+    uint64_t _e137 = val_1;
+    uint64_t _e138 = val_1;
+    uint64_t _e140 = val_1;
+    val_1 = (_e140 + min(_e137, _e138));
+    uint64_t _e142 = val_1;
+    return _e142;
+}
+
+void main() {
+    uint64_t _e3 = uint64_function(67UL);
+    int64_t _e5 = int64_function(60L);
+    _group_0_binding_3_cs.final_value = (_e3 + uint64_t(_e5));
+    return;
+}
+
@@ -0,0 +1,15 @@
+#version 310 es
+#extension GL_EXT_geometry_shader : enable
+#extension GL_OES_geometry_shader : enable
+
+precision highp float;
+precision highp int;
+
+layout(location = 0) out vec4 _fs2p_location0;
+
+void main() {
+    uint primitive_index = uint(gl_PrimitiveID);
+    _fs2p_location0 = vec4(float(primitive_index), 0.0, 0.0, 1.0);
+    return;
+}
+
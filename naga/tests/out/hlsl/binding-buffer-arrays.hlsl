@@ -0,0 +1,47 @@
+struct UniformIndex {
+    uint index;
+};
+
+struct FragmentIn {
+    nointerpolation uint index : LOC0;
+};
+
+ByteAddressBuffer storage_array[1] : register(t0);
+cbuffer uni : register(b0, space1) { UniformIndex uni; }
+
+struct FragmentInput_main {
+    nointerpolation uint index : LOC0;
+};
+
+uint NagaBufferLength(ByteAddressBuffer buffer)
+{
+    uint ret;
+    buffer.GetDimensions(ret);
+    return ret;
+}
+
+uint main(FragmentInput_main fragmentinput_main) : SV_Target0
+{
+    FragmentIn fragment_in = { fragmentinput_main.index };
+    uint u1_ = 0u;
+
+    uint uniform_index = uni.index;
+    uint non_uniform_index = fragment_in.index;
+    uint _expr10 = asuint(storage_array[0].Load(0));
+    uint _expr11 = u1_;
+    u1_ = (_expr11 + _expr10);
+    uint _expr16 = asuint(storage_array[uniform_index].Load(0));
+    uint _expr17 = u1_;
+    u1_ = (_expr17 + _expr16);
+    uint _expr22 = asuint(storage_array[NonUniformResourceIndex(non_uniform_index)].Load(0));
+    uint _expr23 = u1_;
+    u1_ = (_expr23 + _expr22);
+    uint _expr29 = u1_;
+    u1_ = (_expr29 + ((NagaBufferLength(storage_array[0]) - 4) / 4));
+    uint _expr35 = u1_;
+    u1_ = (_expr35 + ((NagaBufferLength(storage_array[uniform_index]) - 4) / 4));
+    uint _expr41 = u1_;
+    u1_ = (_expr41 + ((NagaBufferLength(storage_array[NonUniformResourceIndex(non_uniform_index)]) - 4) / 4));
+    uint _expr43 = u1_;
+    return _expr43;
+}
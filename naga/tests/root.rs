@@ -1,4 +1,5 @@
 mod example_wgsl;
 mod snapshots;
 mod spirv_capabilities;
+mod storage_access;
 mod wgsl_errors;
@@ -616,6 +616,13 @@ fn write_output_hlsl(
             naga::ShaderStage::Vertex => &mut config.vertex,
             naga::ShaderStage::Fragment => &mut config.fragment,
             naga::ShaderStage::Compute => &mut config.compute,
+            naga::ShaderStage::RayGeneration
+            | naga::ShaderStage::Intersection
+            | naga::ShaderStage::AnyHit
+            | naga::ShaderStage::ClosestHit
+            | naga::ShaderStage::Miss
+            | naga::ShaderStage::Task
+            | naga::ShaderStage::Mesh => unreachable!(),
         }
         .push(hlsl_snapshots::ConfigItem {
             entry_point: name.clone(),
@@ -802,7 +809,7 @@ fn convert_wgsl() {
         ),
         (
             "binding-buffer-arrays",
-            Targets::WGSL | Targets::SPIRV, //TODO: more backends, eventually merge into "binding-arrays"
+            Targets::WGSL | Targets::SPIRV | Targets::HLSL, //TODO: more backends, eventually merge into "binding-arrays"
         ),
         ("resource-binding-map", Targets::METAL),
         ("multiview", Targets::SPIRV | Targets::GLSL | Targets::WGSL),
@@ -860,8 +867,10 @@ fn convert_wgsl() {
         ),
         (
             "int64",
-            Targets::SPIRV | Targets::HLSL | Targets::WGSL | Targets::METAL,
+            Targets::SPIRV | Targets::HLSL | Targets::WGSL | Targets::METAL | Targets::GLSL,
         ),
+        ("clip-cull-distances", Targets::GLSL),
+        ("primitive-index", Targets::GLSL),
         (
             "subgroup-operations",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -992,6 +1001,7 @@ fn convert_glsl_variations_check() {
             &naga::front::glsl::Options {
                 stage: naga::ShaderStage::Fragment,
                 defines: Default::default(),
+                combined_samplers: Default::default(),
             },
             &source,
         )
@@ -1027,6 +1037,7 @@ fn convert_glsl_folder() {
                         ext => panic!("Unknown extension for glsl file {ext}"),
                     },
                     defines: Default::default(),
+                    combined_samplers: Default::default(),
                 },
                 &input.read_source(),
             )
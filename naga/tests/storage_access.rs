@@ -0,0 +1,82 @@
+/*!
+Tests that front ends assign read-write, not atomic, access to storage images.
+*/
+
+#![cfg(all(
+    feature = "glsl-in",
+    feature = "spv-in",
+    feature = "spv-out",
+    feature = "wgsl-in",
+    feature = "wgsl-out"
+))]
+
+use naga::{valid, ImageClass, StorageAccess, TypeInner};
+
+const READ_WRITE: StorageAccess = StorageAccess::LOAD.union(StorageAccess::STORE);
+
+fn storage_image_access(module: &naga::Module) -> StorageAccess {
+    module
+        .global_variables
+        .iter()
+        .find_map(|(_, var)| match module.types[var.ty].inner {
+            TypeInner::Image {
+                class: ImageClass::Storage { access, .. },
+                ..
+            } => Some(access),
+            _ => None,
+        })
+        .expect("expected a storage image global")
+}
+
+fn validate(module: &naga::Module) -> valid::ModuleInfo {
+    valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(module)
+        .expect("validation failed")
+}
+
+#[test]
+fn glsl_read_write_image() {
+    let source = r#"
+        #version 450
+        layout(local_size_x = 1) in;
+        layout(r32f, binding = 0) uniform image2D img;
+        void main() {
+            imageStore(img, ivec2(0), imageLoad(img, ivec2(1)));
+        }
+    "#;
+    let module = naga::front::glsl::Frontend::default()
+        .parse(
+            &naga::front::glsl::Options::from(naga::ShaderStage::Compute),
+            source,
+        )
+        .unwrap();
+    assert_eq!(storage_image_access(&module), READ_WRITE);
+
+    let info = validate(&module);
+    let wgsl =
+        naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+            .unwrap();
+    assert!(wgsl.contains("texture_storage_2d<r32float,read_write>"));
+}
+
+#[test]
+fn spirv_read_write_image() {
+    let source = r#"
+        @group(0) @binding(0)
+        var img: texture_storage_2d<r32float, read_write>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            textureStore(img, vec2(0), textureLoad(img, vec2(1)));
+        }
+    "#;
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let info = validate(&module);
+    let words = naga::back::spv::write_vec(&module, &info, &Default::default(), None).unwrap();
+
+    let module = naga::front::spv::Frontend::new(words.into_iter(), &Default::default())
+        .parse()
+        .unwrap();
+    assert_eq!(storage_image_access(&module), READ_WRITE);
+    validate(&module);
+}
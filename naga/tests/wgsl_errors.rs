@@ -954,6 +954,559 @@ fn validation_error(
         .map_err(|e| e.into_inner()) // TODO: Add tests for spans, too?
 }
 
+#[test]
+fn subgroup_cluster_size() {
+    let source = "
+        @compute @workgroup_size(64)
+        fn main(@builtin(subgroup_invocation_id) id: u32) {
+            let sum = subgroupClusteredAdd(id, 3u);
+        }
+    ";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let result = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::SUBGROUP,
+    )
+    .subgroup_stages(naga::valid::ShaderStages::COMPUTE)
+    .subgroup_operations(naga::valid::SubgroupOperationSet::all())
+    .validate(&module)
+    .map_err(|e| e.into_inner());
+    assert!(matches!(
+        result,
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::InvalidSubgroup(
+                    naga::valid::SubgroupError::InvalidClusterSize(3)
+                )
+            ),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn clip_distances() {
+    let validate = |source: &str, capabilities| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+            .validate(&module)
+            .map_err(|e| e.into_inner())
+    };
+    let source = |count: u32| {
+        format!(
+            "
+            struct VertexOutput {{
+                @builtin(position) position: vec4<f32>,
+                @builtin(clip_distances) clip_distances: array<f32, {count}>,
+            }}
+
+            @vertex
+            fn main() -> VertexOutput {{
+                var out: VertexOutput;
+                return out;
+            }}
+            "
+        )
+    };
+
+    assert!(validate(&source(8), naga::valid::Capabilities::CLIP_DISTANCE).is_ok());
+    assert!(matches!(
+        validate(&source(8), naga::valid::Capabilities::empty()),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Result(
+                naga::valid::VaryingError::UnsupportedCapability(
+                    naga::valid::Capabilities::CLIP_DISTANCE
+                )
+            ),
+            ..
+        })
+    ));
+    assert!(matches!(
+        validate(&source(9), naga::valid::Capabilities::CLIP_DISTANCE),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Result(
+                naga::valid::VaryingError::InvalidBuiltInType(naga::BuiltIn::ClipDistance)
+            ),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn barycentric_coords() {
+    let validate = |source: &str, capabilities| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+            .validate(&module)
+            .map_err(|e| e.into_inner())
+    };
+    let source = |ty: &str| {
+        format!(
+            "
+            @fragment
+            fn main(@builtin(barycentric_coords) bary: {ty}) -> @location(0) vec4<f32> {{
+                return vec4<f32>(1.0);
+            }}
+            "
+        )
+    };
+
+    assert!(validate(
+        &source("vec3<f32>"),
+        naga::valid::Capabilities::SHADER_BARYCENTRICS
+    )
+    .is_ok());
+    assert!(matches!(
+        validate(&source("vec3<f32>"), naga::valid::Capabilities::empty()),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Argument(
+                0,
+                naga::valid::VaryingError::UnsupportedCapability(
+                    naga::valid::Capabilities::SHADER_BARYCENTRICS
+                )
+            ),
+            ..
+        })
+    ));
+    assert!(matches!(
+        validate(
+            &source("vec2<f32>"),
+            naga::valid::Capabilities::SHADER_BARYCENTRICS
+        ),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Argument(
+                0,
+                naga::valid::VaryingError::InvalidBuiltInType(naga::BuiltIn::Barycentric)
+            ),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn sample_residency() {
+    let validate = |source: &str, capabilities| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+            .validate(&module)
+            .map_err(|e| e.into_inner())
+    };
+    let source = |ty: &str| {
+        format!(
+            "
+            @group(0) @binding(0) var tex: {ty};
+            @group(0) @binding(1) var samp: sampler;
+
+            fn is_resident(uv: vec2<f32>) -> bool {{
+                let result = textureSampleLevelResidency(tex, samp, uv, 0.0);
+                return result.resident;
+            }}
+            "
+        )
+    };
+
+    assert!(validate(
+        &source("texture_2d<f32>"),
+        naga::valid::Capabilities::SPARSE_RESIDENCY
+    )
+    .is_ok());
+    assert!(matches!(
+        validate(
+            &source("texture_2d<f32>"),
+            naga::valid::Capabilities::empty()
+        ),
+        Err(naga::valid::ValidationError::Function {
+            source: naga::valid::FunctionError::Expression {
+                source: naga::valid::ExpressionError::MissingCapabilities(
+                    naga::valid::Capabilities::SPARSE_RESIDENCY
+                ),
+                ..
+            },
+            ..
+        })
+    ));
+    assert!(matches!(
+        validate(
+            &source("texture_depth_2d"),
+            naga::valid::Capabilities::SPARSE_RESIDENCY
+        ),
+        Err(naga::valid::ValidationError::Function {
+            source: naga::valid::FunctionError::Expression {
+                source: naga::valid::ExpressionError::InvalidResidencySample,
+                ..
+            },
+            ..
+        })
+    ));
+}
+
+#[test]
+fn compute_derivatives() {
+    let validate = |source: &str, capabilities| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+            .validate(&module)
+            .map_err(|e| e.into_inner())
+    };
+    let source = |size: &str| {
+        format!(
+            "
+            @group(0) @binding(0) var<storage, read_write> out: array<f32>;
+
+            @compute @workgroup_size({size})
+            fn main(@builtin(local_invocation_index) index: u32) {{
+                out[index] = dpdx(f32(index));
+            }}
+            "
+        )
+    };
+
+    assert!(validate(
+        &source("8, 8"),
+        naga::valid::Capabilities::COMPUTE_DERIVATIVES
+    )
+    .is_ok());
+    assert!(matches!(
+        validate(&source("8, 8"), naga::valid::Capabilities::empty()),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::ForbiddenStageOperations,
+            ..
+        })
+    ));
+    assert!(matches!(
+        validate(
+            &source("64"),
+            naga::valid::Capabilities::COMPUTE_DERIVATIVES
+        ),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::InvalidDerivativeWorkgroupSize,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn texture_atomics() {
+    let validate = |source: &str, capabilities| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+            .validate(&module)
+            .map_err(|e| e.into_inner())
+    };
+
+    let atomic_source = "
+        @group(0) @binding(0)
+        var image: texture_storage_2d<r32uint, atomic>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            textureAtomicOr(image, vec2(0), 1u);
+        }
+    ";
+    assert!(validate(atomic_source, naga::valid::Capabilities::TEXTURE_ATOMIC).is_ok());
+    assert!(matches!(
+        validate(atomic_source, naga::valid::Capabilities::empty()),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::MissingCapability(
+                    naga::valid::Capabilities::TEXTURE_ATOMIC
+                )
+            ),
+            ..
+        })
+    ));
+
+    let read_write_source = "
+        @group(0) @binding(0)
+        var image: texture_storage_2d<r32uint, read_write>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            textureAtomicOr(image, vec2(0), 1u);
+        }
+    ";
+    assert!(matches!(
+        validate(read_write_source, naga::valid::Capabilities::TEXTURE_ATOMIC),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::InvalidImageAtomic(
+                    naga::valid::ExpressionError::InvalidImageClass(_)
+                )
+            ),
+            ..
+        })
+    ));
+
+    let wrong_value_source = "
+        @group(0) @binding(0)
+        var image: texture_storage_2d<r32sint, atomic>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            textureAtomicMin(image, vec2(0), 1u);
+        }
+    ";
+    assert!(matches!(
+        validate(
+            wrong_value_source,
+            naga::valid::Capabilities::TEXTURE_ATOMIC
+        ),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::InvalidImageAtomicValue(_)
+            ),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn ray_tracing_pipeline() {
+    let validate = |source: &str| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::RAY_TRACING_PIPELINE,
+        )
+        .validate(&module)
+        .map_err(|e| e.into_inner())
+    };
+
+    let raygen_source = "
+        @group(0) @binding(0)
+        var acc_struct: acceleration_structure;
+        var<ray_payload> payload: vec4<f32>;
+
+        @ray_generation
+        fn main() {
+            let desc = RayDesc(0u, 0xffu, 0.1, 100.0, vec3(0.0), vec3(0.0, 0.0, 1.0));
+            traceRay(acc_struct, desc, 0u, 1u, 0u, &payload);
+        }
+    ";
+    assert!(validate(raygen_source).is_ok());
+
+    // Ray tracing operations are not available in other stages.
+    assert!(matches!(
+        validate(&raygen_source.replace("@ray_generation", "@fragment")),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::ForbiddenStageOperations,
+            ..
+        })
+    ));
+
+    // The payload passed to `traceRay` must be a `ray_payload` global.
+    let private_payload_source = raygen_source.replace("var<ray_payload>", "var<private>");
+    assert!(matches!(
+        validate(&private_payload_source),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::InvalidRayPayload(_)
+            ),
+            ..
+        })
+    ));
+
+    // Incoming payloads can't be accessed from ray generation shaders.
+    let incoming_source = "
+        var<incoming_ray_payload> incoming: vec4<f32>;
+
+        @ray_generation
+        fn main() {
+            incoming = vec4(0.0);
+        }
+    ";
+    assert!(matches!(
+        validate(incoming_source),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::ForbiddenRayInterfaceUsage(
+                _,
+                naga::AddressSpace::IncomingRayPayload
+            ),
+            ..
+        })
+    ));
+
+    // Ray tracing stages have no user-defined varyings.
+    let location_source = "
+        @miss
+        fn main(@location(0) value: f32) {}
+    ";
+    assert!(matches!(
+        validate(location_source),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Argument(
+                0,
+                naga::valid::VaryingError::InvalidAttributeInStage("location", _)
+            ),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn mesh_shader() {
+    let validate = |source: &str| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::MESH_SHADER,
+        )
+        .validate(&module)
+        .map_err(|e| e.into_inner())
+    };
+
+    let mesh_source = "
+        struct Vertex {
+            @builtin(position) position: vec4<f32>,
+        }
+
+        struct Primitive {
+            @builtin(triangle_indices) indices: vec3<u32>,
+        }
+
+        var<task_payload> payload: vec4<f32>;
+
+        @mesh @workgroup_size(1) @vertex_output(Vertex, 3) @primitive_output(Primitive, 1)
+        fn main() {
+            setMeshOutputs(3u, 1u);
+            setVertex(0u, Vertex(payload));
+            setPrimitive(0u, Primitive(vec3(0u, 1u, 2u)));
+        }
+    ";
+    assert!(validate(mesh_source).is_ok());
+
+    // Mesh shaders can only read the task payload.
+    assert!(matches!(
+        validate(&mesh_source.replace("setMeshOutputs(3u, 1u);", "payload = vec4(0.0);")),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::InvalidGlobalUsage(_, _),
+            ..
+        })
+    ));
+
+    // The primitive output must have indices matching its topology.
+    assert!(matches!(
+        validate(
+            &mesh_source
+                .replace("triangle_indices", "cull_primitive")
+                .replace("indices: vec3<u32>", "culled: bool")
+                .replace("Primitive(vec3(0u, 1u, 2u))", "Primitive(false)")
+        ),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::MissingMeshPrimitiveIndices(_),
+            ..
+        })
+    ));
+
+    // Mesh shading operations are not available in other stages.
+    assert!(matches!(
+        validate(
+            "
+            @compute @workgroup_size(1)
+            fn main() {
+                emitMeshTasks(1u, 1u, 1u);
+            }
+            "
+        ),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::ForbiddenStageOperations,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn unrestricted_pointer_parameters() {
+    let source = "
+        struct Data {
+            values: array<u32, 4>,
+        }
+
+        @group(0) @binding(0)
+        var<storage, read_write> data: Data;
+
+        var<workgroup> scratch: array<u32, 4>;
+
+        fn fill(values: ptr<storage, array<u32, 4>, read_write>, value: u32) {
+            (*values)[0] = value;
+        }
+
+        fn read(values: ptr<workgroup, array<u32, 4>>) -> u32 {
+            return (*values)[0];
+        }
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let values = &data.values;
+            fill(values, read(&scratch));
+        }
+    ";
+
+    assert!(matches!(
+        validation_error(source, naga::valid::Capabilities::default()),
+        Err(naga::valid::ValidationError::Function {
+            source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
+                space: naga::AddressSpace::Storage { .. },
+                ..
+            },
+            ..
+        })
+    ));
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::UNRESTRICTED_POINTER_PARAMETERS,
+    )
+    .validate(&module)
+    .unwrap();
+
+    // Uses through pointer arguments are attributed to the caller's globals.
+    let (data, _) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("data"))
+        .unwrap();
+    let (scratch, _) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("scratch"))
+        .unwrap();
+    let ep_info = info.get_entry_point(0);
+    assert!(ep_info[data].contains(naga::valid::GlobalUse::WRITE));
+    assert!(ep_info[scratch].contains(naga::valid::GlobalUse::READ));
+    assert!(!ep_info[scratch].contains(naga::valid::GlobalUse::WRITE));
+
+    // Pointers still can't be stored in local variables.
+    let local_source = "
+        struct Data {
+            values: array<u32, 4>,
+        }
+
+        @group(0) @binding(0)
+        var<storage, read_write> data: Data;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            var values: ptr<storage, array<u32, 4>, read_write> = &data.values;
+            (*values)[0] = 1u;
+        }
+    ";
+    assert!(matches!(
+        validation_error(
+            local_source,
+            naga::valid::Capabilities::UNRESTRICTED_POINTER_PARAMETERS
+        ),
+        Err(naga::valid::ValidationError::Function {
+            source: naga::valid::FunctionError::LocalVariable {
+                source: naga::valid::LocalVariableError::InvalidType(_),
+                ..
+            },
+            ..
+        })
+    ));
+}
+
 #[test]
 fn int64_capability() {
     check_validation! {
@@ -1116,6 +1669,46 @@ fn invalid_structs() {
     }
 }
 
+#[test]
+fn invalid_override_sized_arrays() {
+    check_validation! {
+        "
+        override n: u32 = 4;
+        struct Bad { data: array<f32, n> }
+        ",
+        "
+        override n: u32 = 4;
+        var<workgroup> bad: array<array<f32, n>, 2>;
+        ":
+        Err(naga::valid::ValidationError::Type {
+            source: naga::valid::TypeError::InvalidPendingArray(_),
+            ..
+        })
+    }
+
+    check_validation! {
+        "
+        override n: f32 = 4.0;
+        var<workgroup> bad: array<f32, n>;
+        ":
+        Err(naga::valid::ValidationError::Type {
+            source: naga::valid::TypeError::InvalidArrayLengthOverride(_),
+            ..
+        })
+    }
+
+    check_validation! {
+        "
+        override n: u32 = 4;
+        var<private> bad: array<f32, n>;
+        ":
+        Err(naga::valid::ValidationError::GlobalVariable {
+            source: naga::valid::GlobalVariableError::MissingTypeFlags { .. },
+            ..
+        })
+    }
+}
+
 #[test]
 fn invalid_functions() {
     check_validation! {
@@ -26,10 +26,29 @@ fn main() {
     //TODO: setting for the backend bits
     //TODO: setting for the target frame, or controls
 
-    let dir = match std::env::args().nth(1) {
-        Some(arg) if Path::new(&arg).is_dir() => PathBuf::from(arg),
-        _ => panic!("Provide the dir path as the parameter"),
-    };
+    let mut dir = None;
+    let mut compare = None;
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--compare" => {
+                compare = Some(PathBuf::from(
+                    args.next()
+                        .expect("Provide the replay config path after --compare"),
+                ))
+            }
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next()
+                        .expect("Provide the output dir path after --output"),
+                ))
+            }
+            _ if dir.is_none() && Path::new(&arg).is_dir() => dir = Some(PathBuf::from(arg)),
+            _ => panic!("Unexpected argument '{}'", arg),
+        }
+    }
+    let dir = dir.expect("Provide the dir path as the parameter");
 
     log::info!("Loading trace '{:?}'", dir);
     let file = fs::File::open(dir.join(trace::FILE_NAME)).unwrap();
@@ -37,6 +56,15 @@ fn main() {
     actions.reverse(); // allows us to pop from the top
     log::info!("Found {} actions", actions.len());
 
+    if let Some(config_path) = compare {
+        let global = wgc::global::Global::new("player", wgt::InstanceDescriptor::default());
+        let device = init_device(&global, actions.pop(), None);
+        actions.reverse();
+        let output = output.unwrap_or_else(|| dir.join("output"));
+        let passed = compare_to_goldens(&global, device, actions, &dir, &config_path, &output);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     #[cfg(feature = "winit")]
     let event_loop = {
         log::info!("Creating a window");
@@ -62,40 +90,10 @@ fn main() {
     }
     .unwrap();
 
-    let device = match actions.pop() {
-        Some(trace::Action::Init { desc, backend }) => {
-            log::info!("Initializing the device for backend: {:?}", backend);
-            let adapter = global
-                .request_adapter(
-                    &wgc::instance::RequestAdapterOptions {
-                        power_preference: wgt::PowerPreference::None,
-                        force_fallback_adapter: false,
-                        #[cfg(feature = "winit")]
-                        compatible_surface: Some(surface),
-                        #[cfg(not(feature = "winit"))]
-                        compatible_surface: None,
-                    },
-                    wgc::instance::AdapterInputs::IdSet(&[wgc::id::AdapterId::zip(0, 0, backend)]),
-                )
-                .expect("Unable to find an adapter for selected backend");
-
-            let info = gfx_select!(adapter => global.adapter_get_info(adapter)).unwrap();
-            log::info!("Picked '{}'", info.name);
-            let id = wgc::id::Id::zip(1, 0, backend);
-            let (_, _, error) = gfx_select!(adapter => global.adapter_request_device(
-                adapter,
-                &desc,
-                None,
-                Some(id),
-                Some(id.into_queue_id())
-            ));
-            if let Some(e) = error {
-                panic!("{:?}", e);
-            }
-            id
-        }
-        _ => panic!("Expected Action::Init"),
-    };
+    #[cfg(feature = "winit")]
+    let device = init_device(&global, actions.pop(), Some(surface));
+    #[cfg(not(feature = "winit"))]
+    let device = init_device(&global, actions.pop(), None);
 
     log::info!("Executing actions");
     #[cfg(not(feature = "winit"))]
@@ -197,5 +195,132 @@ fn main() {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn init_device(
+    global: &wgc::global::Global,
+    action: Option<wgc::device::trace::Action>,
+    compatible_surface: Option<wgc::id::SurfaceId>,
+) -> wgc::id::DeviceId {
+    use wgc::{device::trace, gfx_select};
+
+    match action {
+        Some(trace::Action::Init { desc, backend }) => {
+            log::info!("Initializing the device for backend: {:?}", backend);
+            let adapter = global
+                .request_adapter(
+                    &wgc::instance::RequestAdapterOptions {
+                        power_preference: wgt::PowerPreference::None,
+                        force_fallback_adapter: false,
+                        compatible_surface,
+                    },
+                    wgc::instance::AdapterInputs::IdSet(&[wgc::id::AdapterId::zip(0, 0, backend)]),
+                )
+                .expect("Unable to find an adapter for selected backend");
+
+            let info = gfx_select!(adapter => global.adapter_get_info(adapter)).unwrap();
+            log::info!("Picked '{}'", info.name);
+            let id = wgc::id::Id::zip(1, 0, backend);
+            let (_, _, error) = gfx_select!(adapter => global.adapter_request_device(
+                adapter,
+                &desc,
+                None,
+                Some(id),
+                Some(id.into_queue_id())
+            ));
+            if let Some(e) = error {
+                panic!("{:?}", e);
+            }
+            id
+        }
+        _ => panic!("Expected Action::Init"),
+    }
+}
+
+/// Replays the trace without a window, and compares the captures listed in
+/// the replay config at `config_path` to the goldens next to it.
+///
+/// The captured images, and the differences of those that don't match their
+/// goldens, are saved to `output`. Returns whether all the captures match.
+#[cfg(not(target_arch = "wasm32"))]
+fn compare_to_goldens(
+    global: &wgc::global::Global,
+    device: wgc::id::DeviceId,
+    actions: Vec<wgc::device::trace::Action>,
+    dir: &std::path::Path,
+    config_path: &std::path::Path,
+    output: &std::path::Path,
+) -> bool {
+    use player::{headless, GlobalPlay as _};
+    use wgc::gfx_select;
+
+    let file = std::fs::File::open(config_path).unwrap();
+    let config: headless::ReplayConfig = ron::de::from_reader(file).unwrap();
+    let goldens = config_path.parent().unwrap();
+    std::fs::create_dir_all(output).unwrap();
+
+    log::info!("Executing actions");
+    let mut command_buffer_id_manager = wgc::identity::IdentityManager::new();
+    let images = gfx_select!(device => global.replay_headless(
+        device,
+        actions,
+        dir,
+        &config.captures,
+        &mut command_buffer_id_manager
+    ));
+    gfx_select!(device => global.device_poll(device, wgt::Maintain::wait())).unwrap();
+
+    let mut passed = true;
+    for (capture, image) in config.captures.iter().zip(images) {
+        let name = &capture.name;
+        let image = match image {
+            Ok(image) => image,
+            Err(e) => {
+                println!("'{}': capture failed: {}", name, e);
+                passed = false;
+                continue;
+            }
+        };
+        image
+            .write_png(&output.join(format!("{}.png", name)))
+            .unwrap();
+
+        let golden = match headless::Image::read_png(&goldens.join(format!("{}.png", name))) {
+            Ok(golden) => golden,
+            Err(e) => {
+                println!("'{}': unable to read the golden: {}", name, e);
+                passed = false;
+                continue;
+            }
+        };
+        let tolerance = capture.tolerance.unwrap_or(config.tolerance);
+        match image.compare(&golden) {
+            Some(comparison) if comparison.passes(&tolerance) => println!("'{}': passed", name),
+            Some(comparison) => {
+                println!(
+                    "'{}': {} of {} pixels differ by more than {}, by up to {}",
+                    name,
+                    comparison.outlier_count(&tolerance),
+                    comparison.pixel_count(),
+                    tolerance.threshold,
+                    comparison.max_difference(),
+                );
+                comparison
+                    .difference_image(&tolerance)
+                    .write_png(&output.join(format!("{}-difference.png", name)))
+                    .unwrap();
+                passed = false;
+            }
+            None => {
+                println!(
+                    "'{}': the image is {}x{}, but the golden is {}x{}",
+                    name, image.width, image.height, golden.width, golden.height
+                );
+                passed = false;
+            }
+        }
+    }
+    passed
+}
+
 #[cfg(target_arch = "wasm32")]
 fn main() {}
@@ -0,0 +1,517 @@
+/*! Replaying traces without a window, and comparing what they render to goldens.
+ *
+ * [`replay`] plays a trace on a device, standing in for the surfaces it
+ * renders to with textures of their configuration, and reads back the
+ * textures designated by [`Capture`]s as [`Image`]s. Comparing them to
+ * golden images with [`Image::compare`] and a [`Tolerance`] catches rendering
+ * regressions in traces recorded by users, on machines without a display.
+!*/
+
+use wgc::device::trace;
+
+use std::{borrow::Cow, collections::HashMap, fmt, fs, io, path::Path, slice, sync::mpsc};
+
+use crate::GlobalPlay as _;
+
+/// What to capture from a replay, and how to compare it, as loaded from RON.
+#[derive(Debug, serde::Deserialize)]
+pub struct ReplayConfig {
+    pub captures: Vec<Capture>,
+    /// The tolerance of the captures that don't specify theirs.
+    #[serde(default)]
+    pub tolerance: Tolerance,
+}
+
+/// A texture to read back during a replay.
+#[derive(Debug, serde::Deserialize)]
+pub struct Capture {
+    /// The name of the image, saved as `<name>.png`.
+    pub name: String,
+    pub source: CaptureSource,
+    #[serde(default)]
+    pub tolerance: Option<Tolerance>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize)]
+pub enum CaptureSource {
+    /// The surface texture of the n-th presented frame, counting from 0,
+    /// right before it's presented.
+    Frame(u32),
+    /// The first mip level and layer of a texture created by the trace, at
+    /// the end of the replay, or right before the trace destroys or frees it.
+    Texture(wgc::id::TextureId),
+}
+
+/// How much an image may differ from its golden.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Tolerance {
+    /// The difference of any channel of a pixel to the golden's above which
+    /// the pixel is an outlier.
+    pub threshold: u8,
+    /// The fraction of the pixels which may be outliers.
+    pub outlier_fraction: f32,
+}
+
+#[derive(Clone, Debug)]
+pub enum CaptureError {
+    /// The trace doesn't create the texture, or present the frame.
+    NotFound,
+    UnsupportedFormat(wgt::TextureFormat),
+    Copy(wgc::command::CopyError),
+    Map(wgc::resource::BufferAccessError),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NotFound => write!(
+                f,
+                "The trace doesn't create the texture or present the frame"
+            ),
+            Self::UnsupportedFormat(format) => {
+                write!(f, "Textures of format {format:?} can't be captured")
+            }
+            Self::Copy(ref e) => write!(f, "Copying the texture failed: {e}"),
+            Self::Map(ref e) => write!(f, "Mapping the texture's data failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// An image with 8-bit RGBA pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    pub fn read_png(path: &Path) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(io::BufReader::new(fs::File::open(path)?));
+        decoder.set_transformations(
+            png::Transformations::normalize_to_color8() | png::Transformations::ALPHA,
+        );
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+        data.truncate(info.buffer_size());
+        let data = match info.color_type {
+            png::ColorType::Rgba => data,
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+                .collect(),
+            color_type => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unexpected color type {color_type:?}"),
+                ))
+            }
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            data,
+        })
+    }
+
+    pub fn write_png(&self, path: &Path) -> io::Result<()> {
+        let file = io::BufWriter::new(fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.data)?;
+        Ok(())
+    }
+
+    /// Compares the image to `golden`, or returns `None` if their sizes
+    /// differ.
+    pub fn compare(&self, golden: &Self) -> Option<Comparison> {
+        if (self.width, self.height) != (golden.width, golden.height) {
+            return None;
+        }
+        let differences = self
+            .data
+            .chunks_exact(4)
+            .zip(golden.data.chunks_exact(4))
+            .map(|(pixel, golden_pixel)| {
+                pixel
+                    .iter()
+                    .zip(golden_pixel)
+                    .map(|(&channel, &golden_channel)| channel.abs_diff(golden_channel))
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        Some(Comparison {
+            width: self.width,
+            height: self.height,
+            differences,
+        })
+    }
+}
+
+/// How an image differs from its golden.
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    width: u32,
+    height: u32,
+    /// The largest difference of a channel, for each pixel.
+    differences: Vec<u8>,
+}
+
+impl Comparison {
+    pub fn pixel_count(&self) -> usize {
+        self.differences.len()
+    }
+
+    pub fn max_difference(&self) -> u8 {
+        self.differences.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn outlier_count(&self, tolerance: &Tolerance) -> usize {
+        self.differences
+            .iter()
+            .filter(|&&difference| difference > tolerance.threshold)
+            .count()
+    }
+
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.outlier_count(tolerance) as f32
+            <= tolerance.outlier_fraction * self.pixel_count() as f32
+    }
+
+    /// An image of the differences, with the outliers in red and the other
+    /// pixels in shades of gray.
+    pub fn difference_image(&self, tolerance: &Tolerance) -> Image {
+        let data = self
+            .differences
+            .iter()
+            .flat_map(|&difference| {
+                if difference > tolerance.threshold {
+                    [255, 0, 0, 255]
+                } else {
+                    [difference, difference, difference, 255]
+                }
+            })
+            .collect();
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+struct VirtualSurface {
+    config: wgt::SurfaceConfiguration<Vec<wgt::TextureFormat>>,
+    texture: Option<wgc::id::TextureId>,
+}
+
+struct Replay<'a> {
+    global: &'a wgc::global::Global,
+    device: wgc::id::DeviceId,
+    captures: &'a [Capture],
+    images: Vec<Option<Result<Image, CaptureError>>>,
+    textures: HashMap<wgc::id::TextureId, (wgt::Extent3d, wgt::TextureFormat)>,
+    surfaces: HashMap<wgc::id::SurfaceId, VirtualSurface>,
+    frame_count: u32,
+    /// The index of the next buffer to read textures back through, above
+    /// those of the trace's buffers.
+    next_buffer_index: u32,
+}
+
+/// Replays `actions` on `device`, and captures the textures designated by
+/// `captures`.
+///
+/// The surfaces are configured as textures, which requesting a surface
+/// texture creates and presenting it drops, so no window is needed.
+///
+/// Returns the images of `captures`, in the same order.
+pub fn replay<A: wgc::hal_api::HalApi>(
+    global: &wgc::global::Global,
+    device: wgc::id::DeviceId,
+    actions: Vec<trace::Action>,
+    dir: &Path,
+    captures: &[Capture],
+    comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
+) -> Vec<Result<Image, CaptureError>> {
+    use wgc::device::trace::Action;
+
+    let next_buffer_index = actions
+        .iter()
+        .filter_map(|action| match *action {
+            Action::CreateBuffer(id, _) => Some(id.unzip().0 + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let mut replay = Replay {
+        global,
+        device,
+        captures,
+        images: captures.iter().map(|_| None).collect(),
+        textures: HashMap::new(),
+        surfaces: HashMap::new(),
+        frame_count: 0,
+        next_buffer_index,
+    };
+
+    for action in actions {
+        match action {
+            Action::ConfigureSurface(surface, config) => {
+                log::info!("Configuring the virtual surface");
+                replay.surfaces.insert(
+                    surface,
+                    VirtualSurface {
+                        config,
+                        texture: None,
+                    },
+                );
+            }
+            Action::GetSurfaceTexture { id, parent_id } => {
+                let surface = replay
+                    .surfaces
+                    .get_mut(&parent_id)
+                    .expect("Surface texture requested before the surface was configured");
+                let config = &surface.config;
+                let desc = wgc::resource::TextureDescriptor {
+                    label: Some(Cow::Borrowed("(player) virtual surface texture")),
+                    size: wgt::Extent3d {
+                        width: config.width,
+                        height: config.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgt::TextureDimension::D2,
+                    format: config.format,
+                    usage: config.usage | wgt::TextureUsages::COPY_SRC,
+                    view_formats: config.view_formats.clone(),
+                };
+                global.device_maintain_ids::<A>(device).unwrap();
+                let (_, error) = global.device_create_texture::<A>(device, &desc, Some(id));
+                if let Some(e) = error {
+                    panic!("{e}");
+                }
+                surface.texture = Some(id);
+                replay.textures.insert(id, (desc.size, desc.format));
+            }
+            Action::Present(surface) => {
+                let texture = replay.take_surface_texture(surface);
+                log::debug!("Presenting frame {}", replay.frame_count);
+                let source = CaptureSource::Frame(replay.frame_count);
+                replay.capture::<A>(source, texture, comb_manager);
+                replay.frame_count += 1;
+                replay.textures.remove(&texture);
+                global.texture_drop::<A>(texture, false);
+            }
+            Action::DiscardSurfaceTexture(surface) => {
+                let texture = replay.take_surface_texture(surface);
+                replay.textures.remove(&texture);
+                global.texture_drop::<A>(texture, false);
+            }
+            Action::CreateTexture(id, mut desc) => {
+                // Captured textures need to be copied from.
+                if replay.is_captured(CaptureSource::Texture(id)) {
+                    desc.usage |= wgt::TextureUsages::COPY_SRC;
+                }
+                replay.textures.insert(id, (desc.size, desc.format));
+                global.process::<A>(device, Action::CreateTexture(id, desc), dir, comb_manager);
+            }
+            Action::DestroyTexture(id) | Action::FreeTexture(id)
+                if replay.textures.contains_key(&id) =>
+            {
+                replay.capture::<A>(CaptureSource::Texture(id), id, comb_manager);
+                replay.textures.remove(&id);
+                global.process::<A>(device, action, dir, comb_manager);
+            }
+            action => global.process::<A>(device, action, dir, comb_manager),
+        }
+    }
+
+    let textures = replay.textures.keys().copied().collect::<Vec<_>>();
+    for texture in textures {
+        replay.capture::<A>(CaptureSource::Texture(texture), texture, comb_manager);
+    }
+
+    replay
+        .images
+        .into_iter()
+        .map(|image| image.unwrap_or(Err(CaptureError::NotFound)))
+        .collect()
+}
+
+impl Replay<'_> {
+    fn take_surface_texture(&mut self, surface: wgc::id::SurfaceId) -> wgc::id::TextureId {
+        self.surfaces
+            .get_mut(&surface)
+            .and_then(|surface| surface.texture.take())
+            .expect("Surface texture used before it was requested")
+    }
+
+    fn is_captured(&self, source: CaptureSource) -> bool {
+        self.captures.iter().any(|capture| capture.source == source)
+    }
+
+    /// Reads back `texture` for the captures of `source` which weren't done
+    /// yet.
+    fn capture<A: wgc::hal_api::HalApi>(
+        &mut self,
+        source: CaptureSource,
+        texture: wgc::id::TextureId,
+        comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
+    ) {
+        let pending = self
+            .captures
+            .iter()
+            .zip(&self.images)
+            .any(|(capture, image)| capture.source == source && image.is_none());
+        if !pending {
+            return;
+        }
+
+        let image = self.read_texture::<A>(texture, comb_manager);
+        for (capture, slot) in self.captures.iter().zip(&mut self.images) {
+            if capture.source == source && slot.is_none() {
+                log::info!("Captured '{}'", capture.name);
+                *slot = Some(image.clone());
+            }
+        }
+    }
+
+    fn read_texture<A: wgc::hal_api::HalApi>(
+        &mut self,
+        texture: wgc::id::TextureId,
+        comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
+    ) -> Result<Image, CaptureError> {
+        let (global, device) = (self.global, self.device);
+        let (size, format) = self.textures[&texture];
+        let swap_red_blue = match format {
+            wgt::TextureFormat::Rgba8Unorm | wgt::TextureFormat::Rgba8UnormSrgb => false,
+            wgt::TextureFormat::Bgra8Unorm | wgt::TextureFormat::Bgra8UnormSrgb => true,
+            _ => return Err(CaptureError::UnsupportedFormat(format)),
+        };
+        let row_size = size.width * 4;
+        let bytes_per_row = wgt::math::align_to(row_size, wgt::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer_size = bytes_per_row as wgt::BufferAddress * size.height as wgt::BufferAddress;
+
+        let buffer = wgc::id::Id::zip(self.next_buffer_index, 1, device.backend());
+        self.next_buffer_index += 1;
+        global.device_maintain_ids::<A>(device).unwrap();
+        let (_, error) = global.device_create_buffer::<A>(
+            device,
+            &wgc::resource::BufferDescriptor {
+                label: Some(Cow::Borrowed("(player) capture buffer")),
+                size: buffer_size,
+                usage: wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+            Some(buffer),
+        );
+        if let Some(e) = error {
+            panic!("{e}");
+        }
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            Some(
+                comb_manager
+                    .process(device.backend())
+                    .into_command_encoder_id(),
+            ),
+        );
+        if let Some(e) = error {
+            panic!("{e}");
+        }
+        let copied = global.command_encoder_copy_texture_to_buffer::<A>(
+            encoder,
+            &wgc::command::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgt::Origin3d::ZERO,
+                aspect: wgt::TextureAspect::All,
+            },
+            &wgc::command::ImageCopyBuffer {
+                buffer,
+                layout: wgt::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            &wgt::Extent3d {
+                depth_or_array_layers: 1,
+                ..size
+            },
+        );
+        if let Err(e) = copied {
+            global.command_encoder_drop::<A>(encoder);
+            global.buffer_drop::<A>(buffer, false);
+            return Err(CaptureError::Copy(e));
+        }
+        let cmdbuf = global.encode_commands::<A>(encoder, Vec::new());
+        global
+            .queue_submit::<A>(device.into_queue_id(), &[cmdbuf])
+            .unwrap();
+
+        let data = read_buffer::<A>(global, device, buffer, buffer_size).map(|mapped| {
+            let mut data = Vec::with_capacity((row_size * size.height) as usize);
+            for row in mapped.chunks_exact(bytes_per_row as usize) {
+                data.extend_from_slice(&row[..row_size as usize]);
+            }
+            if swap_red_blue {
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+            data
+        });
+        global.buffer_drop::<A>(buffer, false);
+
+        Ok(Image {
+            width: size.width,
+            height: size.height,
+            data: data.map_err(CaptureError::Map)?,
+        })
+    }
+}
+
+/// Maps `buffer`, waiting for the device, and copies its contents.
+fn read_buffer<A: wgc::hal_api::HalApi>(
+    global: &wgc::global::Global,
+    device: wgc::id::DeviceId,
+    buffer: wgc::id::BufferId,
+    size: wgt::BufferAddress,
+) -> Result<Vec<u8>, wgc::resource::BufferAccessError> {
+    let (sender, receiver) = mpsc::channel();
+    global.buffer_map_async::<A>(
+        buffer,
+        0,
+        Some(size),
+        wgc::resource::BufferMapOperation {
+            host: wgc::device::HostMap::Read,
+            callback: Some(wgc::resource::BufferMapCallback::from_rust(Box::new(
+                move |result| {
+                    let _ = sender.send(result);
+                },
+            ))),
+        },
+    )?;
+    global
+        .device_poll::<A>(device, wgt::Maintain::wait())
+        .unwrap();
+    receiver
+        .try_recv()
+        .expect("Buffer mapping didn't complete after waiting for the device")?;
+
+    let (ptr, size) = global.buffer_get_mapped_range::<A>(buffer, 0, Some(size))?;
+    let data = unsafe { slice::from_raw_parts(ptr, size as usize) }.to_vec();
+    global.buffer_unmap::<A>(buffer)?;
+    Ok(data)
+}
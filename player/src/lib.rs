@@ -12,6 +12,8 @@ use wgc::device::trace;
 
 use std::{borrow::Cow, fs, path::Path};
 
+pub mod headless;
+
 pub trait GlobalPlay {
     fn encode_commands<A: wgc::hal_api::HalApi>(
         &self,
@@ -25,6 +27,14 @@ pub trait GlobalPlay {
         dir: &Path,
         comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
     );
+    fn replay_headless<A: wgc::hal_api::HalApi>(
+        &self,
+        device: wgc::id::DeviceId,
+        actions: Vec<trace::Action>,
+        dir: &Path,
+        captures: &[headless::Capture],
+        comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
+    ) -> Vec<Result<headless::Image, headless::CaptureError>>;
 }
 
 impl GlobalPlay for wgc::global::Global {
@@ -240,14 +250,24 @@ impl GlobalPlay for wgc::global::Global {
             }
             Action::CreateShaderModule { id, desc, data } => {
                 log::debug!("Creating shader from {}", data);
-                let code = fs::read_to_string(dir.join(&data)).unwrap();
-                let source = if data.ends_with(".wgsl") {
-                    wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code.clone()))
-                } else if data.ends_with(".ron") {
-                    let module = ron::de::from_str(&code).unwrap();
-                    wgc::pipeline::ShaderModuleSource::Naga(module)
+                let (code, source) = if data.ends_with(".naga") {
+                    // Precompiled modules are binary, so there's no code to print.
+                    let bytes = fs::read(dir.join(&data)).unwrap();
+                    (
+                        String::new(),
+                        wgc::pipeline::ShaderModuleSource::Precompiled(Cow::Owned(bytes)),
+                    )
                 } else {
-                    panic!("Unknown shader {}", data);
+                    let code = fs::read_to_string(dir.join(&data)).unwrap();
+                    let source = if data.ends_with(".wgsl") {
+                        wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code.clone()))
+                    } else if data.ends_with(".ron") {
+                        let module = ron::de::from_str(&code).unwrap();
+                        wgc::pipeline::ShaderModuleSource::Naga(module)
+                    } else {
+                        panic!("Unknown shader {}", data);
+                    };
+                    (code, source)
                 };
                 let (_, error) =
                     self.device_create_shader_module::<A>(device, &desc, source, Some(id));
@@ -302,6 +322,16 @@ impl GlobalPlay for wgc::global::Global {
             Action::DestroyRenderPipeline(id) => {
                 self.render_pipeline_drop::<A>(id);
             }
+            Action::CreatePipelineCache { id, desc } => {
+                let (_, error) =
+                    unsafe { self.device_create_pipeline_cache::<A>(device, &desc, Some(id)) };
+                if let Some(e) = error {
+                    panic!("{e}");
+                }
+            }
+            Action::DestroyPipelineCache(id) => {
+                self.pipeline_cache_drop::<A>(id);
+            }
             Action::CreateRenderBundle { id, desc, base } => {
                 let bundle =
                     wgc::command::RenderBundleEncoder::new(&desc, device, Some(base)).unwrap();
@@ -376,4 +406,15 @@ impl GlobalPlay for wgc::global::Global {
             }
         }
     }
+
+    fn replay_headless<A: wgc::hal_api::HalApi>(
+        &self,
+        device: wgc::id::DeviceId,
+        actions: Vec<trace::Action>,
+        dir: &Path,
+        captures: &[headless::Capture],
+        comb_manager: &mut wgc::identity::IdentityManager<wgc::id::markers::CommandBuffer>,
+    ) -> Vec<Result<headless::Image, headless::CaptureError>> {
+        headless::replay::<A>(self, device, actions, dir, captures, comb_manager)
+    }
 }
@@ -6,11 +6,11 @@
  *    - all IDs have the backend `Empty`
  *    - all expected buffers have `MAP_READ` usage
  *    - last action is `Submit`
- *    - no swapchain use
+ *    - captured images have goldens named after them, next to the test
 !*/
 #![cfg(not(target_arch = "wasm32"))]
 
-use player::GlobalPlay;
+use player::{headless, GlobalPlay};
 use std::{
     fs::{read_to_string, File},
     io::{Read, Seek, SeekFrom},
@@ -52,6 +52,7 @@ struct Expectation {
 struct Test<'a> {
     features: wgt::Features,
     expectations: Vec<Expectation>,
+    captures: Vec<headless::Capture>,
     actions: Vec<wgc::device::trace::Action<'a>>,
 }
 
@@ -76,11 +77,14 @@ impl Test<'_> {
         struct SerializedTest<'a> {
             features: Vec<String>,
             expectations: Vec<Expectation>,
+            #[serde(default)]
+            captures: Vec<headless::Capture>,
             actions: Vec<wgc::device::trace::Action<'a>>,
         }
         let SerializedTest {
             features,
             expectations,
+            captures,
             actions,
         } = ron::de::from_str(&string).unwrap();
         let features = features
@@ -93,6 +97,7 @@ impl Test<'_> {
         Test {
             features,
             expectations,
+            captures,
             actions,
         }
     }
@@ -112,6 +117,7 @@ impl Test<'_> {
                 label: None,
                 required_features: self.features,
                 required_limits: wgt::Limits::default(),
+                memory_hints: wgt::MemoryHints::default(),
             },
             None,
             Some(device_id),
@@ -123,9 +129,7 @@ impl Test<'_> {
 
         let mut command_buffer_id_manager = wgc::identity::IdentityManager::new();
         println!("\t\t\tRunning...");
-        for action in self.actions {
-            wgc::gfx_select!(device_id => global.process(device_id, action, dir, &mut command_buffer_id_manager));
-        }
+        let images = wgc::gfx_select!(device_id => global.replay_headless(device_id, self.actions, dir, &self.captures, &mut command_buffer_id_manager));
         println!("\t\t\tMapping...");
         for expect in &self.expectations {
             let buffer = wgc::id::Id::zip(expect.buffer.index, expect.buffer.epoch, backend);
@@ -179,6 +183,23 @@ impl Test<'_> {
             }
         }
 
+        for (capture, image) in self.captures.iter().zip(images) {
+            println!("\t\t\tComparing {}", capture.name);
+            let image = image.unwrap();
+            let golden =
+                headless::Image::read_png(&dir.join(format!("{}.png", capture.name))).unwrap();
+            let comparison = image
+                .compare(&golden)
+                .expect("Captured image size doesn't match the golden's");
+            let tolerance = capture.tolerance.unwrap_or_default();
+            assert!(
+                comparison.passes(&tolerance),
+                "{} of {} pixels differ from the golden",
+                comparison.outlier_count(&tolerance),
+                comparison.pixel_count()
+            );
+        }
+
         wgc::gfx_select!(device_id => global.clear_backend(()));
     }
 }
@@ -0,0 +1,225 @@
+//! Tests for the barriers between commands that depend on each other, which
+//! backends can hold back to record them in batches.
+
+use std::num::NonZeroU64;
+
+use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters};
+
+const SHADER: &str = r#"
+    @group(0) @binding(0)
+    var<storage, read_write> counter: u32;
+
+    @compute @workgroup_size(1)
+    fn main() {
+        counter += 1u;
+    }
+"#;
+
+/// The number of dispatches of each pass, each depending on the previous one.
+const DISPATCH_COUNT: u32 = 8;
+
+#[gpu_test]
+static BARRIERS_BETWEEN_DEPENDENT_COMMANDS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS))
+    .run_async(|ctx| async move {
+        let create_buffer = |usage| {
+            ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 4,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let storage_usage = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST;
+        let first = create_buffer(storage_usage);
+        let second = create_buffer(storage_usage);
+        let readback_usage = wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ;
+        let counted = create_buffer(readback_usage);
+        let cleared = create_buffer(readback_usage);
+
+        let layout = ctx
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(4),
+                    },
+                    count: None,
+                }],
+            });
+        let create_bind_group = |buffer: &wgpu::Buffer| {
+            ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            })
+        };
+        let first_bind_group = create_bind_group(&first);
+        let second_bind_group = create_bind_group(&second);
+
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let pipeline_layout = ctx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "main",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        // Each dispatch reads the counter the previous one wrote.
+        let count = |encoder: &mut wgpu::CommandEncoder, bind_group| {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            for _ in 0..DISPATCH_COUNT {
+                pass.dispatch_workgroups(1, 1, 1);
+            }
+        };
+        encoder.clear_buffer(&first, 0, None);
+        count(&mut encoder, &first_bind_group);
+        // Copies and clears of a buffer that was just written, and then read.
+        encoder.copy_buffer_to_buffer(&first, 0, &second, 0, 4);
+        encoder.clear_buffer(&first, 0, None);
+        encoder.copy_buffer_to_buffer(&first, 0, &cleared, 0, 4);
+        count(&mut encoder, &second_bind_group);
+        encoder.copy_buffer_to_buffer(&second, 0, &counted, 0, 4);
+        ctx.queue.submit([encoder.finish()]);
+
+        counted.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        cleared.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        let read = |buffer: &wgpu::Buffer| {
+            u32::from_le_bytes(buffer.slice(..).get_mapped_range()[..].try_into().unwrap())
+        };
+        assert_eq!(read(&counted), 2 * DISPATCH_COUNT);
+        assert_eq!(read(&cleared), 0);
+    });
+
+/// The width of the render targets of `MSAA_RESOLVE_THEN_PASS`, making one row
+/// of texels as large as the row alignment of texture copies.
+const TARGET_WIDTH: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT / 4;
+
+/// The transitions around a multisampled resolve must not be recorded again
+/// with those of the commands after it.
+#[gpu_test]
+static MSAA_RESOLVE_THEN_PASS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default())
+    .run_async(|ctx| async move {
+        let create_target = |sample_count| {
+            ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: TARGET_WIDTH,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        };
+        let multisampled = create_target(4);
+        let resolved = create_target(1);
+        let other = create_target(1);
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let view =
+            |texture: &wgpu::Texture| texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let multisampled_view = view(&multisampled);
+        let resolved_view = view(&resolved);
+        let other_view = view(&other);
+        let clear = |encoder: &mut wgpu::CommandEncoder,
+                     view: &wgpu::TextureView,
+                     resolve_target: Option<&wgpu::TextureView>,
+                     color: wgpu::Color| {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        };
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        clear(
+            &mut encoder,
+            &multisampled_view,
+            Some(&resolved_view),
+            wgpu::Color::RED,
+        );
+        clear(&mut encoder, &other_view, None, wgpu::Color::GREEN);
+        encoder.copy_texture_to_buffer(
+            resolved.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: TARGET_WIDTH,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        readback.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        let data = readback.slice(..).get_mapped_range();
+        assert!(data.chunks_exact(4).all(|texel| texel == [255, 0, 0, 255]));
+    });
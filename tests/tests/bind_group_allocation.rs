@@ -0,0 +1,266 @@
+//! Tests for creating and destroying many bind groups of one layout, whose
+//! descriptor sets Vulkan allocates in batches and reuses.
+
+use std::num::NonZeroU64;
+
+use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters};
+
+const SHADER: &str = r#"
+    @group(0) @binding(0)
+    var<storage, read_write> output: u32;
+
+    @compute @workgroup_size(1)
+    fn main() {
+        output += 1u;
+    }
+"#;
+
+/// More bind groups than the first batches of descriptor sets hold.
+const BIND_GROUP_COUNT: usize = 200;
+
+#[gpu_test]
+static BIND_GROUPS_ACROSS_BATCHES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS))
+    .run_async(|ctx| async move {
+        let stride = u64::from(ctx.device.limits().min_storage_buffer_offset_alignment);
+        let size = stride * BIND_GROUP_COUNT as u64;
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let layout = ctx
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(4),
+                    },
+                    count: None,
+                }],
+            });
+        let create_bind_group = |index: usize| {
+            ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: index as u64 * stride,
+                        size: NonZeroU64::new(4),
+                    }),
+                }],
+            })
+        };
+
+        // Free bind groups of every batch, and create new ones in their place,
+        // which reuse their sets with other buffer offsets.
+        let mut bind_groups: Vec<_> = (0..BIND_GROUP_COUNT)
+            .map(|index| Some(create_bind_group(index)))
+            .collect();
+        for bind_group in bind_groups.iter_mut().step_by(3) {
+            *bind_group = None;
+        }
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+        for (index, bind_group) in bind_groups.iter_mut().enumerate() {
+            bind_group.get_or_insert_with(|| create_bind_group(index));
+        }
+
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let pipeline_layout = ctx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "main",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            for bind_group in bind_groups.iter().flatten() {
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.dispatch_workgroups(1, 1, 1);
+            }
+        }
+        encoder.copy_buffer_to_buffer(&buffer, 0, &readback, 0, size);
+        ctx.queue.submit([encoder.finish()]);
+        readback.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        // Each offset was bound by exactly one bind group.
+        let data = readback.slice(..).get_mapped_range();
+        for index in 0..BIND_GROUP_COUNT {
+            let offset = index * stride as usize;
+            let value = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+            assert_eq!(value, 1, "bind group {index}");
+        }
+    });
+
+const SAMPLE_SHADER: &str = r#"
+    @group(0) @binding(0)
+    var color: texture_2d<f32>;
+    @group(0) @binding(1)
+    var color_sampler: sampler;
+    @group(0) @binding(2)
+    var<storage, read_write> output: vec4<f32>;
+
+    @compute @workgroup_size(1)
+    fn main() {
+        output = textureSampleLevel(color, color_sampler, vec2(0.5), 0.0);
+    }
+"#;
+
+/// Submissions don't mark the views and samplers of the bind groups they use,
+/// so dropping them along with the bind group while the GPU still uses them
+/// must not free them early.
+#[gpu_test]
+static VIEWS_AND_SAMPLERS_OUTLIVE_BIND_GROUP: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS))
+    .run_async(|ctx| async move {
+        let size = wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        ctx.queue.write_texture(
+            texture.as_image_copy(),
+            &[255, 0, 0, 255],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4),
+                rows_per_image: None,
+            },
+            size,
+        );
+        let output = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SAMPLE_SHADER.into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &module,
+                entry_point: "main",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = ctx
+            .device
+            .create_sampler(&wgpu::SamplerDescriptor::default());
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: output.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        ctx.queue.submit([encoder.finish()]);
+
+        // Free everything but the texture while the dispatch may still run.
+        drop((bind_group, view, sampler));
+        ctx.device.poll(wgpu::Maintain::Poll);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, 16);
+        ctx.queue.submit([encoder.finish()]);
+        readback.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        let data = readback.slice(..).get_mapped_range();
+        let color: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(color, [1.0, 0.0, 0.0, 1.0]);
+    });
@@ -165,6 +165,68 @@ static MAP_OFFSET: GpuTestConfiguration = GpuTestConfiguration::new().run_async(
     }
 });
 
+// Writes to a buffer must land in queue order, whether they are copied from a
+// staging buffer or, on devices with unified memory, written directly.
+#[gpu_test]
+static WRITE_BUFFER_ORDER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_async(|ctx| async move {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        buffer.slice(0..16).get_mapped_range_mut().fill(1);
+        buffer.unmap();
+
+        let readback_descriptor = wgpu::BufferDescriptor {
+            label: None,
+            size: 64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let first = ctx.device.create_buffer(&readback_descriptor);
+        let second = ctx.device.create_buffer(&readback_descriptor);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&buffer, 0, &first, 0, 64);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        // The buffer may or may not still be in use.
+        ctx.queue.write_buffer(&buffer, 16, &[2; 16]);
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+        // The buffer is no longer in use.
+        ctx.queue.write_buffer(&buffer, 32, &[3; 16]);
+        ctx.queue.write_buffer(&buffer, 32, &[4; 16]);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&buffer, 0, &second, 0, 64);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        first
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, Result::unwrap);
+        second
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, Result::unwrap);
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        let mut expected = [0; 64];
+        expected[..16].fill(1);
+        assert_eq!(&first.slice(..).get_mapped_range()[..], &expected[..]);
+        expected[16..32].fill(2);
+        expected[32..48].fill(4);
+        assert_eq!(&second.slice(..).get_mapped_range()[..], &expected[..]);
+    });
+
 /// The WebGPU algorithm [validating shader binding][vsb] requires
 /// implementations to check that buffer bindings are large enough to
 /// hold the WGSL `storage` or `uniform` variables they're bound to.
@@ -225,6 +287,7 @@ static MINIMUM_BUFFER_BINDING_SIZE_LAYOUT: GpuTestConfiguration = GpuTestConfigu
                     module: &shader_module,
                     entry_point: "main",
                     compilation_options: Default::default(),
+                    cache: None,
                 });
         });
     });
@@ -294,6 +357,7 @@ static MINIMUM_BUFFER_BINDING_SIZE_DISPATCH: GpuTestConfiguration = GpuTestConfi
                 module: &shader_module,
                 entry_point: "main",
                 compilation_options: Default::default(),
+                cache: None,
             });
 
         let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
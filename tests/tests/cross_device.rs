@@ -0,0 +1,177 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Tests for `wgpu::util::copy_between_devices`, between two devices of the
+//! same adapter.
+
+use wgpu::util::{copy_between_devices, CrossDeviceCopy, DeviceAndQueue, DeviceExt};
+use wgpu_test::{gpu_test, FailureCase, GpuTestConfiguration, TestParameters, TestingContext};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 4;
+
+fn read_buffer(ctx: &TestingContext, buffer: &wgpu::Buffer) -> Vec<u8> {
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| ());
+    ctx.device.poll(wgpu::Maintain::Wait).panic_on_timeout();
+    let data = slice.get_mapped_range().to_vec();
+    buffer.unmap();
+    data
+}
+
+#[gpu_test]
+static COPY_BUFFER_BETWEEN_DEVICES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            // GL adapters only have one device.
+            .skip(FailureCase::backend(wgpu::Backends::GL)),
+    )
+    .run_sync(|ctx| {
+        let (source_device, source_queue) =
+            pollster::block_on(ctx.adapter.request_device(&Default::default(), None)).unwrap();
+
+        let data: Vec<u8> = (0..256).map(|i| i as u8).collect();
+        let source = source_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("source"),
+            contents: &data,
+            usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        let destination = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("destination"),
+            size: 256,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        copy_between_devices(
+            DeviceAndQueue {
+                device: &source_device,
+                queue: &source_queue,
+            },
+            DeviceAndQueue {
+                device: &ctx.device,
+                queue: &ctx.queue,
+            },
+            CrossDeviceCopy::Buffer {
+                source: &source,
+                source_offset: 128,
+                destination: &destination,
+                destination_offset: 0,
+                size: 128,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(read_buffer(&ctx, &destination)[..128], data[128..]);
+    });
+
+#[gpu_test]
+static COPY_TEXTURE_BETWEEN_DEVICES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().skip(FailureCase::backend(wgpu::Backends::GL)))
+    .run_sync(|ctx| {
+        let (source_device, source_queue) =
+            pollster::block_on(ctx.adapter.request_device(&Default::default(), None)).unwrap();
+
+        let desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: WIDTH,
+                height: HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        // Rows of 256 bytes, which don't need padding.
+        let data: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+        let source = source_device.create_texture_with_data(
+            &source_queue,
+            &desc,
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &data,
+        );
+        let destination = ctx.device.create_texture(&desc);
+
+        copy_between_devices(
+            DeviceAndQueue {
+                device: &source_device,
+                queue: &source_queue,
+            },
+            DeviceAndQueue {
+                device: &ctx.device,
+                queue: &ctx.queue,
+            },
+            CrossDeviceCopy::Texture {
+                source: source.as_image_copy(),
+                destination: destination.as_image_copy(),
+                size: desc.size,
+            },
+        )
+        .unwrap();
+
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: data.len() as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            destination.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(WIDTH * 4),
+                    rows_per_image: None,
+                },
+            },
+            desc.size,
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        assert_eq!(read_buffer(&ctx, &readback), data);
+    });
+
+#[gpu_test]
+static EMPTY_COPY_BETWEEN_DEVICES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().skip(FailureCase::backend(wgpu::Backends::GL)))
+    .run_sync(|ctx| {
+        let (source_device, source_queue) =
+            pollster::block_on(ctx.adapter.request_device(&Default::default(), None)).unwrap();
+        let buffer_desc = wgpu::BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let source = source_device.create_buffer(&buffer_desc);
+        let destination = ctx.device.create_buffer(&buffer_desc);
+
+        // Nothing is read back, so the copy can't fail.
+        let index = copy_between_devices(
+            DeviceAndQueue {
+                device: &source_device,
+                queue: &source_queue,
+            },
+            DeviceAndQueue {
+                device: &ctx.device,
+                queue: &ctx.queue,
+            },
+            CrossDeviceCopy::Buffer {
+                source: &source,
+                source_offset: 0,
+                destination: &destination,
+                destination_offset: 0,
+                size: 0,
+            },
+        )
+        .unwrap();
+        ctx.device
+            .poll(wgpu::Maintain::wait_for(index))
+            .panic_on_timeout();
+    });
@@ -471,6 +471,25 @@ static DEVICE_DESTROY_THEN_MORE: GpuTestConfiguration = GpuTestConfiguration::ne
                 });
         });
 
+        // Creating a shader module dxil should fail.
+        fail(&ctx.device, || unsafe {
+            ctx.device
+                .create_shader_module_dxil(&wgpu::ShaderModuleDescriptorDxil {
+                    label: None,
+                    source: std::borrow::Cow::Borrowed(&[]),
+                });
+        });
+
+        // Creating a shader module msl should fail.
+        fail(&ctx.device, || unsafe {
+            ctx.device
+                .create_shader_module_msl(&wgpu::ShaderModuleDescriptorMsl {
+                    label: None,
+                    source: wgpu::MslSource::Source(std::borrow::Cow::Borrowed("")),
+                    workgroup_size: [1, 1, 1],
+                });
+        });
+
         // Creating a render pipeline should fail.
         fail(&ctx.device, || {
             ctx.device
@@ -488,6 +507,7 @@ static DEVICE_DESTROY_THEN_MORE: GpuTestConfiguration = GpuTestConfiguration::ne
                     multisample: wgpu::MultisampleState::default(),
                     fragment: None,
                     multiview: None,
+                    cache: None,
                 });
         });
 
@@ -500,6 +520,7 @@ static DEVICE_DESTROY_THEN_MORE: GpuTestConfiguration = GpuTestConfiguration::ne
                     module: &shader_module,
                     entry_point: "",
                     compilation_options: Default::default(),
+                    cache: None,
                 });
         });
 
@@ -757,6 +778,7 @@ fn vs_main() -> @builtin(position) vec4<f32> {
                 depth_stencil: None,
                 multisample: wgt::MultisampleState::default(),
                 multiview: None,
+                cache: None,
             });
 
         // fail(&ctx.device, || {
@@ -819,3 +841,41 @@ static DEVICE_DESTROY_THEN_BUFFER_CLEANUP: GpuTestConfiguration = GpuTestConfigu
         // Poll the device, which should try to clean up its resources.
         ctx.instance.poll_all(true);
     });
+
+#[gpu_test]
+static DEVICE_CAPTURE_DIAGNOSTICS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            // The browser doesn't expose its resources and error scopes.
+            .skip(FailureCase::backend(wgpu::Backends::BROWSER_WEBGPU)),
+    )
+    .run_sync(|ctx| {
+        let _buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("diagnosed buffer"),
+            size: 256,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::MAP_WRITE,
+            mapped_at_creation: false,
+        });
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+
+        let report = ctx.device.capture_diagnostics();
+        assert_eq!(report.device.adapter_info, ctx.adapter.get_info());
+        assert_eq!(report.device.features, ctx.device.features());
+        assert!(report
+            .device
+            .resources
+            .buffers
+            .iter()
+            .any(|buffer| buffer.label == "diagnosed buffer" && buffer.size == 256));
+        let scope = report.error_scopes.last().unwrap();
+        assert_eq!(scope.filter, wgpu::ErrorFilter::Validation);
+        assert!(scope.error.is_some());
+    });
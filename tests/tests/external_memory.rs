@@ -0,0 +1,152 @@
+//! Tests for the Vulkan memory and fences exported for other APIs, imported
+//! back into the same device through `as_hal`.
+#![cfg(any(
+    windows,
+    all(
+        unix,
+        not(target_os = "emscripten"),
+        not(target_os = "ios"),
+        not(target_os = "macos")
+    )
+))]
+
+use wgpu::hal::{self, api::Vulkan, Device as _};
+use wgpu_test::{gpu_test, GpuTestConfiguration};
+
+const SIZE: u64 = 256;
+
+fn hal_desc() -> hal::BufferDescriptor<'static> {
+    hal::BufferDescriptor {
+        label: Some("exported buffer"),
+        size: SIZE,
+        usage: hal::BufferUses::COPY_SRC | hal::BufferUses::COPY_DST,
+        memory_flags: hal::MemoryFlags::empty(),
+    }
+}
+
+fn desc() -> wgpu::BufferDescriptor<'static> {
+    wgpu::BufferDescriptor {
+        label: Some("exported buffer"),
+        size: SIZE,
+        usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    }
+}
+
+#[gpu_test]
+static EXPORTED_BUFFER_MEMORY: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_async(|ctx| async move {
+        let exported = unsafe {
+            ctx.device.as_hal::<Vulkan, _, _>(|device| {
+                let device = device.filter(|device| device.supports_export())?;
+                Some(device.create_exportable_buffer(&hal_desc()).unwrap())
+            })
+        };
+        // Only Vulkan devices with the external memory extensions export memory.
+        let Some(Some(exported)) = exported else {
+            return;
+        };
+        let exported = unsafe {
+            ctx.device
+                .create_buffer_from_hal::<Vulkan>(exported, &desc())
+        };
+
+        let imported = unsafe {
+            exported.as_hal::<Vulkan, _, _>(|buffer| {
+                ctx.device
+                    .as_hal::<Vulkan, _, _>(|device| {
+                        let device = device.unwrap();
+                        let memory = device.export_buffer_memory(buffer.unwrap()).unwrap();
+                        assert!(memory.size >= SIZE);
+                        device
+                            .import_buffer_memory(memory.handle, &hal_desc())
+                            .unwrap()
+                    })
+                    .unwrap()
+            })
+        };
+        let imported = unsafe {
+            ctx.device
+                .create_buffer_from_hal::<Vulkan>(imported, &desc())
+        };
+
+        // What is written to the exported buffer is read from the imported one.
+        let data: Vec<u8> = (0..SIZE as u32).map(|i| i as u8).collect();
+        ctx.queue.write_buffer(&exported, 0, &data);
+        let read_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&imported, 0, &read_buffer, 0, SIZE);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = read_buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+        assert_eq!(*slice.get_mapped_range(), data[..]);
+    });
+
+#[gpu_test]
+static EXPORTED_DEVICE_FENCE: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
+    ctx.queue.submit(None);
+    ctx.device.poll(wgpu::Maintain::wait()).panic_on_timeout();
+
+    // Devices without timeline semaphores can't export their fence.
+    let imported = unsafe {
+        ctx.device.fence_as_hal::<Vulkan, _, _>(|fence| {
+            ctx.device
+                .as_hal::<Vulkan, _, _>(|device| {
+                    let device = device.unwrap();
+                    let handle = device.export_fence(fence.unwrap()).ok()?;
+                    Some(device.import_fence(handle).unwrap())
+                })
+                .unwrap()
+        })
+    };
+    let Some(Some(imported)) = imported else {
+        return;
+    };
+
+    // The imported fence shares its value with the device's fence, which is
+    // signaled with the index of each submission.
+    let value = unsafe {
+        ctx.device
+            .as_hal::<Vulkan, _, _>(|device| device.unwrap().get_fence_value(&imported).unwrap())
+            .unwrap()
+    };
+    let device_value = unsafe {
+        ctx.device
+            .fence_as_hal::<Vulkan, _, _>(|fence| {
+                ctx.device
+                    .as_hal::<Vulkan, _, _>(|device| {
+                        device.unwrap().get_fence_value(fence.unwrap()).unwrap()
+                    })
+                    .unwrap()
+            })
+            .unwrap()
+    };
+    assert!(value > 0);
+    assert_eq!(value, device_value);
+
+    // Waiting on a value the fence already reached doesn't block the queue.
+    unsafe {
+        ctx.queue
+            .as_hal::<Vulkan, _, _>(|queue| queue.unwrap().wait_for_fence(&imported, value))
+            .unwrap()
+    };
+    ctx.queue.submit(None);
+    ctx.device.poll(wgpu::Maintain::wait()).panic_on_timeout();
+
+    unsafe {
+        ctx.device
+            .as_hal::<Vulkan, _, _>(|device| device.unwrap().destroy_fence(imported))
+            .unwrap()
+    };
+});
@@ -1,4 +1,4 @@
-use wgpu_test::{fail, gpu_test, GpuTestConfiguration};
+use wgpu_test::{fail, gpu_test, GpuTestConfiguration, TestingContext};
 
 #[gpu_test]
 static BUFFER_DESTROY: GpuTestConfiguration =
@@ -109,3 +109,159 @@ static TEXTURE_DESTROY: GpuTestConfiguration =
 
         texture.destroy();
     });
+
+const SAMPLED_TEXTURE: wgpu::TextureDescriptor<'static> = wgpu::TextureDescriptor {
+    label: None,
+    size: wgpu::Extent3d {
+        width: 4,
+        height: 4,
+        depth_or_array_layers: 1,
+    },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format: wgpu::TextureFormat::Rgba8Unorm,
+    usage: wgpu::TextureUsages::TEXTURE_BINDING.union(wgpu::TextureUsages::RENDER_ATTACHMENT),
+    view_formats: &[],
+};
+
+/// Creates a bind group of a view of a new texture and a sampler.
+fn sampled_bind_group(
+    ctx: &TestingContext,
+) -> (
+    wgpu::Texture,
+    wgpu::TextureView,
+    wgpu::Sampler,
+    wgpu::BindGroup,
+) {
+    let layout = ctx
+        .device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+    let texture = ctx.device.create_texture(&SAMPLED_TEXTURE);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = ctx
+        .device
+        .create_sampler(&wgpu::SamplerDescriptor::default());
+    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+    });
+    (texture, view, sampler, bind_group)
+}
+
+/// Records a render pass to `target` with `bind_group` set.
+fn render_pass_with(
+    ctx: &TestingContext,
+    target: &wgpu::TextureView,
+    bind_group: &wgpu::BindGroup,
+) -> wgpu::CommandBuffer {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_bind_group(0, bind_group, &[]);
+    }
+    encoder.finish()
+}
+
+// The views and samplers of a bind group must outlive the submissions using
+// it, even when they and the bind group are all dropped while those are in
+// flight.
+#[gpu_test]
+static BIND_GROUP_DROP_IN_FLIGHT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_async(|ctx| async move {
+        let target = ctx.device.create_texture(&SAMPLED_TEXTURE);
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+        for _ in 0..2 {
+            let (texture, view, sampler, bind_group) = sampled_bind_group(&ctx);
+            ctx.queue
+                .submit(Some(render_pass_with(&ctx, &target_view, &bind_group)));
+
+            drop(bind_group);
+            drop(sampler);
+            drop(view);
+            drop(texture);
+            ctx.async_poll(wgpu::Maintain::Poll)
+                .await
+                .panic_on_timeout();
+        }
+
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+    });
+
+// A bind group used by several command buffers of a submission, and then by a
+// later submission, must keep its view and sampler alive until the later
+// submission is done. The view and sampler are dropped first, so only the
+// bind group keeps them alive.
+#[gpu_test]
+static BIND_GROUP_REUSED_ACROSS_SUBMISSIONS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_async(|ctx| async move {
+        let target = ctx.device.create_texture(&SAMPLED_TEXTURE);
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let (texture, view, sampler, bind_group) = sampled_bind_group(&ctx);
+
+        ctx.queue.submit([
+            render_pass_with(&ctx, &target_view, &bind_group),
+            render_pass_with(&ctx, &target_view, &bind_group),
+        ]);
+        drop(sampler);
+        drop(view);
+        drop(texture);
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        ctx.queue
+            .submit(Some(render_pass_with(&ctx, &target_view, &bind_group)));
+        drop(bind_group);
+        ctx.async_poll(wgpu::Maintain::Poll)
+            .await
+            .panic_on_timeout();
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+    });
@@ -0,0 +1,155 @@
+//! Tests for custom Vulkan memory allocators.
+#![cfg(any(
+    windows,
+    all(
+        unix,
+        not(target_os = "emscripten"),
+        not(target_os = "ios"),
+        not(target_os = "macos")
+    )
+))]
+
+use std::{
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use wgpu::hal::{api::Vulkan, vulkan, DeviceError};
+use wgpu_test::{gpu_test, GpuTestConfiguration};
+
+/// Counts the allocations and mappings it forwards to a
+/// `GpuAllocMemoryAllocator`.
+#[derive(Debug)]
+struct CountingAllocator {
+    inner: vulkan::GpuAllocMemoryAllocator,
+    live_allocations: Arc<AtomicUsize>,
+    mappings: Arc<AtomicUsize>,
+}
+
+impl vulkan::MemoryAllocator for CountingAllocator {
+    unsafe fn allocate(
+        &self,
+        device: &ash::Device,
+        request: &vulkan::MemoryRequest,
+    ) -> Result<vulkan::MemoryAllocation, DeviceError> {
+        let allocation = unsafe { self.inner.allocate(device, request) }?;
+        self.live_allocations.fetch_add(1, Ordering::SeqCst);
+        Ok(allocation)
+    }
+
+    unsafe fn deallocate(&self, device: &ash::Device, allocation: vulkan::MemoryAllocation) {
+        self.live_allocations.fetch_sub(1, Ordering::SeqCst);
+        unsafe { self.inner.deallocate(device, allocation) }
+    }
+
+    unsafe fn map(
+        &self,
+        device: &ash::Device,
+        allocation: &mut vulkan::MemoryAllocation,
+        offset: u64,
+        size: usize,
+    ) -> Result<NonNull<u8>, DeviceError> {
+        self.mappings.fetch_add(1, Ordering::SeqCst);
+        unsafe { self.inner.map(device, allocation, offset, size) }
+    }
+
+    unsafe fn unmap(&self, device: &ash::Device, allocation: &mut vulkan::MemoryAllocation) {
+        unsafe { self.inner.unmap(device, allocation) }
+    }
+
+    unsafe fn cleanup(&self, device: &ash::Device) {
+        unsafe { self.inner.cleanup(device) }
+    }
+}
+
+#[gpu_test]
+static CUSTOM_MEMORY_ALLOCATOR: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let live_allocations = Arc::new(AtomicUsize::new(0));
+        let mappings = Arc::new(AtomicUsize::new(0));
+
+        let open_device = unsafe {
+            ctx.adapter.as_hal::<Vulkan, _, _>(|adapter| {
+                let adapter = adapter?;
+                let extensions = adapter.required_device_extensions(wgpu::Features::empty());
+                let allocator = CountingAllocator {
+                    inner: vulkan::GpuAllocMemoryAllocator::new(
+                        adapter,
+                        &extensions,
+                        Default::default(),
+                    ),
+                    live_allocations: Arc::clone(&live_allocations),
+                    mappings: Arc::clone(&mappings),
+                };
+                Some(
+                    adapter
+                        .open_with_memory_allocator(
+                            wgpu::Features::empty(),
+                            &wgpu::MemoryHints::default(),
+                            Some(Box::new(allocator)),
+                        )
+                        .unwrap(),
+                )
+            })
+        };
+        // Only Vulkan adapters take custom allocators.
+        let Some(open_device) = open_device else {
+            return;
+        };
+        let (device, queue) = unsafe {
+            ctx.adapter.create_device_from_hal(
+                open_device,
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    required_features: wgpu::Features::empty(),
+                    required_limits: ctx.adapter.limits(),
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                None,
+            )
+        }
+        .unwrap();
+        device.poll(wgpu::Maintain::wait()).panic_on_timeout();
+        let allocations_before = live_allocations.load(Ordering::SeqCst);
+
+        let data: Vec<u8> = (0..=255).collect();
+        let upload = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: data.len() as u64,
+            usage: wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        upload
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(&data);
+        upload.unmap();
+        let download = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: data.len() as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        assert!(live_allocations.load(Ordering::SeqCst) >= allocations_before + 2);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&upload, 0, &download, 0, data.len() as u64);
+        queue.submit(Some(encoder.finish()));
+
+        download
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, Result::unwrap);
+        device.poll(wgpu::Maintain::wait()).panic_on_timeout();
+        assert_eq!(&*download.slice(..).get_mapped_range(), &data[..]);
+        download.unmap();
+        assert!(mappings.load(Ordering::SeqCst) >= 2);
+
+        // Destroyed buffers give their memory back to the allocator.
+        drop(upload);
+        drop(download);
+        device.poll(wgpu::Maintain::wait()).panic_on_timeout();
+        assert_eq!(live_allocations.load(Ordering::SeqCst), allocations_before);
+    });
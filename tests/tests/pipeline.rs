@@ -29,6 +29,7 @@ static PIPELINE_DEFAULT_LAYOUT_BAD_MODULE: GpuTestConfiguration = GpuTestConfigu
                     module: &module,
                     entry_point: "doesn't exist",
                     compilation_options: Default::default(),
+                    cache: None,
                 });
 
             pipeline.get_bind_group_layout(0);
@@ -0,0 +1,74 @@
+use wgpu_test::{fail, gpu_test, valid, GpuTestConfiguration, TestParameters, TestingContext};
+
+const SHADER: &str = "
+@group(0) @binding(0)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = id.x * 2u;
+}
+";
+
+fn create_pipeline(ctx: &TestingContext, cache: Option<&wgpu::PipelineCache>) {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pipeline cache shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+    ctx.device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("pipeline cache pipeline"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            compilation_options: Default::default(),
+            cache,
+        });
+}
+
+fn create_cache(ctx: &TestingContext, data: Option<&[u8]>, fallback: bool) -> wgpu::PipelineCache {
+    unsafe {
+        ctx.device
+            .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("pipeline cache"),
+                data,
+                fallback,
+            })
+    }
+}
+
+#[gpu_test]
+static PIPELINE_CACHE_ROUND_TRIP: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::PIPELINE_CACHE))
+    .run_sync(|ctx| {
+        let cache = valid(&ctx.device, || create_cache(&ctx, None, false));
+        valid(&ctx.device, || create_pipeline(&ctx, Some(&cache)));
+        let data = cache
+            .get_data()
+            .expect("Getting the data of a pipeline cache failed");
+        drop(cache);
+
+        let cache = valid(&ctx.device, || create_cache(&ctx, Some(&data), false));
+        valid(&ctx.device, || create_pipeline(&ctx, Some(&cache)));
+        assert!(cache.get_data().is_some());
+    });
+
+#[gpu_test]
+static PIPELINE_CACHE_INVALID_DATA: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::PIPELINE_CACHE))
+    .run_sync(|ctx| {
+        let cache = valid(&ctx.device, || create_cache(&ctx, None, false));
+        create_pipeline(&ctx, Some(&cache));
+        let mut data = cache.get_data().unwrap();
+        *data.last_mut().unwrap() ^= 1;
+
+        // Corrupted data is rejected, unless falling back to an empty cache.
+        fail(&ctx.device, || create_cache(&ctx, Some(&data), false));
+        let cache = valid(&ctx.device, || create_cache(&ctx, Some(&data), true));
+        valid(&ctx.device, || create_pipeline(&ctx, Some(&cache)));
+        fail(&ctx.device, || {
+            create_cache(&ctx, Some(b"not a pipeline cache"), false)
+        });
+    });
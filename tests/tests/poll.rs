@@ -125,3 +125,31 @@ static WAIT_OUT_OF_ORDER: GpuTestConfiguration =
             .await
             .panic_on_timeout();
     });
+
+#[gpu_test]
+static WAIT_ON_BATCHED_SUBMISSION: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_async(|ctx| async move {
+        let data1 = DummyWorkData::new(&ctx);
+        let data2 = DummyWorkData::new(&ctx);
+
+        ctx.queue
+            .set_submission_batching(wgpu::SubmissionBatching::Batched { max_submissions: 8 });
+
+        // Waiting sends the held back submissions.
+        let index1 = ctx.queue.submit(Some(data1.cmd_buf));
+        let index2 = ctx.queue.submit(Some(data2.cmd_buf));
+        ctx.async_poll(Maintain::wait_for(index1))
+            .await
+            .panic_on_timeout();
+        ctx.async_poll(Maintain::wait_for(index2))
+            .await
+            .panic_on_timeout();
+
+        let data3 = DummyWorkData::new(&ctx);
+        ctx.queue.submit(Some(data3.cmd_buf));
+        ctx.queue.flush();
+        ctx.async_poll(Maintain::wait()).await.panic_on_timeout();
+
+        ctx.queue
+            .set_submission_batching(wgpu::SubmissionBatching::Immediate);
+    });
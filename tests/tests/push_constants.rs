@@ -104,6 +104,7 @@ async fn partial_update_test(ctx: TestingContext) {
             module: &sm,
             entry_point: "main",
             compilation_options: Default::default(),
+            cache: None,
         });
 
     let mut encoder = ctx
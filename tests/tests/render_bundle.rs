@@ -0,0 +1,124 @@
+use wgpu_test::{gpu_test, image, GpuTestConfiguration, TestingContext};
+
+const TEXTURE_HEIGHT: u32 = 2;
+const TEXTURE_WIDTH: u32 = 2;
+const BUFFER_SIZE: usize = (TEXTURE_WIDTH * TEXTURE_HEIGHT * 4) as usize;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+fn create_bundle(ctx: &TestingContext) -> wgpu::RenderBundle {
+    let shader = ctx
+        .device
+        .create_shader_module(wgpu::include_wgsl!("scissor_tests/solid_white.wgsl"));
+
+    let pipeline = ctx
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(FORMAT.into())],
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+    let mut encoder =
+        ctx.device
+            .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: Some("Bundle encoder"),
+                color_formats: &[Some(FORMAT)],
+                depth_stencil: None,
+                sample_count: 1,
+                multiview: None,
+            });
+    encoder.set_pipeline(&pipeline);
+    encoder.draw(0..3, 0..1);
+    encoder.finish(&wgpu::RenderBundleDescriptor {
+        label: Some("Bundle"),
+    })
+}
+
+/// Executes `bundle` in a pass over a new texture, after setting the scissor
+/// rectangle to `scissor` if it is given, and checks the texture's contents.
+async fn execute_bundle(
+    ctx: &TestingContext,
+    bundle: &wgpu::RenderBundle,
+    scissor: Option<[u32; 4]>,
+    expected_data: &[u8; BUFFER_SIZE],
+) {
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen texture"),
+        size: wgpu::Extent3d {
+            width: TEXTURE_WIDTH,
+            height: TEXTURE_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let readback_buffer = image::ReadbackBuffers::new(&ctx.device, &texture);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Renderpass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some([x, y, width, height]) = scissor {
+            render_pass.set_scissor_rect(x, y, width, height);
+        }
+        render_pass.execute_bundles([bundle]);
+    }
+    readback_buffer.copy_from(&ctx.device, &mut encoder, &texture);
+    ctx.queue.submit(Some(encoder.finish()));
+    readback_buffer
+        .assert_buffer_contents(ctx, expected_data)
+        .await;
+}
+
+// Passes that only execute bundles can run them natively on every backend
+// that has native bundles, while other passes can only on some. The bundle
+// must render the same either way, and inherit the scissor rectangle of the
+// pass when there is one.
+#[gpu_test]
+static RENDER_BUNDLE_NATIVE_AND_REPLAYED: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_async(|ctx| async move {
+        let bundle = create_bundle(&ctx);
+
+        execute_bundle(&ctx, &bundle, None, &[255; BUFFER_SIZE]).await;
+
+        let mut expected_data = [0; BUFFER_SIZE];
+        expected_data[..4].copy_from_slice(&[255; 4]);
+        execute_bundle(&ctx, &bundle, Some([0, 0, 1, 1]), &expected_data).await;
+
+        execute_bundle(&ctx, &bundle, None, &[255; BUFFER_SIZE]).await;
+    });
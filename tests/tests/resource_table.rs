@@ -0,0 +1,68 @@
+//! Tests for `wgpu::util::ResourceTable`.
+
+use std::num::NonZeroU32;
+
+use wgpu::util::{ResourceTable, ResourceTableDescriptor};
+use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
+
+fn create_view(ctx: &TestingContext) -> wgpu::TextureView {
+    ctx.device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+#[gpu_test]
+static RESOURCE_TABLE_RECYCLES_INDICES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::TEXTURE_BINDING_ARRAY))
+    .run_async(|ctx| async move {
+        let mut table = ResourceTable::new(
+            &ctx.device,
+            &ResourceTableDescriptor {
+                label: Some("textures"),
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                capacity: NonZeroU32::new(3).unwrap(),
+            },
+            create_view(&ctx),
+        );
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        // An empty table binds the placeholder.
+        table.bind_group(&ctx.device);
+
+        assert_eq!(table.insert(create_view(&ctx)).unwrap(), 0);
+        assert_eq!(table.insert(create_view(&ctx)).unwrap(), 1);
+        assert_eq!(table.insert(create_view(&ctx)).unwrap(), 2);
+        assert!(table.insert(create_view(&ctx)).is_err());
+        table.bind_group(&ctx.device);
+
+        assert!(table.remove(1).is_some());
+        assert!(table.remove(1).is_none());
+        assert!(table.get(1).is_none());
+        assert_eq!(table.len(), 2);
+        table.bind_group(&ctx.device);
+
+        assert_eq!(table.insert(create_view(&ctx)).unwrap(), 1);
+        assert_eq!(table.len(), 3);
+        table.bind_group(&ctx.device);
+
+        assert!(ctx.device.pop_error_scope().await.is_none());
+    });
@@ -5,36 +5,47 @@ mod regression {
     mod issue_4122;
 }
 
+mod barriers;
 mod bgra8unorm_storage;
+mod bind_group_allocation;
 mod bind_group_layout_dedup;
 mod buffer;
 mod buffer_copy;
 mod buffer_usages;
 mod clear_texture;
+mod clip_distances;
 mod create_surface_error;
+mod cross_device;
 mod device;
 mod encoder;
+mod external_memory;
 mod external_texture;
 mod float32_filterable;
 mod instance;
 mod life_cycle;
 mod mem_leaks;
+mod memory_allocator;
 mod nv12_texture;
 mod occlusion_query;
 mod partially_bounded_arrays;
 mod pipeline;
+mod pipeline_cache;
 mod poll;
 mod push_constants;
 mod query_set;
 mod queue_transfer;
+mod render_bundle;
 mod resource_descriptor_accessor;
 mod resource_error;
+mod resource_table;
 mod scissor_tests;
 mod shader;
 mod shader_primitive_index;
 mod shader_view_format;
+mod shared_texture;
 mod subgroup_operations;
 mod texture_bounds;
+mod texture_readback;
 mod texture_view_creation;
 mod transfer;
 mod vertex_indices;
@@ -1,6 +1,6 @@
 use wgpu::include_wgsl;
 
-use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters};
+use wgpu_test::{gpu_test, FailureCase, GpuTestConfiguration, TestParameters};
 
 #[gpu_test]
 static SHADER_COMPILE_SUCCESS: GpuTestConfiguration = GpuTestConfiguration::new()
@@ -47,3 +47,83 @@ static SHADER_COMPILE_ERROR: GpuTestConfiguration = GpuTestConfiguration::new()
             "Expected the column number to be 33, because we're counting lines from 1"
         );
     });
+
+#[gpu_test]
+static SHADER_COMPILE_INCLUDE_ERROR: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default().skip(FailureCase::backend(wgpu::Backends::BROWSER_WEBGPU)),
+    )
+    .run_async(|ctx| async move {
+        let resolve = |path: &str| match path {
+            "error_shader.wgsl" => Ok(include_str!("error_shader.wgsl").to_string()),
+            _ => Err(format!("unknown file {path}")),
+        };
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let sm = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::WgslWithIncludes {
+                    source:
+                        "// A shader split over several files.\n// #include \"error_shader.wgsl\"\n"
+                            .into(),
+                    resolve: wgpu::IncludeResolver(&resolve),
+                },
+            });
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+
+        // The error is located in the included file, not the expanded source.
+        let compilation_info = sm.get_compilation_info().await;
+        let error_message = compilation_info
+            .messages
+            .iter()
+            .find(|message| message.message_type == wgpu::CompilationMessageType::Error)
+            .expect("Expected error message not found");
+        let span = error_message.location.expect("Expected span not found");
+        assert_eq!(span.offset, 32);
+        assert_eq!(span.line_number, 1);
+        assert_eq!(span.line_position, 33);
+    });
+
+#[gpu_test]
+static SHADER_COMPILE_INCLUDE_VALIDATION_ERROR: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default().skip(FailureCase::backend(wgpu::Backends::BROWSER_WEBGPU)),
+    )
+    .run_async(|ctx| async move {
+        // Parses fine, but fails validation without `Features::SHADER_F64`.
+        let resolve = |path: &str| match path {
+            "doubles.wgsl" => Ok("// Doubles.\nvar<private> x: f64;\n".to_string()),
+            _ => Err(format!("unknown file {path}")),
+        };
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let sm = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::WgslWithIncludes {
+                    source:
+                        "// A shader split over several files.\n\n// #include \"doubles.wgsl\"\n"
+                            .into(),
+                    resolve: wgpu::IncludeResolver(&resolve),
+                },
+            });
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+
+        // The error is located in the included file, which is named in the
+        // messages.
+        let compilation_info = sm.get_compilation_info().await;
+        let error_message = compilation_info
+            .messages
+            .iter()
+            .find(|message| message.message_type == wgpu::CompilationMessageType::Error)
+            .expect("Expected error message not found");
+        let span = error_message.location.expect("Expected span not found");
+        assert_eq!(span.line_number, 2);
+        assert!(compilation_info
+            .messages
+            .iter()
+            .any(|message| message.message.contains("included as `doubles.wgsl`")));
+    });
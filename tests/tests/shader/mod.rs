@@ -17,6 +17,7 @@ use wgpu_test::TestingContext;
 
 pub mod compilation_messages;
 pub mod numeric_builtins;
+pub mod reflection;
 pub mod struct_layout;
 pub mod zero_init_workgroup_mem;
 
@@ -309,6 +310,7 @@ async fn shader_input_output_test(
                 module: &sm,
                 entry_point: "cs_main",
                 compilation_options: Default::default(),
+                cache: None,
             });
 
         // -- Initializing data --
@@ -0,0 +1,143 @@
+use wgpu::{
+    BindingType, BufferBindingType, ShaderStages, TextureSampleType, TextureViewDimension,
+    VertexFormat,
+};
+
+use wgpu_test::{gpu_test, FailureCase, GpuTestConfiguration, TestParameters};
+
+const SHADER: &str = r#"
+    struct Globals {
+        transform: mat4x4<f32>,
+    }
+
+    @group(0) @binding(0) var<uniform> globals: Globals;
+    @group(1) @binding(0) var color_texture: texture_2d<f32>;
+    @group(1) @binding(1) var color_sampler: sampler;
+    @group(0) @binding(1) var<storage, read_write> counts: array<u32>;
+
+    struct VertexOutput {
+        @builtin(position) position: vec4<f32>,
+        @location(0) uv: vec2<f32>,
+    }
+
+    @vertex
+    fn vs_main(@location(1) uv: vec2<f32>, @location(0) position: vec3<f32>) -> VertexOutput {
+        return VertexOutput(globals.transform * vec4(position, 1.0), uv);
+    }
+
+    @fragment
+    fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+        return textureSample(color_texture, color_sampler, in.uv);
+    }
+
+    @compute @workgroup_size(8, 4)
+    fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+        counts[id.x] += 1u;
+    }
+"#;
+
+#[gpu_test]
+static SHADER_REFLECTION: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default().skip(FailureCase::backend(wgpu::Backends::BROWSER_WEBGPU)),
+    )
+    .run_sync(|ctx| {
+        let sm = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let reflection = sm.reflect().expect("Expected reflection data");
+
+        let names: Vec<_> = reflection
+            .entry_points
+            .iter()
+            .map(|ep| ep.name.as_str())
+            .collect();
+        assert_eq!(names, ["vs_main", "fs_main", "cs_main"]);
+
+        let vs = reflection
+            .entry_point(ShaderStages::VERTEX, "vs_main")
+            .unwrap();
+        assert_eq!(vs.bindings.len(), 1);
+        assert_eq!(vs.bindings[0].name.as_deref(), Some("globals"));
+        assert_eq!(vs.bindings[0].group, 0);
+        assert_eq!(vs.bindings[0].entry.visibility, ShaderStages::VERTEX);
+        assert!(matches!(
+            vs.bindings[0].entry.ty,
+            BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                ..
+            }
+        ));
+        let inputs: Vec<_> = vs
+            .vertex_inputs
+            .iter()
+            .map(|input| (input.location, input.format))
+            .collect();
+        assert_eq!(
+            inputs,
+            [(0, VertexFormat::Float32x3), (1, VertexFormat::Float32x2)]
+        );
+
+        let fs = reflection
+            .entry_point(ShaderStages::FRAGMENT, "fs_main")
+            .unwrap();
+        assert_eq!(fs.bindings.len(), 2);
+        assert!(matches!(
+            fs.bindings[0].entry.ty,
+            BindingType::Texture {
+                sample_type: TextureSampleType::Float { .. },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            }
+        ));
+        assert!(matches!(fs.bindings[1].entry.ty, BindingType::Sampler(_)));
+        assert_eq!(fs.color_outputs.len(), 1);
+        assert_eq!(fs.color_outputs[0].location, 0);
+        assert_eq!(fs.color_outputs[0].components, 4);
+
+        let cs = reflection
+            .entry_point(ShaderStages::COMPUTE, "cs_main")
+            .unwrap();
+        assert_eq!(cs.workgroup_size, [8, 4, 1]);
+        assert_eq!(cs.bindings.len(), 1);
+        assert!(matches!(
+            cs.bindings[0].entry.ty,
+            BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                ..
+            }
+        ));
+        assert!(cs.push_constant_ranges.is_empty());
+    });
+
+#[gpu_test]
+static PIPELINE_BINDING_BY_NAME: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default().skip(FailureCase::backend(wgpu::Backends::BROWSER_WEBGPU)),
+    )
+    .run_sync(|ctx| {
+        let sm = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &sm,
+                entry_point: "cs_main",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        assert_eq!(pipeline.get_binding("counts"), Some((0, 1)));
+        // Declared in the module, but not used by the entry point.
+        assert_eq!(pipeline.get_binding("globals"), None);
+        assert_eq!(pipeline.get_binding("missing"), None);
+    });
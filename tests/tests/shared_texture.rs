@@ -0,0 +1,116 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Tests for textures shared with other processes, imported back into the
+//! same device.
+//!
+//! Only opaque file descriptors and NT handles are supported, so there is
+//! nothing to test for DMA-BUF or `IOSurface` sharing.
+
+use wgpu_test::{fail, gpu_test, valid, GpuTestConfiguration, TestParameters, TestingContext};
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 4;
+
+fn texture_desc() -> wgpu::TextureDescriptor<'static> {
+    wgpu::TextureDescriptor {
+        label: Some("shared texture"),
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Uint,
+        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    }
+}
+
+fn import(ctx: &TestingContext, texture: &wgpu::Texture) -> wgpu::Texture {
+    let handle = texture
+        .export_shared()
+        .expect("Exporting the shared texture failed");
+    unsafe { ctx.device.import_shared_texture(handle, &texture_desc()) }
+}
+
+#[gpu_test]
+static SHARED_TEXTURE_ROUND_TRIP: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::SHARED_TEXTURES))
+    .run_async(|ctx| async move {
+        let texture = ctx.device.create_shared_texture(&texture_desc());
+        let imported = valid(&ctx.device, || import(&ctx, &texture));
+
+        let data = vec![7u8; (WIDTH * HEIGHT) as usize];
+        ctx.queue.write_texture(
+            texture.as_image_copy(),
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(WIDTH),
+                rows_per_image: None,
+            },
+            texture.size(),
+        );
+        ctx.queue.release_shared_texture(&texture);
+        ctx.queue.submit(None);
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        ctx.queue.acquire_shared_texture(&imported);
+        let read_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (WIDTH * HEIGHT) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            imported.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &read_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(WIDTH),
+                    rows_per_image: None,
+                },
+            },
+            imported.size(),
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = read_buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+        assert_eq!(*slice.get_mapped_range(), data[..]);
+    });
+
+#[gpu_test]
+static SHARED_TEXTURE_OWNERSHIP: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::SHARED_TEXTURES))
+    .run_sync(|ctx| {
+        let texture = ctx.device.create_shared_texture(&texture_desc());
+        let imported = import(&ctx, &texture);
+
+        // The creating process owns the texture, and the importing one
+        // doesn't until it's released.
+        fail(&ctx.device, || ctx.queue.acquire_shared_texture(&texture));
+        fail(&ctx.device, || ctx.queue.release_shared_texture(&imported));
+
+        valid(&ctx.device, || ctx.queue.release_shared_texture(&texture));
+        fail(&ctx.device, || ctx.queue.release_shared_texture(&texture));
+        ctx.queue.submit(None);
+
+        valid(&ctx.device, || ctx.queue.acquire_shared_texture(&imported));
+        fail(&ctx.device, || ctx.queue.acquire_shared_texture(&imported));
+
+        // Regular textures can't be exported.
+        let regular = ctx.device.create_texture(&texture_desc());
+        assert!(regular.export_shared().is_err());
+        fail(&ctx.device, || ctx.queue.release_shared_texture(&regular));
+    });
@@ -0,0 +1,54 @@
+//! Tests for `wgpu::util::DownloadTexture`.
+
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::{DeviceExt, DownloadTexture};
+use wgpu_test::{gpu_test, GpuTestConfiguration};
+
+#[gpu_test]
+static READ_TEXTURE_WITHOUT_ROW_PADDING: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_async(|ctx| async move {
+        // Rows of 20 bytes, which copies pad to 256.
+        let size = wgpu::Extent3d {
+            width: 5,
+            height: 3,
+            depth_or_array_layers: 2,
+        };
+        let data: Vec<u8> = (0..5 * 3 * 2 * 4).map(|i| i as u8).collect();
+        let texture = ctx.device.create_texture_with_data(
+            &ctx.queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &data,
+        );
+
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = Arc::clone(&result);
+        DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            texture.as_image_copy(),
+            size,
+            move |download| *result_clone.lock().unwrap() = Some(download),
+        );
+        ctx.async_poll(wgpu::Maintain::wait())
+            .await
+            .panic_on_timeout();
+
+        let download = result.lock().unwrap().take().unwrap().unwrap();
+        assert_eq!(download.format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(download.bytes_per_row, 20);
+        assert_eq!(download.rows_per_image, 3);
+        assert_eq!(download.rows().len(), 6);
+        assert_eq!(download.image(1), &data[60..]);
+        assert_eq!(download.data, data);
+    });
@@ -1,6 +1,9 @@
 //! Tests for texture copy
 
-use wgpu_test::{gpu_test, GpuTestConfiguration};
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::DownloadTexture;
+use wgpu_test::{fail, gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
 
 #[gpu_test]
 static WRITE_TEXTURE_SUBSET_2D: GpuTestConfiguration =
@@ -191,3 +194,123 @@ static WRITE_TEXTURE_SUBSET_3D: GpuTestConfiguration =
             assert_eq!(*byte, 0);
         }
     });
+
+/// Creates a 64x64 texture of `format`, writes `data` to its `aspect` with
+/// `Queue::write_texture`, and returns what it reads back.
+async fn write_and_read_aspect(
+    ctx: &TestingContext,
+    format: wgpu::TextureFormat,
+    aspect: wgpu::TextureAspect,
+    data: &[u8],
+) -> Vec<u8> {
+    let size = wgpu::Extent3d {
+        width: 64,
+        height: 64,
+        depth_or_array_layers: 1,
+    };
+    let tex = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        dimension: wgpu::TextureDimension::D2,
+        size,
+        format,
+        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        mip_level_count: 1,
+        sample_count: 1,
+        view_formats: &[],
+    });
+    let copy = wgpu::ImageCopyTexture {
+        texture: &tex,
+        mip_level: 0,
+        origin: wgpu::Origin3d::ZERO,
+        aspect,
+    };
+    ctx.queue.write_texture(
+        copy,
+        data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(data.len() as u32 / size.height),
+            rows_per_image: None,
+        },
+        size,
+    );
+
+    let result = Arc::new(Mutex::new(None));
+    let result_clone = Arc::clone(&result);
+    DownloadTexture::read_texture(&ctx.device, &ctx.queue, copy, size, move |download| {
+        *result_clone.lock().unwrap() = Some(download)
+    });
+    ctx.async_poll(wgpu::Maintain::wait())
+        .await
+        .panic_on_timeout();
+    let download = result.lock().unwrap().take().unwrap().unwrap();
+    download.data
+}
+
+#[gpu_test]
+static WRITE_TEXTURE_DEPTH32FLOAT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            .features(wgpu::Features::DEPTH32FLOAT_COPY_DST)
+            .downlevel_flags(wgpu::DownlevelFlags::DEPTH_TEXTURE_AND_BUFFER_COPIES),
+    )
+    .run_async(|ctx| async move {
+        let depth: Vec<f32> = (0..64 * 64).map(|i| i as f32 / 4096.0).collect();
+        let data: &[u8] = bytemuck::cast_slice(&depth);
+
+        let read = write_and_read_aspect(
+            &ctx,
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureAspect::All,
+            data,
+        )
+        .await;
+        assert_eq!(read, data);
+    });
+
+#[gpu_test]
+static WRITE_TEXTURE_STENCIL8: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::DEPTH_TEXTURE_AND_BUFFER_COPIES),
+    )
+    .run_async(|ctx| async move {
+        let data: Vec<u8> = (0..64 * 64).map(|i| i as u8).collect();
+
+        let read = write_and_read_aspect(
+            &ctx,
+            wgpu::TextureFormat::Stencil8,
+            wgpu::TextureAspect::All,
+            &data,
+        )
+        .await;
+        assert_eq!(read, data);
+    });
+
+#[gpu_test]
+static WRITE_TEXTURE_DEPTH32FLOAT_WITHOUT_FEATURE: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let tex = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            dimension: wgpu::TextureDimension::D2,
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::COPY_DST,
+            mip_level_count: 1,
+            sample_count: 1,
+            view_formats: &[],
+        });
+
+        fail(&ctx.device, || {
+            ctx.queue.write_texture(
+                tex.as_image_copy(),
+                &[0; 4],
+                wgpu::ImageDataLayout::default(),
+                wgpu::Extent3d::default(),
+            )
+        });
+    });
@@ -34,6 +34,8 @@ pub enum BindGroupLayoutEntryError {
     StorageTextureCube,
     #[error("Read-write and read-only storage textures are not allowed by webgpu, they require the native only feature TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES")]
     StorageTextureReadWrite,
+    #[error("Atomic storage textures must have the `R32Uint` or `R32Sint` format, got {0:?}")]
+    StorageTextureAtomicFormat(wgt::TextureFormat),
     #[error("Arrays of bindings unsupported for this type of binding")]
     ArrayUnsupported,
     #[error("Multisampled binding with sample type `TextureSampleType::Float` must have filterable set to false.")]
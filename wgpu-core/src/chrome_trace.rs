@@ -0,0 +1,382 @@
+//! Recording `tracing` spans into chrome://tracing files.
+//!
+//! With the `tracing` feature, the API entry points of wgpu-core and the
+//! queue submissions of wgpu-hal are `tracing` spans. A [`ChromeTrace`]
+//! collects the spans entered on every thread, along with GPU spans the
+//! application measured, and writes them in the [Trace Event Format] read by
+//! chrome://tracing and [Perfetto].
+//!
+//! GPU spans come from timestamp queries the application writes and
+//! resolves itself: wgpu doesn't calibrate the GPU clock against the CPU one.
+//! A [`GpuClock`] places GPU timestamps on the CPU timeline, given one moment
+//! at which both clocks were read, like after waiting on a submission that
+//! wrote a timestamp at its end.
+//!
+//! ```ignore
+//! let trace = wgpu_core::chrome_trace::ChromeTrace::new();
+//! tracing::subscriber::set_global_default(trace.subscriber()).unwrap();
+//! // ... render some frames, and record GPU spans ...
+//! trace.write(std::fs::File::create("wgpu.json")?)?;
+//! ```
+//!
+//! [Trace Event Format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [Perfetto]: https://ui.perfetto.dev
+
+use std::{
+    borrow::Cow,
+    io::{self, Write as _},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use tracing::{span, Event, Metadata, Subscriber};
+
+use crate::FastHashMap;
+
+const CPU_PID: u32 = 1;
+const GPU_PID: u32 = 2;
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Begin,
+    End,
+    Complete { duration: Duration },
+}
+
+#[derive(Debug)]
+struct TraceEvent {
+    name: Cow<'static, str>,
+    phase: Phase,
+    pid: u32,
+    tid: u64,
+    /// Time since the trace started.
+    time: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    /// Names and reference counts of the spans that are still open.
+    spans: FastHashMap<u64, (&'static str, usize)>,
+    events: Vec<TraceEvent>,
+    /// Names of the CPU threads and GPU tracks, by `tid`.
+    threads: FastHashMap<(u32, u64), String>,
+    gpu_tracks: FastHashMap<String, u64>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    start: Instant,
+    next_span_id: AtomicU64,
+    recording: Mutex<Recording>,
+}
+
+/// A trace of CPU and GPU spans, to be written as a chrome://tracing file.
+///
+/// See the [module-level documentation](self).
+#[derive(Clone, Debug)]
+pub struct ChromeTrace {
+    shared: Arc<Shared>,
+}
+
+impl Default for ChromeTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChromeTrace {
+    /// Starts an empty trace. Timestamps in the file are relative to now.
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                start: Instant::now(),
+                next_span_id: AtomicU64::new(1),
+                recording: Mutex::new(Recording::default()),
+            }),
+        }
+    }
+
+    /// Returns a `tracing` subscriber recording the spans it sees into this
+    /// trace, to be installed with `tracing::subscriber::set_global_default`
+    /// or `tracing::subscriber::with_default`.
+    pub fn subscriber(&self) -> ChromeTraceSubscriber {
+        ChromeTraceSubscriber {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Records a span `label` that ran on the GPU from `start` to `end`, on the
+    /// track named `track`.
+    ///
+    /// Each track, typically one per queue, is shown as a thread of a
+    /// separate "GPU" process.
+    pub fn record_gpu_span(&self, track: &str, label: &str, start: Instant, end: Instant) {
+        let time = start.saturating_duration_since(self.shared.start);
+        let duration = end.saturating_duration_since(start);
+        let mut recording = self.shared.recording.lock();
+        let next_tid = recording.gpu_tracks.len() as u64 + 1;
+        let tid = *recording
+            .gpu_tracks
+            .entry(track.to_string())
+            .or_insert(next_tid);
+        recording
+            .threads
+            .entry((GPU_PID, tid))
+            .or_insert_with(|| track.to_string());
+        recording.events.push(TraceEvent {
+            name: Cow::Owned(label.to_string()),
+            phase: Phase::Complete { duration },
+            pid: GPU_PID,
+            tid,
+            time,
+        });
+    }
+
+    /// Writes the trace recorded so far as JSON, with the events sorted by time.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let recording = self.shared.recording.lock();
+
+        let mut events = recording.events.iter().collect::<Vec<_>>();
+        // Sorting is stable, so begin and end events at the same time stay in
+        // order.
+        events.sort_by_key(|event| event.time);
+
+        write!(writer, "{{\"traceEvents\":[")?;
+        let mut first = true;
+        let mut separator = |writer: &mut W| -> io::Result<()> {
+            if !first {
+                write!(writer, ",")?;
+            }
+            first = false;
+            writeln!(writer)
+        };
+
+        for (pid, name) in [(CPU_PID, "CPU"), (GPU_PID, "GPU")] {
+            separator(&mut writer)?;
+            write!(
+                writer,
+                "{{\"name\":\"process_name\",\"ph\":\"M\",\"pid\":{pid},\"args\":{{\"name\":\"{name}\"}}}}"
+            )?;
+        }
+        let mut threads = recording.threads.iter().collect::<Vec<_>>();
+        threads.sort();
+        for (&(pid, tid), name) in threads {
+            separator(&mut writer)?;
+            write!(
+                writer,
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{pid},\"tid\":{tid},\"args\":{{\"name\":\"{}\"}}}}",
+                Escaped(name)
+            )?;
+        }
+
+        for event in events {
+            separator(&mut writer)?;
+            let ph = match event.phase {
+                Phase::Begin => "B",
+                Phase::End => "E",
+                Phase::Complete { .. } => "X",
+            };
+            write!(
+                writer,
+                "{{\"name\":\"{}\",\"ph\":\"{ph}\",\"pid\":{},\"tid\":{},\"ts\":{}",
+                Escaped(&event.name),
+                event.pid,
+                event.tid,
+                micros(event.time),
+            )?;
+            if let Phase::Complete { duration } = event.phase {
+                write!(writer, ",\"dur\":{}", micros(duration))?;
+            }
+            write!(writer, "}}")?;
+        }
+
+        writeln!(writer, "]}}")
+    }
+}
+
+/// A moment at which both the CPU and the GPU clocks were read, to place GPU
+/// timestamps on the CPU timeline.
+#[derive(Clone, Copy, Debug)]
+pub struct GpuClock {
+    /// The CPU time.
+    pub instant: Instant,
+    /// The GPU time, in nanoseconds: a timestamp query result multiplied by
+    /// the queue's timestamp period.
+    pub gpu_nanoseconds: u64,
+}
+
+impl GpuClock {
+    /// Returns the CPU time at which the GPU clock read `gpu_nanoseconds`.
+    pub fn to_instant(&self, gpu_nanoseconds: u64) -> Instant {
+        if gpu_nanoseconds >= self.gpu_nanoseconds {
+            self.instant + Duration::from_nanos(gpu_nanoseconds - self.gpu_nanoseconds)
+        } else {
+            let before = Duration::from_nanos(self.gpu_nanoseconds - gpu_nanoseconds);
+            self.instant.checked_sub(before).unwrap_or(self.instant)
+        }
+    }
+}
+
+/// The `tracing` subscriber returned by [`ChromeTrace::subscriber`].
+#[derive(Debug)]
+pub struct ChromeTraceSubscriber {
+    shared: Arc<Shared>,
+}
+
+impl ChromeTraceSubscriber {
+    fn push_span_event(&self, id: &span::Id, phase: Phase) {
+        let time = self.shared.start.elapsed();
+        let tid = THREAD_ID.with(|&tid| tid);
+        let mut recording = self.shared.recording.lock();
+        let Some(&(name, _)) = recording.spans.get(&id.into_u64()) else {
+            return;
+        };
+        if !recording.threads.contains_key(&(CPU_PID, tid)) {
+            let thread = std::thread::current();
+            let thread_name = thread
+                .name()
+                .map_or_else(|| format!("Thread {tid}"), str::to_string);
+            recording.threads.insert((CPU_PID, tid), thread_name);
+        }
+        recording.events.push(TraceEvent {
+            name: Cow::Borrowed(name),
+            phase,
+            pid: CPU_PID,
+            tid,
+            time,
+        });
+    }
+}
+
+impl Subscriber for ChromeTraceSubscriber {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let id = self.shared.next_span_id.fetch_add(1, Ordering::Relaxed);
+        self.shared
+            .recording
+            .lock()
+            .spans
+            .insert(id, (span.metadata().name(), 1));
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &span::Id) {
+        self.push_span_event(span, Phase::Begin);
+    }
+
+    fn exit(&self, span: &span::Id) {
+        self.push_span_event(span, Phase::End);
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        if let Some(&mut (_, ref mut count)) =
+            self.shared.recording.lock().spans.get_mut(&id.into_u64())
+        {
+            *count += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: span::Id) -> bool {
+        let mut recording = self.shared.recording.lock();
+        let Some(&mut (_, ref mut count)) = recording.spans.get_mut(&id.into_u64()) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+        recording.spans.remove(&id.into_u64());
+        true
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+/// Escapes a string for a JSON string literal.
+struct Escaped<'a>(&'a str);
+
+impl std::fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write as _;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c if u32::from(c) < 0x20 => write!(f, "\\u{:04x}", u32::from(c))?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_and_gpu_spans() {
+        let trace = ChromeTrace::new();
+        tracing::subscriber::with_default(trace.subscriber(), || {
+            let _outer = tracing::info_span!("Queue::submit").entered();
+            let _inner = tracing::info_span!("vkQueueSubmit").entered();
+        });
+        let clock = GpuClock {
+            instant: Instant::now(),
+            gpu_nanoseconds: 5_000,
+        };
+        trace.record_gpu_span(
+            "Queue \"main\"",
+            "shadow pass",
+            clock.to_instant(1_000),
+            clock.to_instant(4_000),
+        );
+
+        let mut json = Vec::new();
+        trace.write(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        let begin_outer = json
+            .find("\"name\":\"Queue::submit\",\"ph\":\"B\"")
+            .unwrap();
+        let begin_inner = json
+            .find("\"name\":\"vkQueueSubmit\",\"ph\":\"B\"")
+            .unwrap();
+        let end_inner = json
+            .find("\"name\":\"vkQueueSubmit\",\"ph\":\"E\"")
+            .unwrap();
+        let end_outer = json
+            .find("\"name\":\"Queue::submit\",\"ph\":\"E\"")
+            .unwrap();
+        assert!(begin_outer < begin_inner && begin_inner < end_inner && end_inner < end_outer);
+        assert!(json.contains("\"name\":\"shadow pass\",\"ph\":\"X\",\"pid\":2,\"tid\":1"));
+        assert!(json.contains("\"dur\":3"));
+        assert!(json.contains("\"args\":{\"name\":\"Queue \\\"main\\\"\"}"));
+        // All spans were closed.
+        assert!(trace.shared.recording.lock().spans.is_empty());
+    }
+}
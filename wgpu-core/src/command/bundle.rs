@@ -10,11 +10,11 @@ Not all commands are available in bundles; for example, a render bundle may not
 contain a [`RenderCommand::SetViewport`] command.
 
 Most of `wgpu`'s backend graphics APIs have something like bundles. For example,
-Vulkan calls them "secondary command buffers", and Metal calls them "indirect
-command buffers". Although we plan to take advantage of these platform features
-at some point in the future, for now `wgpu`'s implementation of render bundles
-does not use them: at the hal level, `wgpu` render bundles just replay the
-commands.
+Vulkan calls them "secondary command buffers", D3D12 calls them "bundles", and
+Metal calls them "indirect command buffers". Where the backend supports it (see
+[`hal::NativeRenderBundles`]), `wgpu` records render bundles into these, so
+that executing a bundle costs the same however many commands it has. Otherwise,
+at the hal level, `wgpu` render bundles just replay the commands.
 
 ## Render Bundle Isolation
 
@@ -71,6 +71,12 @@ called. It goes through the commands and issues them into the native command
 buffer. Thanks to isolation, it doesn't track any bind group invalidations or
 index format changes.
 
+The first time a bundle is used in a pass its native bundle could be executed
+in, [`RenderBundle::native`] records `RenderBundle::execute`'s commands into a
+native bundle instead, which is executed from then on. A native bundle is only
+recorded for the attachments of the pass it is first used in, and passes with
+other attachments replay the commands.
+
 [Gdcrbe]: crate::global::Global::device_create_render_bundle_encoder
 [Grbef]: crate::global::Global::render_bundle_encoder_finish
 [wrpeb]: crate::command::render::render_commands::wgpu_render_pass_execute_bundles
@@ -106,7 +112,8 @@ use crate::{
 };
 use arrayvec::ArrayVec;
 
-use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range, sync::Arc};
+use once_cell::sync::OnceCell;
+use std::{borrow::Cow, iter, mem, num::NonZeroU32, ops::Range, sync::Arc};
 use thiserror::Error;
 
 use hal::CommandEncoder as _;
@@ -409,7 +416,7 @@ impl RenderBundleEncoder {
                         .trackers
                         .bind_groups
                         .write()
-                        .add_single(&*bind_group_guard, bind_group_id)
+                        .add_single(&bind_group_guard, bind_group_id)
                         .ok_or(RenderCommandError::InvalidBindGroup(bind_group_id))
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(bind_group.device.info.id())
@@ -474,7 +481,7 @@ impl RenderBundleEncoder {
                         .trackers
                         .render_pipelines
                         .write()
-                        .add_single(&*pipeline_guard, pipeline_id)
+                        .add_single(&pipeline_guard, pipeline_id)
                         .ok_or(RenderCommandError::InvalidPipeline(pipeline_id))
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(pipeline.device.info.id())
@@ -517,7 +524,12 @@ impl RenderBundleEncoder {
                         .trackers
                         .buffers
                         .write()
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDEX)
+                        .merge_single(
+                            &buffer_guard,
+                            buffer_id,
+                            super::buffer_binding_range(offset, size),
+                            hal::BufferUses::INDEX,
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device.info.id())
                         .map_pass_err(scope)?;
@@ -556,7 +568,12 @@ impl RenderBundleEncoder {
                         .trackers
                         .buffers
                         .write()
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::VERTEX)
+                        .merge_single(
+                            &buffer_guard,
+                            buffer_id,
+                            super::buffer_binding_range(offset, size),
+                            hal::BufferUses::VERTEX,
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device.info.id())
                         .map_pass_err(scope)?;
@@ -683,7 +700,12 @@ impl RenderBundleEncoder {
                         .trackers
                         .buffers
                         .write()
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                        .merge_single(
+                            &buffer_guard,
+                            buffer_id,
+                            offset..offset + mem::size_of::<wgt::DrawIndirectArgs>() as u64,
+                            hal::BufferUses::INDIRECT,
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device.info.id())
                         .map_pass_err(scope)?;
@@ -722,7 +744,12 @@ impl RenderBundleEncoder {
                         .trackers
                         .buffers
                         .write()
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                        .merge_single(
+                            &buffer_guard,
+                            buffer_id,
+                            offset..offset + mem::size_of::<wgt::DrawIndexedIndirectArgs>() as u64,
+                            hal::BufferUses::INDIRECT,
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device.info.id())
                         .map_pass_err(scope)?;
@@ -785,6 +812,7 @@ impl RenderBundleEncoder {
             discard_hal_labels: device
                 .instance_flags
                 .contains(wgt::InstanceFlags::DISCARD_HAL_LABELS),
+            native: OnceCell::new(),
         })
     }
 
@@ -850,9 +878,34 @@ impl PrettyError for ExecutionError {
 
 pub type RenderBundleDescriptor<'a> = wgt::RenderBundleDescriptor<Label<'a>>;
 
-//Note: here, `RenderBundle` is just wrapping a raw stream of render commands.
-// The plan is to back it by an actual Vulkan secondary buffer, D3D12 Bundle,
-// or Metal indirect command buffer.
+/// The attachments of the render passes a native render bundle can be executed
+/// in.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct NativeRenderBundleKey {
+    pub color_targets:
+        ArrayVec<Option<hal::RenderBundleColorTarget>, { hal::MAX_COLOR_ATTACHMENTS }>,
+    pub depth_stencil_format: Option<wgt::TextureFormat>,
+    pub sample_count: u32,
+    pub multiview: Option<NonZeroU32>,
+    /// The extent of the pass, on backends where bundles don't inherit the
+    /// viewport and scissor rectangle of the pass, and have to set them.
+    pub extent: Option<wgt::Extent3d>,
+}
+
+/// A [`RenderBundle`]'s commands, recorded into a bundle of the backend.
+#[derive(Debug)]
+struct NativeRenderBundle<A: HalApi> {
+    key: NativeRenderBundleKey,
+    /// The encoder the bundle was recorded with, which is used for nothing
+    /// else until the bundle is destroyed.
+    encoder: A::CommandEncoder,
+    raw: A::RenderBundle,
+    /// The buffers and bind groups the bundle uses, whose raw resources it
+    /// refers to.
+    buffers: Vec<Arc<Buffer<A>>>,
+    bind_groups: Vec<Arc<BindGroup<A>>>,
+}
+
 #[derive(Debug)]
 pub struct RenderBundle<A: HalApi> {
     // Normalized command stream. It can be executed verbatim,
@@ -867,6 +920,9 @@ pub struct RenderBundle<A: HalApi> {
     pub(super) context: RenderPassContext,
     pub(crate) info: ResourceInfo<RenderBundle<A>>,
     discard_hal_labels: bool,
+    /// The native bundle, recorded when the bundle is first executed in a
+    /// pass that allows it. `None` if none could be.
+    native: OnceCell<Option<NativeRenderBundle<A>>>,
 }
 
 impl<A: HalApi> Drop for RenderBundle<A> {
@@ -877,6 +933,15 @@ impl<A: HalApi> Drop for RenderBundle<A> {
         if let Some(t) = self.device.trace.lock().as_mut() {
             t.add(trace::Action::DestroyRenderBundle(self.info.id()));
         }
+
+        // Command buffers hold on to the bundles they execute, so none of
+        // those using the native bundle are still in use.
+        if let Some(mut native) = self.native.take().flatten() {
+            unsafe { native.encoder.reset_render_bundles(iter::once(native.raw)) };
+            self.device
+                .command_allocator
+                .release_encoder(native.encoder);
+        }
     }
 }
 
@@ -1088,6 +1153,139 @@ impl<A: HalApi> RenderBundle<A> {
 
         Ok(())
     }
+
+    /// Returns the native bundle to execute in passes with the attachments
+    /// of `key`, recording it if this is the first time it is asked for.
+    ///
+    /// Returns `None` if the backend doesn't support native bundles, if the
+    /// native bundle was recorded for other attachments, or if it couldn't be
+    /// recorded.
+    pub(super) fn native(
+        &self,
+        key: &NativeRenderBundleKey,
+        snatch_guard: &SnatchGuard,
+    ) -> Option<&A::RenderBundle> {
+        let native = self
+            .native
+            .get_or_init(|| unsafe { self.record_native(key, snatch_guard) })
+            .as_ref()?;
+        (native.key == *key).then_some(&native.raw)
+    }
+
+    unsafe fn record_native(
+        &self,
+        key: &NativeRenderBundleKey,
+        snatch_guard: &SnatchGuard,
+    ) -> Option<NativeRenderBundle<A>> {
+        profiling::scope!("RenderBundle::record_native");
+        let capabilities = &self.device.adapter.raw.capabilities;
+        if capabilities.render_bundles == hal::NativeRenderBundles::Unsupported {
+            return None;
+        }
+        let queue = self.device.get_queue()?;
+        let mut encoder = self
+            .device
+            .command_allocator
+            .acquire_encoder(self.device.raw(), queue.raw.as_ref().unwrap())
+            .ok()?;
+
+        let desc = hal::RenderBundleDescriptor {
+            label: self
+                .base
+                .label
+                .as_deref()
+                .filter(|_| !self.discard_hal_labels),
+            color_targets: &key.color_targets,
+            depth_stencil_format: key.depth_stencil_format,
+            sample_count: key.sample_count,
+            multiview: key.multiview,
+        };
+        if unsafe { encoder.begin_render_bundle(&desc) }.is_err() {
+            self.device.command_allocator.release_encoder(encoder);
+            return None;
+        }
+        if let Some(extent) = key.extent {
+            // The state a render pass starts with.
+            unsafe {
+                encoder.set_viewport(
+                    &hal::Rect {
+                        x: 0.0,
+                        y: 0.0,
+                        w: extent.width as f32,
+                        h: extent.height as f32,
+                    },
+                    0.0..1.0,
+                );
+                encoder.set_scissor_rect(&hal::Rect {
+                    x: 0,
+                    y: 0,
+                    w: extent.width,
+                    h: extent.height,
+                });
+                encoder.set_blend_constants(&[0.0; 4]);
+                encoder.set_stencil_reference(0);
+            }
+        }
+        let executed = unsafe { self.execute(&mut encoder, snatch_guard) };
+        let raw = match (executed, unsafe { encoder.end_render_bundle() }) {
+            (Ok(()), Ok(raw)) => raw,
+            (_, ended) => {
+                unsafe { encoder.reset_render_bundles(ended.into_iter()) };
+                self.device.command_allocator.release_encoder(encoder);
+                return None;
+            }
+        };
+
+        let mut buffers = Vec::new();
+        let mut bind_groups = Vec::new();
+        for command in self.base.commands.iter() {
+            match command {
+                ArcRenderCommand::SetBindGroup { bind_group, .. } => {
+                    bind_groups.push(bind_group.clone())
+                }
+                ArcRenderCommand::SetIndexBuffer { buffer, .. }
+                | ArcRenderCommand::SetVertexBuffer { buffer, .. }
+                | ArcRenderCommand::MultiDrawIndirect { buffer, .. } => {
+                    buffers.push(buffer.clone())
+                }
+                _ => {}
+            }
+        }
+        buffers.sort_by_key(Arc::as_ptr);
+        buffers.dedup_by(|a, b| Arc::ptr_eq(a, b));
+        bind_groups.sort_by_key(Arc::as_ptr);
+        bind_groups.dedup_by(|a, b| Arc::ptr_eq(a, b));
+
+        Some(NativeRenderBundle {
+            key: key.clone(),
+            encoder,
+            raw,
+            buffers,
+            bind_groups,
+        })
+    }
+
+    /// Checks that the resources the native bundle refers to weren't
+    /// destroyed since it was recorded, as [`Self::execute`] would have.
+    pub(super) fn check_native_resources(
+        &self,
+        snatch_guard: &SnatchGuard,
+    ) -> Result<(), ExecutionError> {
+        let Some(Some(native)) = self.native.get() else {
+            return Ok(());
+        };
+        for buffer in native.buffers.iter() {
+            if buffer.raw(snatch_guard).is_none() {
+                return Err(ExecutionError::DestroyedBuffer(buffer.info.id()));
+            }
+        }
+        for bind_group in native.bind_groups.iter() {
+            if bind_group.raw(snatch_guard).is_none() {
+                return Err(ExecutionError::InvalidBindGroup(bind_group.info.id()));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<A: HalApi> Resource for RenderBundle<A> {
@@ -109,10 +109,13 @@ impl Global {
                 return Err(DeviceError::WrongDevice.into());
             }
 
+            // The tracker clamps the range to the buffer's size.
+            let range =
+                offset..size.map_or(wgt::BufferAddress::MAX, |size| offset.saturating_add(size));
             cmd_buf_data
                 .trackers
                 .buffers
-                .set_single(dst_buffer, hal::BufferUses::COPY_DST)
+                .set_single(dst_buffer, range, hal::BufferUses::COPY_DST)
                 .ok_or(ClearError::InvalidBuffer(dst))?
         };
         let snatch_guard = dst_buffer.device.snatchable_lock.read();
@@ -163,10 +166,12 @@ impl Global {
         );
 
         // actual hal barrier & operation
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+        let dst_barrier = dst_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
         let cmd_buf_raw = cmd_buf_data.encoder.open()?;
         unsafe {
-            cmd_buf_raw.transition_buffers(dst_barrier.into_iter());
+            cmd_buf_raw.transition_buffers(dst_barrier);
             cmd_buf_raw.clear_buffer(dst_raw, offset..end_offset);
         }
         Ok(())
@@ -503,6 +508,7 @@ fn clear_texture_via_render_passes<A: HalApi>(
                     multiview: None,
                     timestamp_writes: None,
                     occlusion_query_set: None,
+                    render_bundles_only: false,
                 });
                 encoder.end_render_pass();
             }
@@ -394,7 +394,7 @@ impl Global {
         let timestamp_writes = if let Some(tw) = timestamp_writes {
             let query_set: &resource::QuerySet<A> = tracker
                 .query_sets
-                .add_single(&*query_set_guard, tw.query_set)
+                .add_single(&query_set_guard, tw.query_set)
                 .ok_or(ComputePassErrorInner::InvalidQuerySet(tw.query_set))
                 .map_pass_err(pass_scope)?;
 
@@ -645,7 +645,7 @@ impl Global {
                         .flush_states(
                             raw,
                             &mut intermediate_trackers,
-                            &*bind_group_guard,
+                            &bind_group_guard,
                             None,
                             &snatch_guard,
                         )
@@ -683,15 +683,19 @@ impl Global {
                         .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                         .map_pass_err(scope)?;
 
+                    let end_offset = offset + mem::size_of::<wgt::DispatchIndirectArgs>() as u64;
                     state
                         .scope
                         .buffers
-                        .insert_merge_single(buffer.clone(), hal::BufferUses::INDIRECT)
+                        .insert_merge_single(
+                            buffer.clone(),
+                            *offset..end_offset,
+                            hal::BufferUses::INDIRECT,
+                        )
                         .map_pass_err(scope)?;
                     check_buffer_usage(buffer_id, buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
-                    let end_offset = offset + mem::size_of::<wgt::DispatchIndirectArgs>() as u64;
                     if end_offset > buffer.size {
                         return Err(ComputePassErrorInner::IndirectBufferOverrun {
                             offset: *offset,
@@ -721,7 +725,7 @@ impl Global {
                         .flush_states(
                             raw,
                             &mut intermediate_trackers,
-                            &*bind_group_guard,
+                            &bind_group_guard,
                             Some(buffer.as_info().tracker_index()),
                             &snatch_guard,
                         )
@@ -804,7 +808,7 @@ impl Global {
                 ArcComputeCommand::EndPipelineStatisticsQuery => {
                     let scope = PassErrorScope::EndPipelineStatisticsQuery;
 
-                    end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
+                    end_pipeline_statistics_query(raw, &query_set_guard, &mut active_query)
                         .map_pass_err(scope)?;
                 }
             }
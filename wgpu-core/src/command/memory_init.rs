@@ -223,11 +223,16 @@ impl<A: HalApi> BakedCommands<A> {
             //
             // However, we *know* that it is currently in use, so the tracker
             // must already know about it.
-            let transition = device_tracker
-                .buffers
-                .set_single(&buffer, hal::BufferUses::COPY_DST)
-                .unwrap()
-                .1;
+            let mut transitions = Vec::new();
+            for range in ranges.iter() {
+                transitions.extend(
+                    device_tracker
+                        .buffers
+                        .set_single(&buffer, range.clone(), hal::BufferUses::COPY_DST)
+                        .unwrap()
+                        .1,
+                );
+            }
 
             let raw_buf = buffer
                 .raw
@@ -236,9 +241,9 @@ impl<A: HalApi> BakedCommands<A> {
 
             unsafe {
                 self.encoder.transition_buffers(
-                    transition
-                        .map(|pending| pending.into_hal(&buffer, snatch_guard))
-                        .into_iter(),
+                    transitions
+                        .into_iter()
+                        .map(|pending| pending.into_hal(&buffer, snatch_guard)),
                 );
             }
 
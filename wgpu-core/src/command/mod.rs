@@ -99,7 +99,8 @@ pub(crate) enum CommandEncoderStatus {
 pub(crate) struct CommandEncoder<A: HalApi> {
     /// The underlying `wgpu_hal` [`CommandEncoder`].
     ///
-    /// Successfully executed command buffers' encoders are saved in a
+    /// Once its command buffers have executed, or the owning [`CommandBuffer`]
+    /// is dropped without being submitted, the encoder is reset and saved in a
     /// [`CommandAllocator`] for recycling.
     ///
     /// [`CommandEncoder`]: hal::Api::CommandEncoder
@@ -315,10 +316,7 @@ impl<A: HalApi> Drop for CommandBuffer<A> {
         unsafe {
             baked.encoder.reset_all(baked.list.into_iter());
         }
-        unsafe {
-            use hal::Device;
-            self.device.raw().destroy_command_encoder(baked.encoder);
-        }
+        self.device.command_allocator.release_encoder(baked.encoder);
     }
 }
 
@@ -442,7 +440,10 @@ impl<A: HalApi> CommandBuffer<A> {
             "Extracting BakedCommands from CommandBuffer {:?}",
             self.info.label()
         );
-        let data = self.data.lock().take().unwrap();
+        let mut data = self.data.lock().take().unwrap();
+        // Encoders are reused once they are reset, which requires them to be
+        // closed.
+        data.encoder.discard();
         BakedCommands {
             encoder: data.encoder.raw,
             list: data.encoder.list,
@@ -699,6 +700,20 @@ impl Global {
     }
 }
 
+/// The range of a buffer used by a vertex or index buffer binding, for
+/// tracking its state.
+///
+/// A size of `None` binds the rest of the buffer, which is the range up to
+/// `BufferAddress::MAX`, since the trackers clamp ranges to buffer sizes.
+fn buffer_binding_range(
+    offset: wgt::BufferAddress,
+    size: Option<wgt::BufferSize>,
+) -> std::ops::Range<wgt::BufferAddress> {
+    offset..size.map_or(wgt::BufferAddress::MAX, |size| {
+        offset.saturating_add(size.get())
+    })
+}
+
 fn push_constant_clear<PushFn>(offset: u32, size_bytes: u32, mut push_fn: PushFn)
 where
     PushFn: FnMut(u32, &[u32]),
@@ -354,6 +354,8 @@ impl Global {
         query_set_id: id::QuerySetId,
         query_index: u32,
     ) -> Result<(), QueryError> {
+        profiling::scope!("CommandEncoder::write_timestamp");
+
         let hub = A::hub(self);
 
         let cmd_buf = CommandBuffer::get_encoder(hub, command_encoder_id)?;
@@ -381,7 +383,7 @@ impl Global {
         let query_set_guard = hub.query_sets.read();
         let query_set = tracker
             .query_sets
-            .add_single(&*query_set_guard, query_set_id)
+            .add_single(&query_set_guard, query_set_id)
             .ok_or(QueryError::InvalidQuerySet(query_set_id))?;
 
         query_set.validate_and_write_timestamp(raw_encoder, query_set_id, query_index, None)?;
@@ -398,6 +400,8 @@ impl Global {
         destination: id::BufferId,
         destination_offset: BufferAddress,
     ) -> Result<(), QueryError> {
+        profiling::scope!("CommandEncoder::resolve_query_set");
+
         let hub = A::hub(self);
 
         let cmd_buf = CommandBuffer::get_encoder(hub, command_encoder_id)?;
@@ -426,13 +430,24 @@ impl Global {
         let query_set_guard = hub.query_sets.read();
         let query_set = tracker
             .query_sets
-            .add_single(&*query_set_guard, query_set_id)
+            .add_single(&query_set_guard, query_set_id)
             .ok_or(QueryError::InvalidQuerySet(query_set_id))?;
 
         if query_set.device.as_info().id() != cmd_buf.device.as_info().id() {
             return Err(DeviceError::WrongDevice.into());
         }
 
+        let elements_per_query = match query_set.desc.ty {
+            wgt::QueryType::Occlusion => 1,
+            wgt::QueryType::PipelineStatistics(ps) => ps.bits().count_ones(),
+            wgt::QueryType::Timestamp => 1,
+        };
+        let stride = elements_per_query * wgt::QUERY_SIZE;
+        let bytes_used = (stride * query_count) as BufferAddress;
+
+        let buffer_start_offset = destination_offset;
+        let buffer_end_offset = buffer_start_offset + bytes_used;
+
         let (dst_buffer, dst_pending) = {
             let buffer_guard = hub.buffers.read();
             let dst_buffer = buffer_guard
@@ -445,13 +460,19 @@ impl Global {
 
             tracker
                 .buffers
-                .set_single(dst_buffer, hal::BufferUses::COPY_DST)
+                .set_single(
+                    dst_buffer,
+                    buffer_start_offset..buffer_end_offset,
+                    hal::BufferUses::COPY_DST,
+                )
                 .ok_or(QueryError::InvalidBuffer(destination))?
         };
 
         let snatch_guard = dst_buffer.device.snatchable_lock.read();
 
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+        let dst_barrier = dst_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
 
         if !dst_buffer.usage.contains(wgt::BufferUsages::QUERY_RESOLVE) {
             return Err(ResolveError::MissingBufferUsage.into());
@@ -467,17 +488,6 @@ impl Global {
             .into());
         }
 
-        let elements_per_query = match query_set.desc.ty {
-            wgt::QueryType::Occlusion => 1,
-            wgt::QueryType::PipelineStatistics(ps) => ps.bits().count_ones(),
-            wgt::QueryType::Timestamp => 1,
-        };
-        let stride = elements_per_query * wgt::QUERY_SIZE;
-        let bytes_used = (stride * query_count) as BufferAddress;
-
-        let buffer_start_offset = destination_offset;
-        let buffer_end_offset = buffer_start_offset + bytes_used;
-
         if buffer_end_offset > dst_buffer.size {
             return Err(ResolveError::BufferOverrun {
                 start_query,
@@ -502,7 +512,7 @@ impl Global {
             .ok_or(QueryError::InvalidBuffer(destination))?;
 
         unsafe {
-            raw_encoder.transition_buffers(dst_barrier.into_iter());
+            raw_encoder.transition_buffers(dst_barrier);
             raw_encoder.copy_query_results(
                 query_set.raw(),
                 start_query..end_query,
@@ -48,8 +48,8 @@ use std::sync::Arc;
 use std::{borrow::Cow, fmt, iter, marker::PhantomData, mem, num::NonZeroU32, ops::Range, str};
 
 use super::{
-    memory_init::TextureSurfaceDiscard, CommandBufferTextureMemoryActions, CommandEncoder,
-    QueryResetMap,
+    bundle::NativeRenderBundleKey, memory_init::TextureSurfaceDiscard,
+    CommandBufferTextureMemoryActions, CommandEncoder, QueryResetMap,
 };
 
 /// Operation to perform to the output attachment at the start of a renderpass.
@@ -752,6 +752,9 @@ struct RenderPassInfo<'a, 'd, A: HalApi> {
     pending_discard_init_fixups: SurfacesInDiscardState<A>,
     divergent_discarded_depth_stencil_aspect: Option<(wgt::TextureAspect, &'a TextureView<A>)>,
     multiview: Option<NonZeroU32>,
+    /// The attachments of the pass, if native render bundles can be executed
+    /// in it.
+    native_bundle_key: Option<NativeRenderBundleKey>,
 }
 
 impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
@@ -796,12 +799,14 @@ impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
         timestamp_writes: Option<&RenderPassTimestampWrites>,
         occlusion_query_set: Option<id::QuerySetId>,
+        commands: &[RenderCommand],
         encoder: &mut CommandEncoder<A>,
         trackers: &mut Tracker<A>,
         texture_memory_actions: &mut CommandBufferTextureMemoryActions<A>,
         pending_query_resets: &mut QueryResetMap<A>,
         view_guard: &'a Storage<TextureView<A>>,
         query_set_guard: &'a Storage<QuerySet<A>>,
+        bundle_guard: &Storage<command::RenderBundle<A>>,
         snatch_guard: &SnatchGuard<'a>,
     ) -> Result<Self, RenderPassErrorInner> {
         profiling::scope!("RenderPassInfo::start");
@@ -1198,6 +1203,53 @@ impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
             None
         };
 
+        let mut native_bundle_key = NativeRenderBundleKey {
+            color_targets: context
+                .attachments
+                .colors
+                .iter()
+                .zip(colors.iter())
+                .map(|(format, at)| {
+                    Some(hal::RenderBundleColorTarget {
+                        format: (*format)?,
+                        resolve: at.as_ref()?.resolve_target.is_some(),
+                    })
+                })
+                .collect(),
+            depth_stencil_format: context.attachments.depth_stencil,
+            sample_count,
+            multiview,
+            extent: None,
+        };
+        let mut render_bundles_only = false;
+        let native_bundle_key = match device.adapter.raw.capabilities.render_bundles {
+            hal::NativeRenderBundles::Unsupported => None,
+            hal::NativeRenderBundles::Inline => Some(native_bundle_key),
+            hal::NativeRenderBundles::PassContents => {
+                // Native bundles can only be executed in passes that execute
+                // nothing else, and only if they all have one for the pass.
+                native_bundle_key.extent = Some(extent);
+                render_bundles_only = !commands.is_empty()
+                    && commands.iter().all(|command| {
+                        let RenderCommand::ExecuteBundle(bundle_id) = *command else {
+                            return false;
+                        };
+                        let Ok(bundle) = bundle_guard.get(bundle_id) else {
+                            return false;
+                        };
+                        bundle.device.as_info().id() == device.as_info().id()
+                            && context
+                                .check_compatible(
+                                    &bundle.context,
+                                    RenderPassCompatibilityCheckType::RenderBundle,
+                                )
+                                .is_ok()
+                            && bundle.native(&native_bundle_key, snatch_guard).is_some()
+                    });
+                render_bundles_only.then_some(native_bundle_key)
+            }
+        };
+
         let hal_desc = hal::RenderPassDescriptor {
             label: hal_label(label, device.instance_flags),
             extent,
@@ -1207,6 +1259,7 @@ impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
             multiview,
             timestamp_writes,
             occlusion_query_set,
+            render_bundles_only,
         };
         unsafe {
             encoder.raw.begin_render_pass(&hal_desc);
@@ -1223,6 +1276,7 @@ impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
             pending_discard_init_fixups,
             divergent_discarded_depth_stencil_aspect,
             multiview,
+            native_bundle_key,
         })
     }
 
@@ -1289,6 +1343,7 @@ impl<'a, 'd, A: HalApi> RenderPassInfo<'a, 'd, A> {
                 multiview: self.multiview,
                 timestamp_writes: None,
                 occlusion_query_set: None,
+                render_bundles_only: false,
             };
             unsafe {
                 raw.begin_render_pass(&desc);
@@ -1401,12 +1456,14 @@ impl Global {
                 depth_stencil_attachment,
                 timestamp_writes,
                 occlusion_query_set_id,
+                base.commands,
                 encoder,
                 tracker,
                 texture_memory_actions,
                 pending_query_resets,
-                &*view_guard,
-                &*query_set_guard,
+                &view_guard,
+                &query_set_guard,
+                &bundle_guard,
                 &snatch_guard,
             )
             .map_pass_err(pass_scope)?;
@@ -1467,7 +1524,7 @@ impl Global {
 
                         let bind_group = tracker
                             .bind_groups
-                            .add_single(&*bind_group_guard, bind_group_id)
+                            .add_single(&bind_group_guard, bind_group_id)
                             .ok_or(RenderCommandError::InvalidBindGroup(bind_group_id))
                             .map_pass_err(scope)?;
 
@@ -1535,7 +1592,7 @@ impl Global {
 
                         let pipeline: &pipeline::RenderPipeline<A> = tracker
                             .render_pipelines
-                            .add_single(&*render_pipeline_guard, pipeline_id)
+                            .add_single(&render_pipeline_guard, pipeline_id)
                             .ok_or(RenderCommandError::InvalidPipeline(pipeline_id))
                             .map_pass_err(scope)?;
 
@@ -1665,7 +1722,12 @@ impl Global {
                         let buffer = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDEX)
+                            .merge_single(
+                                &buffer_guard,
+                                buffer_id,
+                                super::buffer_binding_range(offset, size),
+                                hal::BufferUses::INDEX,
+                            )
                             .map_pass_err(scope)?;
 
                         if buffer.device.as_info().id() != device.as_info().id() {
@@ -1718,7 +1780,12 @@ impl Global {
                         let buffer = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::VERTEX)
+                            .merge_single(
+                                &buffer_guard,
+                                buffer_id,
+                                super::buffer_binding_range(offset, size),
+                                hal::BufferUses::VERTEX,
+                            )
                             .map_pass_err(scope)?;
 
                         if buffer.device.as_info().id() != device.as_info().id() {
@@ -2026,10 +2093,18 @@ impl Global {
                             .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                             .map_pass_err(scope)?;
 
+                        let actual_count = count.map_or(1, |c| c.get());
+                        let end_offset = offset + stride as u64 * actual_count as u64;
+
                         let indirect_buffer = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                            .merge_single(
+                                &buffer_guard,
+                                buffer_id,
+                                offset..end_offset,
+                                hal::BufferUses::INDIRECT,
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(
                             buffer_id,
@@ -2043,9 +2118,6 @@ impl Global {
                             .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))
                             .map_pass_err(scope)?;
 
-                        let actual_count = count.map_or(1, |c| c.get());
-
-                        let end_offset = offset + stride as u64 * actual_count as u64;
                         if end_offset > indirect_buffer.size {
                             return Err(RenderPassErrorInner::IndirectBufferOverrun {
                                 count,
@@ -2105,7 +2177,12 @@ impl Global {
                         let indirect_buffer = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                            .merge_single(
+                                &buffer_guard,
+                                buffer_id,
+                                offset..offset + stride * max_count as u64,
+                                hal::BufferUses::INDIRECT,
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(
                             buffer_id,
@@ -2123,8 +2200,9 @@ impl Global {
                             .usage_scope
                             .buffers
                             .merge_single(
-                                &*buffer_guard,
+                                &buffer_guard,
                                 count_buffer_id,
+                                count_buffer_offset..count_buffer_offset + 4,
                                 hal::BufferUses::INDIRECT,
                             )
                             .map_pass_err(scope)?;
@@ -2249,7 +2327,7 @@ impl Global {
 
                         let query_set = tracker
                             .query_sets
-                            .add_single(&*query_set_guard, query_set_id)
+                            .add_single(&query_set_guard, query_set_id)
                             .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
                             .map_pass_err(scope)?;
 
@@ -2272,7 +2350,7 @@ impl Global {
 
                         let query_set = tracker
                             .query_sets
-                            .add_single(&*query_set_guard, query_set_id)
+                            .add_single(&query_set_guard, query_set_id)
                             .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
                             .map_pass_err(scope)?;
 
@@ -2290,7 +2368,7 @@ impl Global {
                         api_log!("RenderPass::end_occlusion_query");
                         let scope = PassErrorScope::EndOcclusionQuery;
 
-                        end_occlusion_query(raw, &*query_set_guard, &mut active_query)
+                        end_occlusion_query(raw, &query_set_guard, &mut active_query)
                             .map_pass_err(scope)?;
                     }
                     RenderCommand::BeginPipelineStatisticsQuery {
@@ -2302,7 +2380,7 @@ impl Global {
 
                         let query_set = tracker
                             .query_sets
-                            .add_single(&*query_set_guard, query_set_id)
+                            .add_single(&query_set_guard, query_set_id)
                             .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
                             .map_pass_err(scope)?;
 
@@ -2320,7 +2398,7 @@ impl Global {
                         api_log!("RenderPass::end_pipeline_statistics_query");
                         let scope = PassErrorScope::EndPipelineStatisticsQuery;
 
-                        end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
+                        end_pipeline_statistics_query(raw, &query_set_guard, &mut active_query)
                             .map_pass_err(scope)?;
                     }
                     RenderCommand::ExecuteBundle(bundle_id) => {
@@ -2328,7 +2406,7 @@ impl Global {
                         let scope = PassErrorScope::ExecuteBundle;
                         let bundle: &command::RenderBundle<A> = tracker
                             .bundles
-                            .add_single(&*bundle_guard, bundle_id)
+                            .add_single(&bundle_guard, bundle_id)
                             .ok_or(RenderCommandError::InvalidRenderBundle(bundle_id))
                             .map_pass_err(scope)?;
 
@@ -2375,19 +2453,32 @@ impl Global {
                                 .extend(texture_memory_actions.register_init_action(action));
                         }
 
-                        unsafe { bundle.execute(raw, &snatch_guard) }
-                            .map_err(|e| match e {
-                                ExecutionError::DestroyedBuffer(id) => {
-                                    RenderCommandError::DestroyedBuffer(id)
-                                }
-                                ExecutionError::InvalidBindGroup(id) => {
-                                    RenderCommandError::InvalidBindGroup(id)
-                                }
-                                ExecutionError::Unimplemented(what) => {
-                                    RenderCommandError::Unimplemented(what)
-                                }
-                            })
-                            .map_pass_err(scope)?;
+                        let native = info
+                            .native_bundle_key
+                            .as_ref()
+                            .and_then(|key| bundle.native(key, &snatch_guard));
+                        match native {
+                            Some(native) => {
+                                bundle
+                                    .check_native_resources(&snatch_guard)
+                                    .map(|()| unsafe {
+                                        raw.execute_render_bundles(iter::once(native))
+                                    })
+                            }
+                            None => unsafe { bundle.execute(raw, &snatch_guard) },
+                        }
+                        .map_err(|e| match e {
+                            ExecutionError::DestroyedBuffer(id) => {
+                                RenderCommandError::DestroyedBuffer(id)
+                            }
+                            ExecutionError::InvalidBindGroup(id) => {
+                                RenderCommandError::InvalidBindGroup(id)
+                            }
+                            ExecutionError::Unimplemented(what) => {
+                                RenderCommandError::Unimplemented(what)
+                            }
+                        })
+                        .map_pass_err(scope)?;
 
                         unsafe {
                             info.usage_scope
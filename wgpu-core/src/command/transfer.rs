@@ -609,7 +609,11 @@ impl Global {
             cmd_buf_data
                 .trackers
                 .buffers
-                .set_single(src_buffer, hal::BufferUses::COPY_SRC)
+                .set_single(
+                    src_buffer,
+                    source_offset..source_offset.saturating_add(size),
+                    hal::BufferUses::COPY_SRC,
+                )
                 .ok_or(TransferError::InvalidBuffer(source))?
         };
         let src_raw = src_buffer
@@ -619,8 +623,9 @@ impl Global {
         if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        // expecting only a single barrier
-        let src_barrier = src_pending.map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
+        let src_barrier = src_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
 
         let (dst_buffer, dst_pending) = {
             let buffer_guard = hub.buffers.read();
@@ -635,7 +640,11 @@ impl Global {
             cmd_buf_data
                 .trackers
                 .buffers
-                .set_single(dst_buffer, hal::BufferUses::COPY_DST)
+                .set_single(
+                    dst_buffer,
+                    destination_offset..destination_offset.saturating_add(size),
+                    hal::BufferUses::COPY_DST,
+                )
                 .ok_or(TransferError::InvalidBuffer(destination))?
         };
         let dst_raw = dst_buffer
@@ -645,7 +654,9 @@ impl Global {
         if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
             return Err(TransferError::MissingCopyDstUsageFlag(Some(destination), None).into());
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+        let dst_barrier = dst_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
 
         if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
             return Err(TransferError::UnalignedCopySize(size).into());
@@ -726,7 +737,7 @@ impl Global {
         };
         let cmd_buf_raw = cmd_buf_data.encoder.open()?;
         unsafe {
-            cmd_buf_raw.transition_buffers(src_barrier.into_iter().chain(dst_barrier));
+            cmd_buf_raw.transition_buffers(src_barrier.chain(dst_barrier));
             cmd_buf_raw.copy_buffer_to_buffer(src_raw, dst_raw, iter::once(region));
         }
         Ok(())
@@ -820,9 +831,15 @@ impl Global {
                 return Err(DeviceError::WrongDevice.into());
             }
 
+            // The copy may read up to the end of the buffer. The tracker
+            // clamps the range to the buffer's size.
             tracker
                 .buffers
-                .set_single(src_buffer, hal::BufferUses::COPY_SRC)
+                .set_single(
+                    src_buffer,
+                    source.layout.offset..wgt::BufferAddress::MAX,
+                    hal::BufferUses::COPY_SRC,
+                )
                 .ok_or(TransferError::InvalidBuffer(source.buffer))?
         };
         let src_raw = src_buffer
@@ -832,7 +849,9 @@ impl Global {
         if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        let src_barrier = src_pending.map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
+        let src_barrier = src_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
 
         let dst_pending = tracker
             .textures
@@ -852,7 +871,11 @@ impl Global {
             return Err(TransferError::CopyAspectNotOne.into());
         }
 
-        if !conv::is_valid_copy_dst_texture_format(dst_texture.desc.format, destination.aspect) {
+        if !conv::is_valid_copy_dst_texture_format(
+            dst_texture.desc.format,
+            destination.aspect,
+            device.features,
+        ) {
             return Err(TransferError::CopyToForbiddenTextureFormat {
                 format: dst_texture.desc.format,
                 aspect: destination.aspect,
@@ -897,7 +920,7 @@ impl Global {
         let cmd_buf_raw = encoder.open()?;
         unsafe {
             cmd_buf_raw.transition_textures(dst_barrier.into_iter());
-            cmd_buf_raw.transition_buffers(src_barrier.into_iter());
+            cmd_buf_raw.transition_buffers(src_barrier);
             cmd_buf_raw.copy_buffer_to_texture(src_raw, dst_raw, regions);
         }
         Ok(())
@@ -1011,9 +1034,15 @@ impl Global {
                 return Err(DeviceError::WrongDevice.into());
             }
 
+            // The copy may write up to the end of the buffer. The tracker
+            // clamps the range to the buffer's size.
             tracker
                 .buffers
-                .set_single(dst_buffer, hal::BufferUses::COPY_DST)
+                .set_single(
+                    dst_buffer,
+                    destination.layout.offset..wgt::BufferAddress::MAX,
+                    hal::BufferUses::COPY_DST,
+                )
                 .ok_or(TransferError::InvalidBuffer(destination.buffer))?
         };
         let dst_raw = dst_buffer
@@ -1025,7 +1054,9 @@ impl Global {
                 TransferError::MissingCopyDstUsageFlag(Some(destination.buffer), None).into(),
             );
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+        let dst_barrier = dst_pending
+            .into_iter()
+            .map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
 
         if !src_base.aspect.is_one() {
             return Err(TransferError::CopyAspectNotOne.into());
@@ -1074,7 +1105,7 @@ impl Global {
         });
         let cmd_buf_raw = encoder.open()?;
         unsafe {
-            cmd_buf_raw.transition_buffers(dst_barrier.into_iter());
+            cmd_buf_raw.transition_buffers(dst_barrier);
             cmd_buf_raw.transition_textures(src_barrier.into_iter());
             cmd_buf_raw.copy_texture_to_buffer(
                 src_raw,
@@ -25,12 +25,15 @@ pub fn is_valid_copy_src_texture_format(
 pub fn is_valid_copy_dst_texture_format(
     format: wgt::TextureFormat,
     aspect: wgt::TextureAspect,
+    features: wgt::Features,
 ) -> bool {
     use wgt::TextureAspect as Ta;
     use wgt::TextureFormat as Tf;
     match (format, aspect) {
-        (Tf::Depth24Plus | Tf::Depth32Float, _)
-        | (Tf::Depth24PlusStencil8 | Tf::Depth32FloatStencil8, Ta::DepthOnly) => false,
+        (Tf::Depth24Plus, _) | (Tf::Depth24PlusStencil8, Ta::DepthOnly) => false,
+        (Tf::Depth32Float, _) | (Tf::Depth32FloatStencil8, Ta::DepthOnly) => {
+            features.contains(wgt::Features::DEPTH32FLOAT_COPY_DST)
+        }
         _ => true,
     }
 }
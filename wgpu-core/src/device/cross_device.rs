@@ -0,0 +1,251 @@
+//! Sharing memory and synchronizing between devices.
+//!
+//! Devices that can import each other's memory and fences, as reported by
+//! [`Global::device_shares_memory_with`], can transfer data without going
+//! through the host: [`Global::device_create_shared_buffers`] creates a buffer
+//! on each device in the same memory, and one device's queue waits on the
+//! other's work with [`Global::queue_wait_for_submission`] before reading what
+//! it wrote.
+//!
+//! On Vulkan, this requires devices opened on the same physical device, and on
+//! DX12, devices on the same adapter.
+
+use std::sync::{Arc, Weak};
+
+use hal::Device as _;
+use thiserror::Error;
+
+#[cfg(feature = "trace")]
+use crate::device::trace;
+use crate::{
+    api_log, conv,
+    device::{queue::WrappedSubmissionIndex, DeviceError},
+    global::Global,
+    hal_api::HalApi,
+    id::{self, DeviceId, QueueId},
+    resource::{self, Resource as _},
+    LabelHelpers as _,
+};
+
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum CrossDeviceError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("Devices {0:?} and {1:?} can't share memory")]
+    IncompatibleDevices(DeviceId, DeviceId),
+    #[error("Shared buffers can't be mapped, and can't have usage {0:?}")]
+    InvalidUsage(wgt::BufferUsages),
+    #[error("Buffer size {requested} is greater than the maximum buffer size ({maximum})")]
+    MaxBufferSize { requested: u64, maximum: u64 },
+}
+
+impl Global {
+    /// Returns whether `device_id` and `other_device_id` can share buffers
+    /// with [`Global::device_create_shared_buffers`] and wait on each other
+    /// with [`Global::queue_wait_for_submission`].
+    pub fn device_shares_memory_with<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        other_device_id: DeviceId,
+    ) -> Result<bool, DeviceError> {
+        let hub = A::hub(self);
+        let device = hub
+            .devices
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+        let other = hub
+            .devices
+            .get(other_device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+        Ok(A::devices_share_memory(device.raw(), other.raw()))
+    }
+
+    /// Creates a buffer on `device_id` and one on `other_device_id`, both in
+    /// the same memory.
+    ///
+    /// What one device writes to its buffer is visible to the other device
+    /// once its queue waited on the writing submission with
+    /// [`Global::queue_wait_for_submission`]. The buffers are considered
+    /// initialized, and can't be mapped.
+    pub fn device_create_shared_buffers<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        other_device_id: DeviceId,
+        desc: &resource::BufferDescriptor,
+        id_in: Option<id::BufferId>,
+        other_id_in: Option<id::BufferId>,
+    ) -> (id::BufferId, id::BufferId, Option<CrossDeviceError>) {
+        profiling::scope!("Device::create_shared_buffers");
+
+        let hub = A::hub(self);
+        let fid = hub.buffers.prepare(id_in);
+        let other_fid = hub.buffers.prepare(other_id_in);
+
+        let error = loop {
+            let (device, other) =
+                match (hub.devices.get(device_id), hub.devices.get(other_device_id)) {
+                    (Ok(device), Ok(other)) => (device, other),
+                    _ => break DeviceError::Invalid.into(),
+                };
+            if !device.is_valid() || !other.is_valid() {
+                break DeviceError::Lost.into();
+            }
+            if !A::devices_share_memory(device.raw(), other.raw()) {
+                break CrossDeviceError::IncompatibleDevices(device_id, other_device_id);
+            }
+
+            let maximum = device
+                .limits
+                .max_buffer_size
+                .min(other.limits.max_buffer_size);
+            if desc.size > maximum {
+                break CrossDeviceError::MaxBufferSize {
+                    requested: desc.size,
+                    maximum,
+                };
+            }
+            if desc.usage.is_empty()
+                || desc.usage.contains_invalid_bits()
+                || desc
+                    .usage
+                    .intersects(wgt::BufferUsages::MAP_READ | wgt::BufferUsages::MAP_WRITE)
+                || desc.mapped_at_creation
+            {
+                break CrossDeviceError::InvalidUsage(desc.usage);
+            }
+
+            // NB: Writes made by the other device won't be recorded in the
+            // replay
+            #[cfg(feature = "trace")]
+            if let Some(trace) = device.trace.lock().as_mut() {
+                trace.add(trace::Action::CreateBuffer(fid.id(), desc.clone()));
+            }
+            #[cfg(feature = "trace")]
+            if let Some(trace) = other.trace.lock().as_mut() {
+                trace.add(trace::Action::CreateBuffer(other_fid.id(), desc.clone()));
+            }
+
+            let hal_desc = hal::BufferDescriptor {
+                label: desc.label.to_hal(device.instance_flags),
+                size: wgt::math::align_to(
+                    desc.size.max(wgt::COPY_BUFFER_ALIGNMENT),
+                    wgt::COPY_BUFFER_ALIGNMENT,
+                ),
+                usage: conv::map_buffer_usage(desc.usage),
+                memory_flags: hal::MemoryFlags::empty(),
+            };
+            let raw = match unsafe { A::create_shared_buffer(device.raw(), &hal_desc) } {
+                Ok(raw) => raw,
+                Err(error) => break DeviceError::from(error).into(),
+            };
+            let other_raw = unsafe {
+                A::export_buffer(device.raw(), &raw)
+                    .and_then(|handle| A::import_buffer(other.raw(), handle, &hal_desc))
+            };
+            let other_raw = match other_raw {
+                Ok(other_raw) => other_raw,
+                Err(error) => {
+                    unsafe { device.raw().destroy_buffer(raw) };
+                    break DeviceError::from(error).into();
+                }
+            };
+
+            let (id, buffer) = fid.assign(Arc::new(device.create_buffer_from_hal(raw, desc)));
+            let (other_id, other_buffer) =
+                other_fid.assign(Arc::new(other.create_buffer_from_hal(other_raw, desc)));
+            api_log!("Device::create_shared_buffers -> {id:?}, {other_id:?}");
+
+            device
+                .trackers
+                .lock()
+                .buffers
+                .insert_single(buffer, hal::BufferUses::empty());
+            other
+                .trackers
+                .lock()
+                .buffers
+                .insert_single(other_buffer, hal::BufferUses::empty());
+
+            return (id, other_id, None);
+        };
+
+        log::error!("Device::create_shared_buffers error: {error}");
+
+        let label = desc.label.borrow_or_default();
+        (
+            fid.assign_error(label),
+            other_fid.assign_error(label),
+            Some(error),
+        )
+    }
+
+    /// Makes the next submission to `queue_id` wait until `submission`, made
+    /// on the queue of another device, is done.
+    ///
+    /// The wait happens on the GPU. Waiting on a submission of the same queue
+    /// does nothing, since submissions are executed in order.
+    pub fn queue_wait_for_submission<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        submission: WrappedSubmissionIndex,
+    ) -> Result<(), CrossDeviceError> {
+        profiling::scope!("Queue::wait_for_submission");
+        api_log!("Queue::wait_for_submission {queue_id:?} {submission:?}");
+
+        let hub = A::hub(self);
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+        let other_queue = hub
+            .queues
+            .get(submission.queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+        let device = queue.device.as_ref().unwrap();
+        let other = other_queue.device.as_ref().unwrap();
+
+        if Arc::ptr_eq(device, other) {
+            return Ok(());
+        }
+        if !A::devices_share_memory(device.raw(), other.raw()) {
+            return Err(CrossDeviceError::IncompatibleDevices(
+                device.as_info().id(),
+                other.as_info().id(),
+            ));
+        }
+
+        // The submission's index is only signaled once it's sent to the
+        // backend.
+        other_queue.flush_batch(&other.snatchable_lock.read())?;
+
+        let mut imported_fences = device.imported_fences.lock();
+        let index = match imported_fences
+            .iter()
+            .position(|(fence_device, _)| Weak::as_ptr(fence_device) == Arc::as_ptr(other))
+        {
+            Some(index) => index,
+            None => {
+                let fence = {
+                    let other_fence = other.fence.read();
+                    unsafe {
+                        A::export_fence(other.raw(), other_fence.as_ref().unwrap())
+                            .and_then(|handle| A::import_fence(device.raw(), handle))
+                    }
+                    .map_err(DeviceError::from)?
+                };
+                imported_fences.push((Arc::downgrade(other), fence));
+                imported_fences.len() - 1
+            }
+        };
+        unsafe {
+            A::queue_wait_for_fence(
+                queue.raw.as_ref().unwrap(),
+                &imported_fences[index].1,
+                submission.index,
+            )
+        }
+        .map_err(DeviceError::from)?;
+        Ok(())
+    }
+}
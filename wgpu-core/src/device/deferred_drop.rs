@@ -0,0 +1,166 @@
+//! Dropping the resources of completed submissions on a background thread.
+//!
+//! Once a submission is done, the [`LifetimeTracker`] lets go of the
+//! resources it was keeping alive for it, and the last of them are destroyed.
+//! Destroying resources and freeing their memory can take a while, so that
+//! doing it in [`Device::maintain`] makes frames hitch when a large scene is
+//! unloaded. Instead, each device has a thread the resources are sent to, and
+//! dropped by.
+//!
+//! The GPU is already done with the resources when they are sent, so they
+//! can be destroyed at any point after that. On targets without threads,
+//! they are dropped right away.
+//!
+//! The thread runs at the default priority: the standard library has no way
+//! to lower the priority of a thread, and doing it through each platform's
+//! API isn't worth the dependencies. The thread sleeps until resources are
+//! sent to it, so it only competes with the threads submitting work while it
+//! destroys something.
+//!
+//! [`LifetimeTracker`]: super::life::LifetimeTracker
+//! [`Device::maintain`]: super::resource::Device::maintain
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{sync::mpsc, thread};
+
+/// A thread dropping the values sent to it, which are the
+/// [`ResourceMaps`](super::life::ResourceMaps) of completed submissions for
+/// devices.
+pub(crate) struct DropThread<T: Send + 'static> {
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: Option<mpsc::Sender<T>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    thread: Option<thread::JoinHandle<()>>,
+    #[cfg(target_arch = "wasm32")]
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> DropThread<T> {
+    pub(crate) fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (sender, receiver) = mpsc::channel::<T>();
+            let thread = thread::Builder::new()
+                .name("wgpu-core resource destruction".to_string())
+                .spawn(move || {
+                    for value in receiver {
+                        profiling::scope!("drop resources");
+                        drop(value);
+                    }
+                });
+            match thread {
+                Ok(thread) => Self {
+                    sender: Some(sender),
+                    thread: Some(thread),
+                },
+                Err(err) => {
+                    log::warn!("Failed to spawn the resource destruction thread: {err}");
+                    Self {
+                        sender: None,
+                        thread: None,
+                    }
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        Self {
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Drops `value`, which holds resources the GPU no longer uses, later.
+    pub(crate) fn drop_later(&self, value: T) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ref sender) = self.sender {
+            // The thread only stops when the sender is dropped, so this
+            // can't fail.
+            let _ = sender.send(value);
+            return;
+        }
+        drop(value);
+    }
+
+    /// Waits for all the values sent to be dropped.
+    ///
+    /// When called from the thread itself, which happens if it drops the last
+    /// reference to the device, the thread is left to finish on its own.
+    pub(crate) fn finish(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.sender = None;
+            if let Some(thread) = self.thread.take() {
+                if thread.thread().id() != thread::current().id() {
+                    let _ = thread.join();
+                }
+            }
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for DropThread<T> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use std::{
+        sync::{mpsc, Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use super::DropThread;
+
+    /// Records the thread it's dropped on.
+    struct DropRecorder {
+        dropped_on: Arc<Mutex<Option<thread::ThreadId>>>,
+        /// How long dropping takes.
+        delay: Duration,
+    }
+
+    impl Drop for DropRecorder {
+        fn drop(&mut self) {
+            thread::sleep(self.delay);
+            *self.dropped_on.lock().unwrap() = Some(thread::current().id());
+        }
+    }
+
+    #[test]
+    fn drops_off_thread() {
+        let drop_thread = DropThread::<Box<dyn Send>>::new();
+        let dropped_on = Arc::new(Mutex::new(None));
+        let (sender, receiver) = mpsc::channel::<()>();
+
+        drop_thread.drop_later(Box::new(DropRecorder {
+            dropped_on: Arc::clone(&dropped_on),
+            delay: Duration::ZERO,
+        }));
+        // Values are dropped in order, so the recorder is dropped once the
+        // sender is.
+        drop_thread.drop_later(Box::new(sender));
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(10)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
+
+        let dropped_on = dropped_on.lock().unwrap().unwrap();
+        assert_ne!(dropped_on, thread::current().id());
+    }
+
+    #[test]
+    fn drop_joins_thread() {
+        // Devices drop their thread when they are dropped.
+        let drop_thread = DropThread::new();
+        let dropped_on = Arc::new(Mutex::new(None));
+
+        drop_thread.drop_later(DropRecorder {
+            dropped_on: Arc::clone(&dropped_on),
+            delay: Duration::from_millis(100),
+        });
+        drop(drop_thread);
+
+        assert!(dropped_on.lock().unwrap().is_some());
+    }
+}
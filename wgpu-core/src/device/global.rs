@@ -186,7 +186,7 @@ impl Global {
 
             let buffer_use = if !desc.mapped_at_creation {
                 hal::BufferUses::empty()
-            } else if desc.usage.contains(wgt::BufferUsages::MAP_WRITE) {
+            } else if desc.usage.contains(wgt::BufferUsages::MAP_WRITE) || buffer.host_writable {
                 // buffer is mappable, so we are just doing that at start
                 let map_size = buffer.size;
                 let ptr = if map_size == 0 {
@@ -549,11 +549,75 @@ impl Global {
         id_in: Option<id::TextureId>,
     ) -> (id::TextureId, Option<resource::CreateTextureError>) {
         profiling::scope!("Device::create_texture");
+        self.create_texture_impl::<A>(device_id, desc, id_in, None)
+    }
+
+    /// Creates a texture which can be shared with other processes, with
+    /// [`Global::texture_export_shared`].
+    ///
+    /// This process owns the texture until it releases it with
+    /// [`Global::queue_release_shared_texture`].
+    pub fn device_create_shared_texture<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &resource::TextureDescriptor,
+        id_in: Option<id::TextureId>,
+    ) -> (id::TextureId, Option<resource::CreateTextureError>) {
+        profiling::scope!("Device::create_shared_texture");
+        self.create_texture_impl::<A>(
+            device_id,
+            desc,
+            id_in,
+            Some(resource::SharedTextureSource::Created),
+        )
+    }
+
+    /// Imports a texture another process exported with
+    /// [`Global::texture_export_shared`].
+    ///
+    /// The texture is owned by the other process, and can only be used once
+    /// it's acquired with [`Global::queue_acquire_shared_texture`].
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a texture created with `desc`, by a
+    ///   device of the same adapter and driver as `device_id`.
+    pub unsafe fn device_import_shared_texture<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        handle: hal::ExternalHandle,
+        desc: &resource::TextureDescriptor,
+        id_in: Option<id::TextureId>,
+    ) -> (id::TextureId, Option<resource::CreateTextureError>) {
+        profiling::scope!("Device::import_shared_texture");
+        self.create_texture_impl::<A>(
+            device_id,
+            desc,
+            id_in,
+            Some(resource::SharedTextureSource::Imported(handle)),
+        )
+    }
 
+    fn create_texture_impl<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &resource::TextureDescriptor,
+        id_in: Option<id::TextureId>,
+        mut shared: Option<resource::SharedTextureSource>,
+    ) -> (id::TextureId, Option<resource::CreateTextureError>) {
         let hub = A::hub(self);
 
         let fid = hub.textures.prepare(id_in);
 
+        // Imported textures are in the state the exporting process released
+        // them in.
+        let initial_use = match shared {
+            Some(resource::SharedTextureSource::Imported(_)) => {
+                resource::SHARED_TEXTURE_RELEASED_USE
+            }
+            _ => hal::TextureUses::UNINITIALIZED,
+        };
+
         let error = loop {
             let device = match hub.devices.get(device_id) {
                 Ok(device) => device,
@@ -562,12 +626,14 @@ impl Global {
             if !device.is_valid() {
                 break DeviceError::Lost.into();
             }
+            // NB: Shared textures are replayed as regular textures, without
+            // the changes of other processes
             #[cfg(feature = "trace")]
             if let Some(ref mut trace) = *device.trace.lock() {
                 trace.add(trace::Action::CreateTexture(fid.id(), desc.clone()));
             }
 
-            let texture = match device.create_texture(&device.adapter, desc) {
+            let texture = match device.create_texture(&device.adapter, desc, shared.take()) {
                 Ok(texture) => texture,
                 Err(error) => break error,
             };
@@ -579,7 +645,7 @@ impl Global {
                 .trackers
                 .lock()
                 .textures
-                .insert_single(resource, hal::TextureUses::UNINITIALIZED);
+                .insert_single(resource, initial_use);
 
             return (id, None);
         };
@@ -723,6 +789,36 @@ impl Global {
         A::hub(self).textures.label_for_resource(id)
     }
 
+    /// Exports a texture created by [`Global::device_create_shared_texture`],
+    /// for another process to import with
+    /// [`Global::device_import_shared_texture`].
+    ///
+    /// Each call returns a new handle.
+    pub fn texture_export_shared<A: HalApi>(
+        &self,
+        texture_id: id::TextureId,
+    ) -> Result<hal::ExternalHandle, resource::SharedTextureError> {
+        profiling::scope!("Texture::export_shared");
+        api_log!("Texture::export_shared {texture_id:?}");
+
+        let hub = A::hub(self);
+
+        let texture = hub
+            .textures
+            .get(texture_id)
+            .map_err(|_| resource::SharedTextureError::InvalidTexture(texture_id))?;
+        if texture.shared_ownership.is_none() {
+            return Err(resource::SharedTextureError::NotShared(texture_id));
+        }
+
+        let snatch_guard = texture.device.snatchable_lock.read();
+        let raw = texture
+            .raw(&snatch_guard)
+            .ok_or(resource::SharedTextureError::DestroyedTexture(texture_id))?;
+        unsafe { A::export_texture(texture.device.raw(), raw) }
+            .map_err(|error| DeviceError::from(error).into())
+    }
+
     pub fn texture_destroy<A: HalApi>(
         &self,
         texture_id: id::TextureId,
@@ -860,6 +956,9 @@ impl Global {
                 .texture_views
                 .insert(view.info.tracker_index(), view.clone());
 
+            // Uses through bind groups that are still alive aren't recorded
+            // on the view, so this only waits for its direct uses. The bind
+            // groups keep the view alive until their own uses are done.
             if wait {
                 match view.device.wait_for_submit(last_submit_index) {
                     Ok(()) => (),
@@ -1217,10 +1316,26 @@ impl Global {
                     pipeline::ShaderModuleSource::Wgsl(ref code) => {
                         trace.make_binary("wgsl", code.as_bytes())
                     }
+                    // Record the expanded source, so that replaying the trace
+                    // doesn't need the include resolver.
+                    #[cfg(feature = "wgsl")]
+                    pipeline::ShaderModuleSource::WgslWithIncludes {
+                        ref source,
+                        resolve,
+                    } => {
+                        let expanded =
+                            naga::front::wgsl::ExpandedSource::new("wgsl", source, resolve)
+                                .map_or_else(|_| source.to_string(), |e| e.source().to_string());
+                        trace.make_binary("wgsl", expanded.as_bytes())
+                    }
                     #[cfg(feature = "glsl")]
                     pipeline::ShaderModuleSource::Glsl(ref code, _) => {
                         trace.make_binary("glsl", code.as_bytes())
                     }
+                    #[cfg(feature = "hlsl")]
+                    pipeline::ShaderModuleSource::Hlsl(ref code, _) => {
+                        trace.make_binary("hlsl", code.as_bytes())
+                    }
                     #[cfg(feature = "spirv")]
                     pipeline::ShaderModuleSource::SpirV(ref code, _) => {
                         trace.make_binary("spirv", bytemuck::cast_slice::<u32, u8>(code))
@@ -1231,6 +1346,10 @@ impl Global {
                                 .unwrap();
                         trace.make_binary("ron", string.as_bytes())
                     }
+                    #[cfg(feature = "precompiled")]
+                    pipeline::ShaderModuleSource::Precompiled(ref bytes) => {
+                        trace.make_binary("naga", bytes)
+                    }
                     pipeline::ShaderModuleSource::Dummy(_) => {
                         panic!("found `ShaderModuleSource::Dummy`")
                     }
@@ -1315,10 +1434,143 @@ impl Global {
         (id, Some(error))
     }
 
+    // Unsafe-ness of internal calls has little to do with unsafe-ness of this.
+    #[allow(unused_unsafe)]
+    /// # Safety
+    ///
+    /// This function passes DXIL binary to the backend as-is and can potentially result in a
+    /// driver crash.
+    pub unsafe fn device_create_shader_module_dxil<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: Cow<[u8]>,
+        id_in: Option<id::ShaderModuleId>,
+    ) -> (
+        id::ShaderModuleId,
+        Option<pipeline::CreateShaderModuleError>,
+    ) {
+        profiling::scope!("Device::create_shader_module");
+
+        let hub = A::hub(self);
+        let fid = hub.shader_modules.prepare(id_in);
+
+        let error = loop {
+            let device = match hub.devices.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
+
+            #[cfg(feature = "trace")]
+            if let Some(ref mut trace) = *device.trace.lock() {
+                let data = trace.make_binary("dxil", &source);
+                trace.add(trace::Action::CreateShaderModule {
+                    id: fid.id(),
+                    desc: desc.clone(),
+                    data,
+                });
+            };
+
+            let shader = match unsafe { device.create_shader_module_dxil(desc, &source) } {
+                Ok(shader) => shader,
+                Err(e) => break e,
+            };
+            let (id, _) = fid.assign(Arc::new(shader));
+            api_log!("Device::create_shader_module_dxil -> {id:?}");
+            return (id, None);
+        };
+
+        log::error!("Device::create_shader_module_dxil error: {error}");
+
+        let id = fid.assign_error(desc.label.borrow_or_default());
+        (id, Some(error))
+    }
+
+    // Unsafe-ness of internal calls has little to do with unsafe-ness of this.
+    #[allow(unused_unsafe)]
+    /// # Safety
+    ///
+    /// This function passes Metal code to the backend as-is and can potentially result in a
+    /// driver crash.
+    pub unsafe fn device_create_shader_module_msl<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: pipeline::MslSource,
+        workgroup_size: [u32; 3],
+        id_in: Option<id::ShaderModuleId>,
+    ) -> (
+        id::ShaderModuleId,
+        Option<pipeline::CreateShaderModuleError>,
+    ) {
+        profiling::scope!("Device::create_shader_module");
+
+        let hub = A::hub(self);
+        let fid = hub.shader_modules.prepare(id_in);
+
+        let error = loop {
+            let device = match hub.devices.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
+
+            #[cfg(feature = "trace")]
+            if let Some(ref mut trace) = *device.trace.lock() {
+                let data = match source {
+                    pipeline::MslSource::Source(ref code) => {
+                        trace.make_binary("metal", code.as_bytes())
+                    }
+                    pipeline::MslSource::Library(ref data) => trace.make_binary("metallib", data),
+                };
+                trace.add(trace::Action::CreateShaderModule {
+                    id: fid.id(),
+                    desc: desc.clone(),
+                    data,
+                });
+            };
+
+            let shader =
+                match unsafe { device.create_shader_module_msl(desc, &source, workgroup_size) } {
+                    Ok(shader) => shader,
+                    Err(e) => break e,
+                };
+            let (id, _) = fid.assign(Arc::new(shader));
+            api_log!("Device::create_shader_module_msl -> {id:?}");
+            return (id, None);
+        };
+
+        log::error!("Device::create_shader_module_msl error: {error}");
+
+        let id = fid.assign_error(desc.label.borrow_or_default());
+        (id, Some(error))
+    }
+
     pub fn shader_module_label<A: HalApi>(&self, id: id::ShaderModuleId) -> String {
         A::hub(self).shader_modules.label_for_resource(id)
     }
 
+    /// Returns the interface of the shader module's entry points.
+    ///
+    /// Returns `None` if the module is invalid, or if it wasn't validated,
+    /// e.g. because it was created from a native shader.
+    pub fn shader_module_reflect<A: HalApi>(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+    ) -> Option<wgt::ShaderReflection> {
+        profiling::scope!("ShaderModule::reflect");
+
+        let hub = A::hub(self);
+        let shader_module = hub.shader_modules.get(shader_module_id).ok()?;
+        let interface = shader_module.interface.as_ref()?;
+        Some(interface.reflect())
+    }
+
     pub fn shader_module_drop<A: HalApi>(&self, shader_module_id: id::ShaderModuleId) {
         profiling::scope!("ShaderModule::drop");
         api_log!("ShaderModule::drop {shader_module_id:?}");
@@ -1672,6 +1924,19 @@ impl Global {
         A::hub(self).render_pipelines.label_for_resource(id)
     }
 
+    /// Returns the `(group, binding)` of the resource called `name`, if any of the
+    /// pipeline's stages uses it.
+    pub fn render_pipeline_get_binding<A: HalApi>(
+        &self,
+        pipeline_id: id::RenderPipelineId,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        let hub = A::hub(self);
+        let pipeline = hub.render_pipelines.get(pipeline_id).ok()?;
+        let bind = pipeline.binding_names.get(name)?;
+        Some((bind.group, bind.binding))
+    }
+
     pub fn render_pipeline_drop<A: HalApi>(&self, render_pipeline_id: id::RenderPipelineId) {
         profiling::scope!("RenderPipeline::drop");
         api_log!("RenderPipeline::drop {render_pipeline_id:?}");
@@ -1803,6 +2068,19 @@ impl Global {
         A::hub(self).compute_pipelines.label_for_resource(id)
     }
 
+    /// Returns the `(group, binding)` of the resource called `name`, if the
+    /// pipeline's compute stage uses it.
+    pub fn compute_pipeline_get_binding<A: HalApi>(
+        &self,
+        pipeline_id: id::ComputePipelineId,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        let hub = A::hub(self);
+        let pipeline = hub.compute_pipelines.get(pipeline_id).ok()?;
+        let bind = pipeline.binding_names.get(name)?;
+        Some((bind.group, bind.binding))
+    }
+
     pub fn compute_pipeline_drop<A: HalApi>(&self, compute_pipeline_id: id::ComputePipelineId) {
         profiling::scope!("ComputePipeline::drop");
         api_log!("ComputePipeline::drop {compute_pipeline_id:?}");
@@ -1823,6 +2101,101 @@ impl Global {
         }
     }
 
+    /// # Safety
+    ///
+    /// The `data` of `desc` must have been returned by
+    /// [`Global::pipeline_cache_get_data`].
+    pub unsafe fn device_create_pipeline_cache<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &pipeline::PipelineCacheDescriptor<'_>,
+        id_in: Option<id::PipelineCacheId>,
+    ) -> (
+        id::PipelineCacheId,
+        Option<pipeline::CreatePipelineCacheError>,
+    ) {
+        profiling::scope!("Device::create_pipeline_cache");
+
+        let hub = A::hub(self);
+        let fid = hub.pipeline_caches.prepare(id_in);
+
+        let error = loop {
+            let device = match hub.devices.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
+
+            #[cfg(feature = "trace")]
+            if let Some(ref mut trace) = *device.trace.lock() {
+                // The data is only valid for this device, and a cache only
+                // affects how fast pipelines are created, so it isn't traced.
+                trace.add(trace::Action::CreatePipelineCache {
+                    id: fid.id(),
+                    desc: pipeline::PipelineCacheDescriptor {
+                        data: None,
+                        ..desc.clone()
+                    },
+                });
+            }
+
+            let cache = match unsafe { device.create_pipeline_cache(desc) } {
+                Ok(cache) => cache,
+                Err(err) => break err,
+            };
+
+            let (id, _) = fid.assign(Arc::new(cache));
+            api_log!("Device::create_pipeline_cache -> {id:?}");
+            return (id, None);
+        };
+
+        let id = fid.assign_error(desc.label.borrow_or_default());
+        (id, Some(error))
+    }
+
+    /// Returns the data of a pipeline cache, to create a cache with later.
+    ///
+    /// Returns `None` if the cache is invalid, or its backend fails to
+    /// serialize it.
+    pub fn pipeline_cache_get_data<A: HalApi>(
+        &self,
+        pipeline_cache_id: id::PipelineCacheId,
+    ) -> Option<Vec<u8>> {
+        profiling::scope!("PipelineCache::get_data");
+        api_log!("PipelineCache::get_data {pipeline_cache_id:?}");
+
+        let hub = A::hub(self);
+
+        let cache = hub.pipeline_caches.get(pipeline_cache_id).ok()?;
+        let device = &cache.device;
+        if !device.is_valid() {
+            return None;
+        }
+        let data = unsafe { device.raw().pipeline_cache_get_data(cache.raw()) }?;
+        let validation_key = device
+            .raw()
+            .pipeline_cache_validation_key()
+            .unwrap_or_default();
+        Some(crate::pipeline_cache::add_header(
+            &data,
+            &device.adapter.raw.info,
+            validation_key,
+        ))
+    }
+
+    pub fn pipeline_cache_drop<A: HalApi>(&self, pipeline_cache_id: id::PipelineCacheId) {
+        profiling::scope!("PipelineCache::drop");
+        api_log!("PipelineCache::drop {pipeline_cache_id:?}");
+
+        let hub = A::hub(self);
+
+        // Pipelines don't need their cache once they are created, so the
+        // cache is destroyed as soon as its last reference is dropped.
+        hub.pipeline_caches.unregister(pipeline_cache_id);
+    }
+
     pub fn surface_configure<A: HalApi>(
         &self,
         surface_id: SurfaceId,
@@ -2030,6 +2403,9 @@ impl Global {
 
                 // Wait for all work to finish before configuring the surface.
                 let snatch_guard = device.snatchable_lock.read();
+                if let Err(e) = device.flush_queue_batch(&snatch_guard) {
+                    break e.into();
+                }
                 let fence = device.fence.read();
                 match device.maintain(fence, wgt::Maintain::Wait, snatch_guard) {
                     Ok((closures, _)) => {
@@ -2110,6 +2486,7 @@ impl Global {
         device_id: DeviceId,
         maintain: wgt::Maintain<queue::WrappedSubmissionIndex>,
     ) -> Result<bool, WaitIdleError> {
+        profiling::scope!("Device::poll");
         api_log!("Device::poll");
 
         let hub = A::hub(self);
@@ -2142,6 +2519,7 @@ impl Global {
         maintain: wgt::Maintain<queue::WrappedSubmissionIndex>,
     ) -> Result<DevicePoll, WaitIdleError> {
         let snatch_guard = device.snatchable_lock.read();
+        device.flush_queue_batch(&snatch_guard)?;
         let fence = device.fence.read();
         let (closures, queue_empty) = device.maintain(fence, maintain, snatch_guard)?;
 
@@ -2201,6 +2579,7 @@ impl Global {
     /// Return `all_queue_empty` indicating whether there are more queue
     /// submissions still in flight.
     pub fn poll_all_devices(&self, force_wait: bool) -> Result<bool, WaitIdleError> {
+        profiling::scope!("poll_all_devices");
         api_log!("poll_all_devices");
         let mut closures = UserClosures::default();
         let mut all_queue_empty = true;
@@ -2235,6 +2614,23 @@ impl Global {
         A::hub(self).devices.label_for_resource(id)
     }
 
+    /// Set where the device caches the translation of the shader modules
+    /// created from now on, or stop caching them with `None`.
+    #[cfg(feature = "shader_cache")]
+    pub fn device_set_shader_cache<A: HalApi>(
+        &self,
+        id: DeviceId,
+        cache: Option<Arc<dyn hal::ShaderCache>>,
+    ) {
+        api_log!("Device::set_shader_cache");
+
+        let hub = A::hub(self);
+
+        if let Ok(device) = hub.devices.get(id) {
+            *device.shader_cache.lock() = cache;
+        }
+    }
+
     pub fn device_start_capture<A: HalApi>(&self, id: DeviceId) {
         api_log!("Device::start_capture");
 
@@ -2261,6 +2657,113 @@ impl Global {
         }
     }
 
+    pub fn device_set_cache_limits<A: HalApi>(
+        &self,
+        id: DeviceId,
+        limits: &wgt::DeviceCacheLimits,
+    ) {
+        api_log!("Device::set_cache_limits {limits:?}");
+
+        let hub = A::hub(self);
+
+        if let Ok(device) = hub.devices.get(id) {
+            if !device.is_valid() {
+                return;
+            }
+            unsafe { device.raw().set_cache_limits(limits) };
+        }
+    }
+
+    pub fn device_trim_caches<A: HalApi>(&self, id: DeviceId) {
+        api_log!("Device::trim_caches");
+
+        let hub = A::hub(self);
+
+        if let Ok(device) = hub.devices.get(id) {
+            if !device.is_valid() {
+                return;
+            }
+            unsafe { device.raw().trim_caches() };
+        }
+    }
+
+    pub fn device_cache_report<A: HalApi>(
+        &self,
+        id: DeviceId,
+    ) -> Result<wgt::DeviceCacheReport, InvalidDevice> {
+        let hub = A::hub(self);
+
+        let device = hub.devices.get(id).map_err(|_| InvalidDevice)?;
+        if !device.is_valid() {
+            return Err(InvalidDevice);
+        }
+
+        Ok(device.raw().cache_report())
+    }
+
+    /// Takes a snapshot of the device, its adapter, and its live resources.
+    pub fn device_capture_diagnostics<A: HalApi>(
+        &self,
+        id: DeviceId,
+    ) -> Result<wgt::DeviceDiagnostics, InvalidDevice> {
+        let hub = A::hub(self);
+
+        let device = hub.devices.get(id).map_err(|_| InvalidDevice)?;
+        if !device.is_valid() {
+            return Err(InvalidDevice);
+        }
+
+        fn labels<T: Resource>(resources: impl Iterator<Item = Arc<T>>) -> Vec<String> {
+            resources
+                .map(|resource| resource.as_info().label.clone())
+                .collect()
+        }
+
+        let resources = {
+            let trackers = device.trackers.lock();
+            wgt::LiveResourceReport {
+                buffers: trackers
+                    .buffers
+                    .used_resources()
+                    .map(|buffer| wgt::BufferReport {
+                        label: buffer.as_info().label.clone(),
+                        size: buffer.size,
+                        usage: buffer.usage,
+                    })
+                    .collect(),
+                textures: trackers
+                    .textures
+                    .used_resources()
+                    .map(|texture| wgt::TextureReport {
+                        label: texture.as_info().label.clone(),
+                        size: texture.desc.size,
+                        mip_level_count: texture.desc.mip_level_count,
+                        sample_count: texture.desc.sample_count,
+                        dimension: texture.desc.dimension,
+                        format: texture.desc.format,
+                        usage: texture.desc.usage,
+                    })
+                    .collect(),
+                texture_views: labels(trackers.views.used_resources()),
+                samplers: labels(trackers.samplers.used_resources()),
+                bind_groups: labels(trackers.bind_groups.used_resources()),
+                compute_pipelines: labels(trackers.compute_pipelines.used_resources()),
+                render_pipelines: labels(trackers.render_pipelines.used_resources()),
+                render_bundles: labels(trackers.bundles.used_resources()),
+                query_sets: labels(trackers.query_sets.used_resources()),
+            }
+        };
+
+        Ok(wgt::DeviceDiagnostics {
+            adapter_info: device.adapter.raw.info.clone(),
+            features: device.features,
+            limits: device.limits.clone(),
+            resources,
+            allocator: device.raw().allocator_report(),
+            caches: device.raw().cache_report(),
+        })
+    }
+
     // This is a test-only function to force the device into an
     // invalid state by inserting an error value in its place in
     // the registry.
@@ -2324,6 +2827,7 @@ impl Global {
     }
 
     pub fn device_destroy<A: HalApi>(&self, device_id: DeviceId) {
+        profiling::scope!("Device::destroy");
         api_log!("Device::destroy {device_id:?}");
 
         let hub = A::hub(self);
@@ -2376,6 +2880,7 @@ impl Global {
         size: Option<BufferAddress>,
         op: BufferMapOperation,
     ) -> BufferAccessResult {
+        profiling::scope!("Buffer::map_async");
         api_log!("Buffer::map_async {buffer_id:?} offset {offset:?} size {size:?} op: {op:?}");
 
         // User callbacks must not be called while holding buffer_map_async_inner's locks, so we
@@ -2502,7 +3007,9 @@ impl Global {
 
             {
                 let mut trackers = buffer.device.as_ref().trackers.lock();
-                trackers.buffers.set_single(&buffer, internal_use);
+                trackers
+                    .buffers
+                    .set_single(&buffer, 0..buffer.size, internal_use);
                 //TODO: Check if draining ALL buffers is correct!
                 let _ = trackers.buffers.drain_transitions(&snatch_guard);
             }
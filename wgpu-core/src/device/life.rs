@@ -2,6 +2,7 @@ use crate::{
     binding_model::{BindGroup, BindGroupLayout, PipelineLayout},
     command::RenderBundle,
     device::{
+        deferred_drop::DropThread,
         queue::{EncoderInFlight, SubmittedWorkDoneClosure, TempResource},
         DeviceError, DeviceLostClosure,
     },
@@ -60,6 +61,39 @@ impl<A: HalApi> ResourceMaps<A> {
         }
     }
 
+    pub(crate) fn is_empty(&self) -> bool {
+        let ResourceMaps {
+            buffers,
+            staging_buffers,
+            textures,
+            texture_views,
+            samplers,
+            bind_groups,
+            bind_group_layouts,
+            render_pipelines,
+            compute_pipelines,
+            pipeline_layouts,
+            render_bundles,
+            query_sets,
+            destroyed_buffers,
+            destroyed_textures,
+        } = self;
+        buffers.is_empty()
+            && staging_buffers.is_empty()
+            && textures.is_empty()
+            && texture_views.is_empty()
+            && samplers.is_empty()
+            && bind_groups.is_empty()
+            && bind_group_layouts.is_empty()
+            && render_pipelines.is_empty()
+            && compute_pipelines.is_empty()
+            && pipeline_layouts.is_empty()
+            && render_bundles.is_empty()
+            && query_sets.is_empty()
+            && destroyed_buffers.is_empty()
+            && destroyed_textures.is_empty()
+    }
+
     pub(crate) fn clear(&mut self) {
         let ResourceMaps {
             buffers,
@@ -148,15 +182,16 @@ impl<A: HalApi> ResourceMaps<A> {
 /// [`ResourceInfo::submission_index`]. When the user drops a resource, if the
 /// submission in which it was last used is still present in the device's queue,
 /// we add the resource to [`ActiveSubmission::last_resources`]. Finally, when
-/// this `ActiveSubmission` is dequeued and dropped in
-/// [`LifetimeTracker::triage_submissions`], we drop `last_resources` along with
-/// it. Thus, unless a resource is dropped by the user, it doesn't need to be
+/// this `ActiveSubmission` is dequeued in
+/// [`LifetimeTracker::triage_submissions`], we send `last_resources` to the
+/// device's [`DropThread`] to be dropped. Thus, unless a resource is dropped by the user, it doesn't need to be
 /// touched at all when processing completed work.
 ///
 /// However, it's not clear that this is effective. See [#5560].
 ///
 /// [`wgpu_hal`]: hal
 /// [`ResourceInfo::submission_index`]: crate::resource::ResourceInfo
+/// [`DropThread`]: super::deferred_drop::DropThread
 /// [#5560]: https://github.com/gfx-rs/wgpu/issues/5560
 struct ActiveSubmission<A: HalApi> {
     /// The index of the submission we track.
@@ -191,7 +226,7 @@ struct ActiveSubmission<A: HalApi> {
     /// the command encoder is recycled.
     ///
     /// [`wgpu_hal::Queue::submit`]: hal::Queue::submit
-    encoders: Vec<EncoderInFlight<A>>,
+    encoders: SmallVec<[EncoderInFlight<A>; 2]>,
 
     /// List of queue "on_submitted_work_done" closures to be called once this
     /// submission has completed.
@@ -308,7 +343,7 @@ impl<A: HalApi> LifetimeTracker<A> {
         &mut self,
         index: SubmissionIndex,
         temp_resources: impl Iterator<Item = TempResource<A>>,
-        encoders: Vec<EncoderInFlight<A>>,
+        encoders: SmallVec<[EncoderInFlight<A>; 2]>,
     ) {
         let mut last_resources = ResourceMaps::new();
         for res in temp_resources {
@@ -350,6 +385,23 @@ impl<A: HalApi> LifetimeTracker<A> {
         });
     }
 
+    /// Hand the command buffers of a submission that was tracked before it
+    /// was sent to the backend over to it.
+    ///
+    /// See [`SubmissionBatch`](super::queue::SubmissionBatch).
+    pub fn add_encoders(
+        &mut self,
+        index: SubmissionIndex,
+        encoders: impl Iterator<Item = EncoderInFlight<A>>,
+    ) {
+        // The submission is only triaged once the fence reaches its index,
+        // which it can't before it is sent.
+        match self.active.iter_mut().find(|a| a.index == index) {
+            Some(submission) => submission.encoders.extend(encoders),
+            None => log::error!("Submission {index} was triaged before being sent"),
+        }
+    }
+
     pub fn post_submit(&mut self) {
         for v in self.future_suspected_buffers.drain(..) {
             self.suspected_resources
@@ -390,6 +442,7 @@ impl<A: HalApi> LifetimeTracker<A> {
         &mut self,
         last_done: SubmissionIndex,
         command_allocator: &crate::command::CommandAllocator<A>,
+        drop_thread: &DropThread<ResourceMaps<A>>,
     ) -> SmallVec<[SubmittedWorkDoneClosure; 1]> {
         profiling::scope!("triage_submissions");
 
@@ -410,6 +463,9 @@ impl<A: HalApi> LifetimeTracker<A> {
                 command_allocator.release_encoder(raw);
             }
             work_done_closures.extend(a.work_done_closures);
+            if !a.last_resources.is_empty() {
+                drop_thread.drop_later(a.last_resources);
+            }
         }
         work_done_closures
     }
@@ -563,6 +619,10 @@ impl<A: HalApi> LifetimeTracker<A> {
             |maps| &mut maps.bind_groups,
         );
         for bind_group in removed_resources {
+            // Submissions don't mark the views and samplers they use through
+            // bind groups, so they inherit the bind group's last use before
+            // being triaged themselves.
+            let submit_index = bind_group.info.submission_index();
             for v in bind_group.used.buffers.drain_resources() {
                 self.suspected_resources
                     .buffers
@@ -574,11 +634,13 @@ impl<A: HalApi> LifetimeTracker<A> {
                     .insert(v.as_info().tracker_index(), v);
             }
             for v in bind_group.used.views.drain_resources() {
+                v.info.use_at_least(submit_index);
                 self.suspected_resources
                     .texture_views
                     .insert(v.as_info().tracker_index(), v);
             }
             for v in bind_group.used.samplers.drain_resources() {
+                v.info.use_at_least(submit_index);
                 self.suspected_resources
                     .samplers
                     .insert(v.as_info().tracker_index(), v);
@@ -19,10 +19,15 @@ use std::{iter, num::NonZeroU32, ptr};
 
 pub mod any_device;
 pub(crate) mod bgl;
+pub mod cross_device;
+mod deferred_drop;
 pub mod global;
 mod life;
 pub mod queue;
 pub mod resource;
+#[cfg(feature = "shader_cache")]
+pub mod shader_cache;
+mod staging;
 #[cfg(any(feature = "trace", feature = "replay"))]
 pub mod trace;
 pub use {life::WaitIdleError, resource::Device};
@@ -7,19 +7,25 @@ use crate::{
         ClearError, CommandAllocator, CommandBuffer, CopySide, ImageCopyTexture, TransferError,
     },
     conv,
-    device::{DeviceError, WaitIdleError},
+    device::{
+        staging::{StagingRing, StagingSlice},
+        DeviceError, WaitIdleError,
+    },
     get_lowest_common_denom,
     global::Global,
     hal_api::HalApi,
     hal_label,
     id::{self, DeviceId, QueueId},
-    init_tracker::{has_copy_partial_init_tracker_coverage, TextureInitRange},
+    init_tracker::{has_copy_partial_init_tracker_coverage, TextureInitRange, TextureInitTracker},
     lock::{rank, Mutex, RwLockWriteGuard},
     resource::{
         Buffer, BufferAccessError, BufferMapState, DestroyedBuffer, DestroyedTexture, Resource,
-        ResourceInfo, ResourceType, StagingBuffer, Texture, TextureInner,
+        ResourceInfo, ResourceType, SharedTextureError, StagingBuffer, Texture, TextureInner,
+        SHARED_TEXTURE_RELEASED_USE,
     },
-    resource_log, track, FastHashMap, SubmissionIndex,
+    resource_log,
+    snatch::SnatchGuard,
+    track, FastHashMap, SubmissionIndex,
 };
 
 use hal::{CommandEncoder as _, Device as _, Queue as _};
@@ -37,6 +43,7 @@ pub struct Queue<A: HalApi> {
     pub(crate) device: Option<Arc<Device<A>>>,
     pub(crate) raw: Option<A::Queue>,
     pub(crate) info: ResourceInfo<Queue<A>>,
+    pub(crate) batch: Mutex<SubmissionBatch<A>>,
 }
 
 impl<A: HalApi> Resource for Queue<A> {
@@ -55,11 +62,146 @@ impl<A: HalApi> Resource for Queue<A> {
 
 impl<A: HalApi> Drop for Queue<A> {
     fn drop(&mut self) {
+        let device = self.device.as_ref().unwrap();
+        if let Err(error) = self.flush_batch(&device.snatchable_lock.read()) {
+            log::error!("Failed to send the held back submissions: {error}");
+        }
         let queue = self.raw.take().unwrap();
-        self.device.as_ref().unwrap().release_queue(queue);
+        device.release_queue(queue);
+    }
+}
+
+impl<A: HalApi> Queue<A> {
+    pub(crate) fn new(raw: A::Queue) -> Self {
+        Self {
+            device: None,
+            raw: Some(raw),
+            info: ResourceInfo::new("<Queue>", None),
+            batch: Mutex::new(rank::QUEUE_BATCH, SubmissionBatch::default()),
+        }
+    }
+
+    /// Send the submissions held back in [`Queue::batch`] to the backend.
+    ///
+    /// This must be done before waiting on the device's fence for the
+    /// index of one of them.
+    pub(crate) fn flush_batch(&self, snatch_guard: &SnatchGuard) -> Result<(), DeviceError> {
+        let device = self.device.as_ref().unwrap();
+        let mut fence_guard = device.fence.write();
+        let fence = fence_guard.as_mut().unwrap();
+        self.submit_batch(&mut self.batch.lock(), fence, snatch_guard)
+    }
+
+    fn submit_batch(
+        &self,
+        batch: &mut SubmissionBatch<A>,
+        fence: &mut A::Fence,
+        snatch_guard: &SnatchGuard,
+    ) -> Result<(), DeviceError> {
+        let Some(last) = batch.submissions.last() else {
+            return Ok(());
+        };
+        profiling::scope!("submit batch");
+        let signal_value = last.index;
+
+        let result = {
+            let refs = batch
+                .submissions
+                .iter()
+                .flat_map(|submission| {
+                    submission
+                        .pending_writes
+                        .iter()
+                        .filter_map(|pending_writes| pending_writes.cmd_buffers.last())
+                        .chain(
+                            submission
+                                .executions
+                                .iter()
+                                .flat_map(|execution| execution.cmd_buffers.iter()),
+                        )
+                })
+                .collect::<SmallVec<[_; 8]>>();
+            let surface_textures = batch
+                .surface_textures
+                .iter()
+                .filter_map(|texture| match texture.inner.get(snatch_guard) {
+                    Some(TextureInner::Surface { raw, .. }) => raw.as_ref(),
+                    _ => None,
+                })
+                .collect::<SmallVec<[_; 2]>>();
+            unsafe {
+                self.raw
+                    .as_ref()
+                    .unwrap()
+                    .submit(&refs, &surface_textures, (fence, signal_value))
+            }
+        };
+
+        // The submissions are already tracked: hand their command buffers
+        // over, to be recycled once they complete.
+        let mut life_tracker = self.device.as_ref().unwrap().lock_life();
+        for submission in batch.submissions.drain(..) {
+            life_tracker.add_encoders(
+                submission.index,
+                submission
+                    .pending_writes
+                    .into_iter()
+                    .chain(submission.executions),
+            );
+        }
+        batch.surface_textures.clear();
+
+        result.map_err(DeviceError::from)
     }
 }
 
+/// Submissions held back under [`wgt::SubmissionBatching::Batched`].
+///
+/// Each held back submission is tracked by the [`LifetimeTracker`] right
+/// away, so that resources it uses are kept alive and buffers it uses are
+/// mapped after it, but without its command buffers. They are submitted all
+/// at once by [`Queue::flush_batch`], with the device's fence signaled to the
+/// index of the last submission, and then handed to the [`LifetimeTracker`].
+///
+/// [`LifetimeTracker`]: super::life::LifetimeTracker
+pub(crate) struct SubmissionBatch<A: HalApi> {
+    pub(crate) batching: wgt::SubmissionBatching,
+    submissions: Vec<BatchedSubmission<A>>,
+    surface_textures: SmallVec<[Arc<Texture<A>>; 2]>,
+}
+
+impl<A: HalApi> Default for SubmissionBatch<A> {
+    fn default() -> Self {
+        Self {
+            batching: wgt::SubmissionBatching::Immediate,
+            submissions: Vec::new(),
+            surface_textures: SmallVec::new(),
+        }
+    }
+}
+
+impl<A: HalApi> SubmissionBatch<A> {
+    fn is_full(&self) -> bool {
+        match self.batching {
+            wgt::SubmissionBatching::Immediate => true,
+            wgt::SubmissionBatching::Batched { max_submissions } => {
+                self.submissions.len() >= max_submissions as usize
+            }
+        }
+    }
+}
+
+struct BatchedSubmission<A: HalApi> {
+    index: SubmissionIndex,
+    /// The pending writes encoder, if writes were recorded since the last
+    /// submission.
+    ///
+    /// Only its last command buffer belongs to this submission: the ones
+    /// before it were submitted earlier, with immediate submissions.
+    pending_writes: Option<EncoderInFlight<A>>,
+    executions: SmallVec<[EncoderInFlight<A>; 2]>,
+}
+
 /// Number of command buffers that we generate from the same pool
 /// for the write_xxx commands, before the pool is recycled.
 ///
@@ -192,7 +334,8 @@ impl<A: HalApi> EncoderInFlight<A> {
 /// When locking pending_writes be sure that tracker is not locked
 /// and try to lock trackers for the minimum timespan possible
 ///
-/// All uses of [`StagingBuffer`]s end up here.
+/// All uses of [`StagingBuffer`]s end up here, as well as the
+/// [`StagingRing`] that most queue writes stage their data in.
 #[derive(Debug)]
 pub(crate) struct PendingWrites<A: HalApi> {
     pub command_encoder: A::CommandEncoder,
@@ -210,10 +353,12 @@ pub(crate) struct PendingWrites<A: HalApi> {
 
     /// All command buffers allocated from `command_encoder`.
     pub executing_command_buffers: Vec<A::CommandBuffer>,
+
+    pub(crate) staging_ring: StagingRing<A>,
 }
 
 impl<A: HalApi> PendingWrites<A> {
-    pub fn new(command_encoder: A::CommandEncoder) -> Self {
+    pub fn new(command_encoder: A::CommandEncoder, capabilities: &hal::Capabilities) -> Self {
         Self {
             command_encoder,
             is_recording: false,
@@ -221,6 +366,7 @@ impl<A: HalApi> PendingWrites<A> {
             dst_buffers: FastHashMap::default(),
             dst_textures: FastHashMap::default(),
             executing_command_buffers: Vec::new(),
+            staging_ring: StagingRing::new(capabilities),
         }
     }
 
@@ -235,6 +381,7 @@ impl<A: HalApi> PendingWrites<A> {
         }
 
         self.temp_resources.clear();
+        self.staging_ring.dispose(device);
     }
 
     pub fn consume_temp(&mut self, resource: TempResource<A>) {
@@ -278,6 +425,21 @@ impl<A: HalApi> PendingWrites<A> {
         }
     }
 
+    /// Hands over the encoder and its command buffers, when the command buffer
+    /// finished by [`PendingWrites::pre_submit`] is held back in a batch.
+    fn take_encoder(
+        &mut self,
+        command_allocator: &CommandAllocator<A>,
+        device: &A::Device,
+        queue: &A::Queue,
+    ) -> Result<EncoderInFlight<A>, DeviceError> {
+        let new_encoder = command_allocator.acquire_encoder(device, queue)?;
+        Ok(EncoderInFlight {
+            raw: mem::replace(&mut self.command_encoder, new_encoder),
+            cmd_buffers: mem::take(&mut self.executing_command_buffers),
+        })
+    }
+
     pub fn activate(&mut self) -> &mut A::CommandEncoder {
         if !self.is_recording {
             unsafe {
@@ -300,6 +462,12 @@ impl<A: HalApi> PendingWrites<A> {
     }
 }
 
+/// The staging memory the data of a buffer write is in.
+enum StagingSource<'a, A: HalApi> {
+    Buffer(&'a StagingBuffer<A>),
+    Ring(&'a StagingSlice<A>),
+}
+
 fn prepare_staging_buffer<A: HalApi>(
     device: &Arc<Device<A>>,
     size: wgt::BufferAddress,
@@ -382,6 +550,8 @@ pub enum QueueSubmitError {
     Unmap(#[from] BufferAccessError),
     #[error("Buffer {0:?} is still mapped")]
     BufferStillMapped(id::BufferId),
+    #[error("Shared texture {0:?} is owned by another process")]
+    SharedTextureNotOwned(id::TextureId),
     #[error("Surface output was dropped before the command buffer got submitted")]
     SurfaceOutputDropped,
     #[error("Surface was unconfigured before the command buffer got submitted")]
@@ -448,13 +618,37 @@ impl Global {
             return Ok(());
         }
 
+        if self.queue_write_buffer_in_place(device, buffer_id, buffer_offset, data)? {
+            return Ok(());
+        }
+
+        let mut pending_writes = device.pending_writes.lock();
+        let pending_writes = pending_writes.as_mut().unwrap();
+
+        if let Some(slice) =
+            pending_writes
+                .staging_ring
+                .allocate(device.raw(), data_size, device.instance_flags)?
+        {
+            unsafe {
+                profiling::scope!("copy");
+                ptr::copy_nonoverlapping(data.as_ptr(), slice.ptr(), data.len());
+                slice.flush(device.raw());
+            }
+            return self.queue_write_staging_buffer_impl(
+                device,
+                pending_writes,
+                StagingSource::Ring(&slice),
+                buffer_id,
+                buffer_offset,
+            );
+        }
+
         // Platform validation requires that the staging buffer always be
         // freed, even if an error occurs. All paths from here must call
         // `device.pending_writes.consume`.
         let (staging_buffer, staging_buffer_ptr) =
             prepare_staging_buffer(device, data_size, device.instance_flags)?;
-        let mut pending_writes = device.pending_writes.lock();
-        let pending_writes = pending_writes.as_mut().unwrap();
 
         let stage_fid = hub.staging_buffers.request();
         let staging_buffer = stage_fid.init(staging_buffer);
@@ -471,7 +665,7 @@ impl Global {
         let result = self.queue_write_staging_buffer_impl(
             device,
             pending_writes,
-            &staging_buffer,
+            StagingSource::Buffer(&staging_buffer),
             buffer_id,
             buffer_offset,
         );
@@ -545,7 +739,7 @@ impl Global {
         let result = self.queue_write_staging_buffer_impl(
             device,
             pending_writes,
-            &staging_buffer,
+            StagingSource::Buffer(&staging_buffer),
             buffer_id,
             buffer_offset,
         );
@@ -574,6 +768,82 @@ impl Global {
         Ok(())
     }
 
+    /// Writes `data` to the buffer by mapping it, if it is
+    /// [`host_writable`](Buffer::host_writable) and the device is done with
+    /// it.
+    ///
+    /// Returns `false` if the data needs to be copied to the buffer from a
+    /// staging buffer instead.
+    fn queue_write_buffer_in_place<A: HalApi>(
+        &self,
+        device: &Device<A>,
+        buffer_id: id::BufferId,
+        buffer_offset: wgt::BufferAddress,
+        data: &[u8],
+    ) -> Result<bool, QueueWriteError> {
+        let hub = A::hub(self);
+        let dst = hub
+            .buffers
+            .get(buffer_id)
+            .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
+        if !dst.host_writable {
+            return Ok(false);
+        }
+        let data_size = data.len() as wgt::BufferAddress;
+        self.queue_validate_write_buffer_impl(&dst, buffer_id, buffer_offset, data_size)?;
+
+        let snatch_guard = device.snatchable_lock.read();
+        let dst_raw = dst
+            .raw(&snatch_guard)
+            .ok_or(TransferError::InvalidBuffer(buffer_id))?;
+        if !matches!(*dst.map_state.lock(), BufferMapState::Idle) {
+            return Ok(false);
+        }
+
+        // Submissions can't start while the fence is locked, so the device
+        // doesn't start using the buffer during the write.
+        let fence_guard = device.fence.read();
+        let fence = fence_guard.as_ref().unwrap();
+        let last_done_index =
+            unsafe { device.raw().get_fence_value(fence) }.map_err(DeviceError::from)?;
+        if dst.info.submission_index() > last_done_index {
+            return Ok(false);
+        }
+        // Earlier writes to the buffer in the pending writes must happen
+        // first.
+        let pending_writes = device.pending_writes.lock();
+        if pending_writes
+            .as_ref()
+            .unwrap()
+            .dst_buffers
+            .contains_key(&buffer_id)
+        {
+            return Ok(false);
+        }
+
+        let range = buffer_offset..buffer_offset + data_size;
+        unsafe {
+            profiling::scope!("copy");
+            let mapping = device
+                .raw()
+                .map_buffer(dst_raw, range.clone())
+                .map_err(DeviceError::from)?;
+            ptr::copy_nonoverlapping(data.as_ptr(), mapping.ptr.as_ptr(), data.len());
+            if !mapping.is_coherent {
+                device
+                    .raw()
+                    .flush_mapped_ranges(dst_raw, iter::once(range.clone()));
+            }
+            device
+                .raw()
+                .unmap_buffer(dst_raw)
+                .map_err(DeviceError::from)?;
+        }
+        dst.initialization_status.write().drain(range);
+
+        Ok(true)
+    }
+
     fn queue_validate_write_buffer_impl<A: HalApi>(
         &self,
         buffer: &Buffer<A>,
@@ -609,12 +879,17 @@ impl Global {
         &self,
         device: &Device<A>,
         pending_writes: &mut PendingWrites<A>,
-        staging_buffer: &StagingBuffer<A>,
+        staging: StagingSource<'_, A>,
         buffer_id: id::BufferId,
         buffer_offset: u64,
     ) -> Result<(), QueueWriteError> {
         let hub = A::hub(self);
 
+        let src_buffer_size = match staging {
+            StagingSource::Buffer(staging_buffer) => staging_buffer.size,
+            StagingSource::Ring(slice) => slice.size,
+        };
+
         let (dst, transition) = {
             let buffer_guard = hub.buffers.read();
             let dst = buffer_guard
@@ -623,7 +898,11 @@ impl Global {
             let mut trackers = device.trackers.lock();
             trackers
                 .buffers
-                .set_single(dst, hal::BufferUses::COPY_DST)
+                .set_single(
+                    dst,
+                    buffer_offset..buffer_offset.saturating_add(src_buffer_size),
+                    hal::BufferUses::COPY_DST,
+                )
                 .ok_or(TransferError::InvalidBuffer(buffer_id))?
         };
         let snatch_guard = device.snatchable_lock.read();
@@ -636,31 +915,42 @@ impl Global {
             return Err(DeviceError::WrongDevice.into());
         }
 
-        let src_buffer_size = staging_buffer.size;
         self.queue_validate_write_buffer_impl(&dst, buffer_id, buffer_offset, src_buffer_size)?;
 
         dst.info
             .use_at(device.active_submission_index.load(Ordering::Relaxed) + 1);
 
+        let inner_buffer;
+        let (src_buffer, src_offset, src_range) = match staging {
+            StagingSource::Buffer(staging_buffer) => {
+                inner_buffer = staging_buffer.raw.lock();
+                (inner_buffer.as_ref().unwrap(), 0, None)
+            }
+            StagingSource::Ring(slice) => (
+                slice.raw(),
+                slice.offset,
+                Some(slice.offset..slice.offset + slice.size),
+            ),
+        };
         let region = wgt::BufferSize::new(src_buffer_size).map(|size| hal::BufferCopy {
-            src_offset: 0,
+            src_offset,
             dst_offset: buffer_offset,
             size,
         });
-        let inner_buffer = staging_buffer.raw.lock();
         let barriers = iter::once(hal::BufferBarrier {
-            buffer: inner_buffer.as_ref().unwrap(),
+            buffer: src_buffer,
+            range: src_range,
             usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
         })
-        .chain(transition.map(|pending| pending.into_hal(&dst, &snatch_guard)));
+        .chain(
+            transition
+                .into_iter()
+                .map(|pending| pending.into_hal(&dst, &snatch_guard)),
+        );
         let encoder = pending_writes.activate();
         unsafe {
             encoder.transition_buffers(barriers);
-            encoder.copy_buffer_to_buffer(
-                inner_buffer.as_ref().unwrap(),
-                dst_raw,
-                region.into_iter(),
-            );
+            encoder.copy_buffer_to_buffer(src_buffer, dst_raw, region.into_iter());
         }
         let dst = hub.buffers.get(buffer_id).unwrap();
         pending_writes.dst_buffers.insert(buffer_id, dst.clone());
@@ -738,7 +1028,11 @@ impl Global {
             return Err(TransferError::CopyAspectNotOne.into());
         }
 
-        if !conv::is_valid_copy_dst_texture_format(dst.desc.format, destination.aspect) {
+        if !conv::is_valid_copy_dst_texture_format(
+            dst.desc.format,
+            destination.aspect,
+            device.features,
+        ) {
             return Err(TransferError::CopyToForbiddenTextureFormat {
                 format: dst.desc.format,
                 aspect: destination.aspect,
@@ -791,6 +1085,11 @@ impl Global {
 
         let mut pending_writes = device.pending_writes.lock();
         let pending_writes = pending_writes.as_mut().unwrap();
+        let slice = pending_writes.staging_ring.allocate(
+            device.raw(),
+            stage_size,
+            device.instance_flags,
+        )?;
         let encoder = pending_writes.activate();
 
         // If the copy does not fully cover the layers, we need to initialize to
@@ -853,14 +1152,18 @@ impl Global {
             .bytes_per_row
             .unwrap_or(width_blocks * block_size);
 
-        // Platform validation requires that the staging buffer always be
-        // freed, even if an error occurs. All paths from here must call
-        // `device.pending_writes.consume`.
-        let (staging_buffer, staging_buffer_ptr) =
-            prepare_staging_buffer(device, stage_size, device.instance_flags)?;
-
-        let stage_fid = hub.staging_buffers.request();
-        let staging_buffer = stage_fid.init(staging_buffer);
+        let (staging_buffer, staging_buffer_ptr) = match slice {
+            Some(ref slice) => (None, slice.ptr()),
+            None => {
+                // Platform validation requires that the staging buffer always
+                // be freed, even if an error occurs. All paths from here must
+                // call `device.pending_writes.consume`.
+                let (staging_buffer, staging_buffer_ptr) =
+                    prepare_staging_buffer(device, stage_size, device.instance_flags)?;
+                let stage_fid = hub.staging_buffers.request();
+                (Some(stage_fid.init(staging_buffer)), staging_buffer_ptr)
+            }
+        };
 
         if stage_bytes_per_row == bytes_per_row {
             profiling::scope!("copy aligned");
@@ -895,19 +1198,27 @@ impl Global {
             }
         }
 
-        if let Err(e) = unsafe { staging_buffer.flush(device.raw()) } {
-            pending_writes.consume(staging_buffer);
-            return Err(e.into());
+        match slice {
+            Some(ref slice) => unsafe { slice.flush(device.raw()) },
+            None => {
+                let staging_buffer = staging_buffer.as_ref().unwrap();
+                if let Err(e) = unsafe { staging_buffer.flush(device.raw()) } {
+                    pending_writes.consume(Arc::clone(staging_buffer));
+                    return Err(e.into());
+                }
+            }
         }
 
+        let src_offset = slice.as_ref().map_or(0, |slice| slice.offset);
         let regions = (0..array_layer_count).map(|rel_array_layer| {
             let mut texture_base = dst_base.clone();
             texture_base.array_layer += rel_array_layer;
             hal::BufferTextureCopy {
                 buffer_layout: wgt::ImageDataLayout {
-                    offset: rel_array_layer as u64
-                        * block_rows_per_image as u64
-                        * stage_bytes_per_row as u64,
+                    offset: src_offset
+                        + rel_array_layer as u64
+                            * block_rows_per_image as u64
+                            * stage_bytes_per_row as u64,
                     bytes_per_row: Some(stage_bytes_per_row),
                     rows_per_image: Some(block_rows_per_image),
                 },
@@ -917,9 +1228,19 @@ impl Global {
         });
 
         {
-            let inner_buffer = staging_buffer.raw.lock();
+            let inner_buffer;
+            let src_buffer = match slice {
+                Some(ref slice) => slice.raw(),
+                None => {
+                    inner_buffer = staging_buffer.as_ref().unwrap().raw.lock();
+                    inner_buffer.as_ref().unwrap()
+                }
+            };
             let barrier = hal::BufferBarrier {
-                buffer: inner_buffer.as_ref().unwrap(),
+                buffer: src_buffer,
+                range: slice
+                    .as_ref()
+                    .map(|slice| slice.offset..slice.offset + slice.size),
                 usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
             };
 
@@ -931,11 +1252,13 @@ impl Global {
             unsafe {
                 encoder.transition_textures(transition.map(|pending| pending.into_hal(dst_raw)));
                 encoder.transition_buffers(iter::once(barrier));
-                encoder.copy_buffer_to_texture(inner_buffer.as_ref().unwrap(), dst_raw, regions);
+                encoder.copy_buffer_to_texture(src_buffer, dst_raw, regions);
             }
         }
 
-        pending_writes.consume(staging_buffer);
+        if let Some(staging_buffer) = staging_buffer {
+            pending_writes.consume(staging_buffer);
+        }
         pending_writes
             .dst_textures
             .insert(destination.texture, dst.clone());
@@ -943,6 +1266,152 @@ impl Global {
         Ok(())
     }
 
+    /// Releases a shared texture, for another process to acquire it with
+    /// [`Global::queue_acquire_shared_texture`].
+    ///
+    /// The texture is transitioned to the state it's shared in at the start
+    /// of the next submission, after the command buffers submitted before,
+    /// and can't be used by the command buffers submitted after. Its
+    /// uninitialized contents are cleared first. The other process may only
+    /// acquire the texture once the submission is done.
+    pub fn queue_release_shared_texture<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        texture_id: id::TextureId,
+    ) -> Result<(), SharedTextureError> {
+        profiling::scope!("Queue::release_shared_texture");
+        api_log!("Queue::release_shared_texture {texture_id:?}");
+
+        let (device, texture) = self.get_shared_texture::<A>(queue_id, texture_id)?;
+        if !texture.is_owned() {
+            return Err(SharedTextureError::NotOwned(texture_id));
+        }
+        if texture.is_destroyed(&device.snatchable_lock.read()) {
+            return Err(SharedTextureError::DestroyedTexture(texture_id));
+        }
+
+        let mut pending_writes = device.pending_writes.lock();
+        let pending_writes = pending_writes.as_mut().unwrap();
+        let encoder = pending_writes.activate();
+
+        // The other process treats the texture as initialized, so
+        // uninitialized ranges are cleared before it's released.
+        let mut initialization_status = texture.initialization_status.write();
+        for mip_level in 0..texture.desc.mip_level_count {
+            let layer_ranges = initialization_status.mips[mip_level as usize]
+                .drain(0..texture.desc.array_layer_count())
+                .collect::<Vec<std::ops::Range<u32>>>();
+            for layer_range in layer_ranges {
+                let mut trackers = device.trackers.lock();
+                crate::command::clear_texture(
+                    &texture,
+                    TextureInitRange {
+                        mip_range: mip_level..(mip_level + 1),
+                        layer_range,
+                    },
+                    encoder,
+                    &mut trackers.textures,
+                    &device.alignments,
+                    device.zero_buffer.as_ref().unwrap(),
+                    &device.snatchable_lock.read(),
+                )?;
+            }
+        }
+
+        let snatch_guard = device.snatchable_lock.read();
+        let raw = texture
+            .raw(&snatch_guard)
+            .ok_or(SharedTextureError::DestroyedTexture(texture_id))?;
+        {
+            let mut trackers = device.trackers.lock();
+            let transition = trackers
+                .textures
+                .set_single(
+                    &texture,
+                    texture.full_range.clone(),
+                    SHARED_TEXTURE_RELEASED_USE,
+                )
+                .ok_or(SharedTextureError::InvalidTexture(texture_id))?;
+            unsafe {
+                encoder.transition_textures(transition.map(|pending| pending.into_hal(raw)));
+            }
+        }
+
+        texture
+            .info
+            .use_at(device.active_submission_index.load(Ordering::Relaxed) + 1);
+        pending_writes
+            .dst_textures
+            .insert(texture_id, texture.clone());
+        texture
+            .shared_ownership
+            .as_ref()
+            .unwrap()
+            .store(false, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Acquires a shared texture released by another process with
+    /// [`Global::queue_release_shared_texture`], once the submission that
+    /// released it is done.
+    ///
+    /// The command buffers submitted after this may use the texture, and see
+    /// the contents the other process left in it.
+    pub fn queue_acquire_shared_texture<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        texture_id: id::TextureId,
+    ) -> Result<(), SharedTextureError> {
+        profiling::scope!("Queue::acquire_shared_texture");
+        api_log!("Queue::acquire_shared_texture {texture_id:?}");
+
+        let (_, texture) = self.get_shared_texture::<A>(queue_id, texture_id)?;
+        if texture.is_owned() {
+            return Err(SharedTextureError::AlreadyOwned(texture_id));
+        }
+
+        // The texture stays in the state it was released in, tracked as the
+        // last state of the texture, or the state of imported textures.
+        *texture.initialization_status.write() =
+            TextureInitTracker::new(texture.desc.mip_level_count, 0);
+        texture
+            .shared_ownership
+            .as_ref()
+            .unwrap()
+            .store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Returns the device of `queue_id`, and its shared texture `texture_id`.
+    fn get_shared_texture<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        texture_id: id::TextureId,
+    ) -> Result<(Arc<Device<A>>, Arc<Texture<A>>), SharedTextureError> {
+        let hub = A::hub(self);
+
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+        let device = queue.device.as_ref().unwrap().clone();
+
+        let texture = hub
+            .textures
+            .get(texture_id)
+            .map_err(|_| SharedTextureError::InvalidTexture(texture_id))?;
+        if texture.device.as_info().id().into_queue_id() != queue_id {
+            return Err(DeviceError::WrongDevice.into());
+        }
+        if texture.shared_ownership.is_none() {
+            return Err(SharedTextureError::NotShared(texture_id));
+        }
+
+        Ok((device, texture))
+    }
+
     #[cfg(webgl)]
     pub fn queue_copy_external_image_to_texture<A: HalApi>(
         &self,
@@ -1168,7 +1637,7 @@ impl Global {
                 .active_submission_index
                 .fetch_add(1, Ordering::Relaxed)
                 + 1;
-            let mut active_executions = Vec::new();
+            let mut active_executions = SmallVec::new();
 
             let mut used_surface_textures = track::TextureUsageScope::default();
 
@@ -1247,6 +1716,11 @@ impl Global {
                                 }
                             }
                             for texture in cmd_buf_trackers.textures.used_resources() {
+                                if !texture.is_owned() {
+                                    return Err(QueueSubmitError::SharedTextureNotOwned(
+                                        texture.info.id(),
+                                    ));
+                                }
                                 let should_extend = match texture.inner.get(&snatch_guard) {
                                     None => {
                                         return Err(QueueSubmitError::DestroyedTexture(
@@ -1274,19 +1748,12 @@ impl Global {
                             for texture_view in cmd_buf_trackers.views.used_resources() {
                                 texture_view.info.use_at(submit_index);
                             }
-                            {
-                                for bg in cmd_buf_trackers.bind_groups.used_resources() {
-                                    bg.info.use_at(submit_index);
-                                    // We need to update the submission indices for the contained
-                                    // state-less (!) resources as well, so that they don't get
-                                    // deleted too early if the parent bind group goes out of scope.
-                                    for view in bg.used.views.used_resources() {
-                                        view.info.use_at(submit_index);
-                                    }
-                                    for sampler in bg.used.samplers.used_resources() {
-                                        sampler.info.use_at(submit_index);
-                                    }
-                                }
+                            for bg in cmd_buf_trackers.bind_groups.used_resources() {
+                                // Bind groups can't change, so their state-less (!)
+                                // views and samplers aren't marked here. The bind group
+                                // keeps them alive, and hands its submission index down
+                                // to them once it is freed.
+                                bg.info.use_at(submit_index);
                             }
                             // assert!(cmd_buf_trackers.samplers.is_empty());
                             for compute_pipeline in
@@ -1436,42 +1903,63 @@ impl Global {
                 }
             }
 
-            let refs = pending_writes
-                .pre_submit()?
-                .into_iter()
-                .chain(
-                    active_executions
-                        .iter()
-                        .flat_map(|pool_execution| pool_execution.cmd_buffers.iter()),
-                )
-                .collect::<Vec<_>>();
+            let mut batch = queue.batch.lock();
+            let immediate = batch.batching == wgt::SubmissionBatching::Immediate;
+            let mut pending_writes_encoder = None;
+            if immediate {
+                // Submissions held back before batching was turned off go first.
+                queue.submit_batch(&mut batch, fence, &snatch_guard)?;
+
+                let refs = pending_writes
+                    .pre_submit()?
+                    .into_iter()
+                    .chain(
+                        active_executions
+                            .iter()
+                            .flat_map(|pool_execution| pool_execution.cmd_buffers.iter()),
+                    )
+                    .collect::<SmallVec<[_; 8]>>();
 
-            let mut submit_surface_textures =
-                SmallVec::<[_; 2]>::with_capacity(submit_surface_textures_owned.len());
+                let mut submit_surface_textures =
+                    SmallVec::<[_; 2]>::with_capacity(submit_surface_textures_owned.len());
 
-            for texture in &submit_surface_textures_owned {
-                submit_surface_textures.extend(match texture.inner.get(&snatch_guard) {
-                    Some(TextureInner::Surface { raw, .. }) => raw.as_ref(),
-                    _ => None,
-                });
-            }
+                for texture in &submit_surface_textures_owned {
+                    submit_surface_textures.extend(match texture.inner.get(&snatch_guard) {
+                        Some(TextureInner::Surface { raw, .. }) => raw.as_ref(),
+                        _ => None,
+                    });
+                }
 
-            unsafe {
-                queue
-                    .raw
-                    .as_ref()
-                    .unwrap()
-                    .submit(&refs, &submit_surface_textures, (fence, submit_index))
-                    .map_err(DeviceError::from)?;
+                unsafe {
+                    queue
+                        .raw
+                        .as_ref()
+                        .unwrap()
+                        .submit(&refs, &submit_surface_textures, (&mut *fence, submit_index))
+                        .map_err(DeviceError::from)?;
+                }
+            } else if pending_writes.pre_submit()?.is_some() {
+                pending_writes_encoder = Some(pending_writes.take_encoder(
+                    &device.command_allocator,
+                    device.raw(),
+                    queue.raw.as_ref().unwrap(),
+                )?);
             }
 
             profiling::scope!("cleanup");
-            if let Some(pending_execution) = pending_writes.post_submit(
-                &device.command_allocator,
-                device.raw(),
-                queue.raw.as_ref().unwrap(),
-            ) {
-                active_executions.push(pending_execution);
+            let last_done_index =
+                unsafe { device.raw().get_fence_value(fence) }.map_err(DeviceError::from)?;
+            pending_writes
+                .staging_ring
+                .submitted(device.raw(), submit_index, last_done_index);
+            if immediate {
+                if let Some(pending_execution) = pending_writes.post_submit(
+                    &device.command_allocator,
+                    device.raw(),
+                    queue.raw.as_ref().unwrap(),
+                ) {
+                    active_executions.push(pending_execution);
+                }
             }
 
             // this will register the new submission to the life time tracker
@@ -1479,12 +1967,29 @@ impl Global {
             device.lock_life().track_submission(
                 submit_index,
                 pending_write_resources.drain(..),
-                active_executions,
+                if immediate {
+                    mem::take(&mut active_executions)
+                } else {
+                    SmallVec::new()
+                },
             );
 
             // pending_write_resources has been drained, so it's empty, but we
             // want to retain its heap allocation.
             pending_writes.temp_resources = pending_write_resources;
+
+            if !immediate {
+                batch.submissions.push(BatchedSubmission {
+                    index: submit_index,
+                    pending_writes: pending_writes_encoder,
+                    executions: active_executions,
+                });
+                batch.surface_textures.extend(submit_surface_textures_owned);
+                if batch.is_full() {
+                    queue.submit_batch(&mut batch, fence, &snatch_guard)?;
+                }
+            }
+            drop(batch);
             drop(pending_writes_guard);
 
             // This will schedule destruction of all resources that are no longer needed
@@ -1512,6 +2017,44 @@ impl Global {
         })
     }
 
+    /// Set how the command buffers submitted to the queue are sent to the
+    /// backend.
+    ///
+    /// The submissions held back so far are sent right away.
+    pub fn queue_set_submission_batching<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        batching: wgt::SubmissionBatching,
+    ) -> Result<(), QueueSubmitError> {
+        api_log!("Queue::set_submission_batching {queue_id:?} {batching:?}");
+
+        let hub = A::hub(self);
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+        let device = queue.device.as_ref().unwrap();
+        queue.flush_batch(&device.snatchable_lock.read())?;
+        queue.batch.lock().batching = batching;
+        Ok(())
+    }
+
+    /// Send the submissions held back by [`wgt::SubmissionBatching::Batched`]
+    /// to the backend.
+    pub fn queue_flush<A: HalApi>(&self, queue_id: QueueId) -> Result<(), QueueSubmitError> {
+        profiling::scope!("Queue::flush");
+        api_log!("Queue::flush {queue_id:?}");
+
+        let hub = A::hub(self);
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+        let device = queue.device.as_ref().unwrap();
+        queue.flush_batch(&device.snatchable_lock.read())?;
+        Ok(())
+    }
+
     pub fn queue_get_timestamp_period<A: HalApi>(
         &self,
         queue_id: QueueId,
@@ -1528,6 +2071,7 @@ impl Global {
         queue_id: QueueId,
         closure: SubmittedWorkDoneClosure,
     ) -> Result<(), InvalidQueue> {
+        profiling::scope!("Queue::on_submitted_work_done");
         api_log!("Queue::on_submitted_work_done {queue_id:?}");
 
         //TODO: flush pending writes
@@ -5,6 +5,7 @@ use crate::{
     command, conv,
     device::{
         bgl,
+        deferred_drop::DropThread,
         life::{LifetimeTracker, WaitIdleError},
         queue::PendingWrites,
         AttachmentData, DeviceLostInvocation, MissingDownlevelFlags, MissingFeatures,
@@ -136,9 +137,22 @@ pub struct Device<A: HalApi> {
     pub(crate) instance_flags: wgt::InstanceFlags,
     pub(crate) pending_writes: Mutex<Option<PendingWrites<A>>>,
     pub(crate) deferred_destroy: Mutex<Vec<DeferredDestroy<A>>>,
+    /// Drops the resources of completed submissions.
+    drop_thread: DropThread<ResourceMaps<A>>,
     #[cfg(feature = "trace")]
     pub(crate) trace: Mutex<Option<trace::Trace>>,
+    /// Where to cache the translation of shader modules, if anywhere.
+    #[cfg(feature = "shader_cache")]
+    pub(crate) shader_cache: Mutex<Option<Arc<dyn hal::ShaderCache>>>,
     pub(crate) usage_scopes: UsageScopePool<A>,
+    /// The fences of other devices this device's queue waited on, imported
+    /// by [`Global::queue_wait_for_submission`].
+    ///
+    /// They're kept until this device is dropped, since the device doesn't
+    /// know when the submissions waiting on them are done.
+    ///
+    /// [`Global::queue_wait_for_submission`]: crate::global::Global::queue_wait_for_submission
+    pub(crate) imported_fences: Mutex<Vec<(Weak<Device<A>>, A::Fence)>>,
 
     /// Temporary storage, cleared at the start of every call,
     /// retained only to save allocations.
@@ -164,6 +178,7 @@ impl<A: HalApi> std::fmt::Debug for Device<A> {
 impl<A: HalApi> Drop for Device<A> {
     fn drop(&mut self) {
         resource_log!("Destroy raw Device {:?}", self.info.label());
+        self.drop_thread.finish();
         let raw = self.raw.take().unwrap();
         let pending_writes = self.pending_writes.lock().take().unwrap();
         pending_writes.dispose(&raw);
@@ -171,6 +186,9 @@ impl<A: HalApi> Drop for Device<A> {
         unsafe {
             raw.destroy_buffer(self.zero_buffer.take().unwrap());
             raw.destroy_fence(self.fence.write().take().unwrap());
+            for (_, fence) in self.imported_fences.lock().drain(..) {
+                raw.destroy_fence(fence);
+            }
             let queue = self.queue_to_drop.take().unwrap();
             raw.exit(queue);
         }
@@ -229,7 +247,8 @@ impl<A: HalApi> Device<A> {
         let pending_encoder = command_allocator
             .acquire_encoder(&raw_device, raw_queue)
             .map_err(|_| CreateDeviceError::OutOfMemory)?;
-        let mut pending_writes = PendingWrites::<A>::new(pending_encoder);
+        let mut pending_writes =
+            PendingWrites::<A>::new(pending_encoder, &adapter.raw.capabilities);
 
         // Create zeroed buffer used for texture clears.
         let zero_buffer = unsafe {
@@ -248,6 +267,7 @@ impl<A: HalApi> Device<A> {
                 .command_encoder
                 .transition_buffers(iter::once(hal::BufferBarrier {
                     buffer: &zero_buffer,
+                    range: None,
                     usage: hal::BufferUses::empty()..hal::BufferUses::COPY_DST,
                 }));
             pending_writes
@@ -257,6 +277,7 @@ impl<A: HalApi> Device<A> {
                 .command_encoder
                 .transition_buffers(iter::once(hal::BufferBarrier {
                     buffer: &zero_buffer,
+                    range: None,
                     usage: hal::BufferUses::COPY_DST..hal::BufferUses::COPY_SRC,
                 }));
         }
@@ -305,7 +326,11 @@ impl<A: HalApi> Device<A> {
             instance_flags,
             pending_writes: Mutex::new(rank::DEVICE_PENDING_WRITES, Some(pending_writes)),
             deferred_destroy: Mutex::new(rank::DEVICE_DEFERRED_DESTROY, Vec::new()),
+            drop_thread: DropThread::new(),
+            #[cfg(feature = "shader_cache")]
+            shader_cache: Mutex::new(rank::DEVICE_SHADER_CACHE, None),
             usage_scopes: Mutex::new(rank::DEVICE_USAGE_SCOPES, Default::default()),
+            imported_fences: Mutex::new(rank::DEVICE_IMPORTED_FENCES, Vec::new()),
         })
     }
 
@@ -376,6 +401,15 @@ impl<A: HalApi> Device<A> {
         self.queue.get().as_ref()?.upgrade()
     }
 
+    /// Sends the submissions held back by the queue to the backend, so that
+    /// they can be waited on.
+    pub(crate) fn flush_queue_batch(&self, snatch_guard: &SnatchGuard) -> Result<(), DeviceError> {
+        match self.get_queue() {
+            Some(queue) => queue.flush_batch(snatch_guard),
+            None => Ok(()),
+        }
+    }
+
     pub fn set_queue(&self, queue: Arc<Queue<A>>) {
         assert!(self.queue.set(Arc::downgrade(&queue)).is_ok());
     }
@@ -429,8 +463,11 @@ impl<A: HalApi> Device<A> {
         };
 
         let mut life_tracker = self.lock_life();
-        let submission_closures =
-            life_tracker.triage_submissions(last_done_index, &self.command_allocator);
+        let submission_closures = life_tracker.triage_submissions(
+            last_done_index,
+            &self.command_allocator,
+            &self.drop_thread,
+        );
 
         life_tracker.triage_suspected(&self.trackers);
 
@@ -616,6 +653,18 @@ impl<A: HalApi> Device<A> {
             usage |= hal::BufferUses::COPY_DST;
         }
 
+        // On unified memory, mappable buffers are as fast as others, so
+        // buffers the host writes to are made mappable to skip staging copies.
+        let host_writable = !transient
+            && self.adapter.raw.capabilities.unified_memory
+            && !desc
+                .usage
+                .intersects(wgt::BufferUsages::MAP_READ | wgt::BufferUsages::MAP_WRITE)
+            && (desc.mapped_at_creation || desc.usage.contains(wgt::BufferUsages::COPY_DST));
+        if host_writable {
+            usage |= hal::BufferUses::MAP_WRITE;
+        }
+
         let actual_size = if desc.size == 0 {
             wgt::COPY_BUFFER_ALIGNMENT
         } else if desc.usage.contains(wgt::BufferUsages::VERTEX) {
@@ -648,6 +697,7 @@ impl<A: HalApi> Device<A> {
             device: self.clone(),
             usage: desc.usage,
             size: desc.size,
+            host_writable,
             initialization_status: RwLock::new(
                 rank::BUFFER_INITIALIZATION_STATUS,
                 BufferInitTracker::new(aligned_size),
@@ -693,6 +743,7 @@ impl<A: HalApi> Device<A> {
             clear_mode: RwLock::new(rank::TEXTURE_CLEAR_MODE, clear_mode),
             views: Mutex::new(rank::TEXTURE_VIEWS, Vec::new()),
             bind_groups: Mutex::new(rank::TEXTURE_BIND_GROUPS, Vec::new()),
+            shared_ownership: None,
         }
     }
 
@@ -708,6 +759,7 @@ impl<A: HalApi> Device<A> {
             device: self.clone(),
             usage: desc.usage,
             size: desc.size,
+            host_writable: false,
             initialization_status: RwLock::new(
                 rank::BUFFER_INITIALIZATION_STATUS,
                 BufferInitTracker::new(0),
@@ -722,17 +774,24 @@ impl<A: HalApi> Device<A> {
         }
     }
 
+    /// Creates a texture, shared with other processes if `shared` is set.
     pub(crate) fn create_texture(
         self: &Arc<Self>,
         adapter: &Adapter<A>,
         desc: &resource::TextureDescriptor,
+        shared: Option<resource::SharedTextureSource>,
     ) -> Result<Texture<A>, resource::CreateTextureError> {
-        use resource::{CreateTextureError, TextureDimensionError};
+        use resource::{CreateTextureError, SharedTextureSource, TextureDimensionError};
 
         if desc.usage.is_empty() || desc.usage.contains_invalid_bits() {
             return Err(CreateTextureError::InvalidUsage(desc.usage));
         }
 
+        if shared.is_some() {
+            self.require_features(wgt::Features::SHARED_TEXTURES)
+                .map_err(CreateTextureError::MissingSharedTexturesFeature)?;
+        }
+
         conv::check_texture_dimension_size(
             desc.dimension,
             desc.size,
@@ -907,7 +966,10 @@ impl<A: HalApi> Device<A> {
             self.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)?;
         }
 
-        let hal_usage = conv::map_texture_usage_for_texture(desc, &format_features);
+        let mut hal_usage = conv::map_texture_usage_for_texture(desc, &format_features);
+        if shared.is_some() {
+            hal_usage |= resource::SHARED_TEXTURE_RELEASED_USE;
+        }
 
         let hal_desc = hal::TextureDescriptor {
             label: desc.label.to_hal(self.instance_flags),
@@ -921,12 +983,21 @@ impl<A: HalApi> Device<A> {
             view_formats: hal_view_formats,
         };
 
+        let shared_ownership = shared
+            .as_ref()
+            .map(|source| AtomicBool::new(matches!(*source, SharedTextureSource::Created)));
+        let hal_device = self.raw();
         let raw_texture = unsafe {
-            self.raw
-                .as_ref()
-                .unwrap()
-                .create_texture(&hal_desc)
-                .map_err(DeviceError::from)?
+            match shared {
+                None => hal_device.create_texture(&hal_desc),
+                Some(SharedTextureSource::Created) => {
+                    A::create_shared_texture(hal_device, &hal_desc)
+                }
+                Some(SharedTextureSource::Imported(handle)) => {
+                    A::import_texture(hal_device, handle, &hal_desc)
+                }
+            }
+            .map_err(DeviceError::from)?
         };
 
         let clear_mode = if hal_usage
@@ -995,6 +1066,7 @@ impl<A: HalApi> Device<A> {
         let mut texture =
             self.create_texture_from_hal(raw_texture, hal_usage, desc, format_features, clear_mode);
         texture.hal_usage = hal_usage;
+        texture.shared_ownership = shared_ownership;
         Ok(texture)
     }
 
@@ -1415,6 +1487,44 @@ impl<A: HalApi> Device<A> {
         desc: &pipeline::ShaderModuleDescriptor<'a>,
         source: pipeline::ShaderModuleSource<'a>,
     ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
+        // Cached modules don't keep their spans, so leave the cache alone when
+        // debugging to keep the source mapping of the generated shaders.
+        #[cfg(feature = "shader_cache")]
+        let cache = self.shader_cache.lock().clone().and_then(|cache| {
+            if self.instance_flags.contains(wgt::InstanceFlags::DEBUG) {
+                return None;
+            }
+            let module_key =
+                super::shader_cache::module_key(&source, self.features, self.downlevel.flags)?;
+            Some(hal::ShaderCacheEntry { cache, module_key })
+        });
+        #[cfg(not(feature = "shader_cache"))]
+        let cache = None;
+
+        #[cfg(feature = "shader_cache")]
+        if let Some((module, source)) = cache.as_ref().and_then(super::shader_cache::load_module) {
+            // Anything could have written the entry, so it's validated like
+            // any other module, and parsed again if it isn't valid.
+            match self.validate_shader_module(desc, &module, &source) {
+                Ok(info) => {
+                    self.check_shader_bind_groups(&module)?;
+                    return self.create_shader_module_from_ir(
+                        desc,
+                        Cow::Owned(module),
+                        info,
+                        source,
+                        cache,
+                    );
+                }
+                Err(err) => log::warn!("Ignoring invalid shader cache entry: {err}"),
+            }
+        }
+
+        // The files spliced into a `WgslWithIncludes` source, used to locate
+        // validation errors.
+        #[cfg(feature = "wgsl")]
+        let mut includes = None;
+
         let (module, source) = match source {
             #[cfg(feature = "wgsl")]
             pipeline::ShaderModuleSource::Wgsl(code) => {
@@ -1428,11 +1538,32 @@ impl<A: HalApi> Device<A> {
                 })?;
                 (Cow::Owned(module), code.into_owned())
             }
+            #[cfg(feature = "wgsl")]
+            pipeline::ShaderModuleSource::WgslWithIncludes {
+                source: code,
+                resolve,
+            } => {
+                profiling::scope!("naga::front::wgsl::parse_str_with_includes");
+                let (module, expanded) = naga::front::wgsl::parse_str_with_includes(
+                    "wgsl", &code, resolve,
+                )
+                .map_err(|err| {
+                    pipeline::CreateShaderModuleError::Parsing(naga::error::ShaderError {
+                        source: err.file.source,
+                        label: desc.label.as_ref().map(|l| l.to_string()),
+                        inner: Box::new(err.error),
+                    })
+                })?;
+                // Spans in the module refer to the expanded source.
+                let source = expanded.source().to_string();
+                includes = Some(expanded);
+                (Cow::Owned(module), source)
+            }
             #[cfg(feature = "spirv")]
             pipeline::ShaderModuleSource::SpirV(spv, options) => {
                 let parser = naga::front::spv::Frontend::new(spv.iter().cloned(), &options);
                 profiling::scope!("naga::front::spv::Frontend");
-                let module = parser.parse().map_err(|inner| {
+                let module = parser.parse_with_span().map_err(|inner| {
                     pipeline::CreateShaderModuleError::ParsingSpirV(naga::error::ShaderError {
                         source: String::new(),
                         label: desc.label.as_ref().map(|l| l.to_string()),
@@ -1454,9 +1585,77 @@ impl<A: HalApi> Device<A> {
                 })?;
                 (Cow::Owned(module), code.into_owned())
             }
+            #[cfg(feature = "hlsl")]
+            pipeline::ShaderModuleSource::Hlsl(code, options) => {
+                let mut parser = naga::front::hlsl::Frontend::default();
+                profiling::scope!("naga::front::hlsl::Frontend.parse");
+                let module = parser.parse(&options, &code).map_err(|inner| {
+                    pipeline::CreateShaderModuleError::ParsingHlsl(naga::error::ShaderError {
+                        source: code.to_string(),
+                        label: desc.label.as_ref().map(|l| l.to_string()),
+                        inner: Box::new(inner),
+                    })
+                })?;
+                (Cow::Owned(module), code.into_owned())
+            }
             pipeline::ShaderModuleSource::Naga(module) => (module, String::new()),
+            #[cfg(feature = "precompiled")]
+            pipeline::ShaderModuleSource::Precompiled(bytes) => {
+                profiling::scope!("naga::precompiled::read");
+                let precompiled = naga::precompiled::read(&bytes)?;
+                (Cow::Owned(precompiled.module), String::new())
+            }
             pipeline::ShaderModuleSource::Dummy(_) => panic!("found `ShaderModuleSource::Dummy`"),
         };
+        self.check_shader_bind_groups(&module)?;
+
+        let info = self.validate_shader_module(desc, &module, &source);
+        // Like parse errors, validation errors are reported against the file
+        // containing the offending code.
+        #[cfg(feature = "wgsl")]
+        let info = info.map_err(|err| match (err, includes.as_ref()) {
+            (pipeline::CreateShaderModuleError::Validation(err), Some(expanded)) => {
+                let (file, inner) = expanded.locate_spans(*err.inner);
+                pipeline::CreateShaderModuleError::Validation(naga::error::ShaderError {
+                    source: expanded.files()[file].source.clone(),
+                    label: err.label,
+                    inner: Box::new(inner),
+                })
+            }
+            (err, _) => err,
+        });
+        let info = info?;
+
+        #[cfg(feature = "shader_cache")]
+        if let Some(ref cache) = cache {
+            super::shader_cache::store_module(cache, &module, &source);
+        }
+
+        self.create_shader_module_from_ir(desc, module, info, source, cache)
+    }
+
+    fn validate_shader_module(
+        self: &Arc<Self>,
+        desc: &pipeline::ShaderModuleDescriptor,
+        module: &naga::Module,
+        source: &str,
+    ) -> Result<naga::valid::ModuleInfo, pipeline::CreateShaderModuleError> {
+        profiling::scope!("naga::validate");
+        self.create_validator(validation::shader_validation_flags())
+            .validate(module)
+            .map_err(|inner| {
+                pipeline::CreateShaderModuleError::Validation(naga::error::ShaderError {
+                    source: source.to_string(),
+                    label: desc.label.as_ref().map(|l| l.to_string()),
+                    inner: Box::new(inner),
+                })
+            })
+    }
+
+    fn check_shader_bind_groups(
+        &self,
+        module: &naga::Module,
+    ) -> Result<(), pipeline::CreateShaderModuleError> {
         for (_, var) in module.global_variables.iter() {
             match var.binding {
                 Some(ref br) if br.group >= self.limits.max_bind_groups => {
@@ -1469,8 +1668,18 @@ impl<A: HalApi> Device<A> {
                 _ => continue,
             };
         }
+        Ok(())
+    }
 
-        profiling::scope!("naga::validate");
+    /// Create a shader module from a parsed and validated naga module.
+    fn create_shader_module_from_ir(
+        self: &Arc<Self>,
+        desc: &pipeline::ShaderModuleDescriptor,
+        module: Cow<'static, naga::Module>,
+        info: naga::valid::ModuleInfo,
+        source: String,
+        cache: Option<hal::ShaderCacheEntry>,
+    ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
         let debug_source =
             if self.instance_flags.contains(wgt::InstanceFlags::DEBUG) && !source.is_empty() {
                 Some(hal::DebugSource {
@@ -1485,23 +1694,13 @@ impl<A: HalApi> Device<A> {
                 None
             };
 
-        let info = self
-            .create_validator(naga::valid::ValidationFlags::all())
-            .validate(&module)
-            .map_err(|inner| {
-                pipeline::CreateShaderModuleError::Validation(naga::error::ShaderError {
-                    source,
-                    label: desc.label.as_ref().map(|l| l.to_string()),
-                    inner: Box::new(inner),
-                })
-            })?;
-
         let interface =
             validation::Interface::new(&module, &info, self.limits.clone(), self.features);
         let hal_shader = hal::ShaderInput::Naga(hal::NagaShader {
             module,
             info,
             debug_source,
+            cache,
         });
         let hal_desc = hal::ShaderModuleDescriptor {
             label: desc.label.to_hal(self.instance_flags),
@@ -1618,6 +1817,37 @@ impl<A: HalApi> Device<A> {
             Caps::SUBGROUP_BARRIER,
             self.features.intersects(wgt::Features::SUBGROUP_BARRIER),
         );
+        caps.set(
+            Caps::TEXTURE_ATOMIC,
+            self.features.contains(wgt::Features::TEXTURE_ATOMIC),
+        );
+        caps.set(
+            Caps::UNRESTRICTED_POINTER_PARAMETERS,
+            self.features
+                .contains(wgt::Features::UNRESTRICTED_POINTER_PARAMETERS),
+        );
+        caps.set(
+            Caps::CLIP_DISTANCE,
+            self.features.contains(wgt::Features::CLIP_DISTANCES),
+        );
+        caps.set(
+            Caps::CULL_DISTANCE,
+            self.features.contains(wgt::Features::CULL_DISTANCES),
+        );
+        caps.set(
+            Caps::SHADER_BARYCENTRICS,
+            self.features.contains(wgt::Features::SHADER_BARYCENTRICS),
+        );
+        caps.set(
+            Caps::SPARSE_RESIDENCY,
+            self.features
+                .contains(wgt::Features::SHADER_RESOURCE_RESIDENCY),
+        );
+        caps.set(
+            Caps::COMPUTE_DERIVATIVES,
+            self.features
+                .contains(wgt::Features::COMPUTE_SHADER_DERIVATIVES),
+        );
 
         let mut subgroup_stages = naga::valid::ShaderStages::empty();
         subgroup_stages.set(
@@ -1631,7 +1861,13 @@ impl<A: HalApi> Device<A> {
 
         let subgroup_operations = if caps.contains(Caps::SUBGROUP) {
             use naga::valid::SubgroupOperationSet as S;
-            S::BASIC | S::VOTE | S::ARITHMETIC | S::BALLOT | S::SHUFFLE | S::SHUFFLE_RELATIVE
+            let mut operations =
+                S::BASIC | S::VOTE | S::ARITHMETIC | S::BALLOT | S::SHUFFLE | S::SHUFFLE_RELATIVE;
+            operations.set(
+                S::CLUSTERED | S::QUAD_FRAGMENT_COMPUTE,
+                self.features.contains(wgt::Features::SUBGROUP_EXTENDED),
+            );
+            operations
         } else {
             naga::valid::SubgroupOperationSet::empty()
         };
@@ -1648,11 +1884,54 @@ impl<A: HalApi> Device<A> {
         source: &'a [u32],
     ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
         self.require_features(wgt::Features::SPIRV_SHADER_PASSTHROUGH)?;
+        unsafe { self.create_shader_module_passthrough(desc, hal::ShaderInput::SpirV(source)) }
+    }
+
+    #[allow(unused_unsafe)]
+    pub(crate) unsafe fn create_shader_module_dxil<'a>(
+        self: &Arc<Self>,
+        desc: &pipeline::ShaderModuleDescriptor<'a>,
+        source: &'a [u8],
+    ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
+        self.require_features(wgt::Features::DXIL_SHADER_PASSTHROUGH)?;
+        unsafe { self.create_shader_module_passthrough(desc, hal::ShaderInput::Dxil(source)) }
+    }
+
+    #[allow(unused_unsafe)]
+    pub(crate) unsafe fn create_shader_module_msl(
+        self: &Arc<Self>,
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: &pipeline::MslSource,
+        workgroup_size: [u32; 3],
+    ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
+        self.require_features(wgt::Features::MSL_SHADER_PASSTHROUGH)?;
+        let source = match *source {
+            pipeline::MslSource::Source(ref source) => hal::MslSource::Source(source),
+            pipeline::MslSource::Library(ref data) => hal::MslSource::Library(data),
+        };
+        unsafe {
+            self.create_shader_module_passthrough(
+                desc,
+                hal::ShaderInput::Msl {
+                    source,
+                    workgroup_size,
+                },
+            )
+        }
+    }
+
+    /// Creates a shader module from backend-specific code that isn't
+    /// validated, so the module has no interface to check pipelines against.
+    #[allow(unused_unsafe)]
+    unsafe fn create_shader_module_passthrough(
+        self: &Arc<Self>,
+        desc: &pipeline::ShaderModuleDescriptor,
+        hal_shader: hal::ShaderInput,
+    ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
         let hal_desc = hal::ShaderModuleDescriptor {
             label: desc.label.to_hal(self.instance_flags),
             runtime_checks: desc.shader_bound_checks.runtime_checks(),
         };
-        let hal_shader = hal::ShaderInput::SpirV(source);
         let raw = match unsafe {
             self.raw
                 .as_ref()
@@ -1801,7 +2080,7 @@ impl<A: HalApi> Device<A> {
                 Bt::StorageTexture {
                     access,
                     view_dimension,
-                    format: _,
+                    format,
                 } => {
                     match view_dimension {
                         TextureViewDimension::Cube | TextureViewDimension::CubeArray => {
@@ -1824,6 +2103,19 @@ impl<A: HalApi> Device<A> {
                                 error: BindGroupLayoutEntryError::StorageTextureReadWrite,
                             });
                         }
+                        wgt::StorageTextureAccess::Atomic
+                            if !matches!(
+                                format,
+                                wgt::TextureFormat::R32Uint | wgt::TextureFormat::R32Sint
+                            ) =>
+                        {
+                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
+                                binding: entry.binding,
+                                error: BindGroupLayoutEntryError::StorageTextureAtomicFormat(
+                                    format,
+                                ),
+                            });
+                        }
                         _ => (),
                     }
                     (
@@ -1843,6 +2135,10 @@ impl<A: HalApi> Device<A> {
                                     wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
                                 WritableStorage::Yes
                             }
+                            wgt::StorageTextureAccess::Atomic => {
+                                required_features |= wgt::Features::TEXTURE_ATOMIC;
+                                WritableStorage::Yes
+                            }
                         },
                     )
                 }
@@ -1995,10 +2291,9 @@ impl<A: HalApi> Device<A> {
             ));
         }
 
-        let buffer = used
-            .buffers
-            .add_single(storage, bb.buffer_id, internal_use)
-            .ok_or(Error::InvalidBuffer(bb.buffer_id))?;
+        let buffer = storage
+            .get(bb.buffer_id)
+            .map_err(|_| Error::InvalidBuffer(bb.buffer_id))?;
 
         if buffer.device.as_info().id() != device_id {
             return Err(DeviceError::WrongDevice.into());
@@ -2042,6 +2337,16 @@ impl<A: HalApi> Device<A> {
             });
         }
 
+        // Dynamic offsets can move the binding anywhere in the buffer, and
+        // the bind group's uses are tracked before the offsets are known, so
+        // dynamic bindings are tracked as using the whole buffer.
+        let used_range = if dynamic {
+            0..buffer.size
+        } else {
+            bb.offset..bind_end
+        };
+        used.buffers.add_single(buffer, used_range, internal_use);
+
         // Record binding info for validating dynamic offsets
         if dynamic {
             dynamic_binding_info.push(binding_model::BindGroupDynamicBindingData {
@@ -2223,7 +2528,7 @@ impl<A: HalApi> Device<A> {
                         &mut dynamic_binding_info,
                         &mut late_buffer_binding_sizes,
                         &mut used,
-                        &*buffer_guard,
+                        &buffer_guard,
                         &self.limits,
                         self.as_info().id(),
                         &snatch_guard,
@@ -2247,7 +2552,7 @@ impl<A: HalApi> Device<A> {
                             &mut dynamic_binding_info,
                             &mut late_buffer_binding_sizes,
                             &mut used,
-                            &*buffer_guard,
+                            &buffer_guard,
                             &self.limits,
                             self.as_info().id(),
                             &snatch_guard,
@@ -2549,7 +2854,7 @@ impl<A: HalApi> Device<A> {
                         }
                         hal::TextureUses::STORAGE_READ
                     }
-                    wgt::StorageTextureAccess::ReadWrite => {
+                    wgt::StorageTextureAccess::ReadWrite | wgt::StorageTextureAccess::Atomic => {
                         if !view
                             .format_features
                             .flags
@@ -2777,6 +3082,7 @@ impl<A: HalApi> Device<A> {
             None => validation::BindingLayoutSource::new_derived(&self.limits),
         };
         let mut shader_binding_sizes = FastHashMap::default();
+        let mut binding_names = validation::BindingNames::default();
         let io = validation::StageIo::default();
 
         let final_entry_point_name;
@@ -2798,6 +3104,7 @@ impl<A: HalApi> Device<A> {
                     io,
                     None,
                 )?;
+                interface.collect_binding_names(&final_entry_point_name, stage, &mut binding_names);
             }
         }
 
@@ -2817,6 +3124,8 @@ impl<A: HalApi> Device<A> {
         let late_sized_buffer_groups =
             Device::make_late_sized_buffer_groups(&shader_binding_sizes, &pipeline_layout);
 
+        let cache = self.pipeline_cache_for(desc.cache, hub)?;
+
         let pipeline_desc = hal::ComputePipelineDescriptor {
             label: desc.label.to_hal(self.instance_flags),
             layout: pipeline_layout.raw(),
@@ -2826,6 +3135,7 @@ impl<A: HalApi> Device<A> {
                 constants: desc.stage.constants.as_ref(),
                 zero_initialize_workgroup_memory: desc.stage.zero_initialize_workgroup_memory,
             },
+            cache: cache.as_ref().map(|cache| cache.raw()),
         };
 
         let raw = unsafe {
@@ -2852,6 +3162,7 @@ impl<A: HalApi> Device<A> {
             device: self.clone(),
             _shader_module: shader_module,
             late_sized_buffer_groups,
+            binding_names,
             info: ResourceInfo::new(
                 desc.label.borrow_or_default(),
                 Some(self.tracker_indices.compute_pipelines.clone()),
@@ -2882,6 +3193,7 @@ impl<A: HalApi> Device<A> {
         }
 
         let mut shader_binding_sizes = FastHashMap::default();
+        let mut binding_names = validation::BindingNames::default();
 
         let num_attachments = desc.fragment.as_ref().map(|f| f.targets.len()).unwrap_or(0);
         let max_attachments = self.limits.max_color_attachments as usize;
@@ -3234,6 +3546,11 @@ impl<A: HalApi> Device<A> {
                     )
                     .map_err(stage_err)?;
                 validated_stages |= stage;
+                interface.collect_binding_names(
+                    &vertex_entry_point_name,
+                    stage,
+                    &mut binding_names,
+                );
             }
 
             hal::ProgrammableStage {
@@ -3289,6 +3606,11 @@ impl<A: HalApi> Device<A> {
                 }
 
                 if let Some(ref interface) = shader_module.interface {
+                    interface.collect_binding_names(
+                        &fragment_entry_point_name,
+                        stage,
+                        &mut binding_names,
+                    );
                     shader_expects_dual_source_blending = interface
                         .fragment_uses_dual_source_blending(&fragment_entry_point_name)
                         .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
@@ -3393,6 +3715,8 @@ impl<A: HalApi> Device<A> {
         let late_sized_buffer_groups =
             Device::make_late_sized_buffer_groups(&shader_binding_sizes, &pipeline_layout);
 
+        let cache = self.pipeline_cache_for(desc.cache, hub)?;
+
         let pipeline_desc = hal::RenderPipelineDescriptor {
             label: desc.label.to_hal(self.instance_flags),
             layout: pipeline_layout.raw(),
@@ -3404,6 +3728,7 @@ impl<A: HalApi> Device<A> {
             fragment_stage,
             color_targets,
             multiview: desc.multiview,
+            cache: cache.as_ref().map(|cache| cache.raw()),
         };
         let raw = unsafe {
             self.raw
@@ -3476,6 +3801,7 @@ impl<A: HalApi> Device<A> {
             strip_index_format: desc.primitive.strip_index_format,
             vertex_steps,
             late_sized_buffer_groups,
+            binding_names,
             info: ResourceInfo::new(
                 desc.label.borrow_or_default(),
                 Some(self.tracker_indices.render_pipelines.clone()),
@@ -3530,6 +3856,7 @@ impl<A: HalApi> Device<A> {
         &self,
         submission_index: SubmissionIndex,
     ) -> Result<(), WaitIdleError> {
+        self.flush_queue_batch(&self.snatchable_lock.read())?;
         let guard = self.fence.read();
         let fence = guard.as_ref().unwrap();
         let last_done_index = unsafe {
@@ -3549,9 +3876,11 @@ impl<A: HalApi> Device<A> {
                     .map_err(DeviceError::from)?
             };
             drop(guard);
-            let closures = self
-                .lock_life()
-                .triage_submissions(submission_index, &self.command_allocator);
+            let closures = self.lock_life().triage_submissions(
+                submission_index,
+                &self.command_allocator,
+                &self.drop_thread,
+            );
             assert!(
                 closures.is_empty(),
                 "wait_for_submit is not expected to work with closures"
@@ -3560,6 +3889,74 @@ impl<A: HalApi> Device<A> {
         Ok(())
     }
 
+    /// Returns the pipeline cache a pipeline is created with.
+    ///
+    /// A cache only speeds up pipeline creation, so invalid caches are
+    /// ignored rather than failing it.
+    fn pipeline_cache_for(
+        &self,
+        cache_id: Option<id::PipelineCacheId>,
+        hub: &Hub<A>,
+    ) -> Result<Option<Arc<pipeline::PipelineCache<A>>>, DeviceError> {
+        let Some(cache_id) = cache_id else {
+            return Ok(None);
+        };
+        let Ok(cache) = hub.pipeline_caches.get(cache_id) else {
+            return Ok(None);
+        };
+        if cache.device.as_info().id() != self.as_info().id() {
+            return Err(DeviceError::WrongDevice);
+        }
+        Ok(Some(cache))
+    }
+
+    /// # Safety
+    ///
+    /// The data of `desc` must have been returned by
+    /// [`Global::pipeline_cache_get_data`](crate::global::Global::pipeline_cache_get_data).
+    pub(crate) unsafe fn create_pipeline_cache(
+        self: &Arc<Self>,
+        desc: &pipeline::PipelineCacheDescriptor,
+    ) -> Result<pipeline::PipelineCache<A>, pipeline::CreatePipelineCacheError> {
+        use crate::pipeline_cache;
+
+        self.require_features(wgt::Features::PIPELINE_CACHE)?;
+
+        let validation_key = self
+            .raw()
+            .pipeline_cache_validation_key()
+            .unwrap_or_default();
+        let data = match desc.data.as_deref() {
+            Some(data) => {
+                match pipeline_cache::validate(data, &self.adapter.raw.info, validation_key) {
+                    Ok(data) => Some(data),
+                    Err(error) if desc.fallback => {
+                        log::warn!("Starting pipeline cache {:?} empty: {error}", desc.label);
+                        None
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            None => None,
+        };
+
+        let hal_desc = hal::PipelineCacheDescriptor {
+            label: desc.label.to_hal(self.instance_flags),
+            data,
+        };
+        let raw = match unsafe { self.raw().create_pipeline_cache(&hal_desc) } {
+            Ok(raw) => raw,
+            Err(hal::PipelineCacheError::Device(error)) => {
+                return Err(DeviceError::from(error).into())
+            }
+        };
+        Ok(pipeline::PipelineCache {
+            raw: Some(raw),
+            device: self.clone(),
+            info: ResourceInfo::new(desc.label.borrow_or_default(), None),
+        })
+    }
+
     pub(crate) fn create_query_set(
         self: &Arc<Self>,
         desc: &resource::QuerySetDescriptor,
@@ -3656,17 +4053,15 @@ impl<A: HalApi> Device<A> {
         unsafe {
             baked.encoder.reset_all(baked.list.into_iter());
         }
-        unsafe {
-            self.raw
-                .as_ref()
-                .unwrap()
-                .destroy_command_encoder(baked.encoder);
-        }
+        self.command_allocator.release_encoder(baked.encoder);
     }
 
     /// Wait for idle and remove resources that we can, before we die.
     pub(crate) fn prepare_to_die(&self) {
         self.pending_writes.lock().as_mut().unwrap().deactivate();
+        if let Err(error) = self.flush_queue_batch(&self.snatchable_lock.read()) {
+            log::error!("failed to send the held back submissions: {error}");
+        }
         let current_index = self.active_submission_index.load(Ordering::Relaxed);
         if let Err(error) = unsafe {
             let fence = self.fence.read();
@@ -3679,7 +4074,11 @@ impl<A: HalApi> Device<A> {
             log::error!("failed to wait for the device: {error}");
         }
         let mut life_tracker = self.lock_life();
-        let _ = life_tracker.triage_submissions(current_index, &self.command_allocator);
+        let _ = life_tracker.triage_submissions(
+            current_index,
+            &self.command_allocator,
+            &self.drop_thread,
+        );
         if let Some(device_lost_closure) = life_tracker.device_lost_closure.take() {
             // It's important to not hold the lock while calling the closure.
             drop(life_tracker);
@@ -0,0 +1,237 @@
+//! Persistent caching of shader module translation.
+//!
+//! When a device has a [`hal::ShaderCache`], creating a shader module first
+//! looks up the result of parsing its source in the cache, and the backend
+//! then does the same for its translation of the module for each pipeline.
+//! Entries are keyed by a hash of everything that went into them, so editing a
+//! shader, changing the device's features or updating `wgpu` or `naga` just
+//! results in a cache miss.
+//!
+//! The cache is outside of `wgpu`'s control, and backends rely on modules
+//! being valid: modules read from it are checksummed and validated again, and
+//! parsed from their source if either fails.
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    pipeline::ShaderModuleSource,
+    pipeline_cache::{fnv1a, FNV_OFFSET_BASIS},
+};
+
+/// A [`hal::ShaderCache`] that stores each entry as a file in a directory.
+///
+/// The directory can be shared by several processes using the same `wgpu`
+/// version, or not; entries written by other versions are never used.
+#[derive(Debug)]
+pub struct DirectoryShaderCache {
+    path: PathBuf,
+}
+
+impl DirectoryShaderCache {
+    /// Use the directory at `path` as the cache, creating it if needed.
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+}
+
+impl hal::ShaderCache for DirectoryShaderCache {
+    fn load(&self, key: &str) -> Option<Vec<u8>> {
+        fs::read(self.path.join(key)).ok()
+    }
+
+    fn store(&self, key: &str, value: &[u8]) {
+        static NEXT_TEMP: AtomicUsize = AtomicUsize::new(0);
+
+        // Write to a temporary file and move it in place, so that concurrent
+        // readers never see a partially written entry.
+        let temp = self.path.join(format!(
+            "{key}.{}-{}.tmp",
+            std::process::id(),
+            NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = fs::write(&temp, value).and_then(|()| fs::rename(&temp, self.path.join(key)));
+        if let Err(err) = result {
+            log::warn!("Failed to store shader cache entry {key}: {err}");
+            let _ = fs::remove_file(&temp);
+        }
+    }
+}
+
+/// Hashes `string` followed by a zero, so that moving bytes from one string
+/// to the next changes the hash.
+fn hash_str(hash: u64, string: &str) -> u64 {
+    fnv1a(fnv1a(hash, string.as_bytes()), &[0])
+}
+
+/// Hash everything the parsed module depends on.
+///
+/// The hash is the same on every run and every platform, so entries can be
+/// shared between processes. Returns `None` for sources that are not worth
+/// caching: naga IR is already parsed, and the contents of includes are only
+/// known once they're resolved.
+pub(crate) fn module_key(
+    source: &ShaderModuleSource,
+    features: wgt::Features,
+    downlevel_flags: wgt::DownlevelFlags,
+) -> Option<u64> {
+    let mut hash = hash_str(FNV_OFFSET_BASIS, env!("CARGO_PKG_VERSION"));
+    hash = hash_str(hash, naga::VERSION);
+    hash = fnv1a(hash, &features.bits().to_le_bytes());
+    hash = fnv1a(hash, &downlevel_flags.bits().to_le_bytes());
+    Some(match *source {
+        #[cfg(feature = "wgsl")]
+        ShaderModuleSource::Wgsl(ref code) => hash_str(hash_str(hash, "wgsl"), code),
+        #[cfg(feature = "glsl")]
+        ShaderModuleSource::Glsl(ref code, ref options) => hash_str(
+            hash_str(hash_str(hash, "glsl"), code),
+            &format!("{options:?}"),
+        ),
+        #[cfg(feature = "hlsl")]
+        ShaderModuleSource::Hlsl(ref code, ref options) => hash_str(
+            hash_str(hash_str(hash, "hlsl"), code),
+            &format!("{options:?}"),
+        ),
+        #[cfg(feature = "spirv")]
+        ShaderModuleSource::SpirV(ref spv, ref options) => {
+            let hash = spv.iter().fold(hash_str(hash, "spirv"), |hash, word| {
+                fnv1a(hash, &word.to_le_bytes())
+            });
+            hash_str(hash, &format!("{options:?}"))
+        }
+        _ => return None,
+    })
+}
+
+fn module_entry_key(entry: &hal::ShaderCacheEntry) -> String {
+    format!("naga-{:016x}", entry.module_key)
+}
+
+/// Look up a parsed module, and the source its spans refer to.
+///
+/// The module still has to be validated.
+pub(crate) fn load_module(entry: &hal::ShaderCacheEntry) -> Option<(naga::Module, String)> {
+    profiling::scope!("shader_cache::load_module");
+    let bytes = entry.cache.load(&module_entry_key(entry))?;
+    // Entries start with a checksum of the rest.
+    let checksum = bytes.get(..8)?;
+    let data = &bytes[8..];
+    if checksum != fnv1a(FNV_OFFSET_BASIS, data).to_le_bytes() {
+        log::warn!("Ignoring corrupted shader cache entry");
+        return None;
+    }
+    match bincode::deserialize(data) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!("Ignoring corrupted shader cache entry: {err}");
+            None
+        }
+    }
+}
+
+pub(crate) fn store_module(entry: &hal::ShaderCacheEntry, module: &naga::Module, source: &str) {
+    profiling::scope!("shader_cache::store_module");
+    match bincode::serialize(&(module, source)) {
+        Ok(data) => {
+            let mut bytes = fnv1a(FNV_OFFSET_BASIS, &data).to_le_bytes().to_vec();
+            bytes.extend_from_slice(&data);
+            entry.cache.store(&module_entry_key(entry), &bytes)
+        }
+        Err(err) => log::warn!("Failed to serialize shader module for the cache: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_module, store_module, DirectoryShaderCache};
+    use hal::ShaderCache as _;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Debug, Default)]
+    struct MemoryCache(Mutex<HashMap<String, Vec<u8>>>);
+
+    impl hal::ShaderCache for MemoryCache {
+        fn load(&self, key: &str) -> Option<Vec<u8>> {
+            self.0.lock().unwrap().get(key).cloned()
+        }
+
+        fn store(&self, key: &str, value: &[u8]) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_vec());
+        }
+    }
+
+    #[cfg(feature = "wgsl")]
+    #[test]
+    fn module_key() {
+        use crate::pipeline::ShaderModuleSource;
+        use std::borrow::Cow;
+
+        let key = |code: &'static str, features| {
+            super::module_key(
+                &ShaderModuleSource::Wgsl(Cow::Borrowed(code)),
+                features,
+                wgt::DownlevelFlags::all(),
+            )
+            .unwrap()
+        };
+        let features = wgt::Features::empty();
+        assert_eq!(key("fn a() {}", features), key("fn a() {}", features));
+        assert_ne!(key("fn a() {}", features), key("fn b() {}", features));
+        assert_ne!(
+            key("fn a() {}", features),
+            key("fn a() {}", wgt::Features::SHADER_F64)
+        );
+    }
+
+    #[test]
+    fn corrupted_module_entries() {
+        let cache = Arc::new(MemoryCache::default());
+        let entry = hal::ShaderCacheEntry {
+            cache: cache.clone(),
+            module_key: 1,
+        };
+        assert!(load_module(&entry).is_none());
+
+        store_module(&entry, &naga::Module::default(), "source");
+        let (_, source) = load_module(&entry).unwrap();
+        assert_eq!(source, "source");
+
+        // A flipped bit fails the checksum.
+        for bytes in cache.0.lock().unwrap().values_mut() {
+            *bytes.last_mut().unwrap() ^= 1;
+        }
+        assert!(load_module(&entry).is_none());
+
+        for bytes in cache.0.lock().unwrap().values_mut() {
+            bytes.truncate(4);
+        }
+        assert!(load_module(&entry).is_none());
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        let path = std::env::temp_dir().join(format!("wgpu-shader-cache-{}", std::process::id()));
+        let cache = DirectoryShaderCache::new(&path).unwrap();
+
+        assert_eq!(cache.load("spv-0"), None);
+        cache.store("spv-0", &[1, 2, 3]);
+        assert_eq!(cache.load("spv-0"), Some(vec![1, 2, 3]));
+        cache.store("spv-0", &[4]);
+        assert_eq!(cache.load("spv-0"), Some(vec![4]));
+
+        // Only the entry remains, no temporary files.
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 1);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+//! A ring of persistently mapped staging memory for queue writes.
+//!
+//! `Queue::write_buffer` and `Queue::write_texture` copy the user's data into
+//! staging memory, and record a copy from there to the destination in
+//! [`PendingWrites`]. Creating, mapping and unmapping a buffer for every
+//! write is expensive, so when the backend allows it, the data is instead
+//! written to [`StagingRing`] chunks, which are mapped once when created.
+//!
+//! Writes are suballocated from the ring's active chunk until it is full.
+//! Full chunks are used by the next queue submission, and are reused once
+//! that submission has finished executing.
+//!
+//! [`PendingWrites`]: super::queue::PendingWrites
+
+use std::{collections::VecDeque, iter, ptr::NonNull, sync::Arc};
+
+use hal::Device as _;
+
+use crate::{device::DeviceError, hal_api::HalApi, hal_label, SubmissionIndex};
+
+/// The size of the chunks the ring is made of.
+///
+/// Writes larger than this get a chunk of their own, which is destroyed once
+/// the write's submission is done.
+const CHUNK_SIZE: wgt::BufferAddress = 4 << 20;
+
+/// The number of unused chunks the ring keeps around for later writes.
+const MAX_FREE_CHUNKS: usize = 4;
+
+/// The smallest alignment of allocations in a chunk.
+///
+/// This is the largest `nonCoherentAtomSize` Vulkan allows, so that flushing
+/// one allocation never flushes part of another.
+const MIN_ALIGNMENT: wgt::BufferAddress = 256;
+
+#[derive(Debug)]
+struct StagingChunk<A: HalApi> {
+    raw: A::Buffer,
+    ptr: NonNull<u8>,
+    size: wgt::BufferAddress,
+    is_coherent: bool,
+}
+
+#[cfg(send_sync)]
+unsafe impl<A: HalApi> Send for StagingChunk<A> {}
+#[cfg(send_sync)]
+unsafe impl<A: HalApi> Sync for StagingChunk<A> {}
+
+/// Part of a [`StagingRing`] chunk, to write the data of one queue write to.
+#[derive(Debug)]
+pub(crate) struct StagingSlice<A: HalApi> {
+    chunk: Arc<StagingChunk<A>>,
+    pub offset: wgt::BufferAddress,
+    pub size: wgt::BufferAddress,
+}
+
+impl<A: HalApi> StagingSlice<A> {
+    pub fn raw(&self) -> &A::Buffer {
+        &self.chunk.raw
+    }
+
+    /// The host address of the start of the slice.
+    pub fn ptr(&self) -> *mut u8 {
+        // The slice is within the chunk, which is mapped in full.
+        unsafe { self.chunk.ptr.as_ptr().add(self.offset as usize) }
+    }
+
+    /// Make the host's writes to the slice visible to the device.
+    pub unsafe fn flush(&self, device: &A::Device) {
+        if !self.chunk.is_coherent {
+            unsafe {
+                device.flush_mapped_ranges(
+                    &self.chunk.raw,
+                    iter::once(self.offset..self.offset + self.size),
+                )
+            };
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct StagingRing<A: HalApi> {
+    /// Whether the backend allows using chunks while they are mapped. If it
+    /// doesn't, the ring is never used.
+    enabled: bool,
+    alignment: wgt::BufferAddress,
+    /// The chunk writes are suballocated from, and how much of it is used.
+    active: Option<(Arc<StagingChunk<A>>, wgt::BufferAddress)>,
+    /// Chunks that are no longer active, used by the pending writes.
+    closed: Vec<Arc<StagingChunk<A>>>,
+    /// Chunks used by submissions, with the last submission using each, in
+    /// submission order.
+    in_flight: VecDeque<(SubmissionIndex, Arc<StagingChunk<A>>)>,
+    free: Vec<Arc<StagingChunk<A>>>,
+}
+
+impl<A: HalApi> StagingRing<A> {
+    pub fn new(capabilities: &hal::Capabilities) -> Self {
+        Self {
+            enabled: capabilities.persistent_mapping,
+            alignment: capabilities
+                .alignments
+                .buffer_copy_offset
+                .get()
+                .max(MIN_ALIGNMENT),
+            active: None,
+            closed: Vec::new(),
+            in_flight: VecDeque::new(),
+            free: Vec::new(),
+        }
+    }
+
+    fn create_chunk(
+        &self,
+        device: &A::Device,
+        size: wgt::BufferAddress,
+        instance_flags: wgt::InstanceFlags,
+    ) -> Result<Arc<StagingChunk<A>>, DeviceError> {
+        profiling::scope!("StagingRing::create_chunk");
+        let desc = hal::BufferDescriptor {
+            label: hal_label(Some("(wgpu internal) Staging ring"), instance_flags),
+            size,
+            usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+            memory_flags: hal::MemoryFlags::empty(),
+        };
+        let raw = unsafe { device.create_buffer(&desc)? };
+        let mapping = match unsafe { device.map_buffer(&raw, 0..size) } {
+            Ok(mapping) => mapping,
+            Err(error) => {
+                unsafe { device.destroy_buffer(raw) };
+                return Err(error.into());
+            }
+        };
+        Ok(Arc::new(StagingChunk {
+            raw,
+            ptr: mapping.ptr,
+            size,
+            is_coherent: mapping.is_coherent,
+        }))
+    }
+
+    /// Allocate `size` bytes to stage a write in.
+    ///
+    /// Returns `None` if the ring isn't used on this backend, in which case
+    /// the write needs a [`StagingBuffer`](crate::resource::StagingBuffer).
+    pub fn allocate(
+        &mut self,
+        device: &A::Device,
+        size: wgt::BufferAddress,
+        instance_flags: wgt::InstanceFlags,
+    ) -> Result<Option<StagingSlice<A>>, DeviceError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        if size > CHUNK_SIZE {
+            let chunk_size = wgt::math::align_to(size, self.alignment);
+            let chunk = self.create_chunk(device, chunk_size, instance_flags)?;
+            self.closed.push(Arc::clone(&chunk));
+            return Ok(Some(StagingSlice {
+                chunk,
+                offset: 0,
+                size,
+            }));
+        }
+
+        if let Some((chunk, used)) = self.active.as_mut() {
+            let offset = wgt::math::align_to(*used, self.alignment);
+            if offset + size <= chunk.size {
+                *used = offset + size;
+                return Ok(Some(StagingSlice {
+                    chunk: Arc::clone(chunk),
+                    offset,
+                    size,
+                }));
+            }
+        }
+
+        let chunk = match self.free.pop() {
+            Some(chunk) => chunk,
+            None => self.create_chunk(device, CHUNK_SIZE, instance_flags)?,
+        };
+        if let Some((full, _)) = self.active.replace((Arc::clone(&chunk), size)) {
+            self.closed.push(full);
+        }
+        Ok(Some(StagingSlice {
+            chunk,
+            offset: 0,
+            size,
+        }))
+    }
+
+    /// Note that the pending writes were submitted as `submission_index`,
+    /// and reclaim the chunks of the submissions up to `last_done_index`.
+    pub fn submitted(
+        &mut self,
+        device: &A::Device,
+        submission_index: SubmissionIndex,
+        last_done_index: SubmissionIndex,
+    ) {
+        while let Some(&(index, _)) = self.in_flight.front() {
+            if index > last_done_index {
+                break;
+            }
+            let (_, chunk) = self.in_flight.pop_front().unwrap();
+            if chunk.size == CHUNK_SIZE && self.free.len() < MAX_FREE_CHUNKS {
+                self.free.push(chunk);
+            } else {
+                Self::destroy_chunk(device, chunk);
+            }
+        }
+        self.in_flight
+            .extend(self.closed.drain(..).map(|chunk| (submission_index, chunk)));
+    }
+
+    fn destroy_chunk(device: &A::Device, chunk: Arc<StagingChunk<A>>) {
+        // Slices only live as long as the write they are allocated for, so
+        // the ring holds the last reference to its chunks.
+        if let Ok(chunk) = Arc::try_unwrap(chunk) {
+            unsafe {
+                let _ = device.unmap_buffer(&chunk.raw);
+                device.destroy_buffer(chunk.raw);
+            }
+        }
+    }
+
+    /// Destroy every chunk. The device must be idle.
+    pub fn dispose(mut self, device: &A::Device) {
+        let chunks = self
+            .active
+            .take()
+            .map(|(chunk, _)| chunk)
+            .into_iter()
+            .chain(self.closed.drain(..))
+            .chain(self.in_flight.drain(..).map(|(_, chunk)| chunk))
+            .chain(self.free.drain(..));
+        for chunk in chunks {
+            Self::destroy_chunk(device, chunk);
+        }
+    }
+}
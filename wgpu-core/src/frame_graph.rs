@@ -0,0 +1,828 @@
+//! Frame graphs: the passes of a frame and the resources they use, declared
+//! up front.
+//!
+//! A [`FrameGraph`] is built by declaring the frame's resources, and then its
+//! passes in the order they will be recorded, with how each pass accesses
+//! each resource. Resources are either imported, like the surface texture,
+//! whose contents outlive the frame, or transient, which only exist for the
+//! duration of the frame and are created from the resulting plan.
+//!
+//! [`FrameGraph::compile`] then works out:
+//!
+//! - Which passes to record. Passes that write nothing that a later pass reads
+//!   or that is imported are culled, unless they are marked as having side
+//!   effects.
+//!
+//! - Which transient resources can share a physical resource. Transient
+//!   resources with the same descriptor whose lifetimes don't overlap are
+//!   aliased, and the usage of each physical resource is the union of the
+//!   usages its accesses need.
+//!
+//! - The load and store operations of render pass attachments. Attachments
+//!   are cleared on their first use, and discarded on their last one, unless
+//!   they are imported.
+//!
+//! - The usage transitions of every resource between passes. These are the
+//!   barriers wgpu-core's trackers insert when the passes are recorded, and
+//!   accesses that the trackers would reject as a usage conflict are rejected
+//!   here already.
+//!
+//! The contents of transient resources are undefined until they are first
+//! written, and a pass reading a transient resource must come after one that
+//! writes it.
+//!
+//! A frame graph is only a planner, behind the `frame_graph` feature: it
+//! allocates no memory and creates no resources, and nothing in wgpu-core uses
+//! it on its own. The caller creates a buffer or texture for each physical
+//! resource of the plan, and records the passes with the planned load and
+//! store operations. Since the physical resources are ordinary wgpu-core
+//! resources, transient resources can only share one if they have the same
+//! descriptor. There are no placed resources or aliasing barriers, so
+//! resources with different descriptors are never placed in the same memory.
+
+use std::borrow::Cow;
+
+use thiserror::Error;
+use wgt::{BufferUsages, TextureUsages};
+
+use crate::{
+    command::{LoadOp, StoreOp},
+    resource, Label,
+};
+
+/// A resource of a [`FrameGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceHandle(u32);
+
+/// A pass of a [`FrameGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PassHandle(u32);
+
+/// How a pass accesses a resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// A texture sampled or loaded from by shaders.
+    Sampled,
+    /// A texture used as a color attachment.
+    ColorAttachment,
+    /// A texture used as the depth-stencil attachment.
+    DepthStencilAttachment {
+        read_only: bool,
+    },
+    /// A storage texture or buffer that shaders only read.
+    StorageRead,
+    /// A storage texture or buffer that shaders write.
+    StorageWrite,
+    CopySrc,
+    CopyDst,
+    /// A buffer used as a vertex buffer.
+    Vertex,
+    /// A buffer used as an index buffer.
+    Index,
+    /// A buffer used as a uniform buffer.
+    Uniform,
+    /// A buffer the arguments of indirect commands are read from.
+    Indirect,
+}
+
+impl Access {
+    /// Whether the access writes to the resource.
+    pub fn is_write(self) -> bool {
+        matches!(
+            self,
+            Self::ColorAttachment
+                | Self::DepthStencilAttachment { read_only: false }
+                | Self::StorageWrite
+                | Self::CopyDst
+        )
+    }
+
+    fn texture_uses(self) -> Option<(TextureUsages, hal::TextureUses)> {
+        Some(match self {
+            Self::Sampled => (TextureUsages::TEXTURE_BINDING, hal::TextureUses::RESOURCE),
+            Self::ColorAttachment => (
+                TextureUsages::RENDER_ATTACHMENT,
+                hal::TextureUses::COLOR_TARGET,
+            ),
+            Self::DepthStencilAttachment { read_only } => (
+                TextureUsages::RENDER_ATTACHMENT,
+                if read_only {
+                    hal::TextureUses::DEPTH_STENCIL_READ
+                } else {
+                    hal::TextureUses::DEPTH_STENCIL_WRITE
+                },
+            ),
+            Self::StorageRead => (
+                TextureUsages::STORAGE_BINDING,
+                hal::TextureUses::STORAGE_READ,
+            ),
+            Self::StorageWrite => (
+                TextureUsages::STORAGE_BINDING,
+                hal::TextureUses::STORAGE_READ_WRITE,
+            ),
+            Self::CopySrc => (TextureUsages::COPY_SRC, hal::TextureUses::COPY_SRC),
+            Self::CopyDst => (TextureUsages::COPY_DST, hal::TextureUses::COPY_DST),
+            Self::Vertex | Self::Index | Self::Uniform | Self::Indirect => return None,
+        })
+    }
+
+    fn buffer_uses(self) -> Option<(BufferUsages, hal::BufferUses)> {
+        Some(match self {
+            Self::StorageRead => (BufferUsages::STORAGE, hal::BufferUses::STORAGE_READ),
+            Self::StorageWrite => (BufferUsages::STORAGE, hal::BufferUses::STORAGE_READ_WRITE),
+            Self::CopySrc => (BufferUsages::COPY_SRC, hal::BufferUses::COPY_SRC),
+            Self::CopyDst => (BufferUsages::COPY_DST, hal::BufferUses::COPY_DST),
+            Self::Vertex => (BufferUsages::VERTEX, hal::BufferUses::VERTEX),
+            Self::Index => (BufferUsages::INDEX, hal::BufferUses::INDEX),
+            Self::Uniform => (BufferUsages::UNIFORM, hal::BufferUses::UNIFORM),
+            Self::Indirect => (BufferUsages::INDIRECT, hal::BufferUses::INDIRECT),
+            Self::Sampled | Self::ColorAttachment | Self::DepthStencilAttachment { .. } => {
+                return None
+            }
+        })
+    }
+}
+
+/// The state a resource is in during a pass, combining all of the pass's
+/// accesses to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Uses {
+    Texture(hal::TextureUses),
+    Buffer(hal::BufferUses),
+}
+
+impl Uses {
+    fn union(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Texture(a), Self::Texture(b)) => Some(Self::Texture(a | b)),
+            (Self::Buffer(a), Self::Buffer(b)) => Some(Self::Buffer(a | b)),
+            _ => None,
+        }
+    }
+
+    /// The same rule as the trackers': exclusive uses can't be combined.
+    fn is_conflicting(self) -> bool {
+        match self {
+            Self::Texture(uses) => {
+                uses.intersects(hal::TextureUses::EXCLUSIVE) && !uses.bits().is_power_of_two()
+            }
+            Self::Buffer(uses) => {
+                uses.intersects(hal::BufferUses::EXCLUSIVE) && !uses.bits().is_power_of_two()
+            }
+        }
+    }
+
+    fn is_ordered(self) -> bool {
+        match self {
+            Self::Texture(uses) => hal::TextureUses::ORDERED.contains(uses),
+            Self::Buffer(uses) => hal::BufferUses::ORDERED.contains(uses),
+        }
+    }
+}
+
+/// The descriptor of a transient resource, or of the physical resource it is
+/// aliased to.
+#[derive(Clone, Debug)]
+pub enum TransientResource {
+    Texture(resource::TextureDescriptor<'static>),
+    Buffer(resource::BufferDescriptor<'static>),
+}
+
+impl TransientResource {
+    /// Whether `self` can be used as `other`, ignoring their labels and
+    /// usages.
+    fn can_alias(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Texture(a), Self::Texture(b)) => {
+                a.size == b.size
+                    && a.mip_level_count == b.mip_level_count
+                    && a.sample_count == b.sample_count
+                    && a.dimension == b.dimension
+                    && a.format == b.format
+                    && a.view_formats == b.view_formats
+            }
+            (Self::Buffer(a), Self::Buffer(b)) => a.size == b.size,
+            _ => false,
+        }
+    }
+
+    fn add_usage(&mut self, uses: Uses) {
+        match (self, uses) {
+            (Self::Texture(desc), Uses::Texture(uses)) => {
+                desc.usage |= texture_usages_of(uses);
+            }
+            (Self::Buffer(desc), Uses::Buffer(uses)) => {
+                desc.usage |= buffer_usages_of(uses);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn texture_usages_of(uses: hal::TextureUses) -> TextureUsages {
+    let mut usage = TextureUsages::empty();
+    for (hal_uses, wgt_usage) in [
+        (hal::TextureUses::RESOURCE, TextureUsages::TEXTURE_BINDING),
+        (
+            hal::TextureUses::COLOR_TARGET
+                | hal::TextureUses::DEPTH_STENCIL_READ
+                | hal::TextureUses::DEPTH_STENCIL_WRITE,
+            TextureUsages::RENDER_ATTACHMENT,
+        ),
+        (
+            hal::TextureUses::STORAGE_READ | hal::TextureUses::STORAGE_READ_WRITE,
+            TextureUsages::STORAGE_BINDING,
+        ),
+        (hal::TextureUses::COPY_SRC, TextureUsages::COPY_SRC),
+        (hal::TextureUses::COPY_DST, TextureUsages::COPY_DST),
+    ] {
+        if uses.intersects(hal_uses) {
+            usage |= wgt_usage;
+        }
+    }
+    usage
+}
+
+fn buffer_usages_of(uses: hal::BufferUses) -> BufferUsages {
+    let mut usage = BufferUsages::empty();
+    for (hal_uses, wgt_usage) in [
+        (
+            hal::BufferUses::STORAGE_READ | hal::BufferUses::STORAGE_READ_WRITE,
+            BufferUsages::STORAGE,
+        ),
+        (hal::BufferUses::COPY_SRC, BufferUsages::COPY_SRC),
+        (hal::BufferUses::COPY_DST, BufferUsages::COPY_DST),
+        (hal::BufferUses::VERTEX, BufferUsages::VERTEX),
+        (hal::BufferUses::INDEX, BufferUsages::INDEX),
+        (hal::BufferUses::UNIFORM, BufferUsages::UNIFORM),
+        (hal::BufferUses::INDIRECT, BufferUsages::INDIRECT),
+    ] {
+        if uses.intersects(hal_uses) {
+            usage |= wgt_usage;
+        }
+    }
+    usage
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResourceKind {
+    Texture,
+    Buffer,
+}
+
+#[derive(Debug)]
+struct ResourceNode {
+    kind: ResourceKind,
+    /// `None` if the resource is imported.
+    transient: Option<TransientResource>,
+}
+
+#[derive(Debug)]
+struct PassNode {
+    accesses: Vec<(ResourceHandle, Access)>,
+    has_side_effects: bool,
+}
+
+/// Describes a pass of a [`FrameGraph`].
+#[derive(Clone, Debug)]
+pub struct PassDescriptor<'a> {
+    pub label: Label<'a>,
+    /// The resources the pass accesses, and how. A resource can be accessed
+    /// in several ways by one pass, as long as the trackers would allow
+    /// them to be combined.
+    pub accesses: &'a [(ResourceHandle, Access)],
+    /// Whether the pass has effects other than writing to the resources of
+    /// the graph, like writing queries, so that it is never culled.
+    pub has_side_effects: bool,
+}
+
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum FrameGraphError {
+    #[error("Pass {pass:?} accesses {resource:?}, which isn't a resource of the frame graph")]
+    InvalidResource {
+        pass: PassHandle,
+        resource: ResourceHandle,
+    },
+    #[error("Pass {pass:?} accesses {resource:?} as {access:?}, which isn't possible for a resource of its kind")]
+    WrongResourceKind {
+        pass: PassHandle,
+        resource: ResourceHandle,
+        access: Access,
+    },
+    #[error("Pass {pass:?} accesses {resource:?} in ways that conflict: {uses:?}")]
+    ConflictingAccesses {
+        pass: PassHandle,
+        resource: ResourceHandle,
+        uses: Uses,
+    },
+    #[error(
+        "Pass {pass:?} reads the transient resource {resource:?} before any pass writes to it"
+    )]
+    ReadBeforeWrite {
+        pass: PassHandle,
+        resource: ResourceHandle,
+    },
+}
+
+/// The load and store operations of a render pass attachment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentOps {
+    pub load: LoadOp,
+    pub store: StoreOp,
+}
+
+/// A change in the state of a resource, at the start of a pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub resource: ResourceHandle,
+    /// The state the resource was left in by the last pass using it, or
+    /// `None` if this is its first use in the frame.
+    pub before: Option<Uses>,
+    pub after: Uses,
+}
+
+/// A pass of a [`CompiledFrameGraph`].
+#[derive(Clone, Debug)]
+pub struct CompiledPass {
+    pub pass: PassHandle,
+    /// The operations of the writable attachments of the pass. Read-only
+    /// depth-stencil attachments take no operations, and are left out.
+    pub attachment_ops: Vec<(ResourceHandle, AttachmentOps)>,
+    pub transitions: Vec<Transition>,
+}
+
+/// The plan for recording a [`FrameGraph`].
+#[derive(Clone, Debug)]
+pub struct CompiledFrameGraph {
+    /// The passes to record, in order. Culled passes are left out.
+    pub passes: Vec<CompiledPass>,
+    /// The physical resources to create for the transient resources.
+    pub physical_resources: Vec<TransientResource>,
+    /// The index in `physical_resources` of each resource of the graph, if it
+    /// is transient and used by a pass that isn't culled.
+    aliases: Vec<Option<usize>>,
+}
+
+impl CompiledFrameGraph {
+    /// Returns the index in [`Self::physical_resources`] of the physical
+    /// resource to use for `resource`.
+    pub fn physical_resource(&self, resource: ResourceHandle) -> Option<usize> {
+        self.aliases.get(resource.0 as usize).copied().flatten()
+    }
+}
+
+/// The accesses of a pass to one resource, combined.
+struct PassUse {
+    resource: usize,
+    uses: Uses,
+    writes: bool,
+    /// Whether the pass is the first to write to the transient resource.
+    initializes: bool,
+    /// Whether the resource is a writable attachment of the pass.
+    attachment: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct FrameGraph {
+    resources: Vec<ResourceNode>,
+    passes: Vec<PassNode>,
+}
+
+impl FrameGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_resource(
+        &mut self,
+        kind: ResourceKind,
+        transient: Option<TransientResource>,
+    ) -> ResourceHandle {
+        self.resources.push(ResourceNode { kind, transient });
+        ResourceHandle(self.resources.len() as u32 - 1)
+    }
+
+    /// Declares a transient texture.
+    ///
+    /// Its usage is that of `desc`, along with the usages its accesses need.
+    pub fn create_texture(&mut self, desc: &resource::TextureDescriptor) -> ResourceHandle {
+        let desc =
+            desc.map_label(|label| label.as_deref().map(|label| Cow::Owned(label.to_owned())));
+        self.add_resource(
+            ResourceKind::Texture,
+            Some(TransientResource::Texture(desc)),
+        )
+    }
+
+    /// Declares a transient buffer.
+    ///
+    /// Its usage is that of `desc`, along with the usages its accesses need.
+    /// Transient buffers can't be mapped at creation.
+    pub fn create_buffer(&mut self, desc: &resource::BufferDescriptor) -> ResourceHandle {
+        let mut desc =
+            desc.map_label(|label| label.as_deref().map(|label| Cow::Owned(label.to_owned())));
+        desc.mapped_at_creation = false;
+        self.add_resource(ResourceKind::Buffer, Some(TransientResource::Buffer(desc)))
+    }
+
+    /// Declares a texture whose contents outlive the frame.
+    pub fn import_texture(&mut self) -> ResourceHandle {
+        self.add_resource(ResourceKind::Texture, None)
+    }
+
+    /// Declares a buffer whose contents outlive the frame.
+    pub fn import_buffer(&mut self) -> ResourceHandle {
+        self.add_resource(ResourceKind::Buffer, None)
+    }
+
+    /// Declares the next pass of the frame.
+    ///
+    /// The label is only used by the caller, to tell passes apart.
+    pub fn add_pass(&mut self, desc: &PassDescriptor) -> PassHandle {
+        self.passes.push(PassNode {
+            accesses: desc.accesses.to_vec(),
+            has_side_effects: desc.has_side_effects,
+        });
+        PassHandle(self.passes.len() as u32 - 1)
+    }
+
+    fn is_imported(&self, resource: usize) -> bool {
+        self.resources[resource].transient.is_none()
+    }
+
+    /// Combines the accesses of every pass, checking them.
+    fn pass_uses(&self) -> Result<Vec<Vec<PassUse>>, FrameGraphError> {
+        let mut initialized = vec![false; self.resources.len()];
+        let mut all_uses = Vec::with_capacity(self.passes.len());
+        for (index, node) in self.passes.iter().enumerate() {
+            let pass = PassHandle(index as u32);
+            let mut pass_uses = Vec::<PassUse>::new();
+            for &(handle, access) in node.accesses.iter() {
+                let resource = handle.0 as usize;
+                let Some(resource_node) = self.resources.get(resource) else {
+                    return Err(FrameGraphError::InvalidResource {
+                        pass,
+                        resource: handle,
+                    });
+                };
+                let uses = match resource_node.kind {
+                    ResourceKind::Texture => {
+                        access.texture_uses().map(|(_, uses)| Uses::Texture(uses))
+                    }
+                    ResourceKind::Buffer => {
+                        access.buffer_uses().map(|(_, uses)| Uses::Buffer(uses))
+                    }
+                };
+                let Some(uses) = uses else {
+                    return Err(FrameGraphError::WrongResourceKind {
+                        pass,
+                        resource: handle,
+                        access,
+                    });
+                };
+                let attachment = matches!(
+                    access,
+                    Access::ColorAttachment | Access::DepthStencilAttachment { read_only: false }
+                );
+                match pass_uses
+                    .iter_mut()
+                    .find(|pass_use| pass_use.resource == resource)
+                {
+                    Some(pass_use) => {
+                        pass_use.uses = pass_use.uses.union(uses).unwrap();
+                        pass_use.writes |= access.is_write();
+                        pass_use.attachment |= attachment;
+                    }
+                    None => pass_uses.push(PassUse {
+                        resource,
+                        uses,
+                        writes: access.is_write(),
+                        initializes: false,
+                        attachment,
+                    }),
+                }
+            }
+
+            for pass_use in pass_uses.iter_mut() {
+                let resource = ResourceHandle(pass_use.resource as u32);
+                if pass_use.uses.is_conflicting() {
+                    return Err(FrameGraphError::ConflictingAccesses {
+                        pass,
+                        resource,
+                        uses: pass_use.uses,
+                    });
+                }
+                if self.is_imported(pass_use.resource) || initialized[pass_use.resource] {
+                    continue;
+                }
+                if !pass_use.writes {
+                    return Err(FrameGraphError::ReadBeforeWrite { pass, resource });
+                }
+                pass_use.initializes = true;
+                initialized[pass_use.resource] = true;
+            }
+            all_uses.push(pass_uses);
+        }
+        Ok(all_uses)
+    }
+
+    /// Works out how to record the frame.
+    pub fn compile(&self) -> Result<CompiledFrameGraph, FrameGraphError> {
+        profiling::scope!("FrameGraph::compile");
+        let all_uses = self.pass_uses()?;
+
+        // Walk the passes backwards, keeping those whose writes are read
+        // later. A pass that initializes a resource satisfies all the reads
+        // after it, while other writes depend on the contents before them.
+        let mut is_needed = vec![false; self.resources.len()];
+        let mut is_kept = vec![false; self.passes.len()];
+        for (index, pass_uses) in all_uses.iter().enumerate().rev() {
+            let keep = self.passes[index].has_side_effects
+                || pass_uses.iter().any(|pass_use| {
+                    pass_use.writes
+                        && (self.is_imported(pass_use.resource) || is_needed[pass_use.resource])
+                });
+            if !keep {
+                continue;
+            }
+            is_kept[index] = true;
+            for pass_use in pass_uses.iter() {
+                is_needed[pass_use.resource] = !pass_use.initializes;
+            }
+        }
+        let kept_uses = all_uses
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| is_kept[index])
+            .collect::<Vec<_>>();
+
+        // The first and last kept passes using each resource.
+        let mut lifetimes = vec![None::<(usize, usize)>; self.resources.len()];
+        for (position, &(_, pass_uses)) in kept_uses.iter().enumerate() {
+            for pass_use in pass_uses.iter() {
+                let lifetime = &mut lifetimes[pass_use.resource];
+                *lifetime = Some((lifetime.map_or(position, |(first, _)| first), position));
+            }
+        }
+
+        // Alias transient resources to the first compatible physical
+        // resource that is no longer used, in the order they are first used.
+        let mut transients = lifetimes
+            .iter()
+            .enumerate()
+            .filter_map(|(resource, lifetime)| Some((resource, (*lifetime)?)))
+            .filter(|&(resource, _)| !self.is_imported(resource))
+            .collect::<Vec<_>>();
+        transients.sort_by_key(|&(_, (first, _))| first);
+        let mut aliases = vec![None; self.resources.len()];
+        let mut physical_resources = Vec::<TransientResource>::new();
+        let mut physical_last_uses = Vec::<usize>::new();
+        for (resource, (first, last)) in transients {
+            let desc = self.resources[resource].transient.as_ref().unwrap();
+            let physical = physical_resources
+                .iter()
+                .zip(physical_last_uses.iter())
+                .position(|(physical, &last_use)| last_use < first && physical.can_alias(desc));
+            let physical = match physical {
+                Some(physical) => {
+                    physical_last_uses[physical] = last;
+                    physical
+                }
+                None => {
+                    physical_resources.push(desc.clone());
+                    physical_last_uses.push(last);
+                    physical_resources.len() - 1
+                }
+            };
+            aliases[resource] = Some(physical);
+        }
+
+        let mut last_uses = vec![None::<Uses>; self.resources.len()];
+        let mut passes = Vec::with_capacity(kept_uses.len());
+        for (position, &(index, pass_uses)) in kept_uses.iter().enumerate() {
+            let mut attachment_ops = Vec::new();
+            let mut transitions = Vec::new();
+            for pass_use in pass_uses.iter() {
+                let resource = ResourceHandle(pass_use.resource as u32);
+                let imported = self.is_imported(pass_use.resource);
+                if let Some(physical) = aliases[pass_use.resource] {
+                    physical_resources[physical].add_usage(pass_use.uses);
+                }
+
+                if pass_use.attachment {
+                    let (_, last) = lifetimes[pass_use.resource].unwrap();
+                    attachment_ops.push((
+                        resource,
+                        AttachmentOps {
+                            load: if pass_use.initializes {
+                                LoadOp::Clear
+                            } else {
+                                LoadOp::Load
+                            },
+                            store: if imported || position < last {
+                                StoreOp::Store
+                            } else {
+                                StoreOp::Discard
+                            },
+                        },
+                    ));
+                }
+
+                let before = last_uses[pass_use.resource].replace(pass_use.uses);
+                if before != Some(pass_use.uses) || !pass_use.uses.is_ordered() {
+                    transitions.push(Transition {
+                        resource,
+                        before,
+                        after: pass_use.uses,
+                    });
+                }
+            }
+            passes.push(CompiledPass {
+                pass: PassHandle(index as u32),
+                attachment_ops,
+                transitions,
+            });
+        }
+
+        Ok(CompiledFrameGraph {
+            passes,
+            physical_resources,
+            aliases,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Access, AttachmentOps, FrameGraph, FrameGraphError, PassDescriptor, PassHandle,
+        ResourceHandle, TransientResource, Uses,
+    };
+    use crate::command::{LoadOp, StoreOp};
+
+    fn texture_desc(width: u32) -> crate::resource::TextureDescriptor<'static> {
+        wgt::TextureDescriptor {
+            label: None,
+            size: wgt::Extent3d {
+                width,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgt::TextureDimension::D2,
+            format: wgt::TextureFormat::Rgba8Unorm,
+            usage: wgt::TextureUsages::empty(),
+            view_formats: Vec::new(),
+        }
+    }
+
+    fn add_pass(graph: &mut FrameGraph, accesses: &[(ResourceHandle, Access)]) -> PassHandle {
+        graph.add_pass(&PassDescriptor {
+            label: None,
+            accesses,
+            has_side_effects: false,
+        })
+    }
+
+    #[test]
+    fn culling_and_aliasing() {
+        let mut graph = FrameGraph::new();
+        let output = graph.import_texture();
+        let first = graph.create_texture(&texture_desc(16));
+        let unused = graph.create_texture(&texture_desc(16));
+        let second = graph.create_texture(&texture_desc(16));
+        let other_size = graph.create_texture(&texture_desc(32));
+
+        let draw_first = add_pass(&mut graph, &[(first, Access::ColorAttachment)]);
+        let read_first = add_pass(
+            &mut graph,
+            &[(first, Access::Sampled), (output, Access::ColorAttachment)],
+        );
+        add_pass(&mut graph, &[(unused, Access::ColorAttachment)]);
+        let draw_second = add_pass(
+            &mut graph,
+            &[
+                (second, Access::ColorAttachment),
+                (other_size, Access::StorageWrite),
+            ],
+        );
+        let read_second = add_pass(
+            &mut graph,
+            &[
+                (second, Access::Sampled),
+                (other_size, Access::Sampled),
+                (output, Access::ColorAttachment),
+            ],
+        );
+
+        let compiled = graph.compile().unwrap();
+        let passes = compiled
+            .passes
+            .iter()
+            .map(|pass| pass.pass)
+            .collect::<Vec<_>>();
+        assert_eq!(passes, [draw_first, read_first, draw_second, read_second]);
+
+        assert_eq!(compiled.physical_resource(output), None);
+        assert_eq!(compiled.physical_resource(unused), None);
+        assert_eq!(compiled.physical_resource(first), Some(0));
+        assert_eq!(compiled.physical_resource(second), Some(0));
+        assert_eq!(compiled.physical_resource(other_size), Some(1));
+        assert_eq!(compiled.physical_resources.len(), 2);
+        let TransientResource::Texture(ref desc) = compiled.physical_resources[0] else {
+            panic!("expected a texture");
+        };
+        assert_eq!(
+            desc.usage,
+            wgt::TextureUsages::RENDER_ATTACHMENT | wgt::TextureUsages::TEXTURE_BINDING
+        );
+    }
+
+    #[test]
+    fn attachment_ops_and_transitions() {
+        let mut graph = FrameGraph::new();
+        let output = graph.import_texture();
+        let color = graph.create_texture(&texture_desc(16));
+        let depth = graph.create_texture(&wgt::TextureDescriptor {
+            format: wgt::TextureFormat::Depth32Float,
+            ..texture_desc(16)
+        });
+
+        add_pass(
+            &mut graph,
+            &[
+                (color, Access::ColorAttachment),
+                (depth, Access::DepthStencilAttachment { read_only: false }),
+            ],
+        );
+        add_pass(
+            &mut graph,
+            &[(color, Access::Sampled), (output, Access::ColorAttachment)],
+        );
+
+        let compiled = graph.compile().unwrap();
+        const CLEAR_DISCARD: AttachmentOps = AttachmentOps {
+            load: LoadOp::Clear,
+            store: StoreOp::Discard,
+        };
+        const CLEAR_STORE: AttachmentOps = AttachmentOps {
+            load: LoadOp::Clear,
+            store: StoreOp::Store,
+        };
+        const LOAD_STORE: AttachmentOps = AttachmentOps {
+            load: LoadOp::Load,
+            store: StoreOp::Store,
+        };
+        assert_eq!(
+            compiled.passes[0].attachment_ops,
+            [(color, CLEAR_STORE), (depth, CLEAR_DISCARD)]
+        );
+        assert_eq!(compiled.passes[1].attachment_ops, [(output, LOAD_STORE)]);
+
+        let transition = compiled.passes[1]
+            .transitions
+            .iter()
+            .find(|transition| transition.resource == color)
+            .unwrap();
+        assert_eq!(
+            transition.before,
+            Some(Uses::Texture(hal::TextureUses::COLOR_TARGET))
+        );
+        assert_eq!(transition.after, Uses::Texture(hal::TextureUses::RESOURCE));
+    }
+
+    #[test]
+    fn errors() {
+        let mut graph = FrameGraph::new();
+        let texture = graph.create_texture(&texture_desc(16));
+        let pass = add_pass(&mut graph, &[(texture, Access::Sampled)]);
+        assert!(matches!(
+            graph.compile(),
+            Err(FrameGraphError::ReadBeforeWrite { pass: p, resource }) if p == pass && resource == texture
+        ));
+
+        let mut graph = FrameGraph::new();
+        let texture = graph.create_texture(&texture_desc(16));
+        add_pass(
+            &mut graph,
+            &[
+                (texture, Access::ColorAttachment),
+                (texture, Access::Sampled),
+            ],
+        );
+        assert!(matches!(
+            graph.compile(),
+            Err(FrameGraphError::ConflictingAccesses { .. })
+        ));
+
+        let mut graph = FrameGraph::new();
+        let texture = graph.import_texture();
+        add_pass(&mut graph, &[(texture, Access::Vertex)]);
+        assert!(matches!(
+            graph.compile(),
+            Err(FrameGraphError::WrongResourceKind { .. })
+        ));
+    }
+}
@@ -132,7 +132,7 @@ impl Drop for Global {
     fn drop(&mut self) {
         profiling::scope!("Global::drop");
         resource_log!("Global::drop");
-        let mut surfaces_locked = self.surfaces.write();
+        let surfaces_locked = self.surfaces.read();
 
         // destroy hubs before the instance gets dropped
         #[cfg(vulkan)]
@@ -152,8 +152,10 @@ impl Drop for Global {
             self.hubs.gl.clear(&surfaces_locked, true);
         }
 
+        drop(surfaces_locked);
+
         // destroy surfaces
-        for element in surfaces_locked.map.drain(..) {
+        for element in self.surfaces.write().drain() {
             if let Element::Occupied(arc_surface, _) = element {
                 let surface = Arc::into_inner(arc_surface)
                     .expect("Surface cannot be destroyed because is still in use");
@@ -12,6 +12,65 @@ pub trait HalApi: hal::Api + 'static + WasmNotSendSync {
     fn instance_as_hal(instance: &Instance) -> Option<&Self::Instance>;
     fn hub(global: &Global) -> &Hub<Self>;
     fn surface_as_hal(surface: &Surface) -> Option<&Self::Surface>;
+
+    /// Creates a texture which can be exported with [`Self::export_texture`].
+    ///
+    /// Only backends exposing [`wgt::Features::SHARED_TEXTURES`] implement
+    /// shared textures, the others fail with `ResourceCreationFailed`.
+    unsafe fn create_shared_texture(
+        device: &Self::Device,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError>;
+    /// Exports a texture created by [`Self::create_shared_texture`].
+    unsafe fn export_texture(
+        device: &Self::Device,
+        texture: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError>;
+    /// Imports a texture exported by [`Self::export_texture`], which was
+    /// created with `desc`.
+    unsafe fn import_texture(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError>;
+
+    /// Returns whether devices `a` and `b` can import the buffers and fences
+    /// exported by each other.
+    fn devices_share_memory(a: &Self::Device, b: &Self::Device) -> bool;
+    /// Creates a buffer which can be exported with [`Self::export_buffer`].
+    unsafe fn create_shared_buffer(
+        device: &Self::Device,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError>;
+    /// Exports a buffer created by [`Self::create_shared_buffer`].
+    unsafe fn export_buffer(
+        device: &Self::Device,
+        buffer: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError>;
+    /// Imports a buffer exported by [`Self::export_buffer`], which was
+    /// created with `desc`.
+    unsafe fn import_buffer(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError>;
+    /// Exports the fence of a device.
+    unsafe fn export_fence(
+        device: &Self::Device,
+        fence: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError>;
+    /// Imports a fence exported by [`Self::export_fence`].
+    unsafe fn import_fence(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError>;
+    /// Makes the next submission to `queue` wait until `fence` reaches
+    /// `value`.
+    unsafe fn queue_wait_for_fence(
+        queue: &Self::Queue,
+        fence: &Self::Fence,
+        value: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError>;
 }
 
 impl HalApi for hal::api::Empty {
@@ -28,6 +87,66 @@ impl HalApi for hal::api::Empty {
     fn surface_as_hal(_: &Surface) -> Option<&Self::Surface> {
         unimplemented!("called empty api")
     }
+    unsafe fn create_shared_texture(
+        _: &Self::Device,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn export_texture(
+        _: &Self::Device,
+        _: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn import_texture(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    fn devices_share_memory(_: &Self::Device, _: &Self::Device) -> bool {
+        unimplemented!("called empty api")
+    }
+    unsafe fn create_shared_buffer(
+        _: &Self::Device,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn export_buffer(
+        _: &Self::Device,
+        _: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn import_buffer(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn export_fence(
+        _: &Self::Device,
+        _: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn import_fence(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
+    unsafe fn queue_wait_for_fence(
+        _: &Self::Queue,
+        _: &Self::Fence,
+        _: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError> {
+        unimplemented!("called empty api")
+    }
 }
 
 #[cfg(vulkan)]
@@ -49,6 +168,67 @@ impl HalApi for hal::api::Vulkan {
     fn surface_as_hal(surface: &Surface) -> Option<&Self::Surface> {
         surface.vulkan.as_ref()
     }
+    unsafe fn create_shared_texture(
+        device: &Self::Device,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unsafe { device.create_exportable_texture(desc) }
+    }
+    unsafe fn export_texture(
+        device: &Self::Device,
+        texture: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_texture_memory(texture) }.map(|memory| memory.handle)
+    }
+    unsafe fn import_texture(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unsafe { device.import_texture_memory(handle, desc) }
+    }
+    fn devices_share_memory(a: &Self::Device, b: &Self::Device) -> bool {
+        a.can_share_memory_with(b)
+    }
+    unsafe fn create_shared_buffer(
+        device: &Self::Device,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unsafe { device.create_exportable_buffer(desc) }
+    }
+    unsafe fn export_buffer(
+        device: &Self::Device,
+        buffer: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_buffer_memory(buffer) }.map(|memory| memory.handle)
+    }
+    unsafe fn import_buffer(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unsafe { device.import_buffer_memory(handle, desc) }
+    }
+    unsafe fn export_fence(
+        device: &Self::Device,
+        fence: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_fence(fence) }
+    }
+    unsafe fn import_fence(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError> {
+        unsafe { device.import_fence(handle) }
+    }
+    unsafe fn queue_wait_for_fence(
+        queue: &Self::Queue,
+        fence: &Self::Fence,
+        value: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError> {
+        unsafe { queue.wait_for_fence(fence, value) };
+        Ok(())
+    }
 }
 
 #[cfg(metal)]
@@ -70,6 +250,66 @@ impl HalApi for hal::api::Metal {
     fn surface_as_hal(surface: &Surface) -> Option<&Self::Surface> {
         surface.metal.as_ref()
     }
+    unsafe fn create_shared_texture(
+        _: &Self::Device,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_texture(
+        _: &Self::Device,
+        _: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_texture(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    fn devices_share_memory(_: &Self::Device, _: &Self::Device) -> bool {
+        false
+    }
+    unsafe fn create_shared_buffer(
+        _: &Self::Device,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_buffer(
+        _: &Self::Device,
+        _: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_buffer(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_fence(
+        _: &Self::Device,
+        _: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_fence(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn queue_wait_for_fence(
+        _: &Self::Queue,
+        _: &Self::Fence,
+        _: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
 }
 
 #[cfg(dx12)]
@@ -91,6 +331,66 @@ impl HalApi for hal::api::Dx12 {
     fn surface_as_hal(surface: &Surface) -> Option<&Self::Surface> {
         surface.dx12.as_ref()
     }
+    unsafe fn create_shared_texture(
+        device: &Self::Device,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unsafe { device.create_exportable_texture(desc) }
+    }
+    unsafe fn export_texture(
+        device: &Self::Device,
+        texture: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_texture_memory(texture) }
+    }
+    unsafe fn import_texture(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        unsafe { device.import_texture_memory(handle, desc) }
+    }
+    fn devices_share_memory(a: &Self::Device, b: &Self::Device) -> bool {
+        a.can_share_memory_with(b)
+    }
+    unsafe fn create_shared_buffer(
+        device: &Self::Device,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unsafe { device.create_exportable_buffer(desc) }
+    }
+    unsafe fn export_buffer(
+        device: &Self::Device,
+        buffer: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_buffer_memory(buffer) }
+    }
+    unsafe fn import_buffer(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+        desc: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        unsafe { device.import_buffer_memory(handle, desc) }
+    }
+    unsafe fn export_fence(
+        device: &Self::Device,
+        fence: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        unsafe { device.export_fence(fence) }
+    }
+    unsafe fn import_fence(
+        device: &Self::Device,
+        handle: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError> {
+        unsafe { device.import_fence(handle) }
+    }
+    unsafe fn queue_wait_for_fence(
+        queue: &Self::Queue,
+        fence: &Self::Fence,
+        value: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError> {
+        unsafe { queue.wait_for_fence(fence, value) }
+    }
 }
 
 #[cfg(gles)]
@@ -113,4 +413,64 @@ impl HalApi for hal::api::Gles {
     fn surface_as_hal(surface: &Surface) -> Option<&Self::Surface> {
         surface.gl.as_ref()
     }
+    unsafe fn create_shared_texture(
+        _: &Self::Device,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_texture(
+        _: &Self::Device,
+        _: &Self::Texture,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_texture(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::TextureDescriptor,
+    ) -> Result<Self::Texture, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    fn devices_share_memory(_: &Self::Device, _: &Self::Device) -> bool {
+        false
+    }
+    unsafe fn create_shared_buffer(
+        _: &Self::Device,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_buffer(
+        _: &Self::Device,
+        _: &Self::Buffer,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_buffer(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+        _: &hal::BufferDescriptor,
+    ) -> Result<Self::Buffer, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn export_fence(
+        _: &Self::Device,
+        _: &Self::Fence,
+    ) -> Result<hal::ExternalHandle, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn import_fence(
+        _: &Self::Device,
+        _: hal::ExternalHandle,
+    ) -> Result<Self::Fence, hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
+    unsafe fn queue_wait_for_fence(
+        _: &Self::Queue,
+        _: &Self::Fence,
+        _: hal::FenceValue,
+    ) -> Result<(), hal::DeviceError> {
+        Err(hal::DeviceError::ResourceCreationFailed)
+    }
 }
@@ -110,7 +110,7 @@ use crate::{
     device::{queue::Queue, Device},
     hal_api::HalApi,
     instance::{Adapter, Surface},
-    pipeline::{ComputePipeline, RenderPipeline, ShaderModule},
+    pipeline::{ComputePipeline, PipelineCache, RenderPipeline, ShaderModule},
     registry::{Registry, RegistryReport},
     resource::{Buffer, QuerySet, Sampler, StagingBuffer, Texture, TextureView},
     storage::{Element, Storage},
@@ -130,6 +130,7 @@ pub struct HubReport {
     pub render_bundles: RegistryReport,
     pub render_pipelines: RegistryReport,
     pub compute_pipelines: RegistryReport,
+    pub pipeline_caches: RegistryReport,
     pub query_sets: RegistryReport,
     pub buffers: RegistryReport,
     pub textures: RegistryReport,
@@ -152,12 +153,13 @@ impl HubReport {
 /// ## Locking
 ///
 /// Each field in `Hub` is a [`Registry`] holding all the values of a
-/// particular type of resource, all protected by a single RwLock.
-/// So for example, to access any [`Buffer`], you must acquire a read
-/// lock on the `Hub`s entire buffers registry. The lock guard
-/// gives you access to the `Registry`'s [`Storage`], which you can
-/// then index with the buffer's id. (Yes, this design causes
-/// contention; see [#2272].)
+/// particular type of resource, split into shards that are each
+/// protected by their own RwLock. Looking up a single [`Buffer`] with
+/// [`Registry::get`] only locks the shard holding it, so threads using
+/// different buffers rarely contend. To access many buffers at once,
+/// [`Registry::read`] locks every shard of the `Hub`s buffers registry,
+/// giving you a [`Storage`] which you can then index with the buffers'
+/// ids. (Holding that for long still causes contention; see [#2272].)
 ///
 /// But most `wgpu` operations require access to several different
 /// kinds of resource, so you often need to hold locks on several
@@ -180,6 +182,7 @@ pub struct Hub<A: HalApi> {
     pub(crate) render_bundles: Registry<RenderBundle<A>>,
     pub(crate) render_pipelines: Registry<RenderPipeline<A>>,
     pub(crate) compute_pipelines: Registry<ComputePipeline<A>>,
+    pub(crate) pipeline_caches: Registry<PipelineCache<A>>,
     pub(crate) query_sets: Registry<QuerySet<A>>,
     pub(crate) buffers: Registry<Buffer<A>>,
     pub(crate) staging_buffers: Registry<StagingBuffer<A>>,
@@ -202,6 +205,7 @@ impl<A: HalApi> Hub<A> {
             render_bundles: Registry::new(A::VARIANT),
             render_pipelines: Registry::new(A::VARIANT),
             compute_pipelines: Registry::new(A::VARIANT),
+            pipeline_caches: Registry::new(A::VARIANT),
             query_sets: Registry::new(A::VARIANT),
             buffers: Registry::new(A::VARIANT),
             staging_buffers: Registry::new(A::VARIANT),
@@ -218,26 +222,27 @@ impl<A: HalApi> Hub<A> {
         use hal::Surface;
 
         let mut devices = self.devices.write();
-        for element in devices.map.iter() {
+        for element in devices.elements() {
             if let Element::Occupied(ref device, _) = *element {
                 device.prepare_to_die();
             }
         }
 
-        self.command_buffers.write().map.clear();
-        self.samplers.write().map.clear();
-        self.texture_views.write().map.clear();
-        self.textures.write().map.clear();
-        self.buffers.write().map.clear();
-        self.bind_groups.write().map.clear();
-        self.shader_modules.write().map.clear();
-        self.bind_group_layouts.write().map.clear();
-        self.pipeline_layouts.write().map.clear();
-        self.compute_pipelines.write().map.clear();
-        self.render_pipelines.write().map.clear();
-        self.query_sets.write().map.clear();
-
-        for element in surface_guard.map.iter() {
+        self.command_buffers.write().clear();
+        self.samplers.write().clear();
+        self.texture_views.write().clear();
+        self.textures.write().clear();
+        self.buffers.write().clear();
+        self.bind_groups.write().clear();
+        self.shader_modules.write().clear();
+        self.bind_group_layouts.write().clear();
+        self.pipeline_layouts.write().clear();
+        self.compute_pipelines.write().clear();
+        self.render_pipelines.write().clear();
+        self.pipeline_caches.write().clear();
+        self.query_sets.write().clear();
+
+        for element in surface_guard.elements() {
             if let Element::Occupied(ref surface, _epoch) = *element {
                 if let Some(ref mut present) = surface.presentation.lock().take() {
                     if let Some(device) = present.device.downcast_ref::<A>() {
@@ -251,12 +256,12 @@ impl<A: HalApi> Hub<A> {
             }
         }
 
-        self.queues.write().map.clear();
-        devices.map.clear();
+        self.queues.write().clear();
+        devices.clear();
 
         if with_adapters {
             drop(devices);
-            self.adapters.write().map.clear();
+            self.adapters.write().clear();
         }
     }
 
@@ -280,6 +285,7 @@ impl<A: HalApi> Hub<A> {
             render_bundles: self.render_bundles.generate_report(),
             render_pipelines: self.render_pipelines.generate_report(),
             compute_pipelines: self.compute_pipelines.generate_report(),
+            pipeline_caches: self.pipeline_caches.generate_report(),
             query_sets: self.query_sets.generate_report(),
             buffers: self.buffers.generate_report(),
             textures: self.textures.generate_report(),
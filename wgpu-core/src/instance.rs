@@ -306,11 +306,7 @@ impl<A: HalApi> Adapter<A> {
             trace_path,
             instance_flags,
         ) {
-            let queue = Queue {
-                device: None,
-                raw: Some(hal_device.queue),
-                info: ResourceInfo::new("<Queue>", None),
-            };
+            let queue = Queue::new(hal_device.queue);
             return Ok((device, queue));
         }
         Err(RequestDeviceError::OutOfMemory)
@@ -363,9 +359,11 @@ impl<A: HalApi> Adapter<A> {
         }
 
         let open = unsafe {
-            self.raw
-                .adapter
-                .open(desc.required_features, &desc.required_limits)
+            self.raw.adapter.open(
+                desc.required_features,
+                &desc.required_limits,
+                &desc.memory_hints,
+            )
         }
         .map_err(|err| match err {
             hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
@@ -1078,7 +1076,7 @@ impl Global {
         };
         if free {
             hub.adapters
-                .unregister_locked(adapter_id, &mut *adapters_locked);
+                .unregister_locked(adapter_id, &mut adapters_locked);
         }
     }
 }
@@ -51,10 +51,14 @@
 )]
 
 pub mod binding_model;
+#[cfg(feature = "tracing")]
+pub mod chrome_trace;
 pub mod command;
 mod conv;
 pub mod device;
 pub mod error;
+#[cfg(feature = "frame_graph")]
+pub mod frame_graph;
 pub mod global;
 pub mod hal_api;
 mod hash_utils;
@@ -65,6 +69,7 @@ mod init_tracker;
 pub mod instance;
 mod lock;
 pub mod pipeline;
+mod pipeline_cache;
 mod pool;
 pub mod present;
 pub mod registry;
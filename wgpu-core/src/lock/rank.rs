@@ -100,6 +100,7 @@ define_lock_ranks! {
         TEXTURE_BIND_GROUP_STATE_TEXTURES,
         BUFFER_MAP_STATE,
         STATELESS_BIND_GROUP_STATE_RESOURCES,
+        COMMAND_ALLOCATOR_FREE_ENCODERS,
     }
     rank DEVICE_SNATCHABLE_LOCK "Device::snatchable_lock" followed by {
         SHARED_TRACKER_INDEX_ALLOCATOR_INNER,
@@ -108,6 +109,8 @@ define_lock_ranks! {
         BUFFER_BIND_GROUP_STATE_BUFFERS,
         TEXTURE_BIND_GROUP_STATE_TEXTURES,
         STATELESS_BIND_GROUP_STATE_RESOURCES,
+        // Recording native render bundles.
+        COMMAND_ALLOCATOR_FREE_ENCODERS,
         // Uncomment this to see an interesting cycle.
         // COMMAND_BUFFER_DATA,
     }
@@ -120,6 +123,10 @@ define_lock_ranks! {
         COMMAND_ALLOCATOR_FREE_ENCODERS,
         SHARED_TRACKER_INDEX_ALLOCATOR_INNER,
         DEVICE_LIFE_TRACKER,
+        QUEUE_BATCH,
+    }
+    rank QUEUE_BATCH "Queue::batch" followed by {
+        DEVICE_LIFE_TRACKER,
     }
     rank DEVICE_LIFE_TRACKER "Device::life_tracker" followed by {
         COMMAND_ALLOCATOR_FREE_ENCODERS,
@@ -136,9 +143,11 @@ define_lock_ranks! {
     rank BUFFER_INITIALIZATION_STATUS "Buffer::initialization_status" followed by { }
     rank BUFFER_SYNC_MAPPED_WRITES "Buffer::sync_mapped_writes" followed by { }
     rank DEVICE_DEFERRED_DESTROY "Device::deferred_destroy" followed by { }
-    rank DEVICE_FENCE "Device::fence" followed by { }
+    rank DEVICE_FENCE "Device::fence" followed by { QUEUE_BATCH }
+    rank DEVICE_IMPORTED_FENCES "Device::imported_fences" followed by { DEVICE_FENCE }
     #[allow(dead_code)]
     rank DEVICE_TRACE "Device::trace" followed by { }
+    rank DEVICE_SHADER_CACHE "Device::shader_cache" followed by { }
     rank DEVICE_TRACKERS "Device::trackers" followed by { }
     rank DEVICE_USAGE_SCOPES "Device::usage_scopes" followed by { }
     rank IDENTITY_MANAGER_VALUES "IdentityManager::values" followed by { }
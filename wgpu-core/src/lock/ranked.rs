@@ -159,6 +159,31 @@ fn acquire(new_rank: LockRank, location: &'static Location<'static>) -> LockStat
     state
 }
 
+/// Check and record the acquisition of a lock with `rank` as part of a walk
+/// over a group of locks that all have that rank.
+///
+/// Acquiring a lock with the same rank as the youngest lock is normally
+/// forbidden, since two threads could take a pair of such locks in opposite
+/// orders. Walks over a group always take its locks in the same order, though,
+/// so we only check the first acquisition of the walk against the locks held
+/// before it, and require the rest to follow a lock of the same rank.
+fn acquire_next_in_group(rank: LockRank, location: &'static Location<'static>) -> LockState {
+    let state = LOCK_STATE.get();
+    let last_rank = state.last_acquired.map(|(last_rank, _)| last_rank.bit);
+    assert_eq!(
+        last_rank,
+        Some(rank.bit),
+        "Lock group walk interrupted before locking {} at {}",
+        rank.bit.name(),
+        location,
+    );
+    LOCK_STATE.set(LockState {
+        last_acquired: Some((rank, location)),
+        depth: state.depth + 1,
+    });
+    state
+}
+
 /// Record the release of a lock whose saved state was `saved`.
 ///
 /// Check that locks are being acquired in stacking order, and update the
@@ -279,6 +304,64 @@ impl<T> RwLock<T> {
             saved: LockStateGuard(saved),
         }
     }
+
+    /// Lock all of `locks`, which must share a rank, for reading.
+    ///
+    /// The walk counts as a single acquisition of that rank. The locks are
+    /// taken last to first, so that dropping the returned array, which drops
+    /// its elements first to last, releases them in stacking order.
+    #[track_caller]
+    pub fn read_all<const N: usize>(locks: &[Self; N]) -> [RwLockReadGuard<T>; N] {
+        let location = Location::caller();
+        let mut guards: Vec<_> = locks
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, lock)| {
+                let saved = match index {
+                    0 => acquire(lock.rank, location),
+                    _ => acquire_next_in_group(lock.rank, location),
+                };
+                RwLockReadGuard {
+                    inner: lock.inner.read(),
+                    saved: LockStateGuard(saved),
+                }
+            })
+            .collect();
+        guards.reverse();
+        match guards.try_into() {
+            Ok(guards) => guards,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Lock all of `locks`, which must share a rank, for writing.
+    ///
+    /// See [`read_all`](Self::read_all).
+    #[track_caller]
+    pub fn write_all<const N: usize>(locks: &[Self; N]) -> [RwLockWriteGuard<T>; N] {
+        let location = Location::caller();
+        let mut guards: Vec<_> = locks
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, lock)| {
+                let saved = match index {
+                    0 => acquire(lock.rank, location),
+                    _ => acquire_next_in_group(lock.rank, location),
+                };
+                RwLockWriteGuard {
+                    inner: lock.inner.write(),
+                    saved: LockStateGuard(saved),
+                }
+            })
+            .collect();
+        guards.reverse();
+        match guards.try_into() {
+            Ok(guards) => guards,
+            Err(_) => unreachable!(),
+        }
+    }
 }
 
 impl<'a, T> RwLockWriteGuard<'a, T> {
@@ -395,3 +478,34 @@ fn non_stack_like() {
 
     drop(guard1);
 }
+
+/// A group of locks with the same rank can be walked, then released.
+#[test]
+fn group_walk() {
+    use super::rank;
+
+    let group: [RwLock<()>; 3] = std::array::from_fn(|_| RwLock::new(rank::PAWN, ()));
+    let lock = Mutex::new(rank::ROOK, ());
+
+    let guards = RwLock::write_all(&group);
+    // The walk leaves the group's rank as the youngest.
+    let guard = lock.lock();
+    drop(guard);
+    drop(guards);
+
+    let guards = RwLock::read_all(&group);
+    drop(guards);
+}
+
+/// Locks with the same rank can't be nested outside of a group walk.
+#[test]
+#[should_panic(expected = "Locking pawn after locking pawn")]
+fn forbidden_same_rank() {
+    use super::rank;
+
+    let lock1 = RwLock::new(rank::PAWN, ());
+    let lock2 = RwLock::new(rank::PAWN, ());
+
+    let _guard1 = lock1.read();
+    let _guard2 = lock2.read();
+}
@@ -84,6 +84,31 @@ impl<T> RwLock<T> {
     pub fn write(&self) -> RwLockWriteGuard<T> {
         RwLockWriteGuard(self.0.write())
     }
+
+    /// Lock all of `locks` for reading, last to first.
+    ///
+    /// The guards are returned in the order of `locks`, so dropping the array
+    /// releases them in reverse order of acquisition.
+    pub fn read_all<const N: usize>(locks: &[Self; N]) -> [RwLockReadGuard<T>; N] {
+        let mut guards: Vec<_> = locks.iter().rev().map(|lock| lock.read()).collect();
+        guards.reverse();
+        match guards.try_into() {
+            Ok(guards) => guards,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Lock all of `locks` for writing, last to first.
+    ///
+    /// See [`read_all`](Self::read_all).
+    pub fn write_all<const N: usize>(locks: &[Self; N]) -> [RwLockWriteGuard<T>; N] {
+        let mut guards: Vec<_> = locks.iter().rev().map(|lock| lock.write()).collect();
+        guards.reverse();
+        match guards.try_into() {
+            Ok(guards) => guards,
+            Err(_) => unreachable!(),
+        }
+    }
 }
 
 impl<'a, T> RwLockWriteGuard<'a, T> {
@@ -5,7 +5,7 @@ use crate::{
     command::ColorAttachmentError,
     device::{Device, DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
     hal_api::HalApi,
-    id::{PipelineLayoutId, ShaderModuleId},
+    id::{PipelineCacheId, PipelineLayoutId, ShaderModuleId},
     resource::{Resource, ResourceInfo, ResourceType},
     resource_log, validation, Label,
 };
@@ -14,6 +14,8 @@ use naga::error::ShaderError;
 use std::{borrow::Cow, marker::PhantomData, num::NonZeroU32, sync::Arc};
 use thiserror::Error;
 
+pub use crate::pipeline_cache::PipelineCacheValidationError;
+
 /// Information about buffer bindings, which
 /// is validated against the shader (and pipeline)
 /// at draw time as opposed to initialization time.
@@ -27,17 +29,40 @@ pub(crate) struct LateSizedBufferGroup {
 pub enum ShaderModuleSource<'a> {
     #[cfg(feature = "wgsl")]
     Wgsl(Cow<'a, str>),
+    /// WGSL source whose `// #include "path"` directives are resolved by
+    /// calling `resolve`. See [`naga::front::wgsl::ExpandedSource`].
+    #[cfg(feature = "wgsl")]
+    WgslWithIncludes {
+        source: Cow<'a, str>,
+        resolve: &'a dyn Fn(&str) -> Result<String, String>,
+    },
     #[cfg(feature = "glsl")]
     Glsl(Cow<'a, str>, naga::front::glsl::Options),
+    #[cfg(feature = "hlsl")]
+    Hlsl(Cow<'a, str>, naga::front::hlsl::Options),
     #[cfg(feature = "spirv")]
     SpirV(Cow<'a, [u32]>, naga::front::spv::Options),
     Naga(Cow<'static, naga::Module>),
+    /// A module parsed ahead of time, encoded by [`naga::precompiled::write`].
+    #[cfg(feature = "precompiled")]
+    Precompiled(Cow<'a, [u8]>),
     /// Dummy variant because `Naga` doesn't have a lifetime and without enough active features it
     /// could be the last one active.
     #[doc(hidden)]
     Dummy(PhantomData<&'a ()>),
 }
 
+/// Metal code for [`Global::device_create_shader_module_msl`].
+///
+/// [`Global::device_create_shader_module_msl`]: crate::global::Global::device_create_shader_module_msl
+#[derive(Clone, Debug)]
+pub enum MslSource<'a> {
+    /// Metal Shading Language source code.
+    Source(Cow<'a, str>),
+    /// A compiled `metallib` library.
+    Library(Cow<'a, [u8]>),
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShaderModuleDescriptor<'a> {
@@ -118,9 +143,15 @@ pub enum CreateShaderModuleError {
     #[cfg(feature = "glsl")]
     #[error(transparent)]
     ParsingGlsl(#[from] ShaderError<naga::front::glsl::ParseErrors>),
+    #[cfg(feature = "hlsl")]
+    #[error(transparent)]
+    ParsingHlsl(#[from] ShaderError<naga::front::hlsl::Error>),
     #[cfg(feature = "spirv")]
     #[error(transparent)]
-    ParsingSpirV(#[from] ShaderError<naga::front::spv::Error>),
+    ParsingSpirV(#[from] ShaderError<naga::WithSpan<naga::front::spv::Error>>),
+    #[cfg(feature = "precompiled")]
+    #[error(transparent)]
+    Precompiled(#[from] naga::precompiled::Error),
     #[error("Failed to generate the backend-specific code")]
     Generation,
     #[error(transparent)]
@@ -192,6 +223,8 @@ pub struct ComputePipelineDescriptor<'a> {
     pub layout: Option<PipelineLayoutId>,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStageDescriptor<'a>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<PipelineCacheId>,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -218,6 +251,7 @@ pub struct ComputePipeline<A: HalApi> {
     pub(crate) device: Arc<Device<A>>,
     pub(crate) _shader_module: Arc<ShaderModule<A>>,
     pub(crate) late_sized_buffer_groups: ArrayVec<LateSizedBufferGroup, { hal::MAX_BIND_GROUPS }>,
+    pub(crate) binding_names: validation::BindingNames,
     pub(crate) info: ResourceInfo<ComputePipeline<A>>,
 }
 
@@ -315,6 +349,79 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<PipelineCacheId>,
+}
+
+/// Describes a pipeline cache.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data returned by an earlier [`pipeline_cache_get_data`], to start the
+    /// cache with.
+    ///
+    /// [`pipeline_cache_get_data`]: crate::global::Global::pipeline_cache_get_data
+    pub data: Option<Cow<'a, [u8]>>,
+    /// Whether to create an empty cache if `data` is not valid for this
+    /// device, instead of failing.
+    pub fallback: bool,
+}
+
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum CreatePipelineCacheError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("Pipeline cache validation failed")]
+    Validation(#[from] PipelineCacheValidationError),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+}
+
+#[derive(Debug)]
+pub struct PipelineCache<A: HalApi> {
+    pub(crate) raw: Option<A::PipelineCache>,
+    pub(crate) device: Arc<Device<A>>,
+    pub(crate) info: ResourceInfo<PipelineCache<A>>,
+}
+
+impl<A: HalApi> Drop for PipelineCache<A> {
+    fn drop(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            resource_log!("Destroy raw PipelineCache {:?}", self.info.label());
+
+            #[cfg(feature = "trace")]
+            if let Some(t) = self.device.trace.lock().as_mut() {
+                t.add(trace::Action::DestroyPipelineCache(self.info.id()));
+            }
+
+            unsafe {
+                use hal::Device;
+                self.device.raw().destroy_pipeline_cache(raw);
+            }
+        }
+    }
+}
+
+impl<A: HalApi> Resource for PipelineCache<A> {
+    const TYPE: ResourceType = "PipelineCache";
+
+    type Marker = crate::id::markers::PipelineCache;
+
+    fn as_info(&self) -> &ResourceInfo<Self> {
+        &self.info
+    }
+
+    fn as_info_mut(&mut self) -> &mut ResourceInfo<Self> {
+        &mut self.info
+    }
+}
+
+impl<A: HalApi> PipelineCache<A> {
+    pub(crate) fn raw(&self) -> &A::PipelineCache {
+        self.raw.as_ref().unwrap()
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -468,6 +575,7 @@ pub struct RenderPipeline<A: HalApi> {
     pub(crate) strip_index_format: Option<wgt::IndexFormat>,
     pub(crate) vertex_steps: Vec<VertexStep>,
     pub(crate) late_sized_buffer_groups: ArrayVec<LateSizedBufferGroup, { hal::MAX_BIND_GROUPS }>,
+    pub(crate) binding_names: validation::BindingNames,
     pub(crate) info: ResourceInfo<RenderPipeline<A>>,
 }
 
@@ -0,0 +1,183 @@
+//! The header in front of the data of pipeline caches.
+//!
+//! Backends only partly check that the data a pipeline cache is created from
+//! was made by the same device and driver, and some trust it entirely. So
+//! [`Global::pipeline_cache_get_data`] puts a header in front of the
+//! backend's data, recording the adapter, driver and backend it comes from,
+//! and a hash of its contents. Data from another device or driver, from
+//! another version of wgpu, or corrupted data, is then rejected before it
+//! reaches the backend.
+//!
+//! [`Global::pipeline_cache_get_data`]: crate::global::Global::pipeline_cache_get_data
+
+use thiserror::Error;
+
+const MAGIC: [u8; 8] = *b"WGPUPLCH";
+
+/// Changes whenever the layout of the header does.
+const HEADER_VERSION: u32 = 1;
+
+/// Data from builds with a different pointer width is rejected, as backends
+/// may store pointer sized values.
+const ABI: u32 = std::mem::size_of::<usize>() as u32;
+
+const HEADER_SIZE: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum PipelineCacheValidationError {
+    #[error("The pipeline cache data is truncated")]
+    Truncated,
+    #[error("The pipeline cache data was not made by wgpu, or by an incompatible version of it")]
+    WrongVersion,
+    #[error("The pipeline cache data was made for another backend, adapter or driver")]
+    DeviceMismatch,
+    #[error("The pipeline cache data is corrupted")]
+    Corrupted,
+}
+
+/// A 64-bit FNV-1a hash, which unlike the standard library's hashers is the
+/// same on every run and every platform.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Hashes what identifies the adapter and the driver.
+fn adapter_key(info: &wgt::AdapterInfo) -> u64 {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, &[info.backend as u8]);
+    hash = fnv1a(hash, &info.vendor.to_le_bytes());
+    hash = fnv1a(hash, &info.device.to_le_bytes());
+    // Strings are terminated by a zero, so that moving bytes from one to the
+    // next changes the hash.
+    for string in [&info.name, &info.driver, &info.driver_info] {
+        hash = fnv1a(hash, string.as_bytes());
+        hash = fnv1a(hash, &[0]);
+    }
+    hash
+}
+
+/// Returns the backend's data, with a header in front of it.
+///
+/// `validation_key` is the device's
+/// [`pipeline_cache_validation_key`](hal::Device::pipeline_cache_validation_key).
+pub(crate) fn add_header(
+    data: &[u8],
+    adapter: &wgt::AdapterInfo,
+    validation_key: [u8; 16],
+) -> Vec<u8> {
+    let mut result = Vec::with_capacity(HEADER_SIZE + data.len());
+    result.extend_from_slice(&MAGIC);
+    result.extend_from_slice(&HEADER_VERSION.to_le_bytes());
+    result.extend_from_slice(&ABI.to_le_bytes());
+    result.extend_from_slice(&adapter_key(adapter).to_le_bytes());
+    result.extend_from_slice(&validation_key);
+    result.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    result.extend_from_slice(&fnv1a(FNV_OFFSET_BASIS, data).to_le_bytes());
+    result.resize(HEADER_SIZE, 0);
+    result.extend_from_slice(data);
+    result
+}
+
+/// Checks the header of `data`, returning the backend's data after it.
+pub(crate) fn validate<'d>(
+    data: &'d [u8],
+    adapter: &wgt::AdapterInfo,
+    validation_key: [u8; 16],
+) -> Result<&'d [u8], PipelineCacheValidationError> {
+    use PipelineCacheValidationError as Error;
+
+    if data.len() < HEADER_SIZE {
+        return Err(Error::Truncated);
+    }
+    let (header, data) = data.split_at(HEADER_SIZE);
+    let field = |offset: usize, size: usize| &header[offset..offset + size];
+    let u32_at = |offset| u32::from_le_bytes(field(offset, 4).try_into().unwrap());
+    let u64_at = |offset| u64::from_le_bytes(field(offset, 8).try_into().unwrap());
+
+    let padding = &header[56..];
+    if field(0, 8) != MAGIC
+        || u32_at(8) != HEADER_VERSION
+        || u32_at(12) != ABI
+        || padding.iter().any(|&byte| byte != 0)
+    {
+        return Err(Error::WrongVersion);
+    }
+    if u64_at(16) != adapter_key(adapter) || field(24, 16) != validation_key {
+        return Err(Error::DeviceMismatch);
+    }
+    if u64_at(40) != data.len() as u64 {
+        return Err(Error::Truncated);
+    }
+    if u64_at(48) != fnv1a(FNV_OFFSET_BASIS, data) {
+        return Err(Error::Corrupted);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_header, validate, PipelineCacheValidationError as Error};
+
+    const KEY: [u8; 16] = [7; 16];
+
+    fn adapter() -> wgt::AdapterInfo {
+        wgt::AdapterInfo {
+            name: "Adapter".to_string(),
+            vendor: 0x10de,
+            device: 0x2204,
+            device_type: wgt::DeviceType::DiscreteGpu,
+            driver: "Driver".to_string(),
+            driver_info: "1.2.3".to_string(),
+            backend: wgt::Backend::Vulkan,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = [1, 2, 3, 4, 5];
+        let cache = add_header(&data, &adapter(), KEY);
+        assert_eq!(validate(&cache, &adapter(), KEY), Ok(&data[..]));
+        let empty = add_header(&[], &adapter(), KEY);
+        assert_eq!(validate(&empty, &adapter(), KEY), Ok(&[][..]));
+    }
+
+    #[test]
+    fn mismatch() {
+        let cache = add_header(&[1, 2, 3], &adapter(), KEY);
+
+        let mut other_driver = adapter();
+        other_driver.driver_info = "1.2.4".to_string();
+        assert_eq!(
+            validate(&cache, &other_driver, KEY),
+            Err(Error::DeviceMismatch)
+        );
+        assert_eq!(
+            validate(&cache, &adapter(), [8; 16]),
+            Err(Error::DeviceMismatch)
+        );
+
+        let mut corrupted = cache.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert_eq!(validate(&corrupted, &adapter(), KEY), Err(Error::Corrupted));
+
+        let mut foreign = cache.clone();
+        foreign[0] = b'X';
+        assert_eq!(
+            validate(&foreign, &adapter(), KEY),
+            Err(Error::WrongVersion)
+        );
+
+        assert_eq!(
+            validate(&cache[..cache.len() - 1], &adapter(), KEY),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            validate(&cache[..10], &adapter(), KEY),
+            Err(Error::Truncated)
+        );
+    }
+}
@@ -238,6 +238,7 @@ impl Global {
                     ),
                     views: Mutex::new(rank::TEXTURE_VIEWS, Vec::new()),
                     bind_groups: Mutex::new(rank::TEXTURE_BIND_GROUPS, Vec::new()),
+                    shared_ownership: None,
                 };
 
                 let (id, resource) = fid.assign(Arc::new(texture));
@@ -313,6 +314,9 @@ impl Global {
             trace.add(Action::Present(surface_id));
         }
 
+        // The submissions rendering to the texture may be held back.
+        queue.flush_batch(&device.snatchable_lock.read())?;
+
         let result = {
             let texture_id = present
                 .acquired_texture
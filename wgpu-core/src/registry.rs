@@ -5,9 +5,9 @@ use wgt::Backend;
 use crate::{
     id::Id,
     identity::IdentityManager,
-    lock::{rank, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    lock::{rank, RwLock},
     resource::Resource,
-    storage::{Element, InvalidId, Storage},
+    storage::{Element, InvalidId, Shard, Storage, StorageMut, SHARDS},
 };
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -36,11 +36,14 @@ impl RegistryReport {
 /// if it's used in active submission or anyway kept alive from
 /// any other dependent resource
 ///
+/// The storage is split into [`SHARDS`] separately locked shards, so that
+/// threads creating, using and dropping different resources don't contend
+/// on a single lock.
 #[derive(Debug)]
 pub(crate) struct Registry<T: Resource> {
     // Must only contain an id which has either never been used or has been released from `storage`
     identity: Arc<IdentityManager<T::Marker>>,
-    storage: RwLock<Storage<T>>,
+    shards: [RwLock<Shard<T>>; SHARDS],
     backend: Backend,
 }
 
@@ -48,7 +51,7 @@ impl<T: Resource> Registry<T> {
     pub(crate) fn new(backend: Backend) -> Self {
         Self {
             identity: Arc::new(IdentityManager::new()),
-            storage: RwLock::new(rank::REGISTRY_STORAGE, Storage::new()),
+            shards: std::array::from_fn(|_| RwLock::new(rank::REGISTRY_STORAGE, Shard::new())),
             backend,
         }
     }
@@ -61,7 +64,7 @@ impl<T: Resource> Registry<T> {
 #[must_use]
 pub(crate) struct FutureId<'a, T: Resource> {
     id: Id<T::Marker>,
-    data: &'a RwLock<Storage<T>>,
+    data: &'a RwLock<Shard<T>>,
 }
 
 impl<T: Resource> FutureId<'_, T> {
@@ -113,57 +116,75 @@ impl<T: Resource> FutureId<'_, T> {
 }
 
 impl<T: Resource> Registry<T> {
+    fn shard(&self, id: Id<T::Marker>) -> &RwLock<Shard<T>> {
+        &self.shards[id.unzip().0 as usize % SHARDS]
+    }
+
     pub(crate) fn prepare(&self, id_in: Option<Id<T::Marker>>) -> FutureId<T> {
+        let id = match id_in {
+            Some(id_in) => {
+                self.identity.mark_as_used(id_in);
+                id_in
+            }
+            None => self.identity.process(self.backend),
+        };
         FutureId {
-            id: match id_in {
-                Some(id_in) => {
-                    self.identity.mark_as_used(id_in);
-                    id_in
-                }
-                None => self.identity.process(self.backend),
-            },
-            data: &self.storage,
+            id,
+            data: self.shard(id),
         }
     }
 
     pub(crate) fn request(&self) -> FutureId<T> {
+        let id = self.identity.process(self.backend);
         FutureId {
-            id: self.identity.process(self.backend),
-            data: &self.storage,
+            id,
+            data: self.shard(id),
         }
     }
     pub(crate) fn try_get(&self, id: Id<T::Marker>) -> Result<Option<Arc<T>>, InvalidId> {
-        self.read().try_get(id).map(|o| o.cloned())
+        self.shard(id).read().try_get(id).map(|o| o.cloned())
     }
     pub(crate) fn get(&self, id: Id<T::Marker>) -> Result<Arc<T>, InvalidId> {
-        self.read().get_owned(id)
+        self.shard(id).read().get(id).cloned()
     }
-    pub(crate) fn read<'a>(&'a self) -> RwLockReadGuard<'a, Storage<T>> {
-        self.storage.read()
+    /// Lock every shard for reading.
+    ///
+    /// Prefer [`get`](Self::get) for looking up a single resource, which only
+    /// locks the shard holding it.
+    ///
+    /// Every walk over the shards takes them in the same order, so walks can't
+    /// deadlock with each other.
+    pub(crate) fn read(&self) -> Storage<T> {
+        Storage {
+            shards: RwLock::read_all(&self.shards),
+        }
     }
-    pub(crate) fn write<'a>(&'a self) -> RwLockWriteGuard<'a, Storage<T>> {
-        self.storage.write()
+    /// Lock every shard for writing.
+    pub(crate) fn write(&self) -> StorageMut<T> {
+        StorageMut {
+            shards: RwLock::write_all(&self.shards),
+        }
     }
     pub(crate) fn unregister_locked(
         &self,
         id: Id<T::Marker>,
-        storage: &mut Storage<T>,
+        storage: &mut StorageMut<T>,
     ) -> Option<Arc<T>> {
         self.identity.free(id);
         storage.remove(id)
     }
     pub(crate) fn force_replace(&self, id: Id<T::Marker>, mut value: T) {
-        let mut storage = self.storage.write();
+        let mut shard = self.shard(id).write();
         value.as_info_mut().set_id(id);
-        storage.force_replace(id, value)
+        shard.force_replace(id, value)
     }
     pub(crate) fn force_replace_with_error(&self, id: Id<T::Marker>, label: &str) {
-        let mut storage = self.storage.write();
-        storage.remove(id);
-        storage.insert_error(id, label);
+        let mut shard = self.shard(id).write();
+        shard.remove(id);
+        shard.insert_error(id, label);
     }
     pub(crate) fn unregister(&self, id: Id<T::Marker>) -> Option<Arc<T>> {
-        let value = self.storage.write().remove(id);
+        let value = self.shard(id).write().remove(id);
         // This needs to happen *after* removing it from the storage, to maintain the
         // invariant that `self.identity` only contains ids which are actually available
         // See https://github.com/gfx-rs/wgpu/issues/5372
@@ -173,9 +194,9 @@ impl<T: Resource> Registry<T> {
     }
 
     pub(crate) fn label_for_resource(&self, id: Id<T::Marker>) -> String {
-        let guard = self.storage.read();
+        let guard = self.shard(id).read();
 
-        let type_name = guard.kind();
+        let type_name = T::TYPE;
         match guard.get(id) {
             Ok(res) => {
                 let label = res.label();
@@ -194,13 +215,13 @@ impl<T: Resource> Registry<T> {
     }
 
     pub(crate) fn generate_report(&self) -> RegistryReport {
-        let storage = self.storage.read();
+        let storage = self.read();
         let mut report = RegistryReport {
             element_size: std::mem::size_of::<T>(),
             ..Default::default()
         };
         report.num_allocated = self.identity.values.lock().count();
-        for element in storage.map.iter() {
+        for element in storage.elements() {
             match *element {
                 Element::Occupied(..) => report.num_kept_from_user += 1,
                 Element::Vacant => report.num_released_from_user += 1,
@@ -2,6 +2,7 @@
 use crate::device::trace;
 use crate::{
     binding_model::BindGroup,
+    command::ClearError,
     device::{
         queue, resource::DeferredDestroy, BufferMapPendingClosure, Device, DeviceError, HostMap,
         MissingDownlevelFlags, MissingFeatures,
@@ -9,8 +10,9 @@ use crate::{
     global::Global,
     hal_api::HalApi,
     id::{
-        AdapterId, BufferId, CommandEncoderId, DeviceId, Id, Marker, SurfaceId, TextureId,
-        TextureViewId,
+        AdapterId, BindGroupId, BindGroupLayoutId, BufferId, CommandEncoderId, ComputePipelineId,
+        DeviceId, Id, Marker, PipelineLayoutId, QuerySetId, QueueId, RenderPipelineId, SamplerId,
+        ShaderModuleId, SurfaceId, TextureId, TextureViewId,
     },
     init_tracker::{BufferInitTracker, TextureInitTracker},
     lock::{Mutex, RwLock},
@@ -33,7 +35,7 @@ use std::{
     ops::Range,
     ptr::NonNull,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Weak,
     },
 };
@@ -138,6 +140,13 @@ impl<T: Resource> ResourceInfo<T> {
             .store(submit_index as _, Ordering::Release);
     }
 
+    /// Record that this resource is used by the queue submission with the
+    /// given index, unless it is already used by a later one.
+    pub(crate) fn use_at_least(&self, submit_index: SubmissionIndex) {
+        self.submission_index
+            .fetch_max(submit_index as _, Ordering::AcqRel);
+    }
+
     pub(crate) fn submission_index(&self) -> SubmissionIndex {
         self.submission_index.load(Ordering::Acquire) as _
     }
@@ -395,6 +404,11 @@ pub struct Buffer<A: HalApi> {
     pub(crate) device: Arc<Device<A>>,
     pub(crate) usage: wgt::BufferUsages,
     pub(crate) size: wgt::BufferAddress,
+    /// Whether the buffer was made mappable for writes even though its
+    /// `usage` doesn't allow the user to map it, so that
+    /// `mapped_at_creation` and `Queue::write_buffer` can write to it
+    /// directly.
+    pub(crate) host_writable: bool,
     pub(crate) initialization_status: RwLock<BufferInitTracker>,
     pub(crate) sync_mapped_writes: Mutex<Option<hal::MemoryRange>>,
     pub(crate) info: ResourceInfo<Buffer<A>>,
@@ -487,10 +501,12 @@ impl<A: HalApi> Buffer<A> {
                 });
                 let transition_src = hal::BufferBarrier {
                     buffer: stage_buffer.raw(&snatch_guard).unwrap(),
+                    range: None,
                     usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
                 };
                 let transition_dst = hal::BufferBarrier {
                     buffer: raw_buf,
+                    range: None,
                     usage: hal::BufferUses::empty()..hal::BufferUses::COPY_DST,
                 };
                 let mut pending_writes = device.pending_writes.lock();
@@ -783,6 +799,29 @@ pub struct Texture<A: HalApi> {
     pub(crate) clear_mode: RwLock<TextureClearMode<A>>,
     pub(crate) views: Mutex<Vec<Weak<TextureView<A>>>>,
     pub(crate) bind_groups: Mutex<Vec<Weak<BindGroup<A>>>>,
+    /// For textures shared with other processes, whether this process owns
+    /// the texture and may use it.
+    ///
+    /// Ownership moves with [`Global::queue_release_shared_texture`] and
+    /// [`Global::queue_acquire_shared_texture`].
+    pub(crate) shared_ownership: Option<AtomicBool>,
+}
+
+/// The state a shared texture is in while no process owns it.
+///
+/// Releasing a texture transitions it to this state, and acquiring it
+/// transitions from it, so any format can be shared. Shared textures are
+/// always created with this usage.
+pub(crate) const SHARED_TEXTURE_RELEASED_USE: hal::TextureUses = hal::TextureUses::COPY_SRC;
+
+/// Where a texture shared with other processes comes from.
+#[derive(Debug)]
+pub(crate) enum SharedTextureSource {
+    /// The texture is created by this process, which owns it.
+    Created,
+    /// The texture is imported from a handle exported by another process,
+    /// which owns it until it's released.
+    Imported(hal::ExternalHandle),
 }
 
 impl<A: HalApi> Drop for Texture<A> {
@@ -838,6 +877,14 @@ impl<A: HalApi> Texture<A> {
         self.inner.get(guard).is_none()
     }
 
+    /// Returns whether this process may use the texture, which it can't
+    /// while another process owns a shared texture.
+    pub(crate) fn is_owned(&self) -> bool {
+        self.shared_ownership
+            .as_ref()
+            .map_or(true, |owned| owned.load(Ordering::Acquire))
+    }
+
     pub(crate) fn inner_mut<'a>(
         &'a self,
         guard: &mut ExclusiveSnatchGuard,
@@ -970,6 +1017,199 @@ impl Global {
         hal_texture_view_callback(hal_texture_view)
     }
 
+    /// # Safety
+    ///
+    /// - The raw buffer handle must not be manually destroyed
+    pub unsafe fn buffer_as_hal<A: HalApi, F: FnOnce(Option<&A::Buffer>) -> R, R>(
+        &self,
+        id: BufferId,
+        hal_buffer_callback: F,
+    ) -> R {
+        profiling::scope!("Buffer::as_hal");
+
+        let hub = A::hub(self);
+        let buffer = hub.buffers.try_get(id).ok().flatten();
+        let snatch_guard = buffer
+            .as_ref()
+            .map(|buffer| buffer.device.snatchable_lock.read());
+        let hal_buffer = buffer
+            .as_ref()
+            .zip(snatch_guard.as_ref())
+            .and_then(|(buffer, snatch_guard)| buffer.raw(snatch_guard));
+
+        hal_buffer_callback(hal_buffer)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw sampler handle must not be manually destroyed
+    pub unsafe fn sampler_as_hal<A: HalApi, F: FnOnce(Option<&A::Sampler>) -> R, R>(
+        &self,
+        id: SamplerId,
+        hal_sampler_callback: F,
+    ) -> R {
+        profiling::scope!("Sampler::as_hal");
+
+        let hub = A::hub(self);
+        let sampler = hub.samplers.try_get(id).ok().flatten();
+        let hal_sampler = sampler.as_ref().and_then(|sampler| sampler.raw.as_ref());
+
+        hal_sampler_callback(hal_sampler)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw bind group layout handle must not be manually destroyed
+    pub unsafe fn bind_group_layout_as_hal<
+        A: HalApi,
+        F: FnOnce(Option<&A::BindGroupLayout>) -> R,
+        R,
+    >(
+        &self,
+        id: BindGroupLayoutId,
+        hal_bind_group_layout_callback: F,
+    ) -> R {
+        profiling::scope!("BindGroupLayout::as_hal");
+
+        let hub = A::hub(self);
+        let bind_group_layout = hub.bind_group_layouts.try_get(id).ok().flatten();
+        let hal_bind_group_layout = bind_group_layout
+            .as_ref()
+            .and_then(|bind_group_layout| bind_group_layout.raw.as_ref());
+
+        hal_bind_group_layout_callback(hal_bind_group_layout)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw bind group handle must not be manually destroyed
+    pub unsafe fn bind_group_as_hal<A: HalApi, F: FnOnce(Option<&A::BindGroup>) -> R, R>(
+        &self,
+        id: BindGroupId,
+        hal_bind_group_callback: F,
+    ) -> R {
+        profiling::scope!("BindGroup::as_hal");
+
+        let hub = A::hub(self);
+        let bind_group = hub.bind_groups.try_get(id).ok().flatten();
+        let snatch_guard = bind_group
+            .as_ref()
+            .map(|bind_group| bind_group.device.snatchable_lock.read());
+        let hal_bind_group = bind_group
+            .as_ref()
+            .zip(snatch_guard.as_ref())
+            .and_then(|(bind_group, snatch_guard)| bind_group.raw(snatch_guard));
+
+        hal_bind_group_callback(hal_bind_group)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw pipeline layout handle must not be manually destroyed
+    pub unsafe fn pipeline_layout_as_hal<
+        A: HalApi,
+        F: FnOnce(Option<&A::PipelineLayout>) -> R,
+        R,
+    >(
+        &self,
+        id: PipelineLayoutId,
+        hal_pipeline_layout_callback: F,
+    ) -> R {
+        profiling::scope!("PipelineLayout::as_hal");
+
+        let hub = A::hub(self);
+        let pipeline_layout = hub.pipeline_layouts.try_get(id).ok().flatten();
+        let hal_pipeline_layout = pipeline_layout
+            .as_ref()
+            .and_then(|pipeline_layout| pipeline_layout.raw.as_ref());
+
+        hal_pipeline_layout_callback(hal_pipeline_layout)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw shader module handle must not be manually destroyed
+    pub unsafe fn shader_module_as_hal<A: HalApi, F: FnOnce(Option<&A::ShaderModule>) -> R, R>(
+        &self,
+        id: ShaderModuleId,
+        hal_shader_module_callback: F,
+    ) -> R {
+        profiling::scope!("ShaderModule::as_hal");
+
+        let hub = A::hub(self);
+        let shader_module = hub.shader_modules.try_get(id).ok().flatten();
+        let hal_shader_module = shader_module
+            .as_ref()
+            .and_then(|shader_module| shader_module.raw.as_ref());
+
+        hal_shader_module_callback(hal_shader_module)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw render pipeline handle must not be manually destroyed
+    pub unsafe fn render_pipeline_as_hal<
+        A: HalApi,
+        F: FnOnce(Option<&A::RenderPipeline>) -> R,
+        R,
+    >(
+        &self,
+        id: RenderPipelineId,
+        hal_render_pipeline_callback: F,
+    ) -> R {
+        profiling::scope!("RenderPipeline::as_hal");
+
+        let hub = A::hub(self);
+        let render_pipeline = hub.render_pipelines.try_get(id).ok().flatten();
+        let hal_render_pipeline = render_pipeline
+            .as_ref()
+            .and_then(|render_pipeline| render_pipeline.raw.as_ref());
+
+        hal_render_pipeline_callback(hal_render_pipeline)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw compute pipeline handle must not be manually destroyed
+    pub unsafe fn compute_pipeline_as_hal<
+        A: HalApi,
+        F: FnOnce(Option<&A::ComputePipeline>) -> R,
+        R,
+    >(
+        &self,
+        id: ComputePipelineId,
+        hal_compute_pipeline_callback: F,
+    ) -> R {
+        profiling::scope!("ComputePipeline::as_hal");
+
+        let hub = A::hub(self);
+        let compute_pipeline = hub.compute_pipelines.try_get(id).ok().flatten();
+        let hal_compute_pipeline = compute_pipeline
+            .as_ref()
+            .and_then(|compute_pipeline| compute_pipeline.raw.as_ref());
+
+        hal_compute_pipeline_callback(hal_compute_pipeline)
+    }
+
+    /// # Safety
+    ///
+    /// - The raw query set handle must not be manually destroyed
+    pub unsafe fn query_set_as_hal<A: HalApi, F: FnOnce(Option<&A::QuerySet>) -> R, R>(
+        &self,
+        id: QuerySetId,
+        hal_query_set_callback: F,
+    ) -> R {
+        profiling::scope!("QuerySet::as_hal");
+
+        let hub = A::hub(self);
+        let query_set = hub.query_sets.try_get(id).ok().flatten();
+        let hal_query_set = query_set
+            .as_ref()
+            .and_then(|query_set| query_set.raw.as_ref());
+
+        hal_query_set_callback(hal_query_set)
+    }
+
     /// # Safety
     ///
     /// - The raw adapter handle must not be manually destroyed
@@ -1021,6 +1261,23 @@ impl Global {
         hal_fence_callback(hal_fence.as_deref().unwrap().as_ref())
     }
 
+    /// # Safety
+    ///
+    /// - The raw queue handle must not be manually destroyed
+    pub unsafe fn queue_as_hal<A: HalApi, F: FnOnce(Option<&A::Queue>) -> R, R>(
+        &self,
+        id: QueueId,
+        hal_queue_callback: F,
+    ) -> R {
+        profiling::scope!("Queue::as_hal");
+
+        let hub = A::hub(self);
+        let queue = hub.queues.try_get(id).ok().flatten();
+        let hal_queue = queue.as_ref().and_then(|queue| queue.raw.as_ref());
+
+        hal_queue_callback(hal_queue)
+    }
+
     /// # Safety
     /// - The raw surface handle must not be manually destroyed
     pub unsafe fn surface_as_hal<A: HalApi, F: FnOnce(Option<&A::Surface>) -> R, R>(
@@ -1205,10 +1462,31 @@ pub enum CreateTextureError {
     MultisampledNotRenderAttachment,
     #[error("Texture format {0:?} can't be used due to missing features")]
     MissingFeatures(wgt::TextureFormat, #[source] MissingFeatures),
+    #[error("Shared textures can't be created due to missing features")]
+    MissingSharedTexturesFeature(#[source] MissingFeatures),
     #[error(transparent)]
     MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum SharedTextureError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("Texture {0:?} is invalid")]
+    InvalidTexture(TextureId),
+    #[error("Texture {0:?} is destroyed")]
+    DestroyedTexture(TextureId),
+    #[error("Texture {0:?} is not shared with other processes")]
+    NotShared(TextureId),
+    #[error("Texture {0:?} is owned by another process")]
+    NotOwned(TextureId),
+    #[error("Texture {0:?} is already owned by this process")]
+    AlreadyOwned(TextureId),
+    #[error(transparent)]
+    Clear(#[from] ClearError),
+}
+
 impl<A: HalApi> Resource for Texture<A> {
     const TYPE: ResourceType = "Texture";
 
@@ -4,10 +4,19 @@ use std::sync::Arc;
 use wgt::Backend;
 
 use crate::id::Id;
+use crate::lock::{RwLockReadGuard, RwLockWriteGuard};
 use crate::resource::Resource;
 use crate::{Epoch, Index};
 
-/// An entry in a `Storage::map` table.
+/// The number of shards a registry's storage is split into.
+///
+/// Each shard holds the ids whose index is the shard's number modulo
+/// `SHARDS`, behind its own lock. Looking up, creating or dropping a single
+/// resource only locks its shard, so threads working on different resources
+/// rarely wait on each other.
+pub(crate) const SHARDS: usize = 16;
+
+/// An entry in a `Shard::map` table.
 #[derive(Debug)]
 pub(crate) enum Element<T> {
     /// There are no live ids with this index.
@@ -27,52 +36,36 @@ pub(crate) enum Element<T> {
 #[derive(Clone, Debug)]
 pub(crate) struct InvalidId;
 
-/// A table of `T` values indexed by the id type `I`.
-///
-/// `Storage` implements [`std::ops::Index`], accepting `Id` values as
-/// indices.
+/// Split an id's index into its shard and its position within the shard.
+fn locate(index: Index) -> (usize, usize) {
+    let index = index as usize;
+    (index % SHARDS, index / SHARDS)
+}
+
+/// One shard of a table of `T` values indexed by the id type `I`.
 ///
-/// The table is represented as a vector indexed by the ids' index
-/// values, so you should use an id allocator like `IdentityManager`
-/// that keeps the index values dense and close to zero.
+/// The shard is represented as a vector indexed by the ids' index
+/// values divided by [`SHARDS`], so you should use an id allocator like
+/// `IdentityManager` that keeps the index values dense and close to zero.
 #[derive(Debug)]
-pub(crate) struct Storage<T>
+pub(crate) struct Shard<T>
 where
     T: Resource,
 {
     pub(crate) map: Vec<Element<T>>,
-    kind: &'static str,
 }
 
-impl<T> ops::Index<Id<T::Marker>> for Storage<T>
-where
-    T: Resource,
-{
-    type Output = Arc<T>;
-    fn index(&self, id: Id<T::Marker>) -> &Arc<T> {
-        self.get(id).unwrap()
-    }
-}
-impl<T> Storage<T>
+impl<T> Shard<T>
 where
     T: Resource,
 {
     pub(crate) fn new() -> Self {
-        Self {
-            map: Vec::new(),
-            kind: T::TYPE,
-        }
+        Self { map: Vec::new() }
     }
-}
 
-impl<T> Storage<T>
-where
-    T: Resource,
-{
-    #[allow(dead_code)]
     pub(crate) fn contains(&self, id: Id<T::Marker>) -> bool {
         let (index, epoch, _) = id.unzip();
-        match self.map.get(index as usize) {
+        match self.map.get(locate(index).1) {
             Some(&Element::Vacant) => false,
             Some(&Element::Occupied(_, storage_epoch) | &Element::Error(storage_epoch, _)) => {
                 storage_epoch == epoch
@@ -90,16 +83,18 @@ where
     /// be in a different hub.
     pub(crate) fn try_get(&self, id: Id<T::Marker>) -> Result<Option<&Arc<T>>, InvalidId> {
         let (index, epoch, _) = id.unzip();
-        let (result, storage_epoch) = match self.map.get(index as usize) {
+        let (result, storage_epoch) = match self.map.get(locate(index).1) {
             Some(&Element::Occupied(ref v, epoch)) => (Ok(Some(v)), epoch),
             Some(&Element::Vacant) => return Ok(None),
             Some(&Element::Error(epoch, ..)) => (Err(InvalidId), epoch),
             None => return Err(InvalidId),
         };
         assert_eq!(
-            epoch, storage_epoch,
+            epoch,
+            storage_epoch,
             "{}[{:?}] is no longer alive",
-            self.kind, id
+            T::TYPE,
+            id
         );
         result
     }
@@ -108,39 +103,36 @@ where
     /// Panics if there is an epoch mismatch, or the entry is empty.
     pub(crate) fn get(&self, id: Id<T::Marker>) -> Result<&Arc<T>, InvalidId> {
         let (index, epoch, _) = id.unzip();
-        let (result, storage_epoch) = match self.map.get(index as usize) {
+        let (result, storage_epoch) = match self.map.get(locate(index).1) {
             Some(&Element::Occupied(ref v, epoch)) => (Ok(v), epoch),
-            Some(&Element::Vacant) => panic!("{}[{:?}] does not exist", self.kind, id),
+            Some(&Element::Vacant) => panic!("{}[{:?}] does not exist", T::TYPE, id),
             Some(&Element::Error(epoch, ..)) => (Err(InvalidId), epoch),
             None => return Err(InvalidId),
         };
         assert_eq!(
-            epoch, storage_epoch,
+            epoch,
+            storage_epoch,
             "{}[{:?}] is no longer alive",
-            self.kind, id
+            T::TYPE,
+            id
         );
         result
     }
 
-    /// Get an owned reference to an item behind a potentially invalid ID.
-    /// Panics if there is an epoch mismatch, or the entry is empty.
-    pub(crate) fn get_owned(&self, id: Id<T::Marker>) -> Result<Arc<T>, InvalidId> {
-        Ok(Arc::clone(self.get(id)?))
-    }
-
     pub(crate) fn label_for_invalid_id(&self, id: Id<T::Marker>) -> &str {
         let (index, _, _) = id.unzip();
-        match self.map.get(index as usize) {
+        match self.map.get(locate(index).1) {
             Some(Element::Error(_, label)) => label,
             _ => "",
         }
     }
 
-    fn insert_impl(&mut self, index: usize, epoch: Epoch, element: Element<T>) {
-        if index >= self.map.len() {
-            self.map.resize_with(index + 1, || Element::Vacant);
+    fn insert_impl(&mut self, index: Index, epoch: Epoch, element: Element<T>) {
+        let slot = locate(index).1;
+        if slot >= self.map.len() {
+            self.map.resize_with(slot + 1, || Element::Vacant);
         }
-        match std::mem::replace(&mut self.map[index], element) {
+        match std::mem::replace(&mut self.map[slot], element) {
             Element::Vacant => {}
             Element::Occupied(_, storage_epoch) => {
                 assert_ne!(
@@ -164,23 +156,19 @@ where
     pub(crate) fn insert(&mut self, id: Id<T::Marker>, value: Arc<T>) {
         log::trace!("User is inserting {}{:?}", T::TYPE, id);
         let (index, epoch, _backend) = id.unzip();
-        self.insert_impl(index as usize, epoch, Element::Occupied(value, epoch))
+        self.insert_impl(index, epoch, Element::Occupied(value, epoch))
     }
 
     pub(crate) fn insert_error(&mut self, id: Id<T::Marker>, label: &str) {
         log::trace!("User is inserting as error {}{:?}", T::TYPE, id);
         let (index, epoch, _) = id.unzip();
-        self.insert_impl(
-            index as usize,
-            epoch,
-            Element::Error(epoch, label.to_string()),
-        )
+        self.insert_impl(index, epoch, Element::Error(epoch, label.to_string()))
     }
 
     pub(crate) fn replace_with_error(&mut self, id: Id<T::Marker>) -> Result<Arc<T>, InvalidId> {
         let (index, epoch, _) = id.unzip();
         match std::mem::replace(
-            &mut self.map[index as usize],
+            &mut self.map[locate(index).1],
             Element::Error(epoch, String::new()),
         ) {
             Element::Vacant => panic!("Cannot access vacant resource"),
@@ -195,13 +183,13 @@ where
     pub(crate) fn force_replace(&mut self, id: Id<T::Marker>, value: T) {
         log::trace!("User is replacing {}{:?}", T::TYPE, id);
         let (index, epoch, _) = id.unzip();
-        self.map[index as usize] = Element::Occupied(Arc::new(value), epoch);
+        self.map[locate(index).1] = Element::Occupied(Arc::new(value), epoch);
     }
 
     pub(crate) fn remove(&mut self, id: Id<T::Marker>) -> Option<Arc<T>> {
         log::trace!("User is removing {}{:?}", T::TYPE, id);
         let (index, epoch, _) = id.unzip();
-        match std::mem::replace(&mut self.map[index as usize], Element::Vacant) {
+        match std::mem::replace(&mut self.map[locate(index).1], Element::Vacant) {
             Element::Occupied(value, storage_epoch) => {
                 assert_eq!(epoch, storage_epoch);
                 Some(value)
@@ -211,23 +199,160 @@ where
         }
     }
 
-    pub(crate) fn iter(&self, backend: Backend) -> impl Iterator<Item = (Id<T::Marker>, &Arc<T>)> {
+    /// Iterate over the live values in this shard, which is shard number
+    /// `shard` of its registry.
+    fn iter(
+        &self,
+        shard: usize,
+        backend: Backend,
+    ) -> impl Iterator<Item = (Id<T::Marker>, &Arc<T>)> {
         self.map
             .iter()
             .enumerate()
-            .filter_map(move |(index, x)| match *x {
+            .filter_map(move |(slot, x)| match *x {
                 Element::Occupied(ref value, storage_epoch) => {
-                    Some((Id::zip(index as Index, storage_epoch, backend), value))
+                    let index = (slot * SHARDS + shard) as Index;
+                    Some((Id::zip(index, storage_epoch, backend), value))
                 }
                 _ => None,
             })
     }
 
-    pub(crate) fn kind(&self) -> &str {
-        self.kind
+    /// One more than the largest index this shard, number `shard` of its
+    /// registry, has room for.
+    fn index_bound(&self, shard: usize) -> usize {
+        match self.map.len() {
+            0 => 0,
+            len => (len - 1) * SHARDS + shard + 1,
+        }
+    }
+}
+
+/// A whole registry's table of `T` values, locked for reading.
+///
+/// `Storage` implements [`std::ops::Index`], accepting `Id` values as
+/// indices.
+pub(crate) struct Storage<'a, T>
+where
+    T: Resource,
+{
+    pub(crate) shards: [RwLockReadGuard<'a, Shard<T>>; SHARDS],
+}
+
+impl<T> ops::Index<Id<T::Marker>> for Storage<'_, T>
+where
+    T: Resource,
+{
+    type Output = Arc<T>;
+    fn index(&self, id: Id<T::Marker>) -> &Arc<T> {
+        self.get(id).unwrap()
+    }
+}
+
+impl<T> Storage<'_, T>
+where
+    T: Resource,
+{
+    fn shard(&self, id: Id<T::Marker>) -> &Shard<T> {
+        &self.shards[locate(id.unzip().0).0]
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn contains(&self, id: Id<T::Marker>) -> bool {
+        self.shard(id).contains(id)
     }
 
+    /// See [`Shard::try_get`].
+    pub(crate) fn try_get(&self, id: Id<T::Marker>) -> Result<Option<&Arc<T>>, InvalidId> {
+        self.shard(id).try_get(id)
+    }
+
+    /// Get a reference to an item behind a potentially invalid ID.
+    /// Panics if there is an epoch mismatch, or the entry is empty.
+    pub(crate) fn get(&self, id: Id<T::Marker>) -> Result<&Arc<T>, InvalidId> {
+        self.shard(id).get(id)
+    }
+
+    /// Get an owned reference to an item behind a potentially invalid ID.
+    /// Panics if there is an epoch mismatch, or the entry is empty.
+    pub(crate) fn get_owned(&self, id: Id<T::Marker>) -> Result<Arc<T>, InvalidId> {
+        Ok(Arc::clone(self.get(id)?))
+    }
+
+    pub(crate) fn iter(&self, backend: Backend) -> impl Iterator<Item = (Id<T::Marker>, &Arc<T>)> {
+        self.shards
+            .iter()
+            .enumerate()
+            .flat_map(move |(shard, guard)| guard.iter(shard, backend))
+    }
+
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &Element<T>> {
+        self.shards.iter().flat_map(|guard| guard.map.iter())
+    }
+
+    /// One more than the largest index the table has room for.
     pub(crate) fn len(&self) -> usize {
-        self.map.len()
+        self.shards
+            .iter()
+            .enumerate()
+            .map(|(shard, guard)| guard.index_bound(shard))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A whole registry's table of `T` values, locked for writing.
+pub(crate) struct StorageMut<'a, T>
+where
+    T: Resource,
+{
+    pub(crate) shards: [RwLockWriteGuard<'a, Shard<T>>; SHARDS],
+}
+
+impl<T> StorageMut<'_, T>
+where
+    T: Resource,
+{
+    fn shard(&self, id: Id<T::Marker>) -> &Shard<T> {
+        &self.shards[locate(id.unzip().0).0]
+    }
+
+    fn shard_mut(&mut self, id: Id<T::Marker>) -> &mut Shard<T> {
+        &mut self.shards[locate(id.unzip().0).0]
+    }
+
+    pub(crate) fn contains(&self, id: Id<T::Marker>) -> bool {
+        self.shard(id).contains(id)
+    }
+
+    pub(crate) fn get(&self, id: Id<T::Marker>) -> Result<&Arc<T>, InvalidId> {
+        self.shard(id).get(id)
+    }
+
+    pub(crate) fn insert_error(&mut self, id: Id<T::Marker>, label: &str) {
+        self.shard_mut(id).insert_error(id, label)
+    }
+
+    pub(crate) fn replace_with_error(&mut self, id: Id<T::Marker>) -> Result<Arc<T>, InvalidId> {
+        self.shard_mut(id).replace_with_error(id)
+    }
+
+    pub(crate) fn remove(&mut self, id: Id<T::Marker>) -> Option<Arc<T>> {
+        self.shard_mut(id).remove(id)
+    }
+
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &Element<T>> {
+        self.shards.iter().flat_map(|guard| guard.map.iter())
+    }
+
+    /// Remove every element, returning them.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = Element<T>> + '_ {
+        self.shards.iter_mut().flat_map(|guard| guard.map.drain(..))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for guard in self.shards.iter_mut() {
+            guard.map.clear();
+        }
     }
 }
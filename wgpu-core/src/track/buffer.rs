@@ -1,13 +1,31 @@
 /*! Buffer Trackers
  *
- * Buffers are represented by a single state for the whole resource,
- * a 16 bit bitflag of buffer usages. Because there is only ever
- * one subresource, they have no selector.
+ * Buffers are usually represented by a single state for the whole
+ * resource, a 16 bit bitflag of buffer usages. The commands using a
+ * buffer often only use part of it though, so that the parts of a
+ * large buffer can be written and read independently, without
+ * barriers between them. Buffers of which different ranges are in
+ * different states are in a "complex" state, stored separately from
+ * the simple states like the complex states of textures.
+ *
+ * Usage scopes still validate the union of all the uses of a buffer,
+ * since their rules apply to buffers as a whole.
+ *
+ * Bindings with dynamic offsets are tracked as using the whole buffer,
+ * since the offsets are only known when the bind group is set, after its
+ * uses have been recorded.
+ *
+ * Like for textures, the special `UNKNOWN` usage is only used in
+ * complex states, to signify that the complex state does not know
+ * anything about those ranges.
 !*/
 
-use std::{borrow::Cow, marker::PhantomData, sync::Arc};
+use std::{borrow::Cow, iter, marker::PhantomData, ops::Range, sync::Arc};
 
-use super::{PendingTransition, ResourceTracker, TrackerIndex};
+use super::{
+    range::RangedStates, EitherIter, PendingTransition, ResourceTracker, SingleOrManyStates,
+    TrackerIndex,
+};
 use crate::{
     hal_api::HalApi,
     id::BufferId,
@@ -21,12 +39,14 @@ use crate::{
     },
 };
 use hal::{BufferBarrier, BufferUses};
-use wgt::{strict_assert, strict_assert_eq};
+use naga::FastHashMap;
+use smallvec::SmallVec;
+use wgt::{strict_assert, strict_assert_eq, BufferAddress};
 
 impl ResourceUses for BufferUses {
     const EXCLUSIVE: Self = Self::EXCLUSIVE;
 
-    type Selector = ();
+    type Selector = Range<BufferAddress>;
 
     fn bits(self) -> u16 {
         Self::bits(&self)
@@ -41,10 +61,69 @@ impl ResourceUses for BufferUses {
     }
 }
 
+/// Represents the complex state of buffers where ranges of the buffer are
+/// potentially in different states.
+#[derive(Clone, Debug, PartialEq)]
+struct ComplexBufferState {
+    ranges: RangedStates<BufferAddress, BufferUses>,
+}
+
+impl ComplexBufferState {
+    /// Creates complex buffer state for a buffer of the given size, in the
+    /// given state.
+    fn new(buffer_size: BufferAddress, state: BufferUses) -> Self {
+        Self {
+            ranges: RangedStates::from_range(0..buffer_size, state),
+        }
+    }
+
+    /// Initialize a complex state for a buffer of the given size from an
+    /// iterator of ranges and the usage of each range.
+    ///
+    /// The ranges not given by the iterator are in the UNKNOWN state.
+    ///
+    /// [`Self::to_range_state_iter`] can be used to create such an iterator.
+    fn from_range_state_iter(
+        buffer_size: BufferAddress,
+        state_iter: impl Iterator<Item = (Range<BufferAddress>, BufferUses)>,
+    ) -> Self {
+        let mut complex = Self::new(buffer_size, BufferUses::UNKNOWN);
+        for (range, desired_state) in state_iter {
+            strict_assert!(range.end <= buffer_size);
+
+            // This should only ever happen with a wgpu bug, but let's just double
+            // check that resource states don't have any conflicts.
+            strict_assert_eq!(invalid_resource_state(desired_state), false);
+
+            for &mut (_, ref mut state) in complex.ranges.isolate(&range, BufferUses::UNKNOWN) {
+                *state = desired_state;
+            }
+        }
+        complex.ranges.coalesce();
+        complex
+    }
+
+    /// Convert a complex state into an iterator over all states stored.
+    ///
+    /// [`Self::from_range_state_iter`] can be used to consume such an iterator.
+    fn to_range_state_iter(
+        &self,
+    ) -> impl Iterator<Item = (Range<BufferAddress>, BufferUses)> + Clone + '_ {
+        self.ranges.iter().cloned()
+    }
+}
+
+#[derive(Debug)]
+struct BufferBindGroupStateData<A: HalApi> {
+    buffer: Arc<Buffer<A>>,
+    range: Range<BufferAddress>,
+    usage: BufferUses,
+}
+
 /// Stores all the buffers that a bind group stores.
 #[derive(Debug)]
 pub(crate) struct BufferBindGroupState<A: HalApi> {
-    buffers: Mutex<Vec<(Arc<Buffer<A>>, BufferUses)>>,
+    buffers: Mutex<Vec<BufferBindGroupStateData<A>>>,
 
     _phantom: PhantomData<A>,
 }
@@ -61,19 +140,17 @@ impl<A: HalApi> BufferBindGroupState<A> {
     ///
     /// When this list of states is merged into a tracker, the memory
     /// accesses will be in a constant ascending order.
-    #[allow(clippy::pattern_type_mismatch)]
     pub(crate) fn optimize(&self) {
         let mut buffers = self.buffers.lock();
-        buffers.sort_unstable_by_key(|(b, _)| b.as_info().tracker_index());
+        buffers.sort_unstable_by_key(|v| v.buffer.as_info().tracker_index());
     }
 
     /// Returns a list of all buffers tracked. May contain duplicates.
-    #[allow(clippy::pattern_type_mismatch)]
     pub fn used_tracker_indices(&self) -> impl Iterator<Item = TrackerIndex> + '_ {
         let buffers = self.buffers.lock();
         buffers
             .iter()
-            .map(|(ref b, _)| b.as_info().tracker_index())
+            .map(|v| v.buffer.as_info().tracker_index())
             .collect::<Vec<_>>()
             .into_iter()
     }
@@ -83,38 +160,78 @@ impl<A: HalApi> BufferBindGroupState<A> {
         let mut buffers = self.buffers.lock();
         buffers
             .drain(..)
-            .map(|(buffer, _u)| buffer)
+            .map(|v| v.buffer)
             .collect::<Vec<_>>()
             .into_iter()
     }
 
-    /// Adds the given resource with the given state.
-    pub fn add_single<'a>(
+    /// Adds the given resource with the given state, for the given range of
+    /// the buffer.
+    pub fn add_single(
         &self,
-        storage: &'a Storage<Buffer<A>>,
-        id: BufferId,
+        buffer: &Arc<Buffer<A>>,
+        range: Range<BufferAddress>,
         state: BufferUses,
-    ) -> Option<&'a Arc<Buffer<A>>> {
-        let buffer = storage.get(id).ok()?;
-
+    ) {
         let mut buffers = self.buffers.lock();
-        buffers.push((buffer.clone(), state));
+        buffers.push(BufferBindGroupStateData {
+            buffer: buffer.clone(),
+            range,
+            usage: state,
+        });
+    }
+}
 
-        Some(buffer)
+/// Container for corresponding simple and complex buffer states.
+#[derive(Debug)]
+pub(crate) struct BufferStateSet {
+    simple: Vec<BufferUses>,
+    complex: FastHashMap<usize, ComplexBufferState>,
+}
+
+impl BufferStateSet {
+    fn new() -> Self {
+        Self {
+            simple: Vec::new(),
+            complex: FastHashMap::default(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.simple.clear();
+        self.complex.clear();
+    }
+
+    fn set_size(&mut self, size: usize) {
+        self.simple.resize(size, BufferUses::empty());
+    }
+
+    fn assert_complex_in_bounds(&self, index: usize) {
+        strict_assert!(index < self.simple.len());
+        strict_assert!(if self.simple[index] == BufferUses::COMPLEX {
+            self.complex.contains_key(&index)
+        } else {
+            true
+        });
     }
 }
 
 /// Stores all buffer state within a single usage scope.
 #[derive(Debug)]
 pub(crate) struct BufferUsageScope<A: HalApi> {
-    state: Vec<BufferUses>,
+    /// The union of the uses of each buffer, which has to be valid as
+    /// usage scope rules apply to buffers as a whole.
+    uses: Vec<BufferUses>,
+    /// The uses of each range of the buffers.
+    set: BufferStateSet,
     metadata: ResourceMetadata<Buffer<A>>,
 }
 
 impl<A: HalApi> Default for BufferUsageScope<A> {
     fn default() -> Self {
         Self {
-            state: Vec::new(),
+            uses: Vec::new(),
+            set: BufferStateSet::new(),
             metadata: ResourceMetadata::new(),
         }
     }
@@ -122,11 +239,15 @@ impl<A: HalApi> Default for BufferUsageScope<A> {
 
 impl<A: HalApi> BufferUsageScope<A> {
     fn tracker_assert_in_bounds(&self, index: usize) {
-        strict_assert!(index < self.state.len());
+        strict_assert!(index < self.uses.len());
         self.metadata.tracker_assert_in_bounds(index);
+        if self.metadata.contains(index) {
+            self.set.assert_complex_in_bounds(index);
+        }
     }
     pub fn clear(&mut self) {
-        self.state.clear();
+        self.uses.clear();
+        self.set.clear();
         self.metadata.clear();
     }
 
@@ -135,13 +256,14 @@ impl<A: HalApi> BufferUsageScope<A> {
     /// Must be called with the highest possible Buffer ID before
     /// all unsafe functions are called.
     pub fn set_size(&mut self, size: usize) {
-        self.state.resize(size, BufferUses::empty());
+        self.uses.resize(size, BufferUses::empty());
+        self.set.set_size(size);
         self.metadata.set_size(size);
     }
 
     /// Extend the vectors to let the given index be valid.
     fn allow_index(&mut self, index: usize) {
-        if index >= self.state.len() {
+        if index >= self.uses.len() {
             self.set_size(index + 1);
         }
     }
@@ -149,7 +271,8 @@ impl<A: HalApi> BufferUsageScope<A> {
     /// Drains all buffers tracked.
     pub fn drain_resources(&mut self) -> impl Iterator<Item = Arc<Buffer<A>>> + '_ {
         let resources = self.metadata.drain_resources();
-        self.state.clear();
+        self.uses.clear();
+        self.set.clear();
         resources.into_iter()
     }
 
@@ -170,19 +293,22 @@ impl<A: HalApi> BufferUsageScope<A> {
         bind_group: &BufferBindGroupState<A>,
     ) -> Result<(), UsageConflict> {
         let buffers = bind_group.buffers.lock();
-        for &(ref resource, state) in &*buffers {
-            let index = resource.as_info().tracker_index().as_usize();
+        for b in &*buffers {
+            let index = b.buffer.as_info().tracker_index().as_usize();
 
             unsafe {
                 insert_or_merge(
-                    None,
-                    &mut self.state,
+                    b.buffer.size,
+                    &mut self.uses,
+                    &mut self.set,
                     &mut self.metadata,
-                    index as _,
                     index,
-                    BufferStateProvider::Direct { state },
+                    BufferStateProvider::Range {
+                        range: b.range.clone(),
+                        state: b.usage,
+                    },
                     ResourceMetadataProvider::Direct {
-                        resource: Cow::Borrowed(resource),
+                        resource: Cow::Borrowed(&b.buffer),
                     },
                 )?
             };
@@ -199,8 +325,8 @@ impl<A: HalApi> BufferUsageScope<A> {
     /// If the given tracker uses IDs higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
     pub fn merge_usage_scope(&mut self, scope: &Self) -> Result<(), UsageConflict> {
-        let incoming_size = scope.state.len();
-        if incoming_size > self.state.len() {
+        let incoming_size = scope.uses.len();
+        if incoming_size > self.uses.len() {
             self.set_size(incoming_size);
         }
 
@@ -209,15 +335,14 @@ impl<A: HalApi> BufferUsageScope<A> {
             scope.tracker_assert_in_bounds(index);
 
             unsafe {
+                let buffer_size = scope.metadata.get_resource_unchecked(index).size;
                 insert_or_merge(
-                    None,
-                    &mut self.state,
+                    buffer_size,
+                    &mut self.uses,
+                    &mut self.set,
                     &mut self.metadata,
-                    index as u32,
                     index,
-                    BufferStateProvider::Indirect {
-                        state: &scope.state,
-                    },
+                    BufferStateProvider::Indirect { set: &scope.set },
                     ResourceMetadataProvider::Indirect {
                         metadata: &scope.metadata,
                     },
@@ -228,37 +353,44 @@ impl<A: HalApi> BufferUsageScope<A> {
         Ok(())
     }
 
-    /// Merge a single state into the UsageScope.
+    /// Merge a single state for the given range of a buffer into the UsageScope.
     ///
     /// If the resulting state is invalid, returns a usage
     /// conflict with the details of the invalid state.
     ///
+    /// The range is clamped to the size of the buffer.
+    ///
     /// If the ID is higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
     pub fn merge_single<'a>(
         &mut self,
         storage: &'a Storage<Buffer<A>>,
         id: BufferId,
+        range: Range<BufferAddress>,
         new_state: BufferUses,
     ) -> Result<&'a Arc<Buffer<A>>, UsageConflict> {
         let buffer = storage
             .get(id)
             .map_err(|_| UsageConflict::BufferInvalid { id })?;
 
-        self.insert_merge_single(buffer.clone(), new_state)
+        self.insert_merge_single(buffer.clone(), range, new_state)
             .map(|_| buffer)
     }
 
-    /// Merge a single state into the UsageScope, using an already resolved buffer.
+    /// Merge a single state for the given range of an already resolved buffer
+    /// into the UsageScope.
     ///
     /// If the resulting state is invalid, returns a usage
     /// conflict with the details of the invalid state.
     ///
+    /// The range is clamped to the size of the buffer.
+    ///
     /// If the ID is higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
     pub fn insert_merge_single(
         &mut self,
         buffer: Arc<Buffer<A>>,
+        range: Range<BufferAddress>,
         new_state: BufferUses,
     ) -> Result<(), UsageConflict> {
         let index = buffer.info.tracker_index().as_usize();
@@ -269,12 +401,15 @@ impl<A: HalApi> BufferUsageScope<A> {
 
         unsafe {
             insert_or_merge(
-                None,
-                &mut self.state,
+                buffer.size,
+                &mut self.uses,
+                &mut self.set,
                 &mut self.metadata,
-                index as _,
                 index,
-                BufferStateProvider::Direct { state: new_state },
+                BufferStateProvider::Range {
+                    range,
+                    state: new_state,
+                },
                 ResourceMetadataProvider::Direct {
                     resource: Cow::Owned(buffer),
                 },
@@ -285,13 +420,17 @@ impl<A: HalApi> BufferUsageScope<A> {
     }
 }
 
+/// The transitions needed to set the state of a single buffer.
+///
+/// Setting the state of a range of a buffer takes at most one transition,
+/// unless the range is in different states.
 pub(crate) type SetSingleResult<A> =
-    Option<(Arc<Buffer<A>>, Option<PendingTransition<BufferUses>>)>;
+    Option<(Arc<Buffer<A>>, SmallVec<[PendingTransition<BufferUses>; 1]>)>;
 
 /// Stores all buffer state within a command buffer or device.
 pub(crate) struct BufferTracker<A: HalApi> {
-    start: Vec<BufferUses>,
-    end: Vec<BufferUses>,
+    start_set: BufferStateSet,
+    end_set: BufferStateSet,
 
     metadata: ResourceMetadata<Buffer<A>>,
 
@@ -334,6 +473,8 @@ impl<A: HalApi> ResourceTracker for BufferTracker<A> {
                 //RefCount 2 means that resource is hold just by DeviceTracker and this suspected resource itself
                 //so it's already been released from user and so it's not inside Registry\Storage
                 if existing_ref_count <= 2 {
+                    self.start_set.complex.remove(&index);
+                    self.end_set.complex.remove(&index);
                     self.metadata.remove(index);
                     return true;
                 }
@@ -348,8 +489,8 @@ impl<A: HalApi> ResourceTracker for BufferTracker<A> {
 impl<A: HalApi> BufferTracker<A> {
     pub fn new() -> Self {
         Self {
-            start: Vec::new(),
-            end: Vec::new(),
+            start_set: BufferStateSet::new(),
+            end_set: BufferStateSet::new(),
 
             metadata: ResourceMetadata::new(),
 
@@ -358,9 +499,13 @@ impl<A: HalApi> BufferTracker<A> {
     }
 
     fn tracker_assert_in_bounds(&self, index: usize) {
-        strict_assert!(index < self.start.len());
-        strict_assert!(index < self.end.len());
+        strict_assert!(index < self.start_set.simple.len());
+        strict_assert!(index < self.end_set.simple.len());
         self.metadata.tracker_assert_in_bounds(index);
+        if self.metadata.contains(index) {
+            self.start_set.assert_complex_in_bounds(index);
+            self.end_set.assert_complex_in_bounds(index);
+        }
     }
 
     /// Sets the size of all the vectors inside the tracker.
@@ -368,15 +513,15 @@ impl<A: HalApi> BufferTracker<A> {
     /// Must be called with the highest possible Buffer ID before
     /// all unsafe functions are called.
     pub fn set_size(&mut self, size: usize) {
-        self.start.resize(size, BufferUses::empty());
-        self.end.resize(size, BufferUses::empty());
+        self.start_set.set_size(size);
+        self.end_set.set_size(size);
 
         self.metadata.set_size(size);
     }
 
     /// Extend the vectors to let the given index be valid.
     fn allow_index(&mut self, index: usize) {
-        if index >= self.start.len() {
+        if index >= self.start_set.simple.len() {
             self.set_size(index + 1);
         }
     }
@@ -419,8 +564,9 @@ impl<A: HalApi> BufferTracker<A> {
             }
 
             insert(
-                Some(&mut self.start),
-                &mut self.end,
+                resource.size,
+                Some(&mut self.start_set),
+                &mut self.end_set,
                 &mut self.metadata,
                 index,
                 BufferStateProvider::Direct { state },
@@ -432,14 +578,21 @@ impl<A: HalApi> BufferTracker<A> {
         }
     }
 
-    /// Sets the state of a single buffer.
+    /// Sets the state of the given range of a single buffer.
+    ///
+    /// If transitions are needed to get the range into the given state, those
+    /// transitions are returned.
     ///
-    /// If a transition is needed to get the buffer into the given state, that transition
-    /// is returned. No more than one transition is needed.
+    /// The range is clamped to the size of the buffer.
     ///
     /// If the ID is higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
-    pub fn set_single(&mut self, buffer: &Arc<Buffer<A>>, state: BufferUses) -> SetSingleResult<A> {
+    pub fn set_single(
+        &mut self,
+        buffer: &Arc<Buffer<A>>,
+        range: Range<BufferAddress>,
+        state: BufferUses,
+    ) -> SetSingleResult<A> {
         let index: usize = buffer.as_info().tracker_index().as_usize();
 
         self.allow_index(index);
@@ -448,11 +601,12 @@ impl<A: HalApi> BufferTracker<A> {
 
         unsafe {
             insert_or_barrier_update(
-                Some(&mut self.start),
-                &mut self.end,
+                buffer.size,
+                Some(&mut self.start_set),
+                &mut self.end_set,
                 &mut self.metadata,
                 index,
-                BufferStateProvider::Direct { state },
+                BufferStateProvider::Range { range, state },
                 None,
                 ResourceMetadataProvider::Direct {
                     resource: Cow::Owned(buffer.clone()),
@@ -461,9 +615,7 @@ impl<A: HalApi> BufferTracker<A> {
             )
         };
 
-        strict_assert!(self.temp.len() <= 1);
-
-        Some((buffer.clone(), self.temp.pop()))
+        Some((buffer.clone(), self.temp.drain(..).collect()))
     }
 
     /// Sets the given state for all buffers in the given tracker.
@@ -475,8 +627,8 @@ impl<A: HalApi> BufferTracker<A> {
     /// If the ID is higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
     pub fn set_from_tracker(&mut self, tracker: &Self) {
-        let incoming_size = tracker.start.len();
-        if incoming_size > self.start.len() {
+        let incoming_size = tracker.start_set.simple.len();
+        if incoming_size > self.start_set.simple.len() {
             self.set_size(incoming_size);
         }
 
@@ -484,16 +636,18 @@ impl<A: HalApi> BufferTracker<A> {
             self.tracker_assert_in_bounds(index);
             tracker.tracker_assert_in_bounds(index);
             unsafe {
+                let buffer_size = tracker.metadata.get_resource_unchecked(index).size;
                 insert_or_barrier_update(
-                    Some(&mut self.start),
-                    &mut self.end,
+                    buffer_size,
+                    Some(&mut self.start_set),
+                    &mut self.end_set,
                     &mut self.metadata,
                     index,
                     BufferStateProvider::Indirect {
-                        state: &tracker.start,
+                        set: &tracker.start_set,
                     },
                     Some(BufferStateProvider::Indirect {
-                        state: &tracker.end,
+                        set: &tracker.end_set,
                     }),
                     ResourceMetadataProvider::Indirect {
                         metadata: &tracker.metadata,
@@ -513,8 +667,8 @@ impl<A: HalApi> BufferTracker<A> {
     /// If the ID is higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
     pub fn set_from_usage_scope(&mut self, scope: &BufferUsageScope<A>) {
-        let incoming_size = scope.state.len();
-        if incoming_size > self.start.len() {
+        let incoming_size = scope.uses.len();
+        if incoming_size > self.start_set.simple.len() {
             self.set_size(incoming_size);
         }
 
@@ -522,14 +676,14 @@ impl<A: HalApi> BufferTracker<A> {
             self.tracker_assert_in_bounds(index);
             scope.tracker_assert_in_bounds(index);
             unsafe {
+                let buffer_size = scope.metadata.get_resource_unchecked(index).size;
                 insert_or_barrier_update(
-                    Some(&mut self.start),
-                    &mut self.end,
+                    buffer_size,
+                    Some(&mut self.start_set),
+                    &mut self.end_set,
                     &mut self.metadata,
                     index,
-                    BufferStateProvider::Indirect {
-                        state: &scope.state,
-                    },
+                    BufferStateProvider::Indirect { set: &scope.set },
                     None,
                     ResourceMetadataProvider::Indirect {
                         metadata: &scope.metadata,
@@ -563,8 +717,8 @@ impl<A: HalApi> BufferTracker<A> {
         scope: &mut BufferUsageScope<A>,
         index_source: impl IntoIterator<Item = TrackerIndex>,
     ) {
-        let incoming_size = scope.state.len();
-        if incoming_size > self.start.len() {
+        let incoming_size = scope.uses.len();
+        if incoming_size > self.start_set.simple.len() {
             self.set_size(incoming_size);
         }
 
@@ -577,14 +731,14 @@ impl<A: HalApi> BufferTracker<A> {
                 continue;
             }
             unsafe {
+                let buffer_size = scope.metadata.get_resource_unchecked(index).size;
                 insert_or_barrier_update(
-                    Some(&mut self.start),
-                    &mut self.end,
+                    buffer_size,
+                    Some(&mut self.start_set),
+                    &mut self.end_set,
                     &mut self.metadata,
                     index,
-                    BufferStateProvider::Indirect {
-                        state: &scope.state,
-                    },
+                    BufferStateProvider::Indirect { set: &scope.set },
                     None,
                     ResourceMetadataProvider::Indirect {
                         metadata: &scope.metadata,
@@ -618,22 +772,69 @@ impl<A: HalApi> BufferTracker<A> {
 enum BufferStateProvider<'a> {
     /// Get a state that was provided directly.
     Direct { state: BufferUses },
-    /// Get a state from an an array of states.
-    Indirect { state: &'a [BufferUses] },
+    /// Get a state that was provided directly for a range of the buffer.
+    Range {
+        range: Range<BufferAddress>,
+        state: BufferUses,
+    },
+    /// Get a state from a buffer set.
+    Indirect { set: &'a BufferStateSet },
 }
-impl BufferStateProvider<'_> {
+impl<'a> BufferStateProvider<'a> {
     /// Gets the state from the provider, given a resource ID index.
     ///
     /// # Safety
     ///
     /// Index must be in bounds for the indirect source iff this is in the indirect state.
     #[inline(always)]
-    unsafe fn get_state(&self, index: usize) -> BufferUses {
-        match *self {
-            BufferStateProvider::Direct { state } => state,
-            BufferStateProvider::Indirect { state } => {
-                strict_assert!(index < state.len());
-                *unsafe { state.get_unchecked(index) }
+    unsafe fn get_state(
+        self,
+        buffer_size: BufferAddress,
+        index: usize,
+    ) -> SingleOrManyStates<
+        BufferUses,
+        impl Iterator<Item = (Range<BufferAddress>, BufferUses)> + Clone + 'a,
+    > {
+        match self {
+            BufferStateProvider::Direct { state } => SingleOrManyStates::Single(state),
+            BufferStateProvider::Range { range, state } => {
+                // Ranges covering the whole buffer are promoted to simple
+                // states, as are empty ranges, which are rare enough that
+                // we don't bother tracking them.
+                let range = range.start.min(buffer_size)..range.end.min(buffer_size);
+                if range.is_empty() || range == (0..buffer_size) {
+                    SingleOrManyStates::Single(state)
+                } else {
+                    SingleOrManyStates::Many(EitherIter::Left(iter::once((range, state))))
+                }
+            }
+            BufferStateProvider::Indirect { set } => {
+                strict_assert!(index < set.simple.len());
+                let new_state = *unsafe { set.simple.get_unchecked(index) };
+
+                if new_state == BufferUses::COMPLEX {
+                    let new_complex = unsafe { set.complex.get(&index).unwrap_unchecked() };
+
+                    SingleOrManyStates::Many(EitherIter::Right(new_complex.to_range_state_iter()))
+                } else {
+                    SingleOrManyStates::Single(new_state)
+                }
+            }
+        }
+    }
+
+    /// Gets the union of all the states from the provider.
+    ///
+    /// # Safety
+    ///
+    /// Index must be in bounds for the indirect source iff this is in the indirect state.
+    #[inline(always)]
+    unsafe fn get_union(self, buffer_size: BufferAddress, index: usize) -> BufferUses {
+        match unsafe { self.get_state(buffer_size, index) } {
+            SingleOrManyStates::Single(state) => state,
+            SingleOrManyStates::Many(states) => {
+                let union = states.fold(BufferUses::empty(), |union, (_, state)| union | state);
+                union - BufferUses::UNKNOWN
             }
         }
     }
@@ -650,21 +851,30 @@ impl BufferStateProvider<'_> {
 /// to this function, either directly or via metadata or provider structs.
 #[inline(always)]
 unsafe fn insert_or_merge<A: HalApi>(
-    start_states: Option<&mut [BufferUses]>,
-    current_states: &mut [BufferUses],
+    buffer_size: BufferAddress,
+    current_uses: &mut [BufferUses],
+    current_state_set: &mut BufferStateSet,
     resource_metadata: &mut ResourceMetadata<Buffer<A>>,
-    index32: u32,
     index: usize,
     state_provider: BufferStateProvider<'_>,
     metadata_provider: ResourceMetadataProvider<'_, Buffer<A>>,
 ) -> Result<(), UsageConflict> {
+    let new_uses = unsafe { state_provider.clone().get_union(buffer_size, index) };
+    let current_uses = unsafe { current_uses.get_unchecked_mut(index) };
+
     let currently_owned = unsafe { resource_metadata.contains_unchecked(index) };
 
     if !currently_owned {
+        // This should only ever happen with a wgpu bug, but let's just double
+        // check that resource states don't have any conflicts.
+        strict_assert_eq!(invalid_resource_state(new_uses), false);
+
+        *current_uses = new_uses;
         unsafe {
             insert(
-                start_states,
-                current_states,
+                buffer_size,
+                None,
+                current_state_set,
                 resource_metadata,
                 index,
                 state_provider,
@@ -675,15 +885,23 @@ unsafe fn insert_or_merge<A: HalApi>(
         return Ok(());
     }
 
-    unsafe {
-        merge(
-            current_states,
-            index32,
-            index,
-            state_provider,
-            metadata_provider,
-        )
+    let merged_uses = *current_uses | new_uses;
+
+    if invalid_resource_state(merged_uses) {
+        return Err(UsageConflict::from_buffer(
+            unsafe { metadata_provider.get_own(index).info.id() },
+            *current_uses,
+            new_uses,
+        ));
     }
+
+    log::trace!("\tbuf {index}: merge {current_uses:?} + {new_uses:?}");
+
+    *current_uses = merged_uses;
+
+    unsafe { merge(buffer_size, current_state_set, index, state_provider) };
+
+    Ok(())
 }
 
 /// If the resource isn't tracked
@@ -705,8 +923,9 @@ unsafe fn insert_or_merge<A: HalApi>(
 /// to this function, either directly or via metadata or provider structs.
 #[inline(always)]
 unsafe fn insert_or_barrier_update<A: HalApi>(
-    start_states: Option<&mut [BufferUses]>,
-    current_states: &mut [BufferUses],
+    buffer_size: BufferAddress,
+    start_state: Option<&mut BufferStateSet>,
+    current_state_set: &mut BufferStateSet,
     resource_metadata: &mut ResourceMetadata<Buffer<A>>,
     index: usize,
     start_state_provider: BufferStateProvider<'_>,
@@ -719,8 +938,9 @@ unsafe fn insert_or_barrier_update<A: HalApi>(
     if !currently_owned {
         unsafe {
             insert(
-                start_states,
-                current_states,
+                buffer_size,
+                start_state,
+                current_state_set,
                 resource_metadata,
                 index,
                 start_state_provider,
@@ -732,102 +952,696 @@ unsafe fn insert_or_barrier_update<A: HalApi>(
     }
 
     let update_state_provider = end_state_provider.unwrap_or_else(|| start_state_provider.clone());
-    unsafe { barrier(current_states, index, start_state_provider, barriers) };
+    unsafe {
+        barrier(
+            buffer_size,
+            current_state_set,
+            index,
+            start_state_provider,
+            barriers,
+        )
+    };
 
-    unsafe { update(current_states, index, update_state_provider) };
+    let start_state_set = start_state.unwrap();
+    unsafe {
+        update(
+            buffer_size,
+            start_state_set,
+            current_state_set,
+            index,
+            update_state_provider,
+        )
+    };
 }
 
 #[inline(always)]
 unsafe fn insert<A: HalApi>(
-    start_states: Option<&mut [BufferUses]>,
-    current_states: &mut [BufferUses],
+    buffer_size: BufferAddress,
+    start_state: Option<&mut BufferStateSet>,
+    end_state: &mut BufferStateSet,
     resource_metadata: &mut ResourceMetadata<Buffer<A>>,
     index: usize,
     start_state_provider: BufferStateProvider<'_>,
     end_state_provider: Option<BufferStateProvider<'_>>,
     metadata_provider: ResourceMetadataProvider<'_, Buffer<A>>,
 ) {
-    let new_start_state = unsafe { start_state_provider.get_state(index) };
-    let new_end_state =
-        end_state_provider.map_or(new_start_state, |p| unsafe { p.get_state(index) });
+    match unsafe { start_state_provider.get_state(buffer_size, index) } {
+        SingleOrManyStates::Single(state) => {
+            // This should only ever happen with a wgpu bug, but let's just double
+            // check that resource states don't have any conflicts.
+            strict_assert_eq!(invalid_resource_state(state), false);
 
-    // This should only ever happen with a wgpu bug, but let's just double
-    // check that resource states don't have any conflicts.
-    strict_assert_eq!(invalid_resource_state(new_start_state), false);
-    strict_assert_eq!(invalid_resource_state(new_end_state), false);
+            log::trace!("\tbuf {index}: insert start {state:?}");
 
-    log::trace!("\tbuf {index}: insert {new_start_state:?}..{new_end_state:?}");
+            if let Some(start_state) = start_state {
+                unsafe { *start_state.simple.get_unchecked_mut(index) = state };
+            }
 
-    unsafe {
-        if let Some(&mut ref mut start_state) = start_states {
-            *start_state.get_unchecked_mut(index) = new_start_state;
+            // We only need to insert ourselves the end state if there is no end state provider.
+            if end_state_provider.is_none() {
+                unsafe { *end_state.simple.get_unchecked_mut(index) = state };
+            }
+        }
+        SingleOrManyStates::Many(state_iter) => {
+            let complex = ComplexBufferState::from_range_state_iter(buffer_size, state_iter);
+
+            log::trace!("\tbuf {index}: insert start {complex:?}");
+
+            if let Some(start_state) = start_state {
+                unsafe { *start_state.simple.get_unchecked_mut(index) = BufferUses::COMPLEX };
+                start_state.complex.insert(index, complex.clone());
+            }
+
+            // We only need to insert ourselves the end state if there is no end state provider.
+            if end_state_provider.is_none() {
+                unsafe { *end_state.simple.get_unchecked_mut(index) = BufferUses::COMPLEX };
+                end_state.complex.insert(index, complex);
+            }
         }
-        *current_states.get_unchecked_mut(index) = new_end_state;
+    }
+
+    if let Some(end_state_provider) = end_state_provider {
+        match unsafe { end_state_provider.get_state(buffer_size, index) } {
+            SingleOrManyStates::Single(state) => {
+                // This should only ever happen with a wgpu bug, but let's just double
+                // check that resource states don't have any conflicts.
+                strict_assert_eq!(invalid_resource_state(state), false);
+
+                log::trace!("\tbuf {index}: insert end {state:?}");
+
+                // We only need to insert into the end, as there is guaranteed to be
+                // a start state provider.
+                unsafe { *end_state.simple.get_unchecked_mut(index) = state };
+            }
+            SingleOrManyStates::Many(state_iter) => {
+                let complex = ComplexBufferState::from_range_state_iter(buffer_size, state_iter);
 
+                log::trace!("\tbuf {index}: insert end {complex:?}");
+
+                // We only need to insert into the end, as there is guaranteed to be
+                // a start state provider.
+                unsafe { *end_state.simple.get_unchecked_mut(index) = BufferUses::COMPLEX };
+                end_state.complex.insert(index, complex);
+            }
+        }
+    }
+
+    unsafe {
         let resource = metadata_provider.get_own(index);
         resource_metadata.insert(index, resource);
     }
 }
 
+/// Merges the given state into the current state of each range.
+///
+/// Unlike texture merges, this doesn't check for usage conflicts, as
+/// [`insert_or_merge`] checks them for the buffer as a whole.
 #[inline(always)]
-unsafe fn merge<A: HalApi>(
-    current_states: &mut [BufferUses],
-    index32: u32,
+unsafe fn merge(
+    buffer_size: BufferAddress,
+    current_state_set: &mut BufferStateSet,
     index: usize,
     state_provider: BufferStateProvider<'_>,
-    metadata_provider: ResourceMetadataProvider<'_, Buffer<A>>,
-) -> Result<(), UsageConflict> {
-    let current_state = unsafe { current_states.get_unchecked_mut(index) };
-    let new_state = unsafe { state_provider.get_state(index) };
+) {
+    let current_simple = unsafe { current_state_set.simple.get_unchecked_mut(index) };
+    let current_state = if *current_simple == BufferUses::COMPLEX {
+        SingleOrManyStates::Many(unsafe {
+            current_state_set.complex.get_mut(&index).unwrap_unchecked()
+        })
+    } else {
+        SingleOrManyStates::Single(current_simple)
+    };
+
+    let new_state = unsafe { state_provider.get_state(buffer_size, index) };
+
+    match (current_state, new_state) {
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Single(new_simple)) => {
+            *current_simple |= new_simple;
+        }
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Many(new_many)) => {
+            // Because we are now demoting this simple state to a complex state,
+            // we actually need to make a whole new complex state for us to use
+            // as there wasn't one before.
+            let mut new_complex = ComplexBufferState::new(buffer_size, *current_simple);
+
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    continue;
+                }
 
-    let merged_state = *current_state | new_state;
+                log::trace!("\tbuf {index}: merge {range:?} {current_simple:?} + {new_state:?}");
 
-    if invalid_resource_state(merged_state) {
-        return Err(UsageConflict::from_buffer(
-            unsafe { metadata_provider.get_own(index).info.id() },
-            *current_state,
-            new_state,
-        ));
-    }
+                for &mut (_, ref mut current_range_state) in
+                    new_complex.ranges.isolate(&range, BufferUses::UNKNOWN)
+                {
+                    *current_range_state |= new_state;
+                }
+            }
+            new_complex.ranges.coalesce();
 
-    log::trace!("\tbuf {index32}: merge {current_state:?} + {new_state:?}");
+            *current_simple = BufferUses::COMPLEX;
+            current_state_set.complex.insert(index, new_complex);
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Single(new_simple)) => {
+            for &mut (ref range, ref mut current_range_state) in current_complex.ranges.iter_mut() {
+                // Once we remove unknown, this will never be empty, as
+                // simple states are never unknown.
+                let merged_state = (*current_range_state | new_simple) - BufferUses::UNKNOWN;
 
-    *current_state = merged_state;
+                log::trace!(
+                    "\tbuf {index}: merge {range:?} {current_range_state:?} + {new_simple:?}"
+                );
 
-    Ok(())
+                *current_range_state = merged_state;
+            }
+            current_complex.ranges.coalesce();
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Many(new_many)) => {
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    continue;
+                }
+
+                for &mut (ref range, ref mut current_range_state) in
+                    current_complex.ranges.isolate(&range, BufferUses::UNKNOWN)
+                {
+                    let merged_state = (*current_range_state | new_state) - BufferUses::UNKNOWN;
+
+                    log::trace!(
+                        "\tbuf {index}: merge {range:?} {current_range_state:?} + {new_state:?}"
+                    );
+
+                    *current_range_state = merged_state;
+                }
+            }
+            current_complex.ranges.coalesce();
+        }
+    }
 }
 
 #[inline(always)]
 unsafe fn barrier(
-    current_states: &mut [BufferUses],
+    buffer_size: BufferAddress,
+    current_state_set: &BufferStateSet,
     index: usize,
     state_provider: BufferStateProvider<'_>,
     barriers: &mut Vec<PendingTransition<BufferUses>>,
 ) {
-    let current_state = unsafe { *current_states.get_unchecked(index) };
-    let new_state = unsafe { state_provider.get_state(index) };
+    let current_simple = unsafe { *current_state_set.simple.get_unchecked(index) };
+    let current_state = if current_simple == BufferUses::COMPLEX {
+        SingleOrManyStates::Many(unsafe {
+            current_state_set.complex.get(&index).unwrap_unchecked()
+        })
+    } else {
+        SingleOrManyStates::Single(current_simple)
+    };
+
+    let new_state = unsafe { state_provider.get_state(buffer_size, index) };
+
+    match (current_state, new_state) {
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Single(new_simple)) => {
+            if skip_barrier(current_simple, new_simple) {
+                return;
+            }
 
-    if skip_barrier(current_state, new_state) {
-        return;
-    }
+            log::trace!("\tbuf {index}: transition {current_simple:?} -> {new_simple:?}");
+
+            barriers.push(PendingTransition {
+                id: index as _,
+                selector: 0..buffer_size,
+                usage: current_simple..new_simple,
+            });
+        }
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Many(new_many)) => {
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    continue;
+                }
+
+                if skip_barrier(current_simple, new_state) {
+                    continue;
+                }
 
-    barriers.push(PendingTransition {
-        id: index as _,
-        selector: (),
-        usage: current_state..new_state,
-    });
+                log::trace!(
+                    "\tbuf {index}: transition {range:?} {current_simple:?} -> {new_state:?}"
+                );
 
-    log::trace!("\tbuf {index}: transition {current_state:?} -> {new_state:?}");
+                barriers.push(PendingTransition {
+                    id: index as _,
+                    selector: range,
+                    usage: current_simple..new_state,
+                });
+            }
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Single(new_simple)) => {
+            for &(ref range, current_range_state) in current_complex.ranges.iter() {
+                if current_range_state == BufferUses::UNKNOWN {
+                    continue;
+                }
+
+                if skip_barrier(current_range_state, new_simple) {
+                    continue;
+                }
+
+                log::trace!(
+                    "\tbuf {index}: transition {range:?} {current_range_state:?} -> {new_simple:?}"
+                );
+
+                barriers.push(PendingTransition {
+                    id: index as _,
+                    selector: range.clone(),
+                    usage: current_range_state..new_simple,
+                });
+            }
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Many(new_many)) => {
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    continue;
+                }
+
+                for (range, current_range_state) in current_complex.ranges.iter_filter(&range) {
+                    if *current_range_state == BufferUses::UNKNOWN {
+                        continue;
+                    }
+
+                    if skip_barrier(*current_range_state, new_state) {
+                        continue;
+                    }
+
+                    log::trace!(
+                        "\tbuf {index}: transition {range:?} {current_range_state:?} -> {new_state:?}"
+                    );
+
+                    barriers.push(PendingTransition {
+                        id: index as _,
+                        selector: range,
+                        usage: *current_range_state..new_state,
+                    });
+                }
+            }
+        }
+    }
 }
 
+#[allow(clippy::needless_option_as_deref)] // we use this for reborrowing Option<&mut T>
 #[inline(always)]
 unsafe fn update(
-    current_states: &mut [BufferUses],
+    buffer_size: BufferAddress,
+    start_state_set: &mut BufferStateSet,
+    current_state_set: &mut BufferStateSet,
     index: usize,
     state_provider: BufferStateProvider<'_>,
 ) {
-    let current_state = unsafe { current_states.get_unchecked_mut(index) };
-    let new_state = unsafe { state_provider.get_state(index) };
+    let start_simple = unsafe { *start_state_set.simple.get_unchecked(index) };
+
+    // We only ever need to update the start state here if the state is complex.
+    //
+    // If the state is simple, the first insert to the tracker would cover it.
+    let mut start_complex = None;
+    if start_simple == BufferUses::COMPLEX {
+        start_complex = Some(unsafe { start_state_set.complex.get_mut(&index).unwrap_unchecked() });
+    }
 
-    *current_state = new_state;
+    let current_simple = unsafe { current_state_set.simple.get_unchecked_mut(index) };
+    let current_state = if *current_simple == BufferUses::COMPLEX {
+        SingleOrManyStates::Many(unsafe {
+            current_state_set.complex.get_mut(&index).unwrap_unchecked()
+        })
+    } else {
+        SingleOrManyStates::Single(current_simple)
+    };
+
+    let new_state = unsafe { state_provider.get_state(buffer_size, index) };
+
+    match (current_state, new_state) {
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Single(new_simple)) => {
+            *current_simple = new_simple;
+        }
+        (SingleOrManyStates::Single(current_simple), SingleOrManyStates::Many(new_many)) => {
+            // Because we are now demoting this simple state to a complex state,
+            // we actually need to make a whole new complex state for us to use
+            // as there wasn't one before.
+            let mut new_complex = ComplexBufferState::new(buffer_size, *current_simple);
+
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    continue;
+                }
+
+                for &mut (_, ref mut current_range_state) in
+                    new_complex.ranges.isolate(&range, BufferUses::UNKNOWN)
+                {
+                    *current_range_state = new_state;
+                }
+            }
+            new_complex.ranges.coalesce();
+
+            *current_simple = BufferUses::COMPLEX;
+            current_state_set.complex.insert(index, new_complex);
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Single(new_single)) => {
+            for &(ref range, current_range_state) in current_complex.ranges.iter() {
+                // If this state is unknown, that means that the start is _also_ unknown.
+                if current_range_state == BufferUses::UNKNOWN {
+                    if let Some(&mut ref mut start_complex) = start_complex {
+                        for &mut (_, ref mut current_start_state) in
+                            start_complex.ranges.isolate(range, BufferUses::UNKNOWN)
+                        {
+                            strict_assert_eq!(*current_start_state, BufferUses::UNKNOWN);
+                            *current_start_state = new_single;
+                        }
+
+                        start_complex.ranges.coalesce();
+                    }
+                }
+            }
+
+            unsafe { *current_state_set.simple.get_unchecked_mut(index) = new_single };
+            unsafe { current_state_set.complex.remove(&index).unwrap_unchecked() };
+        }
+        (SingleOrManyStates::Many(current_complex), SingleOrManyStates::Many(new_many)) => {
+            for (range, new_state) in new_many {
+                if new_state == BufferUses::UNKNOWN {
+                    // We know nothing new
+                    continue;
+                }
+
+                for &mut (ref range, ref mut current_range_state) in
+                    current_complex.ranges.isolate(&range, BufferUses::UNKNOWN)
+                {
+                    if *current_range_state == BufferUses::UNKNOWN {
+                        // We now know something about this range that
+                        // we didn't before so we should go back and update
+                        // the start state.
+                        //
+                        // We know we must have starter state be complex,
+                        // otherwise we would know about this state.
+                        strict_assert!(start_complex.is_some());
+
+                        let start_complex =
+                            unsafe { start_complex.as_deref_mut().unwrap_unchecked() };
+
+                        for &mut (_, ref mut current_start_state) in
+                            start_complex.ranges.isolate(range, BufferUses::UNKNOWN)
+                        {
+                            strict_assert_eq!(*current_start_state, BufferUses::UNKNOWN);
+                            *current_start_state = new_state;
+                        }
+
+                        start_complex.ranges.coalesce();
+                    }
+
+                    *current_range_state = new_state;
+                }
+            }
+            current_complex.ranges.coalesce();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SIZE: BufferAddress = 256;
+
+    /// A set tracking a single buffer, at index 0, in the given state.
+    fn simple_set(state: BufferUses) -> BufferStateSet {
+        let mut set = BufferStateSet::new();
+        set.set_size(1);
+        set.simple[0] = state;
+        set
+    }
+
+    /// A set tracking a single buffer, at index 0, with the given ranges.
+    fn complex_set(ranges: &[(Range<BufferAddress>, BufferUses)]) -> BufferStateSet {
+        let mut set = simple_set(BufferUses::COMPLEX);
+        set.complex.insert(
+            0,
+            ComplexBufferState {
+                ranges: RangedStates::from_slice(ranges),
+            },
+        );
+        set
+    }
+
+    fn ranges(set: &BufferStateSet) -> Vec<(Range<BufferAddress>, BufferUses)> {
+        assert_eq!(set.simple[0], BufferUses::COMPLEX);
+        set.complex[&0].to_range_state_iter().collect()
+    }
+
+    fn range(range: Range<BufferAddress>, state: BufferUses) -> BufferStateProvider<'static> {
+        BufferStateProvider::Range { range, state }
+    }
+
+    fn barriers(
+        set: &BufferStateSet,
+        provider: BufferStateProvider<'_>,
+    ) -> Vec<PendingTransition<BufferUses>> {
+        let mut barriers = Vec::new();
+        unsafe { barrier(SIZE, set, 0, provider, &mut barriers) };
+        barriers
+    }
+
+    fn transition(
+        selector: Range<BufferAddress>,
+        usage: Range<BufferUses>,
+    ) -> PendingTransition<BufferUses> {
+        PendingTransition {
+            id: 0,
+            selector,
+            usage,
+        }
+    }
+
+    #[test]
+    fn whole_buffer_ranges_are_simple() {
+        for bounds in [0..SIZE, 0..SIZE * 2, 64..64, SIZE..SIZE * 2] {
+            match unsafe { range(bounds.clone(), BufferUses::UNIFORM).get_state(SIZE, 0) } {
+                SingleOrManyStates::Single(state) => assert_eq!(state, BufferUses::UNIFORM),
+                SingleOrManyStates::Many(_) => panic!("{bounds:?} is tracked as complex"),
+            }
+        }
+        match unsafe { range(64..SIZE * 2, BufferUses::UNIFORM).get_state(SIZE, 0) } {
+            SingleOrManyStates::Single(_) => panic!("part of the buffer is tracked as simple"),
+            SingleOrManyStates::Many(states) => {
+                assert_eq!(
+                    states.collect::<Vec<_>>(),
+                    [(64..SIZE, BufferUses::UNIFORM)]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn merge_overlapping_ranges() {
+        let mut set = simple_set(BufferUses::COPY_SRC);
+        unsafe { merge(SIZE, &mut set, 0, range(64..128, BufferUses::INDEX)) };
+        assert_eq!(
+            ranges(&set),
+            [
+                (0..64, BufferUses::COPY_SRC),
+                (64..128, BufferUses::COPY_SRC | BufferUses::INDEX),
+                (128..SIZE, BufferUses::COPY_SRC),
+            ]
+        );
+
+        unsafe { merge(SIZE, &mut set, 0, range(96..192, BufferUses::VERTEX)) };
+        assert_eq!(
+            ranges(&set),
+            [
+                (0..64, BufferUses::COPY_SRC),
+                (64..96, BufferUses::COPY_SRC | BufferUses::INDEX),
+                (
+                    96..128,
+                    BufferUses::COPY_SRC | BufferUses::INDEX | BufferUses::VERTEX
+                ),
+                (128..192, BufferUses::COPY_SRC | BufferUses::VERTEX),
+                (192..SIZE, BufferUses::COPY_SRC),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_into_unknown_ranges() {
+        let mut set = complex_set(&[
+            (0..128, BufferUses::UNIFORM),
+            (128..SIZE, BufferUses::UNKNOWN),
+        ]);
+        unsafe { merge(SIZE, &mut set, 0, range(64..192, BufferUses::VERTEX)) };
+        assert_eq!(
+            ranges(&set),
+            [
+                (0..64, BufferUses::UNIFORM),
+                (64..128, BufferUses::UNIFORM | BufferUses::VERTEX),
+                (128..192, BufferUses::VERTEX),
+                (192..SIZE, BufferUses::UNKNOWN),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_adjacent_ranges() {
+        let mut set = complex_set(&[
+            (0..128, BufferUses::UNIFORM),
+            (128..SIZE, BufferUses::UNKNOWN),
+        ]);
+        unsafe { merge(SIZE, &mut set, 0, range(128..SIZE, BufferUses::UNIFORM)) };
+        assert_eq!(ranges(&set), [(0..SIZE, BufferUses::UNIFORM)]);
+    }
+
+    #[test]
+    fn barrier_whole_buffer() {
+        let set = simple_set(BufferUses::COPY_DST);
+        assert_eq!(
+            barriers(&set, range(0..SIZE, BufferUses::UNIFORM)),
+            [transition(
+                0..SIZE,
+                BufferUses::COPY_DST..BufferUses::UNIFORM
+            )]
+        );
+        assert_eq!(
+            barriers(
+                &set,
+                BufferStateProvider::Direct {
+                    state: BufferUses::UNIFORM
+                }
+            ),
+            [transition(
+                0..SIZE,
+                BufferUses::COPY_DST..BufferUses::UNIFORM
+            )]
+        );
+    }
+
+    #[test]
+    fn barrier_ranges() {
+        let set = simple_set(BufferUses::COPY_DST);
+        assert_eq!(
+            barriers(&set, range(0..64, BufferUses::UNIFORM)),
+            [transition(0..64, BufferUses::COPY_DST..BufferUses::UNIFORM)]
+        );
+
+        // Only the parts of the range that change state get barriers.
+        let set = complex_set(&[
+            (0..128, BufferUses::COPY_DST),
+            (128..SIZE, BufferUses::UNIFORM),
+        ]);
+        assert_eq!(
+            barriers(&set, range(64..192, BufferUses::UNIFORM)),
+            [transition(
+                64..128,
+                BufferUses::COPY_DST..BufferUses::UNIFORM
+            )]
+        );
+
+        // A whole buffer use after ranged uses transitions each range.
+        assert_eq!(
+            barriers(&set, range(0..SIZE, BufferUses::COPY_SRC)),
+            [
+                transition(0..128, BufferUses::COPY_DST..BufferUses::COPY_SRC),
+                transition(128..SIZE, BufferUses::UNIFORM..BufferUses::COPY_SRC),
+            ]
+        );
+    }
+
+    #[test]
+    fn barrier_skips_unknown_ranges() {
+        let set = complex_set(&[
+            (0..128, BufferUses::COPY_DST),
+            (128..SIZE, BufferUses::UNKNOWN),
+        ]);
+        assert_eq!(
+            barriers(&set, range(0..SIZE, BufferUses::UNIFORM)),
+            [transition(
+                0..128,
+                BufferUses::COPY_DST..BufferUses::UNIFORM
+            )]
+        );
+    }
+
+    #[test]
+    fn update_ranges() {
+        let mut start = simple_set(BufferUses::COPY_DST);
+        let mut current = simple_set(BufferUses::COPY_DST);
+        unsafe {
+            update(
+                SIZE,
+                &mut start,
+                &mut current,
+                0,
+                range(0..128, BufferUses::STORAGE_READ_WRITE),
+            )
+        };
+        assert_eq!(
+            ranges(&current),
+            [
+                (0..128, BufferUses::STORAGE_READ_WRITE),
+                (128..SIZE, BufferUses::COPY_DST),
+            ]
+        );
+
+        // Setting the range back merges it with its neighbour.
+        unsafe {
+            update(
+                SIZE,
+                &mut start,
+                &mut current,
+                0,
+                range(0..128, BufferUses::COPY_DST),
+            )
+        };
+        assert_eq!(ranges(&current), [(0..SIZE, BufferUses::COPY_DST)]);
+
+        // A whole buffer use makes the state simple again.
+        unsafe {
+            update(
+                SIZE,
+                &mut start,
+                &mut current,
+                0,
+                range(0..SIZE, BufferUses::UNIFORM),
+            )
+        };
+        assert_eq!(current.simple[0], BufferUses::UNIFORM);
+        assert!(current.complex.is_empty());
+        assert_eq!(start.simple[0], BufferUses::COPY_DST);
+    }
+
+    #[test]
+    fn update_fills_unknown_start_ranges() {
+        let unknown = [
+            (0..128, BufferUses::COPY_DST),
+            (128..SIZE, BufferUses::UNKNOWN),
+        ];
+        let mut start = complex_set(&unknown);
+        let mut current = complex_set(&unknown);
+        unsafe {
+            update(
+                SIZE,
+                &mut start,
+                &mut current,
+                0,
+                range(128..192, BufferUses::UNIFORM),
+            )
+        };
+        assert_eq!(
+            ranges(&current),
+            [
+                (0..128, BufferUses::COPY_DST),
+                (128..192, BufferUses::UNIFORM),
+                (192..SIZE, BufferUses::UNKNOWN),
+            ]
+        );
+        // The first use of a range is also its state at the start.
+        assert_eq!(
+            ranges(&start),
+            [
+                (0..128, BufferUses::COPY_DST),
+                (128..192, BufferUses::UNIFORM),
+                (192..SIZE, BufferUses::UNKNOWN),
+            ]
+        );
+    }
 }
@@ -39,7 +39,7 @@ use cases, Bind Group, Usage Scope, and a full Tracker.
 
 Bind Group trackers are just a list of different resources, their refcount,
 and how they are used. Textures are used via a selector and a usage type.
-Buffers by just a usage type. Stateless resources don't have a usage type.
+Buffers via a range and a usage type. Stateless resources don't have a usage type.
 
 Usage Scope trackers are only for stateful resources. These trackers represent
 a single [`UsageScope`] in the spec. When a use is added to a usage scope,
@@ -268,9 +268,19 @@ impl PendingTransition<hal::BufferUses> {
         buf: &'a resource::Buffer<A>,
         snatch_guard: &'a SnatchGuard<'a>,
     ) -> hal::BufferBarrier<'a, A> {
+        // These showing up in a barrier is always a bug
+        strict_assert_ne!(self.usage.start, hal::BufferUses::UNKNOWN);
+        strict_assert_ne!(self.usage.end, hal::BufferUses::UNKNOWN);
+
         let buffer = buf.raw.get(snatch_guard).expect("Buffer is destroyed");
+        let range = if self.selector == (0..buf.size) {
+            None
+        } else {
+            Some(self.selector)
+        };
         hal::BufferBarrier {
             buffer,
+            range,
             usage: self.usage,
         }
     }
@@ -302,6 +312,37 @@ impl PendingTransition<hal::TextureUses> {
     }
 }
 
+/// An iterator adapter that can store two different iterator types.
+#[derive(Clone)]
+pub(crate) enum EitherIter<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L, R, D> Iterator for EitherIter<L, R>
+where
+    L: Iterator<Item = D>,
+    R: Iterator<Item = D>,
+{
+    type Item = D;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            EitherIter::Left(ref mut inner) => inner.next(),
+            EitherIter::Right(ref mut inner) => inner.next(),
+        }
+    }
+}
+
+/// Container that signifies storing both different things
+/// if there is a single state or many different states
+/// involved in the operation.
+#[derive(Debug, Clone)]
+pub(crate) enum SingleOrManyStates<S, M> {
+    Single(S),
+    Many(M),
+}
+
 /// The uses that a resource or subresource can be in.
 pub(crate) trait ResourceUses:
     fmt::Debug + ops::BitAnd<Output = Self> + ops::BitOr<Output = Self> + PartialEq + Sized + Copy
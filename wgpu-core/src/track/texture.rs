@@ -20,7 +20,8 @@
 !*/
 
 use super::{
-    range::RangedStates, PendingTransition, PendingTransitionList, ResourceTracker, TrackerIndex,
+    range::RangedStates, EitherIter, PendingTransition, PendingTransitionList, ResourceTracker,
+    SingleOrManyStates, TrackerIndex,
 };
 use crate::{
     hal_api::HalApi,
@@ -751,37 +752,6 @@ impl<A: HalApi> TextureTracker<A> {
     }
 }
 
-/// An iterator adapter that can store two different iterator types.
-#[derive(Clone)]
-enum EitherIter<L, R> {
-    Left(L),
-    Right(R),
-}
-
-impl<L, R, D> Iterator for EitherIter<L, R>
-where
-    L: Iterator<Item = D>,
-    R: Iterator<Item = D>,
-{
-    type Item = D;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match *self {
-            EitherIter::Left(ref mut inner) => inner.next(),
-            EitherIter::Right(ref mut inner) => inner.next(),
-        }
-    }
-}
-
-/// Container that signifies storing both different things
-/// if there is a single state or many different states
-/// involved in the operation.
-#[derive(Debug, Clone)]
-enum SingleOrManyStates<S, M> {
-    Single(S),
-    Many(M),
-}
-
 /// A source of texture state.
 #[derive(Clone)]
 enum TextureStateProvider<'a> {
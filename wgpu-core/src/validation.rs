@@ -1,7 +1,7 @@
 use crate::{
     device::bgl,
     id::{markers::Buffer, Id},
-    FastHashMap, FastHashSet,
+    FastHashMap, FastHashSet, FastIndexMap,
 };
 use arrayvec::ArrayVec;
 use std::{collections::hash_map::Entry, fmt};
@@ -30,6 +30,8 @@ struct Resource {
     bind: naga::ResourceBinding,
     ty: ResourceType,
     class: naga::AddressSpace,
+    /// Number of elements, for binding arrays of a fixed size.
+    count: Option<std::num::NonZeroU32>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -126,7 +128,10 @@ struct EntryPoint {
     spec_constants: Vec<SpecializationConstant>,
     sampling_pairs: FastHashSet<(naga::Handle<Resource>, naga::Handle<Resource>)>,
     workgroup_size: [u32; 3],
+    /// The workgroup size depends on overrides, so can't be checked here.
+    workgroup_size_overridden: bool,
     dual_source_blending: bool,
+    push_constants_size: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -134,7 +139,7 @@ pub struct Interface {
     limits: wgt::Limits,
     features: wgt::Features,
     resources: naga::Arena<Resource>,
-    entry_points: FastHashMap<(naga::ShaderStage, String), EntryPoint>,
+    entry_points: FastIndexMap<(naga::ShaderStage, String), EntryPoint>,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -523,7 +528,10 @@ impl Resource {
                         let naga_access = match access {
                             wgt::StorageTextureAccess::ReadOnly => naga::StorageAccess::LOAD,
                             wgt::StorageTextureAccess::WriteOnly => naga::StorageAccess::STORE,
-                            wgt::StorageTextureAccess::ReadWrite => naga::StorageAccess::all(),
+                            wgt::StorageTextureAccess::ReadWrite => {
+                                naga::StorageAccess::LOAD | naga::StorageAccess::STORE
+                            }
+                            wgt::StorageTextureAccess::Atomic => naga::StorageAccess::all(),
                         };
                         naga::ImageClass::Storage {
                             format: naga_format,
@@ -597,11 +605,14 @@ impl Resource {
                     },
                     naga::ImageClass::Storage { format, access } => BindingType::StorageTexture {
                         access: {
-                            const LOAD_STORE: naga::StorageAccess = naga::StorageAccess::all();
+                            const LOAD_STORE: naga::StorageAccess =
+                                naga::StorageAccess::LOAD.union(naga::StorageAccess::STORE);
+                            const ATOMIC: naga::StorageAccess = naga::StorageAccess::all();
                             match access {
                                 naga::StorageAccess::LOAD => wgt::StorageTextureAccess::ReadOnly,
                                 naga::StorageAccess::STORE => wgt::StorageTextureAccess::WriteOnly,
                                 LOAD_STORE => wgt::StorageTextureAccess::ReadWrite,
+                                ATOMIC => wgt::StorageTextureAccess::Atomic,
                                 _ => unreachable!(),
                             }
                         },
@@ -671,6 +682,32 @@ impl NumericType {
         }
     }
 
+    /// The vertex format whose data the shader sees unchanged as this type.
+    fn to_vertex_format(self) -> Option<wgt::VertexFormat> {
+        use naga::{Scalar, VectorSize as Vs};
+        use wgt::VertexFormat as Vf;
+
+        Some(match (self.dim, self.scalar) {
+            (NumericDimension::Scalar, Scalar::U32) => Vf::Uint32,
+            (NumericDimension::Vector(Vs::Bi), Scalar::U32) => Vf::Uint32x2,
+            (NumericDimension::Vector(Vs::Tri), Scalar::U32) => Vf::Uint32x3,
+            (NumericDimension::Vector(Vs::Quad), Scalar::U32) => Vf::Uint32x4,
+            (NumericDimension::Scalar, Scalar::I32) => Vf::Sint32,
+            (NumericDimension::Vector(Vs::Bi), Scalar::I32) => Vf::Sint32x2,
+            (NumericDimension::Vector(Vs::Tri), Scalar::I32) => Vf::Sint32x3,
+            (NumericDimension::Vector(Vs::Quad), Scalar::I32) => Vf::Sint32x4,
+            (NumericDimension::Scalar, Scalar::F32) => Vf::Float32,
+            (NumericDimension::Vector(Vs::Bi), Scalar::F32) => Vf::Float32x2,
+            (NumericDimension::Vector(Vs::Tri), Scalar::F32) => Vf::Float32x3,
+            (NumericDimension::Vector(Vs::Quad), Scalar::F32) => Vf::Float32x4,
+            (NumericDimension::Scalar, Scalar::F64) => Vf::Float64,
+            (NumericDimension::Vector(Vs::Bi), Scalar::F64) => Vf::Float64x2,
+            (NumericDimension::Vector(Vs::Tri), Scalar::F64) => Vf::Float64x3,
+            (NumericDimension::Vector(Vs::Quad), Scalar::F64) => Vf::Float64x4,
+            _ => return None,
+        })
+    }
+
     fn from_texture_format(format: wgt::TextureFormat) -> Self {
         use naga::{Scalar, VectorSize as Vs};
         use wgt::TextureFormat as Tf;
@@ -821,6 +858,9 @@ impl<'a> BindingLayoutSource<'a> {
 
 pub type StageIo = FastHashMap<wgt::ShaderLocation, InterfaceVar>;
 
+/// Bindings of the resources used by a pipeline, by variable name.
+pub type BindingNames = FastHashMap<String, naga::ResourceBinding>;
+
 impl Interface {
     fn populate(
         list: &mut Vec<Varying>,
@@ -899,9 +939,16 @@ impl Interface {
             };
             let naga_ty = &module.types[var.ty].inner;
 
-            let inner_ty = match *naga_ty {
-                naga::TypeInner::BindingArray { base, .. } => &module.types[base].inner,
-                ref ty => ty,
+            let (inner_ty, count) = match *naga_ty {
+                naga::TypeInner::BindingArray { base, size } => (
+                    &module.types[base].inner,
+                    match size {
+                        naga::ArraySize::Constant(size) => Some(size),
+                        // Naga only allows override-sized arrays in workgroup memory
+                        naga::ArraySize::Pending(_) | naga::ArraySize::Dynamic => None,
+                    },
+                ),
+                ref ty => (ty, None),
             };
 
             let ty = match *inner_ty {
@@ -918,7 +965,7 @@ impl Interface {
                 naga::TypeInner::Array { stride, size, .. } => {
                     let size = match size {
                         naga::ArraySize::Constant(size) => size.get() * stride,
-                        naga::ArraySize::Dynamic => stride,
+                        naga::ArraySize::Pending(_) | naga::ArraySize::Dynamic => stride,
                     };
                     ResourceType::Buffer {
                         size: wgt::BufferSize::new(size as u64).unwrap(),
@@ -934,13 +981,14 @@ impl Interface {
                     bind,
                     ty,
                     class: var.space,
+                    count,
                 },
                 Default::default(),
             );
             resource_mapping.insert(var_handle, handle);
         }
 
-        let mut entry_points = FastHashMap::default();
+        let mut entry_points = FastIndexMap::default();
         entry_points.reserve(module.entry_points.len());
         for (index, entry_point) in module.entry_points.iter().enumerate() {
             let info = info.get_entry_point(index);
@@ -959,8 +1007,13 @@ impl Interface {
 
             for (var_handle, var) in module.global_variables.iter() {
                 let usage = info[var_handle];
-                if !usage.is_empty() && var.binding.is_some() {
+                if usage.is_empty() {
+                    continue;
+                }
+                if var.binding.is_some() {
                     ep.resources.push(resource_mapping[&var_handle]);
+                } else if var.space == naga::AddressSpace::PushConstant {
+                    ep.push_constants_size = Some(module.types[var.ty].inner.size(module.to_ctx()));
                 }
             }
 
@@ -970,6 +1023,7 @@ impl Interface {
             }
             ep.dual_source_blending = info.dual_source_blending;
             ep.workgroup_size = entry_point.workgroup_size;
+            ep.workgroup_size_overridden = entry_point.workgroup_size_overrides.is_some();
 
             entry_points.insert((entry_point.stage, entry_point.name.clone()), ep);
         }
@@ -1145,7 +1199,7 @@ impl Interface {
         }
 
         // check workgroup size limits
-        if shader_stage == naga::ShaderStage::Compute {
+        if shader_stage == naga::ShaderStage::Compute && !entry_point.workgroup_size_overridden {
             let max_workgroup_size_limits = [
                 self.limits.max_compute_workgroup_size_x,
                 self.limits.max_compute_workgroup_size_y,
@@ -1203,6 +1257,13 @@ impl Interface {
                                         )
                                     }
                                     naga::ShaderStage::Compute => (false, 0),
+                                    naga::ShaderStage::RayGeneration
+                                    | naga::ShaderStage::Intersection
+                                    | naga::ShaderStage::AnyHit
+                                    | naga::ShaderStage::ClosestHit
+                                    | naga::ShaderStage::Miss
+                                    | naga::ShaderStage::Task
+                                    | naga::ShaderStage::Mesh => unreachable!(),
                                 };
                                 if compatible {
                                     Ok(num_components)
@@ -1291,6 +1352,132 @@ impl Interface {
         Ok(outputs)
     }
 
+    /// Describes the interface of every entry point, for users that want to
+    /// build pipeline layouts and vertex buffer layouts matching the module.
+    pub fn reflect(&self) -> wgt::ShaderReflection {
+        let entry_points = self
+            .entry_points
+            .iter()
+            .filter_map(|((stage, name), ep)| {
+                let stage_bit = match *stage {
+                    naga::ShaderStage::Vertex => wgt::ShaderStages::VERTEX,
+                    naga::ShaderStage::Fragment => wgt::ShaderStages::FRAGMENT,
+                    naga::ShaderStage::Compute => wgt::ShaderStages::COMPUTE,
+                    _ => return None,
+                };
+
+                let bindings = ep
+                    .resources
+                    .iter()
+                    .filter_map(|&handle| {
+                        let res = &self.resources[handle];
+                        // Resources that can't be bound would fail pipeline
+                        // creation anyway, so there is nothing to describe.
+                        let ty = res.derive_binding_type().ok()?;
+                        Some(wgt::BindingReflection {
+                            name: res.name.clone(),
+                            group: res.bind.group,
+                            entry: BindGroupLayoutEntry {
+                                binding: res.bind.binding,
+                                visibility: stage_bit,
+                                ty,
+                                count: res.count,
+                            },
+                        })
+                    })
+                    .collect();
+
+                let mut vertex_inputs = Vec::new();
+                if *stage == naga::ShaderStage::Vertex {
+                    vertex_inputs.extend(ep.inputs.iter().filter_map(|input| match *input {
+                        Varying::Local { location, ref iv } => Some(wgt::VertexInputReflection {
+                            location,
+                            format: iv.ty.to_vertex_format()?,
+                        }),
+                        Varying::BuiltIn(_) => None,
+                    }));
+                    vertex_inputs.sort_by_key(|input| input.location);
+                }
+
+                let mut color_outputs = Vec::new();
+                if *stage == naga::ShaderStage::Fragment {
+                    color_outputs.extend(ep.outputs.iter().filter_map(|output| match *output {
+                        Varying::Local { location, ref iv } => {
+                            let sample_type = match iv.ty.scalar.kind {
+                                naga::ScalarKind::Float => {
+                                    wgt::TextureSampleType::Float { filterable: true }
+                                }
+                                naga::ScalarKind::Sint => wgt::TextureSampleType::Sint,
+                                naga::ScalarKind::Uint => wgt::TextureSampleType::Uint,
+                                _ => return None,
+                            };
+                            Some(wgt::ColorOutputReflection {
+                                location,
+                                sample_type,
+                                components: iv.ty.dim.num_components(),
+                            })
+                        }
+                        Varying::BuiltIn(_) => None,
+                    }));
+                    // The second blend source shares its location with the first one.
+                    color_outputs.sort_by_key(|output| output.location);
+                    color_outputs.dedup_by_key(|output| output.location);
+                }
+
+                let push_constant_ranges = ep
+                    .push_constants_size
+                    .map(|size| wgt::PushConstantRange {
+                        stages: stage_bit,
+                        range: 0..size,
+                    })
+                    .into_iter()
+                    .collect();
+
+                Some(wgt::EntryPointReflection {
+                    name: name.clone(),
+                    stage: stage_bit,
+                    bindings,
+                    vertex_inputs,
+                    color_outputs,
+                    workgroup_size: if *stage == naga::ShaderStage::Compute {
+                        ep.workgroup_size
+                    } else {
+                        [0; 3]
+                    },
+                    push_constant_ranges,
+                })
+            })
+            .collect();
+
+        wgt::ShaderReflection { entry_points }
+    }
+
+    /// Adds the names of the resources used by an entry point to `names`.
+    ///
+    /// Names already present are kept, so earlier stages take precedence.
+    pub fn collect_binding_names(
+        &self,
+        entry_point_name: &str,
+        stage_bit: wgt::ShaderStages,
+        names: &mut BindingNames,
+    ) {
+        let pair = (
+            Self::shader_stage_from_stage_bit(stage_bit),
+            entry_point_name.to_string(),
+        );
+        let Some(entry_point) = self.entry_points.get(&pair) else {
+            return;
+        };
+        for &handle in entry_point.resources.iter() {
+            let res = &self.resources[handle];
+            if let Some(ref name) = res.name {
+                names
+                    .entry(name.clone())
+                    .or_insert_with(|| res.bind.clone());
+            }
+        }
+    }
+
     pub fn fragment_uses_dual_source_blending(
         &self,
         entry_point_name: &str,
@@ -1330,3 +1517,60 @@ pub fn validate_color_attachment_bytes_per_sample(
 
     Ok(())
 }
+
+/// The checks Naga makes when validating shader modules.
+///
+/// With the `skip_uniformity_validation` feature, modules aren't rejected for
+/// breaking Naga's uniformity rules. The uniformity analysis still runs, as
+/// backends use its results, but they don't rely on the rules holding.
+pub fn shader_validation_flags() -> naga::valid::ValidationFlags {
+    let flags = naga::valid::ValidationFlags::all();
+    if cfg!(feature = "skip_uniformity_validation") {
+        flags - naga::valid::ValidationFlags::CONTROL_FLOW_UNIFORMITY
+    } else {
+        flags
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shader_validation() {
+        let flags = shader_validation_flags();
+        assert_eq!(
+            flags.contains(naga::valid::ValidationFlags::CONTROL_FLOW_UNIFORMITY),
+            !cfg!(feature = "skip_uniformity_validation")
+        );
+        // Everything the backends rely on is validated with the feature as
+        // well.
+        assert!(flags.contains(
+            naga::valid::ValidationFlags::EXPRESSIONS
+                | naga::valid::ValidationFlags::BLOCKS
+                | naga::valid::ValidationFlags::STRUCT_LAYOUTS
+                | naga::valid::ValidationFlags::CONSTANTS
+                | naga::valid::ValidationFlags::BINDINGS
+        ));
+    }
+
+    #[cfg(feature = "wgsl")]
+    #[test]
+    fn shader_validation_rejects_invalid_modules() {
+        // Naga's WGSL frontend accepts `f64`, which the validator rejects
+        // without the capability.
+        let module = naga::front::wgsl::parse_str(
+            "
+            @compute @workgroup_size(1)
+            fn main() {
+                var x: f64 = 1.0lf;
+            }",
+        )
+        .unwrap();
+        let mut validator = naga::valid::Validator::new(
+            shader_validation_flags(),
+            naga::valid::Capabilities::empty(),
+        );
+        assert!(validator.validate(&module).is_err());
+    }
+}
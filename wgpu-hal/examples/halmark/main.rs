@@ -125,7 +125,11 @@ impl<A: hal::Api> Example<A> {
 
         let hal::OpenDevice { device, queue } = unsafe {
             adapter
-                .open(wgt::Features::empty(), &wgt::Limits::default())
+                .open(
+                    wgt::Features::empty(),
+                    &wgt::Limits::default(),
+                    &wgt::MemoryHints::default(),
+                )
                 .unwrap()
         };
 
@@ -167,6 +171,7 @@ impl<A: hal::Api> Example<A> {
                 module: Cow::Owned(module),
                 info,
                 debug_source: None,
+                cache: None,
             }
         };
         let shader_desc = hal::ShaderModuleDescriptor {
@@ -273,6 +278,7 @@ impl<A: hal::Api> Example<A> {
                 write_mask: wgt::ColorWrites::default(),
             })],
             multiview: None,
+            cache: None,
         };
         let pipeline = unsafe { device.create_render_pipeline(&pipeline_desc).unwrap() };
 
@@ -324,6 +330,7 @@ impl<A: hal::Api> Example<A> {
         {
             let buffer_barrier = hal::BufferBarrier {
                 buffer: &staging_buffer,
+                range: None,
                 usage: hal::BufferUses::empty()..hal::BufferUses::COPY_SRC,
             };
             let texture_barrier1 = hal::TextureBarrier {
@@ -702,6 +709,7 @@ impl<A: hal::Api> Example<A> {
             multiview: None,
             timestamp_writes: None,
             occlusion_query_set: None,
+            render_bundles_only: false,
         };
         unsafe {
             ctx.encoder.begin_render_pass(&pass_desc);
@@ -249,8 +249,15 @@ impl<A: hal::Api> Example<A> {
             .expect("Surface doesn't support presentation");
         log::info!("Surface caps: {:#?}", surface_caps);
 
-        let hal::OpenDevice { device, queue } =
-            unsafe { adapter.open(features, &wgt::Limits::default()).unwrap() };
+        let hal::OpenDevice { device, queue } = unsafe {
+            adapter
+                .open(
+                    features,
+                    &wgt::Limits::default(),
+                    &wgt::MemoryHints::default(),
+                )
+                .unwrap()
+        };
 
         let window_size: (u32, u32) = window.inner_size().into();
         dbg!(&surface_caps.formats);
@@ -339,6 +346,7 @@ impl<A: hal::Api> Example<A> {
                 module: Cow::Owned(module),
                 info,
                 debug_source: None,
+                cache: None,
             }
         };
         let shader_desc = hal::ShaderModuleDescriptor {
@@ -373,6 +381,7 @@ impl<A: hal::Api> Example<A> {
                     constants: &Default::default(),
                     zero_initialize_workgroup_memory: true,
                 },
+                cache: None,
             })
         }
         .unwrap();
@@ -714,6 +723,7 @@ impl<A: hal::Api> Example<A> {
 
             let scratch_buffer_barrier = hal::BufferBarrier {
                 buffer: &scratch_buffer,
+                range: None,
                 usage: hal::BufferUses::BOTTOM_LEVEL_ACCELERATION_STRUCTURE_INPUT
                     ..hal::BufferUses::TOP_LEVEL_ACCELERATION_STRUCTURE_INPUT,
             };
@@ -879,6 +889,7 @@ impl<A: hal::Api> Example<A> {
 
             let scratch_buffer_barrier = hal::BufferBarrier {
                 buffer: &self.scratch_buffer,
+                range: None,
                 usage: hal::BufferUses::BOTTOM_LEVEL_ACCELERATION_STRUCTURE_INPUT
                     ..hal::BufferUses::TOP_LEVEL_ACCELERATION_STRUCTURE_INPUT,
             };
@@ -51,6 +51,13 @@ pub fn map_naga_stage(stage: naga::ShaderStage) -> wgt::ShaderStages {
         naga::ShaderStage::Vertex => wgt::ShaderStages::VERTEX,
         naga::ShaderStage::Fragment => wgt::ShaderStages::FRAGMENT,
         naga::ShaderStage::Compute => wgt::ShaderStages::COMPUTE,
+        naga::ShaderStage::RayGeneration
+        | naga::ShaderStage::Intersection
+        | naga::ShaderStage::AnyHit
+        | naga::ShaderStage::ClosestHit
+        | naga::ShaderStage::Miss
+        | naga::ShaderStage::Task
+        | naga::ShaderStage::Mesh => unreachable!(),
     }
 }
 
@@ -8,7 +8,7 @@ use winapi::{
     shared::{
         dxgi, dxgi1_2, dxgiformat::DXGI_FORMAT_B8G8R8A8_UNORM, minwindef::DWORD, windef, winerror,
     },
-    um::{d3d12 as d3d12_ty, d3d12sdklayers, winuser},
+    um::{d3d12 as d3d12_ty, d3d12sdklayers, winnt, winuser},
 };
 
 impl Drop for super::Adapter {
@@ -46,6 +46,42 @@ impl super::Adapter {
         &self.raw
     }
 
+    pub fn raw_device(&self) -> &d3d12::Device {
+        &self.device
+    }
+
+    /// Wraps a direct command queue created outside of wgpu.
+    ///
+    /// The device keeps its own reference to `raw_queue`, so the caller can
+    /// go on submitting its own work to it, ordered with wgpu's submissions
+    /// by the queue itself.
+    ///
+    /// # Safety
+    ///
+    /// - `raw_queue` must be a queue of type `D3D12_COMMAND_LIST_TYPE_DIRECT`
+    ///   created from [`raw_device`](Self::raw_device).
+    pub unsafe fn device_from_raw(
+        &self,
+        raw_queue: d3d12::CommandQueue,
+        limits: &wgt::Limits,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        let device = super::Device::new(
+            self.device.clone(),
+            raw_queue.clone(),
+            limits,
+            self.private_caps,
+            &self.library,
+            self.dxc_container.clone(),
+        )?;
+        Ok(crate::OpenDevice {
+            device,
+            queue: super::Queue {
+                raw: raw_queue,
+                temp_lists: Mutex::new(Vec::new()),
+            },
+        })
+    }
+
     #[allow(trivial_casts)]
     pub(super) fn expose(
         adapter: d3d12::DxgiAdapter,
@@ -105,6 +141,25 @@ impl super::Adapter {
 
         let device_name = auxil::dxgi::conv::map_adapter_name(desc.Description);
 
+        // Cached pipeline state blobs are only valid for the driver that
+        // created them, so the version of the user mode driver keys them.
+        let mut driver_version: winnt::LARGE_INTEGER = unsafe { mem::zeroed() };
+        let hr = unsafe {
+            adapter.unwrap_adapter2().CheckInterfaceSupport(
+                &<dxgi::IDXGIDevice as winapi::Interface>::uuidof(),
+                &mut driver_version,
+            )
+        };
+        let driver_version = if hr >= 0 {
+            unsafe { *driver_version.QuadPart() as u64 }
+        } else {
+            0
+        };
+        let mut pipeline_cache_validation_key = [0; 16];
+        pipeline_cache_validation_key[0..4].copy_from_slice(&desc.VendorId.to_le_bytes());
+        pipeline_cache_validation_key[4..8].copy_from_slice(&desc.DeviceId.to_le_bytes());
+        pipeline_cache_validation_key[8..16].copy_from_slice(&driver_version.to_le_bytes());
+
         let mut features_architecture: d3d12_ty::D3D12_FEATURE_DATA_ARCHITECTURE =
             unsafe { mem::zeroed() };
         assert_eq!(0, unsafe {
@@ -156,7 +211,7 @@ impl super::Adapter {
             hr == 0 && features2.DepthBoundsTestSupported != 0
         };
 
-        let casting_fully_typed_format_supported = {
+        let (casting_fully_typed_format_supported, barycentrics_supported) = {
             let mut features3: crate::dx12::types::D3D12_FEATURE_DATA_D3D12_OPTIONS3 =
                 unsafe { mem::zeroed() };
             let hr = unsafe {
@@ -166,12 +221,13 @@ impl super::Adapter {
                     mem::size_of::<crate::dx12::types::D3D12_FEATURE_DATA_D3D12_OPTIONS3>() as _,
                 )
             };
-            hr == 0 && features3.CastingFullyTypedFormatSupported != 0
+            (
+                hr == 0 && features3.CastingFullyTypedFormatSupported != 0,
+                hr == 0 && features3.BarycentricsSupported != 0,
+            )
         };
 
-        let shader_model = if dxc_container.is_none() {
-            naga::back::hlsl::ShaderModel::V5_1
-        } else {
+        let device_shader_model = {
             let mut versions = [
                 crate::dx12::types::D3D_SHADER_MODEL_6_7,
                 crate::dx12::types::D3D_SHADER_MODEL_6_6,
@@ -215,6 +271,12 @@ impl super::Adapter {
                 _ => unreachable!(),
             }
         };
+        // FXC can only target shader model 5.1.
+        let shader_model = if dxc_container.is_none() {
+            naga::back::hlsl::ShaderModel::V5_1
+        } else {
+            device_shader_model
+        };
 
         let private_caps = super::PrivateCapabilities {
             instance_flags,
@@ -232,6 +294,7 @@ impl super::Adapter {
             // See https://github.com/gfx-rs/wgpu/issues/3552
             suballocation_supported: !info.name.contains("Iris(R) Xe"),
             shader_model,
+            pipeline_cache_validation_key,
         };
 
         // Theoretically vram limited, but in practice 2^20 is the limit
@@ -285,9 +348,14 @@ impl super::Adapter {
             | wgt::Features::TEXTURE_FORMAT_16BIT_NORM
             | wgt::Features::PUSH_CONSTANTS
             | wgt::Features::SHADER_PRIMITIVE_INDEX
+            | wgt::Features::CLIP_DISTANCES
+            | wgt::Features::CULL_DISTANCES
             | wgt::Features::RG11B10UFLOAT_RENDERABLE
             | wgt::Features::DUAL_SOURCE_BLENDING
-            | wgt::Features::TEXTURE_FORMAT_NV12;
+            | wgt::Features::TEXTURE_FORMAT_NV12
+            | wgt::Features::PIPELINE_CACHE
+            | wgt::Features::SHARED_TEXTURES
+            | wgt::Features::DEPTH32FLOAT_COPY_DST;
 
         //TODO: in order to expose this, we need to run a compute shader
         // that extract the necessary statistics out of the D3D12 result.
@@ -307,6 +375,8 @@ impl super::Adapter {
 
         features.set(
             wgt::Features::TEXTURE_BINDING_ARRAY
+                | wgt::Features::BUFFER_BINDING_ARRAY
+                | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY
                 | wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING
                 | wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
             shader_model >= naga::back::hlsl::ShaderModel::V5_1,
@@ -347,12 +417,39 @@ impl super::Adapter {
                 && features1.Int64ShaderOps != 0,
         );
 
+        // Typed UAV atomics on `R32_UINT` and `R32_SINT` are always supported.
+        features.insert(wgt::Features::TEXTURE_ATOMIC);
+
         features.set(
             wgt::Features::SUBGROUP,
             shader_model >= naga::back::hlsl::ShaderModel::V6_0
                 && hr == 0
                 && features1.WaveOps != 0,
         );
+        // Clustered reductions are emulated with lane reads, and quad
+        // operations are part of the base wave intrinsics.
+        features.set(
+            wgt::Features::SUBGROUP_EXTENDED,
+            features.contains(wgt::Features::SUBGROUP),
+        );
+
+        // Precompiled DXIL only needs the device to support shader model 6.0,
+        // not DXC.
+        features.set(
+            wgt::Features::DXIL_SHADER_PASSTHROUGH,
+            device_shader_model >= naga::back::hlsl::ShaderModel::V6_0,
+        );
+
+        features.set(
+            wgt::Features::SHADER_BARYCENTRICS,
+            shader_model >= naga::back::hlsl::ShaderModel::V6_1 && barycentrics_supported,
+        );
+
+        // Derivatives in compute shaders are a required part of shader model 6.6.
+        features.set(
+            wgt::Features::COMPUTE_SHADER_DERIVATIVES,
+            shader_model >= naga::back::hlsl::ShaderModel::V6_6,
+        );
 
         // float32-filterable should always be available on d3d12
         features.set(wgt::Features::FLOAT32_FILTERABLE, true);
@@ -472,6 +569,10 @@ impl super::Adapter {
                     .unwrap(),
                 },
                 downlevel,
+                persistent_mapping: true,
+                // Buffers in upload heaps can't be written to by the GPU.
+                unified_memory: false,
+                render_bundles: crate::NativeRenderBundles::Inline,
             },
         })
     }
@@ -484,6 +585,7 @@ impl crate::Adapter for super::Adapter {
         &self,
         _features: wgt::Features,
         limits: &wgt::Limits,
+        _memory_hints: &wgt::MemoryHints,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let queue = {
             profiling::scope!("ID3D12Device::CreateCommandQueue");
@@ -497,21 +599,7 @@ impl crate::Adapter for super::Adapter {
                 .into_device_result("Queue creation")?
         };
 
-        let device = super::Device::new(
-            self.device.clone(),
-            queue.clone(),
-            limits,
-            self.private_caps,
-            &self.library,
-            self.dxc_container.clone(),
-        )?;
-        Ok(crate::OpenDevice {
-            device,
-            queue: super::Queue {
-                raw: queue,
-                temp_lists: Mutex::new(Vec::new()),
-            },
-        })
+        unsafe { self.device_from_raw(queue, limits) }
     }
 
     #[allow(trivial_casts)]
@@ -64,6 +64,40 @@ impl Drop for super::CommandEncoder {
 }
 
 impl super::CommandEncoder {
+    /// Records the barriers accumulated by `transition_buffers` and
+    /// `transition_textures`, in a single `ResourceBarrier` call.
+    ///
+    /// This is done before any command that may depend on them, so that the
+    /// barriers of consecutive transitions are merged.
+    fn flush_barriers(&mut self) {
+        if self.temp.barriers.is_empty() {
+            return;
+        }
+        unsafe {
+            self.list
+                .as_ref()
+                .unwrap()
+                .ResourceBarrier(self.temp.barriers.len() as u32, self.temp.barriers.as_ptr())
+        };
+        self.temp.barriers.clear();
+    }
+
+    /// Whether one of the first `count` pending barriers is for `resource`.
+    ///
+    /// The barriers a single transition call makes never overlap, but its
+    /// barriers may have to wait for those of the previous calls.
+    fn has_pending_barrier(&self, count: usize, resource: *mut d3d12_ty::ID3D12Resource) -> bool {
+        self.temp.barriers[..count].iter().any(|barrier| unsafe {
+            match barrier.Type {
+                d3d12_ty::D3D12_RESOURCE_BARRIER_TYPE_TRANSITION => {
+                    barrier.u.Transition().pResource == resource
+                }
+                d3d12_ty::D3D12_RESOURCE_BARRIER_TYPE_UAV => barrier.u.UAV().pResource == resource,
+                _ => false,
+            }
+        })
+    }
+
     unsafe fn begin_pass(&mut self, kind: super::PassKind, label: crate::Label) {
         let list = self.list.as_ref().unwrap();
         self.pass.kind = kind;
@@ -297,6 +331,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         }
     }
     unsafe fn end_encoding(&mut self) -> Result<super::CommandBuffer, crate::DeviceError> {
+        self.flush_barriers();
         let raw = self.list.take().unwrap();
         raw.close()
             .into_device_result("GraphicsCommandList::close")?;
@@ -309,11 +344,68 @@ impl crate::CommandEncoder for super::CommandEncoder {
         self.allocator.reset();
     }
 
+    unsafe fn begin_render_bundle(
+        &mut self,
+        desc: &crate::RenderBundleDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        let allocator = match self.bundle_allocator {
+            Some(ref allocator) => allocator,
+            None => {
+                let allocator = self
+                    .device
+                    .create_command_allocator(d3d12::CmdListType::Bundle)
+                    .into_device_result("Bundle allocator creation")?;
+                self.bundle_allocator.insert(allocator)
+            }
+        };
+        // Bundles inherit the render targets of the pass they are executed in,
+        // so they don't depend on the descriptor.
+        let list = self
+            .device
+            .create_graphics_command_list(
+                d3d12::CmdListType::Bundle,
+                allocator,
+                d3d12::PipelineState::null(),
+                0,
+            )
+            .into_device_result("Create bundle")?;
+
+        if let Some(label) = desc.label {
+            let cwstr = conv::map_label(label);
+            unsafe { list.SetName(cwstr.as_ptr()) };
+        }
+
+        self.list = Some(list);
+        self.temp.clear();
+        self.pass.clear();
+        // Bundles must set the same descriptor heaps as the list executing
+        // them.
+        unsafe { self.begin_pass(super::PassKind::Render, None) };
+        Ok(())
+    }
+
+    unsafe fn end_render_bundle(&mut self) -> Result<super::RenderBundle, crate::DeviceError> {
+        self.pass.clear();
+        let raw = self.list.take().unwrap();
+        raw.close().into_device_result("Bundle close")?;
+        Ok(super::RenderBundle { raw })
+    }
+
+    unsafe fn reset_render_bundles<I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = super::RenderBundle>,
+    {
+        bundles.for_each(drop);
+        if let Some(ref allocator) = self.bundle_allocator {
+            allocator.reset();
+        }
+    }
+
     unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
     {
-        self.temp.barriers.clear();
+        let mut earlier = self.temp.barriers.len();
 
         log::trace!(
             "List {:p} buffer transitions",
@@ -326,6 +418,10 @@ impl crate::CommandEncoder for super::CommandEncoder {
                 barrier.usage.start,
                 barrier.usage.end
             );
+            if self.has_pending_barrier(earlier, barrier.buffer.resource.as_mut_ptr()) {
+                self.flush_barriers();
+                earlier = 0;
+            }
             let s0 = conv::map_buffer_usage_to_state(barrier.usage.start);
             let s1 = conv::map_buffer_usage_to_state(barrier.usage.end);
             if s0 != s1 {
@@ -357,22 +453,13 @@ impl crate::CommandEncoder for super::CommandEncoder {
                 self.temp.barriers.push(raw);
             }
         }
-
-        if !self.temp.barriers.is_empty() {
-            unsafe {
-                self.list
-                    .as_ref()
-                    .unwrap()
-                    .ResourceBarrier(self.temp.barriers.len() as u32, self.temp.barriers.as_ptr())
-            };
-        }
     }
 
     unsafe fn transition_textures<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::TextureBarrier<'a, super::Api>>,
     {
-        self.temp.barriers.clear();
+        let mut earlier = self.temp.barriers.len();
 
         log::trace!(
             "List {:p} texture transitions",
@@ -386,6 +473,10 @@ impl crate::CommandEncoder for super::CommandEncoder {
                 barrier.usage.end,
                 barrier.range
             );
+            if self.has_pending_barrier(earlier, barrier.texture.resource.as_mut_ptr()) {
+                self.flush_barriers();
+                earlier = 0;
+            }
             let s0 = conv::map_texture_usage_to_state(barrier.usage.start);
             let s1 = conv::map_texture_usage_to_state(barrier.usage.end);
             if s0 != s1 {
@@ -465,18 +556,10 @@ impl crate::CommandEncoder for super::CommandEncoder {
                 self.temp.barriers.push(raw);
             }
         }
-
-        if !self.temp.barriers.is_empty() {
-            unsafe {
-                self.list
-                    .as_ref()
-                    .unwrap()
-                    .ResourceBarrier(self.temp.barriers.len() as u32, self.temp.barriers.as_ptr())
-            };
-        }
     }
 
     unsafe fn clear_buffer(&mut self, buffer: &super::Buffer, range: crate::MemoryRange) {
+        self.flush_barriers();
         let list = self.list.as_ref().unwrap();
         let mut offset = range.start;
         while offset < range.end {
@@ -502,6 +585,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferCopy>,
     {
+        self.flush_barriers();
         let list = self.list.as_ref().unwrap();
         for r in regions {
             unsafe {
@@ -525,6 +609,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::TextureCopy>,
     {
+        self.flush_barriers();
         let list = self.list.as_ref().unwrap();
         let mut src_location = d3d12_ty::D3D12_TEXTURE_COPY_LOCATION {
             pResource: src.resource.as_mut_ptr(),
@@ -567,6 +652,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
+        self.flush_barriers();
         let list = self.list.as_ref().unwrap();
         let mut src_location = d3d12_ty::D3D12_TEXTURE_COPY_LOCATION {
             pResource: src.resource.as_mut_ptr(),
@@ -609,6 +695,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
+        self.flush_barriers();
         let list = self.list.as_ref().unwrap();
         let mut src_location = d3d12_ty::D3D12_TEXTURE_COPY_LOCATION {
             pResource: src.resource.as_mut_ptr(),
@@ -634,6 +721,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     }
 
     unsafe fn begin_query(&mut self, set: &super::QuerySet, index: u32) {
+        self.flush_barriers();
         unsafe {
             self.list
                 .as_ref()
@@ -642,6 +730,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         };
     }
     unsafe fn end_query(&mut self, set: &super::QuerySet, index: u32) {
+        self.flush_barriers();
         unsafe {
             self.list
                 .as_ref()
@@ -650,6 +739,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         };
     }
     unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
+        self.flush_barriers();
         unsafe {
             self.list.as_ref().unwrap().EndQuery(
                 set.raw.as_mut_ptr(),
@@ -669,6 +759,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         offset: wgt::BufferAddress,
         _stride: wgt::BufferSize,
     ) {
+        self.flush_barriers();
         unsafe {
             self.list.as_ref().unwrap().ResolveQueryData(
                 set.raw.as_mut_ptr(),
@@ -684,6 +775,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     // render
 
     unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<super::Api>) {
+        self.flush_barriers();
         unsafe { self.begin_pass(super::PassKind::Render, desc.label) };
 
         // Start timestamp if any (before all other commands but after debug marker)
@@ -794,6 +886,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     }
 
     unsafe fn end_render_pass(&mut self) {
+        self.flush_barriers();
         if !self.pass.resolves.is_empty() {
             let list = self.list.as_ref().unwrap();
             self.temp.barriers.clear();
@@ -865,6 +958,9 @@ impl crate::CommandEncoder for super::CommandEncoder {
                     )
                 };
             }
+            // These are recorded already, don't let `flush_barriers` record
+            // them again with the next transitions.
+            self.temp.barriers.clear();
         }
 
         self.write_pass_end_timestamp_if_requested();
@@ -966,6 +1062,25 @@ impl crate::CommandEncoder for super::CommandEncoder {
         unsafe { self.list.as_ref().unwrap().EndEvent() }
     }
 
+    unsafe fn execute_render_bundles<'a, I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = &'a super::RenderBundle>,
+    {
+        let list = self.list.as_ref().unwrap();
+        for bundle in bundles {
+            list.execute_bundle(bundle.raw.clone());
+        }
+
+        // The state the bundles set is left to the list, so the pipeline and
+        // bindings have to be set again, but the pass isn't over.
+        let has_label = self.pass.has_label;
+        let resolves = mem::take(&mut self.pass.resolves);
+        self.pass.clear();
+        self.pass.kind = super::PassKind::Render;
+        self.pass.has_label = has_label;
+        self.pass.resolves = resolves;
+    }
+
     unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
         let list = self.list.as_ref().unwrap().clone();
 
@@ -1158,6 +1273,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         &mut self,
         desc: &crate::ComputePassDescriptor<'a, super::Api>,
     ) {
+        self.flush_barriers();
         unsafe { self.begin_pass(super::PassKind::Compute, desc.label) };
 
         if let Some(timestamp_writes) = desc.timestamp_writes.as_ref() {
@@ -1189,10 +1305,12 @@ impl crate::CommandEncoder for super::CommandEncoder {
     }
 
     unsafe fn dispatch(&mut self, count: [u32; 3]) {
+        self.flush_barriers();
         self.prepare_dispatch(count);
         self.list.as_ref().unwrap().dispatch(count);
     }
     unsafe fn dispatch_indirect(&mut self, buffer: &super::Buffer, offset: wgt::BufferAddress) {
+        self.flush_barriers();
         self.prepare_dispatch([0; 3]);
         //TODO: update special constants indirectly
         unsafe {
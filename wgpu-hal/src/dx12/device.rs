@@ -215,19 +215,37 @@ impl super::Device {
 
         let stage_bit = auxil::map_naga_stage(naga_stage);
 
+        let naga_shader = match stage.module.source {
+            super::ShaderModuleSource::Naga(ref naga) => naga,
+            // The entry point and pipeline constants were baked in when the
+            // DXIL was compiled.
+            super::ShaderModuleSource::Dxil(ref dxil) => {
+                return Ok(super::CompiledShader::Dxc(dxil.clone()))
+            }
+        };
+
         let (module, info) = naga::back::pipeline_constants::process_overrides(
-            &stage.module.naga.module,
-            &stage.module.naga.info,
+            &naga_shader.module,
+            &naga_shader.info,
             stage.constants,
         )
         .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("HLSL: {e:?}")))?;
 
         let needs_temp_options = stage.zero_initialize_workgroup_memory
-            != layout.naga_options.zero_initialize_workgroup_memory;
+            != layout.naga_options.zero_initialize_workgroup_memory
+            || naga_shader.debug_source.is_some();
         let mut temp_options;
         let naga_options = if needs_temp_options {
             temp_options = layout.naga_options.clone();
             temp_options.zero_initialize_workgroup_memory = stage.zero_initialize_workgroup_memory;
+            temp_options.debug_info =
+                naga_shader
+                    .debug_source
+                    .as_ref()
+                    .map(|debug| naga::back::DebugInfo {
+                        file_name: debug.file_name.to_string(),
+                        source_code: debug.source_code.to_string(),
+                    });
             &temp_options
         } else {
             &layout.naga_options
@@ -335,28 +353,17 @@ impl super::Device {
             allocation: None,
         }
     }
-}
 
-impl crate::Device for super::Device {
-    type A = super::Api;
-
-    unsafe fn exit(mut self, _queue: super::Queue) {
-        self.rtv_pool.lock().free_handle(self.null_rtv_handle);
-        self.mem_allocator = None;
-    }
-
-    unsafe fn create_buffer(
-        &self,
+    pub(super) fn buffer_resource_desc(
         desc: &crate::BufferDescriptor,
-    ) -> Result<super::Buffer, DeviceError> {
-        let mut resource = d3d12::Resource::null();
+    ) -> d3d12_ty::D3D12_RESOURCE_DESC {
         let mut size = desc.size;
         if desc.usage.contains(crate::BufferUses::UNIFORM) {
             let align_mask = d3d12_ty::D3D12_CONSTANT_BUFFER_DATA_PLACEMENT_ALIGNMENT as u64 - 1;
             size = ((size - 1) | align_mask) + 1;
         }
 
-        let raw_desc = d3d12_ty::D3D12_RESOURCE_DESC {
+        d3d12_ty::D3D12_RESOURCE_DESC {
             Dimension: d3d12_ty::D3D12_RESOURCE_DIMENSION_BUFFER,
             Alignment: 0,
             Width: size,
@@ -370,7 +377,51 @@ impl crate::Device for super::Device {
             },
             Layout: d3d12_ty::D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
             Flags: conv::map_buffer_usage_to_resource_flags(desc.usage),
-        };
+        }
+    }
+
+    pub(super) fn texture_resource_desc(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> d3d12_ty::D3D12_RESOURCE_DESC {
+        d3d12_ty::D3D12_RESOURCE_DESC {
+            Dimension: conv::map_texture_dimension(desc.dimension),
+            Alignment: 0,
+            Width: desc.size.width as u64,
+            Height: desc.size.height,
+            DepthOrArraySize: desc.size.depth_or_array_layers as u16,
+            MipLevels: desc.mip_level_count as u16,
+            Format: auxil::dxgi::conv::map_texture_format_for_resource(
+                desc.format,
+                desc.usage,
+                !desc.view_formats.is_empty(),
+                self.private_caps.casting_fully_typed_format_supported,
+            ),
+            SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                Count: desc.sample_count,
+                Quality: 0,
+            },
+            Layout: d3d12_ty::D3D12_TEXTURE_LAYOUT_UNKNOWN,
+            Flags: conv::map_texture_usage_to_resource_flags(desc.usage),
+        }
+    }
+}
+
+impl crate::Device for super::Device {
+    type A = super::Api;
+
+    unsafe fn exit(mut self, _queue: super::Queue) {
+        self.rtv_pool.lock().free_handle(self.null_rtv_handle);
+        self.mem_allocator = None;
+    }
+
+    unsafe fn create_buffer(
+        &self,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, DeviceError> {
+        let mut resource = d3d12::Resource::null();
+        let raw_desc = Self::buffer_resource_desc(desc);
+        let size = raw_desc.Width;
 
         let (hr, allocation) =
             super::suballocation::create_buffer_resource(self, desc, raw_desc, &mut resource)?;
@@ -434,26 +485,7 @@ impl crate::Device for super::Device {
 
         let mut resource = d3d12::Resource::null();
 
-        let raw_desc = d3d12_ty::D3D12_RESOURCE_DESC {
-            Dimension: conv::map_texture_dimension(desc.dimension),
-            Alignment: 0,
-            Width: desc.size.width as u64,
-            Height: desc.size.height,
-            DepthOrArraySize: desc.size.depth_or_array_layers as u16,
-            MipLevels: desc.mip_level_count as u16,
-            Format: auxil::dxgi::conv::map_texture_format_for_resource(
-                desc.format,
-                desc.usage,
-                !desc.view_formats.is_empty(),
-                self.private_caps.casting_fully_typed_format_supported,
-            ),
-            SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
-                Count: desc.sample_count,
-                Quality: 0,
-            },
-            Layout: d3d12_ty::D3D12_TEXTURE_LAYOUT_UNKNOWN,
-            Flags: conv::map_texture_usage_to_resource_flags(desc.usage),
-        };
+        let raw_desc = self.texture_resource_desc(desc);
 
         let (hr, allocation) = create_texture_resource(self, desc, raw_desc, &mut resource)?;
 
@@ -674,6 +706,7 @@ impl crate::Device for super::Device {
             null_rtv_handle: self.null_rtv_handle,
             list: None,
             free_lists: Vec::new(),
+            bundle_allocator: None,
             pass: super::PassState::new(),
             temp: super::Temp::default(),
             end_of_pass_timer_query: None,
@@ -989,21 +1022,26 @@ impl crate::Device for super::Device {
                         ..bt.clone()
                     },
                 );
-                info.dynamic_buffers.push(kind);
-
-                log::debug!(
-                    "\tParam[{}] = dynamic {:?} (vis = {:?})",
-                    parameters.len(),
-                    buffer_ty,
-                    dynamic_buffers_visibility,
-                );
-                parameters.push(d3d12::RootParameter::descriptor(
-                    parameter_ty,
-                    dynamic_buffers_visibility,
-                    native_binding(bt),
-                ));
 
-                bt.register += entry.count.map_or(1, NonZeroU32::get);
+                // Root descriptors can't be arrays, so each element of an
+                // array of dynamic buffers gets its own.
+                for _ in 0..entry.count.map_or(1, NonZeroU32::get) {
+                    info.dynamic_buffers.push(kind);
+
+                    log::debug!(
+                        "\tParam[{}] = dynamic {:?} (vis = {:?})",
+                        parameters.len(),
+                        buffer_ty,
+                        dynamic_buffers_visibility,
+                    );
+                    parameters.push(d3d12::RootParameter::descriptor(
+                        parameter_ty,
+                        dynamic_buffers_visibility,
+                        native_binding(bt),
+                    ));
+
+                    bt.register += 1;
+                }
             }
 
             bind_group_infos.push(info);
@@ -1082,6 +1120,7 @@ impl crate::Device for super::Device {
                 special_constants_binding,
                 push_constants_target,
                 zero_initialize_workgroup_memory: true,
+                debug_info: None,
             },
         })
     }
@@ -1279,10 +1318,20 @@ impl crate::Device for super::Device {
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         let raw_name = desc.label.and_then(|label| ffi::CString::new(label).ok());
         match shader {
-            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule { naga, raw_name }),
+            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule {
+                source: super::ShaderModuleSource::Naga(naga),
+                raw_name,
+            }),
             crate::ShaderInput::SpirV(_) => {
                 panic!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
             }
+            crate::ShaderInput::Dxil(dxil) => Ok(super::ShaderModule {
+                source: super::ShaderModuleSource::Dxil(dxil.to_vec()),
+                raw_name,
+            }),
+            crate::ShaderInput::Msl { .. } => {
+                panic!("MSL_SHADER_PASSTHROUGH is not enabled for this backend")
+            }
         }
     }
     unsafe fn destroy_shader_module(&self, _module: super::ShaderModule) {
@@ -1374,7 +1423,29 @@ impl crate::Device for super::Device {
             },
         };
 
-        let raw_desc = d3d12_ty::D3D12_GRAPHICS_PIPELINE_STATE_DESC {
+        let cache_entry = desc.cache.map(|cache| {
+            let shaders = [
+                blob_vs.bytecode(),
+                blob_fs.as_ref().map_or(&[][..], |blob| blob.bytecode()),
+            ];
+            let vertex_buffers = desc
+                .vertex_buffers
+                .iter()
+                .map(|vb| (vb.array_stride, vb.step_mode, vb.attributes))
+                .collect::<Vec<_>>();
+            let state = (
+                vertex_buffers,
+                desc.primitive,
+                &desc.depth_stencil,
+                desc.multisample,
+                desc.color_targets,
+                desc.multiview,
+            );
+            (cache, super::pipeline_cache::blob_key(&shaders, state))
+        });
+        let cached_blob = cache_entry.and_then(|(cache, key)| cache.get(key));
+
+        let mut raw_desc = d3d12_ty::D3D12_GRAPHICS_PIPELINE_STATE_DESC {
             pRootSignature: desc.layout.shared.signature.as_mut_ptr(),
             VS: *blob_vs.create_native_shader(),
             PS: match blob_fs {
@@ -1433,25 +1504,39 @@ impl crate::Device for super::Device {
                 Quality: 0,
             },
             NodeMask: 0,
-            CachedPSO: d3d12_ty::D3D12_CACHED_PIPELINE_STATE {
-                pCachedBlob: ptr::null(),
-                CachedBlobSizeInBytes: 0,
+            CachedPSO: match cached_blob {
+                Some(ref blob) => *d3d12::CachedPSO::from_bytes(blob),
+                None => *d3d12::CachedPSO::null(),
             },
             Flags: d3d12_ty::D3D12_PIPELINE_STATE_FLAG_NONE,
         };
 
-        let mut raw = d3d12::PipelineState::null();
-        let hr = {
-            profiling::scope!("ID3D12Device::CreateGraphicsPipelineState");
-            unsafe {
-                self.raw.CreateGraphicsPipelineState(
-                    &raw_desc,
-                    &d3d12_ty::ID3D12PipelineState::uuidof(),
-                    raw.mut_void(),
-                )
-            }
+        let create_pipeline_state = |raw_desc: &d3d12_ty::D3D12_GRAPHICS_PIPELINE_STATE_DESC| {
+            let mut raw = d3d12::PipelineState::null();
+            let hr = {
+                profiling::scope!("ID3D12Device::CreateGraphicsPipelineState");
+                unsafe {
+                    self.raw.CreateGraphicsPipelineState(
+                        raw_desc,
+                        &d3d12_ty::ID3D12PipelineState::uuidof(),
+                        raw.mut_void(),
+                    )
+                }
+            };
+            (raw, hr)
         };
 
+        let (mut raw, mut hr) = create_pipeline_state(&raw_desc);
+        let mut store_blob = cached_blob.is_none();
+        if hr < 0 && cached_blob.is_some() {
+            // The blob is from another driver, or its key collides with
+            // another pipeline's.
+            log::debug!("Cached blob of render pipeline {:?} is stale", desc.label);
+            raw_desc.CachedPSO = *d3d12::CachedPSO::null();
+            (raw, hr) = create_pipeline_state(&raw_desc);
+            store_blob = true;
+        }
+
         unsafe { blob_vs.destroy() };
         if let Some(blob_fs) = blob_fs {
             unsafe { blob_fs.destroy() };
@@ -1462,6 +1547,12 @@ impl crate::Device for super::Device {
 
         null_comptr_check(&raw)?;
 
+        if let Some((cache, key)) = cache_entry {
+            if store_blob {
+                cache.insert(key, &raw);
+            }
+        }
+
         if let Some(name) = desc.label {
             let cwstr = conv::map_label(name);
             unsafe { raw.SetName(cwstr.as_ptr()) };
@@ -1482,17 +1573,34 @@ impl crate::Device for super::Device {
     ) -> Result<super::ComputePipeline, crate::PipelineError> {
         let blob_cs = self.load_shader(&desc.stage, desc.layout, naga::ShaderStage::Compute)?;
 
-        let pair = {
+        let cache_entry = desc.cache.map(|cache| {
+            let key = super::pipeline_cache::blob_key(&[blob_cs.bytecode()], ());
+            (cache, key)
+        });
+        let cached_blob = cache_entry.and_then(|(cache, key)| cache.get(key));
+
+        let create_pipeline_state = |cached_pso| {
             profiling::scope!("ID3D12Device::CreateComputePipelineState");
             self.raw.create_compute_pipeline_state(
                 &desc.layout.shared.signature,
                 blob_cs.create_native_shader(),
                 0,
-                d3d12::CachedPSO::null(),
+                cached_pso,
                 d3d12::PipelineStateFlags::empty(),
             )
         };
 
+        let mut pair = create_pipeline_state(match cached_blob {
+            Some(ref blob) => d3d12::CachedPSO::from_bytes(blob),
+            None => d3d12::CachedPSO::null(),
+        });
+        let mut store_blob = cached_blob.is_none();
+        if pair.1 < 0 && cached_blob.is_some() {
+            log::debug!("Cached blob of compute pipeline {:?} is stale", desc.label);
+            pair = create_pipeline_state(d3d12::CachedPSO::null());
+            store_blob = true;
+        }
+
         unsafe { blob_cs.destroy() };
 
         let raw = pair.into_result().map_err(|err| {
@@ -1501,6 +1609,12 @@ impl crate::Device for super::Device {
 
         null_comptr_check(&raw)?;
 
+        if let Some((cache, key)) = cache_entry {
+            if store_blob {
+                cache.insert(key, &raw);
+            }
+        }
+
         if let Some(name) = desc.label {
             let cwstr = conv::map_label(name);
             unsafe { raw.SetName(cwstr.as_ptr()) };
@@ -1513,6 +1627,23 @@ impl crate::Device for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Result<super::PipelineCache, crate::PipelineCacheError> {
+        Ok(match desc.data {
+            Some(data) => super::PipelineCache::from_data(data),
+            None => super::PipelineCache::default(),
+        })
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        Some(cache.to_data())
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        Some(self.private_caps.pipeline_cache_validation_key)
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1674,6 +1805,19 @@ impl crate::Device for super::Device {
         }
     }
 
+    unsafe fn set_cache_limits(&self, _limits: &wgt::DeviceCacheLimits) {}
+    unsafe fn trim_caches(&self) {}
+    fn cache_report(&self) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport::default()
+    }
+
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        // Committed resources aren't suballocated, and have no allocator.
+        self.mem_allocator
+            .as_ref()
+            .map(super::suballocation::allocator_report)
+    }
+
     unsafe fn get_acceleration_structure_build_sizes<'a>(
         &self,
         _desc: &crate::GetAccelerationStructureBuildSizesDescriptor<'a, super::Api>,
@@ -0,0 +1,277 @@
+/*!
+Sharing buffers, textures and fences with other processes.
+
+[`Device::create_exportable_buffer`] and [`Device::create_exportable_texture`]
+create resources in shared heaps, which [`Device::export_buffer_memory`] and
+[`Device::export_texture_memory`] export as NT handles, and
+[`Device::import_buffer_memory`] and [`Device::import_texture_memory`] open in
+other processes, or on other devices of the same adapter.
+
+Fences are created shared, so [`Device::export_fence`] can export them as NT
+handles, and [`Device::import_fence`] can open the handles in other processes
+or on other devices of the same adapter. [`Queue::wait_for_fence`] makes the
+queue wait on a value of a fence signaled elsewhere.
+
+[`Device::create_exportable_buffer`]: super::Device::create_exportable_buffer
+[`Device::create_exportable_texture`]: super::Device::create_exportable_texture
+[`Device::export_buffer_memory`]: super::Device::export_buffer_memory
+[`Device::export_texture_memory`]: super::Device::export_texture_memory
+[`Device::import_buffer_memory`]: super::Device::import_buffer_memory
+[`Device::import_texture_memory`]: super::Device::import_texture_memory
+[`Device::export_fence`]: super::Device::export_fence
+[`Device::import_fence`]: super::Device::import_fence
+[`Queue::wait_for_fence`]: super::Queue::wait_for_fence
+*/
+
+use std::{
+    os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle},
+    ptr,
+};
+
+use winapi::{
+    um::{d3d12 as d3d12_ty, winnt},
+    Interface,
+};
+
+use crate::auxil::dxgi::result::HResult as _;
+
+impl super::Device {
+    /// Returns whether `other` can open the resources and fences exported by
+    /// this device, and the other way around, which is the case when both
+    /// are devices of the same adapter.
+    pub fn can_share_memory_with(&self, other: &Self) -> bool {
+        let (luid, other_luid) = unsafe { (self.raw.GetAdapterLuid(), other.raw.GetAdapterLuid()) };
+        luid.LowPart == other_luid.LowPart && luid.HighPart == other_luid.HighPart
+    }
+
+    /// Creates a buffer in a shared heap, which can be exported with
+    /// [`export_buffer_memory`](Self::export_buffer_memory).
+    ///
+    /// # Safety
+    ///
+    /// - `desc.usage` must not contain `MAP_READ` or `MAP_WRITE`.
+    pub unsafe fn create_exportable_buffer(
+        &self,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, crate::DeviceError> {
+        debug_assert!(!desc
+            .usage
+            .intersects(crate::BufferUses::MAP_READ | crate::BufferUses::MAP_WRITE));
+        let raw_desc = Self::buffer_resource_desc(desc);
+        let resource = unsafe { self.create_shared_resource(&raw_desc, desc.label) }?;
+        Ok(unsafe { Self::buffer_from_raw(resource, raw_desc.Width) })
+    }
+
+    /// Creates a texture in a shared heap, which can be exported with
+    /// [`export_texture_memory`](Self::export_texture_memory).
+    pub unsafe fn create_exportable_texture(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        let raw_desc = self.texture_resource_desc(desc);
+        let resource = unsafe { self.create_shared_resource(&raw_desc, desc.label) }?;
+        Ok(texture(resource, desc))
+    }
+
+    unsafe fn create_shared_resource(
+        &self,
+        raw_desc: &d3d12_ty::D3D12_RESOURCE_DESC,
+        label: crate::Label,
+    ) -> Result<d3d12::Resource, crate::DeviceError> {
+        let mut resource = d3d12::Resource::null();
+        let heap_properties = d3d12_ty::D3D12_HEAP_PROPERTIES {
+            Type: d3d12_ty::D3D12_HEAP_TYPE_DEFAULT,
+            CPUPageProperty: d3d12_ty::D3D12_CPU_PAGE_PROPERTY_UNKNOWN,
+            MemoryPoolPreference: d3d12_ty::D3D12_MEMORY_POOL_UNKNOWN,
+            CreationNodeMask: 0,
+            VisibleNodeMask: 0,
+        };
+        unsafe {
+            self.raw.CreateCommittedResource(
+                &heap_properties,
+                d3d12_ty::D3D12_HEAP_FLAG_SHARED,
+                raw_desc,
+                d3d12_ty::D3D12_RESOURCE_STATE_COMMON,
+                ptr::null(), // clear value
+                &d3d12_ty::ID3D12Resource::uuidof(),
+                resource.mut_void(),
+            )
+        }
+        .into_device_result("Shared resource creation")?;
+        super::null_comptr_check(&resource)?;
+
+        if let Some(label) = label {
+            let cwstr = super::conv::map_label(label);
+            unsafe { resource.SetName(cwstr.as_ptr()) };
+        }
+        Ok(resource)
+    }
+
+    /// Exports a buffer created by
+    /// [`create_exportable_buffer`](Self::create_exportable_buffer) as an NT
+    /// handle.
+    ///
+    /// Each call returns a new handle. The buffer stays alive until both it's
+    /// destroyed and the importers have released it.
+    pub unsafe fn export_buffer_memory(
+        &self,
+        buffer: &super::Buffer,
+    ) -> Result<crate::ExternalHandle, crate::DeviceError> {
+        unsafe { self.create_shared_handle(buffer.resource.as_mut_ptr().cast(), "Buffer export") }
+    }
+
+    /// Exports a texture created by
+    /// [`create_exportable_texture`](Self::create_exportable_texture) as an
+    /// NT handle.
+    ///
+    /// Each call returns a new handle. The texture stays alive until both it's
+    /// destroyed and the importers have released it.
+    pub unsafe fn export_texture_memory(
+        &self,
+        texture: &super::Texture,
+    ) -> Result<crate::ExternalHandle, crate::DeviceError> {
+        unsafe { self.create_shared_handle(texture.resource.as_mut_ptr().cast(), "Texture export") }
+    }
+
+    /// Opens a buffer exported by
+    /// [`export_buffer_memory`](Self::export_buffer_memory), possibly in
+    /// another process.
+    ///
+    /// The buffer aliases the exported one.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a buffer created with `desc`, by a
+    ///   device on the same adapter as this one.
+    pub unsafe fn import_buffer_memory(
+        &self,
+        handle: crate::ExternalHandle,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, crate::DeviceError> {
+        let resource = unsafe { self.open_shared_resource(handle, "Buffer import") }?;
+        Ok(unsafe { Self::buffer_from_raw(resource, Self::buffer_resource_desc(desc).Width) })
+    }
+
+    /// Opens a texture exported by
+    /// [`export_texture_memory`](Self::export_texture_memory), possibly in
+    /// another process.
+    ///
+    /// The texture aliases the exported one.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a texture created with `desc`, by a
+    ///   device on the same adapter as this one.
+    pub unsafe fn import_texture_memory(
+        &self,
+        handle: crate::ExternalHandle,
+        desc: &crate::TextureDescriptor,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        let resource = unsafe { self.open_shared_resource(handle, "Texture import") }?;
+        Ok(texture(resource, desc))
+    }
+
+    unsafe fn open_shared_resource(
+        &self,
+        handle: crate::ExternalHandle,
+        description: &str,
+    ) -> Result<d3d12::Resource, crate::DeviceError> {
+        let crate::ExternalHandle::NtHandle(handle) = handle;
+        let mut resource = d3d12::Resource::null();
+        unsafe {
+            self.raw.OpenSharedHandle(
+                handle.as_raw_handle().cast(),
+                &d3d12_ty::ID3D12Resource::uuidof(),
+                resource.mut_void(),
+            )
+        }
+        .into_device_result(description)?;
+        super::null_comptr_check(&resource)?;
+        Ok(resource)
+    }
+
+    /// Exports `fence` as an NT handle.
+    pub unsafe fn export_fence(
+        &self,
+        fence: &super::Fence,
+    ) -> Result<crate::ExternalHandle, crate::DeviceError> {
+        unsafe { self.create_shared_handle(fence.raw.as_mut_ptr().cast(), "Fence export") }
+    }
+
+    unsafe fn create_shared_handle(
+        &self,
+        object: *mut d3d12_ty::ID3D12DeviceChild,
+        description: &str,
+    ) -> Result<crate::ExternalHandle, crate::DeviceError> {
+        let mut handle = ptr::null_mut();
+        unsafe {
+            self.raw.CreateSharedHandle(
+                object,
+                ptr::null(),
+                winnt::GENERIC_ALL,
+                ptr::null(),
+                &mut handle,
+            )
+        }
+        .into_device_result(description)?;
+        Ok(crate::ExternalHandle::NtHandle(unsafe {
+            OwnedHandle::from_raw_handle(handle.cast())
+        }))
+    }
+
+    /// Opens a fence exported by [`export_fence`](Self::export_fence),
+    /// possibly in another process.
+    ///
+    /// The fence shares its value with the exported one.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a fence of a device on the same
+    ///   adapter as this one.
+    pub unsafe fn import_fence(
+        &self,
+        handle: crate::ExternalHandle,
+    ) -> Result<super::Fence, crate::DeviceError> {
+        let crate::ExternalHandle::NtHandle(handle) = handle;
+        let mut raw = d3d12::Fence::null();
+        unsafe {
+            self.raw.OpenSharedHandle(
+                handle.as_raw_handle().cast(),
+                &d3d12_ty::ID3D12Fence::uuidof(),
+                raw.mut_void(),
+            )
+        }
+        .into_device_result("Fence import")?;
+        super::null_comptr_check(&raw)?;
+        Ok(super::Fence { raw })
+    }
+}
+
+/// Wraps the resource of a shared texture created with `desc`.
+fn texture(resource: d3d12::Resource, desc: &crate::TextureDescriptor) -> super::Texture {
+    unsafe {
+        super::Device::texture_from_raw(
+            resource,
+            desc.format,
+            desc.dimension,
+            desc.size,
+            desc.mip_level_count,
+            desc.sample_count,
+        )
+    }
+}
+
+impl super::Queue {
+    /// Makes the queue wait until `fence` reaches `value` before executing
+    /// the next submissions.
+    ///
+    /// This is how work submitted to the queue waits on another process,
+    /// which shared the fence with [`Device::export_fence`](super::Device::export_fence).
+    pub unsafe fn wait_for_fence(
+        &self,
+        fence: &super::Fence,
+        value: crate::FenceValue,
+    ) -> Result<(), crate::DeviceError> {
+        unsafe { self.raw.Wait(fence.raw.as_mut_ptr(), value) }.into_device_result("Fence wait")
+    }
+}
@@ -152,9 +152,7 @@ impl crate::Instance for super::Instance {
 
         adapters
             .into_iter()
-            .filter_map(|raw| {
-                super::Adapter::expose(raw, &self.library, self.flags, self.dxc_container.clone())
-            })
+            .filter_map(|raw| self.expose_adapter(raw))
             .collect()
     }
 }
@@ -38,7 +38,9 @@ mod command;
 mod conv;
 mod descriptor;
 mod device;
+mod external;
 mod instance;
+mod pipeline_cache;
 mod shader_compilation;
 mod suballocation;
 mod types;
@@ -46,6 +48,8 @@ mod view;
 
 use crate::auxil::{self, dxgi::result::HResult as _};
 
+pub use pipeline_cache::PipelineCache;
+
 use arrayvec::ArrayVec;
 use parking_lot::{Mutex, RwLock};
 use std::{ffi, fmt, mem, num::NonZeroU32, sync::Arc};
@@ -67,6 +71,7 @@ impl crate::Api for Api {
     type Queue = Queue;
     type CommandEncoder = CommandEncoder;
     type CommandBuffer = CommandBuffer;
+    type RenderBundle = RenderBundle;
 
     type Buffer = Buffer;
     type Texture = Texture;
@@ -82,6 +87,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 
     type AccelerationStructure = AccelerationStructure;
 }
@@ -141,6 +147,18 @@ impl Instance {
             swap_chain: RwLock::new(None),
         }
     }
+
+    /// Exposes an adapter for a DXGI adapter obtained outside of wgpu, such as
+    /// the one an engine or an OpenXR runtime created its device on.
+    ///
+    /// D3D12 keeps a single device per adapter, so the adapter's
+    /// [`raw_device`](Adapter::raw_device) is the device already created on
+    /// `raw`, which [`Adapter::device_from_raw`] can then wrap.
+    ///
+    /// Returns `None` if no device can be created on `raw`.
+    pub fn expose_adapter(&self, raw: d3d12::DxgiAdapter) -> Option<crate::ExposedAdapter<Api>> {
+        Adapter::expose(raw, &self.library, self.flags, self.dxc_container.clone())
+    }
 }
 
 unsafe impl Send for Instance {}
@@ -196,6 +214,8 @@ struct PrivateCapabilities {
     casting_fully_typed_format_supported: bool,
     suballocation_supported: bool,
     shader_model: naga::back::hlsl::ShaderModel,
+    /// The adapter's vendor and device ids, and the driver's version.
+    pipeline_cache_validation_key: [u8; 16],
 }
 
 #[derive(Default)]
@@ -367,6 +387,8 @@ pub struct CommandEncoder {
     null_rtv_handle: descriptor::Handle,
     list: Option<d3d12::GraphicsCommandList>,
     free_lists: Vec<d3d12::GraphicsCommandList>,
+    /// The allocator of render bundles, created with the first of them.
+    bundle_allocator: Option<d3d12::CommandAllocator>,
     pass: PassState,
     temp: Temp,
 
@@ -395,6 +417,15 @@ pub struct CommandBuffer {
 unsafe impl Send for CommandBuffer {}
 unsafe impl Sync for CommandBuffer {}
 
+/// A render bundle, recorded in a bundle command list.
+#[derive(Debug)]
+pub struct RenderBundle {
+    raw: d3d12::GraphicsCommandList,
+}
+
+unsafe impl Send for RenderBundle {}
+unsafe impl Sync for RenderBundle {}
+
 #[derive(Debug)]
 pub struct Buffer {
     resource: d3d12::Resource,
@@ -571,12 +602,18 @@ pub struct PipelineLayout {
 
 #[derive(Debug)]
 pub struct ShaderModule {
-    naga: crate::NagaShader,
+    source: ShaderModuleSource,
     raw_name: Option<ffi::CString>,
 }
 
+#[derive(Debug)]
+enum ShaderModuleSource {
+    Naga(crate::NagaShader),
+    /// Precompiled DXIL, passed to pipeline creation as-is.
+    Dxil(Vec<u8>),
+}
+
 pub(super) enum CompiledShader {
-    #[allow(unused)]
     Dxc(Vec<u8>),
     Fxc(d3d12::Blob),
 }
@@ -589,6 +626,18 @@ impl CompiledShader {
         }
     }
 
+    fn bytecode(&self) -> &[u8] {
+        match *self {
+            CompiledShader::Dxc(ref shader) => shader,
+            CompiledShader::Fxc(ref shader) => unsafe {
+                std::slice::from_raw_parts(
+                    shader.GetBufferPointer().cast::<u8>(),
+                    shader.GetBufferSize(),
+                )
+            },
+        }
+    }
+
     unsafe fn destroy(self) {}
 }
 
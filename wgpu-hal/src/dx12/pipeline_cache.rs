@@ -0,0 +1,106 @@
+//! Pipeline caches, made of the cached blobs of pipeline state objects.
+//!
+//! D3D12 returns a blob of the compiled code of any pipeline state object,
+//! from which the same pipeline state can be created again much faster. A
+//! blob is only valid for the exact description it was created from, so
+//! caches key them by a hash of everything that goes into the description.
+//!
+//! The runtime checks blobs against the description, the adapter and the
+//! driver version, and fails pipeline creation if they don't match. Pipeline
+//! creation then retries without the blob, and replaces it in the cache.
+
+use std::{
+    hash::{Hash as _, Hasher as _},
+    slice,
+    sync::Arc,
+};
+
+use parking_lot::RwLock;
+
+/// The key of a pipeline's blob in a [`PipelineCache`].
+pub(super) type BlobKey = u64;
+
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    blobs: RwLock<rustc_hash::FxHashMap<BlobKey, Arc<[u8]>>>,
+}
+
+impl PipelineCache {
+    /// Reads the blobs written by [`Self::to_data`].
+    ///
+    /// The data is a count of blobs, followed by each blob's key, size and
+    /// contents, in little endian.
+    pub(super) fn from_data(mut data: &[u8]) -> Self {
+        fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            if data.len() < len {
+                return None;
+            }
+            let (head, tail) = data.split_at(len);
+            *data = tail;
+            Some(head)
+        }
+        fn take_u64(data: &mut &[u8]) -> Option<u64> {
+            Some(u64::from_le_bytes(take(data, 8)?.try_into().unwrap()))
+        }
+
+        let mut blobs = rustc_hash::FxHashMap::default();
+        let mut read = || -> Option<()> {
+            let count = take_u64(&mut data)?;
+            for _ in 0..count {
+                let key = take_u64(&mut data)?;
+                let len = take_u64(&mut data)?;
+                let blob = take(&mut data, usize::try_from(len).ok()?)?;
+                blobs.insert(key, Arc::from(blob));
+            }
+            Some(())
+        };
+        if read().is_none() {
+            log::warn!("Pipeline cache data is truncated");
+        }
+
+        Self {
+            blobs: RwLock::new(blobs),
+        }
+    }
+
+    pub(super) fn to_data(&self) -> Vec<u8> {
+        let blobs = self.blobs.read();
+        let size = 8 + blobs.values().map(|blob| 16 + blob.len()).sum::<usize>();
+        let mut data = Vec::with_capacity(size);
+        data.extend_from_slice(&(blobs.len() as u64).to_le_bytes());
+        for (&key, blob) in blobs.iter() {
+            data.extend_from_slice(&key.to_le_bytes());
+            data.extend_from_slice(&(blob.len() as u64).to_le_bytes());
+            data.extend_from_slice(blob);
+        }
+        data
+    }
+
+    pub(super) fn get(&self, key: BlobKey) -> Option<Arc<[u8]>> {
+        self.blobs.read().get(&key).cloned()
+    }
+
+    /// Stores the blob of `pipeline_state` under `key`.
+    pub(super) fn insert(&self, key: BlobKey, pipeline_state: &d3d12::PipelineState) {
+        let mut blob = d3d12::Blob::null();
+        let hr = unsafe { pipeline_state.GetCachedBlob(blob.mut_self()) };
+        if hr < 0 || blob.is_null() {
+            log::warn!("Failed to get the cached blob of a pipeline: {hr:#x}");
+            return;
+        }
+        let contents = unsafe {
+            slice::from_raw_parts(blob.GetBufferPointer().cast::<u8>(), blob.GetBufferSize())
+        };
+        self.blobs.write().insert(key, Arc::from(contents));
+    }
+}
+
+/// Hashes everything a pipeline's blob depends on besides its root
+/// signature, which the runtime checks the blob against.
+pub(super) fn blob_key(shaders: &[&[u8]], state: impl std::hash::Hash) -> BlobKey {
+    // `FxHasher` isn't randomly seeded, so keys stay the same between runs.
+    let mut hasher = rustc_hash::FxHasher::default();
+    shaders.hash(&mut hasher);
+    state.hash(&mut hasher);
+    hasher.finish()
+}
@@ -142,7 +142,7 @@ mod dxc {
         log::Level,
     ) {
         profiling::scope!("compile_dxc");
-        let mut compile_flags = arrayvec::ArrayVec::<&str, 6>::new_const();
+        let mut compile_flags = arrayvec::ArrayVec::<&str, 7>::new_const();
         compile_flags.push("-Ges"); // d3dcompiler::D3DCOMPILE_ENABLE_STRICTNESS
         compile_flags.push("-Vd"); // Disable implicit validation to work around bugs when dxil.dll isn't in the local directory.
         compile_flags.push("-HV"); // Use HLSL 2018, Naga doesn't supported 2021 yet.
@@ -155,6 +155,7 @@ mod dxc {
         {
             compile_flags.push("-Zi"); // d3dcompiler::D3DCOMPILE_SKIP_OPTIMIZATION
             compile_flags.push("-Od"); // d3dcompiler::D3DCOMPILE_DEBUG
+            compile_flags.push("-Qembed_debug"); // Keep the debug info in the DXIL container, for PIX
         }
 
         let blob = match dxc_container
@@ -1,5 +1,5 @@
 pub(crate) use allocation::{
-    create_allocator_wrapper, create_buffer_resource, create_texture_resource,
+    allocator_report, create_allocator_wrapper, create_buffer_resource, create_texture_resource,
     free_buffer_allocation, free_texture_allocation, AllocationWrapper, GpuAllocatorWrapper,
 };
 
@@ -192,6 +192,16 @@ mod placed {
         };
     }
 
+    pub(crate) fn allocator_report(allocator: &Mutex<GpuAllocatorWrapper>) -> wgt::AllocatorReport {
+        let report = allocator.lock().allocator.generate_report();
+        wgt::AllocatorReport {
+            allocation_count: report.allocations.len() as u64,
+            allocated_bytes: report.total_allocated_bytes,
+            block_count: report.blocks.len() as u64,
+            reserved_bytes: report.total_reserved_bytes,
+        }
+    }
+
     impl From<gpu_allocator::AllocationError> for crate::DeviceError {
         fn from(result: gpu_allocator::AllocationError) -> Self {
             match result {
@@ -365,4 +375,11 @@ mod committed {
     ) {
         // No-op when not using gpu-allocator
     }
+
+    pub(crate) fn allocator_report(
+        _allocator: &Mutex<GpuAllocatorWrapper>,
+    ) -> wgt::AllocatorReport {
+        // Never called, since there is no allocator when not using gpu-allocator
+        wgt::AllocatorReport::default()
+    }
 }
@@ -21,6 +21,7 @@ impl crate::Api for Api {
     type Queue = Context;
     type CommandEncoder = Encoder;
     type CommandBuffer = Resource;
+    type RenderBundle = Resource;
 
     type Buffer = Resource;
     type Texture = Resource;
@@ -37,6 +38,7 @@ impl crate::Api for Api {
     type ShaderModule = Resource;
     type RenderPipeline = Resource;
     type ComputePipeline = Resource;
+    type PipelineCache = Resource;
 }
 
 impl crate::Instance for Context {
@@ -88,6 +90,7 @@ impl crate::Adapter for Context {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_hints: &wgt::MemoryHints,
     ) -> DeviceResult<crate::OpenDevice<Api>> {
         Err(crate::DeviceError::Lost)
     }
@@ -221,6 +224,19 @@ impl crate::Device for Context {
         Ok(Resource)
     }
     unsafe fn destroy_compute_pipeline(&self, pipeline: Resource) {}
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Result<Resource, crate::PipelineCacheError> {
+        Ok(Resource)
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: Resource) {}
+    unsafe fn pipeline_cache_get_data(&self, cache: &Resource) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
 
     unsafe fn create_query_set(
         &self,
@@ -249,6 +265,14 @@ impl crate::Device for Context {
         false
     }
     unsafe fn stop_capture(&self) {}
+    unsafe fn set_cache_limits(&self, _limits: &wgt::DeviceCacheLimits) {}
+    unsafe fn trim_caches(&self) {}
+    fn cache_report(&self) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport::default()
+    }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
     unsafe fn create_acceleration_structure(
         &self,
         desc: &crate::AccelerationStructureDescriptor,
@@ -282,6 +306,17 @@ impl crate::CommandEncoder for Encoder {
     }
     unsafe fn reset_all<I>(&mut self, command_buffers: I) {}
 
+    unsafe fn begin_render_bundle(
+        &mut self,
+        desc: &crate::RenderBundleDescriptor,
+    ) -> DeviceResult<()> {
+        Ok(())
+    }
+    unsafe fn end_render_bundle(&mut self) -> DeviceResult<Resource> {
+        Ok(Resource)
+    }
+    unsafe fn reset_render_bundles<I>(&mut self, bundles: I) {}
+
     unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, Api>>,
@@ -371,6 +406,7 @@ impl crate::CommandEncoder for Encoder {
     unsafe fn end_debug_marker(&mut self) {}
 
     unsafe fn set_render_pipeline(&mut self, pipeline: &Resource) {}
+    unsafe fn execute_render_bundles<'a, I>(&mut self, bundles: I) {}
 
     unsafe fn set_index_buffer<'a>(
         &mut self,
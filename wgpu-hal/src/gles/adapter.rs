@@ -481,16 +481,41 @@ impl super::Adapter {
             extensions.contains("GL_EXT_blend_func_extended")
                 || extensions.contains("GL_ARB_blend_func_extended"),
         );
+        // Before GLSL ES 3.20, naga enables the geometry shader extensions
+        // for `gl_PrimitiveID`, which need GLSL ES 3.10.
         features.set(
             wgt::Features::SHADER_PRIMITIVE_INDEX,
             supported((3, 2), (3, 2))
-                || extensions.contains("OES_geometry_shader")
-                || extensions.contains("GL_ARB_geometry_shader4"),
+                || (shading_language_version >= naga::back::glsl::Version::new_gles(310)
+                    && (extensions.contains("GL_OES_geometry_shader")
+                        || extensions.contains("GL_EXT_geometry_shader"))),
+        );
+        features.set(
+            wgt::Features::CLIP_DISTANCES,
+            full_ver.is_some() || extensions.contains("GL_EXT_clip_cull_distance"),
+        );
+        features.set(
+            wgt::Features::CULL_DISTANCES,
+            full_ver.is_some_and(|full_ver| full_ver >= (4, 5))
+                || extensions.contains("GL_ARB_cull_distance")
+                || extensions.contains("GL_EXT_clip_cull_distance"),
         );
         features.set(
             wgt::Features::SHADER_EARLY_DEPTH_TEST,
             supported((3, 1), (4, 2)) || extensions.contains("GL_ARB_shader_image_load_store"),
         );
+        features.set(
+            wgt::Features::SHADER_INT64,
+            full_ver.is_some_and(|full_ver| full_ver >= (4, 0))
+                && extensions.contains("GL_ARB_gpu_shader_int64"),
+        );
+        features.set(
+            wgt::Features::TEXTURE_ATOMIC,
+            // Image atomics are only core in ES 3.2; ES 3.1 needs the OES extension.
+            supported((3, 2), (4, 2))
+                || extensions.contains("GL_OES_shader_image_atomic")
+                || extensions.contains("GL_ARB_shader_image_load_store"),
+        );
         features.set(wgt::Features::SHADER_UNUSED_VERTEX_OUTPUT, true);
         if extensions.contains("GL_ARB_timer_query") {
             features.set(wgt::Features::TIMESTAMP_QUERY, true);
@@ -859,6 +884,10 @@ impl super::Adapter {
                     buffer_copy_offset: wgt::BufferSize::new(4).unwrap(),
                     buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
                 },
+                // We don't use `GL_MAP_PERSISTENT_BIT`.
+                persistent_mapping: false,
+                unified_memory: false,
+                render_bundles: crate::NativeRenderBundles::Unsupported,
             },
         })
     }
@@ -950,6 +979,7 @@ impl crate::Adapter for super::Adapter {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_hints: &wgt::MemoryHints,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let gl = &self.shared.context.lock();
         unsafe { gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1) };
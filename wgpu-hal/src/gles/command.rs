@@ -272,6 +272,17 @@ impl crate::CommandEncoder for super::CommandEncoder {
         //TODO: could re-use the allocations in all these command buffers
     }
 
+    unsafe fn begin_render_bundle(
+        &mut self,
+        _desc: &crate::RenderBundleDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        unreachable!()
+    }
+    unsafe fn end_render_bundle(&mut self) -> Result<(), crate::DeviceError> {
+        unreachable!()
+    }
+    unsafe fn reset_render_bundles<I>(&mut self, _bundles: I) {}
+
     unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
@@ -839,6 +850,10 @@ impl crate::CommandEncoder for super::CommandEncoder {
         self.cmd_buffer.commands.push(C::PopDebugGroup);
     }
 
+    unsafe fn execute_render_bundles<'a, I>(&mut self, _bundles: I) {
+        unreachable!()
+    }
+
     unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
         self.state.topology = conv::map_primitive_topology(pipeline.primitive.topology);
 
@@ -403,7 +403,9 @@ pub(super) fn map_storage_access(access: wgt::StorageTextureAccess) -> u32 {
     match access {
         wgt::StorageTextureAccess::ReadOnly => glow::READ_ONLY,
         wgt::StorageTextureAccess::WriteOnly => glow::WRITE_ONLY,
-        wgt::StorageTextureAccess::ReadWrite => glow::READ_WRITE,
+        wgt::StorageTextureAccess::ReadWrite | wgt::StorageTextureAccess::Atomic => {
+            glow::READ_WRITE
+        }
     }
 }
 
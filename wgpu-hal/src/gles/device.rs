@@ -100,6 +100,13 @@ impl CompilationContext<'_> {
                     unsafe { gl.bind_frag_data_location(program, location.location, &name) }
                 }
                 naga::ShaderStage::Compute => {}
+                naga::ShaderStage::RayGeneration
+                | naga::ShaderStage::Intersection
+                | naga::ShaderStage::AnyHit
+                | naga::ShaderStage::ClosestHit
+                | naga::ShaderStage::Miss
+                | naga::ShaderStage::Task
+                | naga::ShaderStage::Mesh => unreachable!(),
             }
         }
 
@@ -173,6 +180,13 @@ impl super::Device {
             naga::ShaderStage::Vertex => glow::VERTEX_SHADER,
             naga::ShaderStage::Fragment => glow::FRAGMENT_SHADER,
             naga::ShaderStage::Compute => glow::COMPUTE_SHADER,
+            naga::ShaderStage::RayGeneration
+            | naga::ShaderStage::Intersection
+            | naga::ShaderStage::AnyHit
+            | naga::ShaderStage::ClosestHit
+            | naga::ShaderStage::Miss
+            | naga::ShaderStage::Task
+            | naga::ShaderStage::Mesh => unreachable!(),
         };
 
         let raw = unsafe { gl.create_shader(target) }.unwrap();
@@ -1286,6 +1300,12 @@ impl crate::Device for super::Device {
                 crate::ShaderInput::SpirV(_) => {
                     panic!("`Features::SPIRV_SHADER_PASSTHROUGH` is not enabled")
                 }
+                crate::ShaderInput::Dxil(_) => {
+                    panic!("`Features::DXIL_SHADER_PASSTHROUGH` is not enabled")
+                }
+                crate::ShaderInput::Msl { .. } => {
+                    panic!("`Features::MSL_SHADER_PASSTHROUGH` is not enabled")
+                }
                 crate::ShaderInput::Naga(naga) => naga,
             },
             label: desc.label.map(|str| str.to_string()),
@@ -1406,6 +1426,21 @@ impl crate::Device for super::Device {
         }
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Result<(), crate::PipelineCacheError> {
+        // Program binaries could be cached, but aren't yet.
+        Ok(())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &()) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+
     #[cfg_attr(target_arch = "wasm32", allow(unused))]
     unsafe fn create_query_set(
         &self,
@@ -1513,6 +1548,14 @@ impl crate::Device for super::Device {
                 .end_frame_capture(ptr::null_mut(), ptr::null_mut())
         }
     }
+    unsafe fn set_cache_limits(&self, _limits: &wgt::DeviceCacheLimits) {}
+    unsafe fn trim_caches(&self) {}
+    fn cache_report(&self) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport::default()
+    }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
     unsafe fn create_acceleration_structure(
         &self,
         _desc: &crate::AccelerationStructureDescriptor,
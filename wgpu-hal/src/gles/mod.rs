@@ -145,6 +145,7 @@ impl crate::Api for Api {
     type Queue = Queue;
     type CommandEncoder = CommandEncoder;
     type CommandBuffer = CommandBuffer;
+    type RenderBundle = ();
 
     type Buffer = Buffer;
     type Texture = Texture;
@@ -161,6 +162,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = ();
 }
 
 bitflags::bitflags! {
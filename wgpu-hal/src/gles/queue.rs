@@ -1742,6 +1742,7 @@ impl crate::Queue for super::Queue {
         _surface_textures: &[&super::Texture],
         (signal_fence, signal_value): (&mut super::Fence, crate::FenceValue),
     ) -> Result<(), crate::DeviceError> {
+        profiling::scope!("Queue::submit");
         let shared = Arc::clone(&self.shared);
         let gl = &shared.context.lock();
         for cmd_buf in command_buffers.iter() {
@@ -255,9 +255,15 @@ pub mod gles;
 /// Metal API internals.
 #[cfg(metal)]
 pub mod metal;
+/// Hardware video encoding.
+#[cfg(vulkan)]
+pub mod video;
 /// Vulkan API internals.
 #[cfg(vulkan)]
 pub mod vulkan;
+/// Helpers for rendering to OpenXR swapchains.
+#[cfg(any(vulkan, dx12))]
+pub mod xr;
 
 pub mod auxil;
 pub mod api {
@@ -304,6 +310,21 @@ pub type FenceValue = u64;
 /// Drop guard to signal wgpu-hal is no longer using an externally created object.
 pub type DropGuard = Box<dyn std::any::Any + Send + Sync>;
 
+/// An OS handle to a fence or memory, shared with another API or process.
+///
+/// Send file descriptors to another process over a Unix socket, and duplicate
+/// NT handles into it with `DuplicateHandle`.
+///
+/// Memory file descriptors are opaque, like Vulkan `OPAQUE_FD` handles, and
+/// DMA-BUF file descriptors and `IOSurface`s aren't supported.
+#[derive(Debug)]
+pub enum ExternalHandle {
+    #[cfg(unix)]
+    Fd(std::os::fd::OwnedFd),
+    #[cfg(windows)]
+    NtHandle(std::os::windows::io::OwnedHandle),
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Error)]
 pub enum DeviceError {
     #[error("Out of memory")]
@@ -332,6 +353,12 @@ pub enum PipelineError {
     Device(#[from] DeviceError),
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum PipelineCacheError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Error)]
 pub enum SurfaceError {
     #[error("Surface is lost")]
@@ -400,6 +427,13 @@ pub trait Api: Clone + fmt::Debug + Sized {
     /// [`CommandEncoder`]: Api::CommandEncoder
     type CommandBuffer: WasmNotSendSync + fmt::Debug;
 
+    /// A sequence of render pass commands recorded ahead of time, that can be
+    /// executed in render passes by [`CommandEncoder::execute_render_bundles`].
+    ///
+    /// Only backends whose [`Capabilities::render_bundles`] isn't
+    /// [`NativeRenderBundles::Unsupported`] create these.
+    type RenderBundle: WasmNotSendSync + fmt::Debug;
+
     type Buffer: fmt::Debug + WasmNotSendSync + 'static;
     type Texture: fmt::Debug + WasmNotSendSync + 'static;
     type SurfaceTexture: fmt::Debug + WasmNotSendSync + Borrow<Self::Texture>;
@@ -432,6 +466,7 @@ pub trait Api: Clone + fmt::Debug + Sized {
     type ShaderModule: fmt::Debug + WasmNotSendSync;
     type RenderPipeline: fmt::Debug + WasmNotSendSync;
     type ComputePipeline: fmt::Debug + WasmNotSendSync;
+    type PipelineCache: fmt::Debug + WasmNotSendSync;
 
     type AccelerationStructure: fmt::Debug + WasmNotSendSync + 'static;
 }
@@ -506,6 +541,7 @@ pub trait Adapter: WasmNotSendSync {
         &self,
         features: wgt::Features,
         limits: &wgt::Limits,
+        memory_hints: &wgt::MemoryHints,
     ) -> Result<OpenDevice<Self::A>, DeviceError>;
 
     /// Return the set of supported capabilities for a texture format.
@@ -617,6 +653,24 @@ pub trait Device: WasmNotSendSync {
         desc: &ComputePipelineDescriptor<Self::A>,
     ) -> Result<<Self::A as Api>::ComputePipeline, PipelineError>;
     unsafe fn destroy_compute_pipeline(&self, pipeline: <Self::A as Api>::ComputePipeline);
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &PipelineCacheDescriptor<'_>,
+    ) -> Result<<Self::A as Api>::PipelineCache, PipelineCacheError>;
+    unsafe fn destroy_pipeline_cache(&self, cache: <Self::A as Api>::PipelineCache);
+    /// Returns the contents of `cache`, to create a cache from in a later
+    /// run, or `None` if there are none.
+    unsafe fn pipeline_cache_get_data(
+        &self,
+        cache: &<Self::A as Api>::PipelineCache,
+    ) -> Option<Vec<u8>>;
+    /// A key identifying the format of this device's pipeline cache data.
+    ///
+    /// The data of a cache is only valid for devices with the same key:
+    /// backends include the driver version in it, or an identifier the
+    /// driver changes when its format does. `None` if the backend has no
+    /// pipeline caches.
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]>;
 
     unsafe fn create_query_set(
         &self,
@@ -658,6 +712,21 @@ pub trait Device: WasmNotSendSync {
     unsafe fn start_capture(&self) -> bool;
     unsafe fn stop_capture(&self);
 
+    /// Change how many objects the device keeps in its internal caches,
+    /// evicting the least recently used objects beyond that.
+    ///
+    /// Backends ignore the limits of objects they don't cache.
+    unsafe fn set_cache_limits(&self, limits: &wgt::DeviceCacheLimits);
+    /// Evict every object in the device's internal caches.
+    ///
+    /// Evicted objects used by command buffers that haven't been reset yet
+    /// are only destroyed once they are.
+    unsafe fn trim_caches(&self);
+    fn cache_report(&self) -> wgt::DeviceCacheReport;
+    /// Returns statistics of the device's memory allocator, or `None` if the
+    /// backend doesn't keep them.
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport>;
+
     unsafe fn create_acceleration_structure(
         &self,
         desc: &AccelerationStructureDescriptor,
@@ -852,6 +921,53 @@ pub trait CommandEncoder: WasmNotSendSync + fmt::Debug {
     where
         I: Iterator<Item = <Self::A as Api>::CommandBuffer>;
 
+    /// Begin recording a render bundle.
+    ///
+    /// Until the matching [`end_render_bundle`], this encoder records the
+    /// render pass commands it is given into the bundle, as if they were in a
+    /// render pass with attachments matching `desc`. The render pass commands
+    /// are the only ones allowed in a bundle, and it can't query or write
+    /// timestamps.
+    ///
+    /// # Safety
+    ///
+    /// - This encoder must be in the "closed" state.
+    ///
+    /// - The capabilities of the adapter must have [`render_bundles`] other
+    ///   than [`NativeRenderBundles::Unsupported`].
+    ///
+    /// [`end_render_bundle`]: CommandEncoder::end_render_bundle
+    /// [`render_bundles`]: Capabilities::render_bundles
+    unsafe fn begin_render_bundle(
+        &mut self,
+        desc: &RenderBundleDescriptor,
+    ) -> Result<(), DeviceError>;
+
+    /// Finish recording a render bundle, returning it.
+    ///
+    /// This encoder returns to the "closed" state.
+    ///
+    /// # Safety
+    ///
+    /// This encoder must be recording a render bundle.
+    unsafe fn end_render_bundle(&mut self) -> Result<<Self::A as Api>::RenderBundle, DeviceError>;
+
+    /// Destroy render bundles, and reclaim their memory.
+    ///
+    /// # Safety
+    ///
+    /// - This encoder must be in the "closed" state, with no live
+    ///   [`CommandBuffer`]s built using it.
+    ///
+    /// - The `bundles` iterator must produce all the live render bundles
+    ///   built using this encoder, and no command buffer executing them may
+    ///   still be in use.
+    ///
+    /// [`CommandBuffer`]: Api::CommandBuffer
+    unsafe fn reset_render_bundles<I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = <Self::A as Api>::RenderBundle>;
+
     unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = BufferBarrier<'a, Self::A>>;
@@ -984,6 +1100,26 @@ pub trait CommandEncoder: WasmNotSendSync + fmt::Debug {
 
     unsafe fn set_render_pipeline(&mut self, pipeline: &<Self::A as Api>::RenderPipeline);
 
+    /// Execute render bundles in the current render pass.
+    ///
+    /// The bindings, pipeline, and vertex and index buffers of the pass are
+    /// unset afterwards.
+    ///
+    /// # Safety
+    ///
+    /// - The bundles must have been recorded with a [`RenderBundleDescriptor`]
+    ///   matching the attachments of the pass.
+    ///
+    /// - If the capabilities of the adapter have [`render_bundles`] set to
+    ///   [`NativeRenderBundles::PassContents`], the pass must have been begun
+    ///   with [`RenderPassDescriptor::render_bundles_only`], and this must be
+    ///   its only kind of command.
+    ///
+    /// [`render_bundles`]: Capabilities::render_bundles
+    unsafe fn execute_render_bundles<'a, I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = &'a <Self::A as Api>::RenderBundle>;
+
     unsafe fn set_index_buffer<'a>(
         &mut self,
         binding: BufferBinding<'a, Self::A>,
@@ -1258,6 +1394,11 @@ bitflags::bitflags! {
         /// If a usage is ordered, then if the buffer state doesn't change between draw calls, there
         /// are no barriers needed for synchronization.
         const ORDERED = Self::INCLUSIVE.bits() | Self::MAP_WRITE.bits();
+
+        /// Flag used by the wgpu-core buffer tracker to say different ranges of a buffer are in different states.
+        const COMPLEX = 1 << 14;
+        /// Flag used by the wgpu-core buffer tracker to say that the tracker does not know the state of a range.
+        const UNKNOWN = 1 << 15;
     }
 }
 
@@ -1324,6 +1465,38 @@ pub struct Capabilities {
     pub limits: wgt::Limits,
     pub alignments: Alignments,
     pub downlevel: wgt::DownlevelCapabilities,
+    /// Whether buffers can be used by the GPU while they are mapped.
+    ///
+    /// When this is `true`, a buffer created with `MAP_WRITE` can stay mapped
+    /// for its whole lifetime, with the host writing to the parts of it that
+    /// the GPU isn't using.
+    pub persistent_mapping: bool,
+    /// Whether the GPU uses memory the host can write to as fast as any
+    /// other, as on integrated GPUs.
+    ///
+    /// When this is `true`, buffers created with `MAP_WRITE` can have any
+    /// other usage without slowing the GPU down, so that they can be written
+    /// to by the host instead of through a staging buffer.
+    pub unified_memory: bool,
+    /// How render bundles can be recorded ahead of time.
+    pub render_bundles: NativeRenderBundles,
+}
+
+/// Whether, and where, a backend can execute render bundles it recorded
+/// ahead of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NativeRenderBundles {
+    /// Render bundles can't be recorded.
+    Unsupported,
+    /// Render bundles can only be executed in passes begun with
+    /// [`RenderPassDescriptor::render_bundles_only`], and they don't inherit
+    /// any state from the pass. This is how Vulkan's secondary command buffers
+    /// work.
+    PassContents,
+    /// Render bundles can be executed anywhere in a render pass, and inherit
+    /// its viewport, scissor rectangle, blend constant and stencil reference.
+    /// This is how D3D12's bundles work.
+    Inline,
 }
 
 #[derive(Debug)]
@@ -1580,6 +1753,8 @@ pub struct NagaShader {
     pub info: naga::valid::ModuleInfo,
     /// Source codes for debug
     pub debug_source: Option<DebugSource>,
+    /// Where to cache the backend's translation of this module.
+    pub cache: Option<ShaderCacheEntry>,
 }
 
 // Custom implementation avoids the need to generate Debug impl code
@@ -1595,6 +1770,26 @@ impl fmt::Debug for NagaShader {
 pub enum ShaderInput<'a> {
     Naga(NagaShader),
     SpirV(&'a [u32]),
+    /// A validated DXIL container, for the DX12 backend.
+    Dxil(&'a [u8]),
+    /// Metal code, for the Metal backend.
+    ///
+    /// Metal needs compute dispatches to state the workgroup size, which
+    /// isn't part of the code, so it's given here for all of the module's
+    /// compute entry points.
+    Msl {
+        source: MslSource<'a>,
+        workgroup_size: [u32; 3],
+    },
+}
+
+/// Metal code passed to [`ShaderInput::Msl`].
+#[derive(Clone, Copy, Debug)]
+pub enum MslSource<'a> {
+    /// Metal Shading Language source code.
+    Source(&'a str),
+    /// A compiled `metallib` library.
+    Library(&'a [u8]),
 }
 
 pub struct ShaderModuleDescriptor<'a> {
@@ -1602,6 +1797,56 @@ pub struct ShaderModuleDescriptor<'a> {
     pub runtime_checks: bool,
 }
 
+/// Persistent storage for the results of translating shaders.
+///
+/// Values are opaque to the storage. A missing or unreadable entry is treated
+/// as a cache miss, and failing to store an entry is not an error.
+pub trait ShaderCache: fmt::Debug + WasmNotSendSync {
+    /// Returns the value stored under `key`, if any.
+    fn load(&self, key: &str) -> Option<Vec<u8>>;
+    /// Stores `value` under `key`, replacing any previous value.
+    fn store(&self, key: &str, value: &[u8]);
+}
+
+/// A [`ShaderCache`] together with the key of the module being translated.
+#[derive(Debug, Clone)]
+pub struct ShaderCacheEntry {
+    pub cache: Arc<dyn ShaderCache>,
+    /// Hash of the module, and of everything that went into building it.
+    ///
+    /// Backends combine it with their own options to key their output.
+    pub module_key: u64,
+}
+
+impl ShaderCacheEntry {
+    /// Builds the key of a translation result from the module key and
+    /// everything else the translation depends on.
+    pub fn key(&self, prefix: &str, options: impl std::hash::Hash) -> String {
+        use std::hash::{Hash as _, Hasher as _};
+
+        let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+        self.module_key.hash(&mut hasher);
+        options.hash(&mut hasher);
+        format!("{prefix}-{:016x}", hasher.finish())
+    }
+}
+
+/// A 64-bit FNV-1a hasher, which unlike the standard library's hashers is
+/// guaranteed to give the same hash on every run and with every Rust version.
+struct FnvHasher(u64);
+
+impl std::hash::Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DebugSource {
     pub file_name: Cow<'static, str>,
@@ -1645,6 +1890,20 @@ pub struct ComputePipelineDescriptor<'a, A: Api> {
     pub layout: &'a A::PipelineLayout,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStage<'a, A>,
+    /// The cache to look the pipeline up in, and to store it in.
+    pub cache: Option<&'a A::PipelineCache>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// The contents of a cache created earlier, returned by
+    /// [`Device::pipeline_cache_get_data`].
+    ///
+    /// They must come from a device with the same
+    /// [`Device::pipeline_cache_validation_key`]. Backends may still find
+    /// them unusable, in which case the cache starts out empty.
+    pub data: Option<&'a [u8]>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1681,6 +1940,8 @@ pub struct RenderPipelineDescriptor<'a, A: Api> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The cache to look the pipeline up in, and to store it in.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 #[derive(Debug, Clone)]
@@ -1715,6 +1976,12 @@ pub struct Rect<T> {
 #[derive(Debug, Clone)]
 pub struct BufferBarrier<'a, A: Api> {
     pub buffer: &'a A::Buffer,
+    /// The part of the buffer the barrier applies to, or `None` for the
+    /// whole buffer.
+    ///
+    /// Backends that can't restrict barriers to part of a buffer apply them
+    /// to the whole buffer regardless.
+    pub range: Option<Range<wgt::BufferAddress>>,
     pub usage: Range<BufferUses>,
 }
 
@@ -1837,6 +2104,28 @@ pub struct RenderPassDescriptor<'a, A: Api> {
     pub multiview: Option<NonZeroU32>,
     pub timestamp_writes: Option<RenderPassTimestampWrites<'a, A>>,
     pub occlusion_query_set: Option<&'a A::QuerySet>,
+    /// Whether the pass only executes render bundles.
+    ///
+    /// See [`NativeRenderBundles::PassContents`].
+    pub render_bundles_only: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderBundleColorTarget {
+    pub format: wgt::TextureFormat,
+    /// Whether the attachment is resolved at the end of the pass.
+    pub resolve: bool,
+}
+
+/// Describes the attachments of the render passes a render bundle is
+/// executed in.
+#[derive(Clone, Debug)]
+pub struct RenderBundleDescriptor<'a> {
+    pub label: Label<'a>,
+    pub color_targets: &'a [Option<RenderBundleColorTarget>],
+    pub depth_stencil_format: Option<wgt::TextureFormat>,
+    pub sample_count: u32,
+    pub multiview: Option<NonZeroU32>,
 }
 
 #[derive(Debug)]
@@ -16,22 +16,22 @@ impl super::Adapter {
     pub(super) fn new(shared: Arc<super::AdapterShared>) -> Self {
         Self { shared }
     }
-}
 
-impl crate::Adapter for super::Adapter {
-    type A = super::Api;
-
-    unsafe fn open(
+    /// Wraps a command queue created outside of wgpu.
+    ///
+    /// The device keeps its own reference to `raw_queue`, so the caller can
+    /// go on committing its own command buffers to it, ordered with wgpu's
+    /// submissions by the queue itself.
+    ///
+    /// # Safety
+    ///
+    /// - `raw_queue` must be created from this adapter's `MTLDevice`.
+    /// - `features` must be a subset of the features this adapter exposes.
+    pub unsafe fn device_from_raw(
         &self,
+        raw_queue: metal::CommandQueue,
         features: wgt::Features,
-        _limits: &wgt::Limits,
-    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
-        let queue = self
-            .shared
-            .device
-            .lock()
-            .new_command_queue_with_max_command_buffer_count(MAX_COMMAND_BUFFERS);
-
+    ) -> crate::OpenDevice<super::Api> {
         // Acquiring the meaning of timestamp ticks is hard with Metal!
         // The only thing there is is a method correlating cpu & gpu timestamps (`device.sample_timestamps`).
         // Users are supposed to call this method twice and calculate the difference,
@@ -58,16 +58,35 @@ impl crate::Adapter for super::Adapter {
             1.0
         };
 
-        Ok(crate::OpenDevice {
+        crate::OpenDevice {
             device: super::Device {
                 shared: Arc::clone(&self.shared),
                 features,
             },
             queue: super::Queue {
-                raw: Arc::new(Mutex::new(queue)),
+                raw: Arc::new(Mutex::new(raw_queue)),
                 timestamp_period,
             },
-        })
+        }
+    }
+}
+
+impl crate::Adapter for super::Adapter {
+    type A = super::Api;
+
+    unsafe fn open(
+        &self,
+        features: wgt::Features,
+        _limits: &wgt::Limits,
+        _memory_hints: &wgt::MemoryHints,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        let queue = self
+            .shared
+            .device
+            .lock()
+            .new_command_queue_with_max_command_buffer_count(MAX_COMMAND_BUFFERS);
+
+        Ok(unsafe { self.device_from_raw(queue, features) })
     }
 
     unsafe fn texture_format_capabilities(
@@ -781,9 +800,14 @@ impl super::PrivateCapabilities {
                     MTLFeatureSet::tvOS_GPUFamily1_v2,
                 ],
             ),
-            supports_binary_archives: family_check
+            supports_binary_archives: (os_is_xr || version.at_least((11, 0), (14, 0), os_is_mac))
                 && (device.supports_family(MTLGPUFamily::Apple3)
                     || device.supports_family(MTLGPUFamily::Mac1)),
+            os_version: (
+                version.major as u32,
+                version.minor as u32,
+                version.patch as u32,
+            ),
             supports_capture_manager: version.at_least((10, 13), (11, 0), os_is_mac),
             can_set_maximum_drawables_count: version.at_least((10, 14), (11, 2), os_is_mac),
             can_set_display_sync: version.at_least((10, 13), OS_NOT_SUPPORT, os_is_mac),
@@ -807,6 +831,12 @@ impl super::PrivateCapabilities {
             supports_preserve_invariance: version.at_least((11, 0), (13, 0), os_is_mac),
             // Metal 2.2 on mac, 2.3 on iOS.
             supports_shader_primitive_index: version.at_least((10, 15), (14, 0), os_is_mac),
+            // supportsShaderBarycentricCoordinates available on macOS 10.15+ and iOS 14.0+
+            supports_shader_barycentrics: version.at_least((10, 15), (14, 0), os_is_mac) && {
+                let supported: objc::runtime::BOOL =
+                    unsafe { msg_send![device.as_ref(), supportsShaderBarycentricCoordinates] };
+                supported == objc::runtime::YES
+            },
             has_unified_memory: if version.at_least((10, 15), (13, 0), os_is_mac) {
                 Some(device.has_unified_memory())
             } else {
@@ -845,9 +875,11 @@ impl super::PrivateCapabilities {
             | F::TEXTURE_FORMAT_16BIT_NORM
             | F::SHADER_F16
             | F::DEPTH32FLOAT_STENCIL8
+            | F::DEPTH32FLOAT_COPY_DST
             | F::BGRA8UNORM_STORAGE;
 
         features.set(F::FLOAT32_FILTERABLE, self.supports_float_filtering);
+        features.set(F::PIPELINE_CACHE, self.supports_binary_archives);
         features.set(
             F::INDIRECT_FIRST_INSTANCE | F::MULTI_DRAW_INDIRECT,
             self.indirect_draw_dispatch,
@@ -876,6 +908,10 @@ impl super::PrivateCapabilities {
             F::SHADER_PRIMITIVE_INDEX,
             self.supports_shader_primitive_index,
         );
+        features.set(
+            F::SHADER_BARYCENTRICS,
+            self.supports_shader_barycentrics && self.msl_version >= MTLLanguageVersion::V2_2,
+        );
 
         features.set(
             F::TEXTURE_BINDING_ARRAY
@@ -896,6 +932,13 @@ impl super::PrivateCapabilities {
             F::SHADER_INT64,
             self.int64 && self.msl_version >= MTLLanguageVersion::V2_3,
         );
+        features.set(
+            F::TEXTURE_ATOMIC,
+            self.msl_version >= MTLLanguageVersion::V3_1,
+        );
+        features.insert(F::UNRESTRICTED_POINTER_PARAMETERS);
+        features.insert(F::CLIP_DISTANCES);
+        features.insert(F::MSL_SHADER_PASSTHROUGH);
 
         features.set(
             F::ADDRESS_MODE_CLAMP_TO_BORDER,
@@ -907,7 +950,7 @@ impl super::PrivateCapabilities {
         features.set(F::SHADER_UNUSED_VERTEX_OUTPUT, true);
 
         if self.supports_simd_scoped_operations {
-            features.insert(F::SUBGROUP | F::SUBGROUP_BARRIER);
+            features.insert(F::SUBGROUP | F::SUBGROUP_BARRIER | F::SUBGROUP_EXTENDED);
         }
 
         features
@@ -988,6 +1031,12 @@ impl super::PrivateCapabilities {
                 buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
             },
             downlevel,
+            persistent_mapping: true,
+            // Shared buffers are in the same memory as private ones.
+            unified_memory: self.has_unified_memory == Some(true),
+            // Indirect command buffers could back render bundles, but would
+            // need the bind groups to be in argument buffers.
+            render_bundles: crate::NativeRenderBundles::Unsupported,
         }
     }
 
@@ -228,6 +228,23 @@ impl crate::CommandEncoder for super::CommandEncoder {
         //do nothing
     }
 
+    unsafe fn begin_render_bundle(
+        &mut self,
+        _desc: &crate::RenderBundleDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        unimplemented!()
+    }
+
+    unsafe fn end_render_bundle(&mut self) -> Result<(), crate::DeviceError> {
+        unimplemented!()
+    }
+
+    unsafe fn reset_render_bundles<I>(&mut self, _bundles: I)
+    where
+        I: Iterator<Item = ()>,
+    {
+    }
+
     unsafe fn transition_buffers<'a, T>(&mut self, _barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
@@ -855,6 +872,13 @@ impl crate::CommandEncoder for super::CommandEncoder {
         }
     }
 
+    unsafe fn execute_render_bundles<'a, I>(&mut self, _bundles: I)
+    where
+        I: Iterator<Item = &'a ()>,
+    {
+        unimplemented!()
+    }
+
     unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
         self.state.raw_primitive_type = pipeline.raw_primitive_type;
         self.state.stage_infos.vs.assign_from(&pipeline.vs_info);
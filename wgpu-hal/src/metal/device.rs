@@ -11,6 +11,23 @@ use crate::auxil::map_naga_stage;
 
 type DeviceResult<T> = Result<T, crate::DeviceError>;
 
+/// A new path to serialize a binary archive to, or load one from.
+///
+/// Metal only reads and writes binary archives as files, so the contents of
+/// pipeline caches go through temporary files.
+fn binary_archive_path() -> std::path::PathBuf {
+    static NEXT: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "wgpu-pipeline-cache-{}-{}.metallib",
+        std::process::id(),
+        NEXT.fetch_add(1, atomic::Ordering::Relaxed),
+    ))
+}
+
+fn file_url(path: &std::path::Path) -> metal::URL {
+    metal::URL::new_with_string(&format!("file://{}", path.display()))
+}
+
 struct CompiledShader {
     library: metal::Library,
     function: metal::Function,
@@ -69,9 +86,32 @@ impl super::Device {
     ) -> Result<CompiledShader, crate::PipelineError> {
         let stage_bit = map_naga_stage(naga_stage);
 
+        let naga_shader = match stage.module.source {
+            super::ShaderModuleSource::Naga(ref naga) => naga,
+            super::ShaderModuleSource::Raw {
+                ref library,
+                wg_size,
+            } => {
+                // Pipeline constants aren't applied; the code has to use
+                // its own defaults.
+                let function = library.get_function(stage.entry_point, None).map_err(|e| {
+                    log::error!("get_function: {:?}", e);
+                    crate::PipelineError::EntryPoint(naga_stage)
+                })?;
+                return Ok(CompiledShader {
+                    library: library.clone(),
+                    function,
+                    wg_size,
+                    wg_memory_sizes: Vec::new(),
+                    sized_bindings: Vec::new(),
+                    immutable_buffer_mask: 0,
+                });
+            }
+        };
+
         let (module, module_info) = naga::back::pipeline_constants::process_overrides(
-            &stage.module.naga.module,
-            &stage.module.naga.info,
+            &naga_shader.module,
+            &naga_shader.info,
             stage.constants,
         )
         .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("MSL: {:?}", e)))?;
@@ -113,6 +153,13 @@ impl super::Device {
                 binding_array: naga::proc::BoundsCheckPolicy::Unchecked,
             },
             zero_initialize_workgroup_memory: stage.zero_initialize_workgroup_memory,
+            debug_info: naga_shader
+                .debug_source
+                .as_ref()
+                .map(|debug| naga::back::DebugInfo {
+                    file_name: debug.file_name.to_string(),
+                    source_code: debug.source_code.to_string(),
+                }),
         };
 
         let pipeline_options = naga::back::msl::PipelineOptions {
@@ -640,7 +687,8 @@ impl crate::Device for super::Device {
                             target.mutable = match access {
                                 wgt::StorageTextureAccess::ReadOnly => false,
                                 wgt::StorageTextureAccess::WriteOnly => true,
-                                wgt::StorageTextureAccess::ReadWrite => true,
+                                wgt::StorageTextureAccess::ReadWrite
+                                | wgt::StorageTextureAccess::Atomic => true,
                             };
                         }
                         wgt::BindingType::AccelerationStructure => unimplemented!(),
@@ -801,12 +849,48 @@ impl crate::Device for super::Device {
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         match shader {
             crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule {
-                naga,
+                source: super::ShaderModuleSource::Naga(naga),
                 runtime_checks: desc.runtime_checks,
             }),
             crate::ShaderInput::SpirV(_) => {
                 panic!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
             }
+            crate::ShaderInput::Dxil(_) => {
+                panic!("DXIL_SHADER_PASSTHROUGH is not enabled for this backend")
+            }
+            crate::ShaderInput::Msl {
+                source,
+                workgroup_size,
+            } => {
+                let device = self.shared.device.lock();
+                let library = match source {
+                    crate::MslSource::Source(source) => {
+                        let options = metal::CompileOptions::new();
+                        options.set_language_version(self.shared.private_caps.msl_version);
+                        if self.shared.private_caps.supports_preserve_invariance {
+                            options.set_preserve_invariance(true);
+                        }
+                        device.new_library_with_source(source, &options)
+                    }
+                    crate::MslSource::Library(data) => device.new_library_with_data(data),
+                }
+                .map_err(|err| crate::ShaderError::Compilation(format!("Metal: {err}")))?;
+                if let Some(label) = desc.label {
+                    library.set_label(label);
+                }
+
+                Ok(super::ShaderModule {
+                    source: super::ShaderModuleSource::Raw {
+                        library,
+                        wg_size: metal::MTLSize {
+                            width: workgroup_size[0] as _,
+                            height: workgroup_size[1] as _,
+                            depth: workgroup_size[2] as _,
+                        },
+                    },
+                    runtime_checks: desc.runtime_checks,
+                })
+            }
         }
     }
     unsafe fn destroy_shader_module(&self, _module: super::ShaderModule) {}
@@ -1007,6 +1091,10 @@ impl crate::Device for super::Device {
                 descriptor.set_label(name);
             }
 
+            if let Some(cache) = desc.cache {
+                descriptor.set_binary_archives(&[&cache.raw]);
+            }
+
             let raw = self
                 .shared
                 .device
@@ -1019,6 +1107,16 @@ impl crate::Device for super::Device {
                     )
                 })?;
 
+            if let Some(cache) = desc.cache {
+                // This does nothing if the archive has the functions already.
+                if let Err(e) = cache
+                    .raw
+                    .add_render_pipeline_functions_with_descriptor(&descriptor)
+                {
+                    log::warn!("Failed to add a render pipeline to its cache: {e}");
+                }
+            }
+
             Ok(super::RenderPipeline {
                 raw,
                 vs_lib,
@@ -1076,6 +1174,10 @@ impl crate::Device for super::Device {
                 descriptor.set_label(name);
             }
 
+            if let Some(cache) = desc.cache {
+                descriptor.set_binary_archives(&[&cache.raw]);
+            }
+
             let raw = self
                 .shared
                 .device
@@ -1088,6 +1190,15 @@ impl crate::Device for super::Device {
                     )
                 })?;
 
+            if let Some(cache) = desc.cache {
+                if let Err(e) = cache
+                    .raw
+                    .add_compute_pipeline_functions_with_descriptor(&descriptor)
+                {
+                    log::warn!("Failed to add a compute pipeline to its cache: {e}");
+                }
+            }
+
             Ok(super::ComputePipeline {
                 raw,
                 cs_info,
@@ -1099,6 +1210,90 @@ impl crate::Device for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Result<super::PipelineCache, crate::PipelineCacheError> {
+        objc::rc::autoreleasepool(|| {
+            let new_archive = |path: Option<&std::path::Path>| {
+                let descriptor = metal::BinaryArchiveDescriptor::new();
+                if let Some(path) = path {
+                    descriptor.set_url(&file_url(path));
+                }
+                self.shared
+                    .device
+                    .lock()
+                    .new_binary_archive_with_descriptor(&descriptor)
+            };
+
+            let path = desc.data.and_then(|data| {
+                let path = binary_archive_path();
+                match std::fs::write(&path, data) {
+                    Ok(()) => Some(path),
+                    Err(e) => {
+                        log::warn!("Failed to write the pipeline cache to {path:?}: {e}");
+                        None
+                    }
+                }
+            });
+
+            let (raw, path) = match path {
+                Some(path) => match new_archive(Some(&path)) {
+                    Ok(raw) => (raw, Some(path)),
+                    Err(e) => {
+                        // The archive is from another OS version or GPU.
+                        log::warn!("Failed to load the pipeline cache: {e}");
+                        let _ = std::fs::remove_file(&path);
+                        let raw = new_archive(None)
+                            .map_err(|_| crate::DeviceError::ResourceCreationFailed)?;
+                        (raw, None)
+                    }
+                },
+                None => {
+                    let raw = new_archive(None)
+                        .map_err(|_| crate::DeviceError::ResourceCreationFailed)?;
+                    (raw, None)
+                }
+            };
+
+            if let Some(label) = desc.label {
+                raw.set_label(label);
+            }
+
+            Ok(super::PipelineCache { raw, path })
+        })
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        if let Some(ref path) = cache.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        objc::rc::autoreleasepool(|| {
+            let path = binary_archive_path();
+            if let Err(e) = cache.raw.serialize_to_url(&file_url(&path)) {
+                log::warn!("Failed to serialize the pipeline cache: {e}");
+                return None;
+            }
+            let data = std::fs::read(&path);
+            let _ = std::fs::remove_file(&path);
+            data.ok()
+        })
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        if !self.shared.private_caps.supports_binary_archives {
+            return None;
+        }
+        // Binary archives hold code compiled by the OS's shader compiler.
+        // The GPU is part of the key wgpu-core adds.
+        let (major, minor, patch) = self.shared.private_caps.os_version;
+        let mut key = [0; 16];
+        key[0..4].copy_from_slice(&major.to_le_bytes());
+        key[4..8].copy_from_slice(&minor.to_le_bytes());
+        key[8..12].copy_from_slice(&patch.to_le_bytes());
+        Some(key)
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1236,6 +1431,15 @@ impl crate::Device for super::Device {
         shared_capture_manager.stop_capture();
     }
 
+    unsafe fn set_cache_limits(&self, _limits: &wgt::DeviceCacheLimits) {}
+    unsafe fn trim_caches(&self) {}
+    fn cache_report(&self) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport::default()
+    }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
+
     unsafe fn get_acceleration_structure_build_sizes(
         &self,
         _desc: &crate::GetAccelerationStructureBuildSizesDescriptor<super::Api>,
@@ -51,6 +51,7 @@ impl crate::Api for Api {
     type Queue = Queue;
     type CommandEncoder = CommandEncoder;
     type CommandBuffer = CommandBuffer;
+    type RenderBundle = ();
 
     type Buffer = Buffer;
     type Texture = Texture;
@@ -66,6 +67,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 
     type AccelerationStructure = AccelerationStructure;
 }
@@ -78,6 +80,27 @@ impl Instance {
     pub fn create_surface_from_layer(&self, layer: &metal::MetalLayerRef) -> Surface {
         unsafe { Surface::from_layer(layer) }
     }
+
+    /// Exposes an adapter for a `MTLDevice` obtained outside of wgpu, such as
+    /// the one an engine or an OpenXR runtime renders with.
+    pub fn expose_adapter(&self, raw: metal::Device) -> crate::ExposedAdapter<Api> {
+        let name = raw.name().into();
+        let shared = AdapterShared::new(raw);
+        crate::ExposedAdapter {
+            info: wgt::AdapterInfo {
+                name,
+                vendor: 0,
+                device: 0,
+                device_type: shared.private_caps.device_type(),
+                driver: String::new(),
+                driver_info: String::new(),
+                backend: wgt::Backend::Metal,
+            },
+            features: shared.private_caps.features(),
+            capabilities: shared.private_caps.capabilities(),
+            adapter: Adapter::new(Arc::new(shared)),
+        }
+    }
 }
 
 impl crate::Instance for Instance {
@@ -124,24 +147,7 @@ impl crate::Instance for Instance {
         let devices = metal::Device::all();
         let mut adapters: Vec<crate::ExposedAdapter<Api>> = devices
             .into_iter()
-            .map(|dev| {
-                let name = dev.name().into();
-                let shared = AdapterShared::new(dev);
-                crate::ExposedAdapter {
-                    info: wgt::AdapterInfo {
-                        name,
-                        vendor: 0,
-                        device: 0,
-                        device_type: shared.private_caps.device_type(),
-                        driver: String::new(),
-                        driver_info: String::new(),
-                        backend: wgt::Backend::Metal,
-                    },
-                    features: shared.private_caps.features(),
-                    capabilities: shared.private_caps.capabilities(),
-                    adapter: Adapter::new(Arc::new(shared)),
-                }
-            })
+            .map(|dev| self.expose_adapter(dev))
             .collect();
         adapters.sort_by_key(|ad| {
             (
@@ -257,6 +263,8 @@ struct PrivateCapabilities {
     sample_count_mask: crate::TextureFormatCapabilities,
     supports_debug_markers: bool,
     supports_binary_archives: bool,
+    /// The major, minor and patch version of the operating system.
+    os_version: (u32, u32, u32),
     supports_capture_manager: bool,
     can_set_maximum_drawables_count: bool,
     can_set_display_sync: bool,
@@ -267,6 +275,7 @@ struct PrivateCapabilities {
     supports_depth_clip_control: bool,
     supports_preserve_invariance: bool,
     supports_shader_primitive_index: bool,
+    supports_shader_barycentrics: bool,
     has_unified_memory: Option<bool>,
     timestamp_query_support: TimestampQuerySupport,
     supports_simd_scoped_operations: bool,
@@ -409,8 +418,11 @@ impl crate::Queue for Queue {
                 }
             };
 
-            for cmd_buffer in command_buffers {
-                cmd_buffer.raw.commit();
+            {
+                profiling::scope!("MTLCommandBuffer::commit");
+                for cmd_buffer in command_buffers {
+                    cmd_buffer.raw.commit();
+                }
             }
 
             if let Some(raw) = extra_command_buffer {
@@ -539,6 +551,13 @@ impl<T> ops::Index<naga::ShaderStage> for MultiStageData<T> {
             naga::ShaderStage::Vertex => &self.vs,
             naga::ShaderStage::Fragment => &self.fs,
             naga::ShaderStage::Compute => &self.cs,
+            naga::ShaderStage::RayGeneration
+            | naga::ShaderStage::Intersection
+            | naga::ShaderStage::AnyHit
+            | naga::ShaderStage::ClosestHit
+            | naga::ShaderStage::Miss
+            | naga::ShaderStage::Task
+            | naga::ShaderStage::Mesh => unreachable!(),
         }
     }
 }
@@ -671,10 +690,24 @@ unsafe impl Sync for BindGroup {}
 
 #[derive(Debug)]
 pub struct ShaderModule {
-    naga: crate::NagaShader,
+    source: ShaderModuleSource,
     runtime_checks: bool,
 }
 
+#[derive(Debug)]
+enum ShaderModuleSource {
+    Naga(crate::NagaShader),
+    /// A library built from MSL source or loaded from a `metallib`, whose
+    /// entry points are looked up by name.
+    Raw {
+        library: metal::Library,
+        wg_size: metal::MTLSize,
+    },
+}
+
+unsafe impl Send for ShaderModule {}
+unsafe impl Sync for ShaderModule {}
+
 #[derive(Debug, Default)]
 struct PipelineStageInfo {
     push_constants: Option<PushConstantsInfo>,
@@ -738,6 +771,18 @@ pub struct ComputePipeline {
 unsafe impl Send for ComputePipeline {}
 unsafe impl Sync for ComputePipeline {}
 
+/// A pipeline cache, made of a binary archive of compiled pipeline functions.
+#[derive(Debug)]
+pub struct PipelineCache {
+    raw: metal::BinaryArchive,
+    /// The file the archive was loaded from, which is removed when the cache
+    /// is destroyed.
+    path: Option<std::path::PathBuf>,
+}
+
+unsafe impl Send for PipelineCache {}
+unsafe impl Sync for PipelineCache {}
+
 #[derive(Debug, Clone)]
 pub struct QuerySet {
     raw_buffer: metal::Buffer,
@@ -0,0 +1,77 @@
+/*!
+# Hardware video encoding
+
+Video encoders turn textures into compressed bitstreams on the GPU's
+dedicated encoding hardware, so capture and streaming tools don't need to
+read frames back.
+
+Only the Vulkan backend implements encoding for now, with Vulkan Video, in
+[`vulkan::VideoEncoder`](crate::vulkan::VideoEncoder). Encoders are created
+from an [`EncoderDescriptor`], take frames in an NV12 texture they create, and
+return each frame's bitstream as an [`EncodedFrame`].
+
+Encoding is only available in wgpu-hal: neither wgpu-core nor wgpu have an
+API for it yet. H.264 is the only codec, and Media Foundation, AMF, NVENC and
+VideoToolbox encoders aren't implemented.
+*/
+
+use thiserror::Error;
+
+/// A video compression format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// H.264, in the main profile.
+    H264,
+}
+
+/// How an encoder picks the quantization of frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RateControl {
+    /// Every frame is quantized with `qp`, whatever its size.
+    ConstantQp { qp: u8 },
+    /// The bitrate is kept at `bitrate` bits per second.
+    ConstantBitrate { bitrate: u64 },
+    /// The bitrate averages `average_bitrate` bits per second, and doesn't
+    /// exceed `max_bitrate`, which must be at least `average_bitrate`.
+    VariableBitrate {
+        average_bitrate: u64,
+        max_bitrate: u64,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct EncoderDescriptor<'a> {
+    pub label: crate::Label<'a>,
+    pub codec: Codec,
+    /// The size of the frames, in pixels. Both must be even.
+    pub width: u32,
+    pub height: u32,
+    /// The frame rate, as a numerator and a denominator, which rate control
+    /// budgets bits for.
+    pub frame_rate: (u32, u32),
+    pub rate_control: RateControl,
+    /// The number of frames from one keyframe to the next. Frames in between
+    /// only reference the previous frame.
+    pub keyframe_interval: u32,
+}
+
+/// The bitstream of an encoded frame.
+#[derive(Clone, Debug)]
+pub struct EncodedFrame {
+    /// The NAL units of the frame, in Annex B format. Keyframes start with
+    /// the parameter sets needed to decode them.
+    pub data: Vec<u8>,
+    pub keyframe: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum EncoderError {
+    #[error("Video encoding isn't supported by the device")]
+    NotSupported,
+    #[error("The encoder descriptor isn't supported by the device: {0}")]
+    UnsupportedDescriptor(&'static str),
+    #[error("Encoding the frame failed")]
+    EncodingFailed,
+    #[error(transparent)]
+    Device(#[from] crate::DeviceError),
+}
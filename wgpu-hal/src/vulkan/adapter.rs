@@ -1,6 +1,6 @@
 use super::conv;
 
-use ash::{amd, ext, khr, vk};
+use ash::{amd, ext, khr, nv, vk};
 use parking_lot::Mutex;
 
 use std::{collections::BTreeMap, ffi::CStr, sync::Arc};
@@ -57,6 +57,9 @@ pub struct PhysicalDeviceFeatures {
     /// Features provided by `VK_KHR_multiview`, promoted to Vulkan 1.1.
     multiview: Option<vk::PhysicalDeviceMultiviewFeaturesKHR<'static>>,
 
+    /// Features provided by `VK_KHR_variable_pointers`, promoted to Vulkan 1.1.
+    variable_pointers: Option<vk::PhysicalDeviceVariablePointersFeatures<'static>>,
+
     /// Features provided by `VK_KHR_sampler_ycbcr_conversion`, promoted to Vulkan 1.1.
     sampler_ycbcr_conversion: Option<vk::PhysicalDeviceSamplerYcbcrConversionFeatures<'static>>,
 
@@ -108,6 +111,17 @@ pub struct PhysicalDeviceFeatures {
 
     /// Features provided by `VK_EXT_subgroup_size_control`, promoted to Vulkan 1.3.
     subgroup_size_control: Option<vk::PhysicalDeviceSubgroupSizeControlFeatures<'static>>,
+
+    /// Features provided by `VK_KHR_synchronization2`, promoted to Vulkan 1.3.
+    synchronization2: Option<vk::PhysicalDeviceSynchronization2FeaturesKHR<'static>>,
+
+    /// Features provided by `VK_KHR_fragment_shader_barycentric`.
+    fragment_shader_barycentric:
+        Option<vk::PhysicalDeviceFragmentShaderBarycentricFeaturesKHR<'static>>,
+
+    /// Features provided by `VK_NV_compute_shader_derivatives`.
+    compute_shader_derivatives:
+        Option<vk::PhysicalDeviceComputeShaderDerivativesFeaturesNV<'static>>,
 }
 
 impl PhysicalDeviceFeatures {
@@ -135,6 +149,9 @@ impl PhysicalDeviceFeatures {
         if let Some(ref mut feature) = self.astc_hdr {
             info = info.push_next(feature);
         }
+        if let Some(ref mut feature) = self.variable_pointers {
+            info = info.push_next(feature);
+        }
         if let Some((ref mut f16_i8_feature, ref mut _16bit_feature)) = self.shader_float16 {
             info = info.push_next(f16_i8_feature);
             info = info.push_next(_16bit_feature);
@@ -154,6 +171,15 @@ impl PhysicalDeviceFeatures {
         if let Some(ref mut feature) = self.subgroup_size_control {
             info = info.push_next(feature);
         }
+        if let Some(ref mut feature) = self.synchronization2 {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.fragment_shader_barycentric {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.compute_shader_derivatives {
+            info = info.push_next(feature);
+        }
         info
     }
 
@@ -275,12 +301,14 @@ impl PhysicalDeviceFeatures {
                         | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
                 ))
                 //.shader_storage_image_array_dynamic_indexing(
-                //.shader_clip_distance(requested_features.contains(wgt::Features::SHADER_CLIP_DISTANCE))
-                //.shader_cull_distance(requested_features.contains(wgt::Features::SHADER_CULL_DISTANCE))
+                .shader_clip_distance(requested_features.contains(wgt::Features::CLIP_DISTANCES))
+                .shader_cull_distance(requested_features.contains(wgt::Features::CULL_DISTANCES))
                 .shader_float64(requested_features.contains(wgt::Features::SHADER_F64))
                 .shader_int64(requested_features.contains(wgt::Features::SHADER_INT64))
                 .shader_int16(requested_features.contains(wgt::Features::SHADER_I16))
-                //.shader_resource_residency(requested_features.contains(wgt::Features::SHADER_RESOURCE_RESIDENCY))
+                .shader_resource_residency(
+                    requested_features.contains(wgt::Features::SHADER_RESOURCE_RESIDENCY),
+                )
                 .geometry_shader(requested_features.contains(wgt::Features::SHADER_PRIMITIVE_INDEX))
                 .depth_clamp(requested_features.contains(wgt::Features::DEPTH_CLIP_CONTROL))
                 .dual_src_blend(requested_features.contains(wgt::Features::DUAL_SOURCE_BLENDING)),
@@ -356,6 +384,19 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            variable_pointers: if device_api_version >= vk::API_VERSION_1_1
+                || enabled_extensions.contains(&khr::variable_pointers::NAME)
+            {
+                let requested =
+                    requested_features.contains(wgt::Features::UNRESTRICTED_POINTER_PARAMETERS);
+                Some(
+                    vk::PhysicalDeviceVariablePointersFeatures::default()
+                        .variable_pointers_storage_buffer(requested)
+                        .variable_pointers(requested),
+                )
+            } else {
+                None
+            },
             sampler_ycbcr_conversion: if device_api_version >= vk::API_VERSION_1_1
                 || enabled_extensions.contains(&khr::sampler_ycbcr_conversion::NAME)
             {
@@ -419,6 +460,16 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            synchronization2: if device_api_version >= vk::API_VERSION_1_3
+                || enabled_extensions.contains(&khr::synchronization2::NAME)
+            {
+                Some(
+                    vk::PhysicalDeviceSynchronization2FeaturesKHR::default()
+                        .synchronization2(private_caps.synchronization2),
+                )
+            } else {
+                None
+            },
             subgroup_size_control: if device_api_version >= vk::API_VERSION_1_3
                 || enabled_extensions.contains(&ext::subgroup_size_control::NAME)
             {
@@ -429,6 +480,26 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            fragment_shader_barycentric: if enabled_extensions
+                .contains(&khr::fragment_shader_barycentric::NAME)
+            {
+                Some(
+                    vk::PhysicalDeviceFragmentShaderBarycentricFeaturesKHR::default()
+                        .fragment_shader_barycentric(true),
+                )
+            } else {
+                None
+            },
+            compute_shader_derivatives: if enabled_extensions
+                .contains(&nv::compute_shader_derivatives::NAME)
+            {
+                Some(
+                    vk::PhysicalDeviceComputeShaderDerivativesFeaturesNV::default()
+                        .compute_derivative_group_quads(true),
+                )
+            } else {
+                None
+            },
         }
     }
 
@@ -458,7 +529,9 @@ impl PhysicalDeviceFeatures {
             | F::TIMESTAMP_QUERY_INSIDE_ENCODERS
             | F::TIMESTAMP_QUERY_INSIDE_PASSES
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | F::CLEAR_TEXTURE;
+            | F::CLEAR_TEXTURE
+            | F::PIPELINE_CACHE
+            | F::DEPTH32FLOAT_COPY_DST;
 
         let mut dl_flags = Df::COMPUTE_SHADERS
             | Df::BASE_VERTEX
@@ -552,10 +625,16 @@ impl PhysicalDeviceFeatures {
             features.insert(F::STORAGE_RESOURCE_BINDING_ARRAY);
         }
         //if self.core.shader_storage_image_array_dynamic_indexing != 0 {
-        //if self.core.shader_clip_distance != 0 {
-        //if self.core.shader_cull_distance != 0 {
+        features.set(F::CLIP_DISTANCES, self.core.shader_clip_distance != 0);
+        features.set(
+            F::SHADER_RESOURCE_RESIDENCY,
+            self.core.shader_resource_residency != 0,
+        );
+        features.set(F::CULL_DISTANCES, self.core.shader_cull_distance != 0);
         features.set(F::SHADER_F64, self.core.shader_float64 != 0);
         features.set(F::SHADER_INT64, self.core.shader_int64 != 0);
+        // `STORAGE_IMAGE_ATOMIC` is mandatory for `R32_UINT` and `R32_SINT`.
+        features.insert(F::TEXTURE_ATOMIC);
         features.set(F::SHADER_I16, self.core.shader_int16 != 0);
 
         //if caps.supports_extension(khr::sampler_mirror_clamp_to_edge::NAME) {
@@ -615,6 +694,14 @@ impl PhysicalDeviceFeatures {
             features.set(F::MULTIVIEW, multiview.multiview != 0);
         }
 
+        if let Some(ref variable_pointers) = self.variable_pointers {
+            features.set(
+                F::UNRESTRICTED_POINTER_PARAMETERS,
+                variable_pointers.variable_pointers_storage_buffer != 0
+                    && variable_pointers.variable_pointers != 0,
+            );
+        }
+
         features.set(
             F::TEXTURE_FORMAT_16BIT_NORM,
             is_format_16bit_norm_supported(instance, phd),
@@ -627,6 +714,20 @@ impl PhysicalDeviceFeatures {
             );
         }
 
+        if let Some(ref barycentric) = self.fragment_shader_barycentric {
+            features.set(
+                F::SHADER_BARYCENTRICS,
+                barycentric.fragment_shader_barycentric != 0,
+            );
+        }
+
+        if let Some(ref derivatives) = self.compute_shader_derivatives {
+            features.set(
+                F::COMPUTE_SHADER_DERIVATIVES,
+                derivatives.compute_derivative_group_quads != 0,
+            );
+        }
+
         if let Some((ref f16_i8, ref bit16)) = self.shader_float16 {
             features.set(
                 F::SHADER_F16,
@@ -661,6 +762,13 @@ impl PhysicalDeviceFeatures {
                         .contains(vk::ShaderStageFlags::VERTEX),
                 );
                 features.insert(F::SUBGROUP_BARRIER);
+                features.set(
+                    F::SUBGROUP_EXTENDED,
+                    features.contains(F::SUBGROUP)
+                        && subgroup.supported_operations.contains(
+                            vk::SubgroupFeatureFlags::CLUSTERED | vk::SubgroupFeatureFlags::QUAD,
+                        ),
+                );
             }
         }
 
@@ -698,6 +806,8 @@ impl PhysicalDeviceFeatures {
 
         features.set(F::RAY_QUERY, caps.supports_extension(khr::ray_query::NAME));
 
+        features.set(F::SHARED_TEXTURES, caps.supports_external());
+
         let rg11b10ufloat_renderable = supports_format(
             instance,
             phd,
@@ -799,6 +909,10 @@ pub struct PhysicalDeviceProperties {
     /// Additional `vk::PhysicalDevice` properties from Vulkan 1.1.
     subgroup: Option<vk::PhysicalDeviceSubgroupProperties<'static>>,
 
+    /// The `deviceUUID` and `driverUUID` of the `vk::PhysicalDevice`, from
+    /// Vulkan 1.1.
+    id: Option<vk::PhysicalDeviceIDProperties<'static>>,
+
     /// Additional `vk::PhysicalDevice` properties from the
     /// `VK_EXT_subgroup_size_control` extension, promoted to Vulkan 1.3.
     subgroup_size_control: Option<vk::PhysicalDeviceSubgroupSizeControlProperties<'static>>,
@@ -822,6 +936,18 @@ impl PhysicalDeviceProperties {
             .any(|ep| ep.extension_name_as_c_str() == Ok(extension))
     }
 
+    /// Whether memory and semaphores can be exported and imported.
+    ///
+    /// The [external extensions](super::external::EXTENSIONS) depend on
+    /// `VK_KHR_external_memory`, `VK_KHR_external_semaphore` and
+    /// `VK_KHR_dedicated_allocation`, promoted to Vulkan 1.1.
+    fn supports_external(&self) -> bool {
+        self.device_api_version >= vk::API_VERSION_1_1
+            && super::external::EXTENSIONS
+                .iter()
+                .all(|&extension| self.supports_extension(extension))
+    }
+
     /// Map `requested_features` to the list of Vulkan extension strings required to create the logical device.
     fn get_required_extensions(&self, requested_features: wgt::Features) -> Vec<&'static CStr> {
         let mut extensions = Vec::new();
@@ -859,6 +985,11 @@ impl PhysicalDeviceProperties {
                 extensions.push(khr::multiview::NAME);
             }
 
+            // Require `VK_KHR_variable_pointers` if the associated feature was requested
+            if requested_features.contains(wgt::Features::UNRESTRICTED_POINTER_PARAMETERS) {
+                extensions.push(khr::variable_pointers::NAME);
+            }
+
             // Require `VK_KHR_sampler_ycbcr_conversion` if the associated feature was requested
             if requested_features.contains(wgt::Features::TEXTURE_FORMAT_NV12) {
                 extensions.push(khr::sampler_ycbcr_conversion::NAME);
@@ -918,6 +1049,11 @@ impl PhysicalDeviceProperties {
             if requested_features.contains(wgt::Features::SUBGROUP) {
                 extensions.push(ext::subgroup_size_control::NAME);
             }
+
+            // Optional `VK_KHR_synchronization2`
+            if self.supports_extension(khr::synchronization2::NAME) {
+                extensions.push(khr::synchronization2::NAME);
+            }
         }
 
         // Optional `VK_KHR_swapchain_mutable_format`
@@ -954,6 +1090,11 @@ impl PhysicalDeviceProperties {
             extensions.push(ext::conservative_rasterization::NAME);
         }
 
+        // Optional extensions to export memory and semaphores
+        if self.supports_external() {
+            extensions.extend(super::external::EXTENSIONS);
+        }
+
         // Require `VK_KHR_portability_subset` on macOS/iOS
         #[cfg(any(target_os = "macos", target_os = "ios"))]
         extensions.push(khr::portability_subset::NAME);
@@ -963,6 +1104,16 @@ impl PhysicalDeviceProperties {
             extensions.push(ext::texture_compression_astc_hdr::NAME);
         }
 
+        // Require `VK_KHR_fragment_shader_barycentric` if the associated feature was requested
+        if requested_features.contains(wgt::Features::SHADER_BARYCENTRICS) {
+            extensions.push(khr::fragment_shader_barycentric::NAME);
+        }
+
+        // Require `VK_NV_compute_shader_derivatives` if the associated feature was requested
+        if requested_features.contains(wgt::Features::COMPUTE_SHADER_DERIVATIVES) {
+            extensions.push(nv::compute_shader_derivatives::NAME);
+        }
+
         extensions
     }
 
@@ -1121,6 +1272,11 @@ impl super::InstanceShared {
                         .subgroup
                         .insert(vk::PhysicalDeviceSubgroupProperties::default());
                     properties2 = properties2.push_next(next);
+
+                    let next = capabilities
+                        .id
+                        .insert(vk::PhysicalDeviceIDProperties::default());
+                    properties2 = properties2.push_next(next);
                 }
 
                 if supports_subgroup_size_control {
@@ -1162,6 +1318,16 @@ impl super::InstanceShared {
                 features2 = features2.push_next(next);
             }
 
+            // `VK_KHR_variable_pointers` is promoted to 1.1
+            if capabilities.device_api_version >= vk::API_VERSION_1_1
+                || capabilities.supports_extension(khr::variable_pointers::NAME)
+            {
+                let next = features
+                    .variable_pointers
+                    .insert(vk::PhysicalDeviceVariablePointersFeatures::default());
+                features2 = features2.push_next(next);
+            }
+
             // `VK_KHR_sampler_ycbcr_conversion` is promoted to 1.1
             if capabilities.device_api_version >= vk::API_VERSION_1_1
                 || capabilities.supports_extension(khr::sampler_ycbcr_conversion::NAME)
@@ -1242,6 +1408,16 @@ impl super::InstanceShared {
                 features2 = features2.push_next(next);
             }
 
+            // `VK_KHR_synchronization2` is promoted to 1.3
+            if capabilities.device_api_version >= vk::API_VERSION_1_3
+                || capabilities.supports_extension(khr::synchronization2::NAME)
+            {
+                let next = features
+                    .synchronization2
+                    .insert(vk::PhysicalDeviceSynchronization2FeaturesKHR::default());
+                features2 = features2.push_next(next);
+            }
+
             // `VK_EXT_subgroup_size_control` is promoted to 1.3
             if capabilities.device_api_version >= vk::API_VERSION_1_3
                 || capabilities.supports_extension(ext::subgroup_size_control::NAME)
@@ -1252,6 +1428,20 @@ impl super::InstanceShared {
                 features2 = features2.push_next(next);
             }
 
+            if capabilities.supports_extension(khr::fragment_shader_barycentric::NAME) {
+                let next = features
+                    .fragment_shader_barycentric
+                    .insert(vk::PhysicalDeviceFragmentShaderBarycentricFeaturesKHR::default());
+                features2 = features2.push_next(next);
+            }
+
+            if capabilities.supports_extension(nv::compute_shader_derivatives::NAME) {
+                let next = features
+                    .compute_shader_derivatives
+                    .insert(vk::PhysicalDeviceComputeShaderDerivativesFeaturesNV::default());
+                features2 = features2.push_next(next);
+            }
+
             unsafe { get_device_properties.get_physical_device_features2(phd, &mut features2) };
             features2.features
         } else {
@@ -1446,7 +1636,23 @@ impl super::Instance {
             subgroup_size_control: phd_features
                 .subgroup_size_control
                 .map_or(false, |ext| ext.subgroup_size_control == vk::TRUE),
+            synchronization2: phd_features
+                .synchronization2
+                .map_or(false, |ext| ext.synchronization2 == vk::TRUE),
         };
+        // Integrated GPUs use system memory, of which the part the host can
+        // map is as fast for them as the rest.
+        let unified_memory =
+            phd_capabilities.properties.device_type == vk::PhysicalDeviceType::INTEGRATED_GPU && {
+                let mem_properties =
+                    unsafe { self.shared.raw.get_physical_device_memory_properties(phd) };
+                mem_properties.memory_types_as_slice().iter().any(|mem| {
+                    mem.property_flags.contains(
+                        vk::MemoryPropertyFlags::DEVICE_LOCAL
+                            | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                    )
+                })
+            };
         let capabilities = crate::Capabilities {
             limits: phd_capabilities.to_wgpu_limits(),
             alignments: phd_capabilities.to_hal_alignments(),
@@ -1455,6 +1661,9 @@ impl super::Instance {
                 limits: wgt::DownlevelLimits {},
                 shader_model: wgt::ShaderModel::Sm5, //TODO?
             },
+            persistent_mapping: true,
+            unified_memory,
+            render_bundles: crate::NativeRenderBundles::PassContents,
         };
 
         let adapter = super::Adapter {
@@ -1495,6 +1704,11 @@ impl super::Adapter {
         &self.instance
     }
 
+    /// Returns the `VkFormat` that textures of `format` are created with.
+    pub fn texture_format_as_raw(&self, format: wgt::TextureFormat) -> vk::Format {
+        self.private_caps.map_texture_format(format)
+    }
+
     pub fn required_device_extensions(&self, features: wgt::Features) -> Vec<&'static CStr> {
         let (supported_extensions, unsupported_extensions) = self
             .phd_capabilities
@@ -1545,6 +1759,10 @@ impl super::Adapter {
     /// - `raw_device` must be created from this adapter.
     /// - `raw_device` must be created using `family_index`, `enabled_extensions` and `physical_device_features()`
     /// - `enabled_extensions` must be a superset of `required_device_extensions()`.
+    ///
+    /// The device allocates memory with a
+    /// [`GpuAllocMemoryAllocator`](super::GpuAllocMemoryAllocator) configured
+    /// from `memory_hints`.
     #[allow(clippy::too_many_arguments)]
     pub unsafe fn device_from_raw(
         &self,
@@ -1554,6 +1772,113 @@ impl super::Adapter {
         features: wgt::Features,
         family_index: u32,
         queue_index: u32,
+        memory_hints: &wgt::MemoryHints,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        unsafe {
+            self.device_from_raw_impl(
+                raw_device,
+                handle_is_owned,
+                enabled_extensions,
+                features,
+                family_index,
+                queue_index,
+                None,
+                memory_hints,
+                None,
+            )
+        }
+    }
+
+    /// Like [`Adapter::device_from_raw`](super::Adapter::device_from_raw), but
+    /// with the device's [`VideoEncoder`](super::VideoEncoder)s encoding on
+    /// the first queue of `video_encode_family_index`.
+    ///
+    /// # Safety
+    ///
+    /// The requirements of `device_from_raw` apply, and:
+    ///
+    /// - `raw_device` must be created with a queue of `video_encode_family_index`,
+    ///   which must support video encoding.
+    /// - `enabled_extensions` must contain `VK_KHR_video_queue`,
+    ///   `VK_KHR_video_encode_queue` and `VK_KHR_video_encode_h264`.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn device_from_raw_with_video_encode(
+        &self,
+        raw_device: ash::Device,
+        handle_is_owned: bool,
+        enabled_extensions: &[&'static CStr],
+        features: wgt::Features,
+        family_index: u32,
+        queue_index: u32,
+        video_encode_family_index: u32,
+        memory_hints: &wgt::MemoryHints,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        unsafe {
+            self.device_from_raw_impl(
+                raw_device,
+                handle_is_owned,
+                enabled_extensions,
+                features,
+                family_index,
+                queue_index,
+                Some(video_encode_family_index),
+                memory_hints,
+                None,
+            )
+        }
+    }
+
+    /// Like [`Adapter::device_from_raw`](super::Adapter::device_from_raw), but
+    /// with the device allocating memory with `memory_allocator`, or the
+    /// default allocator if it's `None`.
+    ///
+    /// # Safety
+    ///
+    /// The requirements of `device_from_raw` apply.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn device_from_raw_with_memory_allocator(
+        &self,
+        raw_device: ash::Device,
+        handle_is_owned: bool,
+        enabled_extensions: &[&'static CStr],
+        features: wgt::Features,
+        family_index: u32,
+        queue_index: u32,
+        memory_hints: &wgt::MemoryHints,
+        memory_allocator: Option<Box<dyn super::MemoryAllocator>>,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        unsafe {
+            self.device_from_raw_impl(
+                raw_device,
+                handle_is_owned,
+                enabled_extensions,
+                features,
+                family_index,
+                queue_index,
+                None,
+                memory_hints,
+                memory_allocator,
+            )
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The requirements of `device_from_raw`, and of
+    /// `device_from_raw_with_video_encode` if `video_encode_family_index` is
+    /// set, apply.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn device_from_raw_impl(
+        &self,
+        raw_device: ash::Device,
+        handle_is_owned: bool,
+        enabled_extensions: &[&'static CStr],
+        features: wgt::Features,
+        family_index: u32,
+        queue_index: u32,
+        video_encode_family_index: Option<u32>,
+        memory_hints: &wgt::MemoryHints,
+        memory_allocator: Option<Box<dyn super::MemoryAllocator>>,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let mem_properties = {
             profiling::scope!("vkGetPhysicalDeviceMemoryProperties");
@@ -1602,6 +1927,17 @@ impl super::Adapter {
         } else {
             None
         };
+        let synchronization2_fn = if !self.private_caps.synchronization2 {
+            None
+        } else if enabled_extensions.contains(&khr::synchronization2::NAME) {
+            Some(super::ExtensionFn::Extension(
+                khr::synchronization2::Device::new(&self.instance.raw, &raw_device),
+            ))
+        } else if self.phd_capabilities.device_api_version >= vk::API_VERSION_1_3 {
+            Some(super::ExtensionFn::Promoted)
+        } else {
+            None
+        };
         let ray_tracing_fns = if enabled_extensions.contains(&khr::acceleration_structure::NAME)
             && enabled_extensions.contains(&khr::buffer_device_address::NAME)
         {
@@ -1618,6 +1954,19 @@ impl super::Adapter {
         } else {
             None
         };
+        let external_fns = super::external::ExternalFunctions::load(
+            &self.instance.raw,
+            &raw_device,
+            enabled_extensions,
+        );
+        let video_fns = video_encode_family_index.map(|family_index| unsafe {
+            super::video::VideoFunctions::load(
+                &self.instance.entry,
+                &self.instance.raw,
+                &raw_device,
+                family_index,
+            )
+        });
 
         let naga_options = {
             use naga::back::spv;
@@ -1656,6 +2005,26 @@ impl super::Adapter {
                 capabilities.push(spv::Capability::Geometry);
             }
 
+            if features.contains(wgt::Features::CLIP_DISTANCES) {
+                capabilities.push(spv::Capability::ClipDistance);
+            }
+
+            if features.contains(wgt::Features::CULL_DISTANCES) {
+                capabilities.push(spv::Capability::CullDistance);
+            }
+
+            if features.contains(wgt::Features::SHADER_BARYCENTRICS) {
+                capabilities.push(spv::Capability::FragmentBarycentricKHR);
+            }
+
+            if features.contains(wgt::Features::SHADER_RESOURCE_RESIDENCY) {
+                capabilities.push(spv::Capability::SparseResidency);
+            }
+
+            if features.contains(wgt::Features::COMPUTE_SHADER_DERIVATIVES) {
+                capabilities.push(spv::Capability::ComputeDerivativeGroupQuadsNV);
+            }
+
             if features.intersects(wgt::Features::SUBGROUP | wgt::Features::SUBGROUP_VERTEX) {
                 capabilities.push(spv::Capability::GroupNonUniform);
                 capabilities.push(spv::Capability::GroupNonUniformVote);
@@ -1665,6 +2034,11 @@ impl super::Adapter {
                 capabilities.push(spv::Capability::GroupNonUniformShuffleRelative);
             }
 
+            if features.contains(wgt::Features::SUBGROUP_EXTENDED) {
+                capabilities.push(spv::Capability::GroupNonUniformClustered);
+                capabilities.push(spv::Capability::GroupNonUniformQuad);
+            }
+
             if features.intersects(
                 wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
                     | wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
@@ -1683,6 +2057,11 @@ impl super::Adapter {
                 capabilities.push(spv::Capability::Int64);
             }
 
+            if features.contains(wgt::Features::UNRESTRICTED_POINTER_PARAMETERS) {
+                capabilities.push(spv::Capability::VariablePointersStorageBuffer);
+                capabilities.push(spv::Capability::VariablePointers);
+            }
+
             let mut flags = spv::WriterFlags::empty();
             flags.set(
                 spv::WriterFlags::DEBUG,
@@ -1744,6 +2123,7 @@ impl super::Adapter {
             unsafe { raw_device.get_device_queue(family_index, queue_index) }
         };
 
+        let cache_limits = wgt::DeviceCacheLimits::default();
         let shared = Arc::new(super::DeviceShared {
             raw: raw_device,
             family_index,
@@ -1757,15 +2137,23 @@ impl super::Adapter {
                 debug_utils: debug_utils_fn,
                 draw_indirect_count: indirect_count_fn,
                 timeline_semaphore: timeline_semaphore_fn,
+                synchronization2: synchronization2_fn,
                 ray_tracing: ray_tracing_fns,
+                external: external_fns,
+                video: video_fns,
             },
             vendor_id: self.phd_capabilities.properties.vendor_id,
             timestamp_period: self.phd_capabilities.properties.limits.timestamp_period,
+            pipeline_cache_validation_key: self.phd_capabilities.properties.pipeline_cache_uuid,
+            external_memory_key: self
+                .phd_capabilities
+                .id
+                .map(|id| (id.device_uuid, id.driver_uuid)),
             private_caps: self.private_caps.clone(),
             features,
             workarounds: self.workarounds,
-            render_passes: Mutex::new(Default::default()),
-            framebuffers: Mutex::new(Default::default()),
+            render_passes: super::cache::ObjectCache::new(cache_limits.render_passes),
+            framebuffers: super::cache::ObjectCache::new(cache_limits.framebuffers),
         });
 
         let relay_semaphores = super::RelaySemaphores::new(&shared.raw)?;
@@ -1776,42 +2164,16 @@ impl super::Adapter {
             device: Arc::clone(&shared),
             family_index,
             relay_semaphores: Mutex::new(relay_semaphores),
+            pending_waits: Mutex::new(Vec::new()),
         };
 
-        let mem_allocator = {
-            let limits = self.phd_capabilities.properties.limits;
-            let config = gpu_alloc::Config::i_am_prototyping(); //TODO
-            let max_memory_allocation_size =
-                if let Some(maintenance_3) = self.phd_capabilities.maintenance_3 {
-                    maintenance_3.max_memory_allocation_size
-                } else {
-                    u64::max_value()
-                };
-            let properties = gpu_alloc::DeviceProperties {
-                max_memory_allocation_count: limits.max_memory_allocation_count,
-                max_memory_allocation_size,
-                non_coherent_atom_size: limits.non_coherent_atom_size,
-                memory_types: memory_types
-                    .iter()
-                    .map(|memory_type| gpu_alloc::MemoryType {
-                        props: gpu_alloc::MemoryPropertyFlags::from_bits_truncate(
-                            memory_type.property_flags.as_raw() as u8,
-                        ),
-                        heap: memory_type.heap_index,
-                    })
-                    .collect(),
-                memory_heaps: mem_properties
-                    .memory_heaps_as_slice()
-                    .iter()
-                    .map(|&memory_heap| gpu_alloc::MemoryHeap {
-                        size: memory_heap.size,
-                    })
-                    .collect(),
-                buffer_device_address: enabled_extensions
-                    .contains(&khr::buffer_device_address::NAME),
-            };
-            gpu_alloc::GpuAllocator::new(config, properties)
-        };
+        let mem_allocator = memory_allocator.unwrap_or_else(|| {
+            Box::new(super::GpuAllocMemoryAllocator::new(
+                self,
+                enabled_extensions,
+                super::GpuAllocConfig::from_hints(memory_hints),
+            ))
+        });
         let desc_allocator = gpu_descriptor::DescriptorAllocator::new(
             if let Some(di) = self.phd_capabilities.descriptor_indexing {
                 di.max_update_after_bind_descriptors_in_all_pools
@@ -1822,9 +2184,10 @@ impl super::Adapter {
 
         let device = super::Device {
             shared,
-            mem_allocator: Mutex::new(mem_allocator),
+            mem_allocator,
             desc_allocator: Mutex::new(desc_allocator),
             valid_ash_memory_types,
+            prefer_device_local_host_visible: memory_hints.prefer_device_local_host_visible,
             naga_options,
             #[cfg(feature = "renderdoc")]
             render_doc: Default::default(),
@@ -1832,24 +2195,59 @@ impl super::Adapter {
 
         Ok(crate::OpenDevice { device, queue })
     }
-}
 
-impl crate::Adapter for super::Adapter {
-    type A = super::Api;
-
-    unsafe fn open(
+    /// Like [`crate::Adapter::open`], but with the device allocating memory with
+    /// `memory_allocator`.
+    ///
+    /// A [`GpuAllocMemoryAllocator`](super::GpuAllocMemoryAllocator) for the
+    /// device has to be created with `required_device_extensions(features)`.
+    pub unsafe fn open_with_memory_allocator(
         &self,
         features: wgt::Features,
-        _limits: &wgt::Limits,
+        memory_hints: &wgt::MemoryHints,
+        memory_allocator: Option<Box<dyn super::MemoryAllocator>>,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
-        let enabled_extensions = self.required_device_extensions(features);
-        let mut enabled_phd_features = self.physical_device_features(&enabled_extensions, features);
+        let mut enabled_extensions = self.required_device_extensions(features);
 
         let family_index = 0; //TODO
         let family_info = vk::DeviceQueueCreateInfo::default()
             .queue_family_index(family_index)
             .queue_priorities(&[1.0]);
-        let family_infos = [family_info];
+        let mut family_infos = vec![family_info];
+
+        // Create a video encode queue, in a family of its own, if the device
+        // can encode. Video encoding needs `synchronization2`.
+        let video_encode_family_index = if self.phd_capabilities.device_api_version
+            >= vk::API_VERSION_1_1
+            && self.private_caps.synchronization2
+            && super::video::EXTENSIONS
+                .iter()
+                .all(|&extension| self.supports_extension(extension))
+        {
+            let queue_families = unsafe {
+                self.instance
+                    .raw
+                    .get_physical_device_queue_family_properties(self.raw)
+            };
+            (0..queue_families.len() as u32).find(|&index| {
+                index != family_index
+                    && queue_families[index as usize]
+                        .queue_flags
+                        .contains(vk::QueueFlags::VIDEO_ENCODE_KHR)
+            })
+        } else {
+            None
+        };
+        if let Some(index) = video_encode_family_index {
+            enabled_extensions.extend(super::video::EXTENSIONS);
+            family_infos.push(
+                vk::DeviceQueueCreateInfo::default()
+                    .queue_family_index(index)
+                    .queue_priorities(&[1.0]),
+            );
+        }
+
+        let mut enabled_phd_features = self.physical_device_features(&enabled_extensions, features);
 
         let str_pointers = enabled_extensions
             .iter()
@@ -1869,16 +2267,32 @@ impl crate::Adapter for super::Adapter {
         };
 
         unsafe {
-            self.device_from_raw(
+            self.device_from_raw_impl(
                 raw_device,
                 true,
                 &enabled_extensions,
                 features,
                 family_info.queue_family_index,
                 0,
+                video_encode_family_index,
+                memory_hints,
+                memory_allocator,
             )
         }
     }
+}
+
+impl crate::Adapter for super::Adapter {
+    type A = super::Api;
+
+    unsafe fn open(
+        &self,
+        features: wgt::Features,
+        _limits: &wgt::Limits,
+        memory_hints: &wgt::MemoryHints,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        unsafe { self.open_with_memory_allocator(features, memory_hints, None) }
+    }
 
     unsafe fn texture_format_capabilities(
         &self,
@@ -0,0 +1,240 @@
+/*!
+Bounded caches of the objects that [`DeviceShared`] creates on demand.
+
+Vulkan render passes and framebuffers have no WebGPU equivalent, so they are
+created when a render pass begins and cached by their description. Each
+cache holds at most its capacity of objects, and evicts the least recently
+used ones beyond that.
+
+An evicted object may still be used by command buffers that were recorded
+but haven't finished executing, so it is only destroyed once those command
+buffers have been reset. Command buffers hold a [`CacheUser`] for every cached
+object they use to keep track of this.
+
+[`DeviceShared`]: super::DeviceShared
+*/
+
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use parking_lot::{Mutex, RwLock};
+
+/// Keeps a cached object from being destroyed while it is alive.
+pub(super) type CacheUser = Arc<()>;
+
+struct Entry<V> {
+    raw: V,
+    /// The cache's `clock` when this entry was last looked up.
+    last_used: AtomicU64,
+    users: CacheUser,
+}
+
+pub(super) struct ObjectCache<K, V> {
+    map: RwLock<rustc_hash::FxHashMap<K, Entry<V>>>,
+    /// Objects removed from `map` that may still be in use.
+    retired: Mutex<Vec<(V, CacheUser)>>,
+    capacity: AtomicU32,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K: Clone + Eq + Hash, V: Copy> ObjectCache<K, V> {
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            map: RwLock::new(Default::default()),
+            retired: Mutex::new(Vec::new()),
+            capacity: AtomicU32::new(capacity),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Returns the object cached for `key`, creating it with `create` if
+    /// there is none.
+    ///
+    /// `destroy` is used on the objects this evicts, if they are unused.
+    pub fn get_or_create<E>(
+        &self,
+        key: K,
+        create: impl FnOnce(&K) -> Result<V, E>,
+        mut destroy: impl FnMut(V),
+    ) -> Result<(V, CacheUser), E> {
+        if let Some(entry) = self.map.read().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            entry.last_used.store(self.tick(), Ordering::Relaxed);
+            return Ok((entry.raw, Arc::clone(&entry.users)));
+        }
+
+        // Create the object without holding the lock, so that looking up
+        // other objects doesn't wait on the driver.
+        let raw = create(&key)?;
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let mut map = self.map.write();
+        if let Some(entry) = map.get(&key) {
+            // Another thread created the same object in the meantime.
+            destroy(raw);
+            return Ok((entry.raw, Arc::clone(&entry.users)));
+        }
+        let users = CacheUser::default();
+        map.insert(
+            key,
+            Entry {
+                raw,
+                last_used: AtomicU64::new(self.tick()),
+                users: Arc::clone(&users),
+            },
+        );
+
+        let capacity = self.capacity.load(Ordering::Relaxed) as usize;
+        if map.len() > capacity {
+            // Evict a few more entries than needed, so that we don't have
+            // to sort the cache again on the next miss.
+            self.evict(&mut map, capacity - capacity / 8, &mut destroy);
+        }
+        Ok((raw, users))
+    }
+
+    /// Removes the least recently used entries until at most `target` are
+    /// left.
+    fn evict(
+        &self,
+        map: &mut rustc_hash::FxHashMap<K, Entry<V>>,
+        target: usize,
+        destroy: &mut impl FnMut(V),
+    ) {
+        let count = map.len().saturating_sub(target);
+        let mut by_age = map
+            .iter()
+            .map(|(key, entry)| (entry.last_used.load(Ordering::Relaxed), key.clone()))
+            .collect::<Vec<_>>();
+        by_age.sort_unstable_by_key(|&(last_used, _)| last_used);
+
+        let mut retired = self.retired.lock();
+        for (_, key) in by_age.into_iter().take(count) {
+            let entry = map.remove(&key).unwrap();
+            retired.push((entry.raw, entry.users));
+        }
+        self.evictions.fetch_add(count as u64, Ordering::Relaxed);
+        Self::destroy_unused(&mut retired, destroy);
+    }
+
+    fn destroy_unused(retired: &mut Vec<(V, CacheUser)>, destroy: &mut impl FnMut(V)) {
+        retired.retain(|&(raw, ref users)| {
+            if Arc::strong_count(users) == 1 {
+                destroy(raw);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Removes the entries whose key matches `predicate`.
+    pub fn remove_if(&self, mut predicate: impl FnMut(&K) -> bool, mut destroy: impl FnMut(V)) {
+        let mut map = self.map.write();
+        let mut retired = self.retired.lock();
+        map.retain(|key, entry| {
+            if predicate(key) {
+                retired.push((entry.raw, Arc::clone(&entry.users)));
+                false
+            } else {
+                true
+            }
+        });
+        Self::destroy_unused(&mut retired, &mut destroy);
+    }
+
+    /// Changes how many entries the cache may hold, evicting the least
+    /// recently used ones beyond that.
+    pub fn set_capacity(&self, capacity: u32, mut destroy: impl FnMut(V)) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut map = self.map.write();
+        if map.len() > capacity as usize {
+            self.evict(&mut map, capacity as usize, &mut destroy);
+        }
+    }
+
+    /// Evicts every entry, and destroys the evicted objects that are no
+    /// longer in use.
+    pub fn trim(&self, mut destroy: impl FnMut(V)) {
+        let mut map = self.map.write();
+        self.evict(&mut map, 0, &mut destroy);
+    }
+
+    pub fn statistics(&self) -> wgt::CacheStatistics {
+        wgt::CacheStatistics {
+            entries: self.map.read().len() as u32,
+            capacity: self.capacity.load(Ordering::Relaxed),
+            pending_destruction: self.retired.lock().len() as u32,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Destroys every object, whether it is still in use or not.
+    pub fn free(&self, mut destroy: impl FnMut(V)) {
+        for (_, entry) in self.map.write().drain() {
+            destroy(entry.raw);
+        }
+        for (raw, _) in self.retired.lock().drain(..) {
+            destroy(raw);
+        }
+    }
+}
+
+#[test]
+fn eviction() {
+    let cache = ObjectCache::<u32, u32>::new(8);
+    let mut destroyed = Vec::new();
+    let mut users = Vec::new();
+    for key in 0..8 {
+        let (raw, user) = cache
+            .get_or_create(key, |&key| Ok::<_, ()>(key + 100), |_| unreachable!())
+            .unwrap();
+        assert_eq!(raw, key + 100);
+        users.push(user);
+    }
+    // Make 0 the most recently used entry.
+    cache
+        .get_or_create(0, |_| Err(()), |_| unreachable!())
+        .unwrap();
+    drop(users);
+
+    cache
+        .get_or_create(8, |&key| Ok::<_, ()>(key + 100), |raw| destroyed.push(raw))
+        .unwrap();
+    destroyed.sort();
+    assert_eq!(destroyed, [101, 102]);
+
+    let stats = cache.statistics();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (7, 1, 9));
+    assert_eq!(stats.evictions, 2);
+
+    // Objects still in use are only destroyed once they aren't.
+    let (_, user) = cache
+        .get_or_create(0, |_| Err(()), |_| unreachable!())
+        .unwrap();
+    destroyed.clear();
+    cache.trim(|raw| destroyed.push(raw));
+    assert_eq!(destroyed.len(), 6);
+    assert_eq!(cache.statistics().pending_destruction, 1);
+    drop(user);
+    cache.trim(|raw| destroyed.push(raw));
+    assert_eq!(destroyed.len(), 7);
+    assert_eq!(destroyed[6], 100);
+}
@@ -40,6 +40,96 @@ impl super::Texture {
 }
 
 impl super::CommandEncoder {
+    /// Record the barriers accumulated by `transition_buffers`,
+    /// `transition_textures` and `place_acceleration_structure_barrier`.
+    ///
+    /// Barriers are only recorded before the next command that accesses
+    /// resources, so that consecutive transitions share a single pipeline
+    /// barrier. A barrier on a resource that already has one pending is
+    /// recorded separately, since barriers in the same pipeline barrier
+    /// aren't ordered with respect to each other.
+    fn flush_barriers(&mut self) {
+        let barriers = &mut self.temp.barriers;
+        if barriers.is_empty() {
+            return;
+        }
+
+        if let Some(ref synchronization2) = self.device.extension_fns.synchronization2 {
+            let info = vk::DependencyInfo::default()
+                .memory_barriers(&barriers.memory)
+                .buffer_memory_barriers(&barriers.buffer)
+                .image_memory_barriers(&barriers.image);
+            match *synchronization2 {
+                super::ExtensionFn::Extension(ref ext) => unsafe {
+                    ext.cmd_pipeline_barrier2(self.active, &info)
+                },
+                super::ExtensionFn::Promoted => unsafe {
+                    self.device.raw.cmd_pipeline_barrier2(self.active, &info)
+                },
+            }
+        } else {
+            // Without `synchronization2`, all the barriers share the same
+            // stage masks.
+            //Note: this is done so that we never end up with empty stage flags
+            let mut src_stages = vk::PipelineStageFlags2::TOP_OF_PIPE;
+            let mut dst_stages = vk::PipelineStageFlags2::BOTTOM_OF_PIPE;
+            let mut memory_barrier = None;
+            for bar in barriers.memory.iter() {
+                src_stages |= bar.src_stage_mask;
+                dst_stages |= bar.dst_stage_mask;
+                let combined = memory_barrier.get_or_insert(vk::MemoryBarrier::default());
+                combined.src_access_mask |= conv::map_access2_to_access(bar.src_access_mask);
+                combined.dst_access_mask |= conv::map_access2_to_access(bar.dst_access_mask);
+            }
+
+            self.temp.buffer_barriers.clear();
+            for bar in barriers.buffer.iter() {
+                src_stages |= bar.src_stage_mask;
+                dst_stages |= bar.dst_stage_mask;
+                self.temp.buffer_barriers.push(
+                    vk::BufferMemoryBarrier::default()
+                        .buffer(bar.buffer)
+                        .offset(bar.offset)
+                        .size(bar.size)
+                        .src_access_mask(conv::map_access2_to_access(bar.src_access_mask))
+                        .dst_access_mask(conv::map_access2_to_access(bar.dst_access_mask)),
+                );
+            }
+
+            self.temp.image_barriers.clear();
+            for bar in barriers.image.iter() {
+                src_stages |= bar.src_stage_mask;
+                dst_stages |= bar.dst_stage_mask;
+                self.temp.image_barriers.push(
+                    vk::ImageMemoryBarrier::default()
+                        .image(bar.image)
+                        .subresource_range(bar.subresource_range)
+                        .src_access_mask(conv::map_access2_to_access(bar.src_access_mask))
+                        .dst_access_mask(conv::map_access2_to_access(bar.dst_access_mask))
+                        .old_layout(bar.old_layout)
+                        .new_layout(bar.new_layout),
+                );
+            }
+
+            unsafe {
+                self.device.raw.cmd_pipeline_barrier(
+                    self.active,
+                    conv::map_pipeline_stages2_to_stages(src_stages),
+                    conv::map_pipeline_stages2_to_stages(dst_stages),
+                    vk::DependencyFlags::empty(),
+                    match memory_barrier {
+                        Some(ref barrier) => slice::from_ref(barrier),
+                        None => &[],
+                    },
+                    &self.temp.buffer_barriers,
+                    &self.temp.image_barriers,
+                )
+            };
+        }
+
+        barriers.clear();
+    }
+
     fn write_pass_end_timestamp_if_requested(&mut self) {
         if let Some((query_set, index)) = self.end_of_pass_timer_query.take() {
             unsafe {
@@ -83,10 +173,14 @@ impl crate::CommandEncoder for super::CommandEncoder {
     }
 
     unsafe fn end_encoding(&mut self) -> Result<super::CommandBuffer, crate::DeviceError> {
+        self.flush_barriers();
         let raw = self.active;
         self.active = vk::CommandBuffer::null();
         unsafe { self.device.raw.end_command_buffer(raw) }?;
-        Ok(super::CommandBuffer { raw })
+        Ok(super::CommandBuffer {
+            raw,
+            cache_users: mem::take(&mut self.cache_users),
+        })
     }
 
     unsafe fn discard_encoding(&mut self) {
@@ -97,6 +191,8 @@ impl crate::CommandEncoder for super::CommandEncoder {
 
         self.discarded.push(self.active);
         self.active = vk::CommandBuffer::null();
+        self.temp.barriers.clear();
+        self.cache_users.clear();
     }
 
     unsafe fn reset_all<I>(&mut self, cmd_bufs: I)
@@ -114,56 +210,149 @@ impl crate::CommandEncoder for super::CommandEncoder {
         };
     }
 
+    unsafe fn begin_render_bundle(
+        &mut self,
+        desc: &crate::RenderBundleDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        if self.secondary_free.is_empty() {
+            let vk_info = vk::CommandBufferAllocateInfo::default()
+                .command_pool(self.raw)
+                .level(vk::CommandBufferLevel::SECONDARY)
+                .command_buffer_count(ALLOCATION_GRANULARITY);
+            let cmd_buf_vec = unsafe { self.device.raw.allocate_command_buffers(&vk_info)? };
+            self.secondary_free.extend(cmd_buf_vec);
+        }
+        let raw = self.secondary_free.pop().unwrap();
+
+        unsafe {
+            self.device
+                .set_object_name(raw, desc.label.unwrap_or_default())
+        };
+
+        // Secondary command buffers are recorded for any render pass that is
+        // compatible with this one, which only depends on the formats and
+        // sample counts of the attachments.
+        let caps = &self.device.private_caps;
+        let mut rp_key = super::RenderPassKey {
+            sample_count: desc.sample_count,
+            multiview: desc.multiview,
+            ..Default::default()
+        };
+        for target in desc.color_targets {
+            rp_key.colors.push(target.as_ref().map(|target| {
+                let key = super::AttachmentKey::compatible(
+                    caps.map_texture_format(target.format),
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                );
+                super::ColorAttachmentKey {
+                    base: key.clone(),
+                    resolve: target.resolve.then_some(key),
+                }
+            }));
+        }
+        if let Some(format) = desc.depth_stencil_format {
+            rp_key.depth_stencil = Some(super::DepthStencilAttachmentKey {
+                base: super::AttachmentKey::compatible(
+                    caps.map_texture_format(format),
+                    vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+                ),
+                stencil_ops: crate::AttachmentOps::all(),
+            });
+        }
+        let (raw_pass, pass_user) = self.device.make_render_pass(rp_key)?;
+
+        let vk_inheritance = vk::CommandBufferInheritanceInfo::default()
+            .render_pass(raw_pass)
+            .subpass(0);
+        let vk_info = vk::CommandBufferBeginInfo::default()
+            .flags(
+                vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE
+                    | vk::CommandBufferUsageFlags::SIMULTANEOUS_USE,
+            )
+            .inheritance_info(&vk_inheritance);
+        if let Err(error) = unsafe { self.device.raw.begin_command_buffer(raw, &vk_info) } {
+            self.secondary_free.push(raw);
+            return Err(error.into());
+        }
+        self.active = raw;
+        self.bind_point = vk::PipelineBindPoint::GRAPHICS;
+        self.cache_users.push(pass_user);
+
+        Ok(())
+    }
+
+    unsafe fn end_render_bundle(&mut self) -> Result<super::RenderBundle, crate::DeviceError> {
+        let raw = self.active;
+        self.active = vk::CommandBuffer::null();
+        let cache_users = mem::take(&mut self.cache_users);
+        if let Err(error) = unsafe { self.device.raw.end_command_buffer(raw) } {
+            self.secondary_free.push(raw);
+            return Err(error.into());
+        }
+        Ok(super::RenderBundle { raw, cache_users })
+    }
+
+    unsafe fn reset_render_bundles<I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = super::RenderBundle>,
+    {
+        self.secondary_free.extend(bundles.map(|bundle| bundle.raw));
+        let _ = unsafe {
+            self.device
+                .raw
+                .reset_command_pool(self.raw, vk::CommandPoolResetFlags::default())
+        };
+    }
+
     unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
     {
-        //Note: this is done so that we never end up with empty stage flags
-        let mut src_stages = vk::PipelineStageFlags::TOP_OF_PIPE;
-        let mut dst_stages = vk::PipelineStageFlags::BOTTOM_OF_PIPE;
-        let vk_barriers = &mut self.temp.buffer_barriers;
-        vk_barriers.clear();
-
+        let mut earlier = self.temp.barriers.buffer.len();
         for bar in barriers {
+            let (offset, size) = match bar.range {
+                Some(ref range) => (range.start, range.end - range.start),
+                None => (0, vk::WHOLE_SIZE),
+            };
+            let end = offset.saturating_add(size);
+            if self.temp.barriers.buffer[..earlier].iter().any(|pending| {
+                pending.buffer == bar.buffer.raw
+                    && pending.offset < end
+                    && offset < pending.offset.saturating_add(pending.size)
+            }) {
+                self.flush_barriers();
+                earlier = 0;
+            }
+
             let (src_stage, src_access) = conv::map_buffer_usage_to_barrier(bar.usage.start);
-            src_stages |= src_stage;
             let (dst_stage, dst_access) = conv::map_buffer_usage_to_barrier(bar.usage.end);
-            dst_stages |= dst_stage;
-
-            vk_barriers.push(
-                vk::BufferMemoryBarrier::default()
+            self.temp.barriers.buffer.push(
+                vk::BufferMemoryBarrier2::default()
                     .buffer(bar.buffer.raw)
-                    .size(vk::WHOLE_SIZE)
-                    .src_access_mask(src_access)
-                    .dst_access_mask(dst_access),
+                    .offset(offset)
+                    .size(size)
+                    .src_stage_mask(conv::map_pipeline_stages2(src_stage))
+                    .src_access_mask(conv::map_access2(src_access))
+                    .dst_stage_mask(conv::map_pipeline_stages2(dst_stage))
+                    .dst_access_mask(conv::map_access2(dst_access)),
             )
         }
-
-        if !vk_barriers.is_empty() {
-            unsafe {
-                self.device.raw.cmd_pipeline_barrier(
-                    self.active,
-                    src_stages,
-                    dst_stages,
-                    vk::DependencyFlags::empty(),
-                    &[],
-                    vk_barriers,
-                    &[],
-                )
-            };
-        }
     }
 
     unsafe fn transition_textures<'a, T>(&mut self, barriers: T)
     where
         T: Iterator<Item = crate::TextureBarrier<'a, super::Api>>,
     {
-        let mut src_stages = vk::PipelineStageFlags::empty();
-        let mut dst_stages = vk::PipelineStageFlags::empty();
-        let vk_barriers = &mut self.temp.image_barriers;
-        vk_barriers.clear();
-
+        let mut earlier = self.temp.barriers.image.len();
         for bar in barriers {
+            if self.temp.barriers.image[..earlier]
+                .iter()
+                .any(|pending| pending.image == bar.texture.raw)
+            {
+                self.flush_barriers();
+                earlier = 0;
+            }
+
             let range = conv::map_subresource_range_combined_aspect(
                 &bar.range,
                 bar.texture.format,
@@ -171,38 +360,25 @@ impl crate::CommandEncoder for super::CommandEncoder {
             );
             let (src_stage, src_access) = conv::map_texture_usage_to_barrier(bar.usage.start);
             let src_layout = conv::derive_image_layout(bar.usage.start, bar.texture.format);
-            src_stages |= src_stage;
             let (dst_stage, dst_access) = conv::map_texture_usage_to_barrier(bar.usage.end);
             let dst_layout = conv::derive_image_layout(bar.usage.end, bar.texture.format);
-            dst_stages |= dst_stage;
 
-            vk_barriers.push(
-                vk::ImageMemoryBarrier::default()
+            self.temp.barriers.image.push(
+                vk::ImageMemoryBarrier2::default()
                     .image(bar.texture.raw)
                     .subresource_range(range)
-                    .src_access_mask(src_access)
-                    .dst_access_mask(dst_access)
+                    .src_stage_mask(conv::map_pipeline_stages2(src_stage))
+                    .src_access_mask(conv::map_access2(src_access))
+                    .dst_stage_mask(conv::map_pipeline_stages2(dst_stage))
+                    .dst_access_mask(conv::map_access2(dst_access))
                     .old_layout(src_layout)
                     .new_layout(dst_layout),
             );
         }
-
-        if !vk_barriers.is_empty() {
-            unsafe {
-                self.device.raw.cmd_pipeline_barrier(
-                    self.active,
-                    src_stages,
-                    dst_stages,
-                    vk::DependencyFlags::empty(),
-                    &[],
-                    &[],
-                    vk_barriers,
-                )
-            };
-        }
     }
 
     unsafe fn clear_buffer(&mut self, buffer: &super::Buffer, range: crate::MemoryRange) {
+        self.flush_barriers();
         let range_size = range.end - range.start;
         if self.device.workarounds.contains(
             super::Workarounds::FORCE_FILL_BUFFER_WITH_SIZE_GREATER_4096_ALIGNED_OFFSET_16,
@@ -251,6 +427,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferCopy>,
     {
+        self.flush_barriers();
         let vk_regions_iter = regions.map(|r| vk::BufferCopy {
             src_offset: r.src_offset,
             dst_offset: r.dst_offset,
@@ -276,6 +453,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::TextureCopy>,
     {
+        self.flush_barriers();
         let src_layout = conv::derive_image_layout(src_usage, src.format);
 
         let vk_regions_iter = regions.map(|r| {
@@ -314,6 +492,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
+        self.flush_barriers();
         let vk_regions_iter = dst.map_buffer_copies(regions);
 
         unsafe {
@@ -336,6 +515,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
+        self.flush_barriers();
         let src_layout = conv::derive_image_layout(src_usage, src.format);
         let vk_regions_iter = src.map_buffer_copies(regions);
 
@@ -364,6 +544,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         unsafe { self.device.raw.cmd_end_query(self.active, set.raw, index) };
     }
     unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
+        self.flush_barriers();
         unsafe {
             self.device.raw.cmd_write_timestamp(
                 self.active,
@@ -391,6 +572,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         offset: wgt::BufferAddress,
         stride: wgt::BufferSize,
     ) {
+        self.flush_barriers();
         unsafe {
             self.device.raw.cmd_copy_query_pool_results(
                 self.active,
@@ -410,6 +592,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         super::Api: 'a,
         T: IntoIterator<Item = crate::BuildAccelerationStructureDescriptor<'a, super::Api>>,
     {
+        self.flush_barriers();
         const CAPACITY_OUTER: usize = 8;
         const CAPACITY_INNER: usize = 1;
         let descriptor_count = descriptor_count as usize;
@@ -628,23 +811,18 @@ impl crate::CommandEncoder for super::CommandEncoder {
         let (dst_stage, dst_access) =
             conv::map_acceleration_structure_usage_to_barrier(barrier.usage.end);
 
-        unsafe {
-            self.device.raw.cmd_pipeline_barrier(
-                self.active,
-                src_stage | vk::PipelineStageFlags::TOP_OF_PIPE,
-                dst_stage | vk::PipelineStageFlags::BOTTOM_OF_PIPE,
-                vk::DependencyFlags::empty(),
-                &[vk::MemoryBarrier::default()
-                    .src_access_mask(src_access)
-                    .dst_access_mask(dst_access)],
-                &[],
-                &[],
-            )
-        };
+        self.temp.barriers.memory.push(
+            vk::MemoryBarrier2::default()
+                .src_stage_mask(conv::map_pipeline_stages2(src_stage))
+                .src_access_mask(conv::map_access2(src_access))
+                .dst_stage_mask(conv::map_pipeline_stages2(dst_stage))
+                .dst_access_mask(conv::map_access2(dst_access)),
+        );
     }
     // render
 
     unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<super::Api>) {
+        self.flush_barriers();
         let mut vk_clear_values =
             ArrayVec::<vk::ClearValue, { super::MAX_TOTAL_ATTACHMENTS }>::new();
         let mut vk_image_views = ArrayVec::<vk::ImageView, { super::MAX_TOTAL_ATTACHMENTS }>::new();
@@ -732,11 +910,13 @@ impl crate::CommandEncoder for super::CommandEncoder {
             max_depth: 1.0,
         }];
 
-        let raw_pass = self.device.make_render_pass(rp_key).unwrap();
-        let raw_framebuffer = self
+        let (raw_pass, pass_user) = self.device.make_render_pass(rp_key).unwrap();
+        let (raw_framebuffer, framebuffer_user) = self
             .device
             .make_framebuffer(fb_key, raw_pass, desc.label)
             .unwrap();
+        self.cache_users.push(pass_user);
+        self.cache_users.push(framebuffer_user);
 
         let mut vk_info = vk::RenderPassBeginInfo::default()
             .render_pass(raw_pass)
@@ -779,7 +959,11 @@ impl crate::CommandEncoder for super::CommandEncoder {
             self.device.raw.cmd_begin_render_pass(
                 self.active,
                 &vk_info,
-                vk::SubpassContents::INLINE,
+                if desc.render_bundles_only {
+                    vk::SubpassContents::SECONDARY_COMMAND_BUFFERS
+                } else {
+                    vk::SubpassContents::INLINE
+                },
             );
         };
 
@@ -858,6 +1042,19 @@ impl crate::CommandEncoder for super::CommandEncoder {
         }
     }
 
+    unsafe fn execute_render_bundles<'a, I>(&mut self, bundles: I)
+    where
+        I: Iterator<Item = &'a super::RenderBundle>,
+    {
+        for bundle in bundles {
+            unsafe {
+                self.device
+                    .raw
+                    .cmd_execute_commands(self.active, slice::from_ref(&bundle.raw))
+            };
+        }
+    }
+
     unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
         unsafe {
             self.device.raw.cmd_bind_pipeline(
@@ -1105,6 +1302,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
     }
 
     unsafe fn dispatch(&mut self, count: [u32; 3]) {
+        self.flush_barriers();
         unsafe {
             self.device
                 .raw
@@ -1112,6 +1310,7 @@ impl crate::CommandEncoder for super::CommandEncoder {
         };
     }
     unsafe fn dispatch_indirect(&mut self, buffer: &super::Buffer, offset: wgt::BufferAddress) {
+        self.flush_barriers();
         unsafe {
             self.device
                 .raw
@@ -324,6 +324,27 @@ pub fn map_texture_usage_to_barrier(
     }
 }
 
+/// Convert stages returned by the `map_*_usage_to_barrier` functions for
+/// `synchronization2`, whose flags have the same values as the original ones.
+pub fn map_pipeline_stages2(stages: vk::PipelineStageFlags) -> vk::PipelineStageFlags2 {
+    vk::PipelineStageFlags2::from_raw(stages.as_raw().into())
+}
+
+/// Like [`map_pipeline_stages2`], for access masks.
+pub fn map_access2(access: vk::AccessFlags) -> vk::AccessFlags2 {
+    vk::AccessFlags2::from_raw(access.as_raw().into())
+}
+
+/// The inverse of [`map_pipeline_stages2`].
+pub fn map_pipeline_stages2_to_stages(stages: vk::PipelineStageFlags2) -> vk::PipelineStageFlags {
+    vk::PipelineStageFlags::from_raw(stages.as_raw() as u32)
+}
+
+/// The inverse of [`map_access2`].
+pub fn map_access2_to_access(access: vk::AccessFlags2) -> vk::AccessFlags {
+    vk::AccessFlags::from_raw(access.as_raw() as u32)
+}
+
 pub fn map_vk_image_usage(usage: vk::ImageUsageFlags) -> crate::TextureUses {
     let mut bits = crate::TextureUses::empty();
     if usage.contains(vk::ImageUsageFlags::TRANSFER_SRC) {
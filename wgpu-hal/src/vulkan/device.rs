@@ -1,4 +1,4 @@
-use super::conv;
+use super::{cache::CacheUser, conv};
 
 use arrayvec::ArrayVec;
 use ash::{khr, vk};
@@ -6,7 +6,7 @@ use parking_lot::Mutex;
 
 use std::{
     borrow::Cow,
-    collections::{hash_map::Entry, BTreeMap},
+    collections::BTreeMap,
     ffi::{CStr, CString},
     mem::MaybeUninit,
     num::NonZeroU32,
@@ -14,6 +14,10 @@ use std::{
     sync::Arc,
 };
 
+/// The largest number of descriptor sets allocated at once for a bind group
+/// layout.
+const MAX_DESCRIPTOR_SET_BATCH: u32 = 64;
+
 impl super::DeviceShared {
     pub(super) unsafe fn set_object_name(&self, object: impl vk::Handle, name: &str) {
         let Some(extension) = self.extension_fns.debug_utils.as_ref() else {
@@ -58,131 +62,134 @@ impl super::DeviceShared {
     pub fn make_render_pass(
         &self,
         key: super::RenderPassKey,
+    ) -> Result<(vk::RenderPass, CacheUser), crate::DeviceError> {
+        self.render_passes.get_or_create(
+            key,
+            |key| unsafe { self.create_render_pass(key) },
+            |raw| unsafe { self.raw.destroy_render_pass(raw, None) },
+        )
+    }
+
+    unsafe fn create_render_pass(
+        &self,
+        key: &super::RenderPassKey,
     ) -> Result<vk::RenderPass, crate::DeviceError> {
-        Ok(match self.render_passes.lock().entry(key) {
-            Entry::Occupied(e) => *e.get(),
-            Entry::Vacant(e) => {
-                let mut vk_attachments = Vec::new();
-                let mut color_refs = Vec::with_capacity(e.key().colors.len());
-                let mut resolve_refs = Vec::with_capacity(color_refs.capacity());
-                let mut ds_ref = None;
-                let samples = vk::SampleCountFlags::from_raw(e.key().sample_count);
-                let unused = vk::AttachmentReference {
-                    attachment: vk::ATTACHMENT_UNUSED,
-                    layout: vk::ImageLayout::UNDEFINED,
+        let mut vk_attachments = Vec::new();
+        let mut color_refs = Vec::with_capacity(key.colors.len());
+        let mut resolve_refs = Vec::with_capacity(color_refs.capacity());
+        let mut ds_ref = None;
+        let samples = vk::SampleCountFlags::from_raw(key.sample_count);
+        let unused = vk::AttachmentReference {
+            attachment: vk::ATTACHMENT_UNUSED,
+            layout: vk::ImageLayout::UNDEFINED,
+        };
+        for cat in key.colors.iter() {
+            let (color_ref, resolve_ref) = if let Some(cat) = cat.as_ref() {
+                let color_ref = vk::AttachmentReference {
+                    attachment: vk_attachments.len() as u32,
+                    layout: cat.base.layout,
                 };
-                for cat in e.key().colors.iter() {
-                    let (color_ref, resolve_ref) = if let Some(cat) = cat.as_ref() {
-                        let color_ref = vk::AttachmentReference {
-                            attachment: vk_attachments.len() as u32,
-                            layout: cat.base.layout,
-                        };
-                        vk_attachments.push({
-                            let (load_op, store_op) = conv::map_attachment_ops(cat.base.ops);
-                            vk::AttachmentDescription::default()
-                                .format(cat.base.format)
-                                .samples(samples)
-                                .load_op(load_op)
-                                .store_op(store_op)
-                                .initial_layout(cat.base.layout)
-                                .final_layout(cat.base.layout)
-                        });
-                        let resolve_ref = if let Some(ref rat) = cat.resolve {
-                            let (load_op, store_op) = conv::map_attachment_ops(rat.ops);
-                            let vk_attachment = vk::AttachmentDescription::default()
-                                .format(rat.format)
-                                .samples(vk::SampleCountFlags::TYPE_1)
-                                .load_op(load_op)
-                                .store_op(store_op)
-                                .initial_layout(rat.layout)
-                                .final_layout(rat.layout);
-                            vk_attachments.push(vk_attachment);
-
-                            vk::AttachmentReference {
-                                attachment: vk_attachments.len() as u32 - 1,
-                                layout: rat.layout,
-                            }
-                        } else {
-                            unused
-                        };
-
-                        (color_ref, resolve_ref)
-                    } else {
-                        (unused, unused)
-                    };
-
-                    color_refs.push(color_ref);
-                    resolve_refs.push(resolve_ref);
-                }
-
-                if let Some(ref ds) = e.key().depth_stencil {
-                    ds_ref = Some(vk::AttachmentReference {
-                        attachment: vk_attachments.len() as u32,
-                        layout: ds.base.layout,
-                    });
-                    let (load_op, store_op) = conv::map_attachment_ops(ds.base.ops);
-                    let (stencil_load_op, stencil_store_op) =
-                        conv::map_attachment_ops(ds.stencil_ops);
-                    let vk_attachment = vk::AttachmentDescription::default()
-                        .format(ds.base.format)
+                vk_attachments.push({
+                    let (load_op, store_op) = conv::map_attachment_ops(cat.base.ops);
+                    vk::AttachmentDescription::default()
+                        .format(cat.base.format)
                         .samples(samples)
                         .load_op(load_op)
                         .store_op(store_op)
-                        .stencil_load_op(stencil_load_op)
-                        .stencil_store_op(stencil_store_op)
-                        .initial_layout(ds.base.layout)
-                        .final_layout(ds.base.layout);
+                        .initial_layout(cat.base.layout)
+                        .final_layout(cat.base.layout)
+                });
+                let resolve_ref = if let Some(ref rat) = cat.resolve {
+                    let (load_op, store_op) = conv::map_attachment_ops(rat.ops);
+                    let vk_attachment = vk::AttachmentDescription::default()
+                        .format(rat.format)
+                        .samples(vk::SampleCountFlags::TYPE_1)
+                        .load_op(load_op)
+                        .store_op(store_op)
+                        .initial_layout(rat.layout)
+                        .final_layout(rat.layout);
                     vk_attachments.push(vk_attachment);
-                }
 
-                let vk_subpasses = [{
-                    let mut vk_subpass = vk::SubpassDescription::default()
-                        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-                        .color_attachments(&color_refs)
-                        .resolve_attachments(&resolve_refs);
-
-                    if self
-                        .workarounds
-                        .contains(super::Workarounds::EMPTY_RESOLVE_ATTACHMENT_LISTS)
-                        && resolve_refs.is_empty()
-                    {
-                        vk_subpass.p_resolve_attachments = ptr::null();
+                    vk::AttachmentReference {
+                        attachment: vk_attachments.len() as u32 - 1,
+                        layout: rat.layout,
                     }
+                } else {
+                    unused
+                };
 
-                    if let Some(ref reference) = ds_ref {
-                        vk_subpass = vk_subpass.depth_stencil_attachment(reference)
-                    }
-                    vk_subpass
-                }];
-
-                let mut vk_info = vk::RenderPassCreateInfo::default()
-                    .attachments(&vk_attachments)
-                    .subpasses(&vk_subpasses);
-
-                let mut multiview_info;
-                let mask;
-                if let Some(multiview) = e.key().multiview {
-                    // Sanity checks, better to panic here than cause a driver crash
-                    assert!(multiview.get() <= 8);
-                    assert!(multiview.get() > 1);
-
-                    // Right now we enable all bits on the view masks and correlation masks.
-                    // This means we're rendering to all views in the subpass, and that all views
-                    // can be rendered concurrently.
-                    mask = [(1 << multiview.get()) - 1];
-
-                    // On Vulkan 1.1 or later, this is an alias for core functionality
-                    multiview_info = vk::RenderPassMultiviewCreateInfoKHR::default()
-                        .view_masks(&mask)
-                        .correlation_masks(&mask);
-                    vk_info = vk_info.push_next(&mut multiview_info);
-                }
+                (color_ref, resolve_ref)
+            } else {
+                (unused, unused)
+            };
 
-                let raw = unsafe { self.raw.create_render_pass(&vk_info, None)? };
+            color_refs.push(color_ref);
+            resolve_refs.push(resolve_ref);
+        }
 
-                *e.insert(raw)
+        if let Some(ref ds) = key.depth_stencil {
+            ds_ref = Some(vk::AttachmentReference {
+                attachment: vk_attachments.len() as u32,
+                layout: ds.base.layout,
+            });
+            let (load_op, store_op) = conv::map_attachment_ops(ds.base.ops);
+            let (stencil_load_op, stencil_store_op) = conv::map_attachment_ops(ds.stencil_ops);
+            let vk_attachment = vk::AttachmentDescription::default()
+                .format(ds.base.format)
+                .samples(samples)
+                .load_op(load_op)
+                .store_op(store_op)
+                .stencil_load_op(stencil_load_op)
+                .stencil_store_op(stencil_store_op)
+                .initial_layout(ds.base.layout)
+                .final_layout(ds.base.layout);
+            vk_attachments.push(vk_attachment);
+        }
+
+        let vk_subpasses = [{
+            let mut vk_subpass = vk::SubpassDescription::default()
+                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                .color_attachments(&color_refs)
+                .resolve_attachments(&resolve_refs);
+
+            if self
+                .workarounds
+                .contains(super::Workarounds::EMPTY_RESOLVE_ATTACHMENT_LISTS)
+                && resolve_refs.is_empty()
+            {
+                vk_subpass.p_resolve_attachments = ptr::null();
             }
-        })
+
+            if let Some(ref reference) = ds_ref {
+                vk_subpass = vk_subpass.depth_stencil_attachment(reference)
+            }
+            vk_subpass
+        }];
+
+        let mut vk_info = vk::RenderPassCreateInfo::default()
+            .attachments(&vk_attachments)
+            .subpasses(&vk_subpasses);
+
+        let mut multiview_info;
+        let mask;
+        if let Some(multiview) = key.multiview {
+            // Sanity checks, better to panic here than cause a driver crash
+            assert!(multiview.get() <= 8);
+            assert!(multiview.get() > 1);
+
+            // Right now we enable all bits on the view masks and correlation masks.
+            // This means we're rendering to all views in the subpass, and that all views
+            // can be rendered concurrently.
+            mask = [(1 << multiview.get()) - 1];
+
+            // On Vulkan 1.1 or later, this is an alias for core functionality
+            multiview_info = vk::RenderPassMultiviewCreateInfoKHR::default()
+                .view_masks(&mask)
+                .correlation_masks(&mask);
+            vk_info = vk_info.push_next(&mut multiview_info);
+        }
+
+        Ok(unsafe { self.raw.create_render_pass(&vk_info, None)? })
     }
 
     pub fn make_framebuffer(
@@ -190,78 +197,82 @@ impl super::DeviceShared {
         key: super::FramebufferKey,
         raw_pass: vk::RenderPass,
         pass_label: crate::Label,
-    ) -> Result<vk::Framebuffer, crate::DeviceError> {
-        Ok(match self.framebuffers.lock().entry(key) {
-            Entry::Occupied(e) => *e.get(),
-            Entry::Vacant(e) => {
-                let vk_views = e
-                    .key()
-                    .attachments
-                    .iter()
-                    .map(|at| at.raw)
-                    .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
-                let vk_view_formats = e
-                    .key()
-                    .attachments
-                    .iter()
-                    .map(|at| self.private_caps.map_texture_format(at.view_format))
-                    .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
-                let vk_view_formats_list = e
-                    .key()
-                    .attachments
-                    .iter()
-                    .map(|at| at.raw_view_formats.clone())
-                    .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
+    ) -> Result<(vk::Framebuffer, CacheUser), crate::DeviceError> {
+        self.framebuffers.get_or_create(
+            key,
+            |key| Ok(unsafe { self.create_framebuffer(key, raw_pass, pass_label) }),
+            |raw| unsafe { self.raw.destroy_framebuffer(raw, None) },
+        )
+    }
 
-                let vk_image_infos = e
-                    .key()
-                    .attachments
-                    .iter()
-                    .enumerate()
-                    .map(|(i, at)| {
-                        let mut info = vk::FramebufferAttachmentImageInfo::default()
-                            .usage(conv::map_texture_usage(at.view_usage))
-                            .flags(at.raw_image_flags)
-                            .width(e.key().extent.width)
-                            .height(e.key().extent.height)
-                            .layer_count(e.key().extent.depth_or_array_layers);
-                        // https://registry.khronos.org/vulkan/specs/1.3-extensions/man/html/VkRenderPassBeginInfo.html#VUID-VkRenderPassBeginInfo-framebuffer-03214
-                        if vk_view_formats_list[i].is_empty() {
-                            info = info.view_formats(&vk_view_formats[i..i + 1]);
-                        } else {
-                            info = info.view_formats(&vk_view_formats_list[i]);
-                        };
-                        info
-                    })
-                    .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
-
-                let mut vk_attachment_info = vk::FramebufferAttachmentsCreateInfo::default()
-                    .attachment_image_infos(&vk_image_infos);
-                let mut vk_info = vk::FramebufferCreateInfo::default()
-                    .render_pass(raw_pass)
-                    .width(e.key().extent.width)
-                    .height(e.key().extent.height)
-                    .layers(e.key().extent.depth_or_array_layers);
-
-                if self.private_caps.imageless_framebuffers {
-                    //TODO: https://github.com/MaikKlein/ash/issues/450
-                    vk_info = vk_info
-                        .flags(vk::FramebufferCreateFlags::IMAGELESS_KHR)
-                        .push_next(&mut vk_attachment_info);
-                    vk_info.attachment_count = e.key().attachments.len() as u32;
+    unsafe fn create_framebuffer(
+        &self,
+        key: &super::FramebufferKey,
+        raw_pass: vk::RenderPass,
+        pass_label: crate::Label,
+    ) -> vk::Framebuffer {
+        let vk_views = key
+            .attachments
+            .iter()
+            .map(|at| at.raw)
+            .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
+        let vk_view_formats = key
+            .attachments
+            .iter()
+            .map(|at| self.private_caps.map_texture_format(at.view_format))
+            .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
+        let vk_view_formats_list = key
+            .attachments
+            .iter()
+            .map(|at| at.raw_view_formats.clone())
+            .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
+
+        let vk_image_infos = key
+            .attachments
+            .iter()
+            .enumerate()
+            .map(|(i, at)| {
+                let mut info = vk::FramebufferAttachmentImageInfo::default()
+                    .usage(conv::map_texture_usage(at.view_usage))
+                    .flags(at.raw_image_flags)
+                    .width(key.extent.width)
+                    .height(key.extent.height)
+                    .layer_count(key.extent.depth_or_array_layers);
+                // https://registry.khronos.org/vulkan/specs/1.3-extensions/man/html/VkRenderPassBeginInfo.html#VUID-VkRenderPassBeginInfo-framebuffer-03214
+                if vk_view_formats_list[i].is_empty() {
+                    info = info.view_formats(&vk_view_formats[i..i + 1]);
                 } else {
-                    vk_info = vk_info.attachments(&vk_views);
-                }
+                    info = info.view_formats(&vk_view_formats_list[i]);
+                };
+                info
+            })
+            .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
+
+        let mut vk_attachment_info =
+            vk::FramebufferAttachmentsCreateInfo::default().attachment_image_infos(&vk_image_infos);
+        let mut vk_info = vk::FramebufferCreateInfo::default()
+            .render_pass(raw_pass)
+            .width(key.extent.width)
+            .height(key.extent.height)
+            .layers(key.extent.depth_or_array_layers);
+
+        if self.private_caps.imageless_framebuffers {
+            //TODO: https://github.com/MaikKlein/ash/issues/450
+            vk_info = vk_info
+                .flags(vk::FramebufferCreateFlags::IMAGELESS_KHR)
+                .push_next(&mut vk_attachment_info);
+            vk_info.attachment_count = key.attachments.len() as u32;
+        } else {
+            vk_info = vk_info.attachments(&vk_views);
+        }
 
-                *e.insert(unsafe {
-                    let raw = self.raw.create_framebuffer(&vk_info, None).unwrap();
-                    if let Some(label) = pass_label {
-                        self.set_object_name(raw, label);
-                    }
-                    raw
-                })
+        unsafe {
+            let raw = self.raw.create_framebuffer(&vk_info, None).unwrap();
+            if let Some(label) = pass_label {
+                self.set_object_name(raw, label);
             }
-        })
+            raw
+        }
     }
 
     fn make_memory_ranges<'a, I: 'a + Iterator<Item = crate::MemoryRange>>(
@@ -273,105 +284,23 @@ impl super::DeviceShared {
         let mask = self.private_caps.non_coherent_map_mask;
         Some(ranges.map(move |range| {
             vk::MappedMemoryRange::default()
-                .memory(*block.memory())
-                .offset((block.offset() + range.start) & !mask)
+                .memory(block.memory)
+                .offset((block.offset + range.start) & !mask)
                 .size((range.end - range.start + mask) & !mask)
         }))
     }
 
     unsafe fn free_resources(&self) {
-        for &raw in self.render_passes.lock().values() {
-            unsafe { self.raw.destroy_render_pass(raw, None) };
-        }
-        for &raw in self.framebuffers.lock().values() {
-            unsafe { self.raw.destroy_framebuffer(raw, None) };
-        }
+        self.render_passes
+            .free(|raw| unsafe { self.raw.destroy_render_pass(raw, None) });
+        self.framebuffers
+            .free(|raw| unsafe { self.raw.destroy_framebuffer(raw, None) });
         if self.handle_is_owned {
             unsafe { self.raw.destroy_device(None) };
         }
     }
 }
 
-impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
-    unsafe fn allocate_memory(
-        &self,
-        size: u64,
-        memory_type: u32,
-        flags: gpu_alloc::AllocationFlags,
-    ) -> Result<vk::DeviceMemory, gpu_alloc::OutOfMemory> {
-        let mut info = vk::MemoryAllocateInfo::default()
-            .allocation_size(size)
-            .memory_type_index(memory_type);
-
-        let mut info_flags;
-
-        if flags.contains(gpu_alloc::AllocationFlags::DEVICE_ADDRESS) {
-            info_flags = vk::MemoryAllocateFlagsInfo::default()
-                .flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
-            info = info.push_next(&mut info_flags);
-        }
-
-        match unsafe { self.raw.allocate_memory(&info, None) } {
-            Ok(memory) => Ok(memory),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
-                Err(gpu_alloc::OutOfMemory::OutOfDeviceMemory)
-            }
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
-                Err(gpu_alloc::OutOfMemory::OutOfHostMemory)
-            }
-            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
-            Err(err) => panic!("Unexpected Vulkan error: `{err}`"),
-        }
-    }
-
-    unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
-        unsafe { self.raw.free_memory(memory, None) };
-    }
-
-    unsafe fn map_memory(
-        &self,
-        memory: &mut vk::DeviceMemory,
-        offset: u64,
-        size: u64,
-    ) -> Result<ptr::NonNull<u8>, gpu_alloc::DeviceMapError> {
-        match unsafe {
-            self.raw
-                .map_memory(*memory, offset, size, vk::MemoryMapFlags::empty())
-        } {
-            Ok(ptr) => Ok(ptr::NonNull::new(ptr as *mut u8)
-                .expect("Pointer to memory mapping must not be null")),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
-                Err(gpu_alloc::DeviceMapError::OutOfDeviceMemory)
-            }
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
-                Err(gpu_alloc::DeviceMapError::OutOfHostMemory)
-            }
-            Err(vk::Result::ERROR_MEMORY_MAP_FAILED) => Err(gpu_alloc::DeviceMapError::MapFailed),
-            Err(err) => panic!("Unexpected Vulkan error: `{err}`"),
-        }
-    }
-
-    unsafe fn unmap_memory(&self, memory: &mut vk::DeviceMemory) {
-        unsafe { self.raw.unmap_memory(*memory) };
-    }
-
-    unsafe fn invalidate_memory_ranges(
-        &self,
-        _ranges: &[gpu_alloc::MappedMemoryRange<'_, vk::DeviceMemory>],
-    ) -> Result<(), gpu_alloc::OutOfMemory> {
-        // should never be called
-        unimplemented!()
-    }
-
-    unsafe fn flush_memory_ranges(
-        &self,
-        _ranges: &[gpu_alloc::MappedMemoryRange<'_, vk::DeviceMemory>],
-    ) -> Result<(), gpu_alloc::OutOfMemory> {
-        // should never be called
-        unimplemented!()
-    }
-}
-
 impl
     gpu_descriptor::DescriptorDevice<vk::DescriptorSetLayout, vk::DescriptorPool, vk::DescriptorSet>
     for super::DeviceShared
@@ -522,6 +451,33 @@ struct CompiledStage {
 }
 
 impl super::Device {
+    /// Returns the index and properties of a memory type in `type_bits` with
+    /// the `preferred` properties, or else of one with the `required` ones.
+    ///
+    /// This is for memory not allocated by the device's `MemoryAllocator`.
+    pub(super) fn find_memory_type(
+        &self,
+        type_bits: u32,
+        required: vk::MemoryPropertyFlags,
+        preferred: vk::MemoryPropertyFlags,
+    ) -> Option<(u32, vk::MemoryPropertyFlags)> {
+        let properties = unsafe {
+            self.shared
+                .instance
+                .raw
+                .get_physical_device_memory_properties(self.shared.physical_device)
+        };
+        let type_bits = type_bits & self.valid_ash_memory_types;
+        let find = |flags: vk::MemoryPropertyFlags| {
+            (0..properties.memory_type_count).find_map(|index| {
+                let property_flags = properties.memory_types[index as usize].property_flags;
+                (type_bits & (1 << index) != 0 && property_flags.contains(flags))
+                    .then_some((index, property_flags))
+            })
+        };
+        find(required | preferred).or_else(|| find(required))
+    }
+
     pub(super) unsafe fn create_swapchain(
         &self,
         surface: &super::Surface,
@@ -702,6 +658,34 @@ impl super::Device {
         Ok(raw)
     }
 
+    /// Runs `write` to translate a module to SPIR-V, unless `cache` already
+    /// holds its output for the same `options`.
+    fn write_spv_cached<E>(
+        cache: Option<&crate::ShaderCacheEntry>,
+        options: impl std::hash::Hash,
+        write: impl FnOnce() -> Result<Vec<u32>, E>,
+    ) -> Result<Vec<u32>, E> {
+        let Some(cache) = cache else {
+            return write();
+        };
+        let key = cache.key("spv", options);
+        if let Some(bytes) = cache.cache.load(&key) {
+            if bytes.len() % 4 == 0 {
+                return Ok(bytes
+                    .chunks_exact(4)
+                    .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                    .collect());
+            }
+        }
+        let spv = write()?;
+        let bytes = spv
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        cache.cache.store(&key, &bytes);
+        Ok(spv)
+    }
+
     fn compile_stage(
         &self,
         stage: &crate::ProgrammableStage<super::Api>,
@@ -755,18 +739,30 @@ impl super::Device {
                     &self.naga_options
                 };
 
-                let (module, info) = naga::back::pipeline_constants::process_overrides(
-                    &naga_shader.module,
-                    &naga_shader.info,
-                    stage.constants,
-                )
-                .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{e}")))?;
-
-                let spv = {
-                    profiling::scope!("naga::spv::write_vec");
-                    naga::back::spv::write_vec(&module, &info, options, Some(&pipeline_options))
-                }
-                .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{e}")))?;
+                let mut constants = stage
+                    .constants
+                    .iter()
+                    .map(|(name, value)| (name, value.to_bits()))
+                    .collect::<Vec<_>>();
+                constants.sort();
+                let cache_options = (
+                    format!("{options:?}"),
+                    format!("{pipeline_options:?}"),
+                    constants,
+                );
+                let spv =
+                    Self::write_spv_cached(naga_shader.cache.as_ref(), cache_options, || {
+                        let (module, info) = naga::back::pipeline_constants::process_overrides(
+                            &naga_shader.module,
+                            &naga_shader.info,
+                            stage.constants,
+                        )
+                        .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{e}")))?;
+
+                        profiling::scope!("naga::spv::write_vec");
+                        naga::back::spv::write_vec(&module, &info, options, Some(&pipeline_options))
+                            .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{e}")))
+                    })?;
                 self.create_shader_module_impl(&spv)?
             }
         };
@@ -828,32 +824,25 @@ impl super::Device {
     pub fn shared_instance(&self) -> &super::InstanceShared {
         &self.shared.instance
     }
-}
-
-impl crate::Device for super::Device {
-    type A = super::Api;
-
-    unsafe fn exit(self, queue: super::Queue) {
-        unsafe { self.mem_allocator.into_inner().cleanup(&*self.shared) };
-        unsafe { self.desc_allocator.into_inner().cleanup(&*self.shared) };
-        unsafe {
-            queue
-                .relay_semaphores
-                .into_inner()
-                .destroy(&self.shared.raw)
-        };
-        unsafe { self.shared.free_resources() };
-    }
 
-    unsafe fn create_buffer(
+    /// Creates a buffer, in a dedicated allocation of `external` memory if
+    /// it's set.
+    pub(super) unsafe fn create_buffer_impl(
         &self,
         desc: &crate::BufferDescriptor,
+        external: Option<super::external::ExternalAllocation>,
     ) -> Result<super::Buffer, crate::DeviceError> {
-        let vk_info = vk::BufferCreateInfo::default()
+        let mut vk_info = vk::BufferCreateInfo::default()
             .size(desc.size)
             .usage(conv::map_buffer_usage(desc.usage))
             .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
+        let mut external_info = vk::ExternalMemoryBufferCreateInfo::default();
+        if let Some(ref external) = external {
+            external_info = external_info.handle_types(external.handle_type());
+            vk_info = vk_info.push_next(&mut external_info);
+        }
+
         let raw = unsafe { self.shared.raw.create_buffer(&vk_info, None)? };
         let req = unsafe { self.shared.raw.get_buffer_memory_requirements(raw) };
 
@@ -861,22 +850,34 @@ impl crate::Device for super::Device {
             .usage
             .intersects(crate::BufferUses::MAP_READ | crate::BufferUses::MAP_WRITE)
         {
-            let mut flags = gpu_alloc::UsageFlags::HOST_ACCESS;
+            let mut flags = super::MemoryUsage::HOST_ACCESS;
             //TODO: find a way to use `crate::MemoryFlags::PREFER_COHERENT`
             flags.set(
-                gpu_alloc::UsageFlags::DOWNLOAD,
+                super::MemoryUsage::DOWNLOAD,
                 desc.usage.contains(crate::BufferUses::MAP_READ),
             );
             flags.set(
-                gpu_alloc::UsageFlags::UPLOAD,
+                super::MemoryUsage::UPLOAD,
                 desc.usage.contains(crate::BufferUses::MAP_WRITE),
             );
+            // Buffers the GPU reads directly are worth placing in BAR memory.
+            flags.set(
+                super::MemoryUsage::FAST_DEVICE_ACCESS,
+                self.prefer_device_local_host_visible
+                    && desc.usage.intersects(
+                        crate::BufferUses::INDEX
+                            | crate::BufferUses::VERTEX
+                            | crate::BufferUses::UNIFORM
+                            | crate::BufferUses::STORAGE_READ
+                            | crate::BufferUses::INDIRECT,
+                    ),
+            );
             flags
         } else {
-            gpu_alloc::UsageFlags::FAST_DEVICE_ACCESS
+            super::MemoryUsage::FAST_DEVICE_ACCESS
         };
         alloc_usage.set(
-            gpu_alloc::UsageFlags::TRANSIENT,
+            super::MemoryUsage::TRANSIENT,
             desc.memory_flags.contains(crate::MemoryFlags::TRANSIENT),
         );
 
@@ -889,22 +890,32 @@ impl crate::Device for super::Device {
             req.alignment
         } - 1;
 
-        let block = unsafe {
-            self.mem_allocator.lock().alloc(
-                &*self.shared,
-                gpu_alloc::Request {
-                    size: req.size,
-                    align_mask: alignment_mask,
-                    usage: alloc_usage,
-                    memory_types: req.memory_type_bits & self.valid_ash_memory_types,
-                },
-            )?
+        let block = if let Some(external) = external {
+            unsafe {
+                self.allocate_external(
+                    &req,
+                    external,
+                    super::external::DedicatedResource::Buffer(raw),
+                )?
+            }
+        } else {
+            unsafe {
+                self.mem_allocator.allocate(
+                    &self.shared.raw,
+                    &super::MemoryRequest {
+                        size: req.size,
+                        align_mask: alignment_mask,
+                        usage: alloc_usage,
+                        memory_types: req.memory_type_bits & self.valid_ash_memory_types,
+                    },
+                )?
+            }
         };
 
         unsafe {
             self.shared
                 .raw
-                .bind_buffer_memory(raw, *block.memory(), block.offset())?
+                .bind_buffer_memory(raw, block.memory, block.offset)?
         };
 
         if let Some(label) = desc.label {
@@ -916,77 +927,13 @@ impl crate::Device for super::Device {
             block: Some(Mutex::new(block)),
         })
     }
-    unsafe fn destroy_buffer(&self, buffer: super::Buffer) {
-        unsafe { self.shared.raw.destroy_buffer(buffer.raw, None) };
-        if let Some(block) = buffer.block {
-            unsafe {
-                self.mem_allocator
-                    .lock()
-                    .dealloc(&*self.shared, block.into_inner())
-            };
-        }
-    }
-
-    unsafe fn map_buffer(
-        &self,
-        buffer: &super::Buffer,
-        range: crate::MemoryRange,
-    ) -> Result<crate::BufferMapping, crate::DeviceError> {
-        if let Some(ref block) = buffer.block {
-            let size = range.end - range.start;
-            let mut block = block.lock();
-            let ptr = unsafe { block.map(&*self.shared, range.start, size as usize)? };
-            let is_coherent = block
-                .props()
-                .contains(gpu_alloc::MemoryPropertyFlags::HOST_COHERENT);
-            Ok(crate::BufferMapping { ptr, is_coherent })
-        } else {
-            Err(crate::DeviceError::OutOfMemory)
-        }
-    }
-    unsafe fn unmap_buffer(&self, buffer: &super::Buffer) -> Result<(), crate::DeviceError> {
-        if let Some(ref block) = buffer.block {
-            unsafe { block.lock().unmap(&*self.shared) };
-            Ok(())
-        } else {
-            Err(crate::DeviceError::OutOfMemory)
-        }
-    }
-
-    unsafe fn flush_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
-    where
-        I: Iterator<Item = crate::MemoryRange>,
-    {
-        if let Some(vk_ranges) = self.shared.make_memory_ranges(buffer, ranges) {
-            unsafe {
-                self.shared
-                    .raw
-                    .flush_mapped_memory_ranges(
-                        &smallvec::SmallVec::<[vk::MappedMemoryRange; 32]>::from_iter(vk_ranges),
-                    )
-            }
-            .unwrap();
-        }
-    }
-    unsafe fn invalidate_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
-    where
-        I: Iterator<Item = crate::MemoryRange>,
-    {
-        if let Some(vk_ranges) = self.shared.make_memory_ranges(buffer, ranges) {
-            unsafe {
-                self.shared
-                    .raw
-                    .invalidate_mapped_memory_ranges(&smallvec::SmallVec::<
-                        [vk::MappedMemoryRange; 32],
-                    >::from_iter(vk_ranges))
-            }
-            .unwrap();
-        }
-    }
 
-    unsafe fn create_texture(
+    /// Creates a texture, in a dedicated allocation of `external` memory if
+    /// it's set.
+    pub(super) unsafe fn create_texture_impl(
         &self,
         desc: &crate::TextureDescriptor,
+        external: Option<super::external::ExternalAllocation>,
     ) -> Result<super::Texture, crate::DeviceError> {
         let copy_size = desc.copy_extent();
 
@@ -1035,25 +982,41 @@ impl crate::Device for super::Device {
             vk_info = vk_info.push_next(&mut format_list_info);
         }
 
+        let mut external_info = vk::ExternalMemoryImageCreateInfo::default();
+        if let Some(ref external) = external {
+            external_info = external_info.handle_types(external.handle_type());
+            vk_info = vk_info.push_next(&mut external_info);
+        }
+
         let raw = unsafe { self.shared.raw.create_image(&vk_info, None)? };
         let req = unsafe { self.shared.raw.get_image_memory_requirements(raw) };
 
-        let block = unsafe {
-            self.mem_allocator.lock().alloc(
-                &*self.shared,
-                gpu_alloc::Request {
-                    size: req.size,
-                    align_mask: req.alignment - 1,
-                    usage: gpu_alloc::UsageFlags::FAST_DEVICE_ACCESS,
-                    memory_types: req.memory_type_bits & self.valid_ash_memory_types,
-                },
-            )?
+        let block = if let Some(external) = external {
+            unsafe {
+                self.allocate_external(
+                    &req,
+                    external,
+                    super::external::DedicatedResource::Image(raw),
+                )?
+            }
+        } else {
+            unsafe {
+                self.mem_allocator.allocate(
+                    &self.shared.raw,
+                    &super::MemoryRequest {
+                        size: req.size,
+                        align_mask: req.alignment - 1,
+                        usage: super::MemoryUsage::FAST_DEVICE_ACCESS,
+                        memory_types: req.memory_type_bits & self.valid_ash_memory_types,
+                    },
+                )?
+            }
         };
 
         unsafe {
             self.shared
                 .raw
-                .bind_image_memory(raw, *block.memory(), block.offset())?
+                .bind_image_memory(raw, block.memory, block.offset)?
         };
 
         if let Some(label) = desc.label {
@@ -1071,12 +1034,111 @@ impl crate::Device for super::Device {
             view_formats: wgt_view_formats,
         })
     }
+}
+
+impl crate::Device for super::Device {
+    type A = super::Api;
+
+    unsafe fn exit(self, queue: super::Queue) {
+        unsafe { self.mem_allocator.cleanup(&self.shared.raw) };
+        unsafe { self.desc_allocator.into_inner().cleanup(&*self.shared) };
+        unsafe {
+            queue
+                .relay_semaphores
+                .into_inner()
+                .destroy(&self.shared.raw)
+        };
+        unsafe { self.shared.free_resources() };
+    }
+
+    unsafe fn create_buffer(
+        &self,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, crate::DeviceError> {
+        unsafe { self.create_buffer_impl(desc, None) }
+    }
+    unsafe fn destroy_buffer(&self, buffer: super::Buffer) {
+        unsafe { self.shared.raw.destroy_buffer(buffer.raw, None) };
+        if let Some(block) = buffer.block {
+            unsafe { self.deallocate(block.into_inner()) };
+        }
+    }
+
+    unsafe fn map_buffer(
+        &self,
+        buffer: &super::Buffer,
+        range: crate::MemoryRange,
+    ) -> Result<crate::BufferMapping, crate::DeviceError> {
+        if let Some(ref block) = buffer.block {
+            let size = range.end - range.start;
+            let mut block = block.lock();
+            let ptr = unsafe {
+                self.mem_allocator
+                    .map(&self.shared.raw, &mut block, range.start, size as usize)?
+            };
+            Ok(crate::BufferMapping {
+                ptr,
+                is_coherent: block.coherent,
+            })
+        } else {
+            Err(crate::DeviceError::OutOfMemory)
+        }
+    }
+    unsafe fn unmap_buffer(&self, buffer: &super::Buffer) -> Result<(), crate::DeviceError> {
+        if let Some(ref block) = buffer.block {
+            unsafe {
+                self.mem_allocator
+                    .unmap(&self.shared.raw, &mut block.lock())
+            };
+            Ok(())
+        } else {
+            Err(crate::DeviceError::OutOfMemory)
+        }
+    }
+
+    unsafe fn flush_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
+    where
+        I: Iterator<Item = crate::MemoryRange>,
+    {
+        if let Some(vk_ranges) = self.shared.make_memory_ranges(buffer, ranges) {
+            unsafe {
+                self.shared
+                    .raw
+                    .flush_mapped_memory_ranges(
+                        &smallvec::SmallVec::<[vk::MappedMemoryRange; 32]>::from_iter(vk_ranges),
+                    )
+            }
+            .unwrap();
+        }
+    }
+    unsafe fn invalidate_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
+    where
+        I: Iterator<Item = crate::MemoryRange>,
+    {
+        if let Some(vk_ranges) = self.shared.make_memory_ranges(buffer, ranges) {
+            unsafe {
+                self.shared
+                    .raw
+                    .invalidate_mapped_memory_ranges(&smallvec::SmallVec::<
+                        [vk::MappedMemoryRange; 32],
+                    >::from_iter(vk_ranges))
+            }
+            .unwrap();
+        }
+    }
+
+    unsafe fn create_texture(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        unsafe { self.create_texture_impl(desc, None) }
+    }
     unsafe fn destroy_texture(&self, texture: super::Texture) {
         if texture.drop_guard.is_none() {
             unsafe { self.shared.raw.destroy_image(texture.raw, None) };
         }
         if let Some(block) = texture.block {
-            unsafe { self.mem_allocator.lock().dealloc(&*self.shared, block) };
+            unsafe { self.deallocate(block) };
         }
     }
 
@@ -1135,13 +1197,10 @@ impl crate::Device for super::Device {
     }
     unsafe fn destroy_texture_view(&self, view: super::TextureView) {
         if !self.shared.private_caps.imageless_framebuffers {
-            let mut fbuf_lock = self.shared.framebuffers.lock();
-            for (key, &raw_fbuf) in fbuf_lock.iter() {
-                if key.attachments.iter().any(|at| at.raw == view.raw) {
-                    unsafe { self.shared.raw.destroy_framebuffer(raw_fbuf, None) };
-                }
-            }
-            fbuf_lock.retain(|key, _| !key.attachments.iter().any(|at| at.raw == view.raw));
+            self.shared.framebuffers.remove_if(
+                |key| key.attachments.iter().any(|at| at.raw == view.raw),
+                |raw| unsafe { self.shared.raw.destroy_framebuffer(raw, None) },
+            );
         }
         unsafe { self.shared.raw.destroy_image_view(view.raw, None) };
     }
@@ -1208,8 +1267,10 @@ impl crate::Device for super::Device {
             temp: super::Temp::default(),
             free: Vec::new(),
             discarded: Vec::new(),
+            secondary_free: Vec::new(),
             rpass_debug_marker_active: false,
             end_of_pass_timer_query: None,
+            cache_users: Vec::new(),
         })
     }
     unsafe fn destroy_command_encoder(&self, cmd_encoder: super::CommandEncoder) {
@@ -1345,9 +1406,18 @@ impl crate::Device for super::Device {
             desc_count,
             types: types.into_boxed_slice(),
             binding_arrays,
+            free_sets: Default::default(),
         })
     }
     unsafe fn destroy_bind_group_layout(&self, bg_layout: super::BindGroupLayout) {
+        let mut free_sets = bg_layout.free_sets.lock();
+        free_sets.layout_destroyed = true;
+        unsafe {
+            self.desc_allocator
+                .lock()
+                .free(&*self.shared, free_sets.sets.drain(..))
+        };
+        drop(free_sets);
         unsafe {
             self.shared
                 .raw
@@ -1421,20 +1491,33 @@ impl crate::Device for super::Device {
         &self,
         desc: &crate::BindGroupDescriptor<super::Api>,
     ) -> Result<super::BindGroup, crate::DeviceError> {
-        let mut vk_sets = unsafe {
-            self.desc_allocator.lock().allocate(
-                &*self.shared,
-                &desc.layout.raw,
-                gpu_descriptor::DescriptorSetLayoutCreateFlags::empty(),
-                &desc.layout.desc_count,
-                1,
-            )?
+        let set = {
+            let mut free_sets = desc.layout.free_sets.lock();
+            if free_sets.sets.is_empty() {
+                // Double the number of sets allocated for the layout, so that
+                // layouts used by many bind groups rarely go to the allocator.
+                let count = free_sets.allocated.clamp(1, MAX_DESCRIPTOR_SET_BATCH);
+                let sets = unsafe {
+                    self.desc_allocator.lock().allocate(
+                        &*self.shared,
+                        &desc.layout.raw,
+                        gpu_descriptor::DescriptorSetLayoutCreateFlags::empty(),
+                        &desc.layout.desc_count,
+                        count,
+                    )?
+                };
+                free_sets.allocated += count;
+                free_sets.sets.extend(sets);
+            }
+            free_sets.sets.pop().unwrap()
         };
 
-        let set = vk_sets.pop().unwrap();
-        if let Some(label) = desc.label {
-            unsafe { self.shared.set_object_name(*set.raw(), label) };
-        }
+        // Set the name unconditionally, since the set might have been used
+        // by a previous bind group.
+        unsafe {
+            self.shared
+                .set_object_name(*set.raw(), desc.label.unwrap_or_default())
+        };
 
         /// Helper for splitting off and initializing a given number of elements on a pre-allocated
         /// stack, based on items returned from an [`ExactSizeIterator`].  Typically created from a
@@ -1597,14 +1680,22 @@ impl crate::Device for super::Device {
         }
 
         unsafe { self.shared.raw.update_descriptor_sets(&writes, &[]) };
-        Ok(super::BindGroup { set })
+        Ok(super::BindGroup {
+            set,
+            free_sets: Arc::clone(&desc.layout.free_sets),
+        })
     }
     unsafe fn destroy_bind_group(&self, group: super::BindGroup) {
-        unsafe {
-            self.desc_allocator
-                .lock()
-                .free(&*self.shared, Some(group.set))
-        };
+        let mut free_sets = group.free_sets.lock();
+        if free_sets.layout_destroyed {
+            unsafe {
+                self.desc_allocator
+                    .lock()
+                    .free(&*self.shared, Some(group.set))
+            };
+        } else {
+            free_sets.sets.push(group.set);
+        }
     }
 
     unsafe fn create_shader_module(
@@ -1643,17 +1734,27 @@ impl crate::Device for super::Device {
                         binding_array: naga::proc::BoundsCheckPolicy::Unchecked,
                     };
                 }
-                Cow::Owned(
-                    naga::back::spv::write_vec(
-                        &naga_shader.module,
-                        &naga_shader.info,
-                        &naga_options,
-                        None,
-                    )
-                    .map_err(|e| crate::ShaderError::Compilation(format!("{e}")))?,
-                )
+                Cow::Owned(Self::write_spv_cached(
+                    naga_shader.cache.as_ref(),
+                    format!("{naga_options:?}"),
+                    || {
+                        naga::back::spv::write_vec(
+                            &naga_shader.module,
+                            &naga_shader.info,
+                            &naga_options,
+                            None,
+                        )
+                        .map_err(|e| crate::ShaderError::Compilation(format!("{e}")))
+                    },
+                )?)
             }
             crate::ShaderInput::SpirV(spv) => Cow::Borrowed(spv),
+            crate::ShaderInput::Dxil(_) => {
+                panic!("DXIL_SHADER_PASSTHROUGH is not enabled for this backend")
+            }
+            crate::ShaderInput::Msl { .. } => {
+                panic!("MSL_SHADER_PASSTHROUGH is not enabled for this backend")
+            }
         };
 
         let raw = self.create_shader_module_impl(&spv)?;
@@ -1850,7 +1951,8 @@ impl crate::Device for super::Device {
         let vk_dynamic_state =
             vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
 
-        let raw_pass = self
+        // Pipelines don't need their render pass to stay alive once created.
+        let (raw_pass, _) = self
             .shared
             .make_render_pass(compatible_rp_key)
             .map_err(crate::DeviceError::from)?;
@@ -1870,12 +1972,16 @@ impl crate::Device for super::Device {
                 .render_pass(raw_pass)
         }];
 
+        let pipeline_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
+
         let mut raw_vec = {
             profiling::scope!("vkCreateGraphicsPipelines");
             unsafe {
                 self.shared
                     .raw
-                    .create_graphics_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                    .create_graphics_pipelines(pipeline_cache, &vk_infos, None)
                     .map_err(|(_, e)| crate::DeviceError::from(e))
             }?
         };
@@ -1918,12 +2024,16 @@ impl crate::Device for super::Device {
                 .stage(compiled.create_info)
         }];
 
+        let pipeline_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
+
         let mut raw_vec = {
             profiling::scope!("vkCreateComputePipelines");
             unsafe {
                 self.shared
                     .raw
-                    .create_compute_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                    .create_compute_pipelines(pipeline_cache, &vk_infos, None)
                     .map_err(|(_, e)| crate::DeviceError::from(e))
             }?
         };
@@ -1943,6 +2053,37 @@ impl crate::Device for super::Device {
         unsafe { self.shared.raw.destroy_pipeline(pipeline.raw, None) };
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Result<super::PipelineCache, crate::PipelineCacheError> {
+        // Drivers check the header of the initial data, and ignore it if it
+        // was made by another driver or device.
+        let mut info = vk::PipelineCacheCreateInfo::default();
+        if let Some(data) = desc.data {
+            info = info.initial_data(data);
+        }
+        profiling::scope!("vkCreatePipelineCache");
+        let raw = unsafe { self.shared.raw.create_pipeline_cache(&info, None) }
+            .map_err(crate::DeviceError::from)?;
+
+        if let Some(label) = desc.label {
+            unsafe { self.shared.set_object_name(raw, label) };
+        }
+
+        Ok(super::PipelineCache { raw })
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        unsafe { self.shared.raw.destroy_pipeline_cache(cache.raw, None) }
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        profiling::scope!("vkGetPipelineCacheData");
+        unsafe { self.shared.raw.get_pipeline_cache_data(cache.raw) }.ok()
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        Some(self.shared.pipeline_cache_validation_key)
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1982,7 +2123,14 @@ impl crate::Device for super::Device {
         Ok(if self.shared.private_caps.timeline_semaphores {
             let mut sem_type_info =
                 vk::SemaphoreTypeCreateInfo::default().semaphore_type(vk::SemaphoreType::TIMELINE);
-            let vk_info = vk::SemaphoreCreateInfo::default().push_next(&mut sem_type_info);
+            let mut vk_info = vk::SemaphoreCreateInfo::default().push_next(&mut sem_type_info);
+            // Make every fence exportable, so other APIs can wait on the
+            // submissions of `wgpu-core` devices.
+            let mut export_info = vk::ExportSemaphoreCreateInfo::default();
+            if let Some(handle_type) = self.export_semaphore_handle_type() {
+                export_info = export_info.handle_types(handle_type);
+                vk_info = vk_info.push_next(&mut export_info);
+            }
             let raw = unsafe { self.shared.raw.create_semaphore(&vk_info, None) }?;
             super::Fence::TimelineSemaphore(raw)
         } else {
@@ -2061,6 +2209,41 @@ impl crate::Device for super::Device {
         }
     }
 
+    unsafe fn set_cache_limits(&self, limits: &wgt::DeviceCacheLimits) {
+        let shared = &self.shared;
+        shared
+            .render_passes
+            .set_capacity(limits.render_passes, |raw| unsafe {
+                shared.raw.destroy_render_pass(raw, None)
+            });
+        shared
+            .framebuffers
+            .set_capacity(limits.framebuffers, |raw| unsafe {
+                shared.raw.destroy_framebuffer(raw, None)
+            });
+    }
+
+    unsafe fn trim_caches(&self) {
+        let shared = &self.shared;
+        shared
+            .render_passes
+            .trim(|raw| unsafe { shared.raw.destroy_render_pass(raw, None) });
+        shared
+            .framebuffers
+            .trim(|raw| unsafe { shared.raw.destroy_framebuffer(raw, None) });
+    }
+
+    fn cache_report(&self) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport {
+            render_passes: self.shared.render_passes.statistics(),
+            framebuffers: self.shared.framebuffers.statistics(),
+        }
+    }
+
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        self.mem_allocator.report()
+    }
+
     unsafe fn get_acceleration_structure_build_sizes<'a>(
         &self,
         desc: &crate::GetAccelerationStructureBuildSizesDescriptor<'a, super::Api>,
@@ -2219,19 +2402,19 @@ impl crate::Device for super::Device {
             let raw_buffer = self.shared.raw.create_buffer(&vk_buffer_info, None)?;
             let req = self.shared.raw.get_buffer_memory_requirements(raw_buffer);
 
-            let block = self.mem_allocator.lock().alloc(
-                &*self.shared,
-                gpu_alloc::Request {
+            let block = self.mem_allocator.allocate(
+                &self.shared.raw,
+                &super::MemoryRequest {
                     size: req.size,
                     align_mask: req.alignment - 1,
-                    usage: gpu_alloc::UsageFlags::FAST_DEVICE_ACCESS,
+                    usage: super::MemoryUsage::FAST_DEVICE_ACCESS,
                     memory_types: req.memory_type_bits & self.valid_ash_memory_types,
                 },
             )?;
 
             self.shared
                 .raw
-                .bind_buffer_memory(raw_buffer, *block.memory(), block.offset())?;
+                .bind_buffer_memory(raw_buffer, block.memory, block.offset)?;
 
             if let Some(label) = desc.label {
                 self.shared.set_object_name(raw_buffer, label);
@@ -2279,8 +2462,7 @@ impl crate::Device for super::Device {
                 .raw
                 .destroy_buffer(acceleration_structure.buffer, None);
             self.mem_allocator
-                .lock()
-                .dealloc(&*self.shared, acceleration_structure.block.into_inner());
+                .deallocate(&self.shared.raw, acceleration_structure.block.into_inner());
         }
     }
 }
@@ -2343,30 +2525,6 @@ impl super::DeviceShared {
     }
 }
 
-impl From<gpu_alloc::AllocationError> for crate::DeviceError {
-    fn from(error: gpu_alloc::AllocationError) -> Self {
-        use gpu_alloc::AllocationError as Ae;
-        match error {
-            Ae::OutOfDeviceMemory | Ae::OutOfHostMemory => Self::OutOfMemory,
-            _ => {
-                log::error!("memory allocation: {:?}", error);
-                Self::Lost
-            }
-        }
-    }
-}
-impl From<gpu_alloc::MapError> for crate::DeviceError {
-    fn from(error: gpu_alloc::MapError) -> Self {
-        use gpu_alloc::MapError as Me;
-        match error {
-            Me::OutOfDeviceMemory | Me::OutOfHostMemory => Self::OutOfMemory,
-            _ => {
-                log::error!("memory mapping: {:?}", error);
-                Self::Lost
-            }
-        }
-    }
-}
 impl From<gpu_descriptor::AllocationError> for crate::DeviceError {
     fn from(error: gpu_descriptor::AllocationError) -> Self {
         log::error!("descriptor allocation: {:?}", error);
@@ -0,0 +1,571 @@
+/*!
+Sharing memory and semaphores with other APIs and processes.
+
+CUDA, OpenCL, Level Zero and Vulkan devices of other processes can import
+Vulkan memory and timeline semaphores from OS handles: opaque file
+descriptors on Unix, and opaque NT handles on Windows. When the device supports `VK_KHR_external_memory_fd` and
+`VK_KHR_external_semaphore_fd`, or their Win32 counterparts, wgpu-hal enables
+them and:
+
+- [`Device::create_exportable_buffer`] and [`Device::create_exportable_texture`]
+  create resources in dedicated allocations whose memory
+  [`Device::export_buffer_memory`] and [`Device::export_texture_memory`] export;
+- every timeline semaphore [`Fence`] is exportable, with [`Device::export_fence`].
+  This includes the fence of a `wgpu-core` device, signaled with the index of
+  each submission, which other APIs can wait on.
+
+[`Device::import_buffer_memory`], [`Device::import_texture_memory`] and
+[`Device::import_fence`] create a buffer, a texture and a fence from handles
+exported by another process or device, and [`Queue::wait_for_fence`] makes the
+next submission wait on a value of a fence signaled by another API, process or
+device. Opaque handles can only be imported by devices with the same
+`deviceUUID` and `driverUUID` as the exporting device, which
+[`Device::can_share_memory_with`] checks.
+
+Users of `wgpu` reach these through its `as_hal` functions: the device,
+buffers, textures and the device's fence with `Device::as_hal`,
+`Buffer::as_hal`, `Texture::as_hal` and `Device::fence_as_hal`, and the queue
+with `Queue::as_hal`, behind `wgpu`'s `external_memory` feature. Exportable
+resources are wrapped with `Device::create_buffer_from_hal` and
+`Device::create_texture_from_hal`.
+
+[`Device::create_exportable_buffer`]: super::Device::create_exportable_buffer
+[`Device::create_exportable_texture`]: super::Device::create_exportable_texture
+[`Device::export_buffer_memory`]: super::Device::export_buffer_memory
+[`Device::export_texture_memory`]: super::Device::export_texture_memory
+[`Device::export_fence`]: super::Device::export_fence
+[`Device::import_buffer_memory`]: super::Device::import_buffer_memory
+[`Device::import_texture_memory`]: super::Device::import_texture_memory
+[`Device::can_share_memory_with`]: super::Device::can_share_memory_with
+[`Device::import_fence`]: super::Device::import_fence
+[`Fence`]: super::Fence
+[`Queue::wait_for_fence`]: super::Queue::wait_for_fence
+*/
+
+use ash::{khr, vk};
+
+/// The handle type used to export memory.
+#[cfg(unix)]
+const MEMORY_HANDLE_TYPE: vk::ExternalMemoryHandleTypeFlags =
+    vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD;
+#[cfg(windows)]
+const MEMORY_HANDLE_TYPE: vk::ExternalMemoryHandleTypeFlags =
+    vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32;
+
+/// The handle type used to export semaphores.
+#[cfg(unix)]
+const SEMAPHORE_HANDLE_TYPE: vk::ExternalSemaphoreHandleTypeFlags =
+    vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD;
+#[cfg(windows)]
+const SEMAPHORE_HANDLE_TYPE: vk::ExternalSemaphoreHandleTypeFlags =
+    vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32;
+
+/// The device extensions needed to export memory and semaphores.
+#[cfg(unix)]
+pub(super) const EXTENSIONS: [&std::ffi::CStr; 2] = [
+    khr::external_memory_fd::NAME,
+    khr::external_semaphore_fd::NAME,
+];
+#[cfg(windows)]
+pub(super) const EXTENSIONS: [&std::ffi::CStr; 2] = [
+    khr::external_memory_win32::NAME,
+    khr::external_semaphore_win32::NAME,
+];
+#[cfg(not(any(unix, windows)))]
+pub(super) const EXTENSIONS: [&std::ffi::CStr; 0] = [];
+
+/// The exported memory of a buffer or texture.
+#[derive(Debug)]
+pub struct ExternalMemory {
+    /// A handle of the `OPAQUE_FD` or `OPAQUE_WIN32` type.
+    pub handle: crate::ExternalHandle,
+    /// The size of the allocation, to import it with.
+    ///
+    /// The allocation is dedicated to the resource, and the resource is at
+    /// its start.
+    pub size: u64,
+}
+
+pub(super) struct ExternalFunctions {
+    #[cfg(unix)]
+    memory: khr::external_memory_fd::Device,
+    #[cfg(unix)]
+    semaphore: khr::external_semaphore_fd::Device,
+    #[cfg(windows)]
+    memory: khr::external_memory_win32::Device,
+    #[cfg(windows)]
+    semaphore: khr::external_semaphore_win32::Device,
+}
+
+impl ExternalFunctions {
+    /// Loads the functions, if the [`EXTENSIONS`] are all enabled.
+    #[allow(unused_variables)]
+    pub(super) fn load(
+        instance: &ash::Instance,
+        device: &ash::Device,
+        enabled_extensions: &[&'static std::ffi::CStr],
+    ) -> Option<Self> {
+        if EXTENSIONS.is_empty()
+            || !EXTENSIONS
+                .iter()
+                .all(|extension| enabled_extensions.contains(extension))
+        {
+            return None;
+        }
+        #[cfg(unix)]
+        return Some(Self {
+            memory: khr::external_memory_fd::Device::new(instance, device),
+            semaphore: khr::external_semaphore_fd::Device::new(instance, device),
+        });
+        #[cfg(windows)]
+        return Some(Self {
+            memory: khr::external_memory_win32::Device::new(instance, device),
+            semaphore: khr::external_semaphore_win32::Device::new(instance, device),
+        });
+        #[cfg(not(any(unix, windows)))]
+        None
+    }
+}
+
+/// The `allocator_data` of dedicated allocations made for exportable and
+/// imported resources.
+///
+/// These aren't made by the device's [`MemoryAllocator`](super::MemoryAllocator),
+/// and are freed directly.
+#[derive(Debug)]
+struct DedicatedMemory;
+
+/// The external memory to make a dedicated allocation with.
+pub(super) enum ExternalAllocation {
+    /// New memory, exportable as handles of the given type.
+    Export(vk::ExternalMemoryHandleTypeFlags),
+    /// Memory imported from a handle of the [`MEMORY_HANDLE_TYPE`].
+    Import(crate::ExternalHandle),
+}
+
+impl ExternalAllocation {
+    /// The handle type the resource must be created with.
+    pub(super) fn handle_type(&self) -> vk::ExternalMemoryHandleTypeFlags {
+        match *self {
+            Self::Export(handle_type) => handle_type,
+            Self::Import(_) => MEMORY_HANDLE_TYPE,
+        }
+    }
+}
+
+/// The resource a dedicated allocation is made for.
+pub(super) enum DedicatedResource {
+    Buffer(vk::Buffer),
+    Image(vk::Image),
+}
+
+impl super::Device {
+    /// Returns whether memory and fences can be exported.
+    pub fn supports_export(&self) -> bool {
+        self.shared.extension_fns.external.is_some()
+    }
+
+    /// Returns the handle types to create exportable resources with, if
+    /// exporting is supported.
+    pub(super) fn export_memory_handle_type(&self) -> Option<vk::ExternalMemoryHandleTypeFlags> {
+        self.supports_export().then_some(MEMORY_HANDLE_TYPE)
+    }
+
+    /// Returns whether `other` can import the memory and fences exported by
+    /// this device, and the other way around.
+    ///
+    /// This is the case when both devices support exporting memory and have
+    /// the same `deviceUUID` and `driverUUID`, like devices opened on the same
+    /// physical device.
+    pub fn can_share_memory_with(&self, other: &Self) -> bool {
+        self.supports_export()
+            && other.supports_export()
+            && self.shared.private_caps.timeline_semaphores
+            && other.shared.private_caps.timeline_semaphores
+            && self.shared.external_memory_key.is_some()
+            && self.shared.external_memory_key == other.shared.external_memory_key
+    }
+
+    pub(super) fn export_semaphore_handle_type(
+        &self,
+    ) -> Option<vk::ExternalSemaphoreHandleTypeFlags> {
+        self.supports_export().then_some(SEMAPHORE_HANDLE_TYPE)
+    }
+
+    /// Creates a buffer whose memory can be exported with
+    /// [`export_buffer_memory`](Self::export_buffer_memory).
+    ///
+    /// Fails with [`DeviceError::ResourceCreationFailed`] if exporting isn't
+    /// supported.
+    ///
+    /// # Safety
+    ///
+    /// - `desc.usage` must not contain `MAP_READ` or `MAP_WRITE`.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    pub unsafe fn create_exportable_buffer(
+        &self,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, crate::DeviceError> {
+        debug_assert!(!desc
+            .usage
+            .intersects(crate::BufferUses::MAP_READ | crate::BufferUses::MAP_WRITE));
+        let handle_type = self
+            .export_memory_handle_type()
+            .ok_or(crate::DeviceError::ResourceCreationFailed)?;
+        unsafe { self.create_buffer_impl(desc, Some(ExternalAllocation::Export(handle_type))) }
+    }
+
+    /// Creates a texture whose memory can be exported with
+    /// [`export_texture_memory`](Self::export_texture_memory).
+    ///
+    /// Fails with [`DeviceError::ResourceCreationFailed`] if exporting isn't
+    /// supported.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    pub unsafe fn create_exportable_texture(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        let handle_type = self
+            .export_memory_handle_type()
+            .ok_or(crate::DeviceError::ResourceCreationFailed)?;
+        unsafe { self.create_texture_impl(desc, Some(ExternalAllocation::Export(handle_type))) }
+    }
+
+    /// Creates a buffer in memory exported by
+    /// [`export_buffer_memory`](Self::export_buffer_memory), possibly in
+    /// another process.
+    ///
+    /// The buffer aliases the exported one. Fails with
+    /// [`DeviceError::ResourceCreationFailed`] if exporting isn't supported.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a buffer created with `desc`, by a
+    ///   device with the same `deviceUUID` and `driverUUID` as this one.
+    pub unsafe fn import_buffer_memory(
+        &self,
+        handle: crate::ExternalHandle,
+        desc: &crate::BufferDescriptor,
+    ) -> Result<super::Buffer, crate::DeviceError> {
+        if !self.supports_export() {
+            return Err(crate::DeviceError::ResourceCreationFailed);
+        }
+        unsafe { self.create_buffer_impl(desc, Some(ExternalAllocation::Import(handle))) }
+    }
+
+    /// Creates a texture in memory exported by
+    /// [`export_texture_memory`](Self::export_texture_memory), possibly in
+    /// another process.
+    ///
+    /// The texture aliases the exported one. Fails with
+    /// [`DeviceError::ResourceCreationFailed`] if exporting isn't supported.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a texture created with `desc`, by a
+    ///   device with the same `deviceUUID` and `driverUUID` as this one.
+    pub unsafe fn import_texture_memory(
+        &self,
+        handle: crate::ExternalHandle,
+        desc: &crate::TextureDescriptor,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        if !self.supports_export() {
+            return Err(crate::DeviceError::ResourceCreationFailed);
+        }
+        unsafe { self.create_texture_impl(desc, Some(ExternalAllocation::Import(handle))) }
+    }
+
+    /// Exports the memory of a buffer created by
+    /// [`create_exportable_buffer`](Self::create_exportable_buffer).
+    ///
+    /// Each call returns a new handle. The memory stays alive until both the
+    /// buffer is destroyed and the importers have released it.
+    ///
+    /// # Safety
+    ///
+    /// - `buffer` must be created by `create_exportable_buffer` on this device.
+    pub unsafe fn export_buffer_memory(
+        &self,
+        buffer: &super::Buffer,
+    ) -> Result<ExternalMemory, crate::DeviceError> {
+        let block = buffer.block.as_ref().unwrap().lock();
+        debug_assert!(is_dedicated(&block));
+        unsafe { self.export_memory(block.memory, block.size) }
+    }
+
+    /// Exports the memory of a texture created by
+    /// [`create_exportable_texture`](Self::create_exportable_texture).
+    ///
+    /// Each call returns a new handle. The memory stays alive until both the
+    /// texture is destroyed and the importers have released it.
+    ///
+    /// # Safety
+    ///
+    /// - `texture` must be created by `create_exportable_texture` on this device.
+    pub unsafe fn export_texture_memory(
+        &self,
+        texture: &super::Texture,
+    ) -> Result<ExternalMemory, crate::DeviceError> {
+        let block = texture.block.as_ref().unwrap();
+        debug_assert!(is_dedicated(block));
+        unsafe { self.export_memory(block.memory, block.size) }
+    }
+
+    unsafe fn export_memory(
+        &self,
+        memory: vk::DeviceMemory,
+        size: u64,
+    ) -> Result<ExternalMemory, crate::DeviceError> {
+        let functions = self.shared.extension_fns.external.as_ref().unwrap();
+        #[cfg(unix)]
+        let handle = {
+            use std::os::fd::FromRawFd;
+
+            let info = vk::MemoryGetFdInfoKHR::default()
+                .memory(memory)
+                .handle_type(MEMORY_HANDLE_TYPE);
+            let fd = unsafe { functions.memory.get_memory_fd(&info) }?;
+            crate::ExternalHandle::Fd(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) })
+        };
+        #[cfg(windows)]
+        let handle = {
+            use std::os::windows::io::FromRawHandle;
+
+            let info = vk::MemoryGetWin32HandleInfoKHR::default()
+                .memory(memory)
+                .handle_type(MEMORY_HANDLE_TYPE);
+            let handle = unsafe { functions.memory.get_memory_win32_handle(&info) }?;
+            crate::ExternalHandle::NtHandle(unsafe {
+                std::os::windows::io::OwnedHandle::from_raw_handle(handle)
+            })
+        };
+        Ok(ExternalMemory { handle, size })
+    }
+
+    /// Exports a timeline semaphore fence.
+    ///
+    /// Fences are exportable if [`supports_export`](Self::supports_export)
+    /// returns `true` and the device supports timeline semaphores. Fails with
+    /// [`DeviceError::ResourceCreationFailed`] otherwise.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    pub unsafe fn export_fence(
+        &self,
+        fence: &super::Fence,
+    ) -> Result<crate::ExternalHandle, crate::DeviceError> {
+        let (functions, raw) = match (self.shared.extension_fns.external.as_ref(), fence) {
+            (Some(functions), &super::Fence::TimelineSemaphore(raw)) => (functions, raw),
+            _ => return Err(crate::DeviceError::ResourceCreationFailed),
+        };
+        #[cfg(unix)]
+        let handle = {
+            use std::os::fd::FromRawFd;
+
+            let info = vk::SemaphoreGetFdInfoKHR::default()
+                .semaphore(raw)
+                .handle_type(SEMAPHORE_HANDLE_TYPE);
+            let fd = unsafe { functions.semaphore.get_semaphore_fd(&info) }?;
+            crate::ExternalHandle::Fd(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) })
+        };
+        #[cfg(windows)]
+        let handle = {
+            use std::os::windows::io::FromRawHandle;
+
+            let info = vk::SemaphoreGetWin32HandleInfoKHR::default()
+                .semaphore(raw)
+                .handle_type(SEMAPHORE_HANDLE_TYPE);
+            let handle = unsafe { functions.semaphore.get_semaphore_win32_handle(&info) }?;
+            crate::ExternalHandle::NtHandle(unsafe {
+                std::os::windows::io::OwnedHandle::from_raw_handle(handle)
+            })
+        };
+        Ok(handle)
+    }
+
+    /// Creates a fence from a timeline semaphore exported by
+    /// [`export_fence`](Self::export_fence), possibly in another process.
+    ///
+    /// The fence shares its value with the exported one. Fails with
+    /// [`DeviceError::ResourceCreationFailed`] if fences aren't exportable.
+    ///
+    /// [`DeviceError::ResourceCreationFailed`]: crate::DeviceError::ResourceCreationFailed
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a timeline semaphore of a device with
+    ///   the same `deviceUUID` and `driverUUID` as this one.
+    pub unsafe fn import_fence(
+        &self,
+        handle: crate::ExternalHandle,
+    ) -> Result<super::Fence, crate::DeviceError> {
+        let functions = match self.shared.extension_fns.external.as_ref() {
+            Some(functions) if self.shared.private_caps.timeline_semaphores => functions,
+            _ => return Err(crate::DeviceError::ResourceCreationFailed),
+        };
+        let mut type_info =
+            vk::SemaphoreTypeCreateInfo::default().semaphore_type(vk::SemaphoreType::TIMELINE);
+        let info = vk::SemaphoreCreateInfo::default().push_next(&mut type_info);
+        let raw = unsafe { self.shared.raw.create_semaphore(&info, None) }?;
+
+        #[cfg(unix)]
+        let result = {
+            use std::os::fd::{FromRawFd, IntoRawFd};
+
+            let crate::ExternalHandle::Fd(fd) = handle;
+            let fd = fd.into_raw_fd();
+            let info = vk::ImportSemaphoreFdInfoKHR::default()
+                .semaphore(raw)
+                .handle_type(SEMAPHORE_HANDLE_TYPE)
+                .fd(fd);
+            // The semaphore owns the file descriptor once it's imported.
+            let result = unsafe { functions.semaphore.import_semaphore_fd(&info) };
+            if result.is_err() {
+                drop(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) });
+            }
+            result
+        };
+        #[cfg(windows)]
+        let result = {
+            use std::os::windows::io::AsRawHandle;
+
+            let crate::ExternalHandle::NtHandle(ref handle) = handle;
+            let info = vk::ImportSemaphoreWin32HandleInfoKHR::default()
+                .semaphore(raw)
+                .handle_type(SEMAPHORE_HANDLE_TYPE)
+                .handle(handle.as_raw_handle());
+            // The semaphore doesn't own NT handles, and `handle` is closed
+            // when it's dropped.
+            unsafe { functions.semaphore.import_semaphore_win32_handle(&info) }
+        };
+
+        if let Err(err) = result {
+            unsafe { self.shared.raw.destroy_semaphore(raw, None) };
+            return Err(err.into());
+        }
+        Ok(super::Fence::TimelineSemaphore(raw))
+    }
+
+    /// Makes a dedicated allocation of `external` memory for `resource`.
+    ///
+    /// `resource` must be created with the [`ExternalAllocation::handle_type`]
+    /// of `external`.
+    pub(super) unsafe fn allocate_external(
+        &self,
+        requirements: &vk::MemoryRequirements,
+        external: ExternalAllocation,
+        resource: DedicatedResource,
+    ) -> Result<super::MemoryAllocation, crate::DeviceError> {
+        let (memory_type_index, _) = self
+            .find_memory_type(
+                requirements.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                vk::MemoryPropertyFlags::empty(),
+            )
+            .ok_or(crate::DeviceError::OutOfMemory)?;
+
+        let mut dedicated_info = match resource {
+            DedicatedResource::Buffer(raw) => {
+                vk::MemoryDedicatedAllocateInfo::default().buffer(raw)
+            }
+            DedicatedResource::Image(raw) => vk::MemoryDedicatedAllocateInfo::default().image(raw),
+        };
+        let mut export_info = vk::ExportMemoryAllocateInfo::default();
+        #[cfg(unix)]
+        let mut import_info = vk::ImportMemoryFdInfoKHR::default();
+        #[cfg(windows)]
+        let mut import_info = vk::ImportMemoryWin32HandleInfoKHR::default();
+        let mut info = vk::MemoryAllocateInfo::default()
+            .allocation_size(requirements.size)
+            .memory_type_index(memory_type_index)
+            .push_next(&mut dedicated_info);
+        // The imported handle, which stays open until the memory is allocated.
+        let imported = match external {
+            ExternalAllocation::Export(handle_type) => {
+                export_info = export_info.handle_types(handle_type);
+                info = info.push_next(&mut export_info);
+                None
+            }
+            ExternalAllocation::Import(handle) => {
+                #[cfg(unix)]
+                {
+                    use std::os::fd::AsRawFd;
+
+                    let crate::ExternalHandle::Fd(ref fd) = handle;
+                    import_info = import_info
+                        .handle_type(MEMORY_HANDLE_TYPE)
+                        .fd(fd.as_raw_fd());
+                }
+                #[cfg(windows)]
+                {
+                    use std::os::windows::io::AsRawHandle;
+
+                    let crate::ExternalHandle::NtHandle(ref handle) = handle;
+                    import_info = import_info
+                        .handle_type(MEMORY_HANDLE_TYPE)
+                        .handle(handle.as_raw_handle());
+                }
+                info = info.push_next(&mut import_info);
+                Some(handle)
+            }
+        };
+        let memory = unsafe { self.shared.raw.allocate_memory(&info, None) }?;
+
+        // The memory owns the file descriptor once it's imported, while NT
+        // handles are still owned by `imported`, and closed when it's dropped.
+        #[cfg(unix)]
+        if let Some(crate::ExternalHandle::Fd(fd)) = imported {
+            let _ = std::os::fd::IntoRawFd::into_raw_fd(fd);
+        }
+        #[cfg(windows)]
+        drop(imported);
+
+        Ok(super::MemoryAllocation {
+            memory,
+            offset: 0,
+            size: requirements.size,
+            coherent: false,
+            allocator_data: Box::new(DedicatedMemory),
+        })
+    }
+
+    /// Frees `allocation`, whether it's dedicated or was made by the device's
+    /// memory allocator.
+    pub(super) unsafe fn deallocate(&self, allocation: super::MemoryAllocation) {
+        if is_dedicated(&allocation) {
+            unsafe { self.shared.raw.free_memory(allocation.memory, None) };
+        } else {
+            unsafe { self.mem_allocator.deallocate(&self.shared.raw, allocation) };
+        }
+    }
+}
+
+fn is_dedicated(allocation: &super::MemoryAllocation) -> bool {
+    allocation.allocator_data.is::<DedicatedMemory>()
+}
+
+impl super::Queue {
+    /// Makes the next submission wait until `fence` reaches `value`.
+    ///
+    /// This is how work submitted to the queue waits on another API that
+    /// imported the fence with [`Device::export_fence`](super::Device::export_fence).
+    ///
+    /// # Safety
+    ///
+    /// - `fence` must be a fence of the queue's device, and must not be
+    ///   destroyed before the next submission.
+    /// - The device must support timeline semaphores.
+    pub unsafe fn wait_for_fence(&self, fence: &super::Fence, value: crate::FenceValue) {
+        match *fence {
+            super::Fence::TimelineSemaphore(raw) => self.pending_waits.lock().push((raw, value)),
+            super::Fence::FencePool { .. } => {
+                unreachable!("only timeline semaphores can be waited on")
+            }
+        }
+    }
+}
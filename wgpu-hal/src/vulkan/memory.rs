@@ -0,0 +1,470 @@
+/*!
+Device memory allocation.
+
+Buffers, textures and acceleration structures get their memory from the
+device's [`MemoryAllocator`]. Devices use a [`GpuAllocMemoryAllocator`] unless
+they are opened with another one, see [`Adapter::open_with_memory_allocator`]
+and [`Adapter::device_from_raw_with_memory_allocator`].
+
+To track allocations, or enforce a budget, wrap the allocator that does the
+actual work in a `MemoryAllocator` of your own.
+
+[`Adapter::open_with_memory_allocator`]: super::Adapter::open_with_memory_allocator
+[`Adapter::device_from_raw_with_memory_allocator`]: super::Adapter::device_from_raw_with_memory_allocator
+*/
+
+use std::{
+    any::Any,
+    ffi::CStr,
+    fmt,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ash::{khr, vk};
+use parking_lot::Mutex;
+
+bitflags::bitflags! {
+    /// How the memory requested from a [`MemoryAllocator`] will be used.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct MemoryUsage: u8 {
+        /// The memory is only accessed by the device, and should be fast
+        /// for it.
+        const FAST_DEVICE_ACCESS = 1 << 0;
+        /// The memory is mapped by the host.
+        const HOST_ACCESS = 1 << 1;
+        /// The host reads from the memory.
+        const DOWNLOAD = 1 << 2;
+        /// The host writes to the memory.
+        const UPLOAD = 1 << 3;
+        /// The memory will be freed soon.
+        const TRANSIENT = 1 << 4;
+    }
+}
+
+/// A request for memory to bind a resource to.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryRequest {
+    pub size: u64,
+    /// The offset of the allocation must be a multiple of `align_mask + 1`.
+    pub align_mask: u64,
+    /// Bit `i` is set if memory type `i` may be used.
+    pub memory_types: u32,
+    pub usage: MemoryUsage,
+}
+
+/// Memory allocated by a [`MemoryAllocator`].
+#[derive(Debug)]
+pub struct MemoryAllocation {
+    pub memory: vk::DeviceMemory,
+    /// Offset of the allocation in `memory`.
+    pub offset: u64,
+    pub size: u64,
+    /// Whether `memory` is `HOST_COHERENT`.
+    pub coherent: bool,
+    /// Whatever the allocator needs to keep with the allocation.
+    pub allocator_data: Box<dyn Any + Send + Sync>,
+}
+
+/// Allocates the memory of a device's resources.
+///
+/// # Safety
+///
+/// The `device` passed to each method is the device the allocator was given
+/// to. The methods may be called from several threads at once.
+pub trait MemoryAllocator: fmt::Debug + Send + Sync {
+    /// Allocate memory matching `request`.
+    unsafe fn allocate(
+        &self,
+        device: &ash::Device,
+        request: &MemoryRequest,
+    ) -> Result<MemoryAllocation, crate::DeviceError>;
+
+    /// Free `allocation`, which this allocator allocated.
+    unsafe fn deallocate(&self, device: &ash::Device, allocation: MemoryAllocation);
+
+    /// Map `size` bytes of `allocation`, starting `offset` bytes in.
+    ///
+    /// An allocation is mapped at most once at a time.
+    unsafe fn map(
+        &self,
+        device: &ash::Device,
+        allocation: &mut MemoryAllocation,
+        offset: u64,
+        size: usize,
+    ) -> Result<NonNull<u8>, crate::DeviceError>;
+
+    unsafe fn unmap(&self, device: &ash::Device, allocation: &mut MemoryAllocation);
+
+    /// Release all the memory the allocator holds on to.
+    ///
+    /// This is called once, when the device is destroyed, after every
+    /// allocation has been freed.
+    unsafe fn cleanup(&self, device: &ash::Device);
+
+    /// Statistics of the allocations, if the allocator keeps them.
+    fn report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
+}
+
+/// Block sizes and thresholds of a [`GpuAllocMemoryAllocator`].
+///
+/// See `gpu_alloc::Config` for what each of them does.
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocConfig {
+    pub dedicated_threshold: u64,
+    pub preferred_dedicated_threshold: u64,
+    pub transient_dedicated_threshold: u64,
+    pub starting_free_list_chunk: u64,
+    pub final_free_list_chunk: u64,
+    pub minimal_buddy_size: u64,
+    pub initial_buddy_dedicated_size: u64,
+}
+
+impl Default for GpuAllocConfig {
+    fn default() -> Self {
+        let config = gpu_alloc::Config::i_am_prototyping(); //TODO
+        Self {
+            dedicated_threshold: config.dedicated_threshold,
+            preferred_dedicated_threshold: config.preferred_dedicated_threshold,
+            transient_dedicated_threshold: config.transient_dedicated_threshold,
+            starting_free_list_chunk: config.starting_free_list_chunk,
+            final_free_list_chunk: config.final_free_list_chunk,
+            minimal_buddy_size: config.minimal_buddy_size,
+            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+        }
+    }
+}
+
+impl GpuAllocConfig {
+    /// The default configuration, adjusted to follow `hints`.
+    pub fn from_hints(hints: &wgt::MemoryHints) -> Self {
+        let mut config = Self::default();
+        if let Some(block_size) = hints.block_size {
+            config.starting_free_list_chunk = config.starting_free_list_chunk.min(block_size);
+            config.final_free_list_chunk = block_size;
+            config.initial_buddy_dedicated_size = block_size;
+        }
+        if let Some(threshold) = hints.dedicated_allocation_threshold {
+            config.dedicated_threshold = threshold;
+            config.preferred_dedicated_threshold =
+                config.preferred_dedicated_threshold.min(threshold);
+            config.transient_dedicated_threshold =
+                config.transient_dedicated_threshold.min(threshold);
+        }
+        config
+    }
+}
+
+/// The default [`MemoryAllocator`], using the `gpu-alloc` crate.
+pub struct GpuAllocMemoryAllocator {
+    raw: Mutex<gpu_alloc::GpuAllocator<vk::DeviceMemory>>,
+    allocation_count: AtomicU64,
+    allocated_bytes: AtomicU64,
+    /// The size of each memory block `gpu-alloc` allocated from the device.
+    blocks: Mutex<rustc_hash::FxHashMap<vk::DeviceMemory, u64>>,
+}
+
+impl fmt::Debug for GpuAllocMemoryAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuAllocMemoryAllocator")
+            .finish_non_exhaustive()
+    }
+}
+
+impl GpuAllocMemoryAllocator {
+    /// Create an allocator for a device opened on `adapter` with
+    /// `enabled_extensions`.
+    pub fn new(
+        adapter: &super::Adapter,
+        enabled_extensions: &[&'static CStr],
+        config: GpuAllocConfig,
+    ) -> Self {
+        let mem_properties = {
+            profiling::scope!("vkGetPhysicalDeviceMemoryProperties");
+            unsafe {
+                adapter
+                    .instance
+                    .raw
+                    .get_physical_device_memory_properties(adapter.raw)
+            }
+        };
+        let limits = adapter.phd_capabilities.properties.limits;
+        let max_memory_allocation_size =
+            if let Some(maintenance_3) = adapter.phd_capabilities.maintenance_3 {
+                maintenance_3.max_memory_allocation_size
+            } else {
+                u64::max_value()
+            };
+        let properties = gpu_alloc::DeviceProperties {
+            max_memory_allocation_count: limits.max_memory_allocation_count,
+            max_memory_allocation_size,
+            non_coherent_atom_size: limits.non_coherent_atom_size,
+            memory_types: mem_properties
+                .memory_types_as_slice()
+                .iter()
+                .map(|memory_type| gpu_alloc::MemoryType {
+                    props: gpu_alloc::MemoryPropertyFlags::from_bits_truncate(
+                        memory_type.property_flags.as_raw() as u8,
+                    ),
+                    heap: memory_type.heap_index,
+                })
+                .collect(),
+            memory_heaps: mem_properties
+                .memory_heaps_as_slice()
+                .iter()
+                .map(|&memory_heap| gpu_alloc::MemoryHeap {
+                    size: memory_heap.size,
+                })
+                .collect(),
+            buffer_device_address: enabled_extensions.contains(&khr::buffer_device_address::NAME),
+        };
+        let config = gpu_alloc::Config {
+            dedicated_threshold: config.dedicated_threshold,
+            preferred_dedicated_threshold: config.preferred_dedicated_threshold,
+            transient_dedicated_threshold: config.transient_dedicated_threshold,
+            starting_free_list_chunk: config.starting_free_list_chunk,
+            final_free_list_chunk: config.final_free_list_chunk,
+            minimal_buddy_size: config.minimal_buddy_size,
+            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+        };
+        Self {
+            raw: Mutex::new(gpu_alloc::GpuAllocator::new(config, properties)),
+            allocation_count: AtomicU64::new(0),
+            allocated_bytes: AtomicU64::new(0),
+            blocks: Mutex::new(rustc_hash::FxHashMap::default()),
+        }
+    }
+}
+
+fn map_usage(usage: MemoryUsage) -> gpu_alloc::UsageFlags {
+    let mut flags = gpu_alloc::UsageFlags::empty();
+    flags.set(
+        gpu_alloc::UsageFlags::FAST_DEVICE_ACCESS,
+        usage.contains(MemoryUsage::FAST_DEVICE_ACCESS),
+    );
+    flags.set(
+        gpu_alloc::UsageFlags::HOST_ACCESS,
+        usage.contains(MemoryUsage::HOST_ACCESS),
+    );
+    flags.set(
+        gpu_alloc::UsageFlags::DOWNLOAD,
+        usage.contains(MemoryUsage::DOWNLOAD),
+    );
+    flags.set(
+        gpu_alloc::UsageFlags::UPLOAD,
+        usage.contains(MemoryUsage::UPLOAD),
+    );
+    flags.set(
+        gpu_alloc::UsageFlags::TRANSIENT,
+        usage.contains(MemoryUsage::TRANSIENT),
+    );
+    flags
+}
+
+fn block_mut(allocation: &mut MemoryAllocation) -> &mut gpu_alloc::MemoryBlock<vk::DeviceMemory> {
+    allocation
+        .allocator_data
+        .downcast_mut()
+        .expect("Memory was not allocated by a `GpuAllocMemoryAllocator`")
+}
+
+impl MemoryAllocator for GpuAllocMemoryAllocator {
+    unsafe fn allocate(
+        &self,
+        device: &ash::Device,
+        request: &MemoryRequest,
+    ) -> Result<MemoryAllocation, crate::DeviceError> {
+        let block = unsafe {
+            self.raw.lock().alloc(
+                &AshMemoryDevice(device, &self.blocks),
+                gpu_alloc::Request {
+                    size: request.size,
+                    align_mask: request.align_mask,
+                    usage: map_usage(request.usage),
+                    memory_types: request.memory_types,
+                },
+            )?
+        };
+        self.allocation_count.fetch_add(1, Ordering::Relaxed);
+        self.allocated_bytes
+            .fetch_add(block.size(), Ordering::Relaxed);
+        Ok(MemoryAllocation {
+            memory: *block.memory(),
+            offset: block.offset(),
+            size: block.size(),
+            coherent: block
+                .props()
+                .contains(gpu_alloc::MemoryPropertyFlags::HOST_COHERENT),
+            allocator_data: Box::new(block),
+        })
+    }
+
+    unsafe fn deallocate(&self, device: &ash::Device, allocation: MemoryAllocation) {
+        self.allocation_count.fetch_sub(1, Ordering::Relaxed);
+        self.allocated_bytes
+            .fetch_sub(allocation.size, Ordering::Relaxed);
+        let block = allocation
+            .allocator_data
+            .downcast()
+            .expect("Memory was not allocated by a `GpuAllocMemoryAllocator`");
+        unsafe {
+            self.raw
+                .lock()
+                .dealloc(&AshMemoryDevice(device, &self.blocks), *block)
+        };
+    }
+
+    unsafe fn map(
+        &self,
+        device: &ash::Device,
+        allocation: &mut MemoryAllocation,
+        offset: u64,
+        size: usize,
+    ) -> Result<NonNull<u8>, crate::DeviceError> {
+        Ok(unsafe {
+            block_mut(allocation).map(&AshMemoryDevice(device, &self.blocks), offset, size)?
+        })
+    }
+
+    unsafe fn unmap(&self, device: &ash::Device, allocation: &mut MemoryAllocation) {
+        unsafe { block_mut(allocation).unmap(&AshMemoryDevice(device, &self.blocks)) };
+    }
+
+    unsafe fn cleanup(&self, device: &ash::Device) {
+        unsafe {
+            self.raw
+                .lock()
+                .cleanup(&AshMemoryDevice(device, &self.blocks))
+        };
+    }
+
+    fn report(&self) -> Option<wgt::AllocatorReport> {
+        let blocks = self.blocks.lock();
+        Some(wgt::AllocatorReport {
+            allocation_count: self.allocation_count.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+            block_count: blocks.len() as u64,
+            reserved_bytes: blocks.values().sum(),
+        })
+    }
+}
+
+/// The device `gpu-alloc` allocates memory from, and the sizes of the blocks
+/// it allocated.
+struct AshMemoryDevice<'a>(
+    &'a ash::Device,
+    &'a Mutex<rustc_hash::FxHashMap<vk::DeviceMemory, u64>>,
+);
+
+impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for AshMemoryDevice<'_> {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: gpu_alloc::AllocationFlags,
+    ) -> Result<vk::DeviceMemory, gpu_alloc::OutOfMemory> {
+        let mut info = vk::MemoryAllocateInfo::default()
+            .allocation_size(size)
+            .memory_type_index(memory_type);
+
+        let mut info_flags;
+
+        if flags.contains(gpu_alloc::AllocationFlags::DEVICE_ADDRESS) {
+            info_flags = vk::MemoryAllocateFlagsInfo::default()
+                .flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
+            info = info.push_next(&mut info_flags);
+        }
+
+        match unsafe { self.0.allocate_memory(&info, None) } {
+            Ok(memory) => {
+                self.1.lock().insert(memory, size);
+                Ok(memory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(gpu_alloc::OutOfMemory::OutOfDeviceMemory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
+                Err(gpu_alloc::OutOfMemory::OutOfHostMemory)
+            }
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
+            Err(err) => panic!("Unexpected Vulkan error: `{err}`"),
+        }
+    }
+
+    unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
+        self.1.lock().remove(&memory);
+        unsafe { self.0.free_memory(memory, None) };
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut vk::DeviceMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, gpu_alloc::DeviceMapError> {
+        match unsafe {
+            self.0
+                .map_memory(*memory, offset, size, vk::MemoryMapFlags::empty())
+        } {
+            Ok(ptr) => {
+                Ok(NonNull::new(ptr as *mut u8)
+                    .expect("Pointer to memory mapping must not be null"))
+            }
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                Err(gpu_alloc::DeviceMapError::OutOfDeviceMemory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
+                Err(gpu_alloc::DeviceMapError::OutOfHostMemory)
+            }
+            Err(vk::Result::ERROR_MEMORY_MAP_FAILED) => Err(gpu_alloc::DeviceMapError::MapFailed),
+            Err(err) => panic!("Unexpected Vulkan error: `{err}`"),
+        }
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut vk::DeviceMemory) {
+        unsafe { self.0.unmap_memory(*memory) };
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[gpu_alloc::MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), gpu_alloc::OutOfMemory> {
+        // should never be called
+        unimplemented!()
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        _ranges: &[gpu_alloc::MappedMemoryRange<'_, vk::DeviceMemory>],
+    ) -> Result<(), gpu_alloc::OutOfMemory> {
+        // should never be called
+        unimplemented!()
+    }
+}
+
+impl From<gpu_alloc::AllocationError> for crate::DeviceError {
+    fn from(error: gpu_alloc::AllocationError) -> Self {
+        use gpu_alloc::AllocationError as Ae;
+        match error {
+            Ae::OutOfDeviceMemory | Ae::OutOfHostMemory => Self::OutOfMemory,
+            _ => {
+                log::error!("memory allocation: {:?}", error);
+                Self::Lost
+            }
+        }
+    }
+}
+impl From<gpu_alloc::MapError> for crate::DeviceError {
+    fn from(error: gpu_alloc::MapError) -> Self {
+        use gpu_alloc::MapError as Me;
+        match error {
+            Me::OutOfDeviceMemory | Me::OutOfHostMemory => Self::OutOfMemory,
+            _ => {
+                log::error!("memory mapping: {:?}", error);
+                Self::Lost
+            }
+        }
+    }
+}
@@ -11,10 +11,12 @@ We cope with this requirement by the combination of the following ways:
 
 ## Framebuffers and Render passes
 
-Render passes are cached on the device and kept forever.
+Render passes and framebuffers are cached on the device, up to the
+`wgt::DeviceCacheLimits`. The least recently used ones are evicted past those,
+or all of them when the caches are trimmed. See the `cache` module.
 
-Framebuffers are also cached on the device, but they are removed when
-any of the image views (they have) gets removed.
+Framebuffers are also removed when any of the image views (they have)
+gets removed.
 If Vulkan supports image-less framebuffers,
 then the actual views are excluded from the framebuffer key.
 
@@ -26,10 +28,14 @@ Otherwise, we manage a pool of `VkFence` objects behind each `hal::Fence`.
 !*/
 
 mod adapter;
+mod cache;
 mod command;
 mod conv;
 mod device;
+mod external;
 mod instance;
+mod memory;
+mod video;
 
 use std::{
     borrow::Borrow,
@@ -43,9 +49,20 @@ use std::{
 use arrayvec::ArrayVec;
 use ash::{ext, khr, vk};
 use parking_lot::{Mutex, RwLock};
+use smallvec::SmallVec;
+
+pub use external::ExternalMemory;
+pub use memory::{
+    GpuAllocConfig, GpuAllocMemoryAllocator, MemoryAllocation, MemoryAllocator, MemoryRequest,
+    MemoryUsage,
+};
+pub use video::VideoEncoder;
 
 const MILLIS_TO_NANOS: u64 = 1_000_000;
 const MAX_TOTAL_ATTACHMENTS: usize = crate::MAX_COLOR_ATTACHMENTS * 2 + 1;
+/// Number of semaphores a submission can wait on or signal without
+/// allocating.
+const SUBMIT_SEMAPHORES: usize = 4;
 
 #[derive(Clone, Debug)]
 pub struct Api;
@@ -59,6 +76,7 @@ impl crate::Api for Api {
     type Queue = Queue;
     type CommandEncoder = CommandEncoder;
     type CommandBuffer = CommandBuffer;
+    type RenderBundle = RenderBundle;
 
     type Buffer = Buffer;
     type Texture = Texture;
@@ -75,6 +93,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 struct DebugUtils {
@@ -316,7 +335,14 @@ struct DeviceExtensionFunctions {
     debug_utils: Option<ext::debug_utils::Device>,
     draw_indirect_count: Option<khr::draw_indirect_count::Device>,
     timeline_semaphore: Option<ExtensionFn<khr::timeline_semaphore::Device>>,
+    /// Set if `synchronization2` is enabled, in which case all barriers are
+    /// recorded with `vkCmdPipelineBarrier2`.
+    synchronization2: Option<ExtensionFn<khr::synchronization2::Device>>,
     ray_tracing: Option<RayTracingDeviceExtensionFunctions>,
+    /// Set if memory and semaphores can be exported, see the [`external`] module.
+    external: Option<external::ExternalFunctions>,
+    /// Set if the device has a video encode queue, see the [`video`] module.
+    video: Option<video::VideoFunctions>,
 }
 
 struct RayTracingDeviceExtensionFunctions {
@@ -348,6 +374,7 @@ struct PrivateCapabilities {
     zero_initialize_workgroup_memory: bool,
     image_format_list: bool,
     subgroup_size_control: bool,
+    synchronization2: bool,
 }
 
 bitflags::bitflags!(
@@ -451,19 +478,26 @@ struct DeviceShared {
     extension_fns: DeviceExtensionFunctions,
     vendor_id: u32,
     timestamp_period: f32,
+    /// The `pipelineCacheUUID` of the physical device.
+    pipeline_cache_validation_key: [u8; 16],
+    /// The `deviceUUID` and `driverUUID` of the physical device, which must
+    /// match for devices to share external memory and semaphores.
+    external_memory_key: Option<([u8; 16], [u8; 16])>,
     private_caps: PrivateCapabilities,
     workarounds: Workarounds,
     features: wgt::Features,
-    render_passes: Mutex<rustc_hash::FxHashMap<RenderPassKey, vk::RenderPass>>,
-    framebuffers: Mutex<rustc_hash::FxHashMap<FramebufferKey, vk::Framebuffer>>,
+    render_passes: cache::ObjectCache<RenderPassKey, vk::RenderPass>,
+    framebuffers: cache::ObjectCache<FramebufferKey, vk::Framebuffer>,
 }
 
 pub struct Device {
     shared: Arc<DeviceShared>,
-    mem_allocator: Mutex<gpu_alloc::GpuAllocator<vk::DeviceMemory>>,
+    mem_allocator: Box<dyn MemoryAllocator>,
     desc_allocator:
         Mutex<gpu_descriptor::DescriptorAllocator<vk::DescriptorPool, vk::DescriptorSet>>,
     valid_ash_memory_types: u32,
+    /// See [`wgt::MemoryHints::prefer_device_local_host_visible`].
+    prefer_device_local_host_visible: bool,
     naga_options: naga::back::spv::Options<'static>,
     #[cfg(feature = "renderdoc")]
     render_doc: crate::auxil::renderdoc::RenderDoc,
@@ -533,26 +567,29 @@ pub struct Queue {
     device: Arc<DeviceShared>,
     family_index: u32,
     relay_semaphores: Mutex<RelaySemaphores>,
+    /// Timeline semaphores and values the next submission waits on, see
+    /// [`Queue::wait_for_fence`].
+    pending_waits: Mutex<Vec<(vk::Semaphore, crate::FenceValue)>>,
 }
 
 #[derive(Debug)]
 pub struct Buffer {
     raw: vk::Buffer,
-    block: Option<Mutex<gpu_alloc::MemoryBlock<vk::DeviceMemory>>>,
+    block: Option<Mutex<MemoryAllocation>>,
 }
 
 #[derive(Debug)]
 pub struct AccelerationStructure {
     raw: vk::AccelerationStructureKHR,
     buffer: vk::Buffer,
-    block: Mutex<gpu_alloc::MemoryBlock<vk::DeviceMemory>>,
+    block: Mutex<MemoryAllocation>,
 }
 
 #[derive(Debug)]
 pub struct Texture {
     raw: vk::Image,
     drop_guard: Option<crate::DropGuard>,
-    block: Option<gpu_alloc::MemoryBlock<vk::DeviceMemory>>,
+    block: Option<MemoryAllocation>,
     usage: crate::TextureUses,
     format: wgt::TextureFormat,
     raw_flags: vk::ImageCreateFlags,
@@ -597,6 +634,23 @@ pub struct BindGroupLayout {
     types: Box<[(vk::DescriptorType, u32)]>,
     /// Map of binding index to size,
     binding_arrays: Vec<(u32, NonZeroU32)>,
+    free_sets: Arc<Mutex<FreeDescriptorSets>>,
+}
+
+/// Descriptor sets allocated for a [`BindGroupLayout`] that no bind group
+/// uses.
+///
+/// Sets are allocated in batches, and destroyed bind groups return their
+/// set here, so that creating a bind group usually only pops a set.
+#[derive(Debug, Default)]
+struct FreeDescriptorSets {
+    sets: Vec<gpu_descriptor::DescriptorSet<vk::DescriptorSet>>,
+    /// How many sets have been allocated for the layout, which sizes the
+    /// next batch.
+    allocated: u32,
+    /// Whether the layout was destroyed, in which case sets of the bind
+    /// groups that are still alive go back to the device's allocator.
+    layout_destroyed: bool,
 }
 
 #[derive(Debug)]
@@ -608,12 +662,39 @@ pub struct PipelineLayout {
 #[derive(Debug)]
 pub struct BindGroup {
     set: gpu_descriptor::DescriptorSet<vk::DescriptorSet>,
+    /// The free sets of the bind group's layout, to return `set` to.
+    free_sets: Arc<Mutex<FreeDescriptorSets>>,
+}
+
+/// Barriers accumulated by a [`CommandEncoder`], each with its own stage
+/// masks.
+#[derive(Default)]
+struct PendingBarriers {
+    memory: Vec<vk::MemoryBarrier2<'static>>,
+    buffer: Vec<vk::BufferMemoryBarrier2<'static>>,
+    image: Vec<vk::ImageMemoryBarrier2<'static>>,
+}
+
+impl PendingBarriers {
+    fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.buffer.is_empty() && self.image.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.memory.clear();
+        self.buffer.clear();
+        self.image.clear();
+    }
 }
 
 /// Miscellaneous allocation recycling pool for `CommandAllocator`.
 #[derive(Default)]
 struct Temp {
     marker: Vec<u8>,
+    /// Barriers that haven't been recorded yet, see
+    /// [`CommandEncoder::flush_barriers`].
+    barriers: PendingBarriers,
+    /// Space for converting `barriers` when `synchronization2` isn't enabled.
     buffer_barriers: Vec<vk::BufferMemoryBarrier<'static>>,
     image_barriers: Vec<vk::ImageMemoryBarrier<'static>>,
 }
@@ -621,6 +702,7 @@ struct Temp {
 impl Temp {
     fn clear(&mut self) {
         self.marker.clear();
+        self.barriers.clear();
         self.buffer_barriers.clear();
         self.image_barriers.clear();
         //see also - https://github.com/NotIntMan/inplace_it/issues/8
@@ -662,6 +744,12 @@ pub struct CommandEncoder {
     /// These could be in any Vulkan state except "pending".
     discarded: Vec<vk::CommandBuffer>,
 
+    /// A pool of available secondary command buffers, to record render
+    /// bundles in.
+    ///
+    /// These are all in the Vulkan "initial" state.
+    secondary_free: Vec<vk::CommandBuffer>,
+
     /// If this is true, the active renderpass enabled a debug span,
     /// and needs to be disabled on renderpass close.
     rpass_debug_marker_active: bool,
@@ -669,6 +757,10 @@ pub struct CommandEncoder {
     /// If set, the end of the next render/compute pass will write a timestamp at
     /// the given pool & location.
     end_of_pass_timer_query: Option<(vk::QueryPool, u32)>,
+
+    /// The cached render passes and framebuffers used by the active command
+    /// buffer, which must not be destroyed until it is reset.
+    cache_users: Vec<cache::CacheUser>,
 }
 
 impl CommandEncoder {
@@ -691,6 +783,15 @@ impl fmt::Debug for CommandEncoder {
 #[derive(Debug)]
 pub struct CommandBuffer {
     raw: vk::CommandBuffer,
+    cache_users: Vec<cache::CacheUser>,
+}
+
+/// A render bundle, recorded in a secondary command buffer.
+#[derive(Debug)]
+pub struct RenderBundle {
+    raw: vk::CommandBuffer,
+    /// The render pass the bundle was recorded for.
+    cache_users: Vec<cache::CacheUser>,
 }
 
 #[derive(Debug)]
@@ -713,6 +814,11 @@ pub struct ComputePipeline {
     raw: vk::Pipeline,
 }
 
+#[derive(Debug)]
+pub struct PipelineCache {
+    raw: vk::PipelineCache,
+}
+
 #[derive(Debug)]
 pub struct QuerySet {
     raw: vk::QueryPool,
@@ -863,10 +969,15 @@ impl crate::Queue for Queue {
     ) -> Result<(), crate::DeviceError> {
         let mut fence_raw = vk::Fence::null();
 
-        let mut wait_stage_masks = Vec::new();
-        let mut wait_semaphores = Vec::new();
-        let mut signal_semaphores = Vec::new();
-        let mut signal_values = Vec::new();
+        // Most submissions wait on at most the relay semaphore and one
+        // acquired surface image, and signal at most the relay and fence
+        // semaphores and one surface image.
+        let mut wait_stage_masks = SmallVec::<[vk::PipelineStageFlags; SUBMIT_SEMAPHORES]>::new();
+        let mut wait_semaphores = SmallVec::<[vk::Semaphore; SUBMIT_SEMAPHORES]>::new();
+        // Only timeline semaphores use their value, the others use 0.
+        let mut wait_values = SmallVec::<[u64; SUBMIT_SEMAPHORES]>::new();
+        let mut signal_semaphores = SmallVec::<[vk::Semaphore; SUBMIT_SEMAPHORES]>::new();
+        let mut signal_values = SmallVec::<[u64; SUBMIT_SEMAPHORES]>::new();
 
         // Double check that the same swapchain image isn't being given to us multiple times,
         // as that will deadlock when we try to lock them all.
@@ -886,7 +997,7 @@ impl crate::Queue for Queue {
         let locked_swapchain_semaphores = surface_textures
             .iter()
             .map(|st| st.surface_semaphores.lock())
-            .collect::<Vec<_>>();
+            .collect::<SmallVec<[_; 2]>>();
 
         for mut swapchain_semaphore in locked_swapchain_semaphores {
             swapchain_semaphore.set_used_fence_value(signal_value);
@@ -897,6 +1008,7 @@ impl crate::Queue for Queue {
             if let Some(sem) = swapchain_semaphore.get_acquire_wait_semaphore() {
                 wait_stage_masks.push(vk::PipelineStageFlags::TOP_OF_PIPE);
                 wait_semaphores.push(sem);
+                wait_values.push(0);
             }
 
             // Get the signal semaphore for this surface image and add it to the signal list.
@@ -913,6 +1025,14 @@ impl crate::Queue for Queue {
         if let Some(sem) = semaphore_state.wait {
             wait_stage_masks.push(vk::PipelineStageFlags::TOP_OF_PIPE);
             wait_semaphores.push(sem);
+            wait_values.push(0);
+        }
+
+        // Wait on the fences given to `wait_for_fence`.
+        for (sem, value) in self.pending_waits.lock().drain(..) {
+            wait_stage_masks.push(vk::PipelineStageFlags::ALL_COMMANDS);
+            wait_semaphores.push(sem);
+            wait_values.push(value);
         }
 
         signal_semaphores.push(semaphore_state.signal);
@@ -946,7 +1066,7 @@ impl crate::Queue for Queue {
         let vk_cmd_buffers = command_buffers
             .iter()
             .map(|cmd| cmd.raw)
-            .collect::<Vec<_>>();
+            .collect::<SmallVec<[_; 8]>>();
 
         let mut vk_info = vk::SubmitInfo::default().command_buffers(&vk_cmd_buffers);
 
@@ -958,8 +1078,9 @@ impl crate::Queue for Queue {
         let mut vk_timeline_info;
 
         if self.device.private_caps.timeline_semaphores {
-            vk_timeline_info =
-                vk::TimelineSemaphoreSubmitInfo::default().signal_semaphore_values(&signal_values);
+            vk_timeline_info = vk::TimelineSemaphoreSubmitInfo::default()
+                .wait_semaphore_values(&wait_values)
+                .signal_semaphore_values(&signal_values);
             vk_info = vk_info.push_next(&mut vk_timeline_info);
         }
 
@@ -0,0 +1,1304 @@
+/*!
+Video encoding with Vulkan Video, see the [`video`](crate::video) module.
+
+When the device supports `VK_KHR_video_encode_h264` and has a queue family
+with video encode support other than the one wgpu-hal uses, and the device is
+opened with [`Adapter::open`], a queue of that family is created for the
+[`VideoEncoder`]s of the device. Devices created outside of wgpu-hal are given
+that family with [`Adapter::device_from_raw_with_video_encode`].
+
+Encoders take frames in an NV12 texture created by
+[`VideoEncoder::create_input_texture`], which wgpu can copy frames into once it
+is wrapped by `wgpu::Device::create_texture_from_hal`. The texture is shared
+between the queues, so no ownership transfers are needed.
+
+Frames are encoded as I or P frames, each P frame referencing the previous
+frame, and the bitstream is read back once encoding completes.
+
+[`Adapter::open`]: crate::Adapter::open
+[`Adapter::device_from_raw_with_video_encode`]: super::Adapter::device_from_raw_with_video_encode
+*/
+
+use std::{ffi::CStr, mem, ptr, slice};
+
+use arrayvec::ArrayVec;
+use ash::{khr, vk, vk::native};
+use parking_lot::Mutex;
+
+use crate::video::{Codec, EncodedFrame, EncoderDescriptor, EncoderError, RateControl};
+
+/// The device extensions needed to encode video.
+pub(super) const EXTENSIONS: [&CStr; 3] = [
+    khr::video_queue::NAME,
+    khr::video_encode_queue::NAME,
+    khr::video_encode_h264::NAME,
+];
+
+/// The format of input textures and reconstructed pictures.
+const PICTURE_FORMAT: vk::Format = vk::Format::G8_B8R8_2PLANE_420_UNORM;
+
+/// The number of DPB slots: one for the frame being encoded, and one for the
+/// frame it references.
+const DPB_SLOTS: u32 = 2;
+
+/// The `log2_max_frame_num_minus4` of the SPS, wrapping `frame_num` at 256.
+const LOG2_MAX_FRAME_NUM_MINUS4: u8 = 4;
+
+/// `STD_VIDEO_H264_NO_REFERENCE_PICTURE`, for unused reference list entries.
+const NO_REFERENCE_PICTURE: u8 = 0xff;
+
+pub(super) struct VideoFunctions {
+    instance: khr::video_queue::InstanceFn,
+    queue: khr::video_queue::DeviceFn,
+    encode: khr::video_encode_queue::DeviceFn,
+    family_index: u32,
+    /// The encode queue, shared by the device's encoders.
+    raw_queue: Mutex<vk::Queue>,
+}
+
+impl VideoFunctions {
+    /// # Safety
+    ///
+    /// - `device` must be created with the [`EXTENSIONS`], and a queue of
+    ///   `family_index`.
+    pub(super) unsafe fn load(
+        entry: &ash::Entry,
+        instance: &ash::Instance,
+        device: &ash::Device,
+        family_index: u32,
+    ) -> Self {
+        let load_instance = |name: &CStr| unsafe {
+            mem::transmute(entry.get_instance_proc_addr(instance.handle(), name.as_ptr()))
+        };
+        let load_device = |name: &CStr| unsafe {
+            mem::transmute(instance.get_device_proc_addr(device.handle(), name.as_ptr()))
+        };
+        Self {
+            instance: khr::video_queue::InstanceFn::load(load_instance),
+            queue: khr::video_queue::DeviceFn::load(load_device),
+            encode: khr::video_encode_queue::DeviceFn::load(load_device),
+            family_index,
+            raw_queue: Mutex::new(unsafe { device.get_device_queue(family_index, 0) }),
+        }
+    }
+}
+
+impl From<vk::Result> for EncoderError {
+    fn from(result: vk::Result) -> Self {
+        Self::Device(result.into())
+    }
+}
+
+/// Returns the video profile of encoders, whose codec-specific part is `h264`.
+fn profile_info(
+    h264: &mut vk::VideoEncodeH264ProfileInfoKHR<'static>,
+) -> vk::VideoProfileInfoKHR<'_> {
+    vk::VideoProfileInfoKHR::default()
+        .video_codec_operation(vk::VideoCodecOperationFlagsKHR::ENCODE_H264)
+        .chroma_subsampling(vk::VideoChromaSubsamplingFlagsKHR::TYPE_420)
+        .luma_bit_depth(vk::VideoComponentBitDepthFlagsKHR::TYPE_8)
+        .chroma_bit_depth(vk::VideoComponentBitDepthFlagsKHR::TYPE_8)
+        .push_next(h264)
+}
+
+fn h264_profile_info() -> vk::VideoEncodeH264ProfileInfoKHR<'static> {
+    vk::VideoEncodeH264ProfileInfoKHR::default()
+        .std_profile_idc(native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_MAIN)
+}
+
+fn rate_control_mode(rate_control: RateControl) -> vk::VideoEncodeRateControlModeFlagsKHR {
+    match rate_control {
+        RateControl::ConstantQp { .. } => vk::VideoEncodeRateControlModeFlagsKHR::DISABLED,
+        RateControl::ConstantBitrate { .. } => vk::VideoEncodeRateControlModeFlagsKHR::CBR,
+        RateControl::VariableBitrate { .. } => vk::VideoEncodeRateControlModeFlagsKHR::VBR,
+    }
+}
+
+fn align(value: u32, alignment: u32) -> u32 {
+    value.div_ceil(alignment) * alignment
+}
+
+/// The previous frame, which the next P frame references.
+#[derive(Clone, Copy, Debug)]
+struct Reference {
+    slot: u32,
+    frame_num: u32,
+    picture_order_count: i32,
+    keyframe: bool,
+}
+
+impl Reference {
+    fn std_info(&self) -> native::StdVideoEncodeH264ReferenceInfo {
+        let mut info: native::StdVideoEncodeH264ReferenceInfo = unsafe { mem::zeroed() };
+        info.primary_pic_type = if self.keyframe {
+            native::StdVideoH264PictureType_STD_VIDEO_H264_PICTURE_TYPE_IDR
+        } else {
+            native::StdVideoH264PictureType_STD_VIDEO_H264_PICTURE_TYPE_P
+        };
+        info.FrameNum = self.frame_num;
+        info.PicOrderCnt = self.picture_order_count;
+        info
+    }
+}
+
+/// A Vulkan Video encoder, created by [`Device::create_video_encoder`].
+///
+/// [`Device::create_video_encoder`]: super::Device::create_video_encoder
+#[derive(Debug)]
+pub struct VideoEncoder {
+    session: vk::VideoSessionKHR,
+    session_memory: Vec<vk::DeviceMemory>,
+    parameters: vk::VideoSessionParametersKHR,
+    /// The SPS and PPS, written before each keyframe.
+    parameter_sets: Vec<u8>,
+    /// The reconstructed pictures, one per layer.
+    dpb_image: vk::Image,
+    dpb_memory: vk::DeviceMemory,
+    dpb_view: vk::ImageView,
+    bitstream: vk::Buffer,
+    bitstream_memory: vk::DeviceMemory,
+    bitstream_size: u64,
+    bitstream_coherent: bool,
+    query_pool: vk::QueryPool,
+    command_pool: vk::CommandPool,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+    /// The size of input textures and reconstructed pictures, aligned to
+    /// macroblocks.
+    coded_extent: vk::Extent2D,
+    rate_control: RateControl,
+    frame_rate: (u32, u32),
+    keyframe_interval: u32,
+    /// Whether the first frame reset the session.
+    reset: bool,
+    frames_since_keyframe: u32,
+    frame_num: u32,
+    idr_pic_id: u16,
+    reference: Option<Reference>,
+}
+
+impl super::Device {
+    /// Returns whether the device can encode video.
+    pub fn supports_video_encoding(&self) -> bool {
+        self.shared.extension_fns.video.is_some()
+    }
+
+    /// Creates a video encoder.
+    ///
+    /// Fails with [`EncoderError::NotSupported`] if the device can't encode
+    /// video, or with [`EncoderError::UnsupportedDescriptor`] if it can't with
+    /// the given parameters.
+    pub unsafe fn create_video_encoder(
+        &self,
+        desc: &EncoderDescriptor,
+    ) -> Result<VideoEncoder, EncoderError> {
+        let fns = self
+            .shared
+            .extension_fns
+            .video
+            .as_ref()
+            .ok_or(EncoderError::NotSupported)?;
+        match desc.codec {
+            Codec::H264 => {}
+        }
+        if desc.width == 0 || desc.height == 0 || desc.width % 2 != 0 || desc.height % 2 != 0 {
+            return Err(EncoderError::UnsupportedDescriptor(
+                "the frame size must be even and not zero",
+            ));
+        }
+        if desc.frame_rate.0 == 0 || desc.frame_rate.1 == 0 {
+            return Err(EncoderError::UnsupportedDescriptor(
+                "the frame rate must not be zero",
+            ));
+        }
+        if desc.keyframe_interval == 0 {
+            return Err(EncoderError::UnsupportedDescriptor(
+                "the keyframe interval must not be zero",
+            ));
+        }
+
+        let mut h264_profile = h264_profile_info();
+        let profile = profile_info(&mut h264_profile);
+
+        let mut h264_caps = vk::VideoEncodeH264CapabilitiesKHR::default();
+        let mut encode_caps = vk::VideoEncodeCapabilitiesKHR::default();
+        let mut caps = vk::VideoCapabilitiesKHR::default()
+            .push_next(&mut encode_caps)
+            .push_next(&mut h264_caps);
+        match unsafe {
+            (fns.instance.get_physical_device_video_capabilities_khr)(
+                self.shared.physical_device,
+                &profile,
+                &mut caps,
+            )
+        } {
+            vk::Result::SUCCESS => {}
+            vk::Result::ERROR_VIDEO_PROFILE_OPERATION_NOT_SUPPORTED_KHR
+            | vk::Result::ERROR_VIDEO_PROFILE_FORMAT_NOT_SUPPORTED_KHR
+            | vk::Result::ERROR_VIDEO_PROFILE_CODEC_NOT_SUPPORTED_KHR
+            | vk::Result::ERROR_VIDEO_PICTURE_LAYOUT_NOT_SUPPORTED_KHR => {
+                return Err(EncoderError::NotSupported)
+            }
+            other => return Err(other.into()),
+        }
+        let granularity = caps.picture_access_granularity;
+        let min_coded_extent = caps.min_coded_extent;
+        let max_coded_extent = caps.max_coded_extent;
+        let max_dpb_slots = caps.max_dpb_slots;
+        let max_active_reference_pictures = caps.max_active_reference_pictures;
+        let std_header_version = caps.std_header_version;
+        let min_bitstream_size_alignment = caps.min_bitstream_buffer_size_alignment;
+
+        if max_dpb_slots < DPB_SLOTS || max_active_reference_pictures < 1 {
+            return Err(EncoderError::NotSupported);
+        }
+
+        let coded_extent = vk::Extent2D {
+            width: align(align(desc.width, 16), granularity.width.max(1)),
+            height: align(align(desc.height, 16), granularity.height.max(1)),
+        };
+        if coded_extent.width < min_coded_extent.width
+            || coded_extent.height < min_coded_extent.height
+            || coded_extent.width > max_coded_extent.width
+            || coded_extent.height > max_coded_extent.height
+        {
+            return Err(EncoderError::UnsupportedDescriptor(
+                "the frame size is out of the range of the device",
+            ));
+        }
+
+        match desc.rate_control {
+            RateControl::ConstantQp { qp } => {
+                if i32::from(qp) < h264_caps.min_qp || i32::from(qp) > h264_caps.max_qp {
+                    return Err(EncoderError::UnsupportedDescriptor(
+                        "the QP is out of the range of the device",
+                    ));
+                }
+            }
+            RateControl::ConstantBitrate {
+                bitrate: max_bitrate,
+            }
+            | RateControl::VariableBitrate { max_bitrate, .. } => {
+                if max_bitrate > encode_caps.max_bitrate {
+                    return Err(EncoderError::UnsupportedDescriptor(
+                        "the bitrate is higher than the device's maximum",
+                    ));
+                }
+            }
+        }
+        if let RateControl::VariableBitrate {
+            average_bitrate,
+            max_bitrate,
+        } = desc.rate_control
+        {
+            if average_bitrate > max_bitrate {
+                return Err(EncoderError::UnsupportedDescriptor(
+                    "the average bitrate is higher than the maximum bitrate",
+                ));
+            }
+        }
+        if !encode_caps
+            .rate_control_modes
+            .contains(rate_control_mode(desc.rate_control))
+        {
+            return Err(EncoderError::UnsupportedDescriptor(
+                "the rate control mode isn't supported by the device",
+            ));
+        }
+        let feedback_flags = vk::VideoEncodeFeedbackFlagsKHR::BITSTREAM_BUFFER_OFFSET
+            | vk::VideoEncodeFeedbackFlagsKHR::BITSTREAM_BYTES_WRITTEN;
+        if !encode_caps
+            .supported_encode_feedback_flags
+            .contains(feedback_flags)
+        {
+            return Err(EncoderError::NotSupported);
+        }
+        let level_idc = h264_caps.max_level_idc;
+
+        for usage in [
+            vk::ImageUsageFlags::VIDEO_ENCODE_SRC_KHR,
+            vk::ImageUsageFlags::VIDEO_ENCODE_DPB_KHR,
+        ] {
+            if !unsafe { self.supports_picture_format(fns, &profile, usage) }? {
+                return Err(EncoderError::NotSupported);
+            }
+        }
+
+        let mut encoder = VideoEncoder {
+            session: vk::VideoSessionKHR::null(),
+            session_memory: Vec::new(),
+            parameters: vk::VideoSessionParametersKHR::null(),
+            parameter_sets: Vec::new(),
+            dpb_image: vk::Image::null(),
+            dpb_memory: vk::DeviceMemory::null(),
+            dpb_view: vk::ImageView::null(),
+            bitstream: vk::Buffer::null(),
+            bitstream_memory: vk::DeviceMemory::null(),
+            bitstream_size: 0,
+            bitstream_coherent: false,
+            query_pool: vk::QueryPool::null(),
+            command_pool: vk::CommandPool::null(),
+            command_buffer: vk::CommandBuffer::null(),
+            fence: vk::Fence::null(),
+            coded_extent,
+            rate_control: desc.rate_control,
+            frame_rate: desc.frame_rate,
+            keyframe_interval: desc.keyframe_interval,
+            reset: false,
+            frames_since_keyframe: 0,
+            frame_num: 0,
+            idr_pic_id: 0,
+            reference: None,
+        };
+
+        let session_info = vk::VideoSessionCreateInfoKHR::default()
+            .queue_family_index(fns.family_index)
+            .video_profile(&profile)
+            .picture_format(PICTURE_FORMAT)
+            .max_coded_extent(coded_extent)
+            .reference_picture_format(PICTURE_FORMAT)
+            .max_dpb_slots(DPB_SLOTS)
+            .max_active_reference_pictures(1)
+            .std_header_version(&std_header_version);
+        let sps = sps(desc, coded_extent, level_idc);
+        let pps = pps();
+        let result = unsafe {
+            self.init_video_encoder(
+                fns,
+                &mut encoder,
+                &session_info,
+                &sps,
+                &pps,
+                profile,
+                min_bitstream_size_alignment,
+            )
+        };
+        if let Err(err) = result {
+            unsafe { self.destroy_video_encoder(encoder) };
+            return Err(err);
+        }
+
+        if let Some(label) = desc.label {
+            unsafe { self.shared.set_object_name(encoder.session, label) };
+        }
+
+        Ok(encoder)
+    }
+
+    /// Returns whether [`PICTURE_FORMAT`] is supported by `profile` for `usage`.
+    unsafe fn supports_picture_format(
+        &self,
+        fns: &VideoFunctions,
+        profile: &vk::VideoProfileInfoKHR,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<bool, EncoderError> {
+        let mut profile_list =
+            vk::VideoProfileListInfoKHR::default().profiles(slice::from_ref(profile));
+        let info = vk::PhysicalDeviceVideoFormatInfoKHR::default()
+            .image_usage(usage)
+            .push_next(&mut profile_list);
+
+        let get_properties = fns.instance.get_physical_device_video_format_properties_khr;
+        let mut count = 0;
+        unsafe {
+            get_properties(
+                self.shared.physical_device,
+                &info,
+                &mut count,
+                ptr::null_mut(),
+            )
+        }
+        .result()?;
+        let mut properties = vec![vk::VideoFormatPropertiesKHR::default(); count as usize];
+        unsafe {
+            get_properties(
+                self.shared.physical_device,
+                &info,
+                &mut count,
+                properties.as_mut_ptr(),
+            )
+        }
+        .result()?;
+        properties.truncate(count as usize);
+
+        Ok(properties
+            .iter()
+            .any(|properties| properties.format == PICTURE_FORMAT))
+    }
+
+    /// Creates the objects of `encoder`, which
+    /// [`destroy_video_encoder`](Self::destroy_video_encoder) destroys if this
+    /// fails.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn init_video_encoder(
+        &self,
+        fns: &VideoFunctions,
+        encoder: &mut VideoEncoder,
+        session_info: &vk::VideoSessionCreateInfoKHR,
+        sps: &native::StdVideoH264SequenceParameterSet,
+        pps: &native::StdVideoH264PictureParameterSet,
+        mut profile: vk::VideoProfileInfoKHR,
+        min_bitstream_size_alignment: u64,
+    ) -> Result<(), EncoderError> {
+        let raw = &self.shared.raw;
+        let coded_extent = encoder.coded_extent;
+
+        unsafe {
+            (fns.queue.create_video_session_khr)(
+                raw.handle(),
+                session_info,
+                ptr::null(),
+                &mut encoder.session,
+            )
+        }
+        .result()?;
+
+        let mut count = 0;
+        unsafe {
+            (fns.queue.get_video_session_memory_requirements_khr)(
+                raw.handle(),
+                encoder.session,
+                &mut count,
+                ptr::null_mut(),
+            )
+        }
+        .result()?;
+        let mut requirements =
+            vec![vk::VideoSessionMemoryRequirementsKHR::default(); count as usize];
+        unsafe {
+            (fns.queue.get_video_session_memory_requirements_khr)(
+                raw.handle(),
+                encoder.session,
+                &mut count,
+                requirements.as_mut_ptr(),
+            )
+        }
+        .result()?;
+        requirements.truncate(count as usize);
+
+        let mut binds = Vec::with_capacity(requirements.len());
+        for requirement in requirements.iter() {
+            let memory = unsafe {
+                self.allocate_video_memory(
+                    &requirement.memory_requirements,
+                    vk::MemoryPropertyFlags::empty(),
+                    vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                )
+            }?
+            .0;
+            encoder.session_memory.push(memory);
+            binds.push(
+                vk::BindVideoSessionMemoryInfoKHR::default()
+                    .memory_bind_index(requirement.memory_bind_index)
+                    .memory(memory)
+                    .memory_offset(0)
+                    .memory_size(requirement.memory_requirements.size),
+            );
+        }
+        unsafe {
+            (fns.queue.bind_video_session_memory_khr)(
+                raw.handle(),
+                encoder.session,
+                binds.len() as u32,
+                binds.as_ptr(),
+            )
+        }
+        .result()?;
+
+        let sps_list = [*sps];
+        let pps_list = [*pps];
+        let add_info = vk::VideoEncodeH264SessionParametersAddInfoKHR::default()
+            .std_sp_ss(&sps_list)
+            .std_pp_ss(&pps_list);
+        let mut h264_parameters_info = vk::VideoEncodeH264SessionParametersCreateInfoKHR::default()
+            .max_std_sps_count(1)
+            .max_std_pps_count(1)
+            .parameters_add_info(&add_info);
+        let parameters_info = vk::VideoSessionParametersCreateInfoKHR::default()
+            .video_session(encoder.session)
+            .push_next(&mut h264_parameters_info);
+        unsafe {
+            (fns.queue.create_video_session_parameters_khr)(
+                raw.handle(),
+                &parameters_info,
+                ptr::null(),
+                &mut encoder.parameters,
+            )
+        }
+        .result()?;
+
+        let mut h264_get_info = vk::VideoEncodeH264SessionParametersGetInfoKHR::default()
+            .write_std_sps(true)
+            .write_std_pps(true);
+        let get_info = vk::VideoEncodeSessionParametersGetInfoKHR::default()
+            .video_session_parameters(encoder.parameters)
+            .push_next(&mut h264_get_info);
+        let mut size = 0;
+        unsafe {
+            (fns.encode.get_encoded_video_session_parameters_khr)(
+                raw.handle(),
+                &get_info,
+                ptr::null_mut(),
+                &mut size,
+                ptr::null_mut(),
+            )
+        }
+        .result()?;
+        encoder.parameter_sets.resize(size, 0);
+        unsafe {
+            (fns.encode.get_encoded_video_session_parameters_khr)(
+                raw.handle(),
+                &get_info,
+                ptr::null_mut(),
+                &mut size,
+                encoder.parameter_sets.as_mut_ptr().cast(),
+            )
+        }
+        .result()?;
+        encoder.parameter_sets.truncate(size);
+
+        let profiles = [profile];
+        let mut profile_list = vk::VideoProfileListInfoKHR::default().profiles(&profiles);
+
+        let dpb_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(PICTURE_FORMAT)
+            .extent(vk::Extent3D {
+                width: coded_extent.width,
+                height: coded_extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(DPB_SLOTS)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::VIDEO_ENCODE_DPB_KHR)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .push_next(&mut profile_list);
+        encoder.dpb_image = unsafe { raw.create_image(&dpb_info, None) }?;
+        let requirements = unsafe { raw.get_image_memory_requirements(encoder.dpb_image) };
+        encoder.dpb_memory = unsafe {
+            self.allocate_video_memory(
+                &requirements,
+                vk::MemoryPropertyFlags::empty(),
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )
+        }?
+        .0;
+        unsafe { raw.bind_image_memory(encoder.dpb_image, encoder.dpb_memory, 0) }?;
+        let mut dpb_view_usage = vk::ImageViewUsageCreateInfo::default()
+            .usage(vk::ImageUsageFlags::VIDEO_ENCODE_DPB_KHR);
+        let dpb_view_info = vk::ImageViewCreateInfo::default()
+            .image(encoder.dpb_image)
+            .view_type(vk::ImageViewType::TYPE_2D_ARRAY)
+            .format(PICTURE_FORMAT)
+            .subresource_range(picture_subresource_range(DPB_SLOTS))
+            .push_next(&mut dpb_view_usage);
+        encoder.dpb_view = unsafe { raw.create_image_view(&dpb_view_info, None) }?;
+
+        // Uncompressed frames fit, whatever the rate control.
+        let bitstream_size = u64::from(coded_extent.width) * u64::from(coded_extent.height) * 3 / 2;
+        let alignment = min_bitstream_size_alignment.max(1);
+        encoder.bitstream_size = bitstream_size.div_ceil(alignment) * alignment;
+        let mut profile_list = vk::VideoProfileListInfoKHR::default().profiles(&profiles);
+        let bitstream_info = vk::BufferCreateInfo::default()
+            .size(encoder.bitstream_size)
+            .usage(vk::BufferUsageFlags::VIDEO_ENCODE_DST_KHR)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .push_next(&mut profile_list);
+        encoder.bitstream = unsafe { raw.create_buffer(&bitstream_info, None) }?;
+        let requirements = unsafe { raw.get_buffer_memory_requirements(encoder.bitstream) };
+        let (memory, flags) = unsafe {
+            self.allocate_video_memory(
+                &requirements,
+                vk::MemoryPropertyFlags::HOST_VISIBLE,
+                vk::MemoryPropertyFlags::HOST_CACHED,
+            )
+        }?;
+        encoder.bitstream_memory = memory;
+        encoder.bitstream_coherent = flags.contains(vk::MemoryPropertyFlags::HOST_COHERENT);
+        unsafe { raw.bind_buffer_memory(encoder.bitstream, encoder.bitstream_memory, 0) }?;
+
+        let mut feedback_info = vk::QueryPoolVideoEncodeFeedbackCreateInfoKHR::default()
+            .encode_feedback_flags(
+                vk::VideoEncodeFeedbackFlagsKHR::BITSTREAM_BUFFER_OFFSET
+                    | vk::VideoEncodeFeedbackFlagsKHR::BITSTREAM_BYTES_WRITTEN,
+            );
+        let query_pool_info = vk::QueryPoolCreateInfo::default()
+            .query_type(vk::QueryType::VIDEO_ENCODE_FEEDBACK_KHR)
+            .query_count(1)
+            .push_next(&mut profile)
+            .push_next(&mut feedback_info);
+        encoder.query_pool = unsafe { raw.create_query_pool(&query_pool_info, None) }?;
+
+        let command_pool_info = vk::CommandPoolCreateInfo::default()
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+            .queue_family_index(fns.family_index);
+        encoder.command_pool = unsafe { raw.create_command_pool(&command_pool_info, None) }?;
+        let command_buffer_info = vk::CommandBufferAllocateInfo::default()
+            .command_pool(encoder.command_pool)
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_buffer_count(1);
+        encoder.command_buffer = unsafe { raw.allocate_command_buffers(&command_buffer_info) }?[0];
+        encoder.fence = unsafe { raw.create_fence(&vk::FenceCreateInfo::default(), None) }?;
+
+        Ok(())
+    }
+
+    /// Allocates memory for `requirements`, returning it and its properties.
+    unsafe fn allocate_video_memory(
+        &self,
+        requirements: &vk::MemoryRequirements,
+        required: vk::MemoryPropertyFlags,
+        preferred: vk::MemoryPropertyFlags,
+    ) -> Result<(vk::DeviceMemory, vk::MemoryPropertyFlags), EncoderError> {
+        let (memory_type_index, flags) = self
+            .find_memory_type(requirements.memory_type_bits, required, preferred)
+            .ok_or(crate::DeviceError::OutOfMemory)?;
+        let info = vk::MemoryAllocateInfo::default()
+            .allocation_size(requirements.size)
+            .memory_type_index(memory_type_index);
+        let memory = unsafe { self.shared.raw.allocate_memory(&info, None) }?;
+        Ok((memory, flags))
+    }
+
+    /// Destroys a video encoder.
+    ///
+    /// # Safety
+    ///
+    /// - The encoder must be created by this device.
+    pub unsafe fn destroy_video_encoder(&self, encoder: VideoEncoder) {
+        let raw = &self.shared.raw;
+        let fns = self.shared.extension_fns.video.as_ref().unwrap();
+        unsafe {
+            raw.destroy_fence(encoder.fence, None);
+            raw.destroy_command_pool(encoder.command_pool, None);
+            raw.destroy_query_pool(encoder.query_pool, None);
+            raw.destroy_buffer(encoder.bitstream, None);
+            raw.free_memory(encoder.bitstream_memory, None);
+            raw.destroy_image_view(encoder.dpb_view, None);
+            raw.destroy_image(encoder.dpb_image, None);
+            raw.free_memory(encoder.dpb_memory, None);
+            (fns.queue.destroy_video_session_parameters_khr)(
+                raw.handle(),
+                encoder.parameters,
+                ptr::null(),
+            );
+            (fns.queue.destroy_video_session_khr)(raw.handle(), encoder.session, ptr::null());
+            for memory in encoder.session_memory {
+                raw.free_memory(memory, None);
+            }
+        }
+    }
+}
+
+fn picture_subresource_range(layer_count: u32) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange {
+        aspect_mask: vk::ImageAspectFlags::COLOR,
+        base_mip_level: 0,
+        level_count: 1,
+        base_array_layer: 0,
+        layer_count,
+    }
+}
+
+/// Returns the SPS of an encoder, for progressive frames of `coded_extent`
+/// cropped to the size in `desc`.
+fn sps(
+    desc: &EncoderDescriptor,
+    coded_extent: vk::Extent2D,
+    level_idc: native::StdVideoH264LevelIdc,
+) -> native::StdVideoH264SequenceParameterSet {
+    let mut sps: native::StdVideoH264SequenceParameterSet = unsafe { mem::zeroed() };
+    sps.flags.set_frame_mbs_only_flag(1);
+    sps.flags.set_direct_8x8_inference_flag(1);
+    sps.profile_idc = native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_MAIN;
+    sps.level_idc = level_idc;
+    sps.chroma_format_idc =
+        native::StdVideoH264ChromaFormatIdc_STD_VIDEO_H264_CHROMA_FORMAT_IDC_420;
+    sps.log2_max_frame_num_minus4 = LOG2_MAX_FRAME_NUM_MINUS4;
+    // With every frame a reference frame and no B frames, picture order
+    // counts follow `frame_num`.
+    sps.pic_order_cnt_type = native::StdVideoH264PocType_STD_VIDEO_H264_POC_TYPE_2;
+    sps.max_num_ref_frames = 1;
+    sps.pic_width_in_mbs_minus1 = coded_extent.width / 16 - 1;
+    sps.pic_height_in_map_units_minus1 = coded_extent.height / 16 - 1;
+    // Crop offsets are in units of 2 pixels in 4:2:0.
+    let crop_right = (coded_extent.width - desc.width) / 2;
+    let crop_bottom = (coded_extent.height - desc.height) / 2;
+    if crop_right != 0 || crop_bottom != 0 {
+        sps.flags.set_frame_cropping_flag(1);
+        sps.frame_crop_right_offset = crop_right;
+        sps.frame_crop_bottom_offset = crop_bottom;
+    }
+    sps
+}
+
+fn pps() -> native::StdVideoH264PictureParameterSet {
+    let mut pps: native::StdVideoH264PictureParameterSet = unsafe { mem::zeroed() };
+    pps.flags.set_deblocking_filter_control_present_flag(1);
+    pps.weighted_bipred_idc =
+        native::StdVideoH264WeightedBipredIdc_STD_VIDEO_H264_WEIGHTED_BIPRED_IDC_DEFAULT;
+    pps
+}
+
+unsafe fn pipeline_barrier(
+    shared: &super::DeviceShared,
+    command_buffer: vk::CommandBuffer,
+    info: &vk::DependencyInfo,
+) {
+    // Video encoding is only enabled along with `synchronization2`.
+    match *shared.extension_fns.synchronization2.as_ref().unwrap() {
+        super::ExtensionFn::Extension(ref ext) => unsafe {
+            ext.cmd_pipeline_barrier2(command_buffer, info)
+        },
+        super::ExtensionFn::Promoted => unsafe {
+            shared.raw.cmd_pipeline_barrier2(command_buffer, info)
+        },
+    }
+}
+
+impl VideoEncoder {
+    /// Returns the size of input textures.
+    ///
+    /// This is the size of the frames rounded up to whole macroblocks. Only
+    /// the frame size at the top left is encoded.
+    pub fn input_size(&self) -> wgt::Extent3d {
+        wgt::Extent3d {
+            width: self.coded_extent.width,
+            height: self.coded_extent.height,
+            depth_or_array_layers: 1,
+        }
+    }
+
+    /// Creates a texture to encode frames from.
+    ///
+    /// The texture has the [`NV12`](wgt::TextureFormat::NV12) format and the
+    /// [`input_size`](Self::input_size), and can only be copied to. Wrap it
+    /// with `wgpu::Device::create_texture_from_hal`, with a descriptor of the
+    /// same format and size and the `COPY_DST` usage, and copy frames into
+    /// its planes. This needs `Features::TEXTURE_FORMAT_NV12`.
+    pub unsafe fn create_input_texture(
+        &self,
+        device: &super::Device,
+        label: crate::Label,
+    ) -> Result<super::Texture, crate::DeviceError> {
+        let shared = &device.shared;
+        let fns = shared.extension_fns.video.as_ref().unwrap();
+
+        let mut h264_profile = h264_profile_info();
+        let profiles = [profile_info(&mut h264_profile)];
+        let mut profile_list = vk::VideoProfileListInfoKHR::default().profiles(&profiles);
+        let family_indices = [shared.family_index, fns.family_index];
+        let info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(PICTURE_FORMAT)
+            .extent(vk::Extent3D {
+                width: self.coded_extent.width,
+                height: self.coded_extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::VIDEO_ENCODE_SRC_KHR)
+            .sharing_mode(vk::SharingMode::CONCURRENT)
+            .queue_family_indices(&family_indices)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .push_next(&mut profile_list);
+
+        let raw = unsafe { shared.raw.create_image(&info, None) }?;
+        let req = unsafe { shared.raw.get_image_memory_requirements(raw) };
+        let block = unsafe {
+            device.mem_allocator.allocate(
+                &shared.raw,
+                &super::MemoryRequest {
+                    size: req.size,
+                    align_mask: req.alignment - 1,
+                    usage: super::MemoryUsage::FAST_DEVICE_ACCESS,
+                    memory_types: req.memory_type_bits & device.valid_ash_memory_types,
+                },
+            )
+        };
+        let block = match block {
+            Ok(block) => block,
+            Err(err) => {
+                unsafe { shared.raw.destroy_image(raw, None) };
+                return Err(err);
+            }
+        };
+        if let Err(err) = unsafe {
+            shared
+                .raw
+                .bind_image_memory(raw, block.memory, block.offset)
+        } {
+            unsafe {
+                shared.raw.destroy_image(raw, None);
+                device.mem_allocator.deallocate(&shared.raw, block);
+            }
+            return Err(err.into());
+        }
+
+        if let Some(label) = label {
+            unsafe { shared.set_object_name(raw, label) };
+        }
+
+        Ok(super::Texture {
+            raw,
+            drop_guard: None,
+            block: Some(block),
+            usage: crate::TextureUses::COPY_DST,
+            format: wgt::TextureFormat::NV12,
+            raw_flags: vk::ImageCreateFlags::empty(),
+            copy_size: crate::CopyExtent {
+                width: self.coded_extent.width,
+                height: self.coded_extent.height,
+                depth: 1,
+            },
+            view_formats: Vec::new(),
+        })
+    }
+
+    /// Encodes the frame in `input`, and returns its bitstream once it's
+    /// encoded.
+    ///
+    /// Encoding starts once `wait` is signaled, if it's set. With `wgpu-core`,
+    /// this is the device's fence and the index of the submission writing the
+    /// frame. The frame is a keyframe if `force_keyframe` is set, or if the
+    /// keyframe interval is reached.
+    ///
+    /// # Safety
+    ///
+    /// - `input` must be created by [`create_input_texture`](Self::create_input_texture)
+    ///   with `device`, the device that created the encoder.
+    /// - The last use of `input` must be as a copy destination, and, if `wait`
+    ///   isn't set, that copy must be complete.
+    /// - `input` must not be used by `device` until this returns.
+    pub unsafe fn encode(
+        &mut self,
+        device: &super::Device,
+        input: &super::Texture,
+        wait: Option<(&super::Fence, crate::FenceValue)>,
+        force_keyframe: bool,
+    ) -> Result<EncodedFrame, EncoderError> {
+        let shared = &device.shared;
+
+        if let Some((fence @ &super::Fence::FencePool { .. }, value)) = wait {
+            // Without timeline semaphores that the encode queue can wait on,
+            // wait on the host.
+            unsafe { crate::Device::wait(device, fence, value, !0) }?;
+        }
+
+        let mut view_usage = vk::ImageViewUsageCreateInfo::default()
+            .usage(vk::ImageUsageFlags::VIDEO_ENCODE_SRC_KHR);
+        let view_info = vk::ImageViewCreateInfo::default()
+            .image(input.raw)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(PICTURE_FORMAT)
+            .subresource_range(picture_subresource_range(1))
+            .push_next(&mut view_usage);
+        let input_view = unsafe { shared.raw.create_image_view(&view_info, None) }?;
+
+        let result = unsafe { self.encode_impl(device, input, input_view, wait, force_keyframe) };
+        unsafe { shared.raw.destroy_image_view(input_view, None) };
+        result
+    }
+
+    unsafe fn encode_impl(
+        &mut self,
+        device: &super::Device,
+        input: &super::Texture,
+        input_view: vk::ImageView,
+        wait: Option<(&super::Fence, crate::FenceValue)>,
+        force_keyframe: bool,
+    ) -> Result<EncodedFrame, EncoderError> {
+        let shared = &device.shared;
+        let raw = &shared.raw;
+        let fns = shared.extension_fns.video.as_ref().unwrap();
+        let command_buffer = self.command_buffer;
+
+        let keyframe = force_keyframe
+            || self.reference.is_none()
+            || self.frames_since_keyframe >= self.keyframe_interval;
+        if keyframe {
+            self.frame_num = 0;
+            self.idr_pic_id = self.idr_pic_id.wrapping_add(1);
+            self.frames_since_keyframe = 0;
+        }
+        let reference = if keyframe { None } else { self.reference };
+        let setup = Reference {
+            slot: self
+                .reference
+                .map_or(0, |reference| (reference.slot + 1) % DPB_SLOTS),
+            frame_num: self.frame_num,
+            picture_order_count: 2 * self.frame_num as i32,
+            keyframe,
+        };
+
+        unsafe {
+            raw.reset_command_pool(self.command_pool, vk::CommandPoolResetFlags::empty())?;
+            raw.begin_command_buffer(
+                command_buffer,
+                &vk::CommandBufferBeginInfo::default()
+                    .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+            )?;
+            raw.cmd_reset_query_pool(command_buffer, self.query_pool, 0, 1);
+        }
+
+        let mut barriers = ArrayVec::<vk::ImageMemoryBarrier2, 2>::new();
+        barriers.push(
+            vk::ImageMemoryBarrier2::default()
+                .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                .dst_stage_mask(vk::PipelineStageFlags2::VIDEO_ENCODE_KHR)
+                .dst_access_mask(vk::AccessFlags2::VIDEO_ENCODE_READ_KHR)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::VIDEO_ENCODE_SRC_KHR)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(input.raw)
+                .subresource_range(picture_subresource_range(1)),
+        );
+        if !self.reset {
+            barriers.push(
+                vk::ImageMemoryBarrier2::default()
+                    .dst_stage_mask(vk::PipelineStageFlags2::VIDEO_ENCODE_KHR)
+                    .dst_access_mask(
+                        vk::AccessFlags2::VIDEO_ENCODE_READ_KHR
+                            | vk::AccessFlags2::VIDEO_ENCODE_WRITE_KHR,
+                    )
+                    .old_layout(vk::ImageLayout::UNDEFINED)
+                    .new_layout(vk::ImageLayout::VIDEO_ENCODE_DPB_KHR)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(self.dpb_image)
+                    .subresource_range(picture_subresource_range(DPB_SLOTS)),
+            );
+        }
+        unsafe {
+            pipeline_barrier(
+                shared,
+                command_buffer,
+                &vk::DependencyInfo::default().image_memory_barriers(&barriers),
+            )
+        };
+
+        let dpb_picture = |slot: u32| {
+            vk::VideoPictureResourceInfoKHR::default()
+                .coded_extent(self.coded_extent)
+                .base_array_layer(slot)
+                .image_view_binding(self.dpb_view)
+        };
+        let setup_picture = dpb_picture(setup.slot);
+        let reference_picture = reference.map(|reference| dpb_picture(reference.slot));
+
+        // The setup slot is activated by encoding, so it's bound without a
+        // slot index.
+        let mut begin_slots = ArrayVec::<vk::VideoReferenceSlotInfoKHR, 2>::new();
+        begin_slots.push(
+            vk::VideoReferenceSlotInfoKHR::default()
+                .slot_index(-1)
+                .picture_resource(&setup_picture),
+        );
+        if let (Some(reference), Some(picture)) = (reference, reference_picture.as_ref()) {
+            begin_slots.push(
+                vk::VideoReferenceSlotInfoKHR::default()
+                    .slot_index(reference.slot as i32)
+                    .picture_resource(picture),
+            );
+        }
+
+        let layers = self.rate_control_layers();
+        let mut rate_control_info = self.rate_control_info(&layers);
+        let mut begin_info = vk::VideoBeginCodingInfoKHR::default()
+            .video_session(self.session)
+            .video_session_parameters(self.parameters)
+            .reference_slots(&begin_slots);
+        if self.reset {
+            begin_info = begin_info.push_next(&mut rate_control_info);
+        }
+        unsafe { (fns.queue.cmd_begin_video_coding_khr)(command_buffer, &begin_info) };
+
+        if !self.reset {
+            let mut rate_control_info = self.rate_control_info(&layers);
+            let control_info = vk::VideoCodingControlInfoKHR::default()
+                .flags(
+                    vk::VideoCodingControlFlagsKHR::RESET
+                        | vk::VideoCodingControlFlagsKHR::ENCODE_RATE_CONTROL,
+                )
+                .push_next(&mut rate_control_info);
+            unsafe { (fns.queue.cmd_control_video_coding_khr)(command_buffer, &control_info) };
+        }
+
+        let mut slice_header: native::StdVideoEncodeH264SliceHeader = unsafe { mem::zeroed() };
+        slice_header.slice_type = if keyframe {
+            native::StdVideoH264SliceType_STD_VIDEO_H264_SLICE_TYPE_I
+        } else {
+            native::StdVideoH264SliceType_STD_VIDEO_H264_SLICE_TYPE_P
+        };
+        let constant_qp = match self.rate_control {
+            RateControl::ConstantQp { qp } => i32::from(qp),
+            RateControl::ConstantBitrate { .. } | RateControl::VariableBitrate { .. } => 0,
+        };
+        let slices = [vk::VideoEncodeH264NaluSliceInfoKHR::default()
+            .constant_qp(constant_qp)
+            .std_slice_header(&slice_header)];
+
+        let mut reference_lists: native::StdVideoEncodeH264ReferenceListsInfo =
+            unsafe { mem::zeroed() };
+        reference_lists.RefPicList0 = [NO_REFERENCE_PICTURE; 32];
+        reference_lists.RefPicList1 = [NO_REFERENCE_PICTURE; 32];
+        if let Some(reference) = reference {
+            reference_lists.RefPicList0[0] = reference.slot as u8;
+        }
+
+        let mut picture_info: native::StdVideoEncodeH264PictureInfo = unsafe { mem::zeroed() };
+        picture_info.flags.set_IdrPicFlag(u32::from(keyframe));
+        picture_info.flags.set_is_reference(1);
+        picture_info.idr_pic_id = self.idr_pic_id;
+        picture_info.primary_pic_type = setup.std_info().primary_pic_type;
+        picture_info.frame_num = setup.frame_num;
+        picture_info.PicOrderCnt = setup.picture_order_count;
+        picture_info.pRefLists = &reference_lists;
+        let mut h264_picture_info = vk::VideoEncodeH264PictureInfoKHR::default()
+            .nalu_slice_entries(&slices)
+            .std_picture_info(&picture_info);
+
+        let setup_std_info = setup.std_info();
+        let mut setup_h264_info =
+            vk::VideoEncodeH264DpbSlotInfoKHR::default().std_reference_info(&setup_std_info);
+        let setup_slot = vk::VideoReferenceSlotInfoKHR::default()
+            .slot_index(setup.slot as i32)
+            .picture_resource(&setup_picture)
+            .push_next(&mut setup_h264_info);
+
+        let reference_std_info = reference.map(|reference| reference.std_info());
+        let mut reference_h264_info = reference_std_info
+            .as_ref()
+            .map(|info| vk::VideoEncodeH264DpbSlotInfoKHR::default().std_reference_info(info));
+        let mut reference_slots = ArrayVec::<vk::VideoReferenceSlotInfoKHR, 1>::new();
+        if let (Some(reference), Some(picture), Some(h264_info)) = (
+            reference,
+            reference_picture.as_ref(),
+            reference_h264_info.as_mut(),
+        ) {
+            reference_slots.push(
+                vk::VideoReferenceSlotInfoKHR::default()
+                    .slot_index(reference.slot as i32)
+                    .picture_resource(picture)
+                    .push_next(h264_info),
+            );
+        }
+
+        let src_picture = vk::VideoPictureResourceInfoKHR::default()
+            .coded_extent(self.coded_extent)
+            .base_array_layer(0)
+            .image_view_binding(input_view);
+        let encode_info = vk::VideoEncodeInfoKHR::default()
+            .dst_buffer(self.bitstream)
+            .dst_buffer_offset(0)
+            .dst_buffer_range(self.bitstream_size)
+            .src_picture_resource(src_picture)
+            .setup_reference_slot(&setup_slot)
+            .reference_slots(&reference_slots)
+            .push_next(&mut h264_picture_info);
+
+        unsafe {
+            raw.cmd_begin_query(
+                command_buffer,
+                self.query_pool,
+                0,
+                vk::QueryControlFlags::empty(),
+            );
+            (fns.encode.cmd_encode_video_khr)(command_buffer, &encode_info);
+            raw.cmd_end_query(command_buffer, self.query_pool, 0);
+            (fns.queue.cmd_end_video_coding_khr)(
+                command_buffer,
+                &vk::VideoEndCodingInfoKHR::default(),
+            );
+        }
+
+        // Give the input back to wgpu in the layout it left it in, and make
+        // the bitstream visible to the host.
+        let image_barriers = [vk::ImageMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::VIDEO_ENCODE_KHR)
+            .src_access_mask(vk::AccessFlags2::VIDEO_ENCODE_READ_KHR)
+            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .old_layout(vk::ImageLayout::VIDEO_ENCODE_SRC_KHR)
+            .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(input.raw)
+            .subresource_range(picture_subresource_range(1))];
+        let buffer_barriers = [vk::BufferMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::VIDEO_ENCODE_KHR)
+            .src_access_mask(vk::AccessFlags2::VIDEO_ENCODE_WRITE_KHR)
+            .dst_stage_mask(vk::PipelineStageFlags2::HOST)
+            .dst_access_mask(vk::AccessFlags2::HOST_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(self.bitstream)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)];
+        unsafe {
+            pipeline_barrier(
+                shared,
+                command_buffer,
+                &vk::DependencyInfo::default()
+                    .image_memory_barriers(&image_barriers)
+                    .buffer_memory_barriers(&buffer_barriers),
+            );
+            raw.end_command_buffer(command_buffer)?;
+        }
+
+        let mut wait_semaphores = ArrayVec::<vk::Semaphore, 1>::new();
+        let mut wait_values = ArrayVec::<u64, 1>::new();
+        if let Some((&super::Fence::TimelineSemaphore(semaphore), value)) = wait {
+            wait_semaphores.push(semaphore);
+            wait_values.push(value);
+        }
+        let wait_stages = [vk::PipelineStageFlags::ALL_COMMANDS];
+        let command_buffers = [command_buffer];
+        let mut timeline_info =
+            vk::TimelineSemaphoreSubmitInfo::default().wait_semaphore_values(&wait_values);
+        let mut submit_info = vk::SubmitInfo::default()
+            .command_buffers(&command_buffers)
+            .wait_semaphores(&wait_semaphores)
+            .wait_dst_stage_mask(&wait_stages[..wait_semaphores.len()]);
+        if !wait_semaphores.is_empty() {
+            submit_info = submit_info.push_next(&mut timeline_info);
+        }
+        {
+            let queue = fns.raw_queue.lock();
+            unsafe { raw.queue_submit(*queue, &[submit_info], self.fence) }?;
+        }
+        unsafe {
+            raw.wait_for_fences(&[self.fence], true, u64::MAX)?;
+            raw.reset_fences(&[self.fence])?;
+        }
+
+        // The offset and size of the bitstream, and the status of encoding.
+        let mut feedback = [[0u32; 3]];
+        unsafe {
+            raw.get_query_pool_results(
+                self.query_pool,
+                0,
+                &mut feedback,
+                vk::QueryResultFlags::WAIT | vk::QueryResultFlags::WITH_STATUS_KHR,
+            )
+        }?;
+        let [offset, bytes_written, status] = feedback[0];
+        if status as i32 != vk::QueryResultStatusKHR::COMPLETE.as_raw() {
+            return Err(EncoderError::EncodingFailed);
+        }
+
+        let mut data = Vec::new();
+        if keyframe {
+            data.extend_from_slice(&self.parameter_sets);
+        }
+        unsafe {
+            let ptr = raw.map_memory(
+                self.bitstream_memory,
+                0,
+                vk::WHOLE_SIZE,
+                vk::MemoryMapFlags::empty(),
+            )?;
+            if !self.bitstream_coherent {
+                let range = vk::MappedMemoryRange::default()
+                    .memory(self.bitstream_memory)
+                    .offset(0)
+                    .size(vk::WHOLE_SIZE);
+                raw.invalidate_mapped_memory_ranges(&[range])?;
+            }
+            data.extend_from_slice(slice::from_raw_parts(
+                ptr.cast::<u8>().add(offset as usize),
+                bytes_written as usize,
+            ));
+            raw.unmap_memory(self.bitstream_memory);
+        }
+
+        self.reset = true;
+        self.reference = Some(setup);
+        self.frames_since_keyframe += 1;
+        self.frame_num = (self.frame_num + 1) % (1 << (LOG2_MAX_FRAME_NUM_MINUS4 + 4));
+
+        Ok(EncodedFrame { data, keyframe })
+    }
+
+    fn rate_control_layers(&self) -> ArrayVec<vk::VideoEncodeRateControlLayerInfoKHR<'static>, 1> {
+        let (average_bitrate, max_bitrate) = match self.rate_control {
+            RateControl::ConstantQp { .. } => return ArrayVec::new(),
+            RateControl::ConstantBitrate { bitrate } => (bitrate, bitrate),
+            RateControl::VariableBitrate {
+                average_bitrate,
+                max_bitrate,
+            } => (average_bitrate, max_bitrate),
+        };
+        let mut layers = ArrayVec::new();
+        layers.push(
+            vk::VideoEncodeRateControlLayerInfoKHR::default()
+                .average_bitrate(average_bitrate)
+                .max_bitrate(max_bitrate)
+                .frame_rate_numerator(self.frame_rate.0)
+                .frame_rate_denominator(self.frame_rate.1),
+        );
+        layers
+    }
+
+    fn rate_control_info<'a>(
+        &self,
+        layers: &'a [vk::VideoEncodeRateControlLayerInfoKHR<'static>],
+    ) -> vk::VideoEncodeRateControlInfoKHR<'a> {
+        let info = vk::VideoEncodeRateControlInfoKHR::default()
+            .rate_control_mode(rate_control_mode(self.rate_control))
+            .layers(layers);
+        if layers.is_empty() {
+            info
+        } else {
+            // Buffer a second of frames, starting half full.
+            info.virtual_buffer_size_in_ms(1000)
+                .initial_virtual_buffer_size_in_ms(500)
+        }
+    }
+}
+
+#[test]
+fn sps_crops_to_frame_size() {
+    let desc = |width, height| EncoderDescriptor {
+        label: None,
+        codec: Codec::H264,
+        width,
+        height,
+        frame_rate: (60, 1),
+        rate_control: RateControl::ConstantQp { qp: 26 },
+        keyframe_interval: 60,
+    };
+    let level_idc = native::StdVideoH264LevelIdc_STD_VIDEO_H264_LEVEL_IDC_4_1;
+
+    // 1080 isn't a multiple of the macroblock size, so the 8 extra rows of
+    // the coded frame are cropped.
+    let cropped = sps(
+        &desc(1920, 1080),
+        vk::Extent2D {
+            width: 1920,
+            height: 1088,
+        },
+        level_idc,
+    );
+    assert_eq!(cropped.pic_width_in_mbs_minus1, 119);
+    assert_eq!(cropped.pic_height_in_map_units_minus1, 67);
+    assert_eq!(cropped.flags.frame_cropping_flag(), 1);
+    assert_eq!(cropped.frame_crop_right_offset, 0);
+    assert_eq!(cropped.frame_crop_bottom_offset, 4);
+
+    let uncropped = sps(
+        &desc(1280, 720),
+        vk::Extent2D {
+            width: 1280,
+            height: 720,
+        },
+        level_idc,
+    );
+    assert_eq!(uncropped.flags.frame_cropping_flag(), 0);
+}
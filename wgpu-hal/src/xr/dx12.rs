@@ -0,0 +1,82 @@
+//! Helpers for `XR_KHR_D3D12_enable`.
+//!
+//! With this extension the application creates the device itself, on the
+//! adapter the runtime asks for. The typical setup is:
+//!
+//! 1. Get the adapter LUID and minimum feature level with
+//!    `xrGetD3D12GraphicsRequirementsKHR`, and expose the adapter with
+//!    [`expose_adapter`]. wgpu-hal creates devices at feature level 11.0,
+//!    which current runtimes accept.
+//! 2. Open the device as usual, and pass [`Device::raw_device`] and
+//!    [`Device::raw_queue`] to the `XrGraphicsBindingD3D12KHR` of the
+//!    session.
+//! 3. Create the swapchain with [`swapchain_format`] and
+//!    [`SwapchainDescriptor::usage_flags`], and wrap its images with
+//!    [`texture_from_swapchain_image`].
+//!
+//! [`Device::raw_device`]: dx12::Device::raw_device
+//! [`Device::raw_queue`]: dx12::Device::raw_queue
+
+use std::mem;
+
+use winapi::shared::{dxgi1_2, ntdef};
+
+use super::SwapchainDescriptor;
+use crate::{auxil, dx12};
+
+/// Exposes the adapter with the LUID returned by the runtime, if it is one of
+/// the adapters of `instance`.
+pub fn expose_adapter(
+    instance: &dx12::Instance,
+    adapter_luid: ntdef::LUID,
+) -> Option<crate::ExposedAdapter<dx12::Api>> {
+    let adapters = unsafe { crate::Instance::enumerate_adapters(instance) };
+    adapters.into_iter().find(|exposed| {
+        let mut desc: dxgi1_2::DXGI_ADAPTER_DESC2 = unsafe { mem::zeroed() };
+        unsafe {
+            exposed
+                .adapter
+                .raw_adapter()
+                .unwrap_adapter2()
+                .GetDesc2(&mut desc)
+        };
+        desc.AdapterLuid.LowPart == adapter_luid.LowPart
+            && desc.AdapterLuid.HighPart == adapter_luid.HighPart
+    })
+}
+
+/// Returns the `format` for the `XrSwapchainCreateInfo`, a `DXGI_FORMAT`.
+///
+/// Check that it's one of the formats returned by
+/// `xrEnumerateSwapchainFormats` before creating the swapchain.
+pub fn swapchain_format(format: wgt::TextureFormat) -> i64 {
+    auxil::dxgi::conv::map_texture_format(format).into()
+}
+
+/// Wraps an image of the swapchain created from `desc`, as returned by
+/// `xrEnumerateSwapchainImages`.
+///
+/// # Safety
+///
+/// - `raw_image` must be an image of a swapchain created from `desc`, of a
+///   session using the device the texture is used with.
+/// - The texture must not be used after the swapchain is destroyed.
+pub unsafe fn texture_from_swapchain_image(
+    raw_image: d3d12::Resource,
+    desc: &SwapchainDescriptor,
+) -> dx12::Texture {
+    unsafe {
+        dx12::Device::texture_from_raw(
+            raw_image,
+            desc.format,
+            wgt::TextureDimension::D2,
+            wgt::Extent3d {
+                width: desc.width,
+                height: desc.height,
+                depth_or_array_layers: desc.array_size,
+            },
+            desc.mip_count,
+            desc.sample_count,
+        )
+    }
+}
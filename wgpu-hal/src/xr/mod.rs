@@ -0,0 +1,157 @@
+/*!
+# OpenXR interop
+
+OpenXR runtimes render to swapchains they allocate themselves, and need a say
+in how the Vulkan or D3D12 device is created. The helpers in the backend
+submodules fill in what the runtime asks for with what wgpu-hal needs, and wrap
+the swapchain images as hal textures, which `wgpu::Device::create_texture_from_hal`
+turns into wgpu textures.
+
+None of this depends on a particular OpenXR binding: the runtime calls are left
+to the caller, and the values passed to or returned by them are plain integers
+or raw graphics API handles.
+
+## Image layouts
+
+Between `xrWaitSwapchainImage` and `xrReleaseSwapchainImage`, the runtime
+requires a swapchain image to be in the state given by
+[`SwapchainDescriptor::image_uses`]: the color attachment layout for color
+formats, and the depth-stencil attachment layout for depth formats.
+
+wgpu treats a texture wrapped from a swapchain image as uninitialized, and
+transitions it from there on its first use. Its contents aren't preserved, so
+clear it at the start of each frame. The last use of the texture before
+releasing the image must be as a render attachment, so it is left in the
+state the runtime expects.
+*/
+
+#[cfg(dx12)]
+pub mod dx12;
+#[cfg(vulkan)]
+pub mod vulkan;
+
+use bitflags::bitflags;
+
+bitflags!(
+    /// The `XrSwapchainUsageFlags` of a swapchain.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct SwapchainUsageFlags: u64 {
+        const COLOR_ATTACHMENT = 1 << 0;
+        const DEPTH_STENCIL_ATTACHMENT = 1 << 1;
+        const UNORDERED_ACCESS = 1 << 2;
+        const TRANSFER_SRC = 1 << 3;
+        const TRANSFER_DST = 1 << 4;
+        const SAMPLED = 1 << 5;
+        const MUTABLE_FORMAT = 1 << 6;
+        const INPUT_ATTACHMENT = 1 << 7;
+    }
+);
+
+/// The properties of a swapchain, from which the `XrSwapchainCreateInfo` and
+/// the descriptors of the textures wrapping its images are derived.
+#[derive(Clone, Debug)]
+pub struct SwapchainDescriptor {
+    pub format: wgt::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// The number of array layers, e.g. 2 to render both eyes with multiview.
+    pub array_size: u32,
+    pub mip_count: u32,
+    pub sample_count: u32,
+    pub usage: wgt::TextureUsages,
+    /// The other formats views of the images can have.
+    pub view_formats: Vec<wgt::TextureFormat>,
+}
+
+impl SwapchainDescriptor {
+    /// Returns the `usageFlags` for the `XrSwapchainCreateInfo`.
+    pub fn usage_flags(&self) -> SwapchainUsageFlags {
+        let usage = self.usage;
+        let mut flags = SwapchainUsageFlags::empty();
+        if usage.contains(wgt::TextureUsages::RENDER_ATTACHMENT) {
+            flags |= if self.format.is_depth_stencil_format() {
+                SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+            } else {
+                SwapchainUsageFlags::COLOR_ATTACHMENT
+            };
+        }
+        flags.set(
+            SwapchainUsageFlags::SAMPLED,
+            usage.contains(wgt::TextureUsages::TEXTURE_BINDING),
+        );
+        flags.set(
+            SwapchainUsageFlags::UNORDERED_ACCESS,
+            usage.contains(wgt::TextureUsages::STORAGE_BINDING),
+        );
+        flags.set(
+            SwapchainUsageFlags::TRANSFER_SRC,
+            usage.contains(wgt::TextureUsages::COPY_SRC),
+        );
+        flags.set(
+            SwapchainUsageFlags::TRANSFER_DST,
+            usage.contains(wgt::TextureUsages::COPY_DST),
+        );
+        flags.set(
+            SwapchainUsageFlags::MUTABLE_FORMAT,
+            self.view_formats
+                .iter()
+                .any(|&format| format != self.format),
+        );
+        flags
+    }
+
+    /// Returns the state the runtime hands out swapchain images in, and
+    /// expects them back in.
+    pub fn image_uses(&self) -> crate::TextureUses {
+        if self.format.is_depth_stencil_format() {
+            crate::TextureUses::DEPTH_STENCIL_WRITE
+        } else {
+            crate::TextureUses::COLOR_TARGET
+        }
+    }
+
+    /// Returns the descriptor of the hal textures wrapping the swapchain images.
+    pub fn texture_descriptor<'a>(&self, label: crate::Label<'a>) -> crate::TextureDescriptor<'a> {
+        let mut uses = crate::TextureUses::empty();
+        let usage = self.usage;
+        if usage.contains(wgt::TextureUsages::RENDER_ATTACHMENT) {
+            uses |= if self.format.is_depth_stencil_format() {
+                crate::TextureUses::DEPTH_STENCIL_READ | crate::TextureUses::DEPTH_STENCIL_WRITE
+            } else {
+                crate::TextureUses::COLOR_TARGET
+            };
+        }
+        uses.set(
+            crate::TextureUses::RESOURCE,
+            usage.contains(wgt::TextureUsages::TEXTURE_BINDING),
+        );
+        uses.set(
+            crate::TextureUses::STORAGE_READ | crate::TextureUses::STORAGE_READ_WRITE,
+            usage.contains(wgt::TextureUsages::STORAGE_BINDING),
+        );
+        uses.set(
+            crate::TextureUses::COPY_SRC,
+            usage.contains(wgt::TextureUsages::COPY_SRC),
+        );
+        uses.set(
+            crate::TextureUses::COPY_DST,
+            usage.contains(wgt::TextureUsages::COPY_DST),
+        );
+
+        crate::TextureDescriptor {
+            label,
+            size: wgt::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: self.array_size,
+            },
+            mip_level_count: self.mip_count,
+            sample_count: self.sample_count,
+            dimension: wgt::TextureDimension::D2,
+            format: self.format,
+            usage: uses,
+            memory_flags: crate::MemoryFlags::empty(),
+            view_formats: self.view_formats.clone(),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Helpers for `XR_KHR_vulkan_enable2`.
+//!
+//! With this extension the runtime creates the Vulkan instance and device, by
+//! `xrCreateVulkanInstanceKHR` and `xrCreateVulkanDeviceKHR`, from create
+//! infos the application provides. The typical setup is:
+//!
+//! 1. Get the [`GraphicsRequirements`] with `xrGetVulkanGraphicsRequirements2KHR`.
+//! 2. Create the instance with [`create_instance`].
+//! 3. Get the physical device with `xrGetVulkanGraphicsDevice2KHR`, and expose
+//!    its adapter with [`Instance::expose_adapter`](vulkan::Instance::expose_adapter).
+//! 4. Open the device with [`open_device`], and pass its handles to the
+//!    `XrGraphicsBindingVulkan2KHR` of the session.
+//! 5. Create the swapchain with [`swapchain_format`] and
+//!    [`SwapchainDescriptor::usage_flags`], and wrap its images with
+//!    [`texture_from_swapchain_image`].
+//!
+//! The instance and device are owned by wgpu-hal, as if it created them, so
+//! the XR session must be destroyed before the wgpu-hal device and instance.
+
+use std::ffi::{c_char, CStr, CString};
+
+use ash::vk;
+
+use super::SwapchainDescriptor;
+use crate::vulkan;
+
+/// The Vulkan versions supported by the runtime, as `XrVersion`s, from
+/// `XrGraphicsRequirementsVulkan2KHR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphicsRequirements {
+    pub min_api_version_supported: u64,
+    pub max_api_version_supported: u64,
+}
+
+/// The highest Vulkan version wgpu-hal targets.
+const MAX_API_VERSION: u32 = vk::API_VERSION_1_3;
+
+fn xr_version_to_vk(version: u64) -> u32 {
+    let major = (version >> 48) as u32;
+    let minor = ((version >> 32) & 0xffff) as u32;
+    vk::make_api_version(0, major, minor, 0)
+}
+
+impl GraphicsRequirements {
+    /// Picks the Vulkan version to create the instance with: the highest one
+    /// supported by the loader, wgpu-hal and the runtime.
+    pub fn api_version(&self, entry: &ash::Entry) -> Result<u32, crate::InstanceError> {
+        let loader_version = match unsafe { entry.try_enumerate_instance_version() } {
+            Ok(Some(version)) => version,
+            Ok(None) => vk::API_VERSION_1_0,
+            Err(err) => {
+                return Err(crate::InstanceError::with_source(
+                    String::from("try_enumerate_instance_version() failed"),
+                    err,
+                ));
+            }
+        };
+        let min = xr_version_to_vk(self.min_api_version_supported);
+        let max = xr_version_to_vk(self.max_api_version_supported);
+        // Only compare major and minor versions, the runtime doesn't care
+        // about patch versions.
+        let loader_version = vk::make_api_version(
+            0,
+            vk::api_version_major(loader_version),
+            vk::api_version_minor(loader_version),
+            0,
+        );
+        let version = loader_version.min(MAX_API_VERSION).min(max);
+        if version < min {
+            return Err(crate::InstanceError::new(format!(
+                "the XR runtime requires Vulkan {}.{}, but only {}.{} is available",
+                vk::api_version_major(min),
+                vk::api_version_minor(min),
+                vk::api_version_major(version),
+                vk::api_version_minor(version),
+            )));
+        }
+        Ok(version)
+    }
+}
+
+/// Creates an instance through the XR runtime.
+///
+/// `create_raw` is given the create info to pass to `xrCreateVulkanInstanceKHR`,
+/// and returns the instance it created.
+///
+/// # Safety
+///
+/// - `create_raw` must create the instance from `create_info` and `entry`.
+pub unsafe fn create_instance(
+    entry: ash::Entry,
+    requirements: &GraphicsRequirements,
+    app_name: &str,
+    flags: wgt::InstanceFlags,
+    create_raw: impl FnOnce(&vk::InstanceCreateInfo) -> Result<vk::Instance, crate::InstanceError>,
+) -> Result<vulkan::Instance, crate::InstanceError> {
+    let api_version = requirements.api_version(&entry)?;
+    let extensions = vulkan::Instance::desired_extensions(&entry, api_version, flags)?;
+
+    let app_name = CString::new(app_name).unwrap();
+    let app_info = vk::ApplicationInfo::default()
+        .application_name(app_name.as_c_str())
+        .application_version(1)
+        .engine_name(CStr::from_bytes_with_nul(b"wgpu-hal\0").unwrap())
+        .engine_version(2)
+        .api_version(api_version);
+    let str_pointers = extensions
+        .iter()
+        .map(|&s| {
+            // Safe because `extensions` entries have static lifetime.
+            s.as_ptr()
+        })
+        .collect::<Vec<*const c_char>>();
+    let mut create_flags = vk::InstanceCreateFlags::empty();
+    if extensions.contains(&ash::khr::portability_enumeration::NAME) {
+        create_flags |= vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR;
+    }
+    let create_info = vk::InstanceCreateInfo::default()
+        .flags(create_flags)
+        .application_info(&app_info)
+        .enabled_extension_names(&str_pointers);
+
+    let raw = create_raw(&create_info)?;
+    let raw_instance = unsafe { ash::Instance::load(entry.static_fn(), raw) };
+
+    unsafe {
+        vulkan::Instance::from_raw(
+            entry,
+            raw_instance,
+            api_version,
+            0,
+            None,
+            extensions,
+            flags,
+            false,
+            None,
+        )
+    }
+}
+
+/// Opens a device through the XR runtime.
+///
+/// `create_raw` is given the create info to pass to `xrCreateVulkanDeviceKHR`,
+/// and returns the device it created. The device has a single queue, whose
+/// family and index, as returned by [`Device::queue_family_index`] and
+/// [`Device::queue_index`], are the ones to pass to the
+/// `XrGraphicsBindingVulkan2KHR`.
+///
+/// [`Device::queue_family_index`]: vulkan::Device::queue_family_index
+/// [`Device::queue_index`]: vulkan::Device::queue_index
+///
+/// # Safety
+///
+/// - `adapter` must be exposed for the physical device returned by
+///   `xrGetVulkanGraphicsDevice2KHR`.
+/// - `create_raw` must create the device from `create_info`.
+pub unsafe fn open_device(
+    adapter: &vulkan::Adapter,
+    features: wgt::Features,
+    memory_hints: &wgt::MemoryHints,
+    create_raw: impl FnOnce(&vk::DeviceCreateInfo) -> Result<vk::Device, crate::DeviceError>,
+) -> Result<crate::OpenDevice<vulkan::Api>, crate::DeviceError> {
+    let enabled_extensions = adapter.required_device_extensions(features);
+    let mut enabled_phd_features = adapter.physical_device_features(&enabled_extensions, features);
+
+    let family_index = 0; // Matches `Adapter::open`.
+    let family_info = vk::DeviceQueueCreateInfo::default()
+        .queue_family_index(family_index)
+        .queue_priorities(&[1.0]);
+    let family_infos = [family_info];
+
+    let str_pointers = enabled_extensions
+        .iter()
+        .map(|&s| {
+            // Safe because `enabled_extensions` entries have static lifetime.
+            s.as_ptr()
+        })
+        .collect::<Vec<_>>();
+
+    let pre_info = vk::DeviceCreateInfo::default()
+        .queue_create_infos(&family_infos)
+        .enabled_extension_names(&str_pointers);
+    let create_info = enabled_phd_features.add_to_device_create(pre_info);
+
+    let raw = create_raw(&create_info)?;
+    let raw_device =
+        unsafe { ash::Device::load(adapter.shared_instance().raw_instance().fp_v1_0(), raw) };
+
+    unsafe {
+        adapter.device_from_raw(
+            raw_device,
+            true,
+            &enabled_extensions,
+            features,
+            family_index,
+            0,
+            memory_hints,
+        )
+    }
+}
+
+/// Returns the `format` for the `XrSwapchainCreateInfo`, a `VkFormat`.
+///
+/// Check that it's one of the formats returned by
+/// `xrEnumerateSwapchainFormats` before creating the swapchain.
+pub fn swapchain_format(adapter: &vulkan::Adapter, format: wgt::TextureFormat) -> i64 {
+    adapter.texture_format_as_raw(format).as_raw().into()
+}
+
+/// Wraps an image of the swapchain created from `desc`, as returned by
+/// `xrEnumerateSwapchainImages`.
+///
+/// The image stays owned by the runtime: the texture doesn't destroy it.
+///
+/// # Safety
+///
+/// - `raw_image` must be an image of a swapchain created from `desc`, of a
+///   session using the device the texture is used with.
+/// - The texture must not be used after the swapchain is destroyed.
+pub unsafe fn texture_from_swapchain_image(
+    raw_image: vk::Image,
+    desc: &SwapchainDescriptor,
+) -> vulkan::Texture {
+    unsafe {
+        vulkan::Device::texture_from_raw(
+            raw_image,
+            &desc.texture_descriptor(Some("XR swapchain image")),
+            Some(Box::new(())),
+        )
+    }
+}
@@ -246,7 +246,7 @@ bitflags::bitflags! {
     #[repr(transparent)]
     #[derive(Default)]
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-    pub struct Features: u64 {
+    pub struct Features: u128 {
         //
         // ---- Start numbering at 1 << 0 ----
         //
@@ -590,6 +590,7 @@ bitflags::bitflags! {
         /// values.
         ///
         /// Supported platforms:
+        /// - DX12
         /// - Metal (with MSL 2.2+ on macOS 10.13+)
         /// - Vulkan
         ///
@@ -887,6 +888,7 @@ bitflags::bitflags! {
         /// - Vulkan
         /// - DX12 (DXC only)
         /// - Metal (with MSL 2.3+)
+        /// - OpenGL (with GL 4.0+ and GL_ARB_gpu_shader_int64)
         ///
         /// This is a native only feature.
         const SHADER_INT64 = 1 << 55;
@@ -914,6 +916,167 @@ bitflags::bitflags! {
         ///
         /// This is a native only feature.
         const SUBGROUP_BARRIER = 1 << 58;
+        /// Allows compute and fragment shaders to use clustered subgroup reductions
+        /// (`subgroupClusteredAdd` etc.) and quad operations (`quadBroadcast`, `quadSwapX`,
+        /// `quadSwapY`, `quadSwapDiagonal`).
+        ///
+        /// Requires [`Features::SUBGROUP`].
+        ///
+        /// Supported Platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const SUBGROUP_EXTENDED = 1 << 59;
+        /// Allows shaders to perform atomic operations on storage textures with the
+        /// [`TextureFormat::R32Uint`] and [`TextureFormat::R32Sint`] formats, using the
+        /// `textureAtomic*` functions on textures declared with the `atomic` access mode.
+        ///
+        /// Such textures must be bound with [`StorageTextureAccess::Atomic`].
+        ///
+        /// Supported Platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal (with MSL 3.1+)
+        /// - OpenGL (with GL 4.2+ or GLES 3.1+)
+        ///
+        /// This is a native only feature.
+        const TEXTURE_ATOMIC = 1 << 60;
+        /// Allows shaders to pass pointers in the `storage` and `workgroup` address
+        /// spaces as function arguments, such as `ptr<storage, array<u32>, read_write>`.
+        /// Such pointers can't be stored in `var` locals.
+        ///
+        /// Supported Platforms:
+        /// - Vulkan (with `VK_KHR_variable_pointers`)
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const UNRESTRICTED_POINTER_PARAMETERS = 1 << 61;
+        /// Enables creating shader modules from precompiled DXIL (unsafe).
+        ///
+        /// DXIL data is not parsed or interpreted in any way. It must be a
+        /// validated (signed) DXIL container holding a single entry point,
+        /// as produced by `dxc -T <stage>_6_x`.
+        ///
+        /// Supported platforms:
+        /// - DX12, on devices supporting shader model 6.0+.
+        ///
+        /// This is a native only feature.
+        const DXIL_SHADER_PASSTHROUGH = 1 << 62;
+        /// Enables creating shader modules from Metal Shading Language source
+        /// or a compiled `metallib` (unsafe).
+        ///
+        /// The code isn't validated. It has to bind resources at the buffer,
+        /// texture and sampler indices the pipeline layout assigns them.
+        ///
+        /// Supported platforms:
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const MSL_SHADER_PASSTHROUGH = 1 << 63;
+        /// Allows vertex shaders to output `@builtin(clip_distances)`, an
+        /// array of up to 8 `f32` user clip distances.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `shaderClipDistance`)
+        /// - DX12
+        /// - Metal
+        /// - OpenGL (GL 3.0+ or GLES with `GL_EXT_clip_cull_distance`)
+        ///
+        /// This is a native only feature.
+        const CLIP_DISTANCES = 1 << 64;
+        /// Allows vertex shaders to output `@builtin(cull_distances)`, an
+        /// array of up to 8 `f32` user cull distances.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `shaderCullDistance`)
+        /// - DX12
+        /// - OpenGL (GL 4.5+ or GLES with `GL_EXT_clip_cull_distance`)
+        ///
+        /// This is a native only feature.
+        const CULL_DISTANCES = 1 << 65;
+        /// Allows fragment shaders to read `@builtin(barycentric_coords)`, the
+        /// perspective-correct barycentric coordinates of the fragment within
+        /// its primitive.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `VK_KHR_fragment_shader_barycentric`)
+        /// - DX12 (with `BarycentricsSupported`, shader model 6.1+)
+        /// - Metal (on devices supporting barycentric coordinates, MSL 2.2+)
+        ///
+        /// This is a native only feature.
+        const SHADER_BARYCENTRICS = 1 << 66;
+        /// Allows shaders to use the `textureSample*Residency` builtins, which
+        /// return a `texel` together with a `resident` flag telling whether
+        /// all the texels the sample accessed were resident in memory.
+        ///
+        /// This is intended for virtual texturing with partially-resident
+        /// textures. wgpu cannot create those yet, so until it can, every
+        /// sample reports `resident: true`.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `shaderResourceResidency`)
+        ///
+        /// This is a native only feature.
+        const SHADER_RESOURCE_RESIDENCY = 1 << 67;
+        /// Allows compute shaders to compute derivatives with `dpdx` and
+        /// friends, and to sample textures with an implicit level of detail,
+        /// over 2x2 quads of invocations in the workgroup.
+        ///
+        /// Entry points doing so must have a workgroup width and height that
+        /// are multiples of 2. Quad subgroup operations are already available in
+        /// compute shaders with [`Features::SUBGROUP_EXTENDED`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `VK_NV_compute_shader_derivatives`)
+        /// - DX12 (shader model 6.6+)
+        ///
+        /// This is a native only feature.
+        const COMPUTE_SHADER_DERIVATIVES = 1 << 68;
+        /// Allows creating pipeline caches, which store the compiled code of
+        /// the pipelines created with them. The contents of a cache can be
+        /// saved, and given to a cache in a later run to create the same
+        /// pipelines faster.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - Metal (macOS 11+, iOS 14+)
+        /// - DX12
+        ///
+        /// This is a native only feature.
+        const PIPELINE_CACHE = 1 << 69;
+        /// Allows creating textures shared with other processes, and
+        /// importing them from the handles they are exported as.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `VK_KHR_external_memory_fd` on Unix, or
+        ///   `VK_KHR_external_memory_win32` on Windows)
+        /// - DX12
+        ///
+        /// Textures are shared as opaque file descriptors or NT handles only.
+        /// DMA-BUF file descriptors on Linux and `IOSurface`s on macOS aren't
+        /// supported, so Metal doesn't expose this feature.
+        ///
+        /// This is a native only feature.
+        const SHARED_TEXTURES = 1 << 70;
+        /// Allows textures of format [`TextureFormat::Depth32Float`], and the
+        /// depth aspect of [`TextureFormat::Depth32FloatStencil8`] textures, to
+        /// be the destination of `Queue::write_texture` and
+        /// `CommandEncoder::copy_buffer_to_texture`, to upload depth data.
+        ///
+        /// The written values must be in the 0.0 to 1.0 range.
+        ///
+        /// Stencil aspects, and [`TextureFormat::Depth16Unorm`] textures, can
+        /// always be written.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const DEPTH32FLOAT_COPY_DST = 1 << 71;
     }
 }
 
@@ -1770,6 +1933,9 @@ pub struct DeviceDescriptor<L> {
     /// Exactly the specified limits, and no better or worse,
     /// will be allowed in validation of API calls on the resulting device.
     pub required_limits: Limits,
+    /// Hints for how the device should allocate memory.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory_hints: MemoryHints,
 }
 
 impl<L> DeviceDescriptor<L> {
@@ -1779,10 +1945,191 @@ impl<L> DeviceDescriptor<L> {
             label: fun(&self.label),
             required_features: self.required_features,
             required_limits: self.required_limits.clone(),
+            memory_hints: self.memory_hints.clone(),
         }
     }
 }
 
+/// Hints for how a device should allocate memory for its resources.
+///
+/// These don't change what the device allows, and backends that have no use
+/// for a hint ignore it. The defaults are the backend's own choices, which
+/// suit most applications.
+///
+/// Currently only the Vulkan backend uses these hints.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MemoryHints {
+    /// The size in bytes of the blocks of device memory that small
+    /// resources are suballocated from, or `None` to let the backend choose.
+    ///
+    /// Larger blocks reduce the number of device memory allocations, smaller
+    /// ones waste less memory when few resources are alive.
+    pub block_size: Option<u64>,
+    /// The size in bytes from which resources get a device memory allocation
+    /// of their own, rather than being suballocated from a block, or `None`
+    /// to let the backend choose.
+    pub dedicated_allocation_threshold: Option<u64>,
+    /// Place mappable buffers that the GPU uses directly, for example
+    /// uniform buffers the host rewrites every frame, in memory that is both
+    /// device-local and host-visible if there is any.
+    ///
+    /// Such buffers can only be created with
+    /// [`Features::MAPPABLE_PRIMARY_BUFFERS`]. With resizable BAR, all of the
+    /// GPU's memory is host-visible and this makes the GPU's accesses to
+    /// them faster. Without it, such memory is usually limited to 256MiB.
+    pub prefer_device_local_host_visible: bool,
+}
+
+/// How many objects a device may keep in its internal caches.
+///
+/// Some backends create objects WebGPU has no equivalent for on demand, and
+/// cache them for reuse. Once a cache holds more objects than its limit, the
+/// least recently used ones are evicted. Backends ignore the limits of objects
+/// they don't cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceCacheLimits {
+    /// Render passes, cached by Vulkan.
+    pub render_passes: u32,
+    /// Framebuffers, cached by Vulkan.
+    pub framebuffers: u32,
+}
+
+impl Default for DeviceCacheLimits {
+    fn default() -> Self {
+        Self {
+            render_passes: 512,
+            framebuffers: 1024,
+        }
+    }
+}
+
+/// Statistics of one of a device's internal caches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CacheStatistics {
+    /// Number of objects in the cache.
+    pub entries: u32,
+    /// Maximum number of objects in the cache, see [`DeviceCacheLimits`].
+    pub capacity: u32,
+    /// Number of evicted objects that are still used by command buffers, and
+    /// will be destroyed once they are done executing.
+    pub pending_destruction: u32,
+    /// Number of lookups that found their object in the cache.
+    pub hits: u64,
+    /// Number of lookups that had to create their object.
+    pub misses: u64,
+    /// Number of objects evicted to stay within the cache's capacity, or by
+    /// trimming the cache.
+    pub evictions: u64,
+}
+
+/// Statistics of a device's internal caches.
+///
+/// The caches of a backend that doesn't use them are always empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceCacheReport {
+    /// Render passes, cached by Vulkan.
+    pub render_passes: CacheStatistics,
+    /// Framebuffers, cached by Vulkan.
+    pub framebuffers: CacheStatistics,
+}
+
+/// Statistics of a device's memory allocator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AllocatorReport {
+    /// Number of live allocations, of buffers, textures and other resources.
+    pub allocation_count: u64,
+    /// Total size of the live allocations, in bytes.
+    pub allocated_bytes: u64,
+    /// Number of memory blocks allocated from the driver, which allocations
+    /// are suballocated from.
+    pub block_count: u64,
+    /// Total size of the memory blocks, in bytes.
+    pub reserved_bytes: u64,
+}
+
+/// A live buffer, in a [`LiveResourceReport`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BufferReport {
+    /// Debug label of the buffer, empty if it has none.
+    pub label: String,
+    /// Size of the buffer, in bytes.
+    pub size: BufferAddress,
+    /// Allowed usages of the buffer.
+    pub usage: BufferUsages,
+}
+
+/// A live texture, in a [`LiveResourceReport`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextureReport {
+    /// Debug label of the texture, empty if it has none.
+    pub label: String,
+    /// Size of the texture, in texels.
+    pub size: Extent3d,
+    /// Mip count of the texture.
+    pub mip_level_count: u32,
+    /// Sample count of the texture.
+    pub sample_count: u32,
+    /// Dimensions of the texture.
+    pub dimension: TextureDimension,
+    /// Format of the texture.
+    pub format: TextureFormat,
+    /// Allowed usages of the texture.
+    pub usage: TextureUsages,
+}
+
+/// The resources of a device that are alive, by their labels.
+///
+/// Resources are alive until they're dropped, or until the device is done
+/// with the submissions that use them, whichever is later.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LiveResourceReport {
+    /// The live buffers.
+    pub buffers: Vec<BufferReport>,
+    /// The live textures.
+    pub textures: Vec<TextureReport>,
+    /// Labels of the live texture views.
+    pub texture_views: Vec<String>,
+    /// Labels of the live samplers.
+    pub samplers: Vec<String>,
+    /// Labels of the live bind groups.
+    pub bind_groups: Vec<String>,
+    /// Labels of the live compute pipelines.
+    pub compute_pipelines: Vec<String>,
+    /// Labels of the live render pipelines.
+    pub render_pipelines: Vec<String>,
+    /// Labels of the live render bundles.
+    pub render_bundles: Vec<String>,
+    /// Labels of the live query sets.
+    pub query_sets: Vec<String>,
+}
+
+/// A snapshot of a device and its adapter, to attach to bug reports.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceDiagnostics {
+    /// Information about the adapter of the device.
+    pub adapter_info: AdapterInfo,
+    /// The features enabled on the device.
+    pub features: Features,
+    /// The limits of the device.
+    pub limits: Limits,
+    /// The live resources of the device.
+    pub resources: LiveResourceReport,
+    /// Statistics of the memory allocator, on backends that keep them.
+    pub allocator: Option<AllocatorReport>,
+    /// Statistics of the device's caches.
+    pub caches: DeviceCacheReport,
+}
+
 bitflags::bitflags! {
     /// Describes the shader stages that a binding will be visible from.
     ///
@@ -4519,6 +4866,31 @@ impl MaintainResult {
     }
 }
 
+/// How the command buffers given to `Queue::submit` are sent to the backend.
+///
+/// Each backend submission has a cost, that adds up for applications and
+/// libraries submitting once per pass. Batching submissions sends them to the
+/// backend together, in order, as a single submission.
+///
+/// On WebGPU, this has no effect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SubmissionBatching {
+    /// Every call to `Queue::submit` is sent to the backend right away.
+    #[default]
+    Immediate,
+    /// Calls to `Queue::submit` are held back, and sent to the backend
+    /// together when `max_submissions` of them are held, or when
+    /// `Queue::flush` is called.
+    ///
+    /// Held back submissions are also sent when waiting on the device with
+    /// `Device::poll`, and when presenting a surface texture.
+    Batched {
+        /// The number of submissions to hold back at most.
+        max_submissions: u32,
+    },
+}
+
 /// State of the stencil operation (fixed-pipeline stage).
 ///
 /// For use in [`DepthStencilState`].
@@ -6309,6 +6681,24 @@ pub enum StorageTextureAccess {
     /// layout(set=0, binding=0, r32f) uniform image2D myStorageImage;
     /// ```
     ReadWrite,
+    /// The texture can be read, written, and used with atomic operations in the shader. It must
+    /// be annotated with `atomic` in WGSL and have the [`TextureFormat::R32Uint`] or
+    /// [`TextureFormat::R32Sint`] format.
+    ///
+    /// [`Features::TEXTURE_ATOMIC`] must be enabled to use this access mode. This is a
+    /// nonstandard, native-only extension.
+    ///
+    /// Example WGSL syntax:
+    /// ```rust,ignore
+    /// @group(0) @binding(0)
+    /// var my_storage_image: texture_storage_2d<r32uint, atomic>;
+    /// ```
+    ///
+    /// Example GLSL syntax:
+    /// ```cpp,ignore
+    /// layout(set=0, binding=0, r32ui) uniform uimage2D myStorageImage;
+    /// ```
+    Atomic,
 }
 
 /// Specific type of a sampler binding.
@@ -7036,6 +7426,87 @@ impl Default for ShaderBoundChecks {
     }
 }
 
+/// Interface of a shader module, as seen by the pipelines using it.
+///
+/// This is enough to create the bind group layouts and vertex buffer layouts
+/// a module expects without inspecting its source.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShaderReflection {
+    /// The entry points of the module, in declaration order.
+    pub entry_points: Vec<EntryPointReflection>,
+}
+
+impl ShaderReflection {
+    /// Returns the entry point called `name` for the given `stage`, if any.
+    pub fn entry_point(&self, stage: ShaderStages, name: &str) -> Option<&EntryPointReflection> {
+        self.entry_points
+            .iter()
+            .find(|ep| ep.stage == stage && ep.name == name)
+    }
+}
+
+/// Interface of a single entry point of a shader module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EntryPointReflection {
+    /// Name of the entry point.
+    pub name: String,
+    /// Stage of the entry point, exactly one of `VERTEX`, `FRAGMENT` or `COMPUTE`.
+    pub stage: ShaderStages,
+    /// Resources statically used by the entry point.
+    ///
+    /// The layout entries are visible from [`Self::stage`] only, and describe the
+    /// least restrictive binding type the shader accepts.
+    pub bindings: Vec<BindingReflection>,
+    /// Vertex attributes read by a vertex entry point, sorted by location.
+    ///
+    /// Attributes whose type can't be fed by any [`VertexFormat`] are omitted.
+    pub vertex_inputs: Vec<VertexInputReflection>,
+    /// Color attachments written by a fragment entry point, sorted by location.
+    pub color_outputs: Vec<ColorOutputReflection>,
+    /// Workgroup size of a compute entry point, `[0; 3]` for other stages.
+    pub workgroup_size: [u32; 3],
+    /// Push constant memory used by the entry point.
+    pub push_constant_ranges: Vec<PushConstantRange>,
+}
+
+/// A resource binding used by an entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BindingReflection {
+    /// Name of the resource in the shader, if it has one.
+    pub name: Option<String>,
+    /// Index of the bind group the resource is bound to.
+    pub group: u32,
+    /// Layout entry describing the resource.
+    pub entry: BindGroupLayoutEntry,
+}
+
+/// A vertex attribute read by a vertex entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VertexInputReflection {
+    /// Location of the attribute.
+    pub location: ShaderLocation,
+    /// The vertex format matching the attribute's type in the shader.
+    ///
+    /// Other formats, e.g. normalized ones, may be used for the same attribute.
+    pub format: VertexFormat,
+}
+
+/// A color attachment written by a fragment entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColorOutputReflection {
+    /// Location of the output, which is the index of the color target.
+    pub location: ShaderLocation,
+    /// Sample type of the texture formats this output can be written to.
+    pub sample_type: TextureSampleType,
+    /// Number of components written.
+    pub components: u32,
+}
+
 /// Selects which DX12 shader compiler to use.
 ///
 /// If the `wgpu-hal/dx12-shader-compiler` feature isn't enabled then this will fall back
@@ -13,6 +13,6 @@ fn main() {
         metal: { all(any(target_os = "ios", target_os = "macos"), feature = "metal") },
         // This alias is _only_ if _we_ need naga in the wrapper. wgpu-core provides
         // its own re-export of naga, which can be used in other situations
-        naga: { any(feature = "naga-ir", feature = "spirv", feature = "glsl") },
+        naga: { any(feature = "naga-ir", feature = "spirv", feature = "glsl", feature = "hlsl") },
     }
 }
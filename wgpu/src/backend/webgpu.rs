@@ -1135,6 +1135,8 @@ impl crate::context::Context for ContextWebGpu {
     type TextureData = Sendable<webgpu_sys::GpuTexture>;
     type QuerySetId = Identified<webgpu_sys::GpuQuerySet>;
     type QuerySetData = Sendable<webgpu_sys::GpuQuerySet>;
+    type PipelineCacheId = Unused;
+    type PipelineCacheData = ();
     type PipelineLayoutId = Identified<webgpu_sys::GpuPipelineLayout>;
     type PipelineLayoutData = Sendable<webgpu_sys::GpuPipelineLayout>;
     type RenderPipelineId = Identified<webgpu_sys::GpuRenderPipeline>;
@@ -1530,6 +1532,7 @@ impl crate::context::Context for ContextWebGpu {
         not(any(
             feature = "spirv",
             feature = "glsl",
+            feature = "hlsl",
             feature = "wgsl",
             feature = "naga-ir"
         )),
@@ -1554,7 +1557,7 @@ impl crate::context::Context for ContextWebGpu {
                 };
                 let spv_parser = front::spv::Frontend::new(spv.iter().cloned(), &options);
                 spv_parser
-                    .parse()
+                    .parse_with_span()
                     .map_err(|inner| {
                         CompilationInfo::from(naga::error::ShaderError {
                             source: String::new(),
@@ -1578,6 +1581,7 @@ impl crate::context::Context for ContextWebGpu {
                 ref shader,
                 stage,
                 ref defines,
+                ref combined_samplers,
             } => {
                 use naga::front;
 
@@ -1585,6 +1589,7 @@ impl crate::context::Context for ContextWebGpu {
                 let options = front::glsl::Options {
                     stage,
                     defines: defines.clone(),
+                    combined_samplers: combined_samplers.clone(),
                 };
                 let mut parser = front::glsl::Frontend::default();
                 parser
@@ -1607,6 +1612,39 @@ impl crate::context::Context for ContextWebGpu {
                         })
                     })
             }
+            #[cfg(feature = "hlsl")]
+            crate::ShaderSource::Hlsl {
+                ref shader,
+                ref defines,
+            } => {
+                use naga::front;
+
+                // Parse the given shader code and store its representation.
+                let options = front::hlsl::Options {
+                    defines: defines.clone(),
+                    ..Default::default()
+                };
+                let mut parser = front::hlsl::Frontend::default();
+                parser
+                    .parse(&options, shader)
+                    .map_err(|inner| {
+                        CompilationInfo::from(naga::error::ShaderError {
+                            source: shader.to_string(),
+                            label: desc.label.map(|s| s.to_string()),
+                            inner: Box::new(inner),
+                        })
+                    })
+                    .and_then(|hlsl_module| {
+                        validate_transformed_shader_module(&hlsl_module, shader, &desc).map(|v| {
+                            (
+                                v,
+                                WebShaderCompilationInfo::Transformed {
+                                    compilation_info: CompilationInfo { messages: vec![] },
+                                },
+                            )
+                        })
+                    })
+            }
             #[cfg(feature = "wgsl")]
             crate::ShaderSource::Wgsl(ref code) => {
                 let shader_module = webgpu_sys::GpuShaderModuleDescriptor::new(code);
@@ -1617,6 +1655,14 @@ impl crate::context::Context for ContextWebGpu {
                     },
                 ))
             }
+            #[cfg(feature = "wgsl")]
+            crate::ShaderSource::WgslWithIncludes { .. } => Err(CompilationInfo {
+                messages: vec![crate::CompilationMessage {
+                    message: "WGSL includes are not supported on the WebGPU backend".to_string(),
+                    message_type: crate::CompilationMessageType::Error,
+                    location: None,
+                }],
+            }),
             #[cfg(feature = "naga-ir")]
             crate::ShaderSource::Naga(ref module) => {
                 validate_transformed_shader_module(module, "", &desc).map(|v| {
@@ -1628,6 +1674,26 @@ impl crate::context::Context for ContextWebGpu {
                     )
                 })
             }
+            #[cfg(feature = "precompiled")]
+            crate::ShaderSource::Precompiled(ref bytes) => match naga::precompiled::read(bytes) {
+                Ok(precompiled) => {
+                    validate_transformed_shader_module(&precompiled.module, "", &desc).map(|v| {
+                        (
+                            v,
+                            WebShaderCompilationInfo::Transformed {
+                                compilation_info: CompilationInfo { messages: vec![] },
+                            },
+                        )
+                    })
+                }
+                Err(error) => Err(CompilationInfo {
+                    messages: vec![crate::CompilationMessage {
+                        message: error.to_string(),
+                        message_type: crate::CompilationMessageType::Error,
+                        location: None,
+                    }],
+                }),
+            },
             crate::ShaderSource::Dummy(_) => {
                 panic!("found `ShaderSource::Dummy`")
             }
@@ -1683,6 +1749,24 @@ impl crate::context::Context for ContextWebGpu {
         unreachable!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
     }
 
+    unsafe fn device_create_shader_module_dxil(
+        &self,
+        _device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+        _desc: &crate::ShaderModuleDescriptorDxil<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData) {
+        unreachable!("DXIL_SHADER_PASSTHROUGH is not enabled for this backend")
+    }
+
+    unsafe fn device_create_shader_module_msl(
+        &self,
+        _device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+        _desc: &crate::ShaderModuleDescriptorMsl<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData) {
+        unreachable!("MSL_SHADER_PASSTHROUGH is not enabled for this backend")
+    }
+
     fn device_create_bind_group_layout(
         &self,
         _device: &Self::DeviceId,
@@ -1761,6 +1845,9 @@ impl crate::context::Context for ContextWebGpu {
                             wgt::StorageTextureAccess::ReadWrite => {
                                 webgpu_sys::GpuStorageTextureAccess::ReadWrite
                             }
+                            wgt::StorageTextureAccess::Atomic => {
+                                panic!("Atomic storage textures are not supported on WebGPU")
+                            }
                         };
                         let mut storage_texture = webgpu_sys::GpuStorageTextureBindingLayout::new(
                             map_texture_format(format),
@@ -2083,6 +2170,17 @@ impl crate::context::Context for ContextWebGpu {
         create_identified(device_data.0.create_query_set(&mapped_desc))
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        _device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+        _desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> (Self::PipelineCacheId, Self::PipelineCacheData) {
+        // WebGPU has no pipeline caches, and pipelines ignore the one
+        // returned here.
+        (Unused, ())
+    }
+
     fn device_create_command_encoder(
         &self,
         _device: &Self::DeviceId,
@@ -2284,6 +2382,15 @@ impl crate::context::Context for ContextWebGpu {
         )
     }
 
+    fn shader_module_reflect(
+        &self,
+        _shader: &Self::ShaderModuleId,
+        _shader_data: &Self::ShaderModuleData,
+    ) -> Option<wgt::ShaderReflection> {
+        // The browser doesn't expose the module's interface.
+        None
+    }
+
     fn texture_create_view(
         &self,
         _texture: &Self::TextureId,
@@ -2352,6 +2459,22 @@ impl crate::context::Context for ContextWebGpu {
         // Dropped automatically
     }
 
+    fn pipeline_cache_get_data(
+        &self,
+        _cache: &Self::PipelineCacheId,
+        _cache_data: &Self::PipelineCacheData,
+    ) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn pipeline_cache_drop(
+        &self,
+        _cache: &Self::PipelineCacheId,
+        _cache_data: &Self::PipelineCacheData,
+    ) {
+        // Nothing to drop
+    }
+
     fn bind_group_drop(
         &self,
         _bind_group: &Self::BindGroupId,
@@ -2442,6 +2565,26 @@ impl crate::context::Context for ContextWebGpu {
         create_identified(pipeline_data.0.get_bind_group_layout(index))
     }
 
+    fn compute_pipeline_get_binding(
+        &self,
+        _pipeline: &Self::ComputePipelineId,
+        _pipeline_data: &Self::ComputePipelineData,
+        _name: &str,
+    ) -> Option<(u32, u32)> {
+        // The browser doesn't expose the names of the pipeline's resources.
+        None
+    }
+
+    fn render_pipeline_get_binding(
+        &self,
+        _pipeline: &Self::RenderPipelineId,
+        _pipeline_data: &Self::RenderPipelineData,
+        _name: &str,
+    ) -> Option<(u32, u32)> {
+        // The browser doesn't expose the names of the pipeline's resources.
+        None
+    }
+
     fn command_encoder_copy_buffer_to_buffer(
         &self,
         _encoder: &Self::CommandEncoderId,
@@ -2978,8 +3121,61 @@ impl crate::context::Context for ContextWebGpu {
         unimplemented!()
     }
 
+    fn queue_set_submission_batching(
+        &self,
+        _queue: &Self::QueueId,
+        _queue_data: &Self::QueueData,
+        _batching: wgt::SubmissionBatching,
+    ) {
+        // The browser decides how submissions are sent to the GPU.
+    }
+
+    fn queue_flush(&self, _queue: &Self::QueueId, _queue_data: &Self::QueueData) {}
+
     fn device_start_capture(&self, _device: &Self::DeviceId, _device_data: &Self::DeviceData) {}
     fn device_stop_capture(&self, _device: &Self::DeviceId, _device_data: &Self::DeviceData) {}
+    fn device_set_cache_limits(
+        &self,
+        _device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+        _limits: &wgt::DeviceCacheLimits,
+    ) {
+    }
+    fn device_trim_caches(&self, _device: &Self::DeviceId, _device_data: &Self::DeviceData) {}
+    fn device_cache_report(
+        &self,
+        _device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+    ) -> wgt::DeviceCacheReport {
+        wgt::DeviceCacheReport::default()
+    }
+    fn device_capture_diagnostics(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+    ) -> crate::DiagnosticReport {
+        // The browser doesn't expose the adapter, the resources or the
+        // allocator, and handles error scopes itself.
+        crate::DiagnosticReport {
+            device: wgt::DeviceDiagnostics {
+                adapter_info: wgt::AdapterInfo {
+                    name: String::new(),
+                    vendor: 0,
+                    device: 0,
+                    device_type: wgt::DeviceType::Other,
+                    driver: String::new(),
+                    driver_info: String::new(),
+                    backend: wgt::Backend::BrowserWebGpu,
+                },
+                features: self.device_features(device, device_data),
+                limits: self.device_limits(device, device_data),
+                resources: wgt::LiveResourceReport::default(),
+                allocator: None,
+                caches: wgt::DeviceCacheReport::default(),
+            },
+            error_scopes: Vec::new(),
+        }
+    }
 
     fn compute_pass_set_pipeline(
         &self,
@@ -5,9 +5,9 @@ use crate::{
     CompilationMessageType, ComputePassDescriptor, ComputePipelineDescriptor,
     DownlevelCapabilities, Features, Label, Limits, LoadOp, MapMode, Operations,
     PipelineLayoutDescriptor, RenderBundleEncoderDescriptor, RenderPipelineDescriptor,
-    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, ShaderSource, StoreOp,
-    SurfaceStatus, SurfaceTargetUnsafe, TextureDescriptor, TextureViewDescriptor,
-    UncapturedErrorHandler,
+    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorDxil,
+    ShaderModuleDescriptorMsl, ShaderModuleDescriptorSpirV, ShaderSource, StoreOp, SurfaceStatus,
+    SurfaceTargetUnsafe, TextureDescriptor, TextureViewDescriptor, UncapturedErrorHandler,
 };
 
 use arrayvec::ArrayVec;
@@ -16,6 +16,7 @@ use smallvec::SmallVec;
 use std::{
     any::Any,
     borrow::Cow::{Borrowed, Owned},
+    collections::VecDeque,
     error::Error,
     fmt,
     future::{ready, Ready},
@@ -242,6 +243,165 @@ impl ContextWgpuCore {
         }
     }
 
+    pub unsafe fn buffer_as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Buffer>) -> R, R>(
+        &self,
+        buffer_id: wgc::id::BufferId,
+        hal_buffer_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .buffer_as_hal::<A, F, R>(buffer_id, hal_buffer_callback)
+        }
+    }
+
+    pub unsafe fn sampler_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::Sampler>) -> R,
+        R,
+    >(
+        &self,
+        sampler_id: wgc::id::SamplerId,
+        hal_sampler_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .sampler_as_hal::<A, F, R>(sampler_id, hal_sampler_callback)
+        }
+    }
+
+    pub unsafe fn bind_group_layout_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::BindGroupLayout>) -> R,
+        R,
+    >(
+        &self,
+        bind_group_layout_id: wgc::id::BindGroupLayoutId,
+        hal_bind_group_layout_callback: F,
+    ) -> R {
+        unsafe {
+            self.0.bind_group_layout_as_hal::<A, F, R>(
+                bind_group_layout_id,
+                hal_bind_group_layout_callback,
+            )
+        }
+    }
+
+    pub unsafe fn bind_group_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::BindGroup>) -> R,
+        R,
+    >(
+        &self,
+        bind_group_id: wgc::id::BindGroupId,
+        hal_bind_group_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .bind_group_as_hal::<A, F, R>(bind_group_id, hal_bind_group_callback)
+        }
+    }
+
+    pub unsafe fn pipeline_layout_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::PipelineLayout>) -> R,
+        R,
+    >(
+        &self,
+        pipeline_layout_id: wgc::id::PipelineLayoutId,
+        hal_pipeline_layout_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .pipeline_layout_as_hal::<A, F, R>(pipeline_layout_id, hal_pipeline_layout_callback)
+        }
+    }
+
+    pub unsafe fn shader_module_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::ShaderModule>) -> R,
+        R,
+    >(
+        &self,
+        shader_module_id: wgc::id::ShaderModuleId,
+        hal_shader_module_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .shader_module_as_hal::<A, F, R>(shader_module_id, hal_shader_module_callback)
+        }
+    }
+
+    pub unsafe fn render_pipeline_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::RenderPipeline>) -> R,
+        R,
+    >(
+        &self,
+        render_pipeline_id: wgc::id::RenderPipelineId,
+        hal_render_pipeline_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .render_pipeline_as_hal::<A, F, R>(render_pipeline_id, hal_render_pipeline_callback)
+        }
+    }
+
+    pub unsafe fn compute_pipeline_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::ComputePipeline>) -> R,
+        R,
+    >(
+        &self,
+        compute_pipeline_id: wgc::id::ComputePipelineId,
+        hal_compute_pipeline_callback: F,
+    ) -> R {
+        unsafe {
+            self.0.compute_pipeline_as_hal::<A, F, R>(
+                compute_pipeline_id,
+                hal_compute_pipeline_callback,
+            )
+        }
+    }
+
+    pub unsafe fn query_set_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::QuerySet>) -> R,
+        R,
+    >(
+        &self,
+        query_set_id: wgc::id::QuerySetId,
+        hal_query_set_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .query_set_as_hal::<A, F, R>(query_set_id, hal_query_set_callback)
+        }
+    }
+
+    pub unsafe fn device_fence_as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::Fence>) -> R,
+        R,
+    >(
+        &self,
+        device: &Device,
+        hal_fence_callback: F,
+    ) -> R {
+        unsafe {
+            self.0
+                .device_fence_as_hal::<A, F, R>(device.id, hal_fence_callback)
+        }
+    }
+
+    #[cfg(feature = "external_memory")]
+    pub unsafe fn queue_as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Queue>) -> R, R>(
+        &self,
+        queue: &Queue,
+        hal_queue_callback: F,
+    ) -> R {
+        unsafe { self.0.queue_as_hal::<A, F, R>(queue.id, hal_queue_callback) }
+    }
+
     /// This method will start the wgpu_core level command recording.
     pub unsafe fn command_encoder_as_hal_mut<
         A: wgc::hal_api::HalApi,
@@ -260,6 +420,150 @@ impl ContextWgpuCore {
         }
     }
 
+    #[cfg(feature = "shader_cache")]
+    pub fn device_set_shader_cache(
+        &self,
+        device: &Device,
+        cache: Option<Arc<dyn hal::ShaderCache>>,
+    ) {
+        wgc::gfx_select!(device.id => self.0.device_set_shader_cache(device.id, cache))
+    }
+
+    pub fn device_create_shared_texture(
+        &self,
+        device: &Device,
+        desc: &TextureDescriptor<'_>,
+    ) -> Texture {
+        let descriptor = desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec());
+        let (id, error) = wgc::gfx_select!(device.id => self.0.device_create_shared_texture(
+            device.id,
+            &descriptor,
+            None
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_shared_texture",
+            );
+        }
+        Texture {
+            id,
+            error_sink: Arc::clone(&device.error_sink),
+        }
+    }
+
+    pub unsafe fn device_import_shared_texture(
+        &self,
+        device: &Device,
+        handle: hal::ExternalHandle,
+        desc: &TextureDescriptor<'_>,
+    ) -> Texture {
+        let descriptor = desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec());
+        let (id, error) = unsafe {
+            wgc::gfx_select!(device.id => self.0.device_import_shared_texture(
+                device.id,
+                handle,
+                &descriptor,
+                None
+            ))
+        };
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::import_shared_texture",
+            );
+        }
+        Texture {
+            id,
+            error_sink: Arc::clone(&device.error_sink),
+        }
+    }
+
+    pub fn texture_export_shared(
+        &self,
+        texture: &Texture,
+    ) -> Result<hal::ExternalHandle, wgc::resource::SharedTextureError> {
+        wgc::gfx_select!(texture.id => self.0.texture_export_shared(texture.id))
+    }
+
+    pub fn queue_release_shared_texture(&self, queue: &Queue, texture: &Texture) {
+        if let Err(err) = wgc::gfx_select!(
+            queue.id => self.0.queue_release_shared_texture(queue.id, texture.id)
+        ) {
+            self.handle_error_nolabel(&queue.error_sink, err, "Queue::release_shared_texture")
+        }
+    }
+
+    pub fn queue_acquire_shared_texture(&self, queue: &Queue, texture: &Texture) {
+        if let Err(err) = wgc::gfx_select!(
+            queue.id => self.0.queue_acquire_shared_texture(queue.id, texture.id)
+        ) {
+            self.handle_error_nolabel(&queue.error_sink, err, "Queue::acquire_shared_texture")
+        }
+    }
+
+    pub fn device_shares_memory_with(&self, device: &Device, other: &Device) -> bool {
+        device.id.backend() == other.id.backend()
+            && wgc::gfx_select!(device.id => self.0.device_shares_memory_with(device.id, other.id))
+                .unwrap_or(false)
+    }
+
+    pub fn device_create_shared_buffers(
+        &self,
+        device: &Device,
+        other: &Device,
+        desc: &BufferDescriptor<'_>,
+    ) -> ((wgc::id::BufferId, Buffer), (wgc::id::BufferId, Buffer)) {
+        let (id, other_id, error) = wgc::gfx_select!(device.id => self.0.device_create_shared_buffers(
+            device.id,
+            other.id,
+            &desc.map_label(|l| l.map(Borrowed)),
+            None,
+            None
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_shared_buffers",
+            );
+        }
+        (
+            (
+                id,
+                Buffer {
+                    error_sink: Arc::clone(&device.error_sink),
+                },
+            ),
+            (
+                other_id,
+                Buffer {
+                    error_sink: Arc::clone(&other.error_sink),
+                },
+            ),
+        )
+    }
+
+    pub fn queue_wait_for_submission(
+        &self,
+        queue: &Queue,
+        submission: &wgc::device::queue::WrappedSubmissionIndex,
+    ) {
+        if let Err(err) = wgc::gfx_select!(
+            queue.id => self.0.queue_wait_for_submission(queue.id, *submission)
+        ) {
+            self.handle_error_nolabel(&queue.error_sink, err, "Queue::wait_for_submission")
+        }
+    }
+
     pub fn generate_report(&self) -> wgc::global::GlobalReport {
         self.0.generate_report()
     }
@@ -505,6 +809,8 @@ impl crate::Context for ContextWgpuCore {
     type TextureData = Texture;
     type QuerySetId = wgc::id::QuerySetId;
     type QuerySetData = ();
+    type PipelineCacheId = wgc::id::PipelineCacheId;
+    type PipelineCacheData = ();
     type PipelineLayoutId = wgc::id::PipelineLayoutId;
     type PipelineLayoutData = ();
     type RenderPipelineId = wgc::id::RenderPipelineId;
@@ -854,6 +1160,7 @@ impl crate::Context for ContextWgpuCore {
         not(any(
             feature = "spirv",
             feature = "glsl",
+            feature = "hlsl",
             feature = "wgsl",
             feature = "naga-ir"
         )),
@@ -886,14 +1193,42 @@ impl crate::Context for ContextWgpuCore {
                 ref shader,
                 stage,
                 defines,
+                combined_samplers,
             } => {
-                let options = naga::front::glsl::Options { stage, defines };
+                let options = naga::front::glsl::Options {
+                    stage,
+                    defines,
+                    combined_samplers,
+                };
                 wgc::pipeline::ShaderModuleSource::Glsl(Borrowed(shader), options)
             }
+            #[cfg(feature = "hlsl")]
+            ShaderSource::Hlsl {
+                ref shader,
+                defines,
+            } => {
+                let options = naga::front::hlsl::Options {
+                    defines,
+                    ..Default::default()
+                };
+                wgc::pipeline::ShaderModuleSource::Hlsl(Borrowed(shader), options)
+            }
             #[cfg(feature = "wgsl")]
             ShaderSource::Wgsl(ref code) => wgc::pipeline::ShaderModuleSource::Wgsl(Borrowed(code)),
+            #[cfg(feature = "wgsl")]
+            ShaderSource::WgslWithIncludes {
+                ref source,
+                resolve,
+            } => wgc::pipeline::ShaderModuleSource::WgslWithIncludes {
+                source: Borrowed(source),
+                resolve: resolve.0,
+            },
             #[cfg(feature = "naga-ir")]
             ShaderSource::Naga(module) => wgc::pipeline::ShaderModuleSource::Naga(module),
+            #[cfg(feature = "precompiled")]
+            ShaderSource::Precompiled(ref bytes) => {
+                wgc::pipeline::ShaderModuleSource::Precompiled(Borrowed(bytes))
+            }
             ShaderSource::Dummy(_) => panic!("found `ShaderSource::Dummy`"),
         };
         let (id, error) = wgc::gfx_select!(
@@ -947,6 +1282,76 @@ impl crate::Context for ContextWgpuCore {
         (id, ShaderModule { compilation_info })
     }
 
+    unsafe fn device_create_shader_module_dxil(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &ShaderModuleDescriptorDxil<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData) {
+        let descriptor = wgc::pipeline::ShaderModuleDescriptor {
+            label: desc.label.map(Borrowed),
+            // Doesn't matter the value since DXIL shaders aren't mutated to include
+            // runtime checks
+            shader_bound_checks: unsafe { wgt::ShaderBoundChecks::unchecked() },
+        };
+        let (id, error) = wgc::gfx_select!(
+            device => self.0.device_create_shader_module_dxil(*device, &descriptor, Borrowed(&desc.source), None)
+        );
+        let compilation_info = match error {
+            Some(cause) => {
+                self.handle_error(
+                    &device_data.error_sink,
+                    cause.clone(),
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module_dxil",
+                );
+                CompilationInfo::from(cause)
+            }
+            None => CompilationInfo { messages: vec![] },
+        };
+        (id, ShaderModule { compilation_info })
+    }
+
+    unsafe fn device_create_shader_module_msl(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &ShaderModuleDescriptorMsl<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData) {
+        let descriptor = wgc::pipeline::ShaderModuleDescriptor {
+            label: desc.label.map(Borrowed),
+            // Doesn't matter the value since MSL shaders aren't mutated to include
+            // runtime checks
+            shader_bound_checks: unsafe { wgt::ShaderBoundChecks::unchecked() },
+        };
+        let source = match desc.source {
+            crate::MslSource::Source(ref source) => {
+                wgc::pipeline::MslSource::Source(Borrowed(source))
+            }
+            crate::MslSource::Library(ref data) => {
+                wgc::pipeline::MslSource::Library(Borrowed(data))
+            }
+        };
+        let (id, error) = wgc::gfx_select!(
+            device => self.0.device_create_shader_module_msl(*device, &descriptor, source, desc.workgroup_size, None)
+        );
+        let compilation_info = match error {
+            Some(cause) => {
+                self.handle_error(
+                    &device_data.error_sink,
+                    cause.clone(),
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module_msl",
+                );
+                CompilationInfo::from(cause)
+            }
+            None => CompilationInfo { messages: vec![] },
+        };
+        (id, ShaderModule { compilation_info })
+    }
+
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
@@ -1183,6 +1588,7 @@ impl crate::Context for ContextWgpuCore {
                 targets: Borrowed(frag.targets),
             }),
             multiview: desc.multiview,
+            cache: desc.cache.map(|c| c.id.into()),
         };
 
         let (id, error) = wgc::gfx_select!(device => self.0.device_create_render_pipeline(
@@ -1232,6 +1638,7 @@ impl crate::Context for ContextWgpuCore {
                     .compilation_options
                     .zero_initialize_workgroup_memory,
             },
+            cache: desc.cache.map(|c| c.id.into()),
         };
 
         let (id, error) = wgc::gfx_select!(device => self.0.device_create_compute_pipeline(
@@ -1370,6 +1777,33 @@ impl crate::Context for ContextWgpuCore {
         }
         (id, ())
     }
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> (Self::PipelineCacheId, Self::PipelineCacheData) {
+        use wgc::pipeline as pipe;
+
+        let descriptor = pipe::PipelineCacheDescriptor {
+            label: desc.label.map(Borrowed),
+            data: desc.data.map(Borrowed),
+            fallback: desc.fallback,
+        };
+        let (id, error) = wgc::gfx_select!(
+            device => self.0.device_create_pipeline_cache(*device, &descriptor, None)
+        );
+        if let Some(cause) = error {
+            self.handle_error(
+                &device_data.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::device_create_pipeline_cache",
+            );
+        }
+        (id, ())
+    }
     fn device_create_command_encoder(
         &self,
         device: &Self::DeviceId,
@@ -1502,6 +1936,13 @@ impl crate::Context for ContextWgpuCore {
     ) -> Self::PopErrorScopeFuture {
         let mut error_sink = device_data.error_sink.lock();
         let scope = error_sink.scopes.pop().unwrap();
+        if error_sink.recent_scopes.len() == crate::DiagnosticReport::MAX_ERROR_SCOPES {
+            error_sink.recent_scopes.pop_front();
+        }
+        error_sink.recent_scopes.push_back(crate::ErrorScopeReport {
+            filter: scope.filter,
+            error: scope.error.as_ref().map(ToString::to_string),
+        });
         ready(scope.error)
     }
 
@@ -1571,6 +2012,14 @@ impl crate::Context for ContextWgpuCore {
         ready(shader_data.compilation_info.clone())
     }
 
+    fn shader_module_reflect(
+        &self,
+        shader: &Self::ShaderModuleId,
+        _shader_data: &Self::ShaderModuleData,
+    ) -> Option<wgt::ShaderReflection> {
+        wgc::gfx_select!(*shader => self.0.shader_module_reflect(*shader))
+    }
+
     fn texture_create_view(
         &self,
         texture: &Self::TextureId,
@@ -1646,6 +2095,22 @@ impl crate::Context for ContextWgpuCore {
         wgc::gfx_select!(*query_set => self.0.query_set_drop(*query_set))
     }
 
+    fn pipeline_cache_get_data(
+        &self,
+        cache: &Self::PipelineCacheId,
+        _cache_data: &Self::PipelineCacheData,
+    ) -> Option<Vec<u8>> {
+        wgc::gfx_select!(cache => self.0.pipeline_cache_get_data(*cache))
+    }
+
+    fn pipeline_cache_drop(
+        &self,
+        cache: &Self::PipelineCacheId,
+        _cache_data: &Self::PipelineCacheData,
+    ) {
+        wgc::gfx_select!(cache => self.0.pipeline_cache_drop(*cache))
+    }
+
     fn bind_group_drop(
         &self,
         bind_group: &Self::BindGroupId,
@@ -1744,6 +2209,24 @@ impl crate::Context for ContextWgpuCore {
         (id, ())
     }
 
+    fn compute_pipeline_get_binding(
+        &self,
+        pipeline: &Self::ComputePipelineId,
+        _pipeline_data: &Self::ComputePipelineData,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        wgc::gfx_select!(*pipeline => self.0.compute_pipeline_get_binding(*pipeline, name))
+    }
+
+    fn render_pipeline_get_binding(
+        &self,
+        pipeline: &Self::RenderPipelineId,
+        _pipeline_data: &Self::RenderPipelineData,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        wgc::gfx_select!(*pipeline => self.0.render_pipeline_get_binding(*pipeline, name))
+    }
+
     fn command_encoder_copy_buffer_to_buffer(
         &self,
         encoder: &Self::CommandEncoderId,
@@ -2321,6 +2804,24 @@ impl crate::Context for ContextWgpuCore {
         }
     }
 
+    fn queue_set_submission_batching(
+        &self,
+        queue: &Self::QueueId,
+        _queue_data: &Self::QueueData,
+        batching: wgt::SubmissionBatching,
+    ) {
+        let res = wgc::gfx_select!(queue => self.0.queue_set_submission_batching(*queue, batching));
+        if let Err(cause) = res {
+            self.handle_error_fatal(cause, "Queue::set_submission_batching");
+        }
+    }
+
+    fn queue_flush(&self, queue: &Self::QueueId, _queue_data: &Self::QueueData) {
+        if let Err(cause) = wgc::gfx_select!(queue => self.0.queue_flush(*queue)) {
+            self.handle_error_fatal(cause, "Queue::flush");
+        }
+    }
+
     fn device_start_capture(&self, device: &Self::DeviceId, _device_data: &Self::DeviceData) {
         wgc::gfx_select!(device => self.0.device_start_capture(*device));
     }
@@ -2329,6 +2830,52 @@ impl crate::Context for ContextWgpuCore {
         wgc::gfx_select!(device => self.0.device_stop_capture(*device));
     }
 
+    fn device_set_cache_limits(
+        &self,
+        device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+        limits: &wgt::DeviceCacheLimits,
+    ) {
+        wgc::gfx_select!(device => self.0.device_set_cache_limits(*device, limits));
+    }
+
+    fn device_trim_caches(&self, device: &Self::DeviceId, _device_data: &Self::DeviceData) {
+        wgc::gfx_select!(device => self.0.device_trim_caches(*device));
+    }
+
+    fn device_cache_report(
+        &self,
+        device: &Self::DeviceId,
+        _device_data: &Self::DeviceData,
+    ) -> wgt::DeviceCacheReport {
+        match wgc::gfx_select!(device => self.0.device_cache_report(*device)) {
+            Ok(report) => report,
+            Err(err) => self.handle_error_fatal(err, "Device::cache_report"),
+        }
+    }
+
+    fn device_capture_diagnostics(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+    ) -> crate::DiagnosticReport {
+        let diagnostics = match wgc::gfx_select!(device => self.0.device_capture_diagnostics(*device))
+        {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => self.handle_error_fatal(err, "Device::capture_diagnostics"),
+        };
+        crate::DiagnosticReport {
+            device: diagnostics,
+            error_scopes: device_data
+                .error_sink
+                .lock()
+                .recent_scopes
+                .iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
     fn compute_pass_set_pipeline(
         &self,
         _pass: &mut Self::ComputePassId,
@@ -2975,6 +3522,8 @@ struct ErrorScope {
 
 struct ErrorSinkRaw {
     scopes: Vec<ErrorScope>,
+    /// The results of the last popped scopes, for diagnostic reports.
+    recent_scopes: VecDeque<crate::ErrorScopeReport>,
     uncaptured_handler: Box<dyn crate::UncapturedErrorHandler>,
 }
 
@@ -2982,6 +3531,7 @@ impl ErrorSinkRaw {
     fn new() -> ErrorSinkRaw {
         ErrorSinkRaw {
             scopes: Vec::new(),
+            recent_scopes: VecDeque::new(),
             uncaptured_handler: Box::from(default_error_handler),
         }
     }
@@ -3028,6 +3578,8 @@ impl From<CreateShaderModuleError> for CompilationInfo {
             CreateShaderModuleError::Parsing(v) => v.into(),
             #[cfg(feature = "glsl")]
             CreateShaderModuleError::ParsingGlsl(v) => v.into(),
+            #[cfg(feature = "hlsl")]
+            CreateShaderModuleError::ParsingHlsl(v) => v.into(),
             #[cfg(feature = "spirv")]
             CreateShaderModuleError::ParsingSpirV(v) => v.into(),
             CreateShaderModuleError::Validation(v) => v.into(),
@@ -3,19 +3,20 @@ use std::{any::Any, fmt::Debug, future::Future, num::NonZeroU64, ops::Range, pin
 use wgt::{
     strict_assert, strict_assert_eq, AdapterInfo, BufferAddress, BufferSize, Color,
     DeviceLostReason, DownlevelCapabilities, DynamicOffset, Extent3d, Features, ImageDataLayout,
-    ImageSubresourceRange, IndexFormat, Limits, ShaderStages, SurfaceStatus, TextureFormat,
-    TextureFormatFeatures, WasmNotSend, WasmNotSendSync,
+    ImageSubresourceRange, IndexFormat, Limits, ShaderReflection, ShaderStages, SurfaceStatus,
+    TextureFormat, TextureFormatFeatures, WasmNotSend, WasmNotSendSync,
 };
 
 use crate::{
     AnyWasmNotSendSync, BindGroupDescriptor, BindGroupLayoutDescriptor, Buffer, BufferAsyncError,
     BufferDescriptor, CommandEncoderDescriptor, CompilationInfo, ComputePassDescriptor,
     ComputePipelineDescriptor, DeviceDescriptor, Error, ErrorFilter, ImageCopyBuffer,
-    ImageCopyTexture, Maintain, MaintainResult, MapMode, PipelineLayoutDescriptor,
-    QuerySetDescriptor, RenderBundleDescriptor, RenderBundleEncoderDescriptor,
-    RenderPassDescriptor, RenderPipelineDescriptor, RequestAdapterOptions, RequestDeviceError,
-    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, SurfaceTargetUnsafe,
-    Texture, TextureDescriptor, TextureViewDescriptor, UncapturedErrorHandler,
+    ImageCopyTexture, Maintain, MaintainResult, MapMode, PipelineCacheDescriptor,
+    PipelineLayoutDescriptor, QuerySetDescriptor, RenderBundleDescriptor,
+    RenderBundleEncoderDescriptor, RenderPassDescriptor, RenderPipelineDescriptor,
+    RequestAdapterOptions, RequestDeviceError, SamplerDescriptor, ShaderModuleDescriptor,
+    ShaderModuleDescriptorDxil, ShaderModuleDescriptorMsl, ShaderModuleDescriptorSpirV,
+    SurfaceTargetUnsafe, Texture, TextureDescriptor, TextureViewDescriptor, UncapturedErrorHandler,
 };
 
 /// Meta trait for an id tracked by a context.
@@ -53,6 +54,8 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
     type TextureData: ContextData;
     type QuerySetId: ContextId + WasmNotSendSync;
     type QuerySetData: ContextData;
+    type PipelineCacheId: ContextId + WasmNotSendSync;
+    type PipelineCacheData: ContextData;
     type PipelineLayoutId: ContextId + WasmNotSendSync;
     type PipelineLayoutData: ContextData;
     type RenderPipelineId: ContextId + WasmNotSendSync;
@@ -203,6 +206,18 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         device_data: &Self::DeviceData,
         desc: &ShaderModuleDescriptorSpirV<'_>,
     ) -> (Self::ShaderModuleId, Self::ShaderModuleData);
+    unsafe fn device_create_shader_module_dxil(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &ShaderModuleDescriptorDxil<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData);
+    unsafe fn device_create_shader_module_msl(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &ShaderModuleDescriptorMsl<'_>,
+    ) -> (Self::ShaderModuleId, Self::ShaderModuleData);
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
@@ -257,6 +272,12 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         device_data: &Self::DeviceData,
         desc: &QuerySetDescriptor<'_>,
     ) -> (Self::QuerySetId, Self::QuerySetData);
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        desc: &PipelineCacheDescriptor<'_>,
+    ) -> (Self::PipelineCacheId, Self::PipelineCacheData);
     fn device_create_command_encoder(
         &self,
         device: &Self::DeviceId,
@@ -330,6 +351,11 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         shader: &Self::ShaderModuleId,
         shader_data: &Self::ShaderModuleData,
     ) -> Self::CompilationInfoFuture;
+    fn shader_module_reflect(
+        &self,
+        shader: &Self::ShaderModuleId,
+        shader_data: &Self::ShaderModuleData,
+    ) -> Option<ShaderReflection>;
     fn texture_create_view(
         &self,
         texture: &Self::TextureId,
@@ -350,6 +376,16 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
     );
     fn sampler_drop(&self, sampler: &Self::SamplerId, sampler_data: &Self::SamplerData);
     fn query_set_drop(&self, query_set: &Self::QuerySetId, query_set_data: &Self::QuerySetData);
+    fn pipeline_cache_get_data(
+        &self,
+        cache: &Self::PipelineCacheId,
+        cache_data: &Self::PipelineCacheData,
+    ) -> Option<Vec<u8>>;
+    fn pipeline_cache_drop(
+        &self,
+        cache: &Self::PipelineCacheId,
+        cache_data: &Self::PipelineCacheData,
+    );
     fn bind_group_drop(
         &self,
         bind_group: &Self::BindGroupId,
@@ -408,6 +444,18 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         pipeline_data: &Self::RenderPipelineData,
         index: u32,
     ) -> (Self::BindGroupLayoutId, Self::BindGroupLayoutData);
+    fn compute_pipeline_get_binding(
+        &self,
+        pipeline: &Self::ComputePipelineId,
+        pipeline_data: &Self::ComputePipelineData,
+        name: &str,
+    ) -> Option<(u32, u32)>;
+    fn render_pipeline_get_binding(
+        &self,
+        pipeline: &Self::RenderPipelineId,
+        pipeline_data: &Self::RenderPipelineData,
+        name: &str,
+    ) -> Option<(u32, u32)>;
 
     #[allow(clippy::too_many_arguments)]
     fn command_encoder_copy_buffer_to_buffer(
@@ -609,9 +657,33 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         queue_data: &Self::QueueData,
         callback: SubmittedWorkDoneCallback,
     );
+    fn queue_set_submission_batching(
+        &self,
+        queue: &Self::QueueId,
+        queue_data: &Self::QueueData,
+        batching: wgt::SubmissionBatching,
+    );
+    fn queue_flush(&self, queue: &Self::QueueId, queue_data: &Self::QueueData);
 
     fn device_start_capture(&self, device: &Self::DeviceId, device_data: &Self::DeviceData);
     fn device_stop_capture(&self, device: &Self::DeviceId, device_data: &Self::DeviceData);
+    fn device_set_cache_limits(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+        limits: &wgt::DeviceCacheLimits,
+    );
+    fn device_trim_caches(&self, device: &Self::DeviceId, device_data: &Self::DeviceData);
+    fn device_cache_report(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+    ) -> wgt::DeviceCacheReport;
+    fn device_capture_diagnostics(
+        &self,
+        device: &Self::DeviceId,
+        device_data: &Self::DeviceData,
+    ) -> crate::DiagnosticReport;
 
     fn compute_pass_set_pipeline(
         &self,
@@ -1241,6 +1313,18 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         device_data: &crate::Data,
         desc: &ShaderModuleDescriptorSpirV<'_>,
     ) -> (ObjectId, Box<crate::Data>);
+    unsafe fn device_create_shader_module_dxil(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &ShaderModuleDescriptorDxil<'_>,
+    ) -> (ObjectId, Box<crate::Data>);
+    unsafe fn device_create_shader_module_msl(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &ShaderModuleDescriptorMsl<'_>,
+    ) -> (ObjectId, Box<crate::Data>);
     fn device_create_bind_group_layout(
         &self,
         device: &ObjectId,
@@ -1295,6 +1379,12 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         device_data: &crate::Data,
         desc: &QuerySetDescriptor<'_>,
     ) -> (ObjectId, Box<crate::Data>);
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &PipelineCacheDescriptor<'_>,
+    ) -> (ObjectId, Box<crate::Data>);
     fn device_create_command_encoder(
         &self,
         device: &ObjectId,
@@ -1362,6 +1452,11 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         shader: &ObjectId,
         shader_data: &crate::Data,
     ) -> Pin<ShaderCompilationInfoFuture>;
+    fn shader_module_reflect(
+        &self,
+        shader: &ObjectId,
+        shader_data: &crate::Data,
+    ) -> Option<ShaderReflection>;
     fn texture_create_view(
         &self,
         texture: &ObjectId,
@@ -1378,6 +1473,12 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
     fn texture_view_drop(&self, texture_view: &ObjectId, texture_view_data: &crate::Data);
     fn sampler_drop(&self, sampler: &ObjectId, sampler_data: &crate::Data);
     fn query_set_drop(&self, query_set: &ObjectId, query_set_data: &crate::Data);
+    fn pipeline_cache_get_data(
+        &self,
+        cache: &ObjectId,
+        cache_data: &crate::Data,
+    ) -> Option<Vec<u8>>;
+    fn pipeline_cache_drop(&self, cache: &ObjectId, cache_data: &crate::Data);
     fn bind_group_drop(&self, bind_group: &ObjectId, bind_group_data: &crate::Data);
     fn bind_group_layout_drop(
         &self,
@@ -1404,6 +1505,18 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         pipeline_data: &crate::Data,
         index: u32,
     ) -> (ObjectId, Box<crate::Data>);
+    fn compute_pipeline_get_binding(
+        &self,
+        pipeline: &ObjectId,
+        pipeline_data: &crate::Data,
+        name: &str,
+    ) -> Option<(u32, u32)>;
+    fn render_pipeline_get_binding(
+        &self,
+        pipeline: &ObjectId,
+        pipeline_data: &crate::Data,
+        name: &str,
+    ) -> Option<(u32, u32)>;
 
     #[allow(clippy::too_many_arguments)]
     fn command_encoder_copy_buffer_to_buffer(
@@ -1597,9 +1710,33 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         queue_data: &crate::Data,
         callback: SubmittedWorkDoneCallback,
     );
+    fn queue_set_submission_batching(
+        &self,
+        queue: &ObjectId,
+        queue_data: &crate::Data,
+        batching: wgt::SubmissionBatching,
+    );
+    fn queue_flush(&self, queue: &ObjectId, queue_data: &crate::Data);
 
     fn device_start_capture(&self, device: &ObjectId, data: &crate::Data);
     fn device_stop_capture(&self, device: &ObjectId, data: &crate::Data);
+    fn device_set_cache_limits(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        limits: &wgt::DeviceCacheLimits,
+    );
+    fn device_trim_caches(&self, device: &ObjectId, device_data: &crate::Data);
+    fn device_cache_report(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+    ) -> wgt::DeviceCacheReport;
+    fn device_capture_diagnostics(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+    ) -> crate::DiagnosticReport;
 
     fn compute_pass_set_pipeline(
         &self,
@@ -2232,6 +2369,32 @@ where
         (shader_module.into(), Box::new(data) as _)
     }
 
+    unsafe fn device_create_shader_module_dxil(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &ShaderModuleDescriptorDxil<'_>,
+    ) -> (ObjectId, Box<crate::Data>) {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        let (shader_module, data) =
+            unsafe { Context::device_create_shader_module_dxil(self, &device, device_data, desc) };
+        (shader_module.into(), Box::new(data) as _)
+    }
+
+    unsafe fn device_create_shader_module_msl(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &ShaderModuleDescriptorMsl<'_>,
+    ) -> (ObjectId, Box<crate::Data>) {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        let (shader_module, data) =
+            unsafe { Context::device_create_shader_module_msl(self, &device, device_data, desc) };
+        (shader_module.into(), Box::new(data) as _)
+    }
+
     fn device_create_bind_group_layout(
         &self,
         device: &ObjectId,
@@ -2345,6 +2508,19 @@ where
         (query_set.into(), Box::new(data) as _)
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        desc: &PipelineCacheDescriptor<'_>,
+    ) -> (ObjectId, Box<crate::Data>) {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        let (pipeline_cache, data) =
+            unsafe { Context::device_create_pipeline_cache(self, &device, device_data, desc) };
+        (pipeline_cache.into(), Box::new(data) as _)
+    }
+
     fn device_create_command_encoder(
         &self,
         device: &ObjectId,
@@ -2497,6 +2673,16 @@ where
         Box::pin(future)
     }
 
+    fn shader_module_reflect(
+        &self,
+        shader: &ObjectId,
+        shader_data: &crate::Data,
+    ) -> Option<ShaderReflection> {
+        let shader = <T::ShaderModuleId>::from(*shader);
+        let shader_data = downcast_ref(shader_data);
+        Context::shader_module_reflect(self, &shader, shader_data)
+    }
+
     fn texture_create_view(
         &self,
         texture: &ObjectId,
@@ -2563,6 +2749,22 @@ where
         Context::query_set_drop(self, &query_set, query_set_data)
     }
 
+    fn pipeline_cache_get_data(
+        &self,
+        cache: &ObjectId,
+        cache_data: &crate::Data,
+    ) -> Option<Vec<u8>> {
+        let cache = <T::PipelineCacheId>::from(*cache);
+        let cache_data = downcast_ref(cache_data);
+        Context::pipeline_cache_get_data(self, &cache, cache_data)
+    }
+
+    fn pipeline_cache_drop(&self, cache: &ObjectId, cache_data: &crate::Data) {
+        let cache = <T::PipelineCacheId>::from(*cache);
+        let cache_data = downcast_ref(cache_data);
+        Context::pipeline_cache_drop(self, &cache, cache_data)
+    }
+
     fn bind_group_drop(&self, bind_group: &ObjectId, bind_group_data: &crate::Data) {
         let bind_group = <T::BindGroupId>::from(*bind_group);
         let bind_group_data = downcast_ref(bind_group_data);
@@ -2647,6 +2849,28 @@ where
         (bind_group_layout.into(), Box::new(data) as _)
     }
 
+    fn compute_pipeline_get_binding(
+        &self,
+        pipeline: &ObjectId,
+        pipeline_data: &crate::Data,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        let pipeline = <T::ComputePipelineId>::from(*pipeline);
+        let pipeline_data = downcast_ref(pipeline_data);
+        Context::compute_pipeline_get_binding(self, &pipeline, pipeline_data, name)
+    }
+
+    fn render_pipeline_get_binding(
+        &self,
+        pipeline: &ObjectId,
+        pipeline_data: &crate::Data,
+        name: &str,
+    ) -> Option<(u32, u32)> {
+        let pipeline = <T::RenderPipelineId>::from(*pipeline);
+        let pipeline_data = downcast_ref(pipeline_data);
+        Context::render_pipeline_get_binding(self, &pipeline, pipeline_data, name)
+    }
+
     fn command_encoder_copy_buffer_to_buffer(
         &self,
         encoder: &ObjectId,
@@ -3071,6 +3295,23 @@ where
         Context::queue_on_submitted_work_done(self, &queue, queue_data, callback)
     }
 
+    fn queue_set_submission_batching(
+        &self,
+        queue: &ObjectId,
+        queue_data: &crate::Data,
+        batching: wgt::SubmissionBatching,
+    ) {
+        let queue = <T::QueueId>::from(*queue);
+        let queue_data = downcast_ref(queue_data);
+        Context::queue_set_submission_batching(self, &queue, queue_data, batching)
+    }
+
+    fn queue_flush(&self, queue: &ObjectId, queue_data: &crate::Data) {
+        let queue = <T::QueueId>::from(*queue);
+        let queue_data = downcast_ref(queue_data);
+        Context::queue_flush(self, &queue, queue_data)
+    }
+
     fn device_start_capture(&self, device: &ObjectId, device_data: &crate::Data) {
         let device = <T::DeviceId>::from(*device);
         let device_data = downcast_ref(device_data);
@@ -3083,6 +3324,43 @@ where
         Context::device_stop_capture(self, &device, device_data)
     }
 
+    fn device_set_cache_limits(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+        limits: &wgt::DeviceCacheLimits,
+    ) {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        Context::device_set_cache_limits(self, &device, device_data, limits)
+    }
+
+    fn device_trim_caches(&self, device: &ObjectId, device_data: &crate::Data) {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        Context::device_trim_caches(self, &device, device_data)
+    }
+
+    fn device_cache_report(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+    ) -> wgt::DeviceCacheReport {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        Context::device_cache_report(self, &device, device_data)
+    }
+
+    fn device_capture_diagnostics(
+        &self,
+        device: &ObjectId,
+        device_data: &crate::Data,
+    ) -> crate::DiagnosticReport {
+        let device = <T::DeviceId>::from(*device);
+        let device_data = downcast_ref(device_data);
+        Context::device_capture_diagnostics(self, &device, device_data)
+    }
+
     fn compute_pass_set_pipeline(
         &self,
         pass: &mut ObjectId,
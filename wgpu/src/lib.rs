@@ -46,24 +46,26 @@ use parking_lot::Mutex;
 
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 pub use wgt::{
-    AdapterInfo, AddressMode, AstcBlock, AstcChannel, Backend, Backends, BindGroupLayoutEntry,
-    BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, BufferAddress,
-    BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites,
+    AdapterInfo, AddressMode, AllocatorReport, AstcBlock, AstcChannel, Backend, Backends,
+    BindGroupLayoutEntry, BindingReflection, BindingType, BlendComponent, BlendFactor,
+    BlendOperation, BlendState, BufferAddress, BufferBindingType, BufferReport, BufferSize,
+    BufferUsages, CacheStatistics, Color, ColorOutputReflection, ColorTargetState, ColorWrites,
     CommandBufferDescriptor, CompareFunction, CompositeAlphaMode, DepthBiasState,
-    DepthStencilState, DeviceLostReason, DeviceType, DownlevelCapabilities, DownlevelFlags,
-    Dx12Compiler, DynamicOffset, Extent3d, Face, Features, FilterMode, FrontFace,
-    Gles3MinorVersion, ImageDataLayout, ImageSubresourceRange, IndexFormat, InstanceDescriptor,
-    InstanceFlags, Limits, MaintainResult, MultisampleState, Origin2d, Origin3d,
+    DepthStencilState, DeviceCacheLimits, DeviceCacheReport, DeviceDiagnostics, DeviceLostReason,
+    DeviceType, DownlevelCapabilities, DownlevelFlags, Dx12Compiler, DynamicOffset,
+    EntryPointReflection, Extent3d, Face, Features, FilterMode, FrontFace, Gles3MinorVersion,
+    ImageDataLayout, ImageSubresourceRange, IndexFormat, InstanceDescriptor, InstanceFlags, Limits,
+    LiveResourceReport, MaintainResult, MemoryHints, MultisampleState, Origin2d, Origin3d,
     PipelineStatisticsTypes, PolygonMode, PowerPreference, PredefinedColorSpace, PresentMode,
     PresentationTimestamp, PrimitiveState, PrimitiveTopology, PushConstantRange, QueryType,
     RenderBundleDepthStencil, SamplerBindingType, SamplerBorderColor, ShaderLocation, ShaderModel,
-    ShaderStages, StencilFaceState, StencilOperation, StencilState, StorageTextureAccess,
-    SurfaceCapabilities, SurfaceStatus, TextureAspect, TextureDimension, TextureFormat,
-    TextureFormatFeatureFlags, TextureFormatFeatures, TextureSampleType, TextureUsages,
-    TextureViewDimension, VertexAttribute, VertexFormat, VertexStepMode, WasmNotSend,
-    WasmNotSendSync, WasmNotSync, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
-    MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT, QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES,
-    QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
+    ShaderReflection, ShaderStages, StencilFaceState, StencilOperation, StencilState,
+    StorageTextureAccess, SubmissionBatching, SurfaceCapabilities, SurfaceStatus, TextureAspect,
+    TextureDimension, TextureFormat, TextureFormatFeatureFlags, TextureFormatFeatures,
+    TextureReport, TextureSampleType, TextureUsages, TextureViewDimension, VertexAttribute,
+    VertexFormat, VertexInputReflection, VertexStepMode, WasmNotSend, WasmNotSendSync, WasmNotSync,
+    COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT, MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT,
+    QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES, QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
 };
 
 /// Re-export of our `wgpu-core` dependency.
@@ -77,6 +79,11 @@ pub use ::wgc as core;
 #[cfg(wgpu_core)]
 pub use ::hal;
 
+#[cfg(all(wgpu_core, feature = "shader_cache"))]
+pub use ::hal::ShaderCache;
+#[cfg(all(wgpu_core, feature = "shader_cache"))]
+pub use ::wgc::device::shader_cache::DirectoryShaderCache;
+
 /// Re-export of our `naga` dependency.
 ///
 #[cfg(wgpu_core)]
@@ -106,6 +113,7 @@ pub use wgt::{ExternalImageSource, ImageCopyExternalImage};
 
 /// Filter for error scopes.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ErrorFilter {
     /// Catch only out-of-memory errors.
     OutOfMemory,
@@ -116,6 +124,39 @@ pub enum ErrorFilter {
 }
 static_assertions::assert_impl_all!(ErrorFilter: Send, Sync);
 
+/// The result of a popped error scope, in a [`DiagnosticReport`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorScopeReport {
+    /// The filter of the scope.
+    pub filter: ErrorFilter,
+    /// The message of the error caught by the scope, if any.
+    pub error: Option<String>,
+}
+
+/// A snapshot of a device, to attach to bug reports.
+///
+/// Returned by [`Device::capture_diagnostics`]. With the `serde` feature, it
+/// can be serialized to any format `serde` supports, such as JSON.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiagnosticReport {
+    /// The adapter, the enabled features and limits, the live resources, and
+    /// statistics of the allocator and caches.
+    pub device: DeviceDiagnostics,
+    /// The results of the last popped error scopes, up to
+    /// [`DiagnosticReport::MAX_ERROR_SCOPES`], oldest first.
+    ///
+    /// Error scopes are handled by the browser on WebGPU, so none are
+    /// reported.
+    pub error_scopes: Vec<ErrorScopeReport>,
+}
+
+impl DiagnosticReport {
+    /// The number of popped error scopes a device remembers the results of.
+    pub const MAX_ERROR_SCOPES: usize = 16;
+}
+
 type C = dyn DynContext;
 #[cfg(send_sync)]
 type Data = dyn Any + Send + Sync;
@@ -812,6 +853,20 @@ impl ShaderModule {
         self.context
             .shader_get_compilation_info(&self.id, self.data.as_ref())
     }
+
+    /// Get the interface of the shader module's entry points: the resources they use, their
+    /// vertex inputs and color outputs, workgroup sizes and push constant ranges.
+    ///
+    /// This can be used to create bind group layouts and vertex buffer layouts matching the
+    /// module without parsing its source.
+    ///
+    /// Returns `None` if the module failed to compile, or if its interface is unknown to wgpu.
+    /// This is the case for modules created from native shaders, e.g. with
+    /// [`Device::create_shader_module_spirv`], and for all modules on the WebGPU backend.
+    pub fn reflect(&self) -> Option<ShaderReflection> {
+        self.context
+            .shader_module_reflect(&self.id, self.data.as_ref())
+    }
 }
 
 /// Compilation information for a shader module.
@@ -873,13 +928,36 @@ pub struct SourceLocation {
 #[cfg(all(feature = "wgsl", wgpu_core))]
 impl From<naga::error::ShaderError<naga::front::wgsl::ParseError>> for CompilationInfo {
     fn from(value: naga::error::ShaderError<naga::front::wgsl::ParseError>) -> Self {
-        CompilationInfo {
-            messages: vec![CompilationMessage {
-                message: value.to_string(),
-                message_type: CompilationMessageType::Error,
-                location: value.inner.location(&value.source).map(Into::into),
-            }],
-        }
+        let source = &value.source;
+        let mut labels = value.inner.labels();
+        // The first label is the primary span of the error, the rest point
+        // at related parts of the source and are reported as info messages.
+        let primary = labels.next();
+        let mut messages = vec![CompilationMessage {
+            message: match primary {
+                Some((_, label)) if !label.is_empty() => {
+                    format!("{}: {label}", value.inner.message())
+                }
+                _ => value.inner.message().to_string(),
+            },
+            message_type: CompilationMessageType::Error,
+            location: value.inner.location(source).map(Into::into),
+        }];
+        messages.extend(
+            labels
+                .filter(|&(span, _)| span.is_defined())
+                .map(|(span, label)| CompilationMessage {
+                    message: label.to_string(),
+                    message_type: CompilationMessageType::Info,
+                    location: Some(span.location(source).into()),
+                }),
+        );
+        messages.extend(value.inner.notes().map(|note| CompilationMessage {
+            message: format!("note: {note}"),
+            message_type: CompilationMessageType::Info,
+            location: None,
+        }));
+        CompilationInfo { messages }
     }
 }
 #[cfg(feature = "glsl")]
@@ -898,15 +976,35 @@ impl From<naga::error::ShaderError<naga::front::glsl::ParseErrors>> for Compilat
         CompilationInfo { messages }
     }
 }
+#[cfg(feature = "hlsl")]
+impl From<naga::error::ShaderError<naga::front::hlsl::Error>> for CompilationInfo {
+    fn from(value: naga::error::ShaderError<naga::front::hlsl::Error>) -> Self {
+        CompilationInfo {
+            messages: vec![CompilationMessage {
+                message: value.inner.to_string(),
+                message_type: CompilationMessageType::Error,
+                location: value.inner.location(&value.source).map(Into::into),
+            }],
+        }
+    }
+}
 
 #[cfg(feature = "spirv")]
-impl From<naga::error::ShaderError<naga::front::spv::Error>> for CompilationInfo {
-    fn from(value: naga::error::ShaderError<naga::front::spv::Error>) -> Self {
+impl From<naga::error::ShaderError<naga::WithSpan<naga::front::spv::Error>>> for CompilationInfo {
+    fn from(value: naga::error::ShaderError<naga::WithSpan<naga::front::spv::Error>>) -> Self {
+        // SPIR-V has no lines, so the whole module is treated as a single one
+        // and spans point at the offending instruction's bytes.
+        let location = value.inner.spans().next().map(|&(span, _)| SourceLocation {
+            line_number: 1,
+            line_position: span.start + 1,
+            offset: span.start,
+            length: span.end - span.start,
+        });
         CompilationInfo {
             messages: vec![CompilationMessage {
-                message: value.to_string(),
+                message: value.inner.to_string(),
                 message_type: CompilationMessageType::Error,
-                location: None,
+                location,
             }],
         }
     }
@@ -917,13 +1015,43 @@ impl From<naga::error::ShaderError<naga::WithSpan<naga::valid::ValidationError>>
     for CompilationInfo
 {
     fn from(value: naga::error::ShaderError<naga::WithSpan<naga::valid::ValidationError>>) -> Self {
-        CompilationInfo {
-            messages: vec![CompilationMessage {
-                message: value.to_string(),
-                message_type: CompilationMessageType::Error,
-                location: value.inner.location(&value.source).map(Into::into),
-            }],
+        use std::error::Error as _;
+
+        let source = &value.source;
+        let located = |span: naga::Span| -> Option<SourceLocation> {
+            span.to_range()
+                .filter(|range| range.end <= source.len())
+                .map(|_| span.location(source).into())
+        };
+
+        // Include the whole error chain, the outermost error alone is
+        // usually too vague to act on.
+        let mut message = value.inner.to_string();
+        let mut error: &dyn std::error::Error = value.inner.as_inner();
+        while let Some(next) = error.source() {
+            message = format!("{message}: {next}");
+            error = next;
         }
+
+        let mut messages = vec![CompilationMessage {
+            message,
+            message_type: CompilationMessageType::Error,
+            location: value
+                .inner
+                .spans()
+                .next()
+                .and_then(|&(span, _)| located(span)),
+        }];
+        // Every span, including the primary one, gets a message of its own,
+        // as its description says what the span refers to.
+        messages.extend(value.inner.spans().filter_map(|&(span, ref desc)| {
+            Some(CompilationMessage {
+                message: desc.clone(),
+                message_type: CompilationMessageType::Info,
+                location: Some(located(span)?),
+            })
+        }));
+        CompilationInfo { messages }
     }
 }
 
@@ -968,13 +1096,53 @@ pub enum ShaderSource<'a> {
         stage: naga::ShaderStage,
         /// Defines to unlock configured shader features.
         defines: naga::FastHashMap<String, String>,
+        /// How combined image samplers like `sampler2D` are split into the
+        /// texture and sampler bindings of the pipeline layout.
+        combined_samplers: naga::front::glsl::CombinedSamplerMapping,
+    },
+    /// HLSL module as a string slice.
+    ///
+    /// Entry points are found from their attributes and semantics, and
+    /// resources need explicit registers. See [`naga::front::hlsl`] for the
+    /// subset of HLSL that's supported.
+    #[cfg(feature = "hlsl")]
+    Hlsl {
+        /// The source code of the shader.
+        shader: Cow<'a, str>,
+        /// Defines to unlock configured shader features.
+        defines: naga::FastHashMap<String, String>,
     },
     /// WGSL module as a string slice.
     #[cfg(feature = "wgsl")]
     Wgsl(Cow<'a, str>),
+    /// WGSL module split over several files.
+    ///
+    /// Lines of the form `// #include "path"` are replaced by the contents of
+    /// the named file, as returned by `resolve`. Each path is included at most
+    /// once, no matter how many files include it. Errors are reported against
+    /// the file containing the offending code.
+    ///
+    /// Not supported on the WebGPU backend.
+    #[cfg(feature = "wgsl")]
+    WgslWithIncludes {
+        /// The source code of the root file.
+        source: Cow<'a, str>,
+        /// Returns the source code of an included path, or a message explaining why it
+        /// couldn't be loaded.
+        resolve: IncludeResolver<'a>,
+    },
     /// Naga module.
     #[cfg(feature = "naga-ir")]
     Naga(Cow<'static, naga::Module>),
+    /// Naga module parsed and validated ahead of time, encoded by
+    /// [`naga::precompiled::write`]. This skips the shader front ends, which
+    /// are usually the most expensive part of creating a shader module.
+    ///
+    /// The `naga` command line tool produces these for output files with a
+    /// `.naga` extension, e.g. `naga shader.wgsl shader.naga`. The module is
+    /// validated again when it is created, against the device's features.
+    #[cfg(feature = "precompiled")]
+    Precompiled(Cow<'a, [u8]>),
     /// Dummy variant because `Naga` doesn't have a lifetime and without enough active features it
     /// could be the last one active.
     #[doc(hidden)]
@@ -982,6 +1150,18 @@ pub enum ShaderSource<'a> {
 }
 static_assertions::assert_impl_all!(ShaderSource<'_>: Send, Sync);
 
+/// Callback resolving the `#include` directives of [`ShaderSource::WgslWithIncludes`].
+#[cfg(feature = "wgsl")]
+#[derive(Clone, Copy)]
+pub struct IncludeResolver<'a>(pub &'a (dyn Fn(&str) -> Result<String, String> + Send + Sync));
+
+#[cfg(feature = "wgsl")]
+impl fmt::Debug for IncludeResolver<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IncludeResolver")
+    }
+}
+
 /// Descriptor for use with [`Device::create_shader_module`].
 ///
 /// Corresponds to [WebGPU `GPUShaderModuleDescriptor`](
@@ -1009,6 +1189,48 @@ pub struct ShaderModuleDescriptorSpirV<'a> {
 }
 static_assertions::assert_impl_all!(ShaderModuleDescriptorSpirV<'_>: Send, Sync);
 
+/// Descriptor for a shader module given by precompiled DXIL, for use with
+/// [`Device::create_shader_module_dxil`].
+///
+/// This type is unique to the Rust API of `wgpu`. In the WebGPU specification,
+/// only WGSL source code strings are accepted.
+#[derive(Debug)]
+pub struct ShaderModuleDescriptorDxil<'a> {
+    /// Debug label of the shader module. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// A validated DXIL container holding a single entry point.
+    pub source: Cow<'a, [u8]>,
+}
+static_assertions::assert_impl_all!(ShaderModuleDescriptorDxil<'_>: Send, Sync);
+
+/// Descriptor for a shader module given by Metal code, for use with
+/// [`Device::create_shader_module_msl`].
+///
+/// This type is unique to the Rust API of `wgpu`. In the WebGPU specification,
+/// only WGSL source code strings are accepted.
+#[derive(Debug)]
+pub struct ShaderModuleDescriptorMsl<'a> {
+    /// Debug label of the shader module. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// The Metal code.
+    pub source: MslSource<'a>,
+    /// The workgroup size of the module's compute entry points.
+    ///
+    /// Metal states it when dispatching rather than in the code, so it has to
+    /// be given here.
+    pub workgroup_size: [u32; 3],
+}
+static_assertions::assert_impl_all!(ShaderModuleDescriptorMsl<'_>: Send, Sync);
+
+/// Metal code for a [`ShaderModuleDescriptorMsl`].
+#[derive(Clone, Debug)]
+pub enum MslSource<'a> {
+    /// Metal Shading Language source code, compiled when the module is created.
+    Source(Cow<'a, str>),
+    /// A compiled `metallib` library, as produced by `xcrun metallib`.
+    Library(Cow<'a, [u8]>),
+}
+
 /// Handle to a pipeline layout.
 ///
 /// A `PipelineLayout` object describes the available binding groups of a pipeline.
@@ -1066,6 +1288,16 @@ impl RenderPipeline {
                 .render_pipeline_get_bind_group_layout(&self.id, self.data.as_ref(), index);
         BindGroupLayout { context, id, data }
     }
+
+    /// Look up the `(group, binding)` of a resource by its variable name in the shader.
+    ///
+    /// Only resources used by the pipeline's entry points can be found. Returns `None` if
+    /// no such resource exists, or if the names aren't known, as is the case for pipelines
+    /// built from native shaders and for all pipelines on the WebGPU backend.
+    pub fn get_binding(&self, name: &str) -> Option<(u32, u32)> {
+        self.context
+            .render_pipeline_get_binding(&self.id, self.data.as_ref(), name)
+    }
 }
 
 /// Handle to a compute pipeline.
@@ -1103,6 +1335,66 @@ impl ComputePipeline {
         );
         BindGroupLayout { context, id, data }
     }
+
+    /// Look up the `(group, binding)` of a resource by its variable name in the shader.
+    ///
+    /// Only resources used by the pipeline's entry point can be found. Returns `None` if
+    /// no such resource exists, or if the names aren't known, as is the case for pipelines
+    /// built from native shaders and for all pipelines on the WebGPU backend.
+    pub fn get_binding(&self, name: &str) -> Option<(u32, u32)> {
+        self.context
+            .compute_pipeline_get_binding(&self.id, self.data.as_ref(), name)
+    }
+}
+
+/// Handle to a pipeline cache, which is used to accelerate
+/// creating [`RenderPipeline`]s and [`ComputePipeline`]s
+/// in subsequent executions.
+///
+/// It can be created with [`Device::create_pipeline_cache`], from
+/// the data of an earlier cache returned by [`PipelineCache::get_data`].
+///
+/// This type is unique to the Rust API of `wgpu`.
+///
+/// # Usage
+///
+/// Pass the cache in the `cache` field of the pipeline descriptors. The
+/// pipelines created with it add their compiled code to the cache, and the
+/// pipelines created later with the same description, even by a later
+/// execution of the application, get it from there rather than compiling
+/// it again.
+///
+/// The data is only valid for the adapter and driver version it was made
+/// with, so it's best stored per adapter, for instance under the name
+/// returned by [`util::pipeline_cache_key`].
+///
+/// Requires [`Features::PIPELINE_CACHE`].
+#[derive(Debug)]
+pub struct PipelineCache {
+    context: Arc<C>,
+    id: ObjectId,
+    data: Box<Data>,
+}
+#[cfg(send_sync)]
+static_assertions::assert_impl_all!(PipelineCache: Send, Sync);
+
+impl PipelineCache {
+    /// Get the data of the cache, to create a cache with later.
+    ///
+    /// Returns `None` if the backend can't get the data of the cache.
+    pub fn get_data(&self) -> Option<Vec<u8>> {
+        self.context
+            .pipeline_cache_get_data(&self.id, self.data.as_ref())
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.context
+                .pipeline_cache_drop(&self.id, self.data.as_ref());
+        }
+    }
 }
 
 /// Handle to a command buffer on the GPU.
@@ -1832,6 +2124,8 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a PipelineCache>,
 }
 #[cfg(send_sync)]
 static_assertions::assert_impl_all!(RenderPipelineDescriptor<'_>: Send, Sync);
@@ -1929,10 +2223,39 @@ pub struct ComputePipelineDescriptor<'a> {
     ///
     /// This implements `Default`, and for most users can be set to `Default::default()`
     pub compilation_options: PipelineCompilationOptions<'a>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a PipelineCache>,
 }
 #[cfg(send_sync)]
 static_assertions::assert_impl_all!(ComputePipelineDescriptor<'_>: Send, Sync);
 
+/// Describes a pipeline cache.
+///
+/// For use with [`Device::create_pipeline_cache`].
+///
+/// This type is unique to the Rust API of `wgpu`.
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    /// Debug label of the pipeline cache. This might show up in some logs from `wgpu`.
+    pub label: Label<'a>,
+    /// The data used to initialise the cache, returned by an earlier
+    /// [`PipelineCache::get_data`].
+    ///
+    /// # Safety
+    ///
+    /// This data must have been returned by [`PipelineCache::get_data`].
+    /// `wgpu` rejects data made by other adapters, drivers or versions of
+    /// `wgpu`, and corrupted data, but not data made up to look valid.
+    pub data: Option<&'a [u8]>,
+    /// Whether to create an empty cache if `data` is rejected.
+    ///
+    /// If this is `false`, [`Device::create_pipeline_cache`] raises a
+    /// validation error instead, and returns an invalid cache.
+    pub fallback: bool,
+}
+#[cfg(send_sync)]
+static_assertions::assert_impl_all!(PipelineCacheDescriptor<'_>: Send, Sync);
+
 pub use wgt::ImageCopyBuffer as ImageCopyBufferBase;
 /// View of a buffer which can be used to copy to/from a texture.
 ///
@@ -2737,6 +3060,72 @@ impl Device {
         }
     }
 
+    /// Creates a shader module from precompiled DXIL directly.
+    ///
+    /// Requires [`Features::DXIL_SHADER_PASSTHROUGH`]. The pipeline's entry
+    /// point name and overridable constants are ignored, since they were fixed
+    /// when the DXIL was compiled.
+    ///
+    /// # Safety
+    ///
+    /// This function passes binary data to the backend as-is and can potentially result in a
+    /// driver crash or bogus behaviour. No attempt is made to ensure that data is valid DXIL,
+    /// or that it matches the pipeline layout it's used with.
+    pub unsafe fn create_shader_module_dxil(
+        &self,
+        desc: &ShaderModuleDescriptorDxil<'_>,
+    ) -> ShaderModule {
+        let (id, data) = unsafe {
+            DynContext::device_create_shader_module_dxil(
+                &*self.context,
+                &self.id,
+                self.data.as_ref(),
+                desc,
+            )
+        };
+        ShaderModule {
+            context: Arc::clone(&self.context),
+            id,
+            data,
+        }
+    }
+
+    /// Creates a shader module from Metal Shading Language source or a
+    /// compiled `metallib` directly.
+    ///
+    /// Requires [`Features::MSL_SHADER_PASSTHROUGH`]. Pipelines look up their
+    /// entry point in the library by name; overridable constants are ignored.
+    ///
+    /// Resources are bound at the indices the pipeline layout assigns them:
+    /// for each stage, the buffers, textures and samplers visible to it are
+    /// numbered separately, in order of bind group and then layout entry,
+    /// with binding arrays taking one index per element. If the stage uses
+    /// push constants, they take buffer index 0 and the other buffers follow.
+    ///
+    /// # Safety
+    ///
+    /// This function passes the code to the backend as-is and can potentially result in a
+    /// driver crash or bogus behaviour. No attempt is made to ensure that it's valid or
+    /// matches the pipeline layout it's used with.
+    pub unsafe fn create_shader_module_msl(
+        &self,
+        desc: &ShaderModuleDescriptorMsl<'_>,
+    ) -> ShaderModule {
+        let (id, data) = unsafe {
+            DynContext::device_create_shader_module_msl(
+                &*self.context,
+                &self.id,
+                self.data.as_ref(),
+                desc,
+            )
+        };
+        ShaderModule {
+            context: Arc::clone(&self.context),
+            id,
+            data,
+        }
+    }
+
     /// Creates an empty [`CommandEncoder`].
     pub fn create_command_encoder(&self, desc: &CommandEncoderDescriptor<'_>) -> CommandEncoder {
         let (id, data) = DynContext::device_create_command_encoder(
@@ -2928,6 +3317,103 @@ impl Device {
         }
     }
 
+    /// Creates a [`Texture`] which can be shared with other processes.
+    ///
+    /// Requires [`Features::SHARED_TEXTURES`].
+    ///
+    /// [`Texture::export_shared`] exports the texture as an OS handle, which
+    /// another process imports with [`Device::import_shared_texture`]. Both
+    /// processes then see the same memory, without copies, but only one of
+    /// them owns the texture and may use it at a time:
+    ///
+    /// 1. This process owns the texture first. It renders to it, and gives it
+    ///    up with [`Queue::release_shared_texture`] and a submission.
+    /// 2. Once that submission is done, which it can wait for with
+    ///    [`Device::poll`], it tells the other process, for instance over
+    ///    the channel the handle was sent on.
+    /// 3. The other process takes the texture with
+    ///    [`Queue::acquire_shared_texture`], uses it, and releases it in
+    ///    turn.
+    ///
+    /// Submitting a command buffer using the texture while another process
+    /// owns it is an error. Textures are handed over in the state of copy
+    /// sources, whatever their usages, so any format can be shared.
+    ///
+    /// The handles are opaque file descriptors on Unix, and NT handles on
+    /// Windows. Only devices of the same adapter and driver can import them.
+    /// DMA-BUF file descriptors and `IOSurface`s aren't supported.
+    ///
+    /// # Panics
+    ///
+    /// - If the device isn't a `wgpu-core` device.
+    #[cfg(wgpu_core)]
+    pub fn create_shared_texture(&self, desc: &TextureDescriptor<'_>) -> Texture {
+        let texture = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+            .expect("shared textures require a wgpu-core device")
+            .device_create_shared_texture(self.data.as_ref().downcast_ref().unwrap(), desc);
+        Texture {
+            context: Arc::clone(&self.context),
+            id: ObjectId::from(texture.id()),
+            data: Box::new(texture),
+            owned: true,
+            descriptor: TextureDescriptor {
+                label: None,
+                view_formats: &[],
+                ..desc.clone()
+            },
+        }
+    }
+
+    /// Imports a [`Texture`] another process exported with
+    /// [`Texture::export_shared`].
+    ///
+    /// The other process owns the texture until it releases it; this
+    /// process may only use it after [`Queue::acquire_shared_texture`]. See
+    /// [`Device::create_shared_texture`].
+    ///
+    /// Requires [`Features::SHARED_TEXTURES`].
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be exported from a texture created with `desc`, by a
+    ///   device of the same adapter and driver as this one.
+    ///
+    /// # Panics
+    ///
+    /// - If the device isn't a `wgpu-core` device.
+    #[cfg(wgpu_core)]
+    pub unsafe fn import_shared_texture(
+        &self,
+        handle: hal::ExternalHandle,
+        desc: &TextureDescriptor<'_>,
+    ) -> Texture {
+        let texture = unsafe {
+            self.context
+                .as_any()
+                .downcast_ref::<crate::backend::ContextWgpuCore>()
+                .expect("shared textures require a wgpu-core device")
+                .device_import_shared_texture(
+                    self.data.as_ref().downcast_ref().unwrap(),
+                    handle,
+                    desc,
+                )
+        };
+        Texture {
+            context: Arc::clone(&self.context),
+            id: ObjectId::from(texture.id()),
+            data: Box::new(texture),
+            owned: true,
+            descriptor: TextureDescriptor {
+                label: None,
+                view_formats: &[],
+                ..desc.clone()
+            },
+        }
+    }
+
     /// Creates a [`Buffer`] from a wgpu-hal Buffer.
     ///
     /// # Safety
@@ -2994,6 +3480,37 @@ impl Device {
         }
     }
 
+    /// Creates a [`PipelineCache`] with initial data.
+    ///
+    /// This can be passed to [`Device::create_compute_pipeline`]
+    /// and [`Device::create_render_pipeline`] to either accelerate these
+    /// or add the compiled pipelines to the cache.
+    ///
+    /// Requires [`Features::PIPELINE_CACHE`].
+    ///
+    /// # Safety
+    ///
+    /// The `data` of `desc` must have been returned by
+    /// [`PipelineCache::get_data`]. See [`PipelineCacheDescriptor::data`].
+    pub unsafe fn create_pipeline_cache(
+        &self,
+        desc: &PipelineCacheDescriptor<'_>,
+    ) -> PipelineCache {
+        let (id, data) = unsafe {
+            DynContext::device_create_pipeline_cache(
+                &*self.context,
+                &self.id,
+                self.data.as_ref(),
+                desc,
+            )
+        };
+        PipelineCache {
+            context: Arc::clone(&self.context),
+            id,
+            data,
+        }
+    }
+
     /// Set a callback for errors that are not handled in error scopes.
     pub fn on_uncaptured_error(&self, handler: Box<dyn UncapturedErrorHandler>) {
         self.context
@@ -3012,16 +3529,80 @@ impl Device {
             .device_pop_error_scope(&self.id, self.data.as_ref())
     }
 
-    /// Starts frame capture.
-    pub fn start_capture(&self) {
-        DynContext::device_start_capture(&*self.context, &self.id, self.data.as_ref())
-    }
-
-    /// Stops frame capture.
-    pub fn stop_capture(&self) {
-        DynContext::device_stop_capture(&*self.context, &self.id, self.data.as_ref())
-    }
-
+    /// Cache the translation of the shader modules created from now on in
+    /// `cache`, or stop caching them with `None`.
+    ///
+    /// Creating a shader module then reuses the parsed and validated module
+    /// from a previous run if its source didn't change, and the backend
+    /// reuses its translation of the module for each pipeline. Currently only
+    /// Vulkan caches its translation, other backends still translate modules
+    /// on every pipeline creation.
+    ///
+    /// The cache is bypassed when [`InstanceFlags::DEBUG`] is set, as cached
+    /// modules don't keep the spans needed to map the generated shaders back
+    /// to their source.
+    ///
+    /// This has no effect on the WebGPU backend.
+    #[cfg(all(wgpu_core, feature = "shader_cache"))]
+    pub fn set_shader_cache(&self, cache: Option<Arc<dyn ShaderCache>>) {
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            ctx.device_set_shader_cache(self.data.as_ref().downcast_ref().unwrap(), cache)
+        }
+    }
+
+    /// Starts frame capture.
+    pub fn start_capture(&self) {
+        DynContext::device_start_capture(&*self.context, &self.id, self.data.as_ref())
+    }
+
+    /// Stops frame capture.
+    pub fn stop_capture(&self) {
+        DynContext::device_stop_capture(&*self.context, &self.id, self.data.as_ref())
+    }
+
+    /// Sets how many objects the device may keep in its internal caches.
+    ///
+    /// Some backends cache objects that WebGPU has no equivalent for, such as
+    /// Vulkan render passes and framebuffers. Past these limits, the least
+    /// recently used objects are evicted.
+    pub fn set_cache_limits(&self, limits: &DeviceCacheLimits) {
+        DynContext::device_set_cache_limits(&*self.context, &self.id, self.data.as_ref(), limits)
+    }
+
+    /// Evicts every object from the device's internal caches, releasing their memory.
+    ///
+    /// Objects are created again, and cached, as rendering needs them.
+    /// Objects still used by command buffers that are executing are only
+    /// destroyed once the device is done with them.
+    pub fn trim_caches(&self) {
+        DynContext::device_trim_caches(&*self.context, &self.id, self.data.as_ref())
+    }
+
+    /// Returns statistics of the device's internal caches, to diagnose their
+    /// memory use and efficiency.
+    ///
+    /// On backends that don't cache any objects, every cache is reported empty.
+    pub fn cache_report(&self) -> DeviceCacheReport {
+        DynContext::device_cache_report(&*self.context, &self.id, self.data.as_ref())
+    }
+
+    /// Takes a snapshot of the device, to attach to bug reports.
+    ///
+    /// The report has the adapter info, the enabled features and limits, the
+    /// live resources with their labels and sizes, statistics of the memory
+    /// allocator and internal caches, and the results of the last popped error
+    /// scopes.
+    ///
+    /// The resources are only reported on native backends, and the allocator
+    /// statistics on Vulkan and DX12.
+    pub fn capture_diagnostics(&self) -> DiagnosticReport {
+        DynContext::device_capture_diagnostics(&*self.context, &self.id, self.data.as_ref())
+    }
+
     /// Apply a callback to this `Device`'s underlying backend device.
     ///
     /// If this `Device` is implemented by the backend API given by `A` (Vulkan,
@@ -3057,6 +3638,38 @@ impl Device {
             })
     }
 
+    /// Apply a callback to this `Device`'s underlying backend fence, the one
+    /// signaled as queue submissions complete.
+    ///
+    /// If this `Device` is implemented by the backend API given by `A` (Vulkan,
+    /// Dx12, etc.), then apply `hal_fence_callback` to `Some(&fence)`, where
+    /// `fence` is the underlying backend fence type, [`A::Fence`], and return
+    /// `Some` with the callback's return value. Otherwise, return `None`.
+    ///
+    /// The fence is locked for reading while `hal_fence_callback` runs, so
+    /// submitting to the queue from the callback will deadlock.
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle passed to the callback must not be manually destroyed.
+    ///
+    /// [`A::Fence`]: hal::Api::Fence
+    #[cfg(wgpu_core)]
+    pub unsafe fn fence_as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Fence>) -> R, R>(
+        &self,
+        hal_fence_callback: F,
+    ) -> Option<R> {
+        self.context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+            .map(|ctx| unsafe {
+                ctx.device_fence_as_hal::<A, F, R>(
+                    self.data.as_ref().downcast_ref().unwrap(),
+                    hal_fence_callback,
+                )
+            })
+    }
+
     /// Destroy this device.
     pub fn destroy(&self) {
         DynContext::device_destroy(&*self.context, &self.id, self.data.as_ref())
@@ -3527,6 +4140,24 @@ impl Texture {
         }
     }
 
+    /// Exports a texture created by [`Device::create_shared_texture`], for
+    /// another process to import with [`Device::import_shared_texture`].
+    ///
+    /// Each call returns a new handle, which the other process takes
+    /// ownership of.
+    ///
+    /// # Panics
+    ///
+    /// - If the texture isn't a `wgpu-core` texture.
+    #[cfg(wgpu_core)]
+    pub fn export_shared(&self) -> Result<hal::ExternalHandle, wgc::resource::SharedTextureError> {
+        self.context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+            .expect("shared textures require a wgpu-core device")
+            .texture_export_shared(self.data.as_ref().downcast_ref().unwrap())
+    }
+
     /// Creates a view of this texture.
     pub fn create_view(&self, desc: &TextureViewDescriptor<'_>) -> TextureView {
         let (id, data) =
@@ -5134,6 +5765,82 @@ impl Queue {
         )
     }
 
+    /// Gives up the ownership of a shared texture, for another process to
+    /// acquire it. See [`Device::create_shared_texture`].
+    ///
+    /// The texture is released at the start of the next submission, after
+    /// the command buffers of earlier submissions, and the command buffers
+    /// submitted from now on can't use it. The other process may acquire the
+    /// texture once that submission is done.
+    ///
+    /// Parts of the texture never written to are cleared first.
+    #[cfg(wgpu_core)]
+    pub fn release_shared_texture(&self, texture: &Texture) {
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            ctx.queue_release_shared_texture(
+                self.data.as_ref().downcast_ref().unwrap(),
+                texture.data.as_ref().downcast_ref().unwrap(),
+            )
+        }
+    }
+
+    /// Apply a callback to this `Queue`'s underlying backend queue.
+    ///
+    /// If this `Queue` is implemented by the backend API given by `A` (Vulkan,
+    /// Dx12, etc.), then apply `hal_queue_callback` to `Some(&queue)`, where
+    /// `queue` is the underlying backend queue type, [`A::Queue`], and return
+    /// `Some` with the callback's return value. Otherwise, return `None`.
+    ///
+    /// This is how submissions wait on fences shared with other APIs, like
+    /// the Vulkan timeline semaphores imported with
+    /// `hal::vulkan::Device::import_fence`, which
+    /// `hal::vulkan::Queue::wait_for_fence` makes the next submission wait on.
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle passed to the callback must not be manually destroyed.
+    ///
+    /// [`A::Queue`]: hal::Api::Queue
+    #[cfg(all(wgpu_core, feature = "external_memory"))]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Queue>) -> R, R>(
+        &self,
+        hal_queue_callback: F,
+    ) -> Option<R> {
+        self.context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+            .map(|ctx| unsafe {
+                ctx.queue_as_hal::<A, F, R>(
+                    self.data.as_ref().downcast_ref().unwrap(),
+                    hal_queue_callback,
+                )
+            })
+    }
+
+    /// Takes the ownership of a shared texture another process released.
+    /// See [`Device::create_shared_texture`].
+    ///
+    /// The command buffers submitted from now on can use the texture, and see
+    /// the contents the other process left in it. The submission which
+    /// released the texture in the other process must be done.
+    #[cfg(wgpu_core)]
+    pub fn acquire_shared_texture(&self, texture: &Texture) {
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            ctx.queue_acquire_shared_texture(
+                self.data.as_ref().downcast_ref().unwrap(),
+                texture.data.as_ref().downcast_ref().unwrap(),
+            )
+        }
+    }
+
     /// Submits a series of finished command buffers for execution.
     pub fn submit<I: IntoIterator<Item = CommandBuffer>>(
         &self,
@@ -5182,6 +5889,32 @@ impl Queue {
             Box::new(callback),
         )
     }
+
+    /// Sets how the command buffers given to [`Queue::submit`] are sent to the
+    /// backend.
+    ///
+    /// With [`SubmissionBatching::Batched`], submissions are held back and
+    /// sent together, which saves the cost of each backend submission for
+    /// applications that submit many times per frame. The submissions held
+    /// back so far are sent right away.
+    ///
+    /// This has no effect on WebGPU.
+    pub fn set_submission_batching(&self, batching: SubmissionBatching) {
+        DynContext::queue_set_submission_batching(
+            &*self.context,
+            &self.id,
+            self.data.as_ref(),
+            batching,
+        )
+    }
+
+    /// Sends the submissions held back by [`SubmissionBatching::Batched`] to
+    /// the backend.
+    ///
+    /// This has no effect on WebGPU.
+    pub fn flush(&self) {
+        DynContext::queue_flush(&*self.context, &self.id, self.data.as_ref())
+    }
 }
 
 impl SurfaceTexture {
@@ -5457,6 +6190,32 @@ impl ShaderModule {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal ShaderModule using a callback. The hal shader module will be `None` if the
+    /// backend type argument does not match with this wgpu ShaderModule
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal ShaderModule must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::ShaderModule>) -> R, R>(
+        &self,
+        hal_shader_module_callback: F,
+    ) -> R {
+        let shader_module_id = core::id::ShaderModuleId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe {
+                ctx.shader_module_as_hal::<A, F, R>(shader_module_id, hal_shader_module_callback)
+            }
+        } else {
+            hal_shader_module_callback(None)
+        }
+    }
 }
 
 impl BindGroupLayout {
@@ -5467,6 +6226,39 @@ impl BindGroupLayout {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal BindGroupLayout using a callback. The hal bind group layout will be `None` if the
+    /// backend type argument does not match with this wgpu BindGroupLayout
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal BindGroupLayout must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::BindGroupLayout>) -> R,
+        R,
+    >(
+        &self,
+        hal_bind_group_layout_callback: F,
+    ) -> R {
+        let bind_group_layout_id = core::id::BindGroupLayoutId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe {
+                ctx.bind_group_layout_as_hal::<A, F, R>(
+                    bind_group_layout_id,
+                    hal_bind_group_layout_callback,
+                )
+            }
+        } else {
+            hal_bind_group_layout_callback(None)
+        }
+    }
 }
 
 impl BindGroup {
@@ -5477,6 +6269,30 @@ impl BindGroup {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal BindGroup using a callback. The hal bind group will be `None` if the
+    /// backend type argument does not match with this wgpu BindGroup
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal BindGroup must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::BindGroup>) -> R, R>(
+        &self,
+        hal_bind_group_callback: F,
+    ) -> R {
+        let bind_group_id = core::id::BindGroupId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe { ctx.bind_group_as_hal::<A, F, R>(bind_group_id, hal_bind_group_callback) }
+        } else {
+            hal_bind_group_callback(None)
+        }
+    }
 }
 
 impl TextureView {
@@ -5525,6 +6341,30 @@ impl Sampler {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal Sampler using a callback. The hal sampler will be `None` if the
+    /// backend type argument does not match with this wgpu Sampler
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal Sampler must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Sampler>) -> R, R>(
+        &self,
+        hal_sampler_callback: F,
+    ) -> R {
+        let sampler_id = core::id::SamplerId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe { ctx.sampler_as_hal::<A, F, R>(sampler_id, hal_sampler_callback) }
+        } else {
+            hal_sampler_callback(None)
+        }
+    }
 }
 
 impl Buffer {
@@ -5535,6 +6375,30 @@ impl Buffer {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal Buffer using a callback. The hal buffer will be `None` if the
+    /// backend type argument does not match with this wgpu Buffer
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal Buffer must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::Buffer>) -> R, R>(
+        &self,
+        hal_buffer_callback: F,
+    ) -> R {
+        let buffer_id = core::id::BufferId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe { ctx.buffer_as_hal::<A, F, R>(buffer_id, hal_buffer_callback) }
+        } else {
+            hal_buffer_callback(None)
+        }
+    }
 }
 
 impl Texture {
@@ -5555,6 +6419,30 @@ impl QuerySet {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal QuerySet using a callback. The hal query set will be `None` if the
+    /// backend type argument does not match with this wgpu QuerySet
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal QuerySet must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::QuerySet>) -> R, R>(
+        &self,
+        hal_query_set_callback: F,
+    ) -> R {
+        let query_set_id = core::id::QuerySetId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe { ctx.query_set_as_hal::<A, F, R>(query_set_id, hal_query_set_callback) }
+        } else {
+            hal_query_set_callback(None)
+        }
+    }
 }
 
 impl PipelineLayout {
@@ -5565,6 +6453,35 @@ impl PipelineLayout {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal PipelineLayout using a callback. The hal pipeline layout will be `None` if the
+    /// backend type argument does not match with this wgpu PipelineLayout
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal PipelineLayout must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::PipelineLayout>) -> R, R>(
+        &self,
+        hal_pipeline_layout_callback: F,
+    ) -> R {
+        let pipeline_layout_id = core::id::PipelineLayoutId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe {
+                ctx.pipeline_layout_as_hal::<A, F, R>(
+                    pipeline_layout_id,
+                    hal_pipeline_layout_callback,
+                )
+            }
+        } else {
+            hal_pipeline_layout_callback(None)
+        }
+    }
 }
 
 impl RenderPipeline {
@@ -5575,6 +6492,35 @@ impl RenderPipeline {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal RenderPipeline using a callback. The hal render pipeline will be `None` if the
+    /// backend type argument does not match with this wgpu RenderPipeline
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal RenderPipeline must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<A: wgc::hal_api::HalApi, F: FnOnce(Option<&A::RenderPipeline>) -> R, R>(
+        &self,
+        hal_render_pipeline_callback: F,
+    ) -> R {
+        let render_pipeline_id = core::id::RenderPipelineId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe {
+                ctx.render_pipeline_as_hal::<A, F, R>(
+                    render_pipeline_id,
+                    hal_render_pipeline_callback,
+                )
+            }
+        } else {
+            hal_render_pipeline_callback(None)
+        }
+    }
 }
 
 impl ComputePipeline {
@@ -5585,6 +6531,39 @@ impl ComputePipeline {
     pub fn global_id(&self) -> Id<Self> {
         Id(self.id.global_id(), PhantomData)
     }
+
+    /// Returns the inner hal ComputePipeline using a callback. The hal compute pipeline will be `None` if the
+    /// backend type argument does not match with this wgpu ComputePipeline
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal ComputePipeline must not be manually destroyed
+    #[cfg(wgpu_core)]
+    pub unsafe fn as_hal<
+        A: wgc::hal_api::HalApi,
+        F: FnOnce(Option<&A::ComputePipeline>) -> R,
+        R,
+    >(
+        &self,
+        hal_compute_pipeline_callback: F,
+    ) -> R {
+        let compute_pipeline_id = core::id::ComputePipelineId::from(self.id);
+
+        if let Some(ctx) = self
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+        {
+            unsafe {
+                ctx.compute_pipeline_as_hal::<A, F, R>(
+                    compute_pipeline_id,
+                    hal_compute_pipeline_callback,
+                )
+            }
+        } else {
+            hal_compute_pipeline_callback(None)
+        }
+    }
 }
 
 impl RenderBundle {
@@ -0,0 +1,311 @@
+#[cfg(wgpu_core)]
+use std::sync::Arc;
+use std::{error, fmt, sync::mpsc};
+
+use crate::{
+    Buffer, BufferAddress, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder,
+    CommandEncoderDescriptor, Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout,
+    Maintain, MapMode, Queue, SubmissionIndex, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+/// A device and its queue, one side of a [`copy_between_devices`].
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceAndQueue<'a> {
+    /// The device.
+    pub device: &'a Device,
+    /// The queue of `device`.
+    pub queue: &'a Queue,
+}
+
+/// What [`copy_between_devices`] copies.
+#[derive(Clone, Copy, Debug)]
+pub enum CrossDeviceCopy<'a> {
+    /// Copies `size` bytes of `source` from `source_offset`, to `destination`
+    /// at `destination_offset`.
+    Buffer {
+        /// The buffer to copy from, of the source device.
+        source: &'a Buffer,
+        /// The offset in `source` to copy from, in bytes.
+        source_offset: BufferAddress,
+        /// The buffer to copy to, of the destination device.
+        destination: &'a Buffer,
+        /// The offset in `destination` to copy to, in bytes.
+        destination_offset: BufferAddress,
+        /// The number of bytes to copy.
+        size: BufferAddress,
+    },
+    /// Copies a region of `size` texels from `source` to `destination`,
+    /// which must have the same format.
+    Texture {
+        /// The texture region to copy from, of the source device.
+        source: ImageCopyTexture<'a>,
+        /// The texture region to copy to, of the destination device.
+        destination: ImageCopyTexture<'a>,
+        /// The size of the region, in texels.
+        size: Extent3d,
+    },
+}
+
+/// Why [`copy_between_devices`] didn't copy the data through the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CrossDeviceCopyError {
+    /// Mapping the buffer the data was read back to failed, as happens when
+    /// the source device is lost.
+    Readback(BufferAsyncError),
+    /// The data wasn't read back yet when polling the source device returned.
+    /// This happens on WebGPU, where polling doesn't wait for the GPU.
+    NotReady,
+}
+static_assertions::assert_impl_all!(CrossDeviceCopyError: Send, Sync);
+
+impl fmt::Display for CrossDeviceCopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Readback(ref error) => {
+                write!(
+                    f,
+                    "Reading the data back from the source device failed: {error}"
+                )
+            }
+            Self::NotReady => write!(
+                f,
+                "The data wasn't read back from the source device when polling it returned"
+            ),
+        }
+    }
+}
+
+impl error::Error for CrossDeviceCopyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Readback(ref error) => Some(error),
+            Self::NotReady => None,
+        }
+    }
+}
+
+impl CrossDeviceCopy<'_> {
+    /// The layout of the copied data in a staging buffer, and its size.
+    fn staging_layout(&self) -> (ImageDataLayout, BufferAddress) {
+        match *self {
+            Self::Buffer { size, .. } => (ImageDataLayout::default(), size),
+            Self::Texture {
+                ref source, size, ..
+            } => {
+                let format = source.texture.format();
+                let (block_width, block_height) = format.block_dimensions();
+                // Combined depth stencil formats can't be copied, which the
+                // copy to the staging buffer reports.
+                let block_size = format.block_copy_size(Some(source.aspect)).unwrap_or(0);
+                let bytes_per_row = super::align_to(
+                    size.width / block_width * block_size,
+                    COPY_BYTES_PER_ROW_ALIGNMENT,
+                );
+                let rows_per_image = size.height / block_height;
+                let layout = ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(rows_per_image),
+                };
+                let staging_size = bytes_per_row as BufferAddress
+                    * rows_per_image as BufferAddress
+                    * size.depth_or_array_layers as BufferAddress;
+                (layout, staging_size)
+            }
+        }
+    }
+
+    fn copy_to_staging(
+        &self,
+        encoder: &mut CommandEncoder,
+        staging: &Buffer,
+        layout: ImageDataLayout,
+    ) {
+        match *self {
+            Self::Buffer {
+                source,
+                source_offset,
+                size,
+                ..
+            } => encoder.copy_buffer_to_buffer(source, source_offset, staging, 0, size),
+            Self::Texture { source, size, .. } => encoder.copy_texture_to_buffer(
+                source,
+                ImageCopyBuffer {
+                    buffer: staging,
+                    layout,
+                },
+                size,
+            ),
+        }
+    }
+
+    fn copy_from_staging(
+        &self,
+        encoder: &mut CommandEncoder,
+        staging: &Buffer,
+        layout: ImageDataLayout,
+    ) {
+        match *self {
+            Self::Buffer {
+                destination,
+                destination_offset,
+                size,
+                ..
+            } => encoder.copy_buffer_to_buffer(staging, 0, destination, destination_offset, size),
+            Self::Texture {
+                destination, size, ..
+            } => encoder.copy_buffer_to_texture(
+                ImageCopyBuffer {
+                    buffer: staging,
+                    layout,
+                },
+                destination,
+                size,
+            ),
+        }
+    }
+
+    fn write_from_host(&self, queue: &Queue, data: &[u8], layout: ImageDataLayout) {
+        match *self {
+            Self::Buffer {
+                destination,
+                destination_offset,
+                ..
+            } => queue.write_buffer(destination, destination_offset, data),
+            Self::Texture {
+                destination, size, ..
+            } => queue.write_texture(destination, data, layout, size),
+        }
+    }
+}
+
+/// Copies a buffer range or texture region of `source`'s device to
+/// `destination`'s device.
+///
+/// Devices created by the same [`Instance`] on the same adapter with Vulkan
+/// or DX12 can share memory: the data goes through a buffer in memory shared
+/// by both devices, the destination queue waits on the source queue on the
+/// GPU, and this returns right away. Other devices, including devices on
+/// different adapters such as an integrated and a discrete GPU, don't share
+/// memory. For them, the data is read back to the host, blocking until
+/// `source`'s device is done writing it, and written with
+/// [`Queue::write_buffer`] or [`Queue::write_texture`].
+///
+/// This submits work to both queues, after what was submitted before. Returns
+/// the index of the submission to the destination queue the copy is done in,
+/// or why the data couldn't be read back to the host, in which case nothing
+/// is written to the destination. Invalid copies are reported to the device
+/// they are recorded on, like any other error.
+///
+/// [`Instance`]: crate::Instance
+pub fn copy_between_devices(
+    source: DeviceAndQueue<'_>,
+    destination: DeviceAndQueue<'_>,
+    copy: CrossDeviceCopy<'_>,
+) -> Result<SubmissionIndex, CrossDeviceCopyError> {
+    let (layout, staging_size) = copy.staging_layout();
+    if staging_size == 0 {
+        return Ok(destination.queue.submit(None));
+    }
+
+    #[cfg(wgpu_core)]
+    if let Some((source_staging, destination_staging)) =
+        create_shared_buffers(source.device, destination.device, staging_size)
+    {
+        let mut encoder = source
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+        copy.copy_to_staging(&mut encoder, &source_staging, layout);
+        let index = source.queue.submit(Some(encoder.finish()));
+
+        destination
+            .queue
+            .context
+            .as_any()
+            .downcast_ref::<crate::backend::ContextWgpuCore>()
+            .unwrap()
+            .queue_wait_for_submission(
+                destination.queue.data.as_ref().downcast_ref().unwrap(),
+                index.1.as_ref().downcast_ref().unwrap(),
+            );
+        let mut encoder = destination
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+        copy.copy_from_staging(&mut encoder, &destination_staging, layout);
+        return Ok(destination.queue.submit(Some(encoder.finish())));
+    }
+
+    let staging = source.device.create_buffer(&BufferDescriptor {
+        label: Some("(wgpu internal) copy_between_devices staging buffer"),
+        size: staging_size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = source
+        .device
+        .create_command_encoder(&CommandEncoderDescriptor::default());
+    copy.copy_to_staging(&mut encoder, &staging, layout);
+    let index = source.queue.submit(Some(encoder.finish()));
+
+    let slice = staging.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    let _ = source.device.poll(Maintain::wait_for(index));
+    match receiver.try_recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => return Err(CrossDeviceCopyError::Readback(error)),
+        Err(_) => return Err(CrossDeviceCopyError::NotReady),
+    }
+    copy.write_from_host(destination.queue, &slice.get_mapped_range(), layout);
+    Ok(destination.queue.submit(None))
+}
+
+/// Creates a buffer on `device` and one on `other` in the same memory, if
+/// they can share it.
+#[cfg(wgpu_core)]
+fn create_shared_buffers(
+    device: &Device,
+    other: &Device,
+    size: BufferAddress,
+) -> Option<(Buffer, Buffer)> {
+    if !Arc::ptr_eq(&device.context, &other.context) {
+        return None;
+    }
+    let context = device
+        .context
+        .as_any()
+        .downcast_ref::<crate::backend::ContextWgpuCore>()?;
+    let (device_data, other_data) = (
+        device.data.as_ref().downcast_ref().unwrap(),
+        other.data.as_ref().downcast_ref().unwrap(),
+    );
+    if !context.device_shares_memory_with(device_data, other_data) {
+        return None;
+    }
+
+    let desc = BufferDescriptor {
+        label: Some("(wgpu internal) copy_between_devices shared buffer"),
+        size,
+        usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    };
+    let ((id, data), (other_id, other_data)) =
+        context.device_create_shared_buffers(device_data, other_data, &desc);
+    let buffer = |id, data| Buffer {
+        context: Arc::clone(&device.context),
+        id,
+        data,
+        map_context: parking_lot::Mutex::new(crate::MapContext::new(size)),
+        size,
+        usage: desc.usage,
+    };
+    Some((
+        buffer(crate::context::ObjectId::from(id), Box::new(data)),
+        buffer(
+            crate::context::ObjectId::from(other_id),
+            Box::new(other_data),
+        ),
+    ))
+}
@@ -4,9 +4,12 @@
 //! they are unique to the `wgpu` library.
 
 mod belt;
+mod cross_device;
 mod device;
 mod encoder;
 mod init;
+mod readback;
+mod resource_table;
 
 use std::sync::Arc;
 use std::{
@@ -16,9 +19,14 @@ use std::{
 };
 
 pub use belt::StagingBelt;
+pub use cross_device::{
+    copy_between_devices, CrossDeviceCopy, CrossDeviceCopyError, DeviceAndQueue,
+};
 pub use device::{BufferInitDescriptor, DeviceExt, TextureDataOrder};
 pub use encoder::RenderEncoder;
 pub use init::*;
+pub use readback::DownloadTexture;
+pub use resource_table::{ResourceTable, ResourceTableDescriptor, TableResource};
 pub use wgt::{math::*, DispatchIndirectArgs, DrawIndexedIndirectArgs, DrawIndirectArgs};
 
 /// Treat the given byte slice as a SPIR-V module.
@@ -140,3 +148,33 @@ impl std::ops::Deref for DownloadBuffer {
         self.1.slice()
     }
 }
+
+/// A recommended key for storing [`PipelineCache`]s for the adapter
+/// associated with the given [`AdapterInfo`](wgt::AdapterInfo).
+/// This key will define a class of adapters for which the same cache
+/// might be valid.
+///
+/// If this returns `None`, the adapter doesn't support [`PipelineCache`].
+/// This may be because the API doesn't support application managed caches
+/// (such as browser WebGPU), or that `wgpu` hasn't implemented it for
+/// that API yet.
+///
+/// The key includes the backend and the adapter's vendor and device ids,
+/// and is a valid file name. The data of a cache also depends on the
+/// driver's version, which [`Device::create_pipeline_cache`] checks, so
+/// storing it under this key is enough for the cache to survive driver
+/// updates, at the cost of being rejected once after each.
+///
+/// [`PipelineCache`]: super::PipelineCache
+/// [`Device::create_pipeline_cache`]: super::Device::create_pipeline_cache
+pub fn pipeline_cache_key(adapter_info: &wgt::AdapterInfo) -> Option<String> {
+    match adapter_info.backend {
+        wgt::Backend::Vulkan | wgt::Backend::Metal | wgt::Backend::Dx12 => Some(format!(
+            "wgpu_pipeline_cache_{}_{:08x}_{:08x}",
+            adapter_info.backend.to_str(),
+            adapter_info.vendor,
+            adapter_info.device,
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use crate::{
+    BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Extent3d,
+    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, MapMode, Queue, TextureAspect,
+    TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+/// The texels of a texture region, read back to the CPU by
+/// [`DownloadTexture::read_texture`].
+///
+/// The rows of texel blocks are tightly packed, without the padding copies to
+/// buffers need.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadTexture {
+    /// The format of the texture.
+    pub format: TextureFormat,
+    /// The aspect of the texture that was read.
+    pub aspect: TextureAspect,
+    /// The size of the region, in texels.
+    pub size: Extent3d,
+    /// The number of bytes in a row of texel blocks.
+    pub bytes_per_row: u32,
+    /// The number of rows of texel blocks in an image, which is the height of
+    /// the region divided by the block height.
+    pub rows_per_image: u32,
+    /// The rows of all images, one image after the other.
+    pub data: Vec<u8>,
+}
+
+impl DownloadTexture {
+    /// Asynchronously read a region of a texture.
+    ///
+    /// This copies `size` texels of `texture` to a buffer with rows padded to
+    /// [`COPY_BYTES_PER_ROW_ALIGNMENT`], and calls `callback` with the rows
+    /// without padding once the buffer is mapped, which takes polling the
+    /// device.
+    ///
+    /// # Panics
+    ///
+    /// - If the texture has a depth stencil format and `texture.aspect` is
+    ///   [`TextureAspect::All`]: each aspect is read separately.
+    pub fn read_texture(
+        device: &Device,
+        queue: &Queue,
+        texture: ImageCopyTexture<'_>,
+        size: Extent3d,
+        callback: impl FnOnce(Result<Self, BufferAsyncError>) + Send + 'static,
+    ) {
+        let format = texture.texture.format();
+        let block_size = format
+            .block_copy_size(Some(texture.aspect))
+            .unwrap_or_else(|| panic!("{format:?} textures must be read one aspect at a time"));
+        let (block_width, block_height) = format.block_dimensions();
+        let bytes_per_row = size.width.div_ceil(block_width) * block_size;
+        let rows_per_image = size.height.div_ceil(block_height);
+        let padded_bytes_per_row = super::align_to(bytes_per_row, COPY_BYTES_PER_ROW_ALIGNMENT);
+        let row_count = rows_per_image as usize * size.depth_or_array_layers as usize;
+
+        let mut download = Self {
+            format,
+            aspect: texture.aspect,
+            size,
+            bytes_per_row,
+            rows_per_image,
+            data: Vec::new(),
+        };
+        if bytes_per_row == 0 || row_count == 0 {
+            callback(Ok(download));
+            return;
+        }
+
+        #[allow(clippy::arc_with_non_send_sync)] // False positive on emscripten
+        let buffer = Arc::new(device.create_buffer(&BufferDescriptor {
+            label: Some("(wgpu internal) DownloadTexture buffer"),
+            size: padded_bytes_per_row as u64 * row_count as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }));
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            texture,
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(rows_per_image),
+                },
+            },
+            size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        buffer
+            .clone()
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                if let Err(e) = result {
+                    callback(Err(e));
+                    return;
+                }
+
+                {
+                    let mapped = buffer.slice(..).get_mapped_range();
+                    download
+                        .data
+                        .reserve_exact(bytes_per_row as usize * row_count);
+                    for row in mapped.chunks_exact(padded_bytes_per_row as usize) {
+                        download
+                            .data
+                            .extend_from_slice(&row[..bytes_per_row as usize]);
+                    }
+                }
+                buffer.unmap();
+                callback(Ok(download));
+            });
+    }
+
+    /// Returns the rows of texel blocks of all images, without padding.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[u8]> + '_ {
+        self.data.chunks_exact(self.bytes_per_row.max(1) as usize)
+    }
+
+    /// Returns the rows of the image at `index`, which is an array layer of 2D
+    /// textures, or a depth slice of 3D textures.
+    pub fn image(&self, index: u32) -> &[u8] {
+        let image_size = self.bytes_per_row as usize * self.rows_per_image as usize;
+        let start = index as usize * image_size;
+        &self.data[start..start + image_size]
+    }
+}
@@ -0,0 +1,205 @@
+use std::num::NonZeroU32;
+
+use crate::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBinding, Device, Features,
+    Label, Sampler, ShaderStages, TextureView,
+};
+
+/// A resource a [`ResourceTable`] can hold.
+///
+/// Implemented for [`TextureView`]s, [`Sampler`]s and [`Buffer`]s, which are
+/// bound whole.
+pub trait TableResource {
+    /// Calls `f` with the binding array of `resources`.
+    fn with_binding_array<R>(resources: &[&Self], f: impl FnOnce(BindingResource<'_>) -> R) -> R;
+}
+
+impl TableResource for TextureView {
+    fn with_binding_array<R>(resources: &[&Self], f: impl FnOnce(BindingResource<'_>) -> R) -> R {
+        f(BindingResource::TextureViewArray(resources))
+    }
+}
+
+impl TableResource for Sampler {
+    fn with_binding_array<R>(resources: &[&Self], f: impl FnOnce(BindingResource<'_>) -> R) -> R {
+        f(BindingResource::SamplerArray(resources))
+    }
+}
+
+impl TableResource for Buffer {
+    fn with_binding_array<R>(resources: &[&Self], f: impl FnOnce(BindingResource<'_>) -> R) -> R {
+        let bindings: Vec<BufferBinding<'_>> = resources
+            .iter()
+            .map(|buffer| buffer.as_entire_buffer_binding())
+            .collect();
+        f(BindingResource::BufferArray(&bindings))
+    }
+}
+
+/// Describes a [`ResourceTable`].
+#[derive(Clone, Debug)]
+pub struct ResourceTableDescriptor<'a> {
+    /// Debug label of the table's bind group layout and bind groups.
+    pub label: Label<'a>,
+    /// The shader stages the binding array is visible to.
+    pub visibility: ShaderStages,
+    /// The type of the resources of the binding array, which must match the
+    /// type of resource the table holds.
+    pub ty: BindingType,
+    /// The number of elements of the binding array, which is the maximum
+    /// number of resources in the table.
+    pub capacity: NonZeroU32,
+}
+
+/// A table of resources, bound as a `binding_array` for bindless rendering.
+///
+/// The table owns a bind group layout with a binding array of
+/// [`ResourceTableDescriptor::capacity`] elements at binding 0, and the bind
+/// group of its resources. Inserting a resource gives it a stable index in the
+/// array, which shaders use to access it, until it's removed. The indices of
+/// removed resources are reused by the next inserted ones.
+///
+/// Bind groups can't be changed once created, so the first call to
+/// [`ResourceTable::bind_group`] after the table changed creates a new one.
+/// Bind groups created before keep the resources they were created with
+/// alive, so work using them can still be in flight.
+///
+/// The unused elements of the array are bound to a placeholder resource. With
+/// [`Features::PARTIALLY_BOUND_BINDING_ARRAY`], the array is only bound up to
+/// the highest index in use.
+///
+/// Binding arrays require [`Features::TEXTURE_BINDING_ARRAY`] or
+/// [`Features::BUFFER_BINDING_ARRAY`], depending on the resource type.
+#[derive(Debug)]
+pub struct ResourceTable<T: TableResource> {
+    label: Option<String>,
+    layout: BindGroupLayout,
+    capacity: u32,
+    partially_bound: bool,
+    placeholder: T,
+    slots: Vec<Option<T>>,
+    /// Indices of `slots` whose resource was removed.
+    free_slots: Vec<u32>,
+    /// The bind group of the current resources, if it was created since the
+    /// table last changed.
+    bind_group: Option<BindGroup>,
+}
+
+impl<T: TableResource> ResourceTable<T> {
+    /// Creates an empty table, whose unused elements are bound to
+    /// `placeholder`.
+    pub fn new(device: &Device, desc: &ResourceTableDescriptor<'_>, placeholder: T) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: desc.label,
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: desc.visibility,
+                ty: desc.ty,
+                count: Some(desc.capacity),
+            }],
+        });
+        Self {
+            label: desc.label.map(str::to_owned),
+            layout,
+            capacity: desc.capacity.get(),
+            partially_bound: device
+                .features()
+                .contains(Features::PARTIALLY_BOUND_BINDING_ARRAY),
+            placeholder,
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            bind_group: None,
+        }
+    }
+
+    /// The bind group layout of the table, to create pipeline layouts with.
+    pub fn layout(&self) -> &BindGroupLayout {
+        &self.layout
+    }
+
+    /// The maximum number of resources in the table.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// The number of resources in the table.
+    pub fn len(&self) -> u32 {
+        (self.slots.len() - self.free_slots.len()) as u32
+    }
+
+    /// Returns whether the table has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `resource` to the table, and returns its index in the binding
+    /// array.
+    ///
+    /// Returns `resource` back if the table is full.
+    pub fn insert(&mut self, resource: T) -> Result<u32, T> {
+        let index = match self.free_slots.pop() {
+            Some(index) => index,
+            None if self.slots.len() < self.capacity as usize => {
+                self.slots.push(None);
+                self.slots.len() as u32 - 1
+            }
+            None => return Err(resource),
+        };
+        self.slots[index as usize] = Some(resource);
+        self.bind_group = None;
+        Ok(index)
+    }
+
+    /// Returns the resource at `index`, if any.
+    pub fn get(&self, index: u32) -> Option<&T> {
+        self.slots.get(index as usize)?.as_ref()
+    }
+
+    /// Removes the resource at `index` from the table, and returns it.
+    ///
+    /// The index is free to be reused by the next inserted resource.
+    pub fn remove(&mut self, index: u32) -> Option<T> {
+        let resource = self.slots.get_mut(index as usize)?.take()?;
+        self.free_slots.push(index);
+        self.bind_group = None;
+        Some(resource)
+    }
+
+    /// Returns the bind group of the resources of the table, creating it on
+    /// `device` if the table changed since it was last created.
+    pub fn bind_group(&mut self, device: &Device) -> &BindGroup {
+        let Self {
+            ref label,
+            ref layout,
+            capacity,
+            partially_bound,
+            ref placeholder,
+            ref slots,
+            ..
+        } = *self;
+        self.bind_group.get_or_insert_with(|| {
+            let count = if partially_bound {
+                slots.len().max(1)
+            } else {
+                capacity as usize
+            };
+            let resources: Vec<&T> = (0..count)
+                .map(|index| match slots.get(index) {
+                    Some(Some(resource)) => resource,
+                    _ => placeholder,
+                })
+                .collect();
+            T::with_binding_array(&resources, |resource| {
+                device.create_bind_group(&BindGroupDescriptor {
+                    label: label.as_deref(),
+                    layout,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource,
+                    }],
+                })
+            })
+        })
+    }
+}